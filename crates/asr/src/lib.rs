@@ -0,0 +1,648 @@
+//! Lokale Spracherkennung mit whisper.cpp.
+//!
+//! Das Crate ruft das whisper.cpp-Binary (`whisper-cli`) auf und liest dessen
+//! JSON-Ausgabe (`-oj`). Eingaben, die kein 16-kHz-Mono-WAV sind, wandelt
+//! `ffmpeg` vorher um. Modelle reicht der Aufrufer als [`ModelRef`] herein (in
+//! HausKI aus `configs/models.yml`); [`select_model`] wählt per ID oder nimmt
+//! den ersten `whisper*`-Eintrag.
+//!
+//! Gerät: [`Device::Cpu`] schaltet die GPU ab (`-ng`), [`Device::Gpu`] und
+//! [`Device::Auto`] überlassen whisper.cpp die Wahl. Wie `auto` aufgelöst wird,
+//! entscheidet der Aufrufer – der Core etwa nach GPU-Temperatur, Energiesparmodus
+//! und freiem VRAM.
+//!
+//! Sprachaktivität (VAD) wird zweistufig erkannt: Eine Energieschwelle
+//! ([`EnergyVad`]) überspringt Aufnahmen ohne jedes hörbare Signal, bevor
+//! whisper.cpp startet – auf Stille halluziniert Whisper gern Text. Ist
+//! zusätzlich ein VAD-Modell gesetzt, schneidet whisper.cpp stille Passagen
+//! selbst heraus (`--vad`).
+//!
+//! Für Live-Audio zerlegt [`Segmenter`] einen 16-kHz-Strom anhand derselben
+//! Energieschwelle in Äußerungen (Zwischenstände und Endfassungen), die
+//! [`AsrConfig::transcribe_samples`] einzeln transkribiert.
+//!
+//! Konfiguration ([`AsrConfig::from_env`]):
+//!   HAUSKI_WHISPER_BIN        (Default `whisper-cli`)
+//!   HAUSKI_FFMPEG_BIN         (Default `ffmpeg`)
+//!   HAUSKI_ASR_DEVICE         (`auto` | `gpu` | `cpu`, Default `auto`)
+//!   HAUSKI_ASR_THREADS        (ohne Default; whisper.cpp entscheidet)
+//!   HAUSKI_ASR_SILENCE_DBFS   (Default -50; `off` = keine Energieschwelle)
+//!   HAUSKI_ASR_VAD_MODEL      (ohne Default; ggml-Silero-Modell für `--vad`)
+//!   HAUSKI_ASR_VAD_THRESHOLD  (Default 0.5)
+
+mod stream;
+mod vad;
+
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+pub use stream::{
+    Segmenter, SegmenterConfig, Utterance, UtteranceKind, DEFAULT_CHUNK_MS, DEFAULT_MAX_MS,
+    DEFAULT_PAUSE_MS,
+};
+pub use vad::EnergyVad;
+
+/// Sample rate whisper.cpp expects.
+pub const SAMPLE_RATE: u32 = 16_000;
+pub const DEFAULT_SILENCE_DBFS: f32 = -50.0;
+pub const DEFAULT_VAD_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Error)]
+pub enum AsrError {
+    #[error("model '{0}' is not configured")]
+    UnknownModel(String),
+    #[error("no whisper model configured")]
+    NoModel,
+    #[error("model file {} is missing", .0.display())]
+    ModelMissing(PathBuf),
+    #[error("input {} not found", .0.display())]
+    InputMissing(PathBuf),
+    #[error("{bin} could not be started (set {env}): {source}")]
+    Spawn {
+        bin: String,
+        env: &'static str,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{bin} failed ({status}): {stderr}")]
+    Failed {
+        bin: String,
+        status: String,
+        stderr: String,
+    },
+    #[error("invalid whisper output: {0}")]
+    InvalidOutput(String),
+    #[error("invalid audio: {0}")]
+    Audio(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Where whisper.cpp runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Device {
+    /// Caller decides; whisper.cpp uses the GPU if it was built with one.
+    #[default]
+    Auto,
+    Gpu,
+    Cpu,
+}
+
+impl Device {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Gpu => "gpu",
+            Self::Cpu => "cpu",
+        }
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Device {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "gpu" | "cuda" => Ok(Self::Gpu),
+            "cpu" => Ok(Self::Cpu),
+            other => Err(format!("unknown device '{other}' (auto, gpu, cpu)")),
+        }
+    }
+}
+
+/// A ggml model the caller knows about.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ModelRef {
+    pub id: String,
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+    /// VRAM the model needs on the GPU, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vram_min_gb: Option<u64>,
+}
+
+impl ModelRef {
+    /// Entry with `~` in `path` expanded.
+    pub fn new(id: impl Into<String>, path: &str, vram_min_gb: Option<u64>) -> Self {
+        Self {
+            id: id.into(),
+            path: PathBuf::from(shellexpand::tilde(path).as_ref()),
+            vram_min_gb,
+        }
+    }
+
+    /// Whisper models are recognised by their id (`whisper-small`, ...).
+    pub fn is_whisper(&self) -> bool {
+        self.id.starts_with("whisper")
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.path.is_file()
+    }
+}
+
+/// The model `id`, or the first whisper model without one.
+pub fn select_model(models: &[ModelRef], id: Option<&str>) -> Result<ModelRef, AsrError> {
+    let entry = match id {
+        Some(id) => models.iter().find(|model| model.id == id),
+        None => models.iter().find(|model| model.is_whisper()),
+    };
+    entry.cloned().ok_or_else(|| match id {
+        Some(id) => AsrError::UnknownModel(id.to_string()),
+        None => AsrError::NoModel,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Transcript {
+    /// Detected (or requested) language.
+    pub language: Option<String>,
+    pub segments: Vec<Segment>,
+}
+
+impl Transcript {
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Per-call settings on top of [`AsrConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    /// ISO code like `de`; `None` or `auto` detects the language.
+    pub language: Option<String>,
+    pub device: Device,
+    /// Overrides [`AsrConfig::threads`].
+    pub threads: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsrConfig {
+    pub whisper_bin: String,
+    pub ffmpeg_bin: String,
+    /// Device for requests that do not name one.
+    pub device: Device,
+    pub threads: Option<usize>,
+    /// Inputs whose every frame stays below this level are not transcribed;
+    /// `None` disables the check.
+    pub silence_dbfs: Option<f32>,
+    /// Silero model for whisper.cpp's own VAD (`--vad`).
+    pub vad_model: Option<PathBuf>,
+    pub vad_threshold: f32,
+}
+
+impl Default for AsrConfig {
+    fn default() -> Self {
+        Self {
+            whisper_bin: "whisper-cli".into(),
+            ffmpeg_bin: "ffmpeg".into(),
+            device: Device::Auto,
+            threads: None,
+            silence_dbfs: Some(DEFAULT_SILENCE_DBFS),
+            vad_model: None,
+            vad_threshold: DEFAULT_VAD_THRESHOLD,
+        }
+    }
+}
+
+impl AsrConfig {
+    /// Reads the `HAUSKI_WHISPER_*`/`HAUSKI_ASR_*` variables; invalid values
+    /// keep the default and are logged.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            whisper_bin: env_string("HAUSKI_WHISPER_BIN").unwrap_or(defaults.whisper_bin),
+            ffmpeg_bin: env_string("HAUSKI_FFMPEG_BIN").unwrap_or(defaults.ffmpeg_bin),
+            device: env_parse("HAUSKI_ASR_DEVICE").unwrap_or(defaults.device),
+            threads: env_parse("HAUSKI_ASR_THREADS").filter(|threads| *threads > 0),
+            silence_dbfs: match env_string("HAUSKI_ASR_SILENCE_DBFS").as_deref() {
+                Some("off") => None,
+                Some(_) => env_parse("HAUSKI_ASR_SILENCE_DBFS").or(defaults.silence_dbfs),
+                None => defaults.silence_dbfs,
+            },
+            vad_model: env_string("HAUSKI_ASR_VAD_MODEL")
+                .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref())),
+            vad_threshold: env_parse("HAUSKI_ASR_VAD_THRESHOLD")
+                .filter(|threshold| (0.0..=1.0).contains(threshold))
+                .unwrap_or(defaults.vad_threshold),
+        }
+    }
+
+    /// Transcribes the audio file `input` with `model`.
+    ///
+    /// Blocks until whisper.cpp is done; async callers run it on a blocking
+    /// thread.
+    pub fn transcribe(
+        &self,
+        input: &Path,
+        model: &ModelRef,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript, AsrError> {
+        if !input.is_file() {
+            return Err(AsrError::InputMissing(input.to_path_buf()));
+        }
+        if !model.is_available() {
+            return Err(AsrError::ModelMissing(model.path.clone()));
+        }
+        let work = tempfile::Builder::new().prefix("hauski-asr-").tempdir()?;
+        let wav = self.prepare_wav(input, work.path())?;
+        let language = requested_language(options);
+
+        if let Some(threshold) = self.silence_dbfs {
+            if !EnergyVad::new(threshold).wav_contains_speech(&wav)? {
+                tracing::debug!(input = %input.display(), threshold, "input is silent, whisper skipped");
+                return Ok(Transcript {
+                    language,
+                    segments: Vec::new(),
+                });
+            }
+        }
+        self.run_whisper(&wav, work.path(), model, options, language)
+    }
+
+    /// Transcribes 16 kHz mono samples, e.g. an utterance of a stream.
+    /// Timestamps are relative to the first sample; there is no energy gate,
+    /// the caller decides what to send.
+    pub fn transcribe_samples(
+        &self,
+        samples: &[i16],
+        model: &ModelRef,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript, AsrError> {
+        if !model.is_available() {
+            return Err(AsrError::ModelMissing(model.path.clone()));
+        }
+        let work = tempfile::Builder::new().prefix("hauski-asr-").tempdir()?;
+        let wav = work.path().join("input.wav");
+        let audio = |err: hound::Error| AsrError::Audio(err.to_string());
+        let mut writer = hound::WavWriter::create(&wav, wav_spec()).map_err(audio)?;
+        for &sample in samples {
+            writer.write_sample(sample).map_err(audio)?;
+        }
+        writer.finalize().map_err(audio)?;
+        self.run_whisper(
+            &wav,
+            work.path(),
+            model,
+            options,
+            requested_language(options),
+        )
+    }
+
+    fn run_whisper(
+        &self,
+        wav: &Path,
+        work: &Path,
+        model: &ModelRef,
+        options: &TranscribeOptions,
+        language: Option<String>,
+    ) -> Result<Transcript, AsrError> {
+        let prefix = work.join("transcript");
+        let mut command = Command::new(&self.whisper_bin);
+        command
+            .arg("-m")
+            .arg(&model.path)
+            .arg("-f")
+            .arg(wav)
+            .args(["-l", language.as_deref().unwrap_or("auto")])
+            .args(["-oj", "-np", "-of"])
+            .arg(&prefix);
+        if let Some(threads) = options.threads.or(self.threads) {
+            command.args(["-t", &threads.to_string()]);
+        }
+        if options.device == Device::Cpu {
+            command.arg("-ng");
+        }
+        if let Some(vad_model) = &self.vad_model {
+            command
+                .args(["--vad", "-vm"])
+                .arg(vad_model)
+                .args(["-vt", &self.vad_threshold.to_string()]);
+        }
+        run(&mut command, &self.whisper_bin, "HAUSKI_WHISPER_BIN")?;
+
+        let raw = fs::read_to_string(prefix.with_extension("json"))
+            .map_err(|err| AsrError::InvalidOutput(format!("no JSON output: {err}")))?;
+        let mut transcript = parse_whisper_json(&raw)?;
+        if transcript.language.is_none() {
+            transcript.language = language;
+        }
+        Ok(transcript)
+    }
+
+    /// Returns `input` if it already is 16 kHz mono PCM WAV, else a converted
+    /// copy in `work`.
+    fn prepare_wav(&self, input: &Path, work: &Path) -> Result<PathBuf, AsrError> {
+        if let Ok(reader) = hound::WavReader::open(input) {
+            if reader.spec() == wav_spec() {
+                return Ok(input.to_path_buf());
+            }
+        }
+
+        let wav = work.join("input.wav");
+        let mut command = Command::new(&self.ffmpeg_bin);
+        command
+            .args(["-nostdin", "-loglevel", "error", "-y", "-i"])
+            .arg(input)
+            .args(["-ar", &SAMPLE_RATE.to_string(), "-ac", "1"])
+            .args(["-c:a", "pcm_s16le"])
+            .arg(&wav);
+        run(&mut command, &self.ffmpeg_bin, "HAUSKI_FFMPEG_BIN")?;
+        Ok(wav)
+    }
+}
+
+/// 16 kHz mono 16-bit PCM, the only input whisper.cpp reads.
+fn wav_spec() -> hound::WavSpec {
+    hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+fn requested_language(options: &TranscribeOptions) -> Option<String> {
+    options
+        .language
+        .clone()
+        .filter(|language| !language.is_empty() && language != "auto")
+}
+
+fn run(command: &mut Command, bin: &str, env: &'static str) -> Result<(), AsrError> {
+    let output = command.output().map_err(|source| AsrError::Spawn {
+        bin: bin.to_string(),
+        env,
+        source,
+    })?;
+    if !output.status.success() {
+        return Err(AsrError::Failed {
+            bin: bin.to_string(),
+            status: output.status.to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn env_string(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: fmt::Display,
+{
+    let value = env_string(name)?;
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            tracing::warn!(%name, %value, error = %err, "ignoring invalid ASR setting");
+            None
+        }
+    }
+}
+
+/// Reads the `-oj` output of whisper.cpp.
+pub fn parse_whisper_json(raw: &str) -> Result<Transcript, AsrError> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|err| AsrError::InvalidOutput(err.to_string()))?;
+    let segments = value["transcription"]
+        .as_array()
+        .ok_or_else(|| AsrError::InvalidOutput("missing `transcription`".into()))?
+        .iter()
+        .map(|segment| Segment {
+            start_ms: segment["offsets"]["from"].as_u64().unwrap_or_default(),
+            end_ms: segment["offsets"]["to"].as_u64().unwrap_or_default(),
+            text: segment["text"]
+                .as_str()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        })
+        .filter(|segment| !segment.text.is_empty())
+        .collect();
+    let language = value["result"]["language"]
+        .as_str()
+        .filter(|language| !language.is_empty() && *language != "auto")
+        .map(str::to_string);
+    Ok(Transcript { language, segments })
+}
+
+/// Word error rate of `hypothesis` against `reference` (0.0 = identical),
+/// on lowercase words without punctuation.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> f64 {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let reference = words(reference);
+    let hypothesis = words(hypothesis);
+    if reference.is_empty() {
+        return if hypothesis.is_empty() { 0.0 } else { 1.0 };
+    }
+
+    // Levenshtein distance over words (substitutions, insertions, deletions).
+    let mut previous: Vec<usize> = (0..=hypothesis.len()).collect();
+    for (i, expected) in reference.iter().enumerate() {
+        let mut current = vec![i + 1; hypothesis.len() + 1];
+        for (j, actual) in hypothesis.iter().enumerate() {
+            let substitution = previous[j] + usize::from(expected != actual);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[hypothesis.len()] as f64 / reference.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHISPER_OUTPUT: &str = r#"{
+        "result": {"language": "de"},
+        "transcription": [
+            {"timestamps": {"from": "00:00:00,000", "to": "00:00:02,500"},
+             "offsets": {"from": 0, "to": 2500}, "text": " Hallo Welt."},
+            {"offsets": {"from": 2500, "to": 3723010}, "text": " Wie geht's?"},
+            {"offsets": {"from": 3723010, "to": 3723020}, "text": " "}
+        ]
+    }"#;
+
+    #[test]
+    fn whisper_json_is_parsed_into_segments() {
+        let transcript = parse_whisper_json(WHISPER_OUTPUT).unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        assert_eq!(
+            transcript.segments[1],
+            Segment {
+                start_ms: 2500,
+                end_ms: 3723010,
+                text: "Wie geht's?".into(),
+            }
+        );
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.text(), "Hallo Welt. Wie geht's?");
+        assert!(parse_whisper_json("{}").is_err());
+    }
+
+    #[test]
+    fn word_error_rate_counts_edits_per_reference_word() {
+        assert_eq!(word_error_rate("Hallo Welt", "hallo, welt!"), 0.0);
+        // One substitution and one deletion over four words.
+        assert_eq!(word_error_rate("das ist ein Test", "das war ein"), 0.5);
+        assert_eq!(word_error_rate("", ""), 0.0);
+        assert_eq!(word_error_rate("", "extra"), 1.0);
+    }
+
+    #[test]
+    fn default_model_is_first_whisper_entry() {
+        let models = [
+            ModelRef::new("llama", "/m/llama.gguf", Some(6)),
+            ModelRef::new("whisper-small", "/m/small.bin", None),
+        ];
+        assert_eq!(
+            select_model(&models, None).unwrap().path,
+            PathBuf::from("/m/small.bin")
+        );
+        assert_eq!(select_model(&models, Some("llama")).unwrap().id, "llama");
+        assert!(matches!(
+            select_model(&models, Some("nope")),
+            Err(AsrError::UnknownModel(_))
+        ));
+        assert!(matches!(select_model(&[], None), Err(AsrError::NoModel)));
+    }
+
+    #[test]
+    fn devices_parse_case_insensitively() {
+        assert_eq!("CPU".parse::<Device>(), Ok(Device::Cpu));
+        assert_eq!("cuda".parse::<Device>(), Ok(Device::Gpu));
+        assert!("npu".parse::<Device>().is_err());
+    }
+
+    #[cfg(unix)]
+    mod whisper {
+        use super::*;
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::TempDir;
+
+        /// Writes a `whisper-cli` stand-in that records its arguments.
+        fn fake_whisper(dir: &Path) -> PathBuf {
+            let bin = dir.join("whisper-cli");
+            let script = format!(
+                "#!/bin/sh\necho \"$@\" > {args}\nwhile [ $# -gt 0 ]; do\n  [ \"$1\" = -of ] && out=\"$2\"\n  shift\ndone\nprintf '%s' '{json}' > \"$out.json\"\n",
+                args = dir.join("args").display(),
+                json = r#"{"result":{"language":"de"},"transcription":[{"offsets":{"from":0,"to":900},"text":" Hallo"}]}"#,
+            );
+            fs::write(&bin, script).unwrap();
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+            bin
+        }
+
+        fn wav(dir: &Path, amplitude: i16) -> PathBuf {
+            let path = dir.join(format!("input-{amplitude}.wav"));
+            let mut writer = hound::WavWriter::create(&path, wav_spec()).unwrap();
+            for i in 0..SAMPLE_RATE / 2 {
+                let sign = if i % 16 < 8 { 1 } else { -1 };
+                writer.write_sample(sign * amplitude).unwrap();
+            }
+            writer.finalize().unwrap();
+            path
+        }
+
+        #[test]
+        fn whisper_runs_with_device_and_vad_flags() {
+            let dir = TempDir::new().unwrap();
+            let model_path = dir.path().join("ggml-small.bin");
+            fs::write(&model_path, b"ggml").unwrap();
+            let model = ModelRef::new("whisper-small", model_path.to_str().unwrap(), None);
+            let config = AsrConfig {
+                whisper_bin: fake_whisper(dir.path()).display().to_string(),
+                vad_model: Some(dir.path().join("silero.bin")),
+                ..AsrConfig::default()
+            };
+            let options = TranscribeOptions {
+                language: Some("auto".into()),
+                device: Device::Cpu,
+                threads: Some(2),
+            };
+
+            let transcript = config
+                .transcribe(&wav(dir.path(), 8_000), &model, &options)
+                .unwrap();
+            assert_eq!(transcript.text(), "Hallo");
+            assert_eq!(transcript.language.as_deref(), Some("de"));
+            let args = fs::read_to_string(dir.path().join("args")).unwrap();
+            for expected in ["-l auto", "-t 2", "-ng", "--vad -vm", "-vt 0.5"] {
+                assert!(args.contains(expected), "missing {expected:?} in {args}");
+            }
+
+            // Silent input never reaches whisper.cpp.
+            fs::remove_file(dir.path().join("args")).unwrap();
+            let options = TranscribeOptions {
+                language: Some("de".into()),
+                ..TranscribeOptions::default()
+            };
+            let transcript = config
+                .transcribe(&wav(dir.path(), 0), &model, &options)
+                .unwrap();
+            assert!(transcript.segments.is_empty());
+            assert_eq!(transcript.language.as_deref(), Some("de"));
+            assert!(!dir.path().join("args").exists());
+        }
+
+        #[test]
+        fn missing_model_and_input_are_reported() {
+            let dir = TempDir::new().unwrap();
+            let model = ModelRef::new("whisper-small", "/nonexistent/ggml.bin", None);
+            let config = AsrConfig::default();
+            let options = TranscribeOptions::default();
+            assert!(matches!(
+                config.transcribe(&dir.path().join("missing.wav"), &model, &options),
+                Err(AsrError::InputMissing(_))
+            ));
+            assert!(matches!(
+                config.transcribe(&wav(dir.path(), 0), &model, &options),
+                Err(AsrError::ModelMissing(_))
+            ));
+        }
+    }
+}
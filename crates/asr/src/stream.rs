@@ -0,0 +1,251 @@
+//! Cuts a live 16 kHz mono stream into utterances for whisper.cpp.
+//!
+//! Leading silence is dropped except for a short pre-roll. While speech is
+//! running, the utterance so far is handed out as a partial every
+//! `chunk_ms` of new audio; after a pause of `pause_ms`, or once it reaches
+//! `max_ms`, it is handed out as final and the next utterance starts.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{EnergyVad, Segment, DEFAULT_SILENCE_DBFS, SAMPLE_RATE};
+
+pub const DEFAULT_CHUNK_MS: u64 = 2_000;
+pub const DEFAULT_PAUSE_MS: u64 = 700;
+pub const DEFAULT_MAX_MS: u64 = 30_000;
+/// Audio kept before the first active frame so onsets are not clipped.
+const PREROLL_MS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmenterConfig {
+    pub chunk_ms: u64,
+    pub pause_ms: u64,
+    pub max_ms: u64,
+    /// Energy threshold for speech; `None` treats every frame as speech.
+    pub silence_dbfs: Option<f32>,
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        Self {
+            chunk_ms: DEFAULT_CHUNK_MS,
+            pause_ms: DEFAULT_PAUSE_MS,
+            max_ms: DEFAULT_MAX_MS,
+            silence_dbfs: Some(DEFAULT_SILENCE_DBFS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UtteranceKind {
+    Partial,
+    Final,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    pub kind: UtteranceKind,
+    /// Running number of the utterance; partials share it with their final.
+    pub index: u64,
+    /// Position of the first sample in the stream.
+    pub offset_ms: u64,
+    pub samples: Vec<i16>,
+}
+
+impl Utterance {
+    /// Shifts segment timestamps from the utterance to the stream.
+    pub fn place(&self, segments: Vec<Segment>) -> Vec<Segment> {
+        segments
+            .into_iter()
+            .map(|segment| Segment {
+                start_ms: segment.start_ms + self.offset_ms,
+                end_ms: segment.end_ms + self.offset_ms,
+                text: segment.text,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Segmenter {
+    vad: EnergyVad,
+    chunk_len: usize,
+    pause_len: usize,
+    max_len: usize,
+    preroll_len: usize,
+    /// Samples that do not fill a VAD frame yet.
+    pending: Vec<i16>,
+    /// The current utterance, or the pre-roll while waiting for speech.
+    buffer: Vec<i16>,
+    /// Stream position of `buffer[0]` in samples.
+    start: u64,
+    speech: bool,
+    silence_run: usize,
+    since_partial: usize,
+    index: u64,
+}
+
+impl Segmenter {
+    pub fn new(config: &SegmenterConfig) -> Self {
+        Self {
+            vad: EnergyVad::new(config.silence_dbfs.unwrap_or(f32::NEG_INFINITY)),
+            chunk_len: samples_for(config.chunk_ms),
+            pause_len: samples_for(config.pause_ms),
+            max_len: samples_for(config.max_ms),
+            preroll_len: samples_for(PREROLL_MS),
+            pending: Vec::new(),
+            buffer: Vec::new(),
+            start: 0,
+            speech: false,
+            silence_run: 0,
+            since_partial: 0,
+            index: 0,
+        }
+    }
+
+    /// Feeds samples and returns the utterances that became due, in order.
+    pub fn push(&mut self, samples: &[i16]) -> Vec<Utterance> {
+        self.pending.extend_from_slice(samples);
+        let frame_len = self.vad.frame_len();
+        let mut due = Vec::new();
+        let mut consumed = 0;
+        while self.pending.len() - consumed >= frame_len {
+            let frame = &self.pending[consumed..consumed + frame_len];
+            let active = self.vad.is_active(frame);
+            self.buffer.extend_from_slice(frame);
+            consumed += frame_len;
+            if let Some(utterance) = self.frame(active, frame_len) {
+                due.push(utterance);
+            }
+        }
+        self.pending.drain(..consumed);
+        if self.speech && self.since_partial >= self.chunk_len {
+            self.since_partial = 0;
+            due.push(Utterance {
+                kind: UtteranceKind::Partial,
+                index: self.index,
+                offset_ms: ms_for(self.start),
+                samples: self.buffer.clone(),
+            });
+        }
+        due
+    }
+
+    /// Ends the stream; returns the running utterance as final, if any.
+    pub fn finish(&mut self) -> Option<Utterance> {
+        let rest = std::mem::take(&mut self.pending);
+        if self.vad.is_active(&rest) {
+            self.speech = true;
+        }
+        self.buffer.extend_from_slice(&rest);
+        self.speech.then(|| self.finalize())
+    }
+
+    fn frame(&mut self, active: bool, len: usize) -> Option<Utterance> {
+        if !self.speech {
+            if !active {
+                let excess = self.buffer.len().saturating_sub(self.preroll_len);
+                self.buffer.drain(..excess);
+                self.start += excess as u64;
+                return None;
+            }
+            self.speech = true;
+        }
+        self.since_partial += len;
+        self.silence_run = if active { 0 } else { self.silence_run + len };
+        (self.silence_run >= self.pause_len || self.buffer.len() >= self.max_len)
+            .then(|| self.finalize())
+    }
+
+    fn finalize(&mut self) -> Utterance {
+        let samples = std::mem::take(&mut self.buffer);
+        let utterance = Utterance {
+            kind: UtteranceKind::Final,
+            index: self.index,
+            offset_ms: ms_for(self.start),
+            samples,
+        };
+        self.start += utterance.samples.len() as u64;
+        self.index += 1;
+        self.speech = false;
+        self.silence_run = 0;
+        self.since_partial = 0;
+        utterance
+    }
+}
+
+fn samples_for(ms: u64) -> usize {
+    (ms * u64::from(SAMPLE_RATE) / 1_000) as usize
+}
+
+fn ms_for(samples: u64) -> u64 {
+    samples * 1_000 / u64::from(SAMPLE_RATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(ms: u64) -> Vec<i16> {
+        (0..samples_for(ms))
+            .map(|i| if i % 2 == 0 { 8_000 } else { -8_000 })
+            .collect()
+    }
+
+    #[test]
+    fn speech_is_cut_into_partials_and_finals() {
+        let mut segmenter = Segmenter::new(&SegmenterConfig {
+            chunk_ms: 600,
+            pause_ms: 300,
+            ..SegmenterConfig::default()
+        });
+        assert!(segmenter.push(&vec![0; samples_for(960)]).is_empty());
+
+        let mut due = Vec::new();
+        for chunk in tone(1_500).chunks(1_600) {
+            due.extend(segmenter.push(chunk));
+        }
+        assert!(!due.is_empty());
+        assert!(due
+            .iter()
+            .all(|u| u.kind == UtteranceKind::Partial && u.index == 0));
+        // 300 ms pre-roll before the onset at 960 ms.
+        assert_eq!(due[0].offset_ms, 660);
+
+        let due = segmenter.push(&vec![0; samples_for(400)]);
+        let last = due.last().expect("final after the pause");
+        assert_eq!(last.kind, UtteranceKind::Final);
+        assert_eq!(last.offset_ms, 660);
+        assert!(last.samples.len() >= samples_for(1_800));
+
+        assert!(segmenter.push(&vec![0; samples_for(500)]).is_empty());
+        segmenter.push(&tone(200));
+        let rest = segmenter.finish().expect("flushed at the end");
+        assert_eq!((rest.kind, rest.index), (UtteranceKind::Final, 1));
+        assert!(segmenter.finish().is_none());
+
+        let placed = rest.place(vec![Segment {
+            start_ms: 0,
+            end_ms: 200,
+            text: "hallo".into(),
+        }]);
+        assert_eq!(placed[0].start_ms, rest.offset_ms);
+    }
+
+    #[test]
+    fn long_utterances_are_cut_at_max_length() {
+        let mut segmenter = Segmenter::new(&SegmenterConfig {
+            chunk_ms: 10_000,
+            max_ms: 1_000,
+            ..SegmenterConfig::default()
+        });
+        let finals: Vec<_> = segmenter
+            .push(&tone(2_500))
+            .into_iter()
+            .filter(|u| u.kind == UtteranceKind::Final)
+            .collect();
+        assert_eq!(finals.len(), 2);
+        assert_eq!(finals[1].offset_ms, 1_020);
+    }
+}
@@ -0,0 +1,93 @@
+//! Energy-based voice activity detection on 16-bit mono PCM.
+//!
+//! Frames of 30 ms count as active when their RMS level reaches the
+//! threshold in dBFS. This is a gate against silent input, not a speech
+//! classifier; whisper.cpp's model-based VAD does the fine cutting.
+
+use std::path::Path;
+
+use crate::{AsrError, SAMPLE_RATE};
+
+const FRAME_MS: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyVad {
+    threshold_dbfs: f32,
+    frame_len: usize,
+}
+
+impl EnergyVad {
+    pub fn new(threshold_dbfs: f32) -> Self {
+        Self {
+            threshold_dbfs,
+            frame_len: SAMPLE_RATE as usize * FRAME_MS / 1_000,
+        }
+    }
+
+    /// Samples per frame at [`SAMPLE_RATE`].
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// RMS level of `samples` relative to full scale; `-inf` for digital
+    /// silence.
+    pub fn level_dbfs(samples: &[i16]) -> f32 {
+        if samples.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+        let energy: f64 = samples
+            .iter()
+            .map(|&sample| f64::from(sample) * f64::from(sample))
+            .sum();
+        let rms = (energy / samples.len() as f64).sqrt() / 32_768.0;
+        (20.0 * rms.log10()) as f32
+    }
+
+    pub fn is_active(&self, frame: &[i16]) -> bool {
+        Self::level_dbfs(frame) >= self.threshold_dbfs
+    }
+
+    /// Whether any frame of `samples` is active.
+    pub fn contains_speech(&self, samples: &[i16]) -> bool {
+        samples
+            .chunks(self.frame_len)
+            .any(|frame| self.is_active(frame))
+    }
+
+    /// Like [`contains_speech`](Self::contains_speech) for a 16-bit WAV file,
+    /// read frame by frame and stopping at the first active one.
+    pub(crate) fn wav_contains_speech(&self, path: &Path) -> Result<bool, AsrError> {
+        let audio = |err: hound::Error| AsrError::Audio(err.to_string());
+        let mut reader = hound::WavReader::open(path).map_err(audio)?;
+        let mut frame = Vec::with_capacity(self.frame_len);
+        for sample in reader.samples::<i16>() {
+            frame.push(sample.map_err(audio)?);
+            if frame.len() == self.frame_len {
+                if self.is_active(&frame) {
+                    return Ok(true);
+                }
+                frame.clear();
+            }
+        }
+        Ok(!frame.is_empty() && self.is_active(&frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_above_the_threshold_are_active() {
+        let vad = EnergyVad::new(-50.0);
+        assert_eq!(vad.frame_len(), 480);
+        assert_eq!(EnergyVad::level_dbfs(&[]), f32::NEG_INFINITY);
+        assert!((EnergyVad::level_dbfs(&[i16::MIN; 4])).abs() < 0.01);
+
+        let mut samples = vec![3_i16; 4_800]; // about -80 dBFS
+        assert!(!vad.contains_speech(&samples));
+        samples[2_000] = 20_000;
+        samples[2_001] = -20_000;
+        assert!(vad.contains_speech(&samples));
+    }
+}
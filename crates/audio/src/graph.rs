@@ -0,0 +1,184 @@
+//! State of the PipeWire graph as printed by `pw-dump`.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{glob_match, AudioError};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Device {
+    pub id: u64,
+    pub name: String,
+    pub description: Option<String>,
+    /// `(index, name)` of the available card profiles.
+    pub profiles: Vec<(u64, String)>,
+    pub active_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Node {
+    pub id: u64,
+    pub name: String,
+    pub description: Option<String>,
+    pub class: String,
+    /// Linear volume of the first channel.
+    pub volume: Option<f64>,
+    pub mute: Option<bool>,
+}
+
+impl Node {
+    /// Volume on the cubic scale `wpctl set-volume` expects.
+    pub fn wpctl_volume(&self) -> Option<f64> {
+        self.volume.map(f64::cbrt)
+    }
+}
+
+/// Devices, nodes, defaults and forced clock settings as reported by `pw-dump`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Graph {
+    pub devices: Vec<Device>,
+    pub nodes: Vec<Node>,
+    pub default_sink: Option<String>,
+    pub default_source: Option<String>,
+    /// `clock.force-quantum` from the `settings` metadata; 0 or absent = not forced.
+    pub force_quantum: Option<u32>,
+    /// `clock.force-rate` from the `settings` metadata; 0 or absent = not forced.
+    pub force_rate: Option<u32>,
+}
+
+impl Graph {
+    /// Parses the JSON array printed by `pw-dump`.
+    pub fn from_dump(dump: &Value) -> Result<Self, AudioError> {
+        let objects = dump
+            .as_array()
+            .ok_or_else(|| AudioError::InvalidDump("not a JSON array".into()))?;
+        let mut graph = Graph::default();
+        for object in objects {
+            let id = object["id"].as_u64().unwrap_or_default();
+            let props = &object["info"]["props"];
+            let text = |value: &Value| value.as_str().map(str::to_string);
+            match object["type"].as_str().unwrap_or_default() {
+                "PipeWire:Interface:Device" => {
+                    let Some(name) = text(&props["device.name"]) else {
+                        continue;
+                    };
+                    let params = &object["info"]["params"];
+                    let profiles = params["EnumProfile"]
+                        .as_array()
+                        .map(|profiles| {
+                            profiles
+                                .iter()
+                                .filter_map(|p| Some((p["index"].as_u64()?, text(&p["name"])?)))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    graph.devices.push(Device {
+                        id,
+                        name,
+                        description: text(&props["device.description"]),
+                        profiles,
+                        active_profile: text(&params["Profile"][0]["name"]),
+                    });
+                }
+                "PipeWire:Interface:Node" => {
+                    let (Some(name), Some(class)) =
+                        (text(&props["node.name"]), text(&props["media.class"]))
+                    else {
+                        continue;
+                    };
+                    let node_props = &object["info"]["params"]["Props"][0];
+                    graph.nodes.push(Node {
+                        id,
+                        name,
+                        description: text(&props["node.description"]),
+                        class,
+                        volume: node_props["channelVolumes"][0]
+                            .as_f64()
+                            .or_else(|| node_props["volume"].as_f64()),
+                        mute: node_props["mute"].as_bool(),
+                    });
+                }
+                "PipeWire:Interface:Metadata" => {
+                    let metadata = object["props"]["metadata.name"].as_str();
+                    for entry in object["metadata"].as_array().into_iter().flatten() {
+                        let key = entry["key"].as_str();
+                        match (metadata, key) {
+                            (Some("default"), Some("default.audio.sink")) => {
+                                graph.default_sink = text(&entry["value"]["name"]);
+                            }
+                            (Some("default"), Some("default.audio.source")) => {
+                                graph.default_source = text(&entry["value"]["name"]);
+                            }
+                            (Some("settings"), Some("clock.force-quantum")) => {
+                                graph.force_quantum = setting(&entry["value"]);
+                            }
+                            (Some("settings"), Some("clock.force-rate")) => {
+                                graph.force_rate = setting(&entry["value"]);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        graph.devices.sort_by_key(|device| device.id);
+        graph.nodes.sort_by_key(|node| node.id);
+        Ok(graph)
+    }
+
+    pub(crate) fn node(&self, pattern: &str, class: &str) -> Result<&Node, AudioError> {
+        self.nodes
+            .iter()
+            .find(|node| node.class == class && glob_match(pattern, &node.name))
+            .ok_or_else(|| AudioError::NoNode {
+                class: class.to_string(),
+                pattern: pattern.to_string(),
+            })
+    }
+
+    /// The node currently set as default for `class`.
+    pub fn default_node(&self, class: &str) -> Option<&Node> {
+        let name = match class {
+            "Audio/Sink" => self.default_sink.as_deref()?,
+            "Audio/Source" => self.default_source.as_deref()?,
+            _ => return None,
+        };
+        self.nodes
+            .iter()
+            .find(|node| node.class == class && node.name == name)
+    }
+}
+
+/// Settings values are numbers or numeric strings depending on who set them.
+fn setting(value: &Value) -> Option<u32> {
+    let value = value
+        .as_u64()
+        .or_else(|| value.as_str()?.trim().parse().ok())?;
+    u32::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_is_parsed_into_graph() {
+        let graph = Graph::from_dump(&crate::tests::dump()).unwrap();
+        assert_eq!(graph.devices[0].profiles.len(), 2);
+        assert_eq!(graph.devices[0].active_profile.as_deref(), Some("off"));
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.nodes[0].volume, Some(0.5));
+        assert_eq!(
+            graph.default_sink.as_deref(),
+            Some("alsa_output.pci-0.analog")
+        );
+        assert_eq!(graph.force_quantum, Some(256));
+        assert_eq!(graph.force_rate, Some(0));
+        assert_eq!(
+            graph.default_node("Audio/Sink").map(|node| node.id),
+            Some(51)
+        );
+        assert!(graph.default_node("Audio/Source").is_none());
+    }
+}
@@ -0,0 +1,318 @@
+//! Audio-Profile über PipeWire/WirePlumber.
+//!
+//! Profile stehen in `audio/profiles.yaml` ([`ProfilesFile`]) und beschreiben
+//! Karten-Profile, Default-Ausgang/-Eingang, Lautstärken, Stummschaltung und
+//! erzwungenes Quantum bzw. Samplerate. Den Zustand liest das Crate aus
+//! `pw-dump` ([`Graph`]); angewendet wird über `wpctl` und `pw-metadata`.
+//!
+//! [`AudioConfig::switch`] schaltet ein Profil als Ganzes um: Passt ein Muster
+//! auf kein Gerät oder keinen Knoten, bricht die Umschaltung vor der ersten
+//! Änderung ab. Scheitert ein Befehl mittendrin, stellt ein Schnappschuss des
+//! vorherigen Graphen ([`AudioProfile::snapshot`]) Karten, Defaults,
+//! Lautstärken und Clock-Einstellungen wieder her. Da ein neues Karten-Profil
+//! die Knoten austauscht, werden erst die Karten umgeschaltet, dann der Graph
+//! neu gelesen und erst danach die Knoten eingestellt.
+//!
+//! Die Befehle laufen synchron; asynchrone Aufrufer nutzen `spawn_blocking`.
+//!
+//! Konfiguration ([`AudioConfig::from_env`]):
+//!   HAUSKI_AUDIO_PROFILES     (Default `./audio/profiles.yaml`)
+//!   HAUSKI_PW_DUMP_BIN        (Default `pw-dump`)
+//!   HAUSKI_WPCTL_BIN          (Default `wpctl`)
+//!   HAUSKI_PW_METADATA_BIN    (Default `pw-metadata`)
+
+mod graph;
+mod profile;
+
+use std::{env, fs, io, path::PathBuf, process::Command};
+
+use thiserror::Error;
+
+pub use graph::{Device, Graph, Node};
+pub use profile::{plan, plan_cards, plan_nodes, Action, AudioProfile, ProfilesFile, Target};
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("profile file {}: {reason}", .path.display())]
+    Profiles { path: PathBuf, reason: String },
+    #[error("profile '{name}': {reason}")]
+    InvalidProfile { name: String, reason: String },
+    #[error("profile '{name}' is not configured (known: {})", .known.join(", "))]
+    UnknownProfile { name: String, known: Vec<String> },
+    #[error("no device matches '{0}'")]
+    NoDevice(String),
+    #[error("no {class} node matches '{pattern}'")]
+    NoNode { class: String, pattern: String },
+    #[error("{device} has no card profile '{profile}'")]
+    UnknownCardProfile { device: String, profile: String },
+    #[error("{bin} could not be started (set {env}): {source}")]
+    Spawn {
+        bin: String,
+        env: &'static str,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{command} failed: {stderr}")]
+    Failed { command: String, stderr: String },
+    #[error("invalid pw-dump output: {0}")]
+    InvalidDump(String),
+    #[error("{0}; previous routing restored")]
+    RolledBack(Box<AudioError>),
+    #[error("{error}; restoring the previous routing failed too: {rollback}")]
+    RollbackFailed {
+        error: Box<AudioError>,
+        rollback: Box<AudioError>,
+    },
+}
+
+impl AudioError {
+    /// Whether the routing was left as it was before the call.
+    pub fn is_unchanged(&self) -> bool {
+        !matches!(self, AudioError::RollbackFailed { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioConfig {
+    pub profiles_path: PathBuf,
+    pub pw_dump_bin: String,
+    pub wpctl_bin: String,
+    pub pw_metadata_bin: String,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            profiles_path: PathBuf::from("./audio/profiles.yaml"),
+            pw_dump_bin: "pw-dump".into(),
+            wpctl_bin: "wpctl".into(),
+            pw_metadata_bin: "pw-metadata".into(),
+        }
+    }
+}
+
+impl AudioConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            profiles_path: env_string("HAUSKI_AUDIO_PROFILES")
+                .map(PathBuf::from)
+                .unwrap_or(defaults.profiles_path),
+            pw_dump_bin: env_string("HAUSKI_PW_DUMP_BIN").unwrap_or(defaults.pw_dump_bin),
+            wpctl_bin: env_string("HAUSKI_WPCTL_BIN").unwrap_or(defaults.wpctl_bin),
+            pw_metadata_bin: env_string("HAUSKI_PW_METADATA_BIN")
+                .unwrap_or(defaults.pw_metadata_bin),
+        }
+    }
+
+    /// Reads and validates the profile file.
+    pub fn load_profiles(&self) -> Result<ProfilesFile, AudioError> {
+        let path = &self.profiles_path;
+        let invalid = |reason: String| AudioError::Profiles {
+            path: path.clone(),
+            reason,
+        };
+        let raw = fs::read_to_string(path).map_err(|err| invalid(err.to_string()))?;
+        let file: ProfilesFile =
+            serde_yaml_ng::from_str(&raw).map_err(|err| invalid(err.to_string()))?;
+        for (name, profile) in &file.profiles {
+            profile
+                .validate()
+                .map_err(|reason| AudioError::InvalidProfile {
+                    name: name.clone(),
+                    reason,
+                })?;
+        }
+        Ok(file)
+    }
+
+    pub fn read_graph(&self) -> Result<Graph, AudioError> {
+        let output =
+            Command::new(&self.pw_dump_bin)
+                .output()
+                .map_err(|source| AudioError::Spawn {
+                    bin: self.pw_dump_bin.clone(),
+                    env: "HAUSKI_PW_DUMP_BIN",
+                    source,
+                })?;
+        if !output.status.success() {
+            return Err(AudioError::Failed {
+                command: self.pw_dump_bin.clone(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        let dump = serde_json::from_slice(&output.stdout)
+            .map_err(|err| AudioError::InvalidDump(err.to_string()))?;
+        Graph::from_dump(&dump)
+    }
+
+    /// Command line of `action`, program first.
+    pub fn argv(&self, action: &Action) -> Vec<String> {
+        let bin = if action.is_metadata() {
+            &self.pw_metadata_bin
+        } else {
+            &self.wpctl_bin
+        };
+        std::iter::once(bin.clone()).chain(action.args()).collect()
+    }
+
+    /// Runs `actions` in order, appending each successful command line to
+    /// `done`; stops at the first failure.
+    pub fn execute(&self, actions: &[Action], done: &mut Vec<String>) -> Result<(), AudioError> {
+        for action in actions {
+            let argv = self.argv(action);
+            let env = if action.is_metadata() {
+                "HAUSKI_PW_METADATA_BIN"
+            } else {
+                "HAUSKI_WPCTL_BIN"
+            };
+            let output = Command::new(&argv[0])
+                .args(&argv[1..])
+                .output()
+                .map_err(|source| AudioError::Spawn {
+                    bin: argv[0].clone(),
+                    env,
+                    source,
+                })?;
+            if !output.status.success() {
+                return Err(AudioError::Failed {
+                    command: argv.join(" "),
+                    stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                });
+            }
+            done.push(argv.join(" "));
+        }
+        Ok(())
+    }
+
+    /// Applies `profile` as a whole and returns the commands that ran.
+    ///
+    /// Once a command failed after earlier ones changed the graph, the
+    /// routing from before the call is restored and the error comes back as
+    /// [`AudioError::RolledBack`] (or [`AudioError::RollbackFailed`]).
+    pub fn switch(&self, profile: &AudioProfile) -> Result<Vec<String>, AudioError> {
+        let before = self.read_graph()?;
+        let mut done = Vec::new();
+        let Err(error) = self.apply(profile, before.clone(), &mut done) else {
+            return Ok(done);
+        };
+        if done.is_empty() {
+            return Err(error);
+        }
+        let snapshot = AudioProfile::snapshot(&before);
+        match self
+            .read_graph()
+            .and_then(|graph| self.apply(&snapshot, graph, &mut Vec::new()))
+        {
+            Ok(()) => Err(AudioError::RolledBack(Box::new(error))),
+            Err(rollback) => Err(AudioError::RollbackFailed {
+                error: Box::new(error),
+                rollback: Box::new(rollback),
+            }),
+        }
+    }
+
+    fn apply(
+        &self,
+        profile: &AudioProfile,
+        graph: Graph,
+        done: &mut Vec<String>,
+    ) -> Result<(), AudioError> {
+        let cards = plan_cards(profile, &graph)?;
+        // Without card switches the nodes are planned before anything runs.
+        let graph = if cards.is_empty() {
+            graph
+        } else {
+            self.execute(&cards, done)?;
+            self.read_graph()?
+        };
+        let nodes = plan_nodes(profile, &graph)?;
+        self.execute(&nodes, done)
+    }
+}
+
+/// `*` matches any run of characters; everything else literally.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn env_string(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    pub(crate) fn dump() -> Value {
+        json!([
+            {"id": 40, "type": "PipeWire:Interface:Metadata", "props": {"metadata.name": "default"},
+             "metadata": [{"subject": 0, "key": "default.audio.sink", "value": {"name": "alsa_output.pci-0.analog"}}]},
+            {"id": 41, "type": "PipeWire:Interface:Metadata", "props": {"metadata.name": "settings"},
+             "metadata": [{"subject": 0, "key": "clock.force-quantum", "value": 256},
+                          {"subject": 0, "key": "clock.force-rate", "value": "0"}]},
+            {"id": 45, "type": "PipeWire:Interface:Device",
+             "info": {"props": {"device.name": "alsa_card.usb-Focusrite"},
+                      "params": {"EnumProfile": [{"index": 0, "name": "off"}, {"index": 3, "name": "pro-audio"}],
+                                 "Profile": [{"index": 0, "name": "off"}]}}},
+            {"id": 51, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"node.name": "alsa_output.pci-0.analog", "media.class": "Audio/Sink"},
+                      "params": {"Props": [{"mute": false, "channelVolumes": [0.5, 0.5]}]}}},
+            {"id": 52, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"node.name": "alsa_output.usb-Focusrite.pro", "media.class": "Audio/Sink"}}},
+            {"id": 53, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"node.name": "alsa_input.usb-Focusrite.pro", "media.class": "Audio/Source"}}}
+        ])
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_match(
+            "alsa_output.usb-*",
+            "alsa_output.usb-Focusrite.pro"
+        ));
+        assert!(glob_match("*Focusrite*", "alsa_input.usb-Focusrite.pro"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("alsa_*.pro", "alsa_output.analog"));
+        assert!(!glob_match("a*b*c", "acb"));
+    }
+
+    #[test]
+    fn profiles_are_validated_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.yaml");
+        fs::write(&path, "profiles:\n  loud:\n    volume: 2.0\n").unwrap();
+        let config = AudioConfig {
+            profiles_path: path.clone(),
+            ..AudioConfig::default()
+        };
+        assert!(matches!(
+            config.load_profiles(),
+            Err(AudioError::InvalidProfile { name, .. }) if name == "loud"
+        ));
+        fs::write(&path, "profiles:\n  quiet:\n    volume: 0.2\n    bass: 3\n").unwrap();
+        assert!(matches!(
+            config.load_profiles(),
+            Err(AudioError::Profiles { .. })
+        ));
+    }
+}
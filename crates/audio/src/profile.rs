@@ -0,0 +1,400 @@
+//! Profiles from `audio/profiles.yaml` and the actions applying them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{glob_match, AudioError, Device, Graph};
+
+const MAX_VOLUME: f64 = 1.5;
+/// Volumes closer than this count as equal; `wpctl` gets two decimals.
+const VOLUME_TOLERANCE: f64 = 0.005;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilesFile {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, AudioProfile>,
+}
+
+impl ProfilesFile {
+    /// The profile `name`, naming the known ones otherwise.
+    pub fn get(&self, name: &str) -> Result<&AudioProfile, AudioError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| AudioError::UnknownProfile {
+                name: name.to_string(),
+                known: self.profiles.keys().cloned().collect(),
+            })
+    }
+
+    /// Names of the profiles `graph` currently satisfies.
+    pub fn applied(&self, graph: &Graph) -> Vec<String> {
+        self.profiles
+            .iter()
+            .filter(|(_, profile)| profile.is_applied(graph))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudioProfile {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `device.name` pattern -> card profile name.
+    #[serde(default)]
+    pub card_profiles: BTreeMap<String, String>,
+    #[serde(default)]
+    pub sink: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub volume: Option<f64>,
+    #[serde(default)]
+    pub source_volume: Option<f64>,
+    #[serde(default)]
+    pub mute_source: Option<bool>,
+    #[serde(default)]
+    pub quantum: Option<u32>,
+    #[serde(default)]
+    pub rate: Option<u32>,
+}
+
+impl AudioProfile {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for (field, volume) in [
+            ("volume", self.volume),
+            ("source_volume", self.source_volume),
+        ] {
+            if let Some(volume) = volume {
+                if !(0.0..=MAX_VOLUME).contains(&volume) {
+                    return Err(format!("{field} must be within 0.0–{MAX_VOLUME}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Profile restoring the routing of `graph`: active card profiles,
+    /// default nodes with their volume and mute state, forced clock settings.
+    pub fn snapshot(graph: &Graph) -> Self {
+        let sink = graph.default_node("Audio/Sink");
+        let source = graph.default_node("Audio/Source");
+        Self {
+            description: None,
+            card_profiles: graph
+                .devices
+                .iter()
+                .filter_map(|device| Some((device.name.clone(), device.active_profile.clone()?)))
+                .collect(),
+            sink: sink.map(|node| node.name.clone()),
+            source: source.map(|node| node.name.clone()),
+            volume: sink.and_then(|node| node.wpctl_volume()),
+            source_volume: source.and_then(|node| node.wpctl_volume()),
+            mute_source: source.and_then(|node| node.mute),
+            quantum: Some(graph.force_quantum.unwrap_or(0)),
+            rate: Some(graph.force_rate.unwrap_or(0)),
+        }
+    }
+
+    /// Whether `graph` already matches every setting of the profile.
+    pub fn is_applied(&self, graph: &Graph) -> bool {
+        let cards = self.card_profiles.iter().all(|(pattern, wanted)| {
+            let mut devices = matching_devices(pattern, graph).peekable();
+            devices.peek().is_some()
+                && devices.all(|device| device.active_profile.as_deref() == Some(wanted.as_str()))
+        });
+        let default = |pattern: &Option<String>, current: &Option<String>| match pattern {
+            Some(pattern) => current
+                .as_deref()
+                .is_some_and(|name| glob_match(pattern, name)),
+            None => true,
+        };
+        let sink = graph.default_node("Audio/Sink");
+        let source = graph.default_node("Audio/Source");
+        let volume = |wanted: Option<f64>, node: Option<&crate::Node>| match wanted {
+            Some(wanted) => node
+                .and_then(|node| node.wpctl_volume())
+                .is_some_and(|volume| (volume - wanted).abs() < VOLUME_TOLERANCE),
+            None => true,
+        };
+        let setting = |wanted: Option<u32>, current: Option<u32>| {
+            wanted.is_none_or(|wanted| current.unwrap_or(0) == wanted)
+        };
+        cards
+            && default(&self.sink, &graph.default_sink)
+            && default(&self.source, &graph.default_source)
+            && volume(self.volume, sink)
+            && volume(self.source_volume, source)
+            && self
+                .mute_source
+                .is_none_or(|mute| source.and_then(|node| node.mute) == Some(mute))
+            && setting(self.quantum, graph.force_quantum)
+            && setting(self.rate, graph.force_rate)
+    }
+}
+
+/// Node addressed by `wpctl`: an id or the current default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Id(u64),
+    DefaultSink,
+    DefaultSource,
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Id(id) => write!(f, "{id}"),
+            Target::DefaultSink => f.write_str("@DEFAULT_AUDIO_SINK@"),
+            Target::DefaultSource => f.write_str("@DEFAULT_AUDIO_SOURCE@"),
+        }
+    }
+}
+
+/// One `wpctl`/`pw-metadata` invocation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    CardProfile { device: u64, index: u64 },
+    Default { node: u64 },
+    Volume { node: Target, volume: f64 },
+    Mute { node: Target, mute: bool },
+    Setting { key: &'static str, value: u32 },
+}
+
+impl Action {
+    /// Whether the action runs `pw-metadata` rather than `wpctl`.
+    pub fn is_metadata(&self) -> bool {
+        matches!(self, Action::Setting { .. })
+    }
+
+    /// Arguments after the program name.
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Action::CardProfile { device, index } => {
+                vec!["set-profile".into(), device.to_string(), index.to_string()]
+            }
+            Action::Default { node } => vec!["set-default".into(), node.to_string()],
+            Action::Volume { node, volume } => vec![
+                "set-volume".into(),
+                node.to_string(),
+                format!("{volume:.2}"),
+            ],
+            Action::Mute { node, mute } => vec![
+                "set-mute".into(),
+                node.to_string(),
+                u8::from(*mute).to_string(),
+            ],
+            Action::Setting { key, value } => ["-n", "settings", "0", key]
+                .into_iter()
+                .map(str::to_string)
+                .chain([value.to_string()])
+                .collect(),
+        }
+    }
+}
+
+fn matching_devices<'a>(pattern: &'a str, graph: &'a Graph) -> impl Iterator<Item = &'a Device> {
+    graph
+        .devices
+        .iter()
+        .filter(move |device| glob_match(pattern, &device.name))
+}
+
+/// Card profile switches of `profile`; cards already in the profile are skipped.
+pub fn plan_cards(profile: &AudioProfile, graph: &Graph) -> Result<Vec<Action>, AudioError> {
+    let mut actions = Vec::new();
+    for (pattern, wanted) in &profile.card_profiles {
+        let devices: Vec<&Device> = matching_devices(pattern, graph).collect();
+        if devices.is_empty() {
+            return Err(AudioError::NoDevice(pattern.clone()));
+        }
+        for device in devices {
+            if device.active_profile.as_deref() == Some(wanted.as_str()) {
+                continue;
+            }
+            let index = device
+                .profiles
+                .iter()
+                .find(|(_, name)| name == wanted)
+                .map(|(index, _)| *index)
+                .ok_or_else(|| AudioError::UnknownCardProfile {
+                    device: device.name.clone(),
+                    profile: wanted.clone(),
+                })?;
+            actions.push(Action::CardProfile {
+                device: device.id,
+                index,
+            });
+        }
+    }
+    Ok(actions)
+}
+
+/// Node and clock settings of `profile` against the current `graph`.
+pub fn plan_nodes(profile: &AudioProfile, graph: &Graph) -> Result<Vec<Action>, AudioError> {
+    let mut actions = Vec::new();
+    let sink = profile
+        .sink
+        .as_deref()
+        .map(|pattern| graph.node(pattern, "Audio/Sink"))
+        .transpose()?;
+    let source = profile
+        .source
+        .as_deref()
+        .map(|pattern| graph.node(pattern, "Audio/Source"))
+        .transpose()?;
+    if let Some(sink) = sink {
+        actions.push(Action::Default { node: sink.id });
+    }
+    if let Some(source) = source {
+        actions.push(Action::Default { node: source.id });
+    }
+
+    // Without an explicit node the settings apply to the current defaults.
+    let sink = sink.map_or(Target::DefaultSink, |node| Target::Id(node.id));
+    let source = source.map_or(Target::DefaultSource, |node| Target::Id(node.id));
+    if let Some(volume) = profile.volume {
+        actions.push(Action::Volume { node: sink, volume });
+    }
+    if let Some(volume) = profile.source_volume {
+        actions.push(Action::Volume {
+            node: source,
+            volume,
+        });
+    }
+    if let Some(mute) = profile.mute_source {
+        actions.push(Action::Mute { node: source, mute });
+    }
+    if let Some(value) = profile.quantum {
+        actions.push(Action::Setting {
+            key: "clock.force-quantum",
+            value,
+        });
+    }
+    if let Some(value) = profile.rate {
+        actions.push(Action::Setting {
+            key: "clock.force-rate",
+            value,
+        });
+    }
+    Ok(actions)
+}
+
+/// Every action of `profile` planned against the same `graph`, as a dry run
+/// shows it; nodes that only appear after a card switch are not known yet.
+pub fn plan(profile: &AudioProfile, graph: &Graph) -> Result<Vec<Action>, AudioError> {
+    let mut actions = plan_cards(profile, graph)?;
+    actions.extend(plan_nodes(profile, graph)?);
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AudioConfig;
+
+    fn argv(actions: &[Action]) -> Vec<String> {
+        let config = AudioConfig::default();
+        actions
+            .iter()
+            .map(|action| config.argv(action).join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn profile_is_planned_against_graph() {
+        let profiles: ProfilesFile = serde_yaml_ng::from_str(
+            r#"
+profiles:
+  studio:
+    card_profiles: { "alsa_card.usb-*": pro-audio }
+    sink: "alsa_output.usb-*"
+    source: "*Focusrite*"
+    volume: 0.8
+    mute_source: false
+    quantum: 64
+  music:
+    volume: 0.7
+    mute_source: true
+"#,
+        )
+        .unwrap();
+        let graph = Graph::from_dump(&crate::tests::dump()).unwrap();
+
+        let studio = &profiles.profiles["studio"];
+        assert_eq!(
+            plan_cards(studio, &graph).unwrap(),
+            [Action::CardProfile {
+                device: 45,
+                index: 3
+            }]
+        );
+        assert_eq!(
+            argv(&plan_nodes(studio, &graph).unwrap()),
+            [
+                "wpctl set-default 52",
+                "wpctl set-default 53",
+                "wpctl set-volume 52 0.80",
+                "wpctl set-mute 53 0",
+                "pw-metadata -n settings 0 clock.force-quantum 64",
+            ]
+        );
+
+        assert_eq!(
+            argv(&plan_nodes(&profiles.profiles["music"], &graph).unwrap()),
+            [
+                "wpctl set-volume @DEFAULT_AUDIO_SINK@ 0.70",
+                "wpctl set-mute @DEFAULT_AUDIO_SOURCE@ 1",
+            ]
+        );
+
+        let missing = AudioProfile {
+            sink: Some("bluez_output.*".into()),
+            ..AudioProfile::default()
+        };
+        assert!(matches!(
+            plan_nodes(&missing, &graph),
+            Err(AudioError::NoNode { .. })
+        ));
+        assert!(matches!(
+            profiles.get("call"),
+            Err(AudioError::UnknownProfile { known, .. }) if known == ["music", "studio"]
+        ));
+    }
+
+    #[test]
+    fn snapshot_restores_the_current_routing() {
+        let graph = Graph::from_dump(&crate::tests::dump()).unwrap();
+        let snapshot = AudioProfile::snapshot(&graph);
+        assert_eq!(
+            snapshot.card_profiles,
+            BTreeMap::from([("alsa_card.usb-Focusrite".into(), "off".into())])
+        );
+        assert_eq!(snapshot.sink.as_deref(), Some("alsa_output.pci-0.analog"));
+        // channelVolumes are linear, wpctl takes the cube root.
+        assert!((snapshot.volume.unwrap() - 0.5_f64.cbrt()).abs() < 1e-9);
+        assert_eq!(snapshot.source, None);
+        assert_eq!((snapshot.quantum, snapshot.rate), (Some(256), Some(0)));
+        assert!(plan_cards(&snapshot, &graph).unwrap().is_empty());
+        assert!(snapshot.is_applied(&graph));
+
+        let louder = AudioProfile {
+            volume: Some(0.9),
+            ..snapshot.clone()
+        };
+        assert!(!louder.is_applied(&graph));
+        let studio = AudioProfile {
+            card_profiles: BTreeMap::from([("alsa_card.usb-*".into(), "pro-audio".into())]),
+            ..AudioProfile::default()
+        };
+        assert!(!studio.is_applied(&graph));
+        let unforced = AudioProfile {
+            quantum: Some(0),
+            ..AudioProfile::default()
+        };
+        assert!(!unforced.is_applied(&graph));
+    }
+}
@@ -0,0 +1,68 @@
+//! Optional encryption of whole archives with a passphrase.
+//!
+//! Layout: [`MAGIC`], 16 bytes salt, 24 bytes nonce, then the
+//! XChaCha20-Poly1305 ciphertext. The key comes from Argon2id over the
+//! passphrase and salt; magic and salt are authenticated as associated data.
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::BackupError;
+
+pub(crate) const MAGIC: &[u8; 12] = b"HAUSKI-ENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, BackupError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| BackupError::Encrypt(err.to_string()))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub(crate) fn encrypt(plain: &[u8], passphrase: &str) -> Result<Vec<u8>, BackupError> {
+    let mut header = MAGIC.to_vec();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    header.extend_from_slice(&salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plain,
+                aad: &header,
+            },
+        )
+        .map_err(|err| BackupError::Encrypt(err.to_string()))?;
+    let mut out = header;
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub(crate) fn decrypt(bytes: &[u8], passphrase: &str) -> Result<Vec<u8>, BackupError> {
+    let header_len = MAGIC.len() + SALT_LEN;
+    if !is_encrypted(bytes) || bytes.len() < header_len + NONCE_LEN {
+        return Err(BackupError::Decrypt);
+    }
+    let (header, rest) = bytes.split_at(header_len);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    cipher(passphrase, &header[MAGIC.len()..])?
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| BackupError::Decrypt)
+}
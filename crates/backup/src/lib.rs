@@ -0,0 +1,603 @@
+//! Versionierte Backup-Archive für HausKI.
+//!
+//! Ein Archiv ist `tar` + `zstd` mit `manifest.json` (Formatversion,
+//! HausKI-Version, Zeitpunkt, Zähler und SHA256 jeder weiteren Datei) und
+//! beliebigen weiteren Einträgen; welche, bestimmt der Aufrufer. Mit
+//! Passphrase wird das ganze Archiv verschlüsselt (Argon2id +
+//! XChaCha20-Poly1305), Dateiname dann `….tar.zst.enc`.
+//!
+//! [`write_file`] und [`read_file`] schreiben (atomar über eine temporäre
+//! Datei) bzw. lesen ein Archiv an beliebigem Pfad, lesend mit Prüfung von
+//! Format und Prüfsummen – so arbeitet `hauski export`/`import`.
+//! [`BackupDir`] verwaltet ein Verzeichnis solcher Archive für `/backup`:
+//! schreiben, auflisten, lesen und die neuesten `keep` behalten. Die Namen
+//! (`hauski-<UTC-Zeitstempel>.tar.zst[.enc]`) sortieren chronologisch.
+//!
+//! Beide legen dieselben Einträge ab, soweit sie sie haben:
+//!
+//! - [`INDEX_ENTRY`] – alle Dokumente aus indexd,
+//! - [`MEMORY_DB_ENTRY`] – Snapshot der Memory-DB (nur `/backup`),
+//! - [`MEMORY_ITEMS_ENTRY`] – Memory-Einträge aus `/memory/list` (nur
+//!   `hauski export`),
+//! - [`POLICY_ENTRY`] – gelernter Zustand der Entscheidungs-Policy (nur
+//!   `/backup`),
+//! - [`CONFIG_FILES`] – Konfigurationsdateien.
+
+mod crypt;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Archive layout version; newer archives are refused.
+pub const FORMAT_VERSION: u32 = 1;
+pub const MANIFEST: &str = "manifest.json";
+pub const INDEX_ENTRY: &str = "index/snapshot.json";
+pub const MEMORY_DB_ENTRY: &str = "memory/memory.db";
+pub const MEMORY_ITEMS_ENTRY: &str = "memory/items.json";
+pub const POLICY_ENTRY: &str = "policy/state.json";
+
+/// Config files carried in archives: entry name, env override, default path.
+pub const CONFIG_FILES: [(&str, &str, &str); 9] = [
+    (
+        "configs/hauski.yml",
+        "HAUSKI_CONFIG",
+        "./configs/hauski.yml",
+    ),
+    (
+        "configs/models.yml",
+        "HAUSKI_MODELS",
+        "./configs/models.yml",
+    ),
+    ("configs/flags.yaml", "HAUSKI_FLAGS", "./configs/flags.yaml"),
+    (
+        "configs/schedules.yaml",
+        "HAUSKI_SCHEDULES",
+        "./configs/schedules.yaml",
+    ),
+    (
+        "configs/webhooks.yaml",
+        "HAUSKI_WEBHOOKS",
+        "./configs/webhooks.yaml",
+    ),
+    (
+        "configs/notifications.yaml",
+        "HAUSKI_NOTIFICATIONS",
+        "./configs/notifications.yaml",
+    ),
+    (
+        "policies/limits.yaml",
+        "HAUSKI_LIMITS",
+        "./policies/limits.yaml",
+    ),
+    (
+        "policies/routing.yaml",
+        "HAUSKI_ROUTING",
+        "./policies/routing.yaml",
+    ),
+    (
+        "policies/decisions.yaml",
+        "HAUSKI_DECISION_POLICY_PATH",
+        "policies/decisions.yaml",
+    ),
+];
+
+/// Path of a carried config file on this machine: `env_var` if set, else
+/// `default`.
+pub fn config_path(env_var: &str, default: &str) -> PathBuf {
+    std::env::var(env_var)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map_or_else(|| PathBuf::from(default), PathBuf::from)
+}
+
+const PREFIX: &str = "hauski-";
+const SUFFIX: &str = ".tar.zst";
+const ENCRYPTED_SUFFIX: &str = ".tar.zst.enc";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("{path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("'{0}' is not a backup name")]
+    InvalidName(String),
+    #[error("backup '{0}' not found")]
+    NotFound(String),
+    #[error("not a HausKI backup: {0}")]
+    Format(String),
+    #[error("backup format {found} is not supported (at most {FORMAT_VERSION}) – update HausKI")]
+    UnsupportedFormat { found: u32 },
+    #[error("{0} missing in backup")]
+    MissingEntry(String),
+    #[error("checksum of {0} does not match – backup damaged")]
+    Checksum(String),
+    #[error("backup is encrypted – passphrase required")]
+    PassphraseRequired,
+    #[error("backup could not be decrypted – wrong passphrase or damaged")]
+    Decrypt,
+    #[error("encryption failed: {0}")]
+    Encrypt(String),
+}
+
+impl BackupError {
+    fn io(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Manifest {
+    pub format: u32,
+    pub hauski_version: String,
+    pub created_at: DateTime<Utc>,
+    /// Caller-defined counts, e.g. `documents` or `memory_items`.
+    #[serde(default)]
+    pub counts: BTreeMap<String, u64>,
+    /// SHA256 and size per archive entry (without the manifest itself).
+    pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    fn new(bundle: &Bundle, hauski_version: &str, counts: BTreeMap<String, u64>) -> Self {
+        Self {
+            format: FORMAT_VERSION,
+            hauski_version: hauski_version.to_string(),
+            created_at: Utc::now(),
+            counts,
+            entries: bundle
+                .files
+                .iter()
+                .map(|(name, bytes)| {
+                    let entry = ManifestEntry {
+                        sha256: sha256_hex(bytes),
+                        bytes: bytes.len() as u64,
+                    };
+                    (name.clone(), entry)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ManifestEntry {
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// Archive contents besides the manifest, keyed by entry name.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Bundle {
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Bundle {
+    pub fn insert(&mut self, name: impl Into<String>, bytes: Vec<u8>) {
+        self.files.insert(name.into(), bytes);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.files.get(name).map(Vec::as_slice)
+    }
+
+    /// Parses entry `name` as JSON; `None` if the entry is missing.
+    pub fn json<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, BackupError> {
+        self.get(name)
+            .map(|bytes| {
+                serde_json::from_slice(bytes)
+                    .map_err(|err| BackupError::Format(format!("{name}: {err}")))
+            })
+            .transpose()
+    }
+}
+
+/// An archive in a [`BackupDir`].
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ArchiveInfo {
+    pub name: String,
+    pub bytes: u64,
+    pub created_at: DateTime<Utc>,
+    pub encrypted: bool,
+}
+
+impl ArchiveInfo {
+    fn parse(name: &str, bytes: u64) -> Option<Self> {
+        let (stamp, encrypted) = match name.strip_suffix(ENCRYPTED_SUFFIX) {
+            Some(stamp) => (stamp, true),
+            None => (name.strip_suffix(SUFFIX)?, false),
+        };
+        let created_at =
+            NaiveDateTime::parse_from_str(stamp.strip_prefix(PREFIX)?, TIMESTAMP_FORMAT)
+                .ok()?
+                .and_utc();
+        Some(Self {
+            name: name.to_string(),
+            bytes,
+            created_at,
+            encrypted,
+        })
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Directory holding the archives.
+#[derive(Debug, Clone)]
+pub struct BackupDir {
+    dir: PathBuf,
+}
+
+impl BackupDir {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Archives, oldest first; a missing directory has none.
+    pub fn list(&self) -> Result<Vec<ArchiveInfo>, BackupError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(BackupError::io(&self.dir)(err)),
+        };
+        let mut archives: Vec<ArchiveInfo> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .filter_map(|entry| {
+                let bytes = entry.metadata().map_or(0, |meta| meta.len());
+                ArchiveInfo::parse(entry.file_name().to_str()?, bytes)
+            })
+            .collect();
+        archives.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(archives)
+    }
+
+    /// The archive called `name`; refuses anything but archive names.
+    pub fn get(&self, name: &str) -> Result<ArchiveInfo, BackupError> {
+        ArchiveInfo::parse(name, 0).ok_or_else(|| BackupError::InvalidName(name.to_string()))?;
+        let path = self.dir.join(name);
+        match fs::metadata(&path) {
+            Ok(meta) if meta.is_file() => Ok(ArchiveInfo::parse(name, meta.len())
+                .ok_or_else(|| BackupError::InvalidName(name.to_string()))?),
+            Ok(_) => Err(BackupError::NotFound(name.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(BackupError::NotFound(name.to_string()))
+            }
+            Err(err) => Err(BackupError::io(&path)(err)),
+        }
+    }
+
+    /// Writes `bundle` as a new archive, encrypted if `passphrase` is set.
+    pub fn write(
+        &self,
+        bundle: &Bundle,
+        hauski_version: &str,
+        counts: BTreeMap<String, u64>,
+        passphrase: Option<&str>,
+    ) -> Result<(ArchiveInfo, Manifest), BackupError> {
+        let manifest = Manifest::new(bundle, hauski_version, counts);
+        let stamp = manifest.created_at.format(TIMESTAMP_FORMAT);
+        let suffix = if passphrase.is_some() {
+            ENCRYPTED_SUFFIX
+        } else {
+            SUFFIX
+        };
+        let name = format!("{PREFIX}{stamp}{suffix}");
+        let bytes = store(&self.dir.join(&name), &manifest, bundle, passphrase)?;
+        let info = ArchiveInfo::parse(&name, bytes)
+            .ok_or_else(|| BackupError::InvalidName(name.clone()))?;
+        Ok((info, manifest))
+    }
+
+    /// Reads archive `name` and checks format version and checksums.
+    pub fn read(
+        &self,
+        name: &str,
+        passphrase: Option<&str>,
+    ) -> Result<(Manifest, Bundle), BackupError> {
+        self.get(name)?;
+        read_file(&self.dir.join(name), passphrase)
+    }
+
+    /// Deletes all but the `keep` newest archives (at least one stays) and
+    /// returns the names removed.
+    pub fn prune(&self, keep: usize) -> Result<Vec<String>, BackupError> {
+        let archives = self.list()?;
+        let excess = archives.len().saturating_sub(keep.max(1));
+        let mut removed = Vec::with_capacity(excess);
+        for archive in &archives[..excess] {
+            let path = self.dir.join(&archive.name);
+            fs::remove_file(&path).map_err(BackupError::io(&path))?;
+            removed.push(archive.name.clone());
+        }
+        Ok(removed)
+    }
+}
+
+/// Writes `bundle` as an archive to `path`, encrypted if `passphrase` is set.
+pub fn write_file(
+    path: &Path,
+    bundle: &Bundle,
+    hauski_version: &str,
+    counts: BTreeMap<String, u64>,
+    passphrase: Option<&str>,
+) -> Result<Manifest, BackupError> {
+    let manifest = Manifest::new(bundle, hauski_version, counts);
+    store(path, &manifest, bundle, passphrase)?;
+    Ok(manifest)
+}
+
+/// Reads the archive at `path` and checks format version and checksums.
+pub fn read_file(path: &Path, passphrase: Option<&str>) -> Result<(Manifest, Bundle), BackupError> {
+    let mut bytes = fs::read(path).map_err(BackupError::io(path))?;
+    if crypt::is_encrypted(&bytes) {
+        let passphrase = passphrase.ok_or(BackupError::PassphraseRequired)?;
+        bytes = crypt::decrypt(&bytes, passphrase)?;
+    }
+    unpack(&bytes)
+}
+
+/// Packs (and encrypts) the archive into a temporary file next to `path`,
+/// then renames it into place; returns the size written.
+fn store(
+    path: &Path,
+    manifest: &Manifest,
+    bundle: &Bundle,
+    passphrase: Option<&str>,
+) -> Result<u64, BackupError> {
+    let mut bytes = pack(manifest, bundle).map_err(BackupError::io(path))?;
+    if let Some(passphrase) = passphrase {
+        bytes = crypt::encrypt(&bytes, passphrase)?;
+    }
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).map_err(BackupError::io(dir))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| BackupError::InvalidName(path.display().to_string()))?;
+    let partial = dir.join(format!(".{}.partial", file_name.to_string_lossy()));
+    fs::write(&partial, &bytes).map_err(BackupError::io(&partial))?;
+    fs::rename(&partial, path).map_err(BackupError::io(path))?;
+    Ok(bytes.len() as u64)
+}
+
+fn pack(manifest: &Manifest, bundle: &Bundle) -> io::Result<Vec<u8>> {
+    let encoder = zstd::Encoder::new(Vec::new(), ZSTD_LEVEL)?;
+    let mut tar = tar::Builder::new(encoder);
+    let mtime = manifest.created_at.timestamp().max(0) as u64;
+    let mut append = |name: &str, bytes: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        tar.append_data(&mut header, name, bytes)
+    };
+    append(MANIFEST, &serde_json::to_vec_pretty(manifest)?)?;
+    for (name, bytes) in &bundle.files {
+        append(name, bytes)?;
+    }
+    tar.into_inner()?.finish()
+}
+
+fn unpack(bytes: &[u8]) -> Result<(Manifest, Bundle), BackupError> {
+    let format = |err: io::Error| BackupError::Format(err.to_string());
+    let decoder = zstd::Decoder::new(bytes).map_err(format)?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut manifest: Option<Manifest> = None;
+    let mut bundle = Bundle::default();
+    for entry in archive.entries().map_err(format)? {
+        let mut entry = entry.map_err(format)?;
+        let name = entry.path().map_err(format)?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(format)?;
+        if name == MANIFEST {
+            manifest = Some(
+                serde_json::from_slice(&bytes)
+                    .map_err(|err| BackupError::Format(format!("{MANIFEST}: {err}")))?,
+            );
+        } else {
+            bundle.insert(name, bytes);
+        }
+    }
+    let manifest = manifest.ok_or_else(|| BackupError::MissingEntry(MANIFEST.to_string()))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(BackupError::UnsupportedFormat {
+            found: manifest.format,
+        });
+    }
+    for (name, expected) in &manifest.entries {
+        let bytes = bundle
+            .get(name)
+            .ok_or_else(|| BackupError::MissingEntry(name.clone()))?;
+        if sha256_hex(bytes) != expected.sha256 {
+            return Err(BackupError::Checksum(name.clone()));
+        }
+    }
+    bundle
+        .files
+        .retain(|name, _| manifest.entries.contains_key(name));
+    Ok((manifest, bundle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle() -> Bundle {
+        let mut bundle = Bundle::default();
+        bundle.insert("index/snapshot.json", br#"{"documents":[]}"#.to_vec());
+        bundle.insert("configs/flags.yaml", b"safe_mode: false\n".to_vec());
+        bundle
+    }
+
+    #[test]
+    fn archives_roundtrip_plain_and_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = BackupDir::new(dir.path().join("backups"));
+        assert!(backups.list().unwrap().is_empty());
+
+        let counts = BTreeMap::from([("documents".to_string(), 0)]);
+        let (plain, manifest) = backups.write(&bundle(), "0.1.0", counts, None).unwrap();
+        assert!(plain.name.starts_with("hauski-") && plain.name.ends_with(".tar.zst"));
+        assert_eq!(manifest.entries.len(), 2);
+        let (read, contents) = backups.read(&plain.name, Some("ignored")).unwrap();
+        assert_eq!(read, manifest);
+        assert_eq!(contents, bundle());
+
+        let (sealed, _) = backups
+            .write(&bundle(), "0.1.0", BTreeMap::new(), Some("geheim"))
+            .unwrap();
+        assert!(sealed.encrypted && sealed.name.ends_with(".tar.zst.enc"));
+        let raw = fs::read(dir.path().join("backups").join(&sealed.name)).unwrap();
+        assert!(!raw.windows(9).any(|w| w == b"safe_mode"));
+        assert!(matches!(
+            backups.read(&sealed.name, None),
+            Err(BackupError::PassphraseRequired)
+        ));
+        assert!(matches!(
+            backups.read(&sealed.name, Some("falsch")),
+            Err(BackupError::Decrypt)
+        ));
+        assert_eq!(
+            backups.read(&sealed.name, Some("geheim")).unwrap().1,
+            bundle()
+        );
+
+        let names: Vec<_> = backups
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, [plain.name.clone(), sealed.name.clone()]);
+    }
+
+    #[test]
+    fn damaged_newer_or_foreign_archives_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = BackupDir::new(dir.path());
+        let (info, mut manifest) = backups
+            .write(&bundle(), "0.1.0", BTreeMap::new(), None)
+            .unwrap();
+        let path = dir.path().join(&info.name);
+
+        manifest.format = FORMAT_VERSION + 1;
+        fs::write(&path, pack(&manifest, &bundle()).unwrap()).unwrap();
+        assert!(matches!(
+            backups.read(&info.name, None),
+            Err(BackupError::UnsupportedFormat { .. })
+        ));
+
+        manifest.format = FORMAT_VERSION;
+        manifest
+            .entries
+            .get_mut("configs/flags.yaml")
+            .unwrap()
+            .sha256 = "0".repeat(64);
+        fs::write(&path, pack(&manifest, &bundle()).unwrap()).unwrap();
+        assert!(matches!(
+            backups.read(&info.name, None),
+            Err(BackupError::Checksum(name)) if name == "configs/flags.yaml"
+        ));
+
+        fs::write(&path, b"kein archiv").unwrap();
+        assert!(matches!(
+            backups.read(&info.name, None),
+            Err(BackupError::Format(_))
+        ));
+        for name in ["../etc/passwd", "notes.tar.zst", "hauski-x.tar.zst"] {
+            assert!(matches!(
+                backups.read(name, None),
+                Err(BackupError::InvalidName(_))
+            ));
+        }
+        assert!(matches!(
+            backups.get("hauski-20260101T000000000Z.tar.zst"),
+            Err(BackupError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn files_outside_backup_dirs_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("umzug").join("backup.tar.zst");
+        let counts = BTreeMap::from([("documents".to_string(), 0)]);
+        let manifest = write_file(&path, &bundle(), "0.1.0", counts, Some("geheim")).unwrap();
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+        assert!(matches!(
+            read_file(&path, None),
+            Err(BackupError::PassphraseRequired)
+        ));
+        let (read, contents) = read_file(&path, Some("geheim")).unwrap();
+        assert_eq!(read, manifest);
+        assert_eq!(contents, bundle());
+
+        // Manifests of earlier `hauski export` archives carried counts as fields.
+        let legacy = json!({
+            "format": 1,
+            "hauski_version": "0.1.0",
+            "created_at": "2026-01-01T00:00:00Z",
+            "documents": 3,
+            "memory_items": 0,
+            "entries": {},
+        });
+        let legacy: Manifest = serde_json::from_value(legacy).unwrap();
+        assert!(legacy.counts.is_empty());
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "hauski-20260101T000000000Z.tar.zst",
+            "hauski-20260102T000000000Z.tar.zst.enc",
+            "hauski-20260103T000000000Z.tar.zst",
+            "memory-20260101T000000Z.db",
+            ".hauski-20260104T000000000Z.tar.zst.partial",
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let backups = BackupDir::new(dir.path());
+        assert_eq!(
+            backups.prune(2).unwrap(),
+            ["hauski-20260101T000000000Z.tar.zst"]
+        );
+        assert_eq!(backups.list().unwrap().len(), 2);
+        assert!(backups.prune(0).unwrap().len() == 1);
+        assert!(dir.path().join("memory-20260101T000000Z.db").exists());
+        assert!(dir
+            .path()
+            .join(".hauski-20260104T000000000Z.tar.zst.partial")
+            .exists());
+    }
+}
@@ -0,0 +1,419 @@
+//! Chronik: interner Ereignisbus für HausKI.
+//!
+//! Alles, was im System passiert (Index-Mutationen, Policy-Entscheidungen,
+//! System-Signale, Job-Ereignisse), läuft als [`Envelope`] durch einen
+//! gemeinsamen [`Bus`]:
+//!
+//! - `id` (ULID, zeitlich sortierbar), `occurred_at`, `source` (z. B. `indexd`,
+//!   `jobs`) und `kind` (z. B. `index.upserted`, `job.finished`) plus `payload`.
+//! - In-Process-Abonnenten erhalten Ereignisse über einen Broadcast-Kanal
+//!   ([`Bus::subscribe`]); wer zu langsam liest, verpasst Ereignisse (gezählt in
+//!   `chronik_lagged_total`), blockiert aber nie den Publisher.
+//! - Die letzten `capacity` Ereignisse bleiben im Speicher ([`Bus::recent`]).
+//! - Optional schreibt ein [`EventSink`] jedes Ereignis dauerhaft weg, z. B.
+//!   [`JsonlSink`] als Monatsdateien `<dir>/YYYY-MM.jsonl`.
+//!
+//! Typisierte Ereignisse implementieren [`ChronikEvent`] und werden mit
+//! [`Envelope::from_event`] verpackt bzw. mit [`Envelope::decode`] gelesen.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet, LabelSetEncoder},
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// A single event on the bus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Envelope {
+    /// Unique id (ULID); sorts by creation time.
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Emitting component, e.g. `indexd` or `jobs`.
+    pub source: String,
+    /// Dotted event type, e.g. `index.upserted`.
+    pub kind: String,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+}
+
+/// A typed event with a fixed `kind`.
+pub trait ChronikEvent: Serialize {
+    const KIND: &'static str;
+}
+
+impl Envelope {
+    pub fn new(source: impl Into<String>, kind: impl Into<String>, payload: Value) -> Self {
+        Self {
+            id: ulid::Ulid::new().to_string(),
+            occurred_at: Utc::now(),
+            source: source.into(),
+            kind: kind.into(),
+            payload,
+        }
+    }
+
+    pub fn from_event<E: ChronikEvent>(
+        source: impl Into<String>,
+        event: &E,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(source, E::KIND, serde_json::to_value(event)?))
+    }
+
+    /// The typed payload, or `None` if `kind` differs or the payload does not fit.
+    pub fn decode<E: ChronikEvent + DeserializeOwned>(&self) -> Option<E> {
+        if self.kind != E::KIND {
+            return None;
+        }
+        serde_json::from_value(self.payload.clone()).ok()
+    }
+}
+
+/// Selects events by kind and source.
+///
+/// A kind entry matches itself and everything below it: `job` matches
+/// `job.started` and `job.finished`, but not `jobs.x`. No entries match all kinds.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub kinds: Vec<String>,
+    pub source: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, envelope: &Envelope) -> bool {
+        let kind_ok = self.kinds.is_empty()
+            || self.kinds.iter().any(|wanted| {
+                envelope.kind == *wanted
+                    || envelope
+                        .kind
+                        .strip_prefix(wanted.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            });
+        kind_ok
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|source| *source == envelope.source)
+    }
+}
+
+/// Durable storage for published events.
+pub trait EventSink: Send + Sync {
+    fn persist(&self, envelope: &Envelope);
+}
+
+/// Appends every event as one JSON line to `<dir>/YYYY-MM.jsonl` (UTC month of
+/// `occurred_at`). Write errors are logged and do not stop the bus.
+pub struct JsonlSink {
+    dir: PathBuf,
+    current: Mutex<Option<(String, File)>>,
+}
+
+impl JsonlSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            current: Mutex::new(None),
+        }
+    }
+
+    fn append(&self, envelope: &Envelope) -> std::io::Result<()> {
+        let line = serde_json::to_string(envelope)?;
+        let month = envelope.occurred_at.format("%Y-%m").to_string();
+        let mut current = self
+            .current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.as_ref().is_none_or(|(open, _)| *open != month) {
+            fs::create_dir_all(&self.dir)?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.dir.join(format!("{month}.jsonl")))?;
+            *current = Some((month, file));
+        }
+        let (_, file) = current.as_mut().expect("file opened above");
+        writeln!(file, "{line}")
+    }
+}
+
+impl EventSink for JsonlSink {
+    fn persist(&self, envelope: &Envelope) {
+        if let Err(err) = self.append(envelope) {
+            tracing::warn!(dir = %self.dir.display(), error = %err, "chronik: writing event failed");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EventLabels {
+    source: String,
+    kind: String,
+}
+
+impl EncodeLabelSet for EventLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("source", self.source.as_str()).encode(encoder.encode_label())?;
+        ("kind", self.kind.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+/// In-process publish/subscribe bus with a bounded history.
+pub struct Bus {
+    sender: broadcast::Sender<Arc<Envelope>>,
+    recent: Mutex<VecDeque<Arc<Envelope>>>,
+    capacity: usize,
+    sink: Option<Arc<dyn EventSink>>,
+    published: Family<EventLabels, Counter>,
+    lagged: Counter,
+}
+
+impl Bus {
+    /// A bus keeping the last `capacity` events (at least one); subscribers
+    /// may fall behind by as many events before they miss some.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            recent: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            sink: None,
+            published: Family::default(),
+            lagged: Counter::default(),
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "chronik_events",
+            "Total number of events published on the chronik bus by source and kind",
+            self.published.clone(),
+        );
+        registry.register(
+            "chronik_lagged",
+            "Total number of events missed by slow chronik subscribers",
+            self.lagged.clone(),
+        );
+    }
+
+    /// Publishes an event: persists it (if a sink is set), keeps it in the
+    /// history and hands it to all current subscribers.
+    pub fn publish(&self, envelope: Envelope) -> Arc<Envelope> {
+        let envelope = Arc::new(envelope);
+        if let Some(sink) = &self.sink {
+            sink.persist(&envelope);
+        }
+        self.published
+            .get_or_create(&EventLabels {
+                source: envelope.source.clone(),
+                kind: envelope.kind.clone(),
+            })
+            .inc();
+        {
+            let mut recent = self
+                .recent
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if recent.len() == self.capacity {
+                recent.pop_front();
+            }
+            recent.push_back(envelope.clone());
+        }
+        // No receivers is not an error.
+        let _ = self.sender.send(envelope.clone());
+        envelope
+    }
+
+    pub fn emit(&self, source: &str, kind: &str, payload: Value) -> Arc<Envelope> {
+        self.publish(Envelope::new(source, kind, payload))
+    }
+
+    /// Wraps and publishes a typed event; serialization errors are logged.
+    pub fn publish_event<E: ChronikEvent>(&self, source: &str, event: &E) -> Option<Arc<Envelope>> {
+        match Envelope::from_event(source, event) {
+            Ok(envelope) => Some(self.publish(envelope)),
+            Err(err) => {
+                tracing::warn!(kind = E::KIND, error = %err, "chronik: event serialization failed");
+                None
+            }
+        }
+    }
+
+    /// Events published from now on that match `filter`.
+    pub fn subscribe(&self, filter: EventFilter) -> Subscriber {
+        Subscriber {
+            receiver: self.sender.subscribe(),
+            filter,
+            lagged: self.lagged.clone(),
+        }
+    }
+
+    /// Up to `limit` of the most recent matching events, oldest first.
+    pub fn recent(&self, filter: &EventFilter, limit: usize) -> Vec<Arc<Envelope>> {
+        let recent = self
+            .recent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut matching: Vec<_> = recent
+            .iter()
+            .rev()
+            .filter(|envelope| filter.matches(envelope))
+            .take(limit)
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+}
+
+pub struct Subscriber {
+    receiver: broadcast::Receiver<Arc<Envelope>>,
+    filter: EventFilter,
+    lagged: Counter,
+}
+
+impl Subscriber {
+    /// Next matching event; `None` once the bus is gone. Events missed because
+    /// the subscriber fell behind are skipped.
+    pub async fn recv(&mut self) -> Option<Arc<Envelope>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(envelope) if self.filter.matches(&envelope) => return Some(envelope),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    self.lagged.inc_by(missed);
+                    tracing::debug!(missed, "chronik subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct JobFinished {
+        id: String,
+        ok: bool,
+    }
+
+    impl ChronikEvent for JobFinished {
+        const KIND: &'static str = "job.finished";
+    }
+
+    fn kinds(kinds: &[&str]) -> EventFilter {
+        EventFilter {
+            kinds: kinds.iter().map(|k| k.to_string()).collect(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn typed_events_round_trip() {
+        let event = JobFinished {
+            id: "j1".into(),
+            ok: true,
+        };
+        let envelope = Envelope::from_event("jobs", &event).unwrap();
+        assert_eq!(envelope.kind, "job.finished");
+        assert_eq!(envelope.decode::<JobFinished>(), Some(event));
+
+        let other = Envelope::new("jobs", "job.started", json!({"id": "j1", "ok": true}));
+        assert_eq!(other.decode::<JobFinished>(), None);
+    }
+
+    #[test]
+    fn filters_match_kind_prefixes_and_source() {
+        let envelope = Envelope::new("jobs", "job.finished", json!({}));
+        assert!(EventFilter::default().matches(&envelope));
+        assert!(kinds(&["job"]).matches(&envelope));
+        assert!(kinds(&["index", "job.finished"]).matches(&envelope));
+        assert!(!kinds(&["job.started"]).matches(&envelope));
+        assert!(!kinds(&["jo"]).matches(&envelope));
+        let by_source = EventFilter {
+            kinds: Vec::new(),
+            source: Some("indexd".into()),
+        };
+        assert!(!by_source.matches(&envelope));
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_matching_events_and_history_is_bounded() {
+        let bus = Bus::new(2);
+        let mut jobs = bus.subscribe(kinds(&["job"]));
+
+        bus.emit("indexd", "index.upserted", json!({"doc_id": "a"}));
+        bus.emit("jobs", "job.started", json!({"id": "j1"}));
+        bus.emit("jobs", "job.finished", json!({"id": "j1"}));
+
+        assert_eq!(jobs.recv().await.unwrap().kind, "job.started");
+        assert_eq!(jobs.recv().await.unwrap().kind, "job.finished");
+
+        let recent = bus.recent(&EventFilter::default(), 10);
+        let recent: Vec<_> = recent.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(recent, ["job.started", "job.finished"]);
+        assert_eq!(
+            bus.recent(&EventFilter::default(), 1)[0].kind,
+            "job.finished"
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_skip_missed_events() {
+        let bus = Bus::new(2);
+        let mut slow = bus.subscribe(EventFilter::default());
+        for i in 0..5 {
+            bus.emit("test", "tick", json!(i));
+        }
+        assert_eq!(slow.recv().await.unwrap().payload, json!(3));
+        assert_eq!(bus.lagged.get(), 3);
+    }
+
+    #[test]
+    fn jsonl_sink_appends_monthly_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let bus = Bus::new(8).with_sink(Arc::new(JsonlSink::new(dir.path())));
+        let first = bus.emit("jobs", "job.started", json!({"id": "j1"}));
+        bus.emit("jobs", "job.finished", json!({"id": "j1"}));
+
+        let file = dir
+            .path()
+            .join(format!("{}.jsonl", first.occurred_at.format("%Y-%m")));
+        let lines: Vec<Envelope> = fs::read_to_string(file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], *first);
+        assert_eq!(lines[1].kind, "job.finished");
+    }
+}
@@ -0,0 +1,151 @@
+//! `hauski ask`: Frage an `POST /ask` des laufenden Cores.
+//!
+//! Liefert die besten Treffer und mit `--answer` eine extraktive Antwort samt
+//! Belegstellen. Ziel und Token wie bei allen Remote-Kommandos (`--host`,
+//! `HAUSKI_TOKEN`).
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::{index::Backend, print_table, OutputArgs};
+
+const SNIPPET_CHARS: usize = 80;
+
+#[derive(Args, Debug)]
+pub struct AskArgs {
+    /// Frage (mehrere Wörter werden zusammengefügt)
+    #[arg(required = true)]
+    pub query: Vec<String>,
+    /// Anzahl Treffer
+    #[arg(short, long, default_value_t = 5)]
+    pub k: usize,
+    /// Namespace im Index
+    #[arg(long, default_value = "default")]
+    pub namespace: String,
+    /// Extraktive Antwort aus den besten Treffern zusammenstellen
+    #[arg(long, default_value_t = false)]
+    pub answer: bool,
+    /// Gesprächs-ID für Rückfragen, die sich auf frühere Fragen beziehen
+    #[arg(long)]
+    pub session: Option<String>,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+fn request_body(args: &AskArgs) -> Value {
+    let mut body = json!({
+        "query": args.query.join(" "),
+        "k": args.k,
+        "namespace": args.namespace,
+    });
+    if args.answer {
+        body["answer"] = json!({ "mode": "extractive" });
+    }
+    if let Some(session) = &args.session {
+        body["session_id"] = json!(session);
+    }
+    body
+}
+
+pub fn run(args: AskArgs) -> Result<()> {
+    if args.query.join(" ").trim().is_empty() {
+        bail!("leere Frage");
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    let response =
+        runtime.block_on(backend.call(Method::POST, "/ask", Some(request_body(&args))))?;
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    } else {
+        print_response(&response);
+    }
+    Ok(())
+}
+
+fn print_response(response: &Value) {
+    if let Some(rewritten) = response["rewritten_query"].as_str() {
+        println!("Gesucht nach: {rewritten}");
+    }
+    if let Some(answer) = response["answer"].as_str() {
+        println!("{answer}\n");
+        let citations = response["citations"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for (idx, citation) in citations.iter().enumerate() {
+            println!(
+                "  [{}] {} {}",
+                idx + 1,
+                citation["doc_id"].as_str().unwrap_or_default(),
+                citation["chunk_id"].as_str().unwrap_or_default()
+            );
+        }
+        if !citations.is_empty() {
+            println!();
+        }
+    }
+
+    let hits = response["hits"].as_array().cloned().unwrap_or_default();
+    if hits.is_empty() {
+        println!("Keine Treffer.");
+        return;
+    }
+    let rows = hits
+        .iter()
+        .map(|hit| {
+            let text = hit["snippet"]
+                .as_str()
+                .unwrap_or_default()
+                .replace('\n', " ");
+            let mut snippet: String = text.chars().take(SNIPPET_CHARS).collect();
+            if text.chars().count() > SNIPPET_CHARS {
+                snippet.push('…');
+            }
+            [
+                format!("{:.3}", hit["score"].as_f64().unwrap_or_default()),
+                hit["doc_id"].as_str().unwrap_or_default().to_string(),
+                snippet,
+            ]
+        })
+        .collect();
+    print_table(["Score", "Dokument", "Text"], rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_carries_answer_mode_and_session() {
+        let args = AskArgs {
+            query: vec!["wo".into(), "ist".into(), "das".into(), "runbook".into()],
+            k: 3,
+            namespace: "ops".into(),
+            answer: true,
+            session: Some("cli-1".into()),
+            base_url: None,
+            output: OutputArgs::default(),
+        };
+        assert_eq!(
+            request_body(&args),
+            json!({
+                "query": "wo ist das runbook",
+                "k": 3,
+                "namespace": "ops",
+                "answer": {"mode": "extractive"},
+                "session_id": "cli-1"
+            })
+        );
+    }
+}
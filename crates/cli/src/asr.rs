@@ -0,0 +1,251 @@
+//! `hauski asr transcribe`: Audiodateien lokal mit whisper.cpp transkribieren.
+//!
+//! Das Modell kommt aus `configs/models.yml` (`--model <id>`, Default: der erste
+//! Eintrag, dessen ID mit `whisper` beginnt) oder direkt als Pfad zu einer
+//! ggml-Datei. Aufruf von whisper.cpp, Umwandlung per `ffmpeg` und VAD
+//! übernimmt `hauski-asr`; das Kommando schreibt die Segmente als Text, SRT,
+//! VTT oder JSON.
+//!
+//! Mit `--reference <datei>` wird die Wortfehlerrate (WER) gegen ein
+//! Referenztranskript berechnet und gegen `asr.wer_max_pct` aus
+//! `policies/limits.yaml` geprüft; liegt sie darüber, endet das Kommando mit
+//! Fehler.
+//!
+//! Konfiguration: `HAUSKI_WHISPER_BIN`, `HAUSKI_FFMPEG_BIN` und `HAUSKI_ASR_*`,
+//! siehe `hauski-asr`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use hauski_asr::{
+    select_model, word_error_rate, AsrConfig, AsrError, Device, ModelRef, TranscribeOptions,
+    Transcript,
+};
+use hauski_core::{load_limits, load_models, ModelsFile};
+use serde_json::json;
+
+use crate::OutputArgs;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Txt,
+    Srt,
+    Vtt,
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct TranscribeArgs {
+    /// Audiodatei (WAV, sonst Umwandlung per ffmpeg)
+    pub input: PathBuf,
+    /// Modell-ID aus `models.yml` oder Pfad zu einem ggml-Modell
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Sprache (ISO-Code wie `de`) oder `auto` für Erkennung
+    #[arg(long, default_value = "auto")]
+    pub language: String,
+    /// Ausgabeformat
+    #[arg(long, value_enum, default_value_t = OutputFormat::Txt)]
+    pub format: OutputFormat,
+    /// Ausgabedatei (sonst stdout)
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+    /// Referenztranskript für die WER-Prüfung gegen `asr.wer_max_pct`
+    #[arg(long)]
+    pub reference: Option<PathBuf>,
+    /// Threads für whisper.cpp (Default: `HAUSKI_ASR_THREADS`, sonst whisper.cpp)
+    #[arg(long)]
+    pub threads: Option<usize>,
+    /// Gerät: `cpu` schaltet die GPU ab (Default: `HAUSKI_ASR_DEVICE`)
+    #[arg(long, value_parser = parse_device)]
+    pub device: Option<Device>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub fn transcribe(args: TranscribeArgs) -> Result<()> {
+    let model = resolve_model(args.model.as_deref())?;
+    let config = AsrConfig::from_env();
+    let options = TranscribeOptions {
+        language: Some(args.language.clone()),
+        device: args.device.unwrap_or(config.device),
+        threads: args.threads,
+    };
+    let transcript = config.transcribe(&args.input, &model, &options)?;
+
+    // With `--json` and no `--out`, the transcript itself is printed as JSON.
+    let format = if args.output.json && args.out.is_none() {
+        OutputFormat::Json
+    } else {
+        args.format
+    };
+    let rendered = render(&transcript, format)?;
+    match &args.out {
+        Some(path) => {
+            fs::write(path, rendered)
+                .with_context(|| format!("{} konnte nicht geschrieben werden", path.display()))?;
+            if args.output.json {
+                let result = json!({
+                    "out": path,
+                    "language": transcript.language,
+                    "segments": transcript.segments.len(),
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            }
+        }
+        None => print!("{rendered}"),
+    }
+
+    if let Some(reference) = &args.reference {
+        let reference = fs::read_to_string(reference)
+            .with_context(|| format!("{} konnte nicht gelesen werden", reference.display()))?;
+        let limits_path =
+            env::var("HAUSKI_LIMITS").unwrap_or_else(|_| "./policies/limits.yaml".into());
+        let max_pct = load_limits(limits_path)?.asr.wer_max_pct as f64;
+        let wer_pct = word_error_rate(&reference, &transcript.text()) * 100.0;
+        eprintln!("WER {wer_pct:.1} % (Grenze {max_pct} %)");
+        if wer_pct > max_pct {
+            bail!("WER {wer_pct:.1} % überschreitet asr.wer_max_pct {max_pct} %");
+        }
+    }
+    Ok(())
+}
+
+/// The ggml model for `model` (id from `models.yml` or a file path).
+fn resolve_model(model: Option<&str>) -> Result<ModelRef> {
+    if let Some(path) = model.map(Path::new).filter(|path| path.is_file()) {
+        return Ok(ModelRef {
+            id: path.display().to_string(),
+            path: path.to_path_buf(),
+            vram_min_gb: None,
+        });
+    }
+    let models_path = env::var("HAUSKI_MODELS").unwrap_or_else(|_| "./configs/models.yml".into());
+    let models = load_models(&models_path)?;
+    let model = select_model(&model_refs(&models), model).map_err(|err| match err {
+        AsrError::NoModel => anyhow!("kein whisper-Modell konfiguriert (--model angeben)"),
+        err => anyhow!("{err}"),
+    })?;
+    if !model.is_available() {
+        bail!(
+            "Modelldatei {} fehlt (Eintrag in {models_path})",
+            model.path.display()
+        );
+    }
+    Ok(model)
+}
+
+fn model_refs(models: &ModelsFile) -> Vec<ModelRef> {
+    models
+        .models
+        .iter()
+        .map(|entry| ModelRef::new(&entry.id, &entry.path, entry.vram_min_gb))
+        .collect()
+}
+
+fn render(transcript: &Transcript, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Txt => format!("{}\n", transcript.text()),
+        OutputFormat::Srt => transcript
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    timestamp(segment.start_ms, ','),
+                    timestamp(segment.end_ms, ','),
+                    segment.text
+                )
+            })
+            .collect(),
+        OutputFormat::Vtt => {
+            let cues: String = transcript
+                .segments
+                .iter()
+                .map(|segment| {
+                    format!(
+                        "{} --> {}\n{}\n\n",
+                        timestamp(segment.start_ms, '.'),
+                        timestamp(segment.end_ms, '.'),
+                        segment.text
+                    )
+                })
+                .collect();
+            format!("WEBVTT\n\n{cues}")
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(transcript)?;
+            value["text"] = json!(transcript.text());
+            format!("{}\n", serde_json::to_string_pretty(&value)?)
+        }
+    })
+}
+
+fn parse_device(value: &str) -> Result<Device, String> {
+    value.parse()
+}
+
+/// `HH:MM:SS<sep>mmm` as used by SRT (`,`) and VTT (`.`).
+fn timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        ms % 1_000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hauski_asr::parse_whisper_json;
+    use serde_json::Value;
+
+    const WHISPER_OUTPUT: &str = r#"{
+        "result": {"language": "de"},
+        "transcription": [
+            {"timestamps": {"from": "00:00:00,000", "to": "00:00:02,500"},
+             "offsets": {"from": 0, "to": 2500}, "text": " Hallo Welt."},
+            {"offsets": {"from": 2500, "to": 3723010}, "text": " Wie geht's?"},
+            {"offsets": {"from": 3723010, "to": 3723020}, "text": " "}
+        ]
+    }"#;
+
+    #[test]
+    fn whisper_json_renders_as_subtitles() {
+        let transcript = parse_whisper_json(WHISPER_OUTPUT).unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.text(), "Hallo Welt. Wie geht's?");
+
+        let srt = render(&transcript, OutputFormat::Srt).unwrap();
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:02,500\nHallo Welt.\n\n2\n"));
+        assert!(srt.contains("00:00:02,500 --> 01:02:03,010\n"));
+
+        let vtt = render(&transcript, OutputFormat::Vtt).unwrap();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHallo Welt.\n"));
+
+        let json: Value =
+            serde_json::from_str(&render(&transcript, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["segments"][1]["start_ms"], 2500);
+        assert_eq!(json["text"], "Hallo Welt. Wie geht's?");
+    }
+
+    #[test]
+    fn default_model_is_first_whisper_entry() {
+        let models: ModelsFile = serde_yaml_ng::from_str(
+            "models:\n  - id: llama\n    path: /m/llama.gguf\n  - id: whisper-small\n    path: /m/small.bin\n",
+        )
+        .unwrap();
+        let model = select_model(&model_refs(&models), None).unwrap();
+        assert_eq!(model.id, "whisper-small");
+        assert_eq!(model.path, PathBuf::from("/m/small.bin"));
+    }
+}
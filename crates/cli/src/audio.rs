@@ -0,0 +1,142 @@
+//! `hauski audio …`: Audio-Profile über PipeWire/WirePlumber anwenden.
+//!
+//! Dünne Fassade über `hauski-audio`: Profile stehen in `audio/profiles.yaml`
+//! (`HAUSKI_AUDIO_PROFILES`), der Zustand kommt aus `pw-dump`, angewendet wird
+//! über `wpctl` und `pw-metadata`. Scheitert ein Befehl mittendrin, stellt das
+//! Crate den vorherigen Zustand wieder her.
+//!
+//! Zum Schluss wird der resultierende Knoten-Graph ausgegeben (Tabelle oder
+//! `--json`); `--dry-run` zeigt nur die Befehle.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use clap::Subcommand;
+use hauski_audio::{plan, AudioConfig, Graph};
+use serde_json::Value;
+
+use crate::{print_table, say};
+
+/// Node classes shown in the graph report.
+const AUDIO_CLASSES: &[&str] = &["Audio/Sink", "Audio/Source", "Audio/Duplex"];
+
+#[derive(Subcommand, Debug)]
+pub enum AudioCmd {
+    /// Audio-Profil anwenden und den resultierenden Graph zeigen
+    ProfileSet {
+        profile: String,
+        /// Nur die Befehle zeigen
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Konfigurierte Profile auflisten
+    Profiles,
+    /// Aktuellen Knoten-Graph zeigen
+    Graph,
+}
+
+pub fn run(cmd: AudioCmd, json: bool) -> Result<()> {
+    let config = AudioConfig::from_env();
+    match cmd {
+        AudioCmd::Profiles if json => {
+            let profiles: BTreeMap<String, Value> = config
+                .load_profiles()?
+                .profiles
+                .into_iter()
+                .map(|(name, profile)| {
+                    let summary = serde_json::json!({
+                        "description": profile.description,
+                        "sink": profile.sink,
+                        "source": profile.source,
+                    });
+                    (name, summary)
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&profiles)?);
+        }
+        AudioCmd::Profiles => {
+            let rows = config
+                .load_profiles()?
+                .profiles
+                .into_iter()
+                .map(|(name, profile)| {
+                    [
+                        name,
+                        profile.sink.unwrap_or_else(|| "-".into()),
+                        profile.source.unwrap_or_else(|| "-".into()),
+                        profile.description.unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            print_table(["Profil", "Ausgang", "Eingang", "Beschreibung"], rows);
+        }
+        AudioCmd::Graph => print_graph(&config.read_graph()?, json)?,
+        AudioCmd::ProfileSet { profile, dry_run } => {
+            let profiles = config.load_profiles()?;
+            let selected = profiles.get(&profile)?;
+            if dry_run {
+                let commands: Vec<String> = plan(selected, &config.read_graph()?)?
+                    .iter()
+                    .map(|action| config.argv(action).join(" "))
+                    .collect();
+                for command in &commands {
+                    say(json, command);
+                }
+                if json {
+                    let plan = serde_json::json!({"profile": profile, "commands": commands});
+                    println!("{}", serde_json::to_string_pretty(&plan)?);
+                }
+                return Ok(());
+            }
+
+            config.switch(selected)?;
+            if !json {
+                println!("Profil '{profile}' angewendet.");
+            }
+            print_graph(&config.read_graph()?, json)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_graph(graph: &Graph, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(graph)?);
+        return Ok(());
+    }
+    let rows = graph
+        .nodes
+        .iter()
+        .filter(|node| AUDIO_CLASSES.contains(&node.class.as_str()))
+        .map(|node| {
+            let is_default = [&graph.default_sink, &graph.default_source]
+                .iter()
+                .any(|default| default.as_deref() == Some(node.name.as_str()));
+            let volume = match (node.volume, node.mute) {
+                (_, Some(true)) => "stumm".to_string(),
+                (Some(volume), _) => format!("{:.0} %", volume * 100.0),
+                (None, _) => "-".to_string(),
+            };
+            [
+                node.id.to_string(),
+                node.class.clone(),
+                node.name.clone(),
+                node.description.clone().unwrap_or_default(),
+                volume,
+                if is_default { "*" } else { "" }.to_string(),
+            ]
+        })
+        .collect();
+    print_table(
+        [
+            "ID",
+            "Klasse",
+            "Name",
+            "Beschreibung",
+            "Lautstärke",
+            "Default",
+        ],
+        rows,
+    );
+    Ok(())
+}
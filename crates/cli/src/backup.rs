@@ -0,0 +1,409 @@
+//! `hauski export` / `hauski import`: das ganze Gedächtnis in einem Archiv.
+//!
+//! Das Archiv hat das Format von `hauski-backup` (wie `/backup`) und enthält
+//!
+//! - `index/snapshot.json` – alle Dokumente aus `GET /index/snapshot`,
+//! - `memory/items.json` – alle Einträge aus `/memory/list` (mit Werten),
+//! - `configs/…`, `policies/…` – die Konfigurationsdateien aus
+//!   [`CONFIG_FILES`], soweit vorhanden.
+//!
+//! Der Import prüft Formatversion und Prüfsummen, bevor irgendetwas
+//! geschrieben wird; verschlüsselte Archive aus `/backup` öffnet er mit
+//! `HAUSKI_BACKUP_PASSPHRASE`. Konfigurationen landen an den Pfaden aus
+//! `HAUSKI_CONFIG`, `HAUSKI_MODELS`, `HAUSKI_FLAGS` usw. (bzw. deren Defaults);
+//! vorhandene, abweichende Dateien bleiben ohne `--overwrite-configs` stehen.
+//! Memory-DB und Policy-Zustand aus `/backup` spielt nur
+//! `POST /backup/{name}/restore` ein.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use hauski_backup::{
+    config_path, read_file, write_file, Bundle, CONFIG_FILES, INDEX_ENTRY, MEMORY_DB_ENTRY,
+    MEMORY_ITEMS_ENTRY, POLICY_ENTRY,
+};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::{index::Backend, memory::Client, say, OutputArgs};
+
+/// Page size for `/memory/list` (server maximum).
+const MEMORY_PAGE: usize = 500;
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Zieldatei, z. B. backup.tar.zst
+    #[arg(long)]
+    pub out: PathBuf,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Bearer-Token für `/memory/*` (Default: `HAUSKI_MEMORY_TOKEN`)
+    #[arg(long)]
+    pub token: Option<String>,
+    /// Arbeitsgedächtnis nicht exportieren (kein Token nötig)
+    #[arg(long, default_value_t = false)]
+    pub no_memory: bool,
+    /// Pfad zur hauski.yml (Default: `HAUSKI_CONFIG` bzw. ./configs/hauski.yml)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Vorhandene Zieldatei überschreiben
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Archiv aus `hauski export`
+    pub archive: PathBuf,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Bearer-Token für `/memory/*` (Default: `HAUSKI_MEMORY_TOKEN`)
+    #[arg(long)]
+    pub token: Option<String>,
+    /// Pfad, an den die hauski.yml geschrieben wird
+    /// (Default: `HAUSKI_CONFIG` bzw. ./configs/hauski.yml)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Abweichende vorhandene Konfigurationsdateien überschreiben
+    #[arg(long, default_value_t = false)]
+    pub overwrite_configs: bool,
+    /// Nur Manifest prüfen und Inhalt anzeigen
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")
+}
+
+/// Path of a carried config file on this machine; `--config` replaces the
+/// one for `hauski.yml`.
+fn config_target(name: &str, env_var: &str, default: &str, hauski_yml: Option<&Path>) -> PathBuf {
+    match hauski_yml {
+        Some(path) if name == "configs/hauski.yml" => path.to_path_buf(),
+        _ => config_path(env_var, default),
+    }
+}
+
+pub fn export(args: ExportArgs) -> Result<()> {
+    if args.out.exists() && !args.force {
+        bail!(
+            "{} existiert bereits (--force zum Überschreiben)",
+            args.out.display()
+        );
+    }
+    let runtime = runtime()?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    let mut bundle = Bundle::default();
+    let mut counts = BTreeMap::new();
+    let documents = runtime.block_on(export_index(&backend, &mut bundle))?;
+    counts.insert("documents".to_string(), documents as u64);
+    if !args.no_memory {
+        let client = Client::new(args.base_url.clone(), args.token.clone())?;
+        let items = runtime.block_on(export_memory(&client, &mut bundle))?;
+        counts.insert("memory_items".to_string(), items as u64);
+    }
+    let mut configs = 0;
+    for (name, env_var, default) in CONFIG_FILES {
+        let path = config_target(name, env_var, default, args.config.as_deref());
+        match fs::read(&path) {
+            Ok(bytes) => {
+                bundle.insert(name, bytes);
+                configs += 1;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("Hinweis: {} fehlt – nicht im Archiv", path.display());
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("{} nicht lesbar", path.display()))
+            }
+        }
+    }
+
+    counts.insert("configs".to_string(), configs);
+    let manifest = write_file(&args.out, &bundle, env!("CARGO_PKG_VERSION"), counts, None)
+        .with_context(|| format!("{} nicht beschreibbar", args.out.display()))?;
+    if args.output.json {
+        let result = json!({"out": args.out, "manifest": manifest});
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!(
+            "{} geschrieben: {documents} Dokumente, {} Memory-Einträge, {configs} Konfigurationsdateien",
+            args.out.display(),
+            count(&manifest.counts, "memory_items"),
+        );
+    }
+    Ok(())
+}
+
+fn count(counts: &BTreeMap<String, u64>, key: &str) -> u64 {
+    counts.get(key).copied().unwrap_or(0)
+}
+
+pub fn import(args: ImportArgs) -> Result<()> {
+    let passphrase = env::var("HAUSKI_BACKUP_PASSPHRASE")
+        .ok()
+        .filter(|value| !value.is_empty());
+    let (manifest, bundle) = read_file(&args.archive, passphrase.as_deref())
+        .with_context(|| format!("{} ist kein gültiges HausKI-Archiv", args.archive.display()))?;
+    say(
+        args.output.json,
+        format!(
+            "Archiv von HausKI {} ({}): {} Dokumente, {} Memory-Einträge",
+            manifest.hauski_version,
+            manifest.created_at,
+            count(&manifest.counts, "documents"),
+            count(&manifest.counts, "memory_items"),
+        ),
+    );
+    if manifest.hauski_version != env!("CARGO_PKG_VERSION") {
+        eprintln!(
+            "Hinweis: Archiv stammt von HausKI {}, installiert ist {}",
+            manifest.hauski_version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+    if args.dry_run {
+        if args.output.json {
+            let result = json!({"dry_run": true, "manifest": manifest});
+            println!("{}", serde_json::to_string_pretty(&result)?);
+            return Ok(());
+        }
+        for (name, entry) in &manifest.entries {
+            println!("  {name} ({} Bytes)", entry.bytes);
+        }
+        println!("Dry run – nichts geschrieben.");
+        return Ok(());
+    }
+
+    for entry in [MEMORY_DB_ENTRY, POLICY_ENTRY] {
+        if bundle.get(entry).is_some() {
+            eprintln!(
+                "Hinweis: {entry} bleibt aus – nur POST /backup/{{name}}/restore spielt es ein"
+            );
+        }
+    }
+    let mut configs_written = Vec::new();
+    for (name, env_var, default) in CONFIG_FILES {
+        let Some(bytes) = bundle.get(name) else {
+            continue;
+        };
+        let path = config_target(name, env_var, default, args.config.as_deref());
+        if restore_config(&path, bytes, args.overwrite_configs)? {
+            say(args.output.json, format!("{} geschrieben.", path.display()));
+            configs_written.push(path);
+        }
+    }
+
+    let runtime = runtime()?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    let restored = runtime.block_on(import_index(&backend, &bundle))?;
+    say(
+        args.output.json,
+        format!("{restored} Dokumente in den Index übernommen."),
+    );
+
+    let items = bundle
+        .json::<Vec<Value>>(MEMORY_ITEMS_ENTRY)?
+        .unwrap_or_default();
+    if !items.is_empty() {
+        let client = Client::new(args.base_url.clone(), args.token.clone())?;
+        runtime.block_on(import_memory(&client, &items))?;
+        say(
+            args.output.json,
+            format!("{} Memory-Einträge gesetzt.", items.len()),
+        );
+    }
+    if args.output.json {
+        let result = json!({
+            "archive": args.archive,
+            "hauski_version": manifest.hauski_version,
+            "documents_restored": restored,
+            "memory_items_restored": items.len(),
+            "configs_written": configs_written,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// Writes `bytes` to `path` unless a different file is already there;
+/// returns whether the file was written.
+fn restore_config(path: &Path, bytes: &[u8], overwrite: bool) -> Result<bool> {
+    match fs::read(path) {
+        Ok(current) if current == bytes => return Ok(false),
+        Ok(_) if !overwrite => {
+            eprintln!(
+                "Hinweis: {} weicht ab und bleibt (--overwrite-configs zum Ersetzen)",
+                path.display()
+            );
+            return Ok(false);
+        }
+        _ => {}
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("{} konnte nicht angelegt werden", parent.display()))?;
+    }
+    fs::write(path, bytes)
+        .with_context(|| format!("{} konnte nicht geschrieben werden", path.display()))?;
+    Ok(true)
+}
+
+/// Adds the index snapshot to `bundle` and returns the document count.
+async fn export_index(backend: &Backend, bundle: &mut Bundle) -> Result<usize> {
+    let snapshot = backend.call(Method::GET, "/index/snapshot", None).await?;
+    let documents = snapshot["documents"].as_array().map_or(0, Vec::len);
+    bundle.insert(INDEX_ENTRY, serde_json::to_vec_pretty(&snapshot)?);
+    Ok(documents)
+}
+
+async fn import_index(backend: &Backend, bundle: &Bundle) -> Result<usize> {
+    let Some(snapshot) = bundle.json::<Value>(INDEX_ENTRY)? else {
+        return Ok(0);
+    };
+    let response = backend
+        .call(Method::POST, "/index/snapshot", Some(snapshot))
+        .await
+        .map_err(|e| anyhow!("Index-Snapshot wurde nicht übernommen: {e}"))?;
+    Ok(response["restored"].as_u64().unwrap_or(0) as usize)
+}
+
+/// Adds every memory entry (with value) to `bundle` and returns the count.
+async fn export_memory(client: &Client, bundle: &mut Bundle) -> Result<usize> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let response = client
+            .post(
+                "/memory/list",
+                json!({"cursor": cursor, "limit": MEMORY_PAGE, "include_values": true}),
+            )
+            .await?;
+        items.extend(response["items"].as_array().cloned().unwrap_or_default());
+        match response["next_cursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    let count = items.len();
+    bundle.insert(MEMORY_ITEMS_ENTRY, serde_json::to_vec_pretty(&items)?);
+    Ok(count)
+}
+
+async fn import_memory(client: &Client, items: &[Value]) -> Result<()> {
+    for item in items {
+        let key = item["key"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Memory-Eintrag ohne key: {item}"))?;
+        client
+            .post("/memory/set", memory_set_body(item, key))
+            .await
+            .map_err(|e| anyhow!("Memory-Eintrag '{key}' nicht gesetzt: {e}"))?;
+    }
+    Ok(())
+}
+
+/// `/memory/set` body restoring TTL and pin state as exported; entries
+/// without TTL clear it so the policy default does not kick in.
+fn memory_set_body(item: &Value, key: &str) -> Value {
+    let mut body = json!({
+        "key": key,
+        "value": item["value"].as_str().unwrap_or_default(),
+        "pinned": item["pinned"].as_bool().unwrap_or(false),
+    });
+    match item["ttl_sec"].as_i64() {
+        Some(ttl) => body["ttl_sec"] = json!(ttl),
+        None => body["clear_ttl"] = json!(true),
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_roundtrip_restores_index() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "Alpha Umzug").unwrap();
+        let archive = dir.path().join("backup.tar.zst");
+        runtime().unwrap().block_on(async {
+            let source = Backend::offline();
+            crate::index::upsert_paths(&source, &[dir.path().join("a.md")], "notes", "user")
+                .await
+                .unwrap();
+            let mut bundle = Bundle::default();
+            assert_eq!(export_index(&source, &mut bundle).await.unwrap(), 1);
+            bundle.insert("configs/flags.yaml", b"safe_mode: false\n".to_vec());
+            let counts = BTreeMap::from([("documents".to_string(), 1)]);
+            write_file(&archive, &bundle, "0.1.0", counts, None).unwrap();
+
+            let (manifest, bundle) = read_file(&archive, None).unwrap();
+            assert_eq!(manifest.format, hauski_backup::FORMAT_VERSION);
+            assert_eq!(count(&manifest.counts, "documents"), 1);
+            assert_eq!(
+                bundle.get("configs/flags.yaml"),
+                Some(&b"safe_mode: false\n"[..])
+            );
+
+            let target = Backend::offline();
+            assert_eq!(import_index(&target, &bundle).await.unwrap(), 1);
+            let stats = target
+                .call(Method::GET, "/index/stats", None)
+                .await
+                .unwrap();
+            assert_eq!(stats["total_documents"], 1);
+        });
+    }
+
+    #[test]
+    fn config_targets_follow_env_and_config_flag() {
+        let own = Path::new("/srv/hauski/hauski.yml");
+        assert_eq!(
+            config_target(
+                "configs/hauski.yml",
+                "HAUSKI_CONFIG",
+                "./configs/hauski.yml",
+                Some(own)
+            ),
+            own
+        );
+        assert_eq!(
+            config_target(
+                "policies/limits.yaml",
+                "HAUSKI_TEST_UNSET_LIMITS",
+                "./policies/limits.yaml",
+                Some(own)
+            ),
+            Path::new("./policies/limits.yaml")
+        );
+    }
+
+    #[test]
+    fn memory_set_body_keeps_ttl_and_pin_state() {
+        let item = json!({"key": "a", "value": "v", "ttl_sec": 60, "pinned": true});
+        assert_eq!(
+            memory_set_body(&item, "a"),
+            json!({"key": "a", "value": "v", "pinned": true, "ttl_sec": 60})
+        );
+        let item = json!({"key": "b", "value": "w", "ttl_sec": null, "pinned": false});
+        assert_eq!(memory_set_body(&item, "b")["clear_ttl"], true);
+    }
+}
@@ -0,0 +1,378 @@
+//! `hauski bench`: Last gegen einen laufenden Core und Abgleich mit den Budgets.
+//!
+//! Schickt je Ziel `--requests` Anfragen mit `--concurrency` parallelen
+//! Verbindungen (nach `--warmup` ungezählten Aufwärm-Requests) und misst die
+//! Latenzen bis zur vollständigen Antwort:
+//!
+//! - `search`: `POST /index/search` mit `k: 20` → Budget `latency.index_topk20_ms`
+//! - `ask`: `GET /ask` mit `k=20` → Budget `latency.index_topk20_ms`
+//! - `chat`: `POST /v1/chat` → Budget `latency.llm_p95_ms` (braucht einen Upstream)
+//!
+//! Bewertet wird wie in `/self/state` das p95 gegen `limits.yaml`. Exit-Code 1,
+//! wenn ein Ziel sein Budget reißt oder Requests fehlschlagen.
+
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, ValueEnum};
+use hauski_core::{load_limits, Limits};
+use serde::Serialize;
+use serde_json::json;
+use tokio::task::JoinSet;
+
+use crate::{remote, say, OutputArgs};
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Ziele (mehrfach oder kommagetrennt)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "search,ask")]
+    pub target: Vec<Target>,
+    /// Gemessene Requests je Ziel
+    #[arg(long, short = 'n', default_value_t = 100)]
+    pub requests: usize,
+    /// Gleichzeitige Requests
+    #[arg(long, short = 'c', default_value_t = 4)]
+    pub concurrency: usize,
+    /// Ungezählte Aufwärm-Requests je Ziel
+    #[arg(long, default_value_t = 5)]
+    pub warmup: usize,
+    /// Suchanfrage bzw. Chat-Prompt
+    #[arg(long, default_value = "Wie richte ich HausKI ein?")]
+    pub query: String,
+    /// Namespace für `search`/`ask`
+    #[arg(long, default_value = "default")]
+    pub namespace: String,
+    /// Modell für `chat` (Default: im Core konfiguriert)
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Budgets (Default: `HAUSKI_LIMITS` oder ./policies/limits.yaml)
+    #[arg(long)]
+    pub limits: Option<String>,
+    /// Timeout je Request in Sekunden
+    #[arg(long, default_value_t = 30)]
+    pub timeout_secs: u64,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Search,
+    Ask,
+    Chat,
+}
+
+impl Target {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Search => "/index/search",
+            Self::Ask => "/ask",
+            Self::Chat => "/v1/chat",
+        }
+    }
+
+    /// Budget name and value, mapped like the budgets in `/self/state`.
+    fn budget(self, limits: &Limits) -> (&'static str, u64) {
+        match self {
+            Self::Search | Self::Ask => ("index_topk20", limits.latency.index_topk20_ms),
+            Self::Chat => ("llm_p95", limits.latency.llm_p95_ms),
+        }
+    }
+}
+
+/// Everything a worker needs to send requests for one target.
+struct Load {
+    client: reqwest::Client,
+    base: String,
+    target: Target,
+    query: String,
+    namespace: String,
+    model: Option<String>,
+}
+
+impl Load {
+    async fn send(&self) -> Result<(), String> {
+        let url = format!("{}{}", self.base, self.target.path());
+        let request = match self.target {
+            Target::Search => self.client.post(&url).json(&json!({
+                "query": self.query,
+                "k": 20,
+                "namespace": self.namespace,
+            })),
+            Target::Ask => self.client.get(&url).query(&[
+                ("q", self.query.as_str()),
+                ("k", "20"),
+                ("ns", self.namespace.as_str()),
+            ]),
+            Target::Chat => self.client.post(&url).json(&json!({
+                "messages": [{"role": "user", "content": self.query}],
+                "model": self.model,
+            })),
+        };
+        let response = request.send().await.map_err(|err| err.to_string())?;
+        let status = response.status();
+        // Read the whole body: the budget covers the complete answer.
+        response.bytes().await.map_err(|err| err.to_string())?;
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {status}"))
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TargetReport {
+    target: Target,
+    path: &'static str,
+    requests: usize,
+    errors: usize,
+    /// Example error, if any request failed.
+    last_error: Option<String>,
+    p50_ms: Option<f64>,
+    p95_ms: Option<f64>,
+    p99_ms: Option<f64>,
+    max_ms: Option<f64>,
+    requests_per_sec: f64,
+    budget: &'static str,
+    budget_ms: u64,
+    within_budget: bool,
+}
+
+impl TargetReport {
+    fn passed(&self) -> bool {
+        self.within_budget && self.errors == 0
+    }
+}
+
+pub fn run(args: BenchArgs) -> Result<i32> {
+    let limits_path = args
+        .limits
+        .clone()
+        .or_else(|| env::var("HAUSKI_LIMITS").ok())
+        .unwrap_or_else(|| "./policies/limits.yaml".to_string());
+    let limits = load_limits(&limits_path)
+        .map_err(|e| anyhow!("Budgets aus {limits_path} nicht lesbar: {e}"))?;
+    let base = remote::base_url(args.base_url.clone())
+        .trim_end_matches('/')
+        .to_string();
+    let client = remote::client_builder()?
+        .timeout(Duration::from_secs(args.timeout_secs))
+        .build()?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+
+    let mut reports = Vec::with_capacity(args.target.len());
+    for &target in &args.target {
+        say(
+            args.output.json,
+            format!(
+                "{} {}: {} Requests, {} parallel …",
+                target.path(),
+                args.query,
+                args.requests,
+                args.concurrency
+            ),
+        );
+        let load = Arc::new(Load {
+            client: client.clone(),
+            base: base.clone(),
+            target,
+            query: args.query.clone(),
+            namespace: args.namespace.clone(),
+            model: args.model.clone(),
+        });
+        reports.push(runtime.block_on(measure(load, &args, &limits)));
+    }
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        print_reports(&reports);
+    }
+    Ok(if reports.iter().all(TargetReport::passed) {
+        0
+    } else {
+        1
+    })
+}
+
+async fn measure(load: Arc<Load>, args: &BenchArgs, limits: &Limits) -> TargetReport {
+    for _ in 0..args.warmup {
+        let _ = load.send().await;
+    }
+
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let mut workers = JoinSet::new();
+    for _ in 0..args.concurrency.clamp(1, args.requests.max(1)) {
+        let load = load.clone();
+        let next = next.clone();
+        let total = args.requests;
+        workers.spawn(async move {
+            let mut latencies = Vec::new();
+            let mut errors = Vec::new();
+            while next.fetch_add(1, Ordering::Relaxed) < total {
+                let sent = Instant::now();
+                match load.send().await {
+                    Ok(()) => latencies.push(sent.elapsed().as_secs_f64() * 1000.0),
+                    Err(err) => errors.push(err),
+                }
+            }
+            (latencies, errors)
+        });
+    }
+    let mut latencies = Vec::with_capacity(args.requests);
+    let mut errors = Vec::new();
+    while let Some(joined) = workers.join_next().await {
+        match joined {
+            Ok((worker_latencies, worker_errors)) => {
+                latencies.extend(worker_latencies);
+                errors.extend(worker_errors);
+            }
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    latencies.sort_by(f64::total_cmp);
+    let (budget, budget_ms) = load.target.budget(limits);
+    let p95_ms = percentile(&latencies, 0.95);
+    TargetReport {
+        target: load.target,
+        path: load.target.path(),
+        requests: args.requests,
+        errors: errors.len(),
+        last_error: errors.pop(),
+        p50_ms: percentile(&latencies, 0.50),
+        p95_ms,
+        p99_ms: percentile(&latencies, 0.99),
+        max_ms: latencies.last().copied(),
+        requests_per_sec: if elapsed > 0.0 {
+            latencies.len() as f64 / elapsed
+        } else {
+            0.0
+        },
+        budget,
+        budget_ms,
+        within_budget: p95_ms.is_some_and(|p95| p95 <= budget_ms as f64),
+    }
+}
+
+/// Nearest-rank percentile of ascending `sorted`, as in `/self/state`.
+fn percentile(sorted: &[f64], quantile: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((sorted.len() as f64) * quantile).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn print_reports(reports: &[TargetReport]) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "–".to_string(), |v| format!("{v:.1}"));
+    let rows = reports
+        .iter()
+        .map(|report| {
+            [
+                report.path.to_string(),
+                format!("{}/{}", report.requests - report.errors, report.requests),
+                ms(report.p50_ms),
+                ms(report.p95_ms),
+                ms(report.p99_ms),
+                format!("{:.1}", report.requests_per_sec),
+                format!("{} ({} ms)", report.budget, report.budget_ms),
+                if report.passed() { "PASS" } else { "FAIL" }.to_string(),
+            ]
+        })
+        .collect();
+    crate::print_table(
+        [
+            "Ziel", "OK", "p50 ms", "p95 ms", "p99 ms", "Req/s", "Budget", "Ergebnis",
+        ],
+        rows,
+    );
+    for report in reports {
+        if let Some(err) = &report.last_error {
+            println!("{}: {} Fehler, zuletzt: {err}", report.path, report.errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 0.50), Some(50.0));
+        assert_eq!(percentile(&sorted, 0.95), Some(95.0));
+        assert_eq!(percentile(&sorted, 0.99), Some(99.0));
+        assert_eq!(percentile(&[7.0], 0.99), Some(7.0));
+        assert_eq!(percentile(&[], 0.5), None);
+    }
+
+    #[test]
+    fn measures_search_against_the_index_budget() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let app = axum::Router::new()
+                .nest("/index", hauski_indexd::router())
+                .with_state(hauski_indexd::IndexState::new(
+                    60,
+                    Arc::new(|_, _, _, _| {}),
+                    None,
+                    None,
+                ));
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            let args = BenchArgs {
+                base_url: None,
+                target: vec![Target::Search],
+                requests: 20,
+                concurrency: 3,
+                warmup: 1,
+                query: "hallo".into(),
+                namespace: "default".into(),
+                model: None,
+                limits: None,
+                timeout_secs: 5,
+                output: OutputArgs::default(),
+            };
+            let load = Arc::new(Load {
+                client: reqwest::Client::new(),
+                base: format!("http://{addr}"),
+                target: Target::Search,
+                query: args.query.clone(),
+                namespace: args.namespace.clone(),
+                model: None,
+            });
+            let limits: Limits =
+                serde_yaml_ng::from_str("latency:\n  index_topk20_ms: 5000\n").unwrap();
+            let report = measure(load, &args, &limits).await;
+            assert_eq!(report.errors, 0, "{:?}", report.last_error);
+            assert_eq!(report.budget, "index_topk20");
+            assert!(report.p99_ms >= report.p50_ms);
+            assert!(report.passed());
+        });
+    }
+}
@@ -0,0 +1,444 @@
+//! `hauski chat`: interaktiver Chat mit dem laufenden Core.
+//!
+//! Im Terminal startet eine REPL (Zeilenbearbeitung und Verlauf über
+//! rustyline, Verlauf in `~/.hauski_chat_history`). Antworten kommen per
+//! `POST /v1/chat/stream` und werden Token für Token ausgegeben; mit
+//! `--no-stream` als Ganzes über `POST /v1/chat`.
+//!
+//! Der Gesprächsverlauf liegt auf dem Server (`session_id`, siehe
+//! `/v1/chat/sessions/{id}`): mit `--session <id>` lässt sich eine Sitzung
+//! später fortsetzen. Slash-Kommandos: `/model [name]`, `/clear`,
+//! `/save <datei>`, `/help`, `/exit`.
+//!
+//! Ist stdin kein Terminal (Pipe), wird der Prompt aus den Argumenten und
+//! stdin gebildet, einmal beantwortet und das Programm beendet, z. B.
+//! `git diff | hauski chat "Fasse die Änderungen zusammen"`.
+
+use std::{
+    fs,
+    io::{IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use rustyline::{error::ReadlineError, DefaultEditor};
+use serde_json::{json, Value};
+
+use crate::{remote, OutputArgs};
+
+const HISTORY_FILE: &str = "~/.hauski_chat_history";
+
+#[derive(Args, Debug)]
+pub struct ChatOptions {
+    /// Prompt für eine einzelne Frage (sonst REPL); wird mit stdin kombiniert
+    pub prompt: Vec<String>,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Sitzung fortsetzen bzw. unter dieser ID anlegen (Default: neue Sitzung)
+    #[arg(long)]
+    pub session: Option<String>,
+    /// Modell statt des konfigurierten Standardmodells
+    #[arg(long)]
+    pub model: Option<String>,
+    /// System-Prompt für neue Sitzungen
+    #[arg(long)]
+    pub system: Option<String>,
+    /// Antwort am Stück statt als Stream
+    #[arg(long, default_value_t = false)]
+    pub no_stream: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// REPL command starting with `/`.
+#[derive(Debug, PartialEq, Eq)]
+enum Slash {
+    Model(Option<String>),
+    Clear,
+    Save(PathBuf),
+    Help,
+    Exit,
+}
+
+/// Parses `line` as slash command; `None` for ordinary messages.
+fn parse_slash(line: &str) -> Option<Result<Slash, String>> {
+    let rest = line.trim().strip_prefix('/')?;
+    let (name, arg) = match rest.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, Some(arg.trim()).filter(|arg| !arg.is_empty())),
+        None => (rest, None),
+    };
+    Some(match name {
+        "model" => Ok(Slash::Model(arg.map(str::to_string))),
+        "clear" => Ok(Slash::Clear),
+        "save" => arg
+            .map(|path| Slash::Save(PathBuf::from(shellexpand::tilde(path).as_ref())))
+            .ok_or_else(|| "Aufruf: /save <datei>".to_string()),
+        "help" | "?" => Ok(Slash::Help),
+        "exit" | "quit" => Ok(Slash::Exit),
+        other => Err(format!("Unbekanntes Kommando /{other} – /help zeigt alle")),
+    })
+}
+
+const HELP: &str = "\
+/model [name]   Modell anzeigen bzw. wechseln (leer = Standard des Servers)
+/clear          Verlauf der Sitzung auf dem Server löschen
+/save <datei>   Verlauf als Markdown speichern
+/exit           Beenden (auch Ctrl-D)";
+
+struct Chat {
+    http: reqwest::Client,
+    base: String,
+    session: String,
+    model: Option<String>,
+    system: Option<String>,
+    stream: bool,
+    /// Print the whole answer as JSON (implies no streaming).
+    json: bool,
+    /// The server session holds no messages yet (system prompt pending).
+    fresh: bool,
+}
+
+impl Chat {
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base.trim_end_matches('/'))
+    }
+
+    fn session_path(&self) -> String {
+        self.url(&format!("/v1/chat/sessions/{}", self.session))
+    }
+
+    async fn history(&self) -> Result<Vec<Value>> {
+        let response = self
+            .http
+            .get(self.session_path())
+            .send()
+            .await
+            .with_context(|| format!("HausKI-Core unter {} nicht erreichbar", self.base))?;
+        let body = checked_json(response).await?;
+        Ok(body["messages"].as_array().cloned().unwrap_or_default())
+    }
+
+    async fn clear(&mut self) -> Result<()> {
+        let response = self.http.delete(self.session_path()).send().await?;
+        if !response.status().is_success() {
+            bail!("HTTP {}: {}", response.status(), response.text().await?);
+        }
+        self.fresh = true;
+        Ok(())
+    }
+
+    /// Sends `content` and prints the answer as it arrives.
+    async fn ask(&mut self, content: &str) -> Result<()> {
+        let mut messages = Vec::new();
+        if self.fresh {
+            if let Some(system) = &self.system {
+                messages.push(json!({"role": "system", "content": system}));
+            }
+        }
+        messages.push(json!({"role": "user", "content": content}));
+        let body = json!({
+            "messages": messages,
+            "session_id": self.session,
+            "model": self.model,
+        });
+
+        let path = if self.stream {
+            "/v1/chat/stream"
+        } else {
+            "/v1/chat"
+        };
+        let response = self
+            .http
+            .post(self.url(path))
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("HausKI-Core unter {} nicht erreichbar", self.base))?;
+        if !self.stream {
+            let answer = checked_json(response).await?;
+            if self.json {
+                let result = json!({
+                    "session": self.session,
+                    "model": answer["model"],
+                    "content": answer["content"],
+                    "usage": answer["usage"],
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{}", answer["content"].as_str().unwrap_or_default());
+            }
+            self.fresh = false;
+            return Ok(());
+        }
+
+        if !response.status().is_success() {
+            bail!("HTTP {}: {}", response.status(), response.text().await?);
+        }
+        let mut response = response;
+        // Chunks may split multi-byte characters; only complete UTF-8 moves on.
+        let mut bytes_pending = Vec::new();
+        let mut buffer = String::new();
+        let mut stdout = std::io::stdout();
+        let mut ends_with_newline = true;
+        while let Some(bytes) = response.chunk().await? {
+            bytes_pending.extend_from_slice(&bytes);
+            let valid = match std::str::from_utf8(&bytes_pending) {
+                Ok(text) => text.len(),
+                Err(err) => err.valid_up_to(),
+            };
+            buffer.push_str(&String::from_utf8_lossy(&bytes_pending[..valid]));
+            bytes_pending.drain(..valid);
+            for (event, data) in take_events(&mut buffer) {
+                let data: Value = serde_json::from_str(&data).unwrap_or(Value::Null);
+                match event.as_str() {
+                    "delta" => {
+                        let text = data["content"].as_str().unwrap_or_default();
+                        print!("{text}");
+                        stdout.flush()?;
+                        ends_with_newline = text.ends_with('\n');
+                    }
+                    "done" => {
+                        if !ends_with_newline {
+                            println!();
+                        }
+                        self.fresh = false;
+                        return Ok(());
+                    }
+                    "error" => {
+                        if !ends_with_newline {
+                            println!();
+                        }
+                        bail!(
+                            "{}",
+                            data["message"].as_str().unwrap_or("Stream abgebrochen")
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        bail!("Stream endete ohne Abschluss")
+    }
+
+    async fn save(&self, path: &Path) -> Result<usize> {
+        let messages = self.history().await?;
+        fs::write(path, transcript(&self.session, &messages))
+            .with_context(|| format!("{} konnte nicht geschrieben werden", path.display()))?;
+        Ok(messages.len())
+    }
+}
+
+/// Removes complete SSE events from `buffer`; returns `(event, data)` pairs.
+fn take_events(buffer: &mut String) -> Vec<(String, String)> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.find("\n\n") {
+        let block: String = buffer.drain(..end + 2).collect();
+        let mut event = String::from("message");
+        let mut data = Vec::new();
+        for line in block.lines() {
+            if let Some(name) = line.strip_prefix("event:") {
+                event = name.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        if !data.is_empty() {
+            events.push((event, data.join("\n")));
+        }
+    }
+    events
+}
+
+/// Markdown transcript of a session.
+fn transcript(session: &str, messages: &[Value]) -> String {
+    let mut out = format!("# HausKI-Chat `{session}`\n");
+    for message in messages {
+        let role = match message["role"].as_str().unwrap_or_default() {
+            "user" => "Du",
+            "assistant" => "HausKI",
+            "system" => "System",
+            other => other,
+        };
+        let content = message["content"].as_str().unwrap_or_default().trim_end();
+        out.push_str(&format!("\n**{role}:**\n\n{content}\n"));
+    }
+    out
+}
+
+async fn checked_json(response: reqwest::Response) -> Result<Value> {
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        bail!("HTTP {status}: {text}");
+    }
+    serde_json::from_str(&text).map_err(|e| anyhow!("ungültige Antwort ({e}): {text}"))
+}
+
+pub fn run(opts: ChatOptions) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    runtime.block_on(run_async(opts))
+}
+
+async fn run_async(opts: ChatOptions) -> Result<()> {
+    let session = opts.session.unwrap_or_else(|| {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        format!("cli-{millis}")
+    });
+    let mut chat = Chat {
+        http: remote::client()?,
+        base: remote::base_url(opts.base_url),
+        session,
+        model: opts.model,
+        system: opts.system,
+        // JSON answers only come from single, unstreamed questions.
+        stream: !opts.no_stream && !opts.output.json,
+        json: opts.output.json,
+        fresh: true,
+    };
+
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() || !opts.prompt.is_empty() {
+        let mut prompt = opts.prompt.join(" ");
+        if !stdin.is_terminal() {
+            let mut piped = String::new();
+            stdin
+                .lock()
+                .read_to_string(&mut piped)
+                .context("stdin konnte nicht gelesen werden")?;
+            if !piped.trim().is_empty() {
+                if !prompt.is_empty() {
+                    prompt.push_str("\n\n");
+                }
+                prompt.push_str(piped.trim_end());
+            }
+        }
+        if prompt.trim().is_empty() {
+            bail!("leerer Prompt");
+        }
+        chat.fresh = chat.history().await?.is_empty();
+        return chat.ask(&prompt).await;
+    }
+    if opts.output.json {
+        bail!("--json braucht einen Prompt oder stdin (kein REPL)");
+    }
+
+    repl(&mut chat).await
+}
+
+async fn repl(chat: &mut Chat) -> Result<()> {
+    let history = chat.history().await?;
+    chat.fresh = history.is_empty();
+    if chat.fresh {
+        println!(
+            "HausKI-Chat (Sitzung {}) – /help für Kommandos",
+            chat.session
+        );
+    } else {
+        println!(
+            "Sitzung {} fortgesetzt ({} Nachrichten) – /help für Kommandos",
+            chat.session,
+            history.len()
+        );
+    }
+
+    let mut editor = DefaultEditor::new().context("Zeileneditor nicht verfügbar")?;
+    let history_file = PathBuf::from(shellexpand::tilde(HISTORY_FILE).as_ref());
+    let _ = editor.load_history(&history_file);
+
+    loop {
+        let line = match editor.readline("› ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let result = match parse_slash(line) {
+            None => chat.ask(line).await,
+            Some(Err(message)) => {
+                eprintln!("{message}");
+                continue;
+            }
+            Some(Ok(Slash::Exit)) => break,
+            Some(Ok(Slash::Help)) => {
+                println!("{HELP}");
+                continue;
+            }
+            Some(Ok(Slash::Model(None))) => {
+                println!(
+                    "Modell: {}",
+                    chat.model.as_deref().unwrap_or("(Standard des Servers)")
+                );
+                continue;
+            }
+            Some(Ok(Slash::Model(Some(model)))) => {
+                println!("Modell: {model}");
+                chat.model = Some(model);
+                continue;
+            }
+            Some(Ok(Slash::Clear)) => chat.clear().await.map(|()| println!("Verlauf gelöscht.")),
+            Some(Ok(Slash::Save(path))) => chat
+                .save(&path)
+                .await
+                .map(|count| println!("{count} Nachrichten nach {} gespeichert.", path.display())),
+        };
+        if let Err(err) = result {
+            eprintln!("Fehler: {err:#}");
+        }
+    }
+
+    if let Err(err) = editor.save_history(&history_file) {
+        tracing::debug!(error = %err, "chat history not saved");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slash_commands_are_parsed() {
+        assert_eq!(parse_slash("hallo /model"), None);
+        assert_eq!(
+            parse_slash("/model llama3"),
+            Some(Ok(Slash::Model(Some("llama3".into()))))
+        );
+        assert_eq!(parse_slash(" /model "), Some(Ok(Slash::Model(None))));
+        assert_eq!(
+            parse_slash("/save chat.md"),
+            Some(Ok(Slash::Save("chat.md".into())))
+        );
+        assert!(matches!(parse_slash("/save"), Some(Err(_))));
+        assert!(matches!(parse_slash("/nope"), Some(Err(_))));
+    }
+
+    #[test]
+    fn sse_events_are_split_across_chunks() {
+        let mut buffer = String::from("event: delta\ndata: {\"content\":\"Hal\"}\n\nevent: de");
+        assert_eq!(
+            take_events(&mut buffer),
+            [("delta".to_string(), r#"{"content":"Hal"}"#.to_string())]
+        );
+        buffer.push_str("lta\ndata: {\"content\":\"lo\"}\n\n: keep-alive\n\n");
+        let events = take_events(&mut buffer);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "delta");
+        assert!(buffer.is_empty());
+    }
+}
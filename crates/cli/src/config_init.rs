@@ -0,0 +1,488 @@
+//! `hauski config init`: Erstkonfiguration für die aktuelle Maschine erzeugen.
+//!
+//! Schreibt `configs/hauski.yml`, `configs/flags.yaml`, `configs/models.yml`
+//! und `policies/limits.yaml` unter `--dir` (Default: aktuelles Verzeichnis).
+//! Vorab erkennt das Kommando:
+//!
+//! - GPU, VRAM und Power-Limit per `nvidia-smi` (fehlt es: keine GPU),
+//! - einen laufenden Ollama-Server samt installierter Modelle.
+//!
+//! Daraus ergeben sich Chat-Upstream, Default-Embedder, die Modellauswahl nach
+//! VRAM sowie Latenz- und Thermal-Budgets. Am Terminal fragt das Kommando die
+//! wichtigsten Werte mit diesen Vorschlägen ab; `--yes` oder die Optionen
+//! (`--port`, `--ollama-url`, …) überspringen die Fragen. Vorhandene Dateien
+//! bleiben ohne `--force` unangetastet.
+//!
+//! Konfiguration:
+//!   HAUSKI_NVIDIA_SMI_BIN (Default `nvidia-smi`)
+
+use std::{
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::PathBuf,
+    process::Command,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use hauski_core::ConfigKind;
+
+use crate::{models::yaml_scalar, say, OutputArgs};
+
+const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
+
+/// Models offered by `config init`: id, file name, minimum VRAM (GB), canary.
+const MODEL_CATALOG: [(&str, &str, u64, bool); 3] = [
+    ("llama3.1-8b-q4", "llama3.1-8b-q4.gguf", 6, false),
+    ("whisper-medium", "whisper-medium.bin", 4, true),
+    ("whisper-base", "whisper-base.bin", 0, false),
+];
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Zielverzeichnis (darin `configs/` und `policies/`)
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+    /// Ohne Rückfragen die erkannten Werte übernehmen
+    #[arg(long, short = 'y', default_value_t = false)]
+    pub yes: bool,
+    /// Vorhandene Dateien überschreiben
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    /// Port des Core-Servers
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Ollama-URL (Default: Erkennung unter http://127.0.0.1:11434)
+    #[arg(long)]
+    pub ollama_url: Option<String>,
+    /// Chat-Modell am Upstream (Default: erstes Ollama-Modell ohne `embed`)
+    #[arg(long)]
+    pub chat_model: Option<String>,
+    /// Pfad zum Obsidian-Vault
+    #[arg(long)]
+    pub vault: Option<String>,
+    /// VRAM in GB statt Erkennung (0 = ohne GPU)
+    #[arg(long)]
+    pub vram_gb: Option<u64>,
+    /// Safe-Mode einschalten (keine Plugins, keine Cloud)
+    #[arg(long, default_value_t = false)]
+    pub safe_mode: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Gpu {
+    pub(crate) name: String,
+    pub(crate) vram_mib: u64,
+    pub(crate) power_w: Option<u64>,
+}
+
+/// Values the generated files are rendered from.
+#[derive(Debug, Clone, PartialEq)]
+struct Answers {
+    port: u16,
+    vault: String,
+    ollama_url: Option<String>,
+    chat_model: Option<String>,
+    vram_gb: u64,
+    power_w: Option<u64>,
+    safe_mode: bool,
+}
+
+pub fn run(args: InitArgs) -> Result<()> {
+    let files = [
+        (
+            "configs/hauski.yml",
+            render_hauski as fn(&Answers) -> String,
+        ),
+        ("configs/flags.yaml", render_flags),
+        ("configs/models.yml", render_models),
+        ("policies/limits.yaml", render_limits),
+    ];
+    let existing: Vec<String> = files
+        .iter()
+        .map(|(name, _)| args.dir.join(name))
+        .filter(|path| path.exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    if !existing.is_empty() && !args.force {
+        bail!(
+            "Dateien existieren bereits ({}) – mit --force überschreiben",
+            existing.join(", ")
+        );
+    }
+
+    let gpu = detect_gpu();
+    let ollama_url = args
+        .ollama_url
+        .clone()
+        .unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string());
+    let ollama_models = detect_ollama(&ollama_url);
+    report_detection(
+        gpu.as_ref(),
+        &ollama_url,
+        ollama_models.as_deref(),
+        args.output.json,
+    );
+
+    let mut answers = Answers {
+        port: args.port.unwrap_or(8080),
+        vault: args
+            .vault
+            .clone()
+            .unwrap_or_else(|| "$HOME/vault-gewebe".to_string()),
+        ollama_url: (args.ollama_url.is_some() || ollama_models.is_some()).then_some(ollama_url),
+        chat_model: args.chat_model.clone().or_else(|| {
+            ollama_models
+                .as_deref()
+                .and_then(|models| models.iter().find(|name| !name.contains("embed")).cloned())
+        }),
+        vram_gb: args
+            .vram_gb
+            .unwrap_or_else(|| gpu.as_ref().map_or(0, |gpu| (gpu.vram_mib + 512) / 1024)),
+        power_w: gpu.as_ref().and_then(|gpu| gpu.power_w),
+        safe_mode: args.safe_mode,
+    };
+    if !args.yes && io::stdin().is_terminal() {
+        answers = ask_answers(&args, answers)?;
+    }
+
+    let mut written = Vec::with_capacity(files.len());
+    for (name, render) in files {
+        let path = args.dir.join(name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Verzeichnis {} nicht anlegbar", dir.display()))?;
+        }
+        fs::write(&path, render(&answers))
+            .with_context(|| format!("{} nicht schreibbar", path.display()))?;
+        say(args.output.json, format!("geschrieben: {}", path.display()));
+        written.push(path);
+    }
+    if args.output.json {
+        let result = serde_json::json!({
+            "written": written,
+            "gpu": gpu,
+            "ollama_url": answers.ollama_url,
+            "ollama_models": ollama_models,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+    println!(
+        "Weiter: `hauski config validate --file {}` und `hauski models pull <id>`",
+        args.dir.join("configs/hauski.yml").display()
+    );
+    Ok(())
+}
+
+fn report_detection(gpu: Option<&Gpu>, ollama_url: &str, ollama: Option<&[String]>, json: bool) {
+    let line = match gpu {
+        Some(gpu) => format!(
+            "GPU: {} ({} MiB VRAM{})",
+            gpu.name,
+            gpu.vram_mib,
+            gpu.power_w
+                .map(|watts| format!(", {watts} W"))
+                .unwrap_or_default()
+        ),
+        None => "GPU: keine erkannt (nvidia-smi nicht verfügbar)".to_string(),
+    };
+    say(json, line);
+    let line = match ollama {
+        Some([]) => format!("Ollama: {ollama_url} (keine Modelle)"),
+        Some(models) => format!("Ollama: {ollama_url} ({})", models.join(", ")),
+        None => format!("Ollama: unter {ollama_url} nicht erreichbar"),
+    };
+    say(json, line);
+}
+
+/// Prompts for values not given as options; empty input keeps the proposal.
+fn ask_answers(args: &InitArgs, mut answers: Answers) -> Result<Answers> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut ask = |question: &str, proposal: &str| -> Result<String> {
+        eprint!("{question} [{proposal}]: ");
+        io::stderr().flush()?;
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let line = line.trim();
+        Ok(if line.is_empty() { proposal } else { line }.to_string())
+    };
+
+    if args.port.is_none() {
+        answers.port = ask("Port des Core-Servers", &answers.port.to_string())?
+            .parse()
+            .map_err(|err| anyhow!("ungültiger Port: {err}"))?;
+    }
+    if args.vault.is_none() {
+        answers.vault = ask("Obsidian-Vault", &answers.vault)?;
+    }
+    if args.ollama_url.is_none() {
+        let proposal = answers.ollama_url.as_deref().unwrap_or("-");
+        answers.ollama_url = Some(ask("Ollama-URL (- = ohne)", proposal)?).filter(|url| url != "-");
+    }
+    if args.chat_model.is_none() && answers.ollama_url.is_some() {
+        let proposal = answers.chat_model.as_deref().unwrap_or("-");
+        answers.chat_model = Some(ask("Chat-Modell (- = keins)", proposal)?).filter(|m| m != "-");
+    }
+    if args.vram_gb.is_none() {
+        answers.vram_gb = ask("VRAM in GB (0 = ohne GPU)", &answers.vram_gb.to_string())?
+            .parse()
+            .map_err(|err| anyhow!("ungültige VRAM-Angabe: {err}"))?;
+    }
+    Ok(answers)
+}
+
+pub(crate) fn detect_gpu() -> Option<Gpu> {
+    let bin = env::var("HAUSKI_NVIDIA_SMI_BIN").unwrap_or_else(|_| "nvidia-smi".to_string());
+    let output = Command::new(bin)
+        .args([
+            "--query-gpu=name,memory.total,power.limit",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the first GPU of `nvidia-smi --query-gpu=name,memory.total,power.limit`.
+fn parse_nvidia_smi(text: &str) -> Option<Gpu> {
+    let line = text.lines().find(|line| !line.trim().is_empty())?;
+    let mut fields = line.split(',').map(str::trim);
+    let name = fields.next()?.to_string();
+    let vram_mib = fields.next()?.parse().ok()?;
+    let power_w = fields
+        .next()
+        .and_then(|value| value.parse::<f64>().ok())
+        .map(|watts| watts.round() as u64);
+    Some(Gpu {
+        name,
+        vram_mib,
+        power_w,
+    })
+}
+
+#[derive(Deserialize)]
+struct OllamaTags {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+/// Installed Ollama models, or `None` if no server answers at `url`.
+pub(crate) fn detect_ollama(url: &str) -> Option<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .ok()?;
+    runtime.block_on(async {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .ok()?;
+        let response = client
+            .get(format!("{}/api/tags", url.trim_end_matches('/')))
+            .send()
+            .await
+            .ok()
+            .filter(|response| response.status().is_success())?;
+        let tags: OllamaTags = response.json().await.ok()?;
+        Some(tags.models.into_iter().map(|model| model.name).collect())
+    })
+}
+
+fn scalar(value: &str) -> String {
+    yaml_scalar(value).unwrap_or_else(|_| format!("{value:?}"))
+}
+
+fn optional(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), scalar)
+}
+
+fn render_hauski(answers: &Answers) -> String {
+    let url = answers.ollama_url.as_deref().unwrap_or(DEFAULT_OLLAMA_URL);
+    format!(
+        r#"# Erzeugt von `hauski config init`.
+version: {version}
+data_dir: "$HOME/.local/state/hauski"
+models_dir: "./models"
+
+server:
+  host: 127.0.0.1
+  port: {port}
+
+obsidian:
+  vault_path: {vault}
+
+index:
+  path: "$HOME/.local/state/hauski/index"
+  provider:
+    embedder: "ollama"   # oder "local": model_dir mit config.json, model.safetensors, tokenizer.json
+    model: "nomic-embed-text"
+    url: {url}
+
+budgets:
+  index_topk20_ms: 60
+
+plugins:
+  enabled:
+    - "obsidian_index"
+"#,
+        version = ConfigKind::Config.current_version(),
+        port = answers.port,
+        vault = scalar(&answers.vault),
+        url = scalar(url),
+    )
+}
+
+fn render_flags(answers: &Answers) -> String {
+    format!(
+        r#"# Erzeugt von `hauski config init`; Overrides per HAUSKI_* haben Vorrang.
+version: {version}
+safe_mode: {safe_mode}
+# Upstream für /v1/chat (Ollama oder llama.cpp --server)
+chat_upstream_url: {upstream}
+chat_model: {model}
+# Memory-API (/memory/*) braucht zusätzlich memory_token.
+memory_api: false
+memory_token: null
+"#,
+        version = ConfigKind::Flags.current_version(),
+        safe_mode = answers.safe_mode,
+        upstream = optional(answers.ollama_url.as_deref()),
+        model = optional(answers.chat_model.as_deref()),
+    )
+}
+
+fn render_models(answers: &Answers) -> String {
+    let mut out = format!(
+        "# Erzeugt von `hauski config init` für {} GB VRAM.\n\
+         # `hauski models pull <id> --url … --sha256 …` lädt die Dateien.\n\
+         version: {}\n\
+         models:",
+        answers.vram_gb,
+        ConfigKind::Models.current_version()
+    );
+    let models: Vec<_> = MODEL_CATALOG
+        .iter()
+        .filter(|(_, _, vram, _)| *vram <= answers.vram_gb)
+        .collect();
+    if models.is_empty() {
+        out.push_str(" []\n");
+    } else {
+        out.push('\n');
+    }
+    for (id, file, vram, canary) in models {
+        out.push_str(&format!(
+            "  - id: {id}\n    path: ./models/{file}\n    vram_min_gb: {vram}\n    canary: {canary}\n"
+        ));
+    }
+
+    let ollama_default = answers.ollama_url.is_some();
+    out.push_str("\nembedders:\n");
+    if let Some(url) = &answers.ollama_url {
+        out.push_str(&format!(
+            "  - id: nomic-ollama\n    provider: ollama\n    model: nomic-embed-text\n    url: {}\n    \
+             dimension: 768\n    max_tokens: 8192\n    normalization: none\n    default: true\n",
+            scalar(url)
+        ));
+    }
+    out.push_str(&format!(
+        "  - id: minilm-local\n    provider: local\n    model: all-MiniLM-L6-v2\n    \
+         model_dir: ./models/all-MiniLM-L6-v2\n    dimension: 384\n    max_tokens: 256\n    \
+         normalization: l2\n    pooling: mean\n    default: {}\n",
+        !ollama_default
+    ));
+    out
+}
+
+fn render_limits(answers: &Answers) -> String {
+    let gpu = answers.vram_gb > 0;
+    format!(
+        "# Erzeugt von `hauski config init` ({}).\n\
+         version: {}\n\
+         latency:\n  llm_p95_ms: {}\n  index_topk20_ms: 60\n\
+         thermal:\n  gpu_max_c: 80\n  dgpu_power_w: {}\n\
+         asr:\n  wer_max_pct: 10\n",
+        if gpu { "GPU" } else { "CPU" },
+        ConfigKind::Limits.current_version(),
+        if gpu { 400 } else { 1500 },
+        answers.power_w.unwrap_or(220),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hauski_core::{parse_config, FeatureFlags, Limits, ModelsFile};
+
+    fn answers(ollama: bool, vram_gb: u64) -> Answers {
+        Answers {
+            port: 8181,
+            vault: "~/Notizen: privat".to_string(),
+            ollama_url: ollama.then(|| DEFAULT_OLLAMA_URL.to_string()),
+            chat_model: ollama.then(|| "llama3.1:8b".to_string()),
+            vram_gb,
+            power_w: Some(175),
+            safe_mode: false,
+        }
+    }
+
+    #[test]
+    fn parses_first_gpu_from_nvidia_smi() {
+        let gpu = parse_nvidia_smi("NVIDIA GeForce RTX 4070, 12282, 200.00\nOther, 1, 1\n");
+        assert_eq!(
+            gpu,
+            Some(Gpu {
+                name: "NVIDIA GeForce RTX 4070".into(),
+                vram_mib: 12282,
+                power_w: Some(200),
+            })
+        );
+        assert_eq!(
+            parse_nvidia_smi("Tesla T4, 15360, [N/A]").and_then(|gpu| gpu.power_w),
+            None
+        );
+        assert_eq!(parse_nvidia_smi(""), None);
+    }
+
+    #[test]
+    fn rendered_files_parse_with_core_types() {
+        for (ollama, vram) in [(true, 12), (false, 0)] {
+            let answers = answers(ollama, vram);
+            let (limits, _, _) =
+                parse_config::<Limits>(&render_limits(&answers), ConfigKind::Limits).unwrap();
+            assert_eq!(limits.thermal.dgpu_power_w, 175);
+            let (flags, _, _) =
+                parse_config::<FeatureFlags>(&render_flags(&answers), ConfigKind::Flags).unwrap();
+            assert_eq!(flags.chat_model.is_some(), ollama);
+            let (models, _, migrated) =
+                parse_config::<ModelsFile>(&render_models(&answers), ConfigKind::Models).unwrap();
+            assert!(!migrated.is_upgrade());
+            assert_eq!(models.models.len(), if vram > 0 { 3 } else { 1 });
+            assert_eq!(models.embedders.iter().filter(|e| e.default).count(), 1);
+        }
+    }
+
+    #[test]
+    fn rendered_hauski_yml_passes_validate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hauski.yml");
+        fs::write(&path, render_hauski(&answers(true, 8))).unwrap();
+        crate::validate_config(path.to_str().unwrap(), false, false).unwrap();
+        let config: serde_yaml_ng::Value =
+            serde_yaml_ng::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["server"]["port"], 8181);
+        assert_eq!(config["obsidian"]["vault_path"], "~/Notizen: privat");
+    }
+}
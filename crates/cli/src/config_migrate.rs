@@ -0,0 +1,162 @@
+//! `hauski config migrate`: Schema-Versionen der Konfiguration prüfen und anheben.
+//!
+//! Geprüft werden die `hauski.yml` (`--file`, sofern vorhanden) und die
+//! Einzeldateien aus HAUSKI_LIMITS, HAUSKI_MODELS, HAUSKI_ROUTING und
+//! HAUSKI_FLAGS. Ohne `--write` zeigt das Kommando nur, welche Dateien älter
+//! sind (der Server hebt sie beim Laden ohnehin im Speicher an); mit `--write`
+//! werden sie auf die aktuelle Version geschrieben, das Original bleibt als
+//! `<datei>.bak` liegen. Kommentare gehen beim Umschreiben verloren,
+//! `${VAR}`-Platzhalter bleiben erhalten. Dateien mit einer neueren Version,
+//! als dieser Build kennt, sind ein Fehler.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Serialize;
+
+use hauski_core::{migrate_file, ConfigKind, FallbackPaths, Migrated};
+
+use crate::{say, OutputArgs};
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// Pfad zur hauski.yml
+    #[arg(long, default_value = "./configs/hauski.yml")]
+    pub file: String,
+    /// Ältere Dateien auf die aktuelle Version umschreiben (mit Backup)
+    #[arg(long, default_value_t = false)]
+    pub write: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    path: PathBuf,
+    kind: ConfigKind,
+    #[serde(flatten)]
+    migrated: Option<Migrated>,
+    written: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn run(args: MigrateArgs) -> Result<()> {
+    let fallbacks = FallbackPaths::from_env();
+    let files = [
+        (
+            PathBuf::from(shellexpand::full(&args.file)?.as_ref()),
+            ConfigKind::Config,
+        ),
+        (fallbacks.limits, ConfigKind::Limits),
+        (fallbacks.models, ConfigKind::Models),
+        (fallbacks.routing, ConfigKind::Routing),
+        (fallbacks.flags, ConfigKind::Flags),
+    ];
+
+    let mut reports = Vec::new();
+    for (path, kind) in files {
+        if !path.exists() {
+            say(
+                args.output.json,
+                format!("{}: fehlt, übersprungen", path.display()),
+            );
+            continue;
+        }
+        let report = match migrate_file(&path, kind, args.write) {
+            Ok(migrated) => {
+                let written = args.write && migrated.is_upgrade();
+                say(args.output.json, describe(&path, &migrated, written));
+                FileReport {
+                    path,
+                    kind,
+                    migrated: Some(migrated),
+                    written,
+                    error: None,
+                }
+            }
+            Err(err) => {
+                say(args.output.json, format!("{}: {err}", path.display()));
+                FileReport {
+                    path,
+                    kind,
+                    migrated: None,
+                    written: false,
+                    error: Some(err.to_string()),
+                }
+            }
+        };
+        reports.push(report);
+    }
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    }
+    let failed = reports
+        .iter()
+        .filter(|report| report.error.is_some())
+        .count();
+    if failed > 0 {
+        bail!("{failed} Konfigurationsdatei(en) nicht migrierbar");
+    }
+    let pending = reports
+        .iter()
+        .filter(|report| !report.written)
+        .filter(|report| report.migrated.as_ref().is_some_and(Migrated::is_upgrade))
+        .count();
+    if pending > 0 {
+        say(
+            args.output.json,
+            format!("{pending} Datei(en) veraltet – mit --write umschreiben"),
+        );
+    }
+    Ok(())
+}
+
+fn describe(path: &std::path::Path, migrated: &Migrated, written: bool) -> String {
+    let path = path.display();
+    if !migrated.is_upgrade() {
+        return format!("{path}: Version {} (aktuell)", migrated.to);
+    }
+    let steps = migrated.applied.join("; ");
+    if written {
+        format!(
+            "{path}: Version {} → {} geschrieben ({steps}), Backup: {path}.bak",
+            migrated.from, migrated.to
+        )
+    } else {
+        format!(
+            "{path}: Version {} → {} ({steps})",
+            migrated.from, migrated.to
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_names_versions_and_backup() {
+        let path = std::path::Path::new("/etc/hauski/limits.yaml");
+        let current = Migrated {
+            from: 1,
+            to: 1,
+            applied: Vec::new(),
+        };
+        assert_eq!(
+            describe(path, &current, false),
+            "/etc/hauski/limits.yaml: Version 1 (aktuell)"
+        );
+        let upgrade = Migrated {
+            from: 1,
+            to: 2,
+            applied: vec!["rename budget_ms"],
+        };
+        assert_eq!(
+            describe(path, &upgrade, true),
+            "/etc/hauski/limits.yaml: Version 1 → 2 geschrieben (rename budget_ms), Backup: /etc/hauski/limits.yaml.bak"
+        );
+    }
+}
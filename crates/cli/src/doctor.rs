@@ -0,0 +1,577 @@
+//! `hauski doctor`: Diagnose der lokalen Installation.
+//!
+//! Prüft nacheinander Konfiguration (`hauski.yml`, Limits, Modelle, Routing,
+//! Flags), Speicherorte samt Schreibrechten (Daten, Index, Memory-/Policy-DB,
+//! Chronik), Erreichbarkeit von Ollama, Embeddern und Chat-Upstream, die GPU,
+//! den Server-Port und den freien Plattenplatz. Jede Prüfung endet mit `PASS`,
+//! `WARN` oder `FAIL` und – wo sinnvoll – einem konkreten Hinweis.
+//!
+//! Exit-Code: 0 = alles in Ordnung, 1 = nur Warnungen, 2 = mindestens ein
+//! Fehler. `--json` gibt die Ergebnisse maschinenlesbar aus.
+//!
+//! Schwellen für freien Speicher: unter 5 GiB Warnung, unter 1 GiB Fehler.
+
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener},
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use hauski_core::{load_flags, load_limits, load_models, load_routing, FeatureFlags, ModelsFile};
+use serde::Serialize;
+
+use crate::{check_config, config_init, CheckedConfig, OutputArgs};
+
+const DISK_WARN_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+const DISK_FAIL_BYTES: u64 = 1024 * 1024 * 1024;
+const LOCAL_EMBEDDER_FILES: [&str; 2] = ["config.json", "model.safetensors"];
+/// A local embedder needs one of these tokenizer files.
+const LOCAL_TOKENIZER_FILES: [&str; 2] = ["tokenizer.json", "vocab.txt"];
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Pfad zur HausKI-Konfiguration
+    #[arg(long, default_value = "./configs/hauski.yml")]
+    pub config: String,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Finding {
+    check: String,
+    status: Status,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+}
+
+#[derive(Default)]
+struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn push(&mut self, status: Status, check: &str, detail: impl Into<String>, hint: Option<&str>) {
+        self.findings.push(Finding {
+            check: check.to_string(),
+            status,
+            detail: detail.into(),
+            hint: hint.map(str::to_string),
+        });
+    }
+
+    fn pass(&mut self, check: &str, detail: impl Into<String>) {
+        self.push(Status::Pass, check, detail, None);
+    }
+
+    fn warn(&mut self, check: &str, detail: impl Into<String>, hint: &str) {
+        self.push(Status::Warn, check, detail, Some(hint));
+    }
+
+    fn fail(&mut self, check: &str, detail: impl Into<String>, hint: &str) {
+        self.push(Status::Fail, check, detail, Some(hint));
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self.findings.iter().map(|finding| finding.status).max() {
+            Some(Status::Fail) => 2,
+            Some(Status::Warn) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Runs all checks, prints them and returns the exit code.
+pub fn run(args: DoctorArgs) -> Result<i32> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let mut report = Report::default();
+
+    let config = match check_config(&args.config, false) {
+        Ok(checked) => {
+            report.pass("config", format!("{} gültig", checked.path.display()));
+            for warning in &checked.warnings {
+                report.warn("config", warning.clone(), "Block in hauski.yml ergänzen");
+            }
+            Some(checked)
+        }
+        Err(err) => {
+            report.fail(
+                "config",
+                format!("{}: {err}", args.config),
+                "`hauski config init` ausführen oder --config angeben",
+            );
+            None
+        }
+    };
+    let (models, flags) = check_policy_files(&mut report);
+
+    check_storage(&mut report, config.as_ref());
+    check_upstreams(
+        &mut report,
+        &runtime,
+        config.as_ref(),
+        models.as_ref(),
+        flags.as_ref(),
+    );
+    check_gpu(&mut report);
+    check_port(&mut report, &runtime, &bind_address(config.as_ref()));
+    check_disk(&mut report, config.as_ref());
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&report.findings)?);
+    } else {
+        let rows = report
+            .findings
+            .iter()
+            .map(|finding| {
+                [
+                    finding.status.label().to_string(),
+                    finding.check.clone(),
+                    finding.detail.clone(),
+                    finding.hint.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        crate::print_table(["Status", "Prüfung", "Details", "Hinweis"], rows);
+    }
+    Ok(report.exit_code())
+}
+
+fn check_policy_files(report: &mut Report) -> (Option<ModelsFile>, Option<FeatureFlags>) {
+    let path = |var: &str, default: &str| env::var(var).unwrap_or_else(|_| default.to_string());
+    let hint = "`hauski config init` ausführen oder Umgebungsvariable setzen";
+
+    let limits = path("HAUSKI_LIMITS", "./policies/limits.yaml");
+    match load_limits(&limits) {
+        Ok(_) => report.pass("limits", limits),
+        Err(err) => report.fail("limits", err.to_string(), hint),
+    }
+    let routing = path("HAUSKI_ROUTING", "./policies/routing.yaml");
+    match load_routing(&routing) {
+        Ok(_) => report.pass("routing", routing),
+        Err(err) => report.fail("routing", err.to_string(), hint),
+    }
+    let models_path = path("HAUSKI_MODELS", "./configs/models.yml");
+    let models = match load_models(&models_path) {
+        Ok(models) => {
+            report.pass(
+                "models",
+                format!("{models_path} ({} Modelle)", models.models.len()),
+            );
+            Some(models)
+        }
+        Err(err) => {
+            report.fail("models", err.to_string(), hint);
+            None
+        }
+    };
+    let flags_path = path("HAUSKI_FLAGS", "./configs/flags.yaml");
+    let flags = match load_flags(&flags_path) {
+        Ok(flags) => {
+            report.pass("flags", flags_path);
+            Some(flags)
+        }
+        Err(err) => {
+            report.fail("flags", err.to_string(), hint);
+            None
+        }
+    };
+    (models, flags)
+}
+
+pub(crate) fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .unwrap_or_else(|| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".local/state")
+        })
+        .join("hauski")
+}
+
+fn expand(path: &str) -> PathBuf {
+    PathBuf::from(
+        shellexpand::full(path)
+            .map(|expanded| expanded.into_owned())
+            .unwrap_or_else(|_| path.to_string()),
+    )
+}
+
+/// Directories HausKI writes to, with a label for the report.
+fn storage_dirs(config: Option<&CheckedConfig>) -> Vec<(String, PathBuf)> {
+    let mut dirs = vec![("state (memory.db)".to_string(), state_dir())];
+    if let Some(checked) = config {
+        if let Some(data_dir) = &checked.config.data_dir {
+            dirs.push(("data_dir".to_string(), expand(data_dir)));
+        }
+        if let Some(parent) = checked.index_path.parent() {
+            dirs.push(("index".to_string(), parent.to_path_buf()));
+        }
+    }
+    if let Some(parent) = env::var("HAUSKI_POLICY_DB_PATH")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .and_then(|value| PathBuf::from(value).parent().map(Path::to_path_buf))
+    {
+        dirs.push(("policy db".to_string(), parent));
+    }
+    if let Some(dir) = env::var("HAUSKI_CHRONIK_DIR")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        dirs.push(("chronik".to_string(), PathBuf::from(dir)));
+    }
+    dirs
+}
+
+fn check_storage(report: &mut Report, config: Option<&CheckedConfig>) {
+    for (label, dir) in storage_dirs(config) {
+        let check = format!("storage: {label}");
+        let hint = "Rechte prüfen (chown/chmod) oder Pfad umstellen";
+        match dir_writable(&dir) {
+            Ok(true) => report.pass(&check, dir.display().to_string()),
+            Ok(false) => report.pass(
+                &check,
+                format!("{} (wird beim ersten Start angelegt)", dir.display()),
+            ),
+            Err(err) => report.fail(&check, format!("{}: {err}", dir.display()), hint),
+        }
+    }
+    let memory_db = state_dir().join("memory.db");
+    if memory_db.is_file() {
+        if let Err(err) = OpenOptions::new().append(true).open(&memory_db) {
+            report.fail(
+                "storage: memory.db",
+                format!("{} nicht schreibbar: {err}", memory_db.display()),
+                "Rechte der DB-Datei prüfen",
+            );
+        }
+    }
+}
+
+/// Whether `dir` exists (and is writable); a missing directory is fine as long
+/// as its nearest existing ancestor is writable.
+fn dir_writable(dir: &Path) -> Result<bool, String> {
+    let existing = dir.ancestors().find(|path| path.exists()).unwrap_or(dir);
+    if !existing.is_dir() {
+        return Err(format!("{} ist kein Verzeichnis", existing.display()));
+    }
+    let probe = existing.join(format!(".hauski-doctor-{}", std::process::id()));
+    fs::write(&probe, b"")
+        .map_err(|err| format!("{} nicht schreibbar: {err}", existing.display()))?;
+    let _ = fs::remove_file(&probe);
+    Ok(existing == dir)
+}
+
+/// Whether an Ollama model list contains `model` (`name` means `name:latest`).
+fn has_ollama_model(models: &[String], model: &str) -> bool {
+    models.iter().any(|name| {
+        name == model || name.strip_suffix(":latest") == Some(model) || {
+            model.strip_suffix(":latest") == Some(name.as_str())
+        }
+    })
+}
+
+fn check_ollama(report: &mut Report, check: &str, url: &str, model: &str) {
+    match config_init::detect_ollama(url) {
+        Some(models) if has_ollama_model(&models, model) => {
+            report.pass(check, format!("{url} ({model})"));
+        }
+        Some(_) => report.warn(
+            check,
+            format!("{url} erreichbar, Modell {model} fehlt"),
+            &format!("`ollama pull {model}`"),
+        ),
+        None => report.fail(
+            check,
+            format!("{url} nicht erreichbar"),
+            "`ollama serve` starten oder URL korrigieren",
+        ),
+    }
+}
+
+fn check_upstreams(
+    report: &mut Report,
+    runtime: &tokio::runtime::Runtime,
+    config: Option<&CheckedConfig>,
+    models: Option<&ModelsFile>,
+    flags: Option<&FeatureFlags>,
+) {
+    if let Some(checked) = config {
+        let provider = &checked.index().provider;
+        if provider.embedder == "ollama" {
+            check_ollama(report, "index provider", &provider.url, &provider.model);
+        }
+    }
+
+    for spec in models.map_or(&[][..], |models| models.embedders.as_slice()) {
+        let check = format!("embedder: {}", spec.id);
+        match spec.provider {
+            hauski_embeddings::Provider::Ollama => {
+                let url = spec.url.as_deref().unwrap_or("http://127.0.0.1:11434");
+                check_ollama(report, &check, url, &spec.model);
+            }
+            hauski_embeddings::Provider::Local => {
+                let dir = expand(spec.model_dir.as_deref().unwrap_or_default());
+                let mut missing: Vec<&str> = LOCAL_EMBEDDER_FILES
+                    .into_iter()
+                    .filter(|file| !dir.join(file).is_file())
+                    .collect();
+                if !LOCAL_TOKENIZER_FILES
+                    .iter()
+                    .any(|file| dir.join(file).is_file())
+                {
+                    missing.push("tokenizer.json (oder vocab.txt)");
+                }
+                if missing.is_empty() {
+                    report.pass(&check, dir.display().to_string());
+                } else {
+                    let status = if spec.default {
+                        Status::Fail
+                    } else {
+                        Status::Warn
+                    };
+                    report.push(
+                        status,
+                        &check,
+                        format!("{}: fehlt {}", dir.display(), missing.join(", ")),
+                        Some("Modell nach model_dir exportieren"),
+                    );
+                }
+            }
+        }
+    }
+
+    match chat_upstream_url(flags) {
+        Some(url) => match runtime.block_on(probe(&url)) {
+            Ok(_) => report.pass("chat upstream", url),
+            Err(err) => report.fail(
+                "chat upstream",
+                format!("{url}: {err}"),
+                "Upstream starten oder chat_upstream_url korrigieren",
+            ),
+        },
+        None => report.warn(
+            "chat upstream",
+            "nicht konfiguriert – /v1/chat antwortet 503",
+            "chat_upstream_url in flags.yaml setzen",
+        ),
+    }
+}
+
+/// Chat upstream as the server resolves it: environment before `flags.yaml`.
+pub(crate) fn chat_upstream_url(flags: Option<&FeatureFlags>) -> Option<String> {
+    env::var("HAUSKI_CHAT_UPSTREAM_URL")
+        .or_else(|_| env::var("CHAT_UPSTREAM_URL"))
+        .ok()
+        .or_else(|| flags.and_then(|flags| flags.chat_upstream_url.clone()))
+        .filter(|url| !url.trim().is_empty())
+}
+
+/// Any HTTP answer counts as reachable; only transport errors fail.
+pub(crate) async fn probe(url: &str) -> Result<reqwest::StatusCode, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|err| err.to_string())?;
+    client
+        .get(url)
+        .send()
+        .await
+        .map(|response| response.status())
+        .map_err(|err| err.to_string())
+}
+
+fn check_gpu(report: &mut Report) {
+    match config_init::detect_gpu() {
+        Some(gpu) => report.pass("gpu", format!("{} ({} MiB VRAM)", gpu.name, gpu.vram_mib)),
+        None => report.warn(
+            "gpu",
+            "keine NVIDIA-GPU erkannt – Modelle laufen auf der CPU",
+            "Treiber/nvidia-smi prüfen, falls eine GPU verbaut ist",
+        ),
+    }
+}
+
+fn bind_address(config: Option<&CheckedConfig>) -> String {
+    env::var("HAUSKI_BIND").unwrap_or_else(|_| {
+        let server = config.and_then(|checked| checked.config.server.as_ref());
+        format!(
+            "{}:{}",
+            server
+                .and_then(|server| server.host.as_deref())
+                .unwrap_or("127.0.0.1"),
+            server.and_then(|server| server.port).unwrap_or(8080)
+        )
+    })
+}
+
+fn check_port(report: &mut Report, runtime: &tokio::runtime::Runtime, bind: &str) {
+    let addr: SocketAddr = match bind.parse() {
+        Ok(addr) => addr,
+        Err(err) => {
+            report.fail(
+                "port",
+                format!("{bind}: {err}"),
+                "HAUSKI_BIND bzw. server.host/port korrigieren",
+            );
+            return;
+        }
+    };
+    match TcpListener::bind(addr) {
+        Ok(_) => report.pass("port", format!("{addr} frei")),
+        Err(err) if err.kind() == ErrorKind::AddrInUse => {
+            match runtime.block_on(probe(&format!("http://{addr}/health"))) {
+                Ok(status) if status.is_success() => {
+                    report.pass("port", format!("{addr}: HausKI läuft bereits"));
+                }
+                _ => report.fail(
+                    "port",
+                    format!("{addr} von einem anderen Prozess belegt"),
+                    "Prozess per `ss -ltnp` finden oder HAUSKI_BIND ändern",
+                ),
+            }
+        }
+        Err(err) => report.fail(
+            "port",
+            format!("{addr}: {err}"),
+            "HAUSKI_BIND bzw. server.host/port korrigieren",
+        ),
+    }
+}
+
+fn check_disk(report: &mut Report, config: Option<&CheckedConfig>) {
+    let mut seen = Vec::new();
+    for (label, dir) in storage_dirs(config) {
+        let Some(existing) = dir.ancestors().find(|path| path.exists()) else {
+            continue;
+        };
+        let Some((mount, available)) = free_space(existing) else {
+            continue;
+        };
+        if seen.contains(&mount) {
+            continue;
+        }
+        let check = format!("disk: {mount}");
+        let detail = format!("{} frei ({label})", crate::models::human_bytes(available));
+        let hint = "Platz schaffen (z. B. alte Modelle mit `hauski models rm`)";
+        if available < DISK_FAIL_BYTES {
+            report.fail(&check, detail, hint);
+        } else if available < DISK_WARN_BYTES {
+            report.warn(&check, detail, hint);
+        } else {
+            report.pass(&check, detail);
+        }
+        seen.push(mount);
+    }
+}
+
+/// Mount point and available bytes for `path`, via `df -Pk`.
+fn free_space(path: &Path) -> Option<(String, u64)> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_df(text: &str) -> Option<(String, u64)> {
+    let fields: Vec<&str> = text.lines().nth(1)?.split_whitespace().collect();
+    let available_kib: u64 = fields.get(3)?.parse().ok()?;
+    let mount = fields.get(5..)?.join(" ");
+    Some((mount, available_kib * 1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_reflects_worst_status() {
+        let mut report = Report::default();
+        assert_eq!(report.exit_code(), 0);
+        report.pass("a", "ok");
+        assert_eq!(report.exit_code(), 0);
+        report.warn("b", "hm", "tu was");
+        assert_eq!(report.exit_code(), 1);
+        report.fail("c", "kaputt", "reparieren");
+        report.pass("d", "ok");
+        assert_eq!(report.exit_code(), 2);
+    }
+
+    #[test]
+    fn parses_df_and_ollama_names() {
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                  /dev/nvme0n1p2 488245288 300000000 188245288 62% /home/my disk\n";
+        assert_eq!(
+            parse_df(df),
+            Some(("/home/my disk".to_string(), 188_245_288 * 1024))
+        );
+        assert_eq!(parse_df("Filesystem\n"), None);
+
+        let models = vec![
+            "nomic-embed-text:latest".to_string(),
+            "llama3:8b".to_string(),
+        ];
+        assert!(has_ollama_model(&models, "nomic-embed-text"));
+        assert!(has_ollama_model(&models, "llama3:8b"));
+        assert!(!has_ollama_model(&models, "llama3"));
+    }
+
+    #[test]
+    fn dir_writable_accepts_missing_dirs_below_writable_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(dir_writable(dir.path()), Ok(true));
+        assert_eq!(dir_writable(&dir.path().join("neu/index")), Ok(false));
+
+        let file = dir.path().join("datei");
+        fs::write(&file, b"").unwrap();
+        assert!(dir_writable(&file.join("sub")).is_err());
+    }
+
+    #[test]
+    fn occupied_port_without_hauski_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut report = Report::default();
+        check_port(&mut report, &runtime, &addr.to_string());
+        assert_eq!(
+            report.findings[0].status,
+            Status::Fail,
+            "{:?}",
+            report.findings
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! `hauski embed`: Ad-hoc-Embeddings mit einem Embedder aus `configs/models.yml`.
+//!
+//! Zum Eingrenzen von Dimensionsfehlern und Cache-Verhalten: Der Embedder wird
+//! wie im Core aus dem Abschnitt `embedders` gebaut (Timeouts, Retries,
+//! Normalisierung, Cache), der Text kommt aus einer Datei oder stdin. Die
+//! Ausgabe enthält je Text Dimension (gegen `dimension` der Spec geprüft),
+//! L2-Norm und ob der Vektor aus dem Cache kam; `--repeat` bettet mehrfach ein
+//! und zeigt so Cache-Treffer. Vektoren gehen als JSON oder `.npy` (float32,
+//! Form `(n, dim)`) nach stdout bzw. `--out`. Exit-Code 1 bei abweichender
+//! Dimension.
+
+use std::{
+    env, fs,
+    io::{IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use hauski_core::load_models;
+use hauski_embeddings::{ConfiguredEmbedder, Embedder, EmbedderRegistry, EmbedderSpec};
+use serde::Serialize;
+
+use crate::{say, OutputArgs};
+
+#[derive(Args, Debug)]
+pub struct EmbedArgs {
+    /// Datei mit dem Text; `-` oder leer = stdin
+    pub input: Option<PathBuf>,
+    /// Embedder-ID aus `embedders` (Default: der Default-Embedder)
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Pfad zu models.yml (Default: `HAUSKI_MODELS` oder ./configs/models.yml)
+    #[arg(long)]
+    pub models: Option<PathBuf>,
+    /// Jede nicht-leere Zeile als eigenen Text einbetten
+    #[arg(long, default_value_t = false)]
+    pub lines: bool,
+    /// Ausgabeformat der Vektoren
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    pub format: Format,
+    /// Vektoren in diese Datei statt nach stdout schreiben
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+    /// So oft einbetten (ab dem zweiten Durchlauf aus dem Cache)
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub repeat: u32,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Npy,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    embedder: String,
+    provider: &'static str,
+    model: String,
+    dimension: usize,
+    normalization: String,
+    passes: Vec<Pass>,
+    embeddings: Vec<Embedding>,
+}
+
+#[derive(Debug, Serialize)]
+struct Pass {
+    elapsed_ms: f64,
+    /// Texts served from the cache in this pass.
+    cached: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Embedding {
+    chars: usize,
+    dimension: usize,
+    dimension_ok: bool,
+    norm: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    vector: Vec<f32>,
+}
+
+impl Report {
+    fn mismatches(&self) -> usize {
+        self.embeddings.iter().filter(|e| !e.dimension_ok).count()
+    }
+}
+
+pub fn run(args: EmbedArgs) -> Result<i32> {
+    let texts = split_texts(&read_input(args.input.as_deref())?, args.lines);
+    if texts.is_empty() {
+        bail!("kein Text zum Einbetten");
+    }
+    let spec = select_spec(&args)?;
+    let embedder = spec
+        .build()
+        .map_err(|e| anyhow!("Embedder {} nicht nutzbar: {e}", spec.id))?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let (passes, vectors) = runtime.block_on(embed_passes(&embedder, &texts, args.repeat))?;
+    let report = Report {
+        embedder: spec.id.clone(),
+        provider: spec.provider.as_str(),
+        model: spec.model.clone(),
+        dimension: spec.dimension,
+        normalization: format!("{:?}", spec.normalization).to_lowercase(),
+        passes,
+        embeddings: texts
+            .iter()
+            .zip(vectors)
+            .map(|(text, vector)| Embedding {
+                chars: text.chars().count(),
+                dimension: vector.len(),
+                dimension_ok: spec.validate(&vector).is_ok(),
+                norm: vector.iter().map(|x| x * x).sum::<f32>().sqrt(),
+                vector,
+            })
+            .collect(),
+    };
+
+    let data = match args.format {
+        Format::Json => {
+            let mut data = serde_json::to_vec_pretty(&report)?;
+            data.push(b'\n');
+            data
+        }
+        Format::Npy => encode_npy(&report.embeddings)?,
+    };
+    match &args.out {
+        Some(path) => fs::write(path, &data)
+            .with_context(|| format!("{} nicht schreibbar", path.display()))?,
+        None if args.format == Format::Npy && std::io::stdout().is_terminal() => {
+            bail!("npy ist binär: --out setzen oder stdout umleiten")
+        }
+        None => std::io::stdout().write_all(&data)?,
+    }
+
+    let code = i32::from(report.mismatches() > 0);
+    if args.output.json && args.out.is_some() {
+        let mut summary = report;
+        summary.embeddings.iter_mut().for_each(|e| e.vector.clear());
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        // Vectors on stdout leave the summary for stderr.
+        print_summary(&report, args.out.is_none());
+    }
+    Ok(code)
+}
+
+fn read_input(input: Option<&Path>) -> Result<String> {
+    match input {
+        Some(path) if path != Path::new("-") => {
+            fs::read_to_string(path).with_context(|| format!("{} nicht lesbar", path.display()))
+        }
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+    }
+}
+
+fn split_texts(input: &str, lines: bool) -> Vec<String> {
+    if lines {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else if input.trim().is_empty() {
+        Vec::new()
+    } else {
+        vec![input.trim_end_matches(['\n', '\r']).to_string()]
+    }
+}
+
+fn select_spec(args: &EmbedArgs) -> Result<EmbedderSpec> {
+    let path = args.models.clone().unwrap_or_else(|| {
+        PathBuf::from(
+            env::var("HAUSKI_MODELS").unwrap_or_else(|_| "./configs/models.yml".to_string()),
+        )
+    });
+    let models = load_models(&path).map_err(|e| anyhow!("{} nicht lesbar: {e}", path.display()))?;
+    let registry = EmbedderRegistry::new(models.embedders)
+        .map_err(|e| anyhow!("embedders in {}: {e}", path.display()))?;
+    let spec = match &args.model {
+        Some(id) => registry.get(id).ok_or_else(|| {
+            let known: Vec<&str> = registry.iter().map(|spec| spec.id.as_str()).collect();
+            anyhow!(
+                "Embedder {id} nicht in {} (vorhanden: {})",
+                path.display(),
+                known.join(", ")
+            )
+        })?,
+        None => registry
+            .default_spec()
+            .ok_or_else(|| anyhow!("keine embedders in {} konfiguriert", path.display()))?,
+    };
+    Ok(spec.clone())
+}
+
+async fn embed_passes(
+    embedder: &ConfiguredEmbedder,
+    texts: &[String],
+    repeat: u32,
+) -> Result<(Vec<Pass>, Vec<Vec<f32>>)> {
+    let mut passes = Vec::with_capacity(repeat as usize);
+    let mut vectors = Vec::new();
+    for _ in 0..repeat {
+        let cached = texts.iter().filter(|text| embedder.is_cached(text)).count();
+        let started = Instant::now();
+        vectors = embedder.embed(texts).await?;
+        passes.push(Pass {
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            cached,
+        });
+    }
+    Ok((passes, vectors))
+}
+
+/// `.npy` version 1.0: little-endian float32, shape `(n, dim)`, C order.
+fn encode_npy(embeddings: &[Embedding]) -> Result<Vec<u8>> {
+    let dim = embeddings.first().map_or(0, |e| e.vector.len());
+    if embeddings.iter().any(|e| e.vector.len() != dim) {
+        bail!("Vektoren unterschiedlicher Länge passen nicht in ein npy-Array");
+    }
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {dim}), }}",
+        embeddings.len()
+    );
+    // Magic (6) + version (2) + header length (2) + header, padded to 64 bytes.
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut out = Vec::with_capacity(10 + header.len() + embeddings.len() * dim * 4);
+    out.extend_from_slice(b"\x93NUMPY\x01\x00");
+    out.extend_from_slice(&u16::try_from(header.len())?.to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    for value in embeddings.iter().flat_map(|e| &e.vector) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(out)
+}
+
+fn print_summary(report: &Report, to_stderr: bool) {
+    say(
+        to_stderr,
+        format!(
+            "Embedder {} ({} {}), Dimension {}, Normalisierung {}",
+            report.embedder, report.provider, report.model, report.dimension, report.normalization
+        ),
+    );
+    for (idx, pass) in report.passes.iter().enumerate() {
+        say(
+            to_stderr,
+            format!(
+                "  Durchlauf {}: {:.1} ms, {}/{} aus dem Cache",
+                idx + 1,
+                pass.elapsed_ms,
+                pass.cached,
+                report.embeddings.len()
+            ),
+        );
+    }
+    for (idx, embedding) in report.embeddings.iter().enumerate() {
+        let check = if embedding.dimension_ok {
+            "✓".to_string()
+        } else {
+            format!("✗ erwartet {}", report.dimension)
+        };
+        say(
+            to_stderr,
+            format!(
+                "  Text {}: {} Zeichen, Dimension {} {check}, Norm {:.4}",
+                idx + 1,
+                embedding.chars,
+                embedding.dimension,
+                embedding.norm
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use hauski_embeddings::{CircuitBreakerConfig, Normalization, Provider, RetryConfig};
+    use serde_json::{json, Value};
+
+    fn spec(url: &str, dimension: usize) -> EmbedderSpec {
+        EmbedderSpec {
+            id: "fake".into(),
+            provider: Provider::Ollama,
+            model: "fake-embed".into(),
+            dimension,
+            max_tokens: 512,
+            normalization: Normalization::L2,
+            pooling: None,
+            device: None,
+            url: Some(url.into()),
+            model_dir: None,
+            default: true,
+            timeout_ms: None,
+            retry: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            latency_budget_ms: None,
+            batch_size: None,
+            cache_entries: None,
+            concurrency: None,
+        }
+    }
+
+    #[test]
+    fn npy_header_is_aligned_and_describes_the_shape() {
+        let embeddings = [1.0f32, 2.0, 3.0, 4.0]
+            .chunks(2)
+            .map(|vector| Embedding {
+                chars: 1,
+                dimension: 2,
+                dimension_ok: true,
+                norm: 0.0,
+                vector: vector.to_vec(),
+            })
+            .collect::<Vec<_>>();
+        let npy = encode_npy(&embeddings).unwrap();
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(header.contains("'shape': (2, 2)"));
+        assert!(header.ends_with('\n'));
+        assert_eq!(&npy[10 + header_len..][..4], &1.0f32.to_le_bytes());
+        assert_eq!(npy.len(), 10 + header_len + 16);
+    }
+
+    #[test]
+    fn lines_mode_skips_blank_lines() {
+        assert_eq!(split_texts("a\n\n b \n", true), ["a", "b"]);
+        assert_eq!(split_texts("a\nb\n", false), ["a\nb"]);
+        assert!(split_texts(" \n", false).is_empty());
+    }
+
+    #[tokio::test]
+    async fn repeated_passes_hit_the_cache_and_flag_wrong_dimensions() {
+        let app = Router::new().route(
+            "/api/embed",
+            post(|Json(body): Json<Value>| async move {
+                let n = body["input"].as_array().map_or(0, Vec::len);
+                Json(json!({ "embeddings": vec![[3.0, 4.0]; n] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let embedder = spec(&url, 2).build().unwrap();
+        let texts = vec!["eins".to_string(), "zwei".to_string()];
+        let (passes, vectors) = embed_passes(&embedder, &texts, 2).await.unwrap();
+        assert_eq!(passes.iter().map(|p| p.cached).collect::<Vec<_>>(), [0, 2]);
+        assert!((vectors[0][0] - 0.6).abs() < 1e-6, "L2-normalized");
+        assert!(spec(&url, 2).validate(&vectors[0]).is_ok());
+        assert!(spec(&url, 3).validate(&vectors[0]).is_err());
+    }
+}
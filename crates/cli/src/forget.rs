@@ -0,0 +1,285 @@
+//! `hauski forget`: Dokumente aus dem Index vergessen – mit Sicherheitsnetz.
+//!
+//! Ohne `--execute` zeigt das Kommando nur, was `POST /index/forget` entfernen
+//! würde (Dry Run). Mit `--execute` folgt auf die Vorschau eine getippte
+//! Bestätigung: der angezeigte Satz, etwa `forget 42 docs in chronik`, muss
+//! exakt eingegeben werden. Skripte übergeben ihn ohne Terminal per
+//! `--confirm "<satz>"`. Die Begründung (`--reason`) landet im Audit-Log
+//! (`GET /policy/audit`, Ereignis `index_forget`).
+
+use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal, Write},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::{index::Backend, print_table, OutputArgs};
+
+/// Documents listed in the preview; the rest is summarised.
+const PREVIEW_ROWS: usize = 20;
+
+#[derive(Args, Debug)]
+pub struct ForgetArgs {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Namespace (ohne: alle Namespaces)
+    #[arg(long)]
+    pub namespace: Option<String>,
+    /// Einzelnes Dokument
+    #[arg(long)]
+    pub doc_id: Option<String>,
+    /// `source_ref.origin`, z. B. `tool`
+    #[arg(long)]
+    pub origin: Option<String>,
+    /// RFC-3339-Zeitstempel, z. B. 2024-01-01T00:00:00Z
+    #[arg(long)]
+    pub older_than: Option<String>,
+    /// Ganzen Namespace leeren (nur zusammen mit `--namespace`)
+    #[arg(long, requires = "namespace", default_value_t = false)]
+    pub all: bool,
+    /// Begründung (landet im Audit-Log)
+    #[arg(long)]
+    pub reason: String,
+    /// Wirklich löschen (sonst nur Vorschau)
+    #[arg(long, default_value_t = false)]
+    pub execute: bool,
+    /// Bestätigungssatz für Skripte statt interaktiver Eingabe
+    #[arg(long, requires = "execute")]
+    pub confirm: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+impl ForgetArgs {
+    fn request(&self, execute: bool) -> Value {
+        json!({
+            "filter": {
+                "namespace": self.namespace,
+                "doc_id": self.doc_id,
+                "source_ref_origin": self.origin,
+                "older_than": self.older_than,
+                "allow_namespace_wipe": self.all,
+            },
+            "reason": self.reason,
+            "confirm": execute,
+            "dry_run": !execute,
+        })
+    }
+
+    /// The sentence that has to be typed to confirm `count` deletions.
+    fn phrase(&self, count: u64) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("forget {count} docs in {namespace}"),
+            None => format!("forget {count} docs in all namespaces"),
+        }
+    }
+}
+
+pub fn run(args: ForgetArgs) -> Result<()> {
+    if args.reason.trim().is_empty() {
+        bail!("--reason darf nicht leer sein");
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    runtime.block_on(forget(&backend, &args, prompt))
+}
+
+/// Reads the confirmation sentence from the terminal.
+fn prompt(phrase: &str) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        bail!("Bestätigung nötig: --confirm \"{phrase}\" angeben (stdin ist kein Terminal)");
+    }
+    eprint!("Zum Bestätigen „{phrase}“ eintippen: ");
+    io::stderr().flush()?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line)
+}
+
+async fn forget(
+    backend: &Backend,
+    args: &ForgetArgs,
+    ask: impl FnOnce(&str) -> Result<String>,
+) -> Result<()> {
+    let preview = backend
+        .call(Method::POST, "/index/forget", Some(args.request(false)))
+        .await?;
+    let count = preview["forgotten_count"].as_u64().unwrap_or(0);
+    if args.output.json && !args.execute {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
+    print_summary(
+        &preview,
+        if args.output.json {
+            Stream::Stderr
+        } else {
+            Stream::Stdout
+        },
+    );
+
+    if !args.execute {
+        println!("Dry run – mit --execute wirklich vergessen.");
+        return Ok(());
+    }
+    if count == 0 {
+        eprintln!("Nichts zu vergessen.");
+        return Ok(());
+    }
+
+    let phrase = args.phrase(count);
+    let typed = match &args.confirm {
+        Some(confirm) => confirm.clone(),
+        None => ask(&phrase)?,
+    };
+    if typed.trim() != phrase {
+        bail!("Bestätigung stimmt nicht („{phrase}“ erwartet) – nichts gelöscht");
+    }
+
+    let result = backend
+        .call(Method::POST, "/index/forget", Some(args.request(true)))
+        .await?;
+    let forgotten = result["forgotten_count"].as_u64().unwrap_or(0);
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!("{forgotten} Dokumente vergessen (Begründung im Audit-Log).");
+    }
+    if forgotten != count {
+        eprintln!("Hinweis: Vorschau zeigte {count}, gelöscht wurden {forgotten} Dokumente.");
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+fn print_summary(preview: &Value, stream: Stream) {
+    let docs = preview["forgotten_docs"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut per_namespace: BTreeMap<&str, usize> = BTreeMap::new();
+    for doc in docs {
+        *per_namespace
+            .entry(doc["namespace"].as_str().unwrap_or_default())
+            .or_default() += 1;
+    }
+    let breakdown = per_namespace
+        .iter()
+        .map(|(namespace, count)| format!("{namespace}: {count}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let headline = format!(
+        "Vorschau: {} Dokumente würden vergessen{}",
+        docs.len(),
+        if breakdown.is_empty() {
+            String::new()
+        } else {
+            format!(" ({breakdown})")
+        }
+    );
+    if matches!(stream, Stream::Stderr) {
+        // JSON mode keeps stdout machine-readable.
+        eprintln!("{headline}");
+        return;
+    }
+    println!("{headline}");
+    let rows: Vec<[String; 3]> = docs
+        .iter()
+        .take(PREVIEW_ROWS)
+        .map(|doc| {
+            [
+                doc["doc_id"].as_str().unwrap_or_default().to_string(),
+                doc["namespace"].as_str().unwrap_or_default().to_string(),
+                doc["ingested_at"].as_str().unwrap_or_default().to_string(),
+            ]
+        })
+        .collect();
+    if !rows.is_empty() {
+        print_table(["Dokument", "Namespace", "Abgelegt"], rows);
+    }
+    if docs.len() > PREVIEW_ROWS {
+        println!("… und {} weitere", docs.len() - PREVIEW_ROWS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn args(execute: bool, confirm: Option<&str>) -> ForgetArgs {
+        ForgetArgs {
+            base_url: None,
+            namespace: Some("default".into()),
+            doc_id: None,
+            origin: None,
+            older_than: None,
+            all: true,
+            reason: "Aufräumen".into(),
+            execute,
+            confirm: confirm.map(str::to_string),
+            output: OutputArgs::default(),
+        }
+    }
+
+    async fn total(backend: &Backend) -> u64 {
+        let stats = backend
+            .call(Method::GET, "/index/stats", None)
+            .await
+            .unwrap();
+        stats["total_documents"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn forget_requires_the_typed_phrase() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "Alpha").unwrap();
+        fs::write(dir.path().join("b.md"), "Beta").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let backend = Backend::offline();
+            crate::index::upsert_paths(&backend, &[dir.path().to_path_buf()], "default", "user")
+                .await
+                .unwrap();
+
+            forget(&backend, &args(false, None), |_| unreachable!())
+                .await
+                .unwrap();
+            assert_eq!(total(&backend).await, 2);
+
+            let wrong = forget(&backend, &args(true, None), |phrase| {
+                assert_eq!(phrase, "forget 2 docs in default");
+                Ok("forget 3 docs in default\n".into())
+            })
+            .await;
+            assert!(wrong.is_err());
+            assert_eq!(total(&backend).await, 2);
+
+            forget(
+                &backend,
+                &args(true, Some("forget 2 docs in default")),
+                |_| unreachable!(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(total(&backend).await, 0);
+        });
+    }
+}
@@ -0,0 +1,543 @@
+//! `hauski index …`: Dokumente ablegen, suchen, Statistik und Vergessen.
+//!
+//! Standardmäßig spricht das Kommando mit dem laufenden Core (`--base-url`,
+//! Default `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080). Mit `--offline`
+//! läuft stattdessen ein eingebetteter `IndexState` hinter demselben Router
+//! wie im Server, also mit denselben Prüfungen. Dieser Index lebt nur im
+//! Speicher des Prozesses; `--load` liest vorher Dateien oder Verzeichnisse
+//! ein, etwa für eine schnelle Suche über einen Ordner ohne Server.
+//!
+//! Ausgabe als Tabelle (Default) oder mit `--json` als JSON.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum::{body::Body, Router};
+use clap::{Args, Subcommand};
+use hauski_indexd::IndexState;
+use reqwest::Method;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use tracing::warn;
+
+use crate::{print_table, remote, OutputArgs};
+
+/// Files above this size are skipped on upsert.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Target size of a chunk; paragraphs are merged up to it.
+const MAX_CHUNK_CHARS: usize = 1_200;
+/// Latency budget of the embedded index (as `limits.latency.index_topk20_ms`).
+const OFFLINE_BUDGET_MS: u64 = 60;
+/// Characters of chunk text shown in the search table.
+const SNIPPET_CHARS: usize = 80;
+
+#[derive(Args, Debug)]
+pub struct IndexOptions {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long, global = true)]
+    pub base_url: Option<String>,
+    /// Eingebetteten Index im Prozess statt des Servers nutzen (nur im Speicher)
+    #[arg(long, global = true, default_value_t = false)]
+    pub offline: bool,
+    /// Offline: Dateien/Verzeichnisse vorab einlesen (mehrfach möglich)
+    #[arg(long, global = true, requires = "offline")]
+    pub load: Vec<PathBuf>,
+    /// Namespace (Default: `default`)
+    #[arg(long, global = true, default_value = "default")]
+    pub namespace: String,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCmd {
+    /// Dateien oder Verzeichnisse (rekursiv) als Dokumente ablegen
+    Upsert {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Herkunft für `source_ref` (bestimmt den Trust-Level)
+        #[arg(long, default_value = "user")]
+        origin: String,
+    },
+    /// Volltextsuche im Namespace
+    Search {
+        query: String,
+        /// Anzahl Treffer
+        #[arg(long, short, default_value_t = 10)]
+        k: usize,
+    },
+    /// Dokumente und Chunks je Namespace
+    Stats,
+    /// Dokumente vergessen; ohne `--yes` nur Vorschau (dry run)
+    Forget {
+        #[arg(long)]
+        doc_id: Option<String>,
+        /// `source_ref.origin`, z. B. `tool`
+        #[arg(long)]
+        origin: Option<String>,
+        /// RFC-3339-Zeitstempel, z. B. 2024-01-01T00:00:00Z
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Ganzen Namespace leeren (nur mit `--namespace`-Filter sinnvoll)
+        #[arg(long, default_value_t = false)]
+        allow_namespace_wipe: bool,
+        /// Begründung (landet im Audit-Log des Index)
+        #[arg(long)]
+        reason: String,
+        /// Wirklich löschen
+        #[arg(long, short = 'y', default_value_t = false)]
+        yes: bool,
+    },
+}
+
+/// Where index requests go: the running server or an embedded index.
+pub(crate) enum Backend {
+    Remote {
+        client: reqwest::Client,
+        base: String,
+    },
+    Offline(Router),
+}
+
+impl Backend {
+    /// The running core at `base_url` (resolved via [`remote::base_url`]).
+    pub(crate) fn remote(base_url: Option<String>) -> Result<Self> {
+        Ok(Self::Remote {
+            client: remote::client()?,
+            base: remote::base_url(base_url),
+        })
+    }
+
+    pub(crate) fn offline() -> Self {
+        let state = IndexState::new(OFFLINE_BUDGET_MS, Arc::new(|_, _, _, _| {}), None, None);
+        Self::Offline(
+            Router::new()
+                .nest("/index", hauski_indexd::router())
+                .with_state(state),
+        )
+    }
+
+    /// Sends `body` to `path` (e.g. `/index/search`) and returns the JSON
+    /// response; non-2xx statuses are errors.
+    pub(crate) async fn call(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let (status, text) = match self {
+            Self::Remote { client, base } => {
+                let url = format!("{}{path}", base.trim_end_matches('/'));
+                let mut request = client.request(method, &url);
+                if let Some(body) = &body {
+                    request = request.json(body);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .with_context(|| format!("HausKI-Core unter {base} nicht erreichbar"))?;
+                (response.status().as_u16(), response.text().await?)
+            }
+            Self::Offline(router) => {
+                let request = axum::http::Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .header("content-type", "application/json")
+                    .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))?;
+                let response = router.clone().oneshot(request).await?;
+                let status = response.status().as_u16();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+                (status, String::from_utf8_lossy(&bytes).into_owned())
+            }
+        };
+        if !(200..300).contains(&status) {
+            bail!("HTTP {status}: {text}");
+        }
+        serde_json::from_str(&text).map_err(|e| anyhow!("ungültige Antwort ({e}): {text}"))
+    }
+}
+
+pub fn run(opts: IndexOptions, cmd: IndexCmd) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    runtime.block_on(run_async(opts, cmd))
+}
+
+async fn run_async(opts: IndexOptions, cmd: IndexCmd) -> Result<()> {
+    let backend = if opts.offline {
+        let backend = Backend::offline();
+        if !opts.load.is_empty() {
+            upsert_paths(&backend, &opts.load, &opts.namespace, "user").await?;
+        }
+        backend
+    } else {
+        Backend::remote(opts.base_url.clone())?
+    };
+
+    match cmd {
+        IndexCmd::Upsert { paths, origin } => {
+            let results = upsert_paths(&backend, &paths, &opts.namespace, &origin).await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
+                let rows: Vec<[String; 2]> = results
+                    .iter()
+                    .map(|result| {
+                        [
+                            result["doc_id"].as_str().unwrap_or_default().to_string(),
+                            result["ingested"].to_string(),
+                        ]
+                    })
+                    .collect();
+                println!("{} Dokumente in '{}' abgelegt.", rows.len(), opts.namespace);
+                print_table(["Dokument", "Chunks"], rows);
+            }
+        }
+        IndexCmd::Search { query, k } => {
+            let response = backend
+                .call(
+                    Method::POST,
+                    "/index/search",
+                    Some(json!({"query": query, "k": k, "namespace": opts.namespace})),
+                )
+                .await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                print_matches(&response);
+            }
+        }
+        IndexCmd::Stats => {
+            let response = backend.call(Method::GET, "/index/stats", None).await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                print_stats(&response);
+            }
+        }
+        IndexCmd::Forget {
+            doc_id,
+            origin,
+            older_than,
+            allow_namespace_wipe,
+            reason,
+            yes,
+        } => {
+            let body = json!({
+                "filter": {
+                    "namespace": opts.namespace,
+                    "doc_id": doc_id,
+                    "source_ref_origin": origin,
+                    "older_than": older_than,
+                    "allow_namespace_wipe": allow_namespace_wipe,
+                },
+                "reason": reason,
+                "confirm": yes,
+                "dry_run": !yes,
+            });
+            let response = backend
+                .call(Method::POST, "/index/forget", Some(body))
+                .await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                print_forgotten(&response);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Upserts every readable text file below `paths`; returns
+/// `{doc_id, ingested}` per document.
+pub(crate) async fn upsert_paths(
+    backend: &Backend,
+    paths: &[PathBuf],
+    namespace: &str,
+    origin: &str,
+) -> Result<Vec<Value>> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(path, &mut files)
+            .map_err(|e| anyhow!("{} konnte nicht gelesen werden: {e}", path.display()))?;
+    }
+    let mut results = Vec::with_capacity(files.len());
+    for file in files {
+        let Some(text) = read_text(&file) else {
+            continue;
+        };
+        let doc_id = file.to_string_lossy().into_owned();
+        let chunks: Vec<Value> = chunk_text(&text)
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| json!({"chunk_id": format!("{doc_id}#{i}"), "text": text}))
+            .collect();
+        if chunks.is_empty() {
+            continue;
+        }
+        let body = json!({
+            "doc_id": doc_id,
+            "namespace": namespace,
+            "chunks": chunks,
+            "meta": {"path": doc_id},
+            "source_ref": {
+                "origin": origin,
+                "id": doc_id,
+                "trust_level": hauski_indexd::TrustLevel::default_for_origin(origin),
+                "injected_by": "hauski-cli",
+            },
+        });
+        let response = backend
+            .call(Method::POST, "/index/upsert", Some(body))
+            .await
+            .map_err(|e| anyhow!("{doc_id} konnte nicht abgelegt werden: {e}"))?;
+        results.push(json!({"doc_id": doc_id, "ingested": response["ingested"]}));
+    }
+    Ok(results)
+}
+
+/// Files below `path` (itself if a file), sorted; hidden entries are skipped.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        fs::metadata(path)?;
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if !hidden {
+            collect_files(&entry, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Contents of `path` if it is UTF-8 text within [`MAX_FILE_BYTES`].
+fn read_text(path: &Path) -> Option<String> {
+    let size = fs::metadata(path).ok()?.len();
+    if size > MAX_FILE_BYTES {
+        warn!(
+            "{} übersprungen: größer als {MAX_FILE_BYTES} Bytes",
+            path.display()
+        );
+        return None;
+    }
+    match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(err) => {
+            warn!("{} übersprungen: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Splits `text` into chunks of whole paragraphs up to [`MAX_CHUNK_CHARS`];
+/// longer paragraphs are cut at character boundaries.
+pub fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let paragraph_chars = paragraph.chars().count();
+        if !current.is_empty() && current.chars().count() + 2 + paragraph_chars > MAX_CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if paragraph_chars > MAX_CHUNK_CHARS {
+            let chars: Vec<char> = paragraph.chars().collect();
+            chunks.extend(
+                chars
+                    .chunks(MAX_CHUNK_CHARS)
+                    .map(|part| part.iter().collect::<String>()),
+            );
+            continue;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn print_matches(response: &Value) {
+    let matches = response["matches"].as_array().cloned().unwrap_or_default();
+    if matches.is_empty() {
+        println!("Keine Treffer.");
+        return;
+    }
+    let rows = matches
+        .iter()
+        .map(|m| {
+            let text = m["text"].as_str().unwrap_or_default().replace('\n', " ");
+            let mut snippet: String = text.chars().take(SNIPPET_CHARS).collect();
+            if text.chars().count() > SNIPPET_CHARS {
+                snippet.push('…');
+            }
+            [
+                format!("{:.3}", m["score"].as_f64().unwrap_or_default()),
+                m["doc_id"].as_str().unwrap_or_default().to_string(),
+                m["chunk_id"].as_str().unwrap_or_default().to_string(),
+                snippet,
+            ]
+        })
+        .collect();
+    print_table(["Score", "Dokument", "Chunk", "Text"], rows);
+}
+
+fn print_stats(response: &Value) {
+    println!(
+        "{} Dokumente, {} Chunks, Budget {} ms",
+        response["total_documents"], response["total_chunks"], response["budget_ms"]
+    );
+    let mut rows: Vec<[String; 2]> = response["namespaces"]
+        .as_object()
+        .map(|namespaces| {
+            namespaces
+                .iter()
+                .map(|(name, count)| [name.clone(), count.to_string()])
+                .collect()
+        })
+        .unwrap_or_default();
+    if !rows.is_empty() {
+        rows.sort();
+        print_table(["Namespace", "Dokumente"], rows);
+    }
+}
+
+fn print_forgotten(response: &Value) {
+    let count = &response["forgotten_count"];
+    if response["dry_run"].as_bool().unwrap_or(true) {
+        println!("Vorschau: {count} Dokumente würden vergessen (mit --yes ausführen).");
+    } else {
+        println!("{count} Dokumente vergessen.");
+    }
+    let rows: Vec<[String; 3]> = response["forgotten_docs"]
+        .as_array()
+        .map(|docs| {
+            docs.iter()
+                .map(|doc| {
+                    [
+                        doc["doc_id"].as_str().unwrap_or_default().to_string(),
+                        doc["namespace"].as_str().unwrap_or_default().to_string(),
+                        doc["ingested_at"].as_str().unwrap_or_default().to_string(),
+                    ]
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !rows.is_empty() {
+        print_table(["Dokument", "Namespace", "Abgelegt"], rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_merge_paragraphs_and_split_long_ones() {
+        assert!(chunk_text(" \n\n ").is_empty());
+        assert_eq!(chunk_text("eins\n\nzwei"), ["eins\n\nzwei"]);
+
+        let paragraph = "a".repeat(MAX_CHUNK_CHARS - 3);
+        let chunks = chunk_text(&format!("{paragraph}\n\nkurz"));
+        assert_eq!(chunks, [paragraph.as_str(), "kurz"]);
+
+        let long = "b".repeat(MAX_CHUNK_CHARS * 2 + 1);
+        let chunks = chunk_text(&long);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2], "b");
+    }
+
+    #[test]
+    fn offline_backend_upserts_searches_and_forgets() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("notes.md"),
+            "Die Heizung wartet Herr Meier.",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git").join("HEAD"), "Heizung").unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let backend = Backend::offline();
+            let results = upsert_paths(&backend, &[dir.path().to_path_buf()], "default", "user")
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0]["ingested"], 1);
+
+            let response = backend
+                .call(
+                    Method::POST,
+                    "/index/search",
+                    Some(json!({"query": "heizung", "k": 5})),
+                )
+                .await
+                .unwrap();
+            let matches = response["matches"].as_array().unwrap();
+            assert_eq!(matches.len(), 1);
+            let doc_id = matches[0]["doc_id"].as_str().unwrap().to_string();
+            assert!(doc_id.ends_with("notes.md"));
+
+            let stats = backend
+                .call(Method::GET, "/index/stats", None)
+                .await
+                .unwrap();
+            assert_eq!(stats["total_documents"], 1);
+
+            let forget = |confirm: bool| {
+                json!({
+                    "filter": {"doc_id": doc_id},
+                    "reason": "test",
+                    "confirm": confirm,
+                    "dry_run": !confirm,
+                })
+            };
+            let preview = backend
+                .call(Method::POST, "/index/forget", Some(forget(false)))
+                .await
+                .unwrap();
+            assert_eq!(preview["dry_run"], true);
+            assert_eq!(preview["forgotten_count"], 1);
+            backend
+                .call(Method::POST, "/index/forget", Some(forget(true)))
+                .await
+                .unwrap();
+            let stats = backend
+                .call(Method::GET, "/index/stats", None)
+                .await
+                .unwrap();
+            assert_eq!(stats["total_documents"], 0);
+
+            let refused = backend
+                .call(
+                    Method::POST,
+                    "/index/forget",
+                    Some(json!({"filter": {}, "reason": "test", "confirm": true})),
+                )
+                .await;
+            assert!(refused.unwrap_err().to_string().starts_with("HTTP 400"));
+        });
+    }
+}
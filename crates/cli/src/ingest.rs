@@ -0,0 +1,364 @@
+//! `hauski ingest <pfad>…`: PDF, HTML, DOCX und EPUB in den Index übernehmen.
+//!
+//! Text und Gliederung extrahiert `hauski-ingest` lokal, genau wie
+//! `/ingest/file` im Server: Chunks aus ganzen Absätzen mit Überschriften,
+//! Seite (PDF) und Zeichen-Offsets in den Metadaten, Provenienz
+//! `source_ref.origin = "ingest"` mit dem Dateipfad. Die `doc_id` leitet sich
+//! aus dem Inhalt ab; erneutes Einlesen ersetzt das Dokument.
+//!
+//! Jede Datei geht als `ingest`-Job (`POST /jobs`) an den Core, der fehlende
+//! Embeddings berechnet; mit `--wait` wartet das Kommando auf die Jobs.
+//! Verzeichnisse werden rekursiv nach den Endungen aus `--ext` durchsucht,
+//! versteckte Pfade bleiben außen vor. `--dry-run` zeigt nur, was extrahiert
+//! würde, und braucht keinen Server.
+
+use std::{
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use hauski_ingest::{Chunk, Document, Format};
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{index::Backend, print_table, OutputArgs};
+
+/// Accepted `--chunk-chars`, as for `/ingest/file`.
+const CHUNK_CHARS: RangeInclusive<usize> = 100..=16_000;
+/// Pause between job status requests with `--wait`.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Args, Debug)]
+pub struct IngestArgs {
+    /// Dateien oder Verzeichnisse (rekursiv)
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// Namespace im Index
+    #[arg(long = "ns", default_value = hauski_ingest::DEFAULT_NAMESPACE)]
+    pub namespace: String,
+    /// Höchstgröße eines Chunks in Zeichen
+    #[arg(long, default_value_t = hauski_ingest::DEFAULT_CHUNK_CHARS)]
+    pub chunk_chars: usize,
+    /// Dateiendungen in Verzeichnissen (mehrfach oder kommagetrennt)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "pdf,html,htm,xhtml,docx,epub"
+    )]
+    pub ext: Vec<String>,
+    /// Nur extrahieren und anzeigen, nichts ablegen
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+    /// Auf das Ende der Jobs warten
+    #[arg(long, default_value_t = false)]
+    pub wait: bool,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// A file read into chunks, ready to be submitted.
+struct Prepared {
+    path: PathBuf,
+    doc_id: String,
+    document: Document,
+    chunks: Vec<Chunk>,
+}
+
+/// Outcome per file, printed as a table row or JSON.
+#[derive(Debug, Serialize)]
+struct FileReport {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<Format>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pages: Option<u32>,
+    chunks: usize,
+    /// `preview`, the job status or `error`.
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl FileReport {
+    fn new(prepared: &Prepared, status: &str) -> Self {
+        Self {
+            path: prepared.path.display().to_string(),
+            doc_id: Some(prepared.doc_id.clone()),
+            format: Some(prepared.document.format),
+            title: prepared.document.title.clone(),
+            pages: prepared.document.pages,
+            chunks: prepared.chunks.len(),
+            status: status.to_string(),
+            job_id: None,
+            error: None,
+        }
+    }
+
+    fn failed(path: &Path, error: impl std::fmt::Display) -> Self {
+        Self {
+            path: path.display().to_string(),
+            doc_id: None,
+            format: None,
+            title: None,
+            pages: None,
+            chunks: 0,
+            status: "error".to_string(),
+            job_id: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+pub fn run(args: IngestArgs) -> Result<()> {
+    if !CHUNK_CHARS.contains(&args.chunk_chars) {
+        bail!(
+            "--chunk-chars muss zwischen {} und {} liegen",
+            CHUNK_CHARS.start(),
+            CHUNK_CHARS.end()
+        );
+    }
+    let extensions: Vec<String> = args
+        .ext
+        .iter()
+        .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+        .collect();
+    let mut files = Vec::new();
+    for path in &args.paths {
+        collect_files(path, &extensions, &mut files)
+            .map_err(|e| anyhow!("{} konnte nicht gelesen werden: {e}", path.display()))?;
+    }
+    if files.is_empty() {
+        bail!("keine Dateien mit den Endungen {}", extensions.join(", "));
+    }
+
+    let mut reports = Vec::with_capacity(files.len());
+    let mut submitted = Vec::new();
+    for path in files {
+        match prepare(&path, args.chunk_chars) {
+            Ok(prepared) if args.dry_run => reports.push(FileReport::new(&prepared, "preview")),
+            Ok(prepared) => submitted.push(prepared),
+            Err(err) => reports.push(FileReport::failed(&path, format!("{err:#}"))),
+        }
+    }
+    if !submitted.is_empty() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Tokio Runtime konnte nicht erzeugt werden")?;
+        let backend = Backend::remote(args.base_url.clone())?;
+        runtime.block_on(async {
+            for prepared in &submitted {
+                let report = match submit(&backend, prepared, &args.namespace).await {
+                    Ok(job_id) if args.wait => wait(&backend, prepared, job_id).await,
+                    Ok(job_id) => FileReport {
+                        job_id: Some(job_id),
+                        ..FileReport::new(prepared, "queued")
+                    },
+                    Err(err) => FileReport::failed(&prepared.path, format!("{err:#}")),
+                };
+                reports.push(report);
+            }
+        });
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|report| report.error.is_some())
+        .count();
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        let rows = reports
+            .iter()
+            .map(|report| {
+                [
+                    report.path.clone(),
+                    report.format.map(|f| f.to_string()).unwrap_or_default(),
+                    report.title.clone().unwrap_or_default(),
+                    report.pages.map(|p| p.to_string()).unwrap_or_default(),
+                    report.chunks.to_string(),
+                    match &report.error {
+                        Some(error) => format!("Fehler: {error}"),
+                        None => report.status.clone(),
+                    },
+                ]
+            })
+            .collect();
+        print_table(
+            ["Datei", "Format", "Titel", "Seiten", "Chunks", "Status"],
+            rows,
+        );
+    }
+    if failed > 0 {
+        bail!("{failed} von {} Dateien nicht übernommen", reports.len());
+    }
+    Ok(())
+}
+
+/// Files below `path` with one of `extensions` (itself if a file, whatever
+/// its extension), sorted; hidden entries are skipped.
+fn collect_files(path: &Path, extensions: &[String], files: &mut Vec<PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        fs::metadata(path)?;
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        let wanted = entry.is_dir()
+            || entry.extension().is_some_and(|ext| {
+                extensions.contains(&ext.to_string_lossy().to_ascii_lowercase())
+            });
+        if !hidden && wanted {
+            collect_files(&entry, extensions, files)?;
+        }
+    }
+    Ok(())
+}
+
+fn prepare(path: &Path, chunk_chars: usize) -> Result<Prepared> {
+    let bytes = fs::read(path)?;
+    let name = path.file_name().map(|name| name.to_string_lossy());
+    let format = Format::detect(name.as_deref(), &bytes)?;
+    let document = hauski_ingest::extract(&bytes, format)?;
+    let chunks = document.chunks(chunk_chars);
+    Ok(Prepared {
+        path: path.canonicalize()?,
+        doc_id: hauski_ingest::doc_id(&bytes),
+        document,
+        chunks,
+    })
+}
+
+/// The `POST /jobs` body ingesting `prepared` into `namespace`.
+fn job_request(prepared: &Prepared, namespace: &str) -> Result<Value> {
+    let mut source_ref = hauski_ingest::source_ref(&prepared.path.to_string_lossy());
+    source_ref.injected_by = Some("hauski-cli".to_string());
+    let upsert =
+        prepared
+            .document
+            .upsert_request(&prepared.chunks, &prepared.doc_id, namespace, source_ref);
+    Ok(json!({ "kind": "ingest", "documents": [serde_json::to_value(upsert)?] }))
+}
+
+async fn submit(backend: &Backend, prepared: &Prepared, namespace: &str) -> Result<String> {
+    let record = backend
+        .call(
+            Method::POST,
+            "/jobs",
+            Some(job_request(prepared, namespace)?),
+        )
+        .await?;
+    record["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Antwort ohne Job-Id: {record}"))
+}
+
+/// Polls the job until it ends; failures of the job become report errors.
+async fn wait(backend: &Backend, prepared: &Prepared, job_id: String) -> FileReport {
+    let path = format!("/jobs/{job_id}");
+    loop {
+        let record = match backend.call(Method::GET, &path, None).await {
+            Ok(record) => record,
+            Err(err) => return FileReport::failed(&prepared.path, format!("{err:#}")),
+        };
+        let status = record["status"].as_str().unwrap_or_default();
+        if matches!(status, "succeeded" | "failed" | "cancelled") {
+            let failures = record["result"]["failed"]
+                .as_array()
+                .filter(|failed| !failed.is_empty())
+                .map(|failed| Value::Array(failed.clone()).to_string());
+            return FileReport {
+                job_id: Some(job_id),
+                error: record["error"].as_str().map(str::to_string).or(failures),
+                ..FileReport::new(prepared, status)
+            };
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<html><head><title>Garten</title></head>\
+        <body><h1>Beete</h1><p>Im März umgraben.</p></body></html>";
+
+    #[test]
+    fn directories_are_walked_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("garten.html"), PAGE).unwrap();
+        fs::write(dir.path().join("notizen.md"), "# Notizen").unwrap();
+        fs::create_dir(dir.path().join("archiv")).unwrap();
+        fs::write(dir.path().join("archiv").join("alt.HTM"), PAGE).unwrap();
+        fs::create_dir(dir.path().join(".cache")).unwrap();
+        fs::write(dir.path().join(".cache").join("kopie.html"), PAGE).unwrap();
+
+        let extensions = vec!["html".to_string(), "htm".to_string()];
+        let mut files = Vec::new();
+        collect_files(dir.path(), &extensions, &mut files).unwrap();
+        assert_eq!(
+            files,
+            [
+                dir.path().join("archiv").join("alt.HTM"),
+                dir.path().join("garten.html")
+            ]
+        );
+
+        // Named files are taken regardless of their extension.
+        let mut files = Vec::new();
+        collect_files(&dir.path().join("notizen.md"), &extensions, &mut files).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(prepare(&files[0], 1_200).is_err());
+    }
+
+    #[test]
+    fn jobs_carry_chunks_with_offsets_and_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("garten.html");
+        fs::write(&path, PAGE).unwrap();
+
+        let prepared = prepare(&path, 1_200).unwrap();
+        assert_eq!(prepared.doc_id, hauski_ingest::doc_id(PAGE.as_bytes()));
+        assert_eq!(prepared.document.title.as_deref(), Some("Garten"));
+        let request = job_request(&prepared, "garten").unwrap();
+        assert_eq!(request["kind"], "ingest");
+        let document = &request["documents"][0];
+        assert_eq!(document["namespace"], "garten");
+        assert_eq!(document["meta"]["kind"], "html");
+        assert_eq!(
+            document["chunks"][0]["meta"],
+            json!({ "section": 0, "headings": ["Beete"], "start": 0, "end": 24 })
+        );
+        assert_eq!(document["source_ref"]["origin"], "ingest");
+        assert_eq!(document["source_ref"]["trust_level"], "medium");
+        assert_eq!(document["source_ref"]["injected_by"], "hauski-cli");
+        assert_eq!(
+            document["source_ref"]["id"],
+            path.canonicalize().unwrap().to_string_lossy().as_ref()
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! `hauski logs tail`: Server-Logs lesen und filtern.
+//!
+//! Quelle ist das Journal der systemd-User-Unit (`--unit`, Default
+//! `hauski-core`) oder eine Logdatei (`--file`, Default `HAUSKI_LOG_FILE`).
+//! Verstanden werden beide Ausgabeformate des Servers: Text und JSON-Zeilen
+//! (`HAUSKI_LOG_FORMAT=json`). Filter: Mindest-Level (`--level warn`),
+//! Request-ID (`--request-id`, aus dem Antwort-Header `x-request-id`) und
+//! Route-Präfix (`--route /index`). `-n` gilt vor dem Filtern.
+
+use std::{
+    collections::VecDeque,
+    env,
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+
+/// Poll interval when following a log file.
+const FOLLOW_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Subcommand, Debug)]
+pub enum LogsCmd {
+    /// Letzte Logzeilen zeigen und optional folgen, gefiltert nach Level, Request-ID, Route
+    Tail(TailArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TailArgs {
+    /// systemd-User-Unit, deren Journal gelesen wird
+    #[arg(long, default_value = "hauski-core")]
+    pub unit: String,
+    /// Logdatei statt Journal (Default: `HAUSKI_LOG_FILE`, falls gesetzt)
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+    /// Mindest-Level
+    #[arg(long, value_enum)]
+    pub level: Option<Level>,
+    /// Nur Einträge dieser Request-ID (Header `x-request-id`)
+    #[arg(long)]
+    pub request_id: Option<String>,
+    /// Nur Einträge, deren Route mit diesem Pfad beginnt, z. B. `/index`
+    #[arg(long)]
+    pub route: Option<String>,
+    /// Anzahl Zeilen (vor dem Filtern)
+    #[arg(long, short = 'n', default_value_t = 200)]
+    pub lines: usize,
+    /// Neuen Einträgen folgen
+    #[arg(long, short, default_value_t = false)]
+    pub follow: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn parse(token: &str) -> Option<Self> {
+        match token.to_ascii_uppercase().as_str() {
+            "TRACE" => Some(Self::Trace),
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "ERROR" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// One log line with the fields the filters look at.
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    level: Option<Level>,
+    request_id: Option<String>,
+    route: Option<String>,
+    /// Parsed JSON line (`HAUSKI_LOG_FORMAT=json`).
+    json: Option<Value>,
+    /// Line without ANSI colours.
+    text: String,
+}
+
+fn parse_line(line: &str) -> Entry {
+    let text = strip_ansi(line.trim_end());
+    if let Some(value) = text
+        .starts_with('{')
+        .then(|| serde_json::from_str::<Value>(&text).ok())
+        .flatten()
+    {
+        return Entry {
+            level: value["level"].as_str().and_then(Level::parse),
+            request_id: json_field(&value, "request_id"),
+            route: json_field(&value, "route"),
+            json: Some(value),
+            text,
+        };
+    }
+    Entry {
+        level: text.split_whitespace().take(3).find_map(Level::parse),
+        request_id: text_field(&text, "request_id"),
+        route: text_field(&text, "route"),
+        json: None,
+        text,
+    }
+}
+
+/// `key` from the event fields, the current span or any parent span.
+fn json_field(value: &Value, key: &str) -> Option<String> {
+    let spans = value["spans"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    [&value["fields"], &value["span"]]
+        .into_iter()
+        .chain(spans.iter().rev())
+        .find_map(|object| object[key].as_str())
+        .map(str::to_string)
+}
+
+/// `key=value` as written by the text formatter, e.g. in `request{request_id=… route=/ask}:`.
+fn text_field(text: &str, key: &str) -> Option<String> {
+    let needle = format!("{key}=");
+    let mut offset = 0;
+    while let Some(found) = text[offset..].find(&needle) {
+        let start = offset + found;
+        let boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c == '{' || c.is_whitespace());
+        if boundary {
+            let value: String = text[start + needle.len()..]
+                .chars()
+                .take_while(|c| !c.is_whitespace() && *c != '}')
+                .collect();
+            return Some(value.trim_end_matches(':').to_string());
+        }
+        offset = start + needle.len();
+    }
+    None
+}
+
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI sequence: ESC [ … final byte in @..~
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+struct Filter {
+    level: Option<Level>,
+    request_id: Option<String>,
+    route: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, entry: &Entry) -> bool {
+        let level = self
+            .level
+            .is_none_or(|min| entry.level.is_some_and(|level| level >= min));
+        let request_id = self
+            .request_id
+            .as_ref()
+            .is_none_or(|id| entry.request_id.as_ref() == Some(id));
+        let route = self.route.as_ref().is_none_or(|prefix| {
+            entry
+                .route
+                .as_ref()
+                .is_some_and(|route| route.starts_with(prefix.as_str()))
+        });
+        level && request_id && route
+    }
+}
+
+pub fn run(cmd: LogsCmd, json: bool) -> Result<()> {
+    let LogsCmd::Tail(args) = cmd;
+    let filter = Filter {
+        level: args.level,
+        request_id: args.request_id.clone(),
+        route: args.route.clone(),
+    };
+    let json_mode = json;
+    let mut emit = |line: &str| {
+        let entry = parse_line(line);
+        if filter.matches(&entry) {
+            println!("{}", render(&entry, json_mode));
+        }
+    };
+
+    let file = args.file.clone().or_else(|| {
+        env::var("HAUSKI_LOG_FILE")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from)
+    });
+    match file {
+        Some(path) => tail_file(&path, args.lines, args.follow, &mut emit),
+        None => tail_journal(&args.unit, args.lines, args.follow, &mut emit),
+    }
+}
+
+fn tail_journal(unit: &str, lines: usize, follow: bool, emit: &mut dyn FnMut(&str)) -> Result<()> {
+    let lines = lines.to_string();
+    // `-o cat`: only the message, i.e. exactly what the server wrote.
+    let mut argv = vec![
+        "--user",
+        "-u",
+        unit,
+        "-n",
+        &lines,
+        "-o",
+        "cat",
+        "--no-pager",
+    ];
+    if follow {
+        argv.push("-f");
+    }
+    let mut child = Command::new("journalctl")
+        .args(&argv)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("journalctl nicht ausführbar")?;
+    let stdout = child.stdout.take().context("journalctl ohne stdout")?;
+    for line in BufReader::new(stdout).lines() {
+        emit(&line?);
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("journalctl {} fehlgeschlagen", argv.join(" "));
+    }
+    Ok(())
+}
+
+/// Emits the last `lines` lines of `path`; with `follow`, polls for appended
+/// lines and starts over when the file shrinks (rotation, truncation).
+fn tail_file(path: &Path, lines: usize, follow: bool, emit: &mut dyn FnMut(&str)) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("{} nicht lesbar", path.display()))?;
+    let mut last = VecDeque::with_capacity(lines);
+    for line in BufReader::new(&mut file).lines() {
+        if last.len() == lines {
+            last.pop_front();
+        }
+        if lines > 0 {
+            last.push_back(line?);
+        }
+    }
+    for line in &last {
+        emit(line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut position = file.stream_position()?;
+    let mut partial = String::new();
+    loop {
+        thread::sleep(FOLLOW_POLL);
+        let len = std::fs::metadata(path)?.len();
+        if len < position {
+            file = File::open(path)?;
+            position = 0;
+            partial.clear();
+        }
+        if len == position {
+            continue;
+        }
+        file.seek(SeekFrom::Start(position))?;
+        let mut chunk = Vec::new();
+        position += file.read_to_end(&mut chunk)? as u64;
+        partial.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(newline) = partial.find('\n') {
+            let line: String = partial.drain(..=newline).collect();
+            emit(line.trim_end_matches(['\n', '\r']));
+        }
+    }
+}
+
+fn render(entry: &Entry, json_mode: bool) -> String {
+    match (&entry.json, json_mode) {
+        (Some(value), true) => value.to_string(),
+        (None, true) => json!({
+            "level": entry.level.map(|level| format!("{level:?}").to_uppercase()),
+            "request_id": entry.request_id,
+            "route": entry.route,
+            "line": entry.text,
+        })
+        .to_string(),
+        (Some(value), false) => {
+            let mut line = format!(
+                "{} {:>5}",
+                value["timestamp"].as_str().unwrap_or_default(),
+                value["level"].as_str().unwrap_or_default()
+            );
+            if let Some(id) = &entry.request_id {
+                line.push_str(&format!(" [{id}"));
+                if let Some(route) = &entry.route {
+                    line.push_str(&format!(" {route}"));
+                }
+                line.push(']');
+            }
+            if let Some(target) = value["target"].as_str() {
+                line.push_str(&format!(" {target}:"));
+            }
+            if let Some(fields) = value["fields"].as_object() {
+                if let Some(message) = fields.get("message").and_then(Value::as_str) {
+                    line.push_str(&format!(" {message}"));
+                }
+                for (key, field) in fields.iter().filter(|(key, _)| *key != "message") {
+                    match field.as_str() {
+                        Some(text) => line.push_str(&format!(" {key}={text}")),
+                        None => line.push_str(&format!(" {key}={field}")),
+                    }
+                }
+            }
+            line
+        }
+        (None, false) => entry.text.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "2025-01-01T10:00:00.000Z \u{1b}[32m INFO\u{1b}[0m request{request_id=66f-1f method=POST route=/index/search}: hauski_core::request_log: request finished status=200 latency_ms=4.2";
+    const JSON: &str = r#"{"timestamp":"2025-01-01T10:00:01Z","level":"WARN","fields":{"message":"request failed","status":503,"latency_ms":12.5},"target":"hauski_core::request_log","span":{"method":"POST","name":"request","request_id":"ci-7","route":"/v1/chat"},"spans":[{"method":"POST","name":"request","request_id":"ci-7","route":"/v1/chat"}]}"#;
+
+    #[test]
+    fn parses_text_and_json_lines() {
+        let text = parse_line(TEXT);
+        assert_eq!(text.level, Some(Level::Info));
+        assert_eq!(text.request_id.as_deref(), Some("66f-1f"));
+        assert_eq!(text.route.as_deref(), Some("/index/search"));
+        assert!(!text.text.contains('\u{1b}'));
+
+        let json = parse_line(JSON);
+        assert_eq!(json.level, Some(Level::Warn));
+        assert_eq!(json.request_id.as_deref(), Some("ci-7"));
+        assert_eq!(json.route.as_deref(), Some("/v1/chat"));
+        assert_eq!(
+            render(&json, false),
+            "2025-01-01T10:00:01Z  WARN [ci-7 /v1/chat] hauski_core::request_log: request failed latency_ms=12.5 status=503"
+        );
+    }
+
+    #[test]
+    fn filters_by_level_request_id_and_route() {
+        let entries = [
+            parse_line(TEXT),
+            parse_line(JSON),
+            parse_line("panicked at main.rs"),
+        ];
+        let count = |filter: Filter| entries.iter().filter(|e| filter.matches(e)).count();
+        let filter = |level, request_id: Option<&str>, route: Option<&str>| Filter {
+            level,
+            request_id: request_id.map(str::to_string),
+            route: route.map(str::to_string),
+        };
+
+        assert_eq!(count(filter(None, None, None)), 3);
+        assert_eq!(count(filter(Some(Level::Warn), None, None)), 1);
+        assert_eq!(count(filter(None, Some("66f-1f"), None)), 1);
+        assert_eq!(count(filter(None, None, Some("/index"))), 1);
+        assert_eq!(count(filter(Some(Level::Info), None, Some("/v1"))), 1);
+    }
+
+    #[test]
+    fn tail_file_keeps_the_last_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hauski.log");
+        std::fs::write(&path, "eins\nzwei\ndrei\n").unwrap();
+        let mut seen = Vec::new();
+        tail_file(&path, 2, false, &mut |line| seen.push(line.to_string())).unwrap();
+        assert_eq!(seen, ["zwei", "drei"]);
+    }
+}
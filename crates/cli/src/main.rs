@@ -1,22 +1,47 @@
 use anyhow::{anyhow, bail, Context, Result};
 use axum::http::HeaderValue;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use serde::Deserialize;
 use std::{
     env,
-    io::{self, IsTerminal, Write},
     net::SocketAddr,
     path::{Path, PathBuf},
 };
 use tokio::{net::TcpListener, runtime::Builder as RuntimeBuilder, signal};
 use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use url::Url;
 
 use hauski_core::{
-    build_app_with_state, intent, load_flags, load_limits, load_models, load_routing, ModelsFile,
+    build_app_from_config, collect_config_from, collect_layered, intent, load_layered, load_models,
+    load_routing, read_config_file, strict_problems, ConfigErrors, ConfigLayers, FallbackPaths,
+    ModelsFile, RoutingPolicy,
 };
 
+mod ask;
+mod asr;
+mod audio;
+mod backup;
+mod bench;
+mod chat;
+mod config_init;
+mod config_migrate;
+mod doctor;
+mod embed;
+mod forget;
+mod index;
+mod ingest;
+mod logs;
+mod memory;
+mod models;
+mod playbook;
+mod quarantine;
+mod remote;
+mod retention;
+mod service;
+mod status;
+mod vault;
+mod watch;
+
 #[derive(Parser, Debug)]
 #[command(name = "hauski", version, about = "HausKI CLI")]
 struct Cli {
@@ -24,6 +49,15 @@ struct Cli {
     #[arg(long, short, global = true)]
     verbose: bool,
 
+    /// Maschinenlesbare Ausgabe als JSON (auch per `HAUSKI_OUTPUT=json`)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Entfernter HausKI-Core, z. B. `nas` oder https://hauski.example (auch per
+    /// `HAUSKI_HOST`); das API-Token kommt aus `HAUSKI_TOKEN`
+    #[arg(long, global = true)]
+    host: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -40,18 +74,19 @@ enum Commands {
         /// Bind-Adresse überschreiben (z. B. 0.0.0.0:8080)
         #[arg(long)]
         bind: Option<String>,
+        /// Konfigurationswert setzen, stärkste Schicht (z. B. flags.safe_mode=true)
+        #[arg(long = "set", value_name = "ABSCHNITT.SCHLÜSSEL=WERT")]
+        set: Vec<String>,
     },
-    /// ASR-Werkzeuge
-    #[command(hide = true)] // Experimental/internal: hidden until stabilized
+    /// ASR-Werkzeuge (whisper.cpp)
     Asr {
         #[command(subcommand)]
         cmd: AsrCmd,
     },
     /// Audio-Profile (`PipeWire`)
-    #[command(hide = true)] // Experimental/internal: hidden until stabilized
     Audio {
         #[command(subcommand)]
-        cmd: AudioCmd,
+        cmd: audio::AudioCmd,
     },
     /// Konfigurationswerkzeuge
     Config {
@@ -66,7 +101,93 @@ enum Commands {
         /// Alle Schritte ohne Bestätigung ausführen
         #[arg(long, short = 'y', default_value_t = false)]
         yes: bool,
+        /// Rohe Shell-Schritte (`run:`) erlauben – nur für vertrauenswürdige Playbooks
+        #[arg(long, default_value_t = false)]
+        unsafe_shell: bool,
+        /// Freigegebene Pfade für `file_read` (mehrfach möglich; Default: aktuelles Verzeichnis)
+        #[arg(long = "allow-read")]
+        allow_read: Vec<PathBuf>,
+        /// Basis-URL des HausKI-Cores für `index_upsert`/`memory_set`
+        /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+        #[arg(long)]
+        base_url: Option<String>,
+        /// Playbook-Variable setzen, überschreibt `vars:` (mehrfach möglich)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = playbook::parse_var)]
+        vars: Vec<(String, String)>,
+        /// Höchstzahl gleichzeitig laufender Schritte (nur mit `--yes`)
+        #[arg(long, default_value_t = 4)]
+        parallel: usize,
+    },
+    /// Dokumente im Index ablegen, suchen, zählen und vergessen
+    Index {
+        #[command(flatten)]
+        opts: index::IndexOptions,
+        #[command(subcommand)]
+        cmd: index::IndexCmd,
+    },
+    /// Dokumente in Quarantäne sichten, freigeben oder löschen
+    Quarantine {
+        #[command(flatten)]
+        opts: quarantine::QuarantineOptions,
+        #[command(subcommand)]
+        cmd: quarantine::QuarantineCmd,
+    },
+    /// Retention je Namespace ansehen, setzen und Decay vorab prüfen
+    Retention {
+        #[command(flatten)]
+        opts: retention::RetentionOptions,
+        #[command(subcommand)]
+        cmd: retention::RetentionCmd,
     },
+    /// Verzeichnis beobachten und Änderungen laufend in den Index übernehmen
+    Watch(watch::WatchArgs),
+    /// PDF, HTML, DOCX und EPUB extrahieren, chunken und in den Index übernehmen
+    Ingest(ingest::IngestArgs),
+    /// Obsidian-Vault (Frontmatter, Wikilinks, Anhänge) in den Index übernehmen
+    Vault {
+        #[command(subcommand)]
+        cmd: vault::VaultCmd,
+    },
+    /// Text mit einem Embedder aus models.yml einbetten (JSON oder npy)
+    Embed(embed::EmbedArgs),
+    /// Frage an den Index (`/ask`), optional mit extraktiver Antwort
+    Ask(ask::AskArgs),
+    /// Interaktiver Chat (REPL) oder einzelne Frage per Pipe
+    Chat {
+        #[command(flatten)]
+        opts: chat::ChatOptions,
+    },
+    /// Arbeitsgedächtnis lesen, setzen, räumen und auflisten
+    Memory {
+        #[command(flatten)]
+        opts: memory::MemoryOptions,
+        #[command(subcommand)]
+        cmd: memory::MemoryCmd,
+    },
+    /// Dokumente aus dem Index vergessen (Vorschau, getippte Bestätigung, Audit)
+    Forget(forget::ForgetArgs),
+    /// Index, Arbeitsgedächtnis und Konfiguration in ein Archiv sichern (tar.zst)
+    Export(backup::ExportArgs),
+    /// Archiv aus `hauski export` prüfen und einspielen
+    Import(backup::ImportArgs),
+    /// `hauski serve` als systemd-User-Dienst installieren und steuern
+    Service {
+        #[command(flatten)]
+        opts: service::ServiceOptions,
+        #[command(subcommand)]
+        cmd: service::ServiceCmd,
+    },
+    /// Prüft Konfiguration, Speicherorte, Upstreams, GPU, Port und Plattenplatz
+    Doctor(doctor::DoctorArgs),
+    /// Kurzüberblick über den laufenden Core (Bereitschaft, Index, Memory, Budgets)
+    Status(status::StatusArgs),
+    /// Server-Logs (Journal oder Datei) gefiltert lesen
+    Logs {
+        #[command(subcommand)]
+        cmd: logs::LogsCmd,
+    },
+    /// Last auf /ask, /index/search und /v1/chat, p50/p95/p99 gegen die Budgets
+    Bench(bench::BenchArgs),
     /// Bestimmt den Intent aus dem aktuellen Kontext (Git/CI)
     Intent {
         /// Optional: Ausgabe in Datei (sonst stdout)
@@ -82,36 +203,41 @@ enum Commands {
 enum ModelsCmd {
     /// verfügbare Modelle anzeigen (aus configs/models.yml)
     Ls,
-    /// Modell herunterladen/registrieren
-    #[command(hide = true)] // Experimental/internal: hidden until stabilized
-    Pull { id: String },
+    /// Modell herunterladen, per SHA256 prüfen und in models.yml eintragen
+    Pull(models::PullArgs),
+    /// Lokale Modelldatei löschen (Eintrag in models.yml bleibt)
+    Rm(models::RmArgs),
+    /// Dateien aller Modelle gegen die hinterlegte SHA256 prüfen
+    Verify(models::VerifyArgs),
 }
 
 #[derive(Subcommand, Debug)]
 enum AsrCmd {
-    /// Datei transkribieren (Stub)
-    Transcribe {
-        input: String,
-        #[arg(long)]
-        model: Option<String>,
-        #[arg(long)]
-        out: Option<String>,
-    },
-}
-
-#[derive(Subcommand, Debug)]
-enum AudioCmd {
-    /// Audio-Profil setzen (Stub)
-    ProfileSet { profile: String },
+    /// Audiodatei transkribieren (Text, SRT, VTT oder JSON)
+    Transcribe(asr::TranscribeArgs),
 }
 
 #[derive(Subcommand, Debug)]
 enum ConfigCmd {
+    /// Erzeugt hauski.yml, flags.yaml, models.yml und limits.yaml für diese Maschine
+    Init(config_init::InitArgs),
     /// Validiert die HausKI-Konfiguration
     Validate {
         /// Pfad zur YAML-Datei
         #[arg(long, default_value = "./configs/hauski.yml")]
         file: String,
+        /// Zusätzlich Einzeldateien und alles prüfen, was der Server sonst nur
+        /// mit Warnung toleriert (wie HAUSKI_CONFIG_STRICT=1)
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Prüft die Schema-Versionen der Konfigurationsdateien und hebt ältere an
+    Migrate(config_migrate::MigrateArgs),
+    /// Zeigt die zusammengeführte Server-Konfiguration mit Quelle je Schlüssel
+    Effective {
+        /// Wert wie bei `hauski serve --set` überschreiben
+        #[arg(long = "set", value_name = "ABSCHNITT.SCHLÜSSEL=WERT")]
+        set: Vec<String>,
     },
 }
 
@@ -120,6 +246,11 @@ fn main() -> Result<()> {
     if cli.verbose {
         eprintln!("verbose on");
     }
+    let json = json_output(cli.json);
+    let output = OutputArgs { json };
+    if let Some(host) = cli.host {
+        remote::set_host(host);
+    }
 
     match cli.command {
         Commands::Models { cmd } => match cmd {
@@ -127,38 +258,155 @@ fn main() -> Result<()> {
                 let path = std::env::var("HAUSKI_MODELS")
                     .unwrap_or_else(|_| "./configs/models.yml".to_string());
                 let file = load_models(&path)?;
-                print_models_table(&file);
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&file)?);
+                } else {
+                    print_models_table(&file);
+                }
+            }
+            ModelsCmd::Pull(mut args) => {
+                args.output = output;
+                models::pull(args, playbook_routing_policy())?
             }
-            ModelsCmd::Pull { id } => {
-                eprintln!("Error: Feature not implemented (stub).");
-                println!("(stub) models pull {id}");
-                std::process::exit(1);
+            ModelsCmd::Rm(mut args) => {
+                args.output = output;
+                models::rm(args, &playbook_routing_policy())?
+            }
+            ModelsCmd::Verify(mut args) => {
+                args.output = output;
+                models::verify(args)?
             }
         },
-        Commands::Serve { bind } => {
-            run_core_server(bind)?;
+        Commands::Serve { bind, set } => {
+            run_core_server(bind, set)?;
         }
         Commands::Asr { cmd } => match cmd {
-            AsrCmd::Transcribe { input, model, out } => {
-                eprintln!("Error: Feature not implemented (stub).");
-                println!("(stub) asr transcribe {input} --model {model:?} --out {out:?}");
-                std::process::exit(1);
-            }
-        },
-        Commands::Audio { cmd } => match cmd {
-            AudioCmd::ProfileSet { profile } => {
-                eprintln!("Error: Feature not implemented (stub).");
-                println!("(stub) audio profile set {profile}");
-                std::process::exit(1);
+            AsrCmd::Transcribe(mut args) => {
+                args.output = output;
+                asr::transcribe(args)?
             }
         },
+        Commands::Audio { cmd } => audio::run(cmd, json)?,
         Commands::Config { cmd } => match cmd {
-            ConfigCmd::Validate { file } => {
-                validate_config(&file)?;
+            ConfigCmd::Init(mut args) => {
+                args.output = output;
+                config_init::run(args)?
+            }
+            ConfigCmd::Validate { file, strict } => {
+                validate_config(&file, json, strict)?;
+            }
+            ConfigCmd::Migrate(mut args) => {
+                args.output = output;
+                config_migrate::run(args)?
             }
+            ConfigCmd::Effective { set } => print_effective_config(set, json)?,
         },
-        Commands::Assist { playbook, yes } => {
-            run_playbook(&playbook, yes)?;
+        Commands::Assist {
+            playbook,
+            yes,
+            unsafe_shell,
+            allow_read,
+            base_url,
+            vars,
+            parallel,
+        } => {
+            let opts = playbook::RunOptions {
+                yes,
+                unsafe_shell,
+                base_url: remote::base_url(base_url),
+                read_roots: if allow_read.is_empty() {
+                    vec![PathBuf::from(".")]
+                } else {
+                    allow_read
+                },
+                routing: playbook_routing_policy(),
+                memory_token: env::var("HAUSKI_MEMORY_TOKEN")
+                    .ok()
+                    .filter(|token| !token.trim().is_empty()),
+                api_token: remote::token(),
+                json,
+                vars,
+                parallel,
+            };
+            playbook::run_playbook(&playbook, &opts)?;
+        }
+        Commands::Embed(mut args) => {
+            args.output = output;
+            let code = embed::run(args)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Ask(mut args) => {
+            args.output = output;
+            ask::run(args)?;
+        }
+        Commands::Index { mut opts, cmd } => {
+            opts.output = output;
+            index::run(opts, cmd)?;
+        }
+        Commands::Quarantine { mut opts, cmd } => {
+            opts.output = output;
+            quarantine::run(opts, cmd)?;
+        }
+        Commands::Retention { mut opts, cmd } => {
+            opts.output = output;
+            retention::run(opts, cmd)?;
+        }
+        Commands::Watch(mut args) => {
+            args.output = output;
+            watch::run(args)?
+        }
+        Commands::Ingest(mut args) => {
+            args.output = output;
+            ingest::run(args)?
+        }
+        Commands::Vault { cmd } => vault::run(cmd, json)?,
+        Commands::Chat { mut opts } => {
+            opts.output = output;
+            chat::run(opts)?;
+        }
+        Commands::Forget(mut args) => {
+            args.output = output;
+            forget::run(args)?
+        }
+        Commands::Export(mut args) => {
+            args.output = output;
+            backup::export(args)?
+        }
+        Commands::Import(mut args) => {
+            args.output = output;
+            backup::import(args)?
+        }
+        Commands::Service { mut opts, cmd } => {
+            opts.output = output;
+            service::run(opts, cmd)?
+        }
+        Commands::Doctor(mut args) => {
+            args.output = output;
+            let code = doctor::run(args)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Status(mut args) => {
+            args.output = output;
+            let code = status::run(args)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Logs { cmd } => logs::run(cmd, json)?,
+        Commands::Bench(mut args) => {
+            args.output = output;
+            let code = bench::run(args)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Memory { mut opts, cmd } => {
+            opts.output = output;
+            memory::run(opts, cmd)?;
         }
         Commands::Intent { output, format } => {
             run_intent(output, format)?;
@@ -168,6 +416,30 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Output mode shared by the subcommands, set from the global `--json` after
+/// parsing.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub(crate) struct OutputArgs {
+    /// JSON statt Tabelle/Text (globales `--json` bzw. `HAUSKI_OUTPUT=json`)
+    #[arg(skip)]
+    pub json: bool,
+}
+
+/// JSON output requested via `--json` or `HAUSKI_OUTPUT=json`.
+fn json_output(flag: bool) -> bool {
+    flag || env::var("HAUSKI_OUTPUT").is_ok_and(|value| value.trim().eq_ignore_ascii_case("json"))
+}
+
+/// Prints a human-readable status line: stdout normally, stderr in JSON mode
+/// so stdout stays machine-readable.
+pub(crate) fn say(json: bool, line: impl std::fmt::Display) {
+    if json {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
 fn run_intent(output_path: Option<String>, format: String) -> Result<()> {
     let ctx = intent::gather_context()?;
     let resolver = intent::IntentResolver::default();
@@ -202,75 +474,19 @@ fn run_intent(output_path: Option<String>, format: String) -> Result<()> {
     Ok(())
 }
 
-fn run_playbook(playbook_path: &str, yes: bool) -> Result<()> {
-    let content = std::fs::read_to_string(playbook_path)
-        .with_context(|| format!("Could not read playbook file: {playbook_path}"))?;
-    let playbook: serde_yaml_ng::Value = serde_yaml_ng::from_str(&content)
-        .with_context(|| format!("Could not parse playbook file: {playbook_path}"))?;
-
-    if let Some(steps) = playbook.get("steps").and_then(|s| s.as_sequence()) {
-        for (i, step) in steps.iter().enumerate() {
-            if let Some(run_cmd) = step.get("run").and_then(|r| r.as_str()) {
-                if !yes {
-                    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
-                        bail!(
-                            "Confirmation required for step {}: '{}'. Use --yes to bypass (stdin/stderr not a TTY).",
-                            i + 1,
-                            run_cmd
-                        );
-                    }
-                    eprintln!("\n--- Step {} (of {}):", i + 1, steps.len());
-                    eprintln!("Command: {}", run_cmd);
-                    eprint!("Execute this step? [y/N] ");
-                    io::stderr().flush()?;
-
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input)?;
-                    let input = input.trim().to_lowercase();
-                    if input != "y" && input != "yes" {
-                        bail!("Execution aborted by user at step {}.", i + 1);
-                    }
-                }
-
-                info!("Executing step {}: {}", i + 1, run_cmd);
-                let output = std::process::Command::new("sh")
-                    .arg("-c")
-                    .arg(run_cmd)
-                    .output()
-                    .with_context(|| format!("Failed to execute command: {run_cmd}"))?;
-
-                if !output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let mut error_output = String::new();
-                    if !stdout.is_empty() {
-                        error_output.push_str("stdout:\n");
-                        error_output.push_str(&stdout);
-                    }
-                    if !stderr.is_empty() {
-                        if !error_output.is_empty() {
-                            error_output.push('\n');
-                        }
-                        error_output.push_str("stderr:\n");
-                        error_output.push_str(&stderr);
-                    }
-                    bail!(
-                        "Step {} failed with status {}:\n{}",
-                        i + 1,
-                        output.status,
-                        error_output
-                    );
-                }
-            }
-        }
-    }
-
-    Ok(())
+/// Egress-Policy für `http_call`; ohne lesbare `routing.yaml` wird alles verweigert.
+fn playbook_routing_policy() -> RoutingPolicy {
+    let routing_path =
+        env::var("HAUSKI_ROUTING").unwrap_or_else(|_| "./policies/routing.yaml".into());
+    load_routing(&routing_path).unwrap_or_else(|err| {
+        warn!("routing policy unavailable ({err}) – denying all http_call egress");
+        RoutingPolicy::deny_all()
+    })
 }
 
 // ---- Modelle (nutzt hauski_core::ModelsFile) ----
 
-fn build_table_separator(widths: &[usize; 4]) -> String {
+fn build_table_separator<const N: usize>(widths: &[usize; N]) -> String {
     let mut parts = Vec::with_capacity(widths.len());
     for &width in widths {
         parts.push("-".repeat(width + 2));
@@ -278,7 +494,7 @@ fn build_table_separator(widths: &[usize; 4]) -> String {
     format!("+{}+", parts.join("+"))
 }
 
-fn format_table_row(columns: [&str; 4], widths: &[usize; 4]) -> String {
+fn format_table_row<const N: usize>(columns: [&str; N], widths: &[usize; N]) -> String {
     let mut formatted = String::new();
     formatted.push('|');
     for (idx, column) in columns.iter().enumerate() {
@@ -295,50 +511,72 @@ fn format_table_row(columns: [&str; 4], widths: &[usize; 4]) -> String {
 fn print_models_table(file: &ModelsFile) {
     if file.models.is_empty() {
         println!("Keine Modelle in der Konfiguration gefunden.");
-        return;
+    } else {
+        let rows = file
+            .models
+            .iter()
+            .map(|model| {
+                let vram = model
+                    .vram_min_gb
+                    .map(|value| format!("{value} GB"))
+                    .unwrap_or_default();
+                let canary = model
+                    .canary
+                    .map(|value| value.to_string())
+                    .unwrap_or_default();
+                [model.id.clone(), model.path.clone(), vram, canary]
+            })
+            .collect();
+        print_table(["ID", "Path", "VRAM Min", "Canary"], rows);
     }
 
-    const HEADERS: [&str; 4] = ["ID", "Path", "VRAM Min", "Canary"];
-
-    let mut rows: Vec<[String; 4]> = Vec::new();
-    let mut widths = HEADERS.map(|header| header.chars().count());
-
-    for model in &file.models {
-        let vram = model
-            .vram_min_gb
-            .map(|value| format!("{value} GB"))
-            .unwrap_or_default();
-        let canary = model
-            .canary
-            .map(|value| value.to_string())
-            .unwrap_or_default();
-
-        let row = [model.id.clone(), model.path.clone(), vram, canary];
+    if !file.embedders.is_empty() {
+        println!();
+        let rows = file
+            .embedders
+            .iter()
+            .map(|embedder| {
+                let id = if embedder.default {
+                    format!("{} (default)", embedder.id)
+                } else {
+                    embedder.id.clone()
+                };
+                let provider = serde_json::to_value(embedder.provider)
+                    .ok()
+                    .and_then(|value| value.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                [
+                    id,
+                    format!("{provider}:{}", embedder.model),
+                    embedder.dimension.to_string(),
+                    embedder.max_tokens.to_string(),
+                ]
+            })
+            .collect();
+        print_table(
+            ["Embedder", "Provider:Model", "Dimension", "Max Tokens"],
+            rows,
+        );
+    }
+}
 
+fn print_table<const N: usize>(headers: [&str; N], rows: Vec<[String; N]>) {
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in &rows {
         for (idx, column) in row.iter().enumerate() {
             widths[idx] = widths[idx].max(column.chars().count());
         }
-
-        rows.push(row);
     }
 
     let separator = build_table_separator(&widths);
     println!("{separator}");
-    println!("{}", format_table_row(HEADERS, &widths));
+    println!("{}", format_table_row(headers, &widths));
     println!("{separator}");
 
     for row in &rows {
         println!(
             "{}",
-            format_table_row(
-                [
-                    row[0].as_str(),
-                    row[1].as_str(),
-                    row[2].as_str(),
-                    row[3].as_str(),
-                ],
-                &widths,
-            )
+            format_table_row(row.each_ref().map(String::as_str), &widths)
         );
     }
 
@@ -349,11 +587,19 @@ fn print_models_table(file: &ModelsFile) {
 
 #[derive(Debug, Deserialize)]
 struct HauskiConfig {
+    data_dir: Option<String>,
+    server: Option<ServerConfig>,
     index: Option<IndexConfig>,
     budgets: Option<BudgetsConfig>,
     plugins: Option<PluginsConfig>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ServerConfig {
+    host: Option<String>,
+    port: Option<u16>,
+}
+
 #[derive(Debug, Deserialize)]
 struct IndexConfig {
     path: String,
@@ -377,7 +623,24 @@ struct PluginsConfig {
     enabled: Option<Vec<String>>,
 }
 
-fn validate_config(file: &str) -> Result<()> {
+/// A `hauski.yml` that passed validation, plus non-fatal findings.
+struct CheckedConfig {
+    path: PathBuf,
+    config: HauskiConfig,
+    index_path: PathBuf,
+    warnings: Vec<String>,
+}
+
+impl CheckedConfig {
+    fn index(&self) -> &IndexConfig {
+        self.config
+            .index
+            .as_ref()
+            .expect("checked config has an index block")
+    }
+}
+
+fn check_config(file: &str, strict: bool) -> Result<CheckedConfig> {
     let expanded_path = shellexpand::full(file)?;
     let path = PathBuf::from(expanded_path.as_ref());
     if !path.exists() {
@@ -392,65 +655,135 @@ fn validate_config(file: &str) -> Result<()> {
     })?;
     let config: HauskiConfig = serde_yaml_ng::from_str(&content)
         .context("Konfiguration konnte nicht als YAML geparst werden")?;
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let index_path = match &config.index {
+        Some(index) => check_index_config(index, &mut errors, &mut warnings)?,
+        None => {
+            errors.push("index-Block fehlt".to_string());
+            None
+        }
+    };
 
-    let index = config
-        .index
-        .as_ref()
-        .ok_or_else(|| anyhow!("index-Block fehlt"))?;
-
-    if index.path.trim().is_empty() {
-        bail!("index.path darf nicht leer sein");
+    if let Some(budgets) = &config.budgets {
+        if budgets.index_topk20_ms.is_none() {
+            warnings.push("budgets.index_topk20_ms ist nicht gesetzt".to_string());
+        }
+    } else {
+        warnings.push("budgets-Block fehlt".to_string());
     }
 
-    let expanded_index_path = shellexpand::full(&index.path)?;
-    let index_path = PathBuf::from(expanded_index_path.as_ref());
-    if !index_path.is_absolute() {
-        bail!("index.path muss ein absoluter Pfad sein (nach Expansion)");
+    match config
+        .plugins
+        .as_ref()
+        .map(|plugins| plugins.enabled.as_ref())
+    {
+        Some(Some(enabled)) => {
+            if !enabled.iter().any(|entry| entry == "obsidian_index") {
+                errors.push("plugins.enabled muss obsidian_index enthalten".to_string());
+            }
+        }
+        Some(None) => errors.push("plugins.enabled fehlt".to_string()),
+        None => errors.push("plugins-Block fehlt".to_string()),
     }
 
-    if let Some(parent) = index_path.parent() {
-        if !parent.exists() {
-            eprintln!(
-                "warn: Index-Verzeichnis {} existiert noch nicht (wird bei erstem Lauf erstellt)",
-                parent.display()
-            );
+    // Server-Abschnitte (limits, models, routing, flags, …) wie beim Start prüfen;
+    // strikt zusätzlich samt Einzeldateien und allem, was der Server sonst toleriert.
+    if strict {
+        match collect_config_from(Some(&path), &FallbackPaths::from_env()) {
+            Ok(server) => errors.extend(strict_problems(&server)),
+            Err(err) => errors.extend(err.0),
         }
+    } else if let Err(err) = read_config_file(&path) {
+        errors.push(format!("Server-Abschnitte ungültig: {err}"));
     }
 
-    Url::parse(&index.provider.url).context("index.provider.url ist keine gültige URL")?;
+    match index_path {
+        Some(index_path) if errors.is_empty() => Ok(CheckedConfig {
+            path,
+            config,
+            index_path,
+            warnings,
+        }),
+        _ => Err(ConfigErrors(errors).into()),
+    }
+}
 
+/// Checks the `index` block; returns the expanded index path if it is usable.
+fn check_index_config(
+    index: &IndexConfig,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) -> Result<Option<PathBuf>> {
+    if Url::parse(&index.provider.url).is_err() {
+        errors.push("index.provider.url ist keine gültige URL".to_string());
+    }
     if index.provider.embedder.trim().is_empty() {
-        bail!("index.provider.embedder darf nicht leer sein");
+        errors.push("index.provider.embedder darf nicht leer sein".to_string());
     }
-
     if index.provider.model.trim().is_empty() {
-        bail!("index.provider.model darf nicht leer sein");
+        errors.push("index.provider.model darf nicht leer sein".to_string());
     }
 
-    if let Some(budgets) = &config.budgets {
-        if budgets.index_topk20_ms.is_none() {
-            eprintln!("warn: budgets.index_topk20_ms ist nicht gesetzt");
-        }
-    } else {
-        eprintln!("warn: budgets-Block fehlt");
+    if index.path.trim().is_empty() {
+        errors.push("index.path darf nicht leer sein".to_string());
+        return Ok(None);
+    }
+    let expanded_index_path = shellexpand::full(&index.path)?;
+    let index_path = PathBuf::from(expanded_index_path.as_ref());
+    if !index_path.is_absolute() {
+        errors.push("index.path muss ein absoluter Pfad sein (nach Expansion)".to_string());
+        return Ok(None);
     }
 
-    if let Some(plugins) = &config.plugins {
-        let enabled = plugins
-            .enabled
-            .as_ref()
-            .ok_or_else(|| anyhow!("plugins.enabled fehlt"))?;
-        if !enabled.iter().any(|entry| entry == "obsidian_index") {
-            bail!("plugins.enabled muss obsidian_index enthalten");
+    if let Some(parent) = index_path.parent() {
+        if !parent.exists() {
+            warnings.push(format!(
+                "Index-Verzeichnis {} existiert noch nicht (wird bei erstem Lauf erstellt)",
+                parent.display()
+            ));
         }
-    } else {
-        bail!("plugins-Block fehlt");
     }
+    Ok(Some(index_path))
+}
 
+fn validate_config(file: &str, json: bool, strict: bool) -> Result<()> {
+    let checked = match check_config(file, strict) {
+        Ok(checked) => checked,
+        Err(err) => {
+            if let (true, Some(errors)) = (json, err.downcast_ref::<ConfigErrors>()) {
+                let report = serde_json::json!({
+                    "valid": false,
+                    "strict": strict,
+                    "errors": errors.0,
+                });
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+            return Err(err);
+        }
+    };
+    let index = checked.index();
+    if json {
+        let report = serde_json::json!({
+            "valid": true,
+            "strict": strict,
+            "path": checked.path,
+            "index_path": checked.index_path,
+            "embedder": index.provider.embedder,
+            "model": index.provider.model,
+            "warnings": checked.warnings,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for warning in &checked.warnings {
+        eprintln!("warn: {warning}");
+    }
     println!(
         "Konfiguration gültig: {}\n  index.path: {}\n  provider: {} ({})",
-        path.display(),
-        index_path.display(),
+        checked.path.display(),
+        checked.index_path.display(),
         index.provider.embedder,
         index.provider.model
     );
@@ -458,27 +791,45 @@ fn validate_config(file: &str) -> Result<()> {
     Ok(())
 }
 
-fn run_core_server(bind_override: Option<String>) -> Result<()> {
+/// Prints every key of the layered configuration with its value and origin.
+fn print_effective_config(set: Vec<String>, json: bool) -> Result<()> {
+    let config = collect_layered(&ConfigLayers::from_env().with_overrides(set))?;
+    let effective = config.effective();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&effective)?);
+        return Ok(());
+    }
+    let width = effective
+        .entries
+        .iter()
+        .map(|entry| entry.key.len())
+        .max()
+        .unwrap_or(0);
+    for entry in &effective.entries {
+        println!(
+            "{:width$}  {}  [{}]",
+            entry.key,
+            serde_json::to_string(&entry.value)?,
+            entry.origin
+        );
+    }
+    Ok(())
+}
+
+fn run_core_server(bind_override: Option<String>, set: Vec<String>) -> Result<()> {
     let runtime = RuntimeBuilder::new_multi_thread()
         .enable_all()
         .build()
         .context("Tokio Runtime konnte nicht erzeugt werden")?;
 
-    runtime.block_on(async move { run_core_server_async(bind_override).await })
+    runtime.block_on(async move { run_core_server_async(bind_override, set).await })
 }
 
-async fn run_core_server_async(bind_override: Option<String>) -> Result<()> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer())
-        .try_init()
-        .ok();
+async fn run_core_server_async(bind_override: Option<String>, set: Vec<String>) -> Result<()> {
+    hauski_core::init_tracing();
 
-    let limits_path = env::var("HAUSKI_LIMITS").unwrap_or_else(|_| "./policies/limits.yaml".into());
-    let models_path = env::var("HAUSKI_MODELS").unwrap_or_else(|_| "./configs/models.yml".into());
-    let routing_path =
-        env::var("HAUSKI_ROUTING").unwrap_or_else(|_| "./policies/routing.yaml".into());
-    let flags_path = env::var("HAUSKI_FLAGS").unwrap_or_else(|_| "./configs/flags.yaml".into());
+    let layers = ConfigLayers::from_env().with_overrides(set);
+    let config = load_layered(&layers)?;
     let expose_config = env::var("HAUSKI_EXPOSE_CONFIG")
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
         .unwrap_or(false);
@@ -493,19 +844,16 @@ async fn run_core_server_async(bind_override: Option<String>) -> Result<()> {
         )
     })?;
 
-    let (app, state) = build_app_with_state(
-        load_limits(limits_path)?,
-        load_models(models_path)?,
-        load_routing(routing_path)?,
-        load_flags(flags_path)?,
-        expose_config,
-        allowed_origin_header,
-    );
+    let (app, state) = build_app_from_config(config, expose_config, allowed_origin_header);
+    state.watch_config(layers);
 
     let addr = resolve_bind_addr(bind_override, expose_config)?;
     info!(%addr, expose_config, "starte HausKI-Core (CLI)");
     let listener = TcpListener::bind(addr).await?;
     state.set_ready();
+    state.resume_jobs().await;
+    state.resume_outbox().await;
+    state.start_scheduler();
     axum::serve(listener, app)
         .with_graceful_shutdown(shutdown_signal())
         .await?;
@@ -584,27 +932,93 @@ mod tests {
     use super::*;
     use hauski_core::ModelEntry;
 
+    #[test]
+    fn json_flag_is_global() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from(["hauski", "index", "stats", "--json"]).unwrap();
+        assert!(cli.json);
+        let cli = Cli::try_parse_from(["hauski", "--json", "audio", "graph"]).unwrap();
+        assert!(cli.json);
+        assert!(json_output(true));
+    }
+
+    #[test]
+    fn check_config_reports_all_errors_and_strict_adds_server_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hauski.yml");
+        let policies = concat!(env!("CARGO_MANIFEST_DIR"), "/../../policies");
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+index:
+  path: "/tmp/hauski/index"
+  trust_policy: "{policies}/trust.yaml"
+  context_policy: "{policies}/context.yaml"
+  provider: {{ embedder: ollama, model: nomic-embed-text, url: "not a url" }}
+limits: {{}}
+models: []
+routing:
+  egress:
+    default: deny
+    allow: ["https://*."]
+flags: {{}}
+"#
+            ),
+        )
+        .unwrap();
+        let file = path.to_str().unwrap();
+
+        let err = check_config(file, false).err().unwrap();
+        let errors = &err.downcast_ref::<ConfigErrors>().unwrap().0;
+        assert_eq!(
+            errors,
+            &[
+                "index.provider.url ist keine gültige URL",
+                "plugins-Block fehlt"
+            ]
+        );
+
+        let err = check_config(file, true).err().unwrap();
+        let errors = &err.downcast_ref::<ConfigErrors>().unwrap().0;
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(
+            errors[2].contains("invalid host in allow entry"),
+            "{errors:?}"
+        );
+    }
+
     #[test]
     fn print_models_table_handles_empty_list() {
-        let models = ModelsFile { models: vec![] };
+        let models = ModelsFile::default();
         print_models_table(&models);
     }
 
     #[test]
     fn print_models_table_handles_mixed_list() {
         let models = ModelsFile {
+            embedders: Vec::new(),
+            embedder_fallback: Vec::new(),
+            voices: Vec::new(),
             models: vec![
                 ModelEntry {
                     id: "test-model-1".into(),
                     path: "/path/to/model-1".into(),
                     vram_min_gb: Some(4),
                     canary: Some(true),
+                    cost: None,
+                    url: None,
+                    sha256: None,
                 },
                 ModelEntry {
                     id: "test-model-2".into(),
                     path: "/path/to/model-2".into(),
                     vram_min_gb: None,
                     canary: None,
+                    cost: None,
+                    url: None,
+                    sha256: None,
                 },
             ],
         };
@@ -0,0 +1,316 @@
+//! `hauski memory …`: Arbeitsgedächtnis lesen, setzen, räumen und auflisten.
+//!
+//! Spricht die POST-Routen `/memory/*` des laufenden Cores an (`--base-url`,
+//! Default `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080). Alle Routen
+//! verlangen das Bearer-Token aus `--token` bzw. `HAUSKI_MEMORY_TOKEN`.
+//!
+//! Ausgabe als Tabelle (Default) oder mit `--json` als JSON.
+
+use std::{env, io::Read};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use serde_json::{json, Value};
+
+use crate::{print_table, remote, OutputArgs};
+
+/// Characters of a value shown in the list table.
+const VALUE_CHARS: usize = 60;
+
+#[derive(Args, Debug)]
+pub struct MemoryOptions {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long, global = true)]
+    pub base_url: Option<String>,
+    /// Bearer-Token (Default: `HAUSKI_MEMORY_TOKEN`)
+    #[arg(long, global = true)]
+    pub token: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MemoryCmd {
+    /// Wert eines Schlüssels lesen
+    Get { key: String },
+    /// Wert setzen (`-` liest den Wert von stdin)
+    Set {
+        key: String,
+        value: String,
+        /// Lebensdauer in Sekunden
+        #[arg(long, conflicts_with = "clear_ttl")]
+        ttl_sec: Option<i64>,
+        /// Eintrag anheften (`true`) oder lösen (`false`)
+        #[arg(long)]
+        pinned: Option<bool>,
+        /// Bestehende Lebensdauer entfernen
+        #[arg(long, default_value_t = false)]
+        clear_ttl: bool,
+    },
+    /// Schlüssel entfernen
+    Evict { key: String },
+    /// Schlüssel auflisten
+    List {
+        /// Namespace, z. B. `ask.session`
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Präfix innerhalb des Namespace
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// Einträge pro Seite (1–500)
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Fortsetzung ab `next_cursor` einer vorherigen Seite
+        #[arg(long, conflicts_with = "all")]
+        cursor: Option<String>,
+        /// Allen Seiten folgen
+        #[arg(long, default_value_t = false)]
+        all: bool,
+        /// Werte mit ausgeben
+        #[arg(long, default_value_t = false)]
+        values: bool,
+    },
+    /// Anzahl Einträge und TTL-Räumungen
+    Stats,
+}
+
+pub(crate) struct Client {
+    http: reqwest::Client,
+    base: String,
+    token: String,
+}
+
+impl Client {
+    /// Client for the core at `base_url` with `token` (Defaults: see
+    /// [`remote::base_url`]; `HAUSKI_MEMORY_TOKEN`, else the API token
+    /// `HAUSKI_TOKEN`, which the core accepts for `/memory/*` as well).
+    pub(crate) fn new(base_url: Option<String>, token: Option<String>) -> Result<Self> {
+        let token = token
+            .or_else(|| env::var("HAUSKI_MEMORY_TOKEN").ok())
+            .filter(|token| !token.trim().is_empty())
+            .or_else(remote::token)
+            .ok_or_else(|| {
+                anyhow!("kein Token: --token, HAUSKI_MEMORY_TOKEN oder HAUSKI_TOKEN setzen")
+            })?;
+        Ok(Self {
+            http: remote::client()?,
+            base: remote::base_url(base_url),
+            token,
+        })
+    }
+
+    /// Posts `body` to `path` (e.g. `/memory/get`) and returns the JSON
+    /// response; non-2xx statuses are errors.
+    pub(crate) async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        let url = format!("{}{path}", self.base.trim_end_matches('/'));
+        let response = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("HausKI-Core unter {} nicht erreichbar", self.base))?;
+        let status = response.status().as_u16();
+        let text = response.text().await?;
+        if !(200..300).contains(&status) {
+            bail!("HTTP {status}: {text}");
+        }
+        serde_json::from_str(&text).map_err(|e| anyhow!("ungültige Antwort ({e}): {text}"))
+    }
+}
+
+pub fn run(opts: MemoryOptions, cmd: MemoryCmd) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    runtime.block_on(run_async(opts, cmd))
+}
+
+async fn run_async(opts: MemoryOptions, cmd: MemoryCmd) -> Result<()> {
+    let client = Client::new(opts.base_url, opts.token)?;
+
+    match cmd {
+        MemoryCmd::Get { key } => {
+            let response = client.post("/memory/get", json!({"key": key})).await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else if let Some(value) = response["value"].as_str() {
+                println!("{value}");
+            } else {
+                bail!("Schlüssel '{key}' nicht gefunden");
+            }
+        }
+        MemoryCmd::Set {
+            key,
+            value,
+            ttl_sec,
+            pinned,
+            clear_ttl,
+        } => {
+            let value = if value == "-" {
+                let mut buf = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut buf)
+                    .context("stdin konnte nicht gelesen werden")?;
+                buf
+            } else {
+                value
+            };
+            let response = client
+                .post(
+                    "/memory/set",
+                    set_body(&key, &value, ttl_sec, pinned, clear_ttl),
+                )
+                .await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                println!("'{key}' gesetzt.");
+            }
+        }
+        MemoryCmd::Evict { key } => {
+            let response = client.post("/memory/evict", json!({"key": key})).await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else if response["ok"].as_bool().unwrap_or(false) {
+                println!("'{key}' entfernt.");
+            } else {
+                println!("'{key}' war nicht vorhanden.");
+            }
+        }
+        MemoryCmd::List {
+            namespace,
+            prefix,
+            limit,
+            cursor,
+            all,
+            values,
+        } => {
+            let mut items = Vec::new();
+            let mut cursor = cursor;
+            let next_cursor = loop {
+                let body = json!({
+                    "namespace": namespace,
+                    "prefix": prefix,
+                    "cursor": cursor,
+                    "limit": limit,
+                    "include_values": values,
+                });
+                let response = client.post("/memory/list", body).await?;
+                items.extend(response["items"].as_array().cloned().unwrap_or_default());
+                let next = response["next_cursor"].as_str().map(str::to_string);
+                match next {
+                    Some(next) if all => cursor = Some(next),
+                    next => break next,
+                }
+            };
+            if opts.output.json {
+                let response = json!({"items": items, "next_cursor": next_cursor});
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                print_items(&items, values);
+                if let Some(next) = next_cursor {
+                    println!("Weitere Einträge: --cursor {next} (oder --all)");
+                }
+            }
+        }
+        MemoryCmd::Stats => {
+            let response = client.post("/memory/stats", json!({})).await?;
+            if opts.output.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                println!(
+                    "{} Einträge ({} angeheftet, {} flüchtig), {} per TTL geräumt",
+                    response["total"],
+                    response["pinned"],
+                    response["unpinned"],
+                    response["expired_evictions_total"]
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Request body for `/memory/set`; unset options are left out so the server
+/// keeps the stored TTL and pin state.
+fn set_body(
+    key: &str,
+    value: &str,
+    ttl_sec: Option<i64>,
+    pinned: Option<bool>,
+    clear_ttl: bool,
+) -> Value {
+    let mut body = json!({"key": key, "value": value, "clear_ttl": clear_ttl});
+    if let Some(ttl_sec) = ttl_sec {
+        body["ttl_sec"] = json!(ttl_sec);
+    }
+    if let Some(pinned) = pinned {
+        body["pinned"] = json!(pinned);
+    }
+    body
+}
+
+fn print_items(items: &[Value], values: bool) {
+    if items.is_empty() {
+        println!("Keine Einträge.");
+        return;
+    }
+    let field = |item: &Value, name: &str| match &item[name] {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if values {
+        let rows = items
+            .iter()
+            .map(|item| {
+                let value = field(item, "value").replace('\n', " ");
+                let mut shown: String = value.chars().take(VALUE_CHARS).collect();
+                if value.chars().count() > VALUE_CHARS {
+                    shown.push('…');
+                }
+                [
+                    field(item, "key"),
+                    field(item, "ttl_sec"),
+                    field(item, "pinned"),
+                    shown,
+                ]
+            })
+            .collect();
+        print_table(["Schlüssel", "TTL (s)", "Angeheftet", "Wert"], rows);
+    } else {
+        let rows = items
+            .iter()
+            .map(|item| {
+                [
+                    field(item, "key"),
+                    field(item, "ttl_sec"),
+                    field(item, "pinned"),
+                    field(item, "updated_ts"),
+                ]
+            })
+            .collect();
+        print_table(["Schlüssel", "TTL (s)", "Angeheftet", "Geändert"], rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_body_omits_unset_options() {
+        let body = set_body("ask.session:a", "v", None, None, false);
+        assert_eq!(
+            body,
+            json!({"key": "ask.session:a", "value": "v", "clear_ttl": false})
+        );
+        let body = set_body("k", "v", Some(60), Some(true), false);
+        assert_eq!(body["ttl_sec"], 60);
+        assert_eq!(body["pinned"], true);
+    }
+}
@@ -0,0 +1,1055 @@
+//! `hauski models pull`: Modelldateien laden, prüfen und registrieren.
+//!
+//! Die Quelle kommt aus `configs/models.yml` (`url`, `sha256`, `path` des
+//! Eintrags), aus `--url`/`--sha256`/`--path` oder aus einem Registry-Index
+//! (`--registry` bzw. `HAUSKI_MODEL_REGISTRY`: YAML/JSON mit einer
+//! `models:`-Liste gleicher Felder). Ohne bekannte SHA256 wird nichts geladen.
+//!
+//! Der Download läuft über den Egress-Guard aus `policies/routing.yaml`
+//! (auch für Redirects): Host und CDN müssen unter `egress.allow` stehen.
+//! Abgebrochene Downloads bleiben als `<path>.part` liegen und werden beim
+//! nächsten Aufruf per HTTP-Range fortgesetzt. Erst nach erfolgreicher
+//! Prüfung wird die Datei an den Zielpfad verschoben.
+//!
+//! Pfade aus dem Registry-Index gelten relativ zu `HAUSKI_MODELS_DIR`; absolute
+//! Pfade, `..`, `~` und Variablen werden abgelehnt, damit ein Index nur unterhalb
+//! des Modellverzeichnisses schreiben kann. Liefert der Server bei `206` einen
+//! `Content-Range`, der nicht am lokalen Stand beginnt, startet der Download neu.
+//!
+//! Fehlen Eintrag, `url` oder `sha256` in `models.yml`, ergänzt das Kommando
+//! sie zeilenweise; Kommentare und Reihenfolge bleiben erhalten.
+//!
+//! `hauski models rm <id>` löscht die lokale Modelldatei (nach Rückfrage oder
+//! mit `--yes`); der Eintrag in `models.yml` bleibt für ein späteres `pull`
+//! stehen. Taucht die ID oder der Pfad irgendwo in `policies/routing.yaml` auf,
+//! verweigert das Kommando das Löschen.
+//!
+//! `hauski models verify` prüft alle Einträge mit `sha256` gegen die Dateien
+//! und meldet Abweichungen (`drift`); dann endet es mit Fehler. Fehlende
+//! Dateien werden nur gemeldet, mit `--strict` gelten sie ebenfalls als Fehler.
+//!
+//! Konfiguration:
+//!   HAUSKI_MODELS          (Default `./configs/models.yml`)
+//!   HAUSKI_ROUTING         (Default `./policies/routing.yaml`)
+//!   HAUSKI_MODELS_DIR      (Default `./models`, Ziel für Pfade aus der Registry)
+//!   HAUSKI_MODEL_REGISTRY  (optional, URL des Registry-Index)
+
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use hauski_core::{
+    load_models, parse_config, AllowlistedClient, ConfigKind, EgressGuard, ModelsFile,
+    RoutingPolicy,
+};
+use reqwest::{
+    header::{CONTENT_RANGE, RANGE},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{say, OutputArgs};
+
+#[derive(Args, Debug)]
+pub struct PullArgs {
+    /// Modell-ID aus `models.yml` oder dem Registry-Index
+    pub id: String,
+    /// Download-URL (überschreibt `url` aus `models.yml`)
+    #[arg(long)]
+    pub url: Option<String>,
+    /// Erwartete SHA256-Prüfsumme (hex)
+    #[arg(long)]
+    pub sha256: Option<String>,
+    /// Zielpfad für Modelle, die noch nicht in `models.yml` stehen
+    #[arg(long)]
+    pub path: Option<String>,
+    /// URL eines Registry-Index (Default: `HAUSKI_MODEL_REGISTRY`)
+    #[arg(long)]
+    pub registry: Option<String>,
+    /// Erneut laden, auch wenn die Datei bereits gültig vorliegt
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryIndex {
+    #[serde(default)]
+    models: Vec<RegistryEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RegistryEntry {
+    id: String,
+    url: String,
+    sha256: String,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+/// Where a model comes from and where it goes, after merging all sources.
+#[derive(Debug, Clone, PartialEq)]
+struct PullPlan {
+    id: String,
+    url: String,
+    sha256: String,
+    /// Path as written in `models.yml` (before `~`/env expansion).
+    path: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RmArgs {
+    /// Modell-ID aus `models.yml`
+    pub id: String,
+    /// Ohne Rückfrage löschen
+    #[arg(long, short = 'y', default_value_t = false)]
+    pub yes: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Fehlende Dateien ebenfalls als Fehler werten
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+fn models_path() -> String {
+    env::var("HAUSKI_MODELS").unwrap_or_else(|_| "./configs/models.yml".to_string())
+}
+
+fn models_dir() -> PathBuf {
+    env::var_os("HAUSKI_MODELS_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from("./models"), PathBuf::from)
+}
+
+/// Places a path from registry data under `models_dir`. Only plain relative
+/// paths are accepted: no root, no `..`, and nothing `shellexpand` would touch.
+fn registry_path(models_dir: &Path, path: &str) -> Result<String> {
+    use std::path::Component;
+
+    let relative = Path::new(path);
+    let plain = !path.trim().is_empty()
+        && !path.contains(['~', '$', '\\'])
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !plain {
+        bail!(
+            "Registry-Pfad {path:?} ist nicht erlaubt: nur relative Pfade ohne `..`, `~` oder `$`"
+        );
+    }
+    Ok(models_dir.join(relative).display().to_string())
+}
+
+fn expand_path(path: &str) -> Result<PathBuf> {
+    Ok(PathBuf::from(
+        shellexpand::full(path)
+            .map_err(|err| anyhow!("Pfad {path} nicht auflösbar: {err}"))?
+            .into_owned(),
+    ))
+}
+
+pub fn pull(args: PullArgs, routing: RoutingPolicy) -> Result<()> {
+    let models_path = models_path();
+    let models = load_models(&models_path)?;
+    let guard = EgressGuard::from_policy(&routing)
+        .map_err(|err| anyhow!("Egress-Policy ungültig: {err}"))?;
+    // `AllowlistedClient::build` turns off automatic redirects and checks every hop itself.
+    let client = AllowlistedClient::build(reqwest::Client::builder(), guard)
+        .context("HTTP-Client konnte nicht erzeugt werden")?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    runtime.block_on(pull_async(args, &models, Path::new(&models_path), &client))
+}
+
+async fn pull_async(
+    args: PullArgs,
+    models: &ModelsFile,
+    models_path: &Path,
+    client: &AllowlistedClient,
+) -> Result<()> {
+    let entry = models.models.iter().find(|model| model.id == args.id);
+    let needs_registry = args.url.is_none() && entry.and_then(|e| e.url.as_ref()).is_none()
+        || args.sha256.is_none() && entry.and_then(|e| e.sha256.as_ref()).is_none()
+        || args.path.is_none() && entry.is_none();
+    let registry_url = args
+        .registry
+        .clone()
+        .or_else(|| env::var("HAUSKI_MODEL_REGISTRY").ok())
+        .filter(|url| !url.trim().is_empty());
+    let registry = match registry_url {
+        Some(url) if needs_registry => fetch_registry_entry(client, &url, &args.id).await?,
+        _ => None,
+    };
+
+    let plan = resolve(&args, entry, registry.as_ref(), &models_dir())?;
+    let target = expand_path(&plan.path)?;
+
+    if target.exists() && !args.force {
+        if sha256_file(&target)? == plan.sha256 {
+            say(
+                args.output.json,
+                format!("{} ist aktuell: {}", plan.id, target.display()),
+            );
+            register(models_path, entry, &plan, args.output.json)?;
+            return print_pulled(&plan, &target, false, args.output.json);
+        }
+        eprintln!(
+            "{}: Prüfsumme von {} weicht ab – lade neu",
+            plan.id,
+            target.display()
+        );
+    }
+
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Verzeichnis {} nicht anlegbar", dir.display()))?;
+    }
+    let part = part_path(&target);
+    download(client, &plan, &part).await?;
+
+    let actual = sha256_file(&part)?;
+    if actual != plan.sha256 {
+        // A corrupt partial file must not be resumed on the next attempt.
+        let _ = fs::remove_file(&part);
+        bail!(
+            "Prüfsumme für {} stimmt nicht: erwartet {}, erhalten {actual}",
+            plan.id,
+            plan.sha256
+        );
+    }
+    fs::rename(&part, &target).with_context(|| {
+        format!(
+            "{} nicht nach {} verschiebbar",
+            part.display(),
+            target.display()
+        )
+    })?;
+    say(
+        args.output.json,
+        format!("{} → {} (sha256 ok)", plan.id, target.display()),
+    );
+    register(models_path, entry, &plan, args.output.json)?;
+    print_pulled(&plan, &target, true, args.output.json)
+}
+
+/// `--json` result of `models pull`.
+fn print_pulled(plan: &PullPlan, target: &Path, downloaded: bool, json: bool) -> Result<()> {
+    if json {
+        let result = serde_json::json!({
+            "id": plan.id,
+            "path": target,
+            "sha256": plan.sha256,
+            "downloaded": downloaded,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+fn resolve(
+    args: &PullArgs,
+    entry: Option<&hauski_core::ModelEntry>,
+    registry: Option<&RegistryEntry>,
+    models_dir: &Path,
+) -> Result<PullPlan> {
+    let url = args
+        .url
+        .clone()
+        .or_else(|| entry.and_then(|e| e.url.clone()))
+        .or_else(|| registry.map(|r| r.url.clone()))
+        .ok_or_else(|| {
+            anyhow!(
+                "keine Download-URL für {}: --url, `url` in models.yml oder --registry angeben",
+                args.id
+            )
+        })?;
+    let sha256 = args
+        .sha256
+        .clone()
+        .or_else(|| entry.and_then(|e| e.sha256.clone()))
+        .or_else(|| registry.map(|r| r.sha256.clone()))
+        .ok_or_else(|| {
+            anyhow!(
+                "keine SHA256 für {}: --sha256, `sha256` in models.yml oder --registry angeben",
+                args.id
+            )
+        })?
+        .trim()
+        .to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!(
+            "SHA256 für {} ist keine 64-stellige Hex-Zeichenkette",
+            args.id
+        );
+    }
+    let path = match entry.map(|e| e.path.clone()).or_else(|| args.path.clone()) {
+        Some(path) => path,
+        None => registry
+            .and_then(|r| r.path.as_deref())
+            .map(|path| registry_path(models_dir, path))
+            .transpose()?
+            .ok_or_else(|| {
+                anyhow!(
+                    "kein Zielpfad für {}: Eintrag in models.yml oder --path angeben",
+                    args.id
+                )
+            })?,
+    };
+    Ok(PullPlan {
+        id: args.id.clone(),
+        url,
+        sha256,
+        path,
+    })
+}
+
+async fn fetch_registry_entry(
+    client: &AllowlistedClient,
+    url: &str,
+    id: &str,
+) -> Result<Option<RegistryEntry>> {
+    let request = client
+        .get(url)
+        .map_err(|err| anyhow!("Registry {url}: {err}"))?;
+    let response = client
+        .send(request)
+        .await
+        .map_err(|err| anyhow!("Registry {url} nicht erreichbar: {err}"))?;
+    if !response.status().is_success() {
+        bail!("Registry {url}: HTTP {}", response.status());
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|err| anyhow!("Registry {url} nicht lesbar: {err}"))?;
+    let index: RegistryIndex =
+        serde_yaml_ng::from_str(&body).map_err(|err| anyhow!("Registry {url} ungültig: {err}"))?;
+    Ok(index.models.into_iter().find(|entry| entry.id == id))
+}
+
+fn part_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Downloads `plan.url` into `part`, resuming from its current length.
+///
+/// A `206` is only appended when its `Content-Range` starts at the local
+/// offset; otherwise the partial file is dropped and the download restarts.
+async fn download(client: &AllowlistedClient, plan: &PullPlan, part: &Path) -> Result<()> {
+    let mut offset = fs::metadata(part).map(|meta| meta.len()).unwrap_or(0);
+    let (mut response, append) = loop {
+        let mut request = client
+            .get(&plan.url)
+            .map_err(|err| anyhow!("Download {}: {err}", plan.url))?;
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={offset}-"));
+        }
+        let response = client
+            .send(request)
+            .await
+            .map_err(|err| anyhow!("Download {} fehlgeschlagen: {err}", plan.url))?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let start = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(content_range_start);
+                if start == Some(offset) {
+                    break (response, true);
+                }
+                if offset == 0 {
+                    bail!(
+                        "Download {}: HTTP 206 ohne passenden Content-Range",
+                        plan.url
+                    );
+                }
+                eprintln!(
+                    "{}: Server setzt nicht bei {} fort – lade von vorn",
+                    plan.id,
+                    human_bytes(offset)
+                );
+                offset = 0;
+            }
+            // The partial file already holds everything; the checksum decides.
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+            status if status.is_success() => break (response, false),
+            status => bail!("Download {}: HTTP {status}", plan.url),
+        }
+    };
+    let done = if append { offset } else { 0 };
+    if append {
+        eprintln!(
+            "{}: setze Download bei {} fort",
+            plan.id,
+            human_bytes(offset)
+        );
+    }
+    let total = response.content_length().map(|len| len + done);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(part)
+        .with_context(|| format!("{} nicht schreibbar", part.display()))?;
+    let mut progress = Progress::new(&plan.id, done, total);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| anyhow!("Download {} abgebrochen: {err}", plan.url))?
+    {
+        file.write_all(&chunk)
+            .with_context(|| format!("{} nicht schreibbar", part.display()))?;
+        progress.advance(chunk.len() as u64);
+    }
+    file.flush()?;
+    progress.finish();
+    Ok(())
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<total>` header.
+fn content_range_start(value: &str) -> Option<u64> {
+    let (start, _) = value.trim().strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+struct Progress<'a> {
+    id: &'a str,
+    done: u64,
+    total: Option<u64>,
+    last_pct: Option<u64>,
+    enabled: bool,
+}
+
+impl<'a> Progress<'a> {
+    fn new(id: &'a str, done: u64, total: Option<u64>) -> Self {
+        Self {
+            id,
+            done,
+            total,
+            last_pct: None,
+            enabled: std::io::stderr().is_terminal(),
+        }
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        let Some(total) = self.total.filter(|total| *total > 0) else {
+            return;
+        };
+        let pct = self.done.saturating_mul(100) / total;
+        if self.enabled && self.last_pct != Some(pct) {
+            self.last_pct = Some(pct);
+            eprint!(
+                "\r{}: {pct:>3}% ({} / {})",
+                self.id,
+                human_bytes(self.done),
+                human_bytes(total)
+            );
+        }
+    }
+
+    fn finish(&self) {
+        if self.enabled && self.last_pct.is_some() {
+            eprintln!();
+        }
+    }
+}
+
+pub(crate) fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("{} nicht lesbar", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("{} nicht lesbar", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex_digest(hasher))
+}
+
+/// Lower-case hex of the finished digest.
+pub(crate) fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut out, byte| {
+            use std::fmt::Write as _;
+            write!(&mut out, "{byte:02x}").expect("writing to String cannot fail");
+            out
+        })
+}
+
+pub fn rm(args: RmArgs, routing: &RoutingPolicy) -> Result<()> {
+    let models = load_models(models_path())?;
+    let entry = models
+        .models
+        .iter()
+        .find(|model| model.id == args.id)
+        .ok_or_else(|| anyhow!("Modell {} steht nicht in models.yml", args.id))?;
+    let routing = serde_yaml_ng::to_value(routing).context("serialize routing policy")?;
+    if let Some(location) = routing_reference(&routing, &[&entry.id, &entry.path]) {
+        bail!(
+            "Modell {} wird in der Routing-Policy verwendet ({location}) – erst dort entfernen",
+            entry.id
+        );
+    }
+
+    let target = expand_path(&entry.path)?;
+    let part = part_path(&target);
+    let existing: Vec<&Path> = [target.as_path(), part.as_path()]
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    if existing.is_empty() {
+        say(
+            args.output.json,
+            format!(
+                "{}: keine lokale Datei unter {}",
+                entry.id,
+                target.display()
+            ),
+        );
+    } else if !args.yes {
+        confirm(&format!("{} löschen ({})?", entry.id, target.display()))?;
+    }
+    for path in &existing {
+        fs::remove_file(path).with_context(|| format!("{} nicht löschbar", path.display()))?;
+        say(args.output.json, format!("gelöscht: {}", path.display()));
+    }
+    if args.output.json {
+        let result = serde_json::json!({"id": entry.id, "deleted": existing});
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        bail!("Bestätigung nötig: --yes angeben (stdin/stderr ist kein Terminal)");
+    }
+    eprint!("{question} [y/N] ");
+    io::stderr().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if !matches!(
+        input.trim().to_lowercase().as_str(),
+        "y" | "yes" | "j" | "ja"
+    ) {
+        bail!("abgebrochen");
+    }
+    Ok(())
+}
+
+/// Returns the YAML path of the first string scalar equal to one of `needles`.
+fn routing_reference(value: &serde_yaml_ng::Value, needles: &[&str]) -> Option<String> {
+    use serde_yaml_ng::Value;
+    match value {
+        Value::String(text) if needles.contains(&text.as_str()) => Some(String::from("$")),
+        Value::Sequence(items) => items.iter().enumerate().find_map(|(index, item)| {
+            routing_reference(item, needles)
+                .map(|inner| inner.replacen('$', &format!("$[{index}]"), 1))
+        }),
+        Value::Mapping(map) => map.iter().find_map(|(key, item)| {
+            let key = key
+                .as_str()
+                .map_or_else(|| format!("{key:?}"), str::to_string);
+            routing_reference(item, needles)
+                .map(|inner| inner.replacen('$', &format!("$.{key}"), 1))
+        }),
+        Value::Tagged(tagged) => routing_reference(&tagged.value, needles),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum VerifyStatus {
+    Ok,
+    Drift,
+    Missing,
+    Unchecked,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyReport {
+    id: String,
+    path: String,
+    status: VerifyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual: Option<String>,
+}
+
+pub fn verify(args: VerifyArgs) -> Result<()> {
+    let models = load_models(models_path())?;
+    let reports = models
+        .models
+        .iter()
+        .map(verify_entry)
+        .collect::<Result<Vec<_>>>()?;
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        let rows = reports
+            .iter()
+            .map(|report| {
+                let detail = match report.status {
+                    VerifyStatus::Drift => format!(
+                        "erwartet {}, erhalten {}",
+                        short_hash(report.expected.as_deref()),
+                        short_hash(report.actual.as_deref())
+                    ),
+                    VerifyStatus::Unchecked => "keine sha256 hinterlegt".to_string(),
+                    _ => String::new(),
+                };
+                [
+                    report.id.clone(),
+                    serde_json::to_value(report.status)
+                        .ok()
+                        .and_then(|value| value.as_str().map(str::to_string))
+                        .unwrap_or_default(),
+                    report.path.clone(),
+                    detail,
+                ]
+            })
+            .collect();
+        crate::print_table(["ID", "Status", "Path", "Details"], rows);
+    }
+
+    let count = |status| reports.iter().filter(|r| r.status == status).count();
+    let (drift, missing) = (count(VerifyStatus::Drift), count(VerifyStatus::Missing));
+    if drift > 0 {
+        bail!("{drift} Modell(e) weichen von der hinterlegten SHA256 ab");
+    }
+    if args.strict && missing > 0 {
+        bail!("{missing} Modell(e) fehlen lokal");
+    }
+    Ok(())
+}
+
+fn verify_entry(entry: &hauski_core::ModelEntry) -> Result<VerifyReport> {
+    let expected = entry
+        .sha256
+        .as_deref()
+        .map(|sha| sha.trim().to_ascii_lowercase());
+    let target = expand_path(&entry.path)?;
+    let (status, actual) = match &expected {
+        _ if !target.is_file() => (VerifyStatus::Missing, None),
+        None => (VerifyStatus::Unchecked, None),
+        Some(expected) => {
+            let actual = sha256_file(&target)?;
+            let status = if &actual == expected {
+                VerifyStatus::Ok
+            } else {
+                VerifyStatus::Drift
+            };
+            (status, Some(actual))
+        }
+    };
+    Ok(VerifyReport {
+        id: entry.id.clone(),
+        path: entry.path.clone(),
+        status,
+        expected,
+        actual,
+    })
+}
+
+fn short_hash(hash: Option<&str>) -> String {
+    hash.map(|hash| hash.chars().take(12).collect())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Writes missing or changed `url`/`sha256` (and new entries) to `models.yml`.
+fn register(
+    models_path: &Path,
+    entry: Option<&hauski_core::ModelEntry>,
+    plan: &PullPlan,
+    json: bool,
+) -> Result<()> {
+    let mut fields = Vec::new();
+    if entry.and_then(|e| e.url.as_deref()) != Some(plan.url.as_str()) {
+        fields.push(("url", plan.url.as_str()));
+    }
+    if entry.and_then(|e| e.sha256.as_deref()) != Some(plan.sha256.as_str()) {
+        fields.push(("sha256", plan.sha256.as_str()));
+    }
+    if entry.is_none() {
+        fields.insert(0, ("path", plan.path.as_str()));
+    }
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let text = fs::read_to_string(models_path)
+        .with_context(|| format!("{} nicht lesbar", models_path.display()))?;
+    let updated = upsert_model_yaml(&text, &plan.id, &fields)?;
+    fs::write(models_path, updated)
+        .with_context(|| format!("{} nicht schreibbar", models_path.display()))?;
+    say(
+        json,
+        format!("{} aktualisiert ({})", models_path.display(), plan.id),
+    );
+    Ok(())
+}
+
+/// Sets `fields` on the entry `id` of the `models:` list, appending the entry
+/// when it is missing. Works on lines so comments and ordering survive.
+fn upsert_model_yaml(text: &str, id: &str, fields: &[(&str, &str)]) -> Result<String> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_end() == "models:")
+        .ok_or_else(|| anyhow!("models.yml enthält keine `models:`-Liste"))?;
+
+    // The list ends before the next top-level key; trailing comments and
+    // blank lines belong to whatever follows.
+    let mut end = start + 1;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.starts_with([' ', '-']) {
+            end = index + 1;
+        } else if !(line.trim().is_empty() || line.starts_with('#')) {
+            break;
+        }
+    }
+
+    let indent = lines[start + 1..end]
+        .iter()
+        .find_map(|line| item(line).map(|(indent, _)| indent))
+        .unwrap_or(2);
+    let pad = " ".repeat(indent + 2);
+
+    let found = (start + 1..end).find(|&index| {
+        item(&lines[index]).is_some_and(|(_, rest)| {
+            rest.strip_prefix("id:")
+                .map(|value| value.trim().trim_matches(['"', '\'']) == id)
+                .unwrap_or(false)
+        })
+    });
+
+    match found {
+        Some(first) => {
+            let last = (first + 1..end)
+                .find(|&index| item(&lines[index]).is_some_and(|(i, _)| i == indent))
+                .unwrap_or(end);
+            let mut insert_at = (first..last)
+                .rev()
+                .find(|&index| !lines[index].trim().is_empty())
+                .map_or(first + 1, |index| index + 1);
+            for (key, value) in fields {
+                let line = format!("{pad}{key}: {}", yaml_scalar(value)?);
+                let prefix = format!("{pad}{key}:");
+                match (first + 1..insert_at).find(|&index| lines[index].starts_with(&prefix)) {
+                    Some(index) => lines[index] = line,
+                    None => {
+                        lines.insert(insert_at, line);
+                        insert_at += 1;
+                    }
+                }
+            }
+        }
+        None => {
+            let mut block = vec![format!("{}- id: {}", " ".repeat(indent), yaml_scalar(id)?)];
+            for (key, value) in fields {
+                block.push(format!("{pad}{key}: {}", yaml_scalar(value)?));
+            }
+            lines.splice(end..end, block);
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    let (check, _, _) = parse_config::<ModelsFile>(&updated, ConfigKind::Models)
+        .map_err(|err| anyhow!("models.yml nach Aktualisierung ungültig: {err}"))?;
+    if !check.models.iter().any(|model| model.id == id) {
+        bail!("models.yml: Eintrag {id} konnte nicht geschrieben werden");
+    }
+    Ok(updated)
+}
+
+/// Indentation and content of a YAML list item line (`- ...`).
+fn item(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    trimmed
+        .strip_prefix("- ")
+        .map(|rest| (line.len() - trimmed.len(), rest.trim()))
+}
+
+pub(crate) fn yaml_scalar(value: &str) -> Result<String> {
+    Ok(serde_yaml_ng::to_string(value)
+        .map_err(|err| anyhow!("{err}"))?
+        .trim_end()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: &str = "\
+version: 1
+models:
+  - id: llama
+    path: /opt/models/llama.gguf
+    vram_min_gb: 6
+  - id: whisper
+    path: /opt/models/whisper.bin
+    url: https://old.example/whisper.bin
+
+# Embedder-Registry
+embedders: []
+";
+
+    #[test]
+    fn upsert_adds_fields_and_entries_in_place() {
+        let updated =
+            upsert_model_yaml(MODELS, "llama", &[("url", "https://m.example/l.gguf")]).unwrap();
+        assert!(updated
+            .contains("    vram_min_gb: 6\n    url: https://m.example/l.gguf\n  - id: whisper"));
+
+        let updated =
+            upsert_model_yaml(&updated, "whisper", &[("url", "https://new.example/w")]).unwrap();
+        assert!(updated.contains("    url: https://new.example/w\n\n# Embedder-Registry"));
+        assert!(!updated.contains("old.example"));
+
+        let updated =
+            upsert_model_yaml(&updated, "tiny", &[("path", "~/models/tiny.bin")]).unwrap();
+        assert!(updated.contains(
+            "  - id: tiny\n    path: ~/models/tiny.bin\n\n# Embedder-Registry\nembedders: []\n"
+        ));
+        let (file, _, _) = parse_config::<ModelsFile>(&updated, ConfigKind::Models).unwrap();
+        assert_eq!(file.models.len(), 3);
+    }
+
+    #[test]
+    fn resolve_prefers_flags_then_models_then_registry() {
+        let args = PullArgs {
+            id: "whisper".into(),
+            url: None,
+            sha256: Some("AB".repeat(32)),
+            path: Some("/ignored".into()),
+            registry: None,
+            force: false,
+            output: OutputArgs::default(),
+        };
+        let (file, _, _) = parse_config::<ModelsFile>(MODELS, ConfigKind::Models).unwrap();
+        let registry = RegistryEntry {
+            id: "whisper".into(),
+            url: "https://registry.example/w".into(),
+            sha256: "00".repeat(32),
+            path: None,
+        };
+        let models_dir = Path::new("/srv/models");
+        let plan = resolve(&args, file.models.get(1), Some(&registry), models_dir).unwrap();
+        assert_eq!(plan.url, "https://old.example/whisper.bin");
+        assert_eq!(plan.sha256, "ab".repeat(32));
+        assert_eq!(plan.path, "/opt/models/whisper.bin");
+
+        let args = PullArgs {
+            sha256: None,
+            ..args
+        };
+        assert!(resolve(&args, file.models.first(), None, models_dir).is_err());
+    }
+
+    #[test]
+    fn registry_paths_stay_inside_the_models_dir() {
+        let models_dir = Path::new("/srv/models");
+        assert_eq!(
+            registry_path(models_dir, "whisper/medium.bin").unwrap(),
+            "/srv/models/whisper/medium.bin"
+        );
+        for path in [
+            "/etc/passwd",
+            "../../.bashrc",
+            "whisper/../../x",
+            "~/.bashrc",
+            "$HOME/.bashrc",
+            "${HOME}/x",
+            "..\\x",
+            "",
+        ] {
+            assert!(registry_path(models_dir, path).is_err(), "{path}");
+        }
+
+        let args = PullArgs {
+            id: "tiny".into(),
+            url: None,
+            sha256: None,
+            path: None,
+            registry: None,
+            force: false,
+            output: OutputArgs::default(),
+        };
+        let mut registry = RegistryEntry {
+            id: "tiny".into(),
+            url: "https://registry.example/t".into(),
+            sha256: "00".repeat(32),
+            path: Some("~/.bashrc".into()),
+        };
+        assert!(resolve(&args, None, Some(&registry), models_dir).is_err());
+        registry.path = Some("tiny.bin".into());
+        let plan = resolve(&args, None, Some(&registry), models_dir).unwrap();
+        assert_eq!(plan.path, "/srv/models/tiny.bin");
+
+        // Paths chosen locally are not restricted.
+        let args = PullArgs {
+            path: Some("~/models/tiny.bin".into()),
+            ..args
+        };
+        registry.path = Some("/etc/passwd".into());
+        let plan = resolve(&args, None, Some(&registry), models_dir).unwrap();
+        assert_eq!(plan.path, "~/models/tiny.bin");
+    }
+
+    #[test]
+    fn routing_reference_finds_nested_model_ids() {
+        let routing: serde_yaml_ng::Value = serde_yaml_ng::from_str(
+            "routing:\n  fallbacks:\n    - model: llama\n  prefer_local: true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            routing_reference(&routing, &["llama", "/opt/models/llama.gguf"]).as_deref(),
+            Some("$.routing.fallbacks[0].model")
+        );
+        assert_eq!(routing_reference(&routing, &["whisper"]), None);
+    }
+
+    #[test]
+    fn verify_reports_drift_missing_and_unchecked() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("model.bin");
+        fs::write(&file, b"weights").unwrap();
+        let entry = |sha256: Option<String>, path: &Path| hauski_core::ModelEntry {
+            id: "m".into(),
+            path: path.display().to_string(),
+            vram_min_gb: None,
+            canary: None,
+            cost: None,
+            url: None,
+            sha256,
+        };
+        let good = sha256_file(&file).unwrap();
+
+        let report = verify_entry(&entry(Some(good.to_uppercase()), &file)).unwrap();
+        assert_eq!(report.status, VerifyStatus::Ok);
+        let report = verify_entry(&entry(Some("00".repeat(32)), &file)).unwrap();
+        assert_eq!(report.status, VerifyStatus::Drift);
+        assert_eq!(report.actual.as_deref(), Some(good.as_str()));
+        let report = verify_entry(&entry(None, &file)).unwrap();
+        assert_eq!(report.status, VerifyStatus::Unchecked);
+        let report = verify_entry(&entry(Some(good), &dir.path().join("gone"))).unwrap();
+        assert_eq!(report.status, VerifyStatus::Missing);
+    }
+
+    #[tokio::test]
+    async fn download_resumes_partial_file_and_verifies_checksum() {
+        use axum::{
+            http::{HeaderMap, HeaderValue},
+            response::IntoResponse,
+            routing::get,
+            Router,
+        };
+
+        const BODY: &[u8] = b"ggml model bytes for the resume test";
+        async fn serve(headers: HeaderMap, skew: usize) -> axum::response::Response {
+            let offset = headers
+                .get(RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("bytes="))
+                .and_then(|value| value.trim_end_matches('-').parse::<usize>().ok());
+            match offset {
+                Some(offset) => {
+                    // `skew` simulates a server that answers from another position.
+                    let start = offset - skew;
+                    let range = format!("bytes {start}-{}/{}", BODY.len() - 1, BODY.len());
+                    let mut response =
+                        (StatusCode::PARTIAL_CONTENT, BODY[start..].to_vec()).into_response();
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_RANGE, HeaderValue::from_str(&range).unwrap());
+                    response
+                }
+                None => (StatusCode::OK, BODY.to_vec()).into_response(),
+            }
+        }
+        let router = Router::new()
+            .route("/model.bin", get(|headers| serve(headers, 0)))
+            .route("/skewed.bin", get(|headers| serve(headers, 4)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let expected = Sha256::digest(BODY)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let client =
+            AllowlistedClient::build(reqwest::Client::builder(), EgressGuard::allow_all()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["model.bin", "skewed.bin"] {
+            let part = dir.path().join(format!("{name}.part"));
+            fs::write(&part, &BODY[..10]).unwrap();
+            let plan = PullPlan {
+                id: "test".into(),
+                url: format!("http://{addr}/{name}"),
+                sha256: String::new(),
+                path: String::new(),
+            };
+            download(&client, &plan, &part).await.unwrap();
+            assert_eq!(fs::read(&part).unwrap(), BODY, "{name}");
+            assert_eq!(sha256_file(&part).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn content_range_start_parses_byte_ranges() {
+        assert_eq!(content_range_start("bytes 10-35/36"), Some(10));
+        assert_eq!(content_range_start("bytes 0-0/*"), Some(0));
+        assert_eq!(content_range_start("bytes */36"), None);
+        assert_eq!(content_range_start("items 1-2/3"), None);
+    }
+}
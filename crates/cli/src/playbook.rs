@@ -0,0 +1,1543 @@
+//! Playbook-Ausführung für `hauski assist`.
+//!
+//! Schritte deklarieren einen Schritt-Typ, der nativ und ohne Shell ausgeführt
+//! wird – als eigener Schlüssel (`http: {url: …}`) oder in der älteren Form
+//! `action: http_call` mit den Feldern auf Schritt-Ebene:
+//!
+//! - `http` (`http_call`): HTTP-Request, geprüft gegen die Egress-Allowlist aus `routing.yaml`.
+//! - `index_upsert`: Dokument über `/index/upsert` des lokalen Cores ablegen
+//!   (`source_ref` mit `origin: tool`, `trust_level: low`).
+//! - `memory_set`: Key über `/memory/set` setzen (Token aus `HAUSKI_MEMORY_TOKEN`).
+//! - `ask`: Frage an `/ask` des Cores, optional mit extraktiver Antwort.
+//! - `file_read`: Datei lesen, nur unterhalb der per `--allow-read` freigegebenen Pfade.
+//!
+//! Jeder native Schritt liefert neben dem Text (`steps.<id>.output`) typisierte
+//! Ausgaben als JSON, z. B. `steps.<id>.outputs.status` oder
+//! `steps.<id>.outputs.hits.0.doc_id`.
+//!
+//! Rohe Shell-Schritte (`run:`) werden nur mit `--unsafe-shell` ausgeführt, da
+//! Playbooks auch vom Modell vorgeschlagen sein können.
+//!
+//! Zwischen den Schritten fließen Werte über `${{ … }}`-Ausdrücke in
+//! String-Feldern: `vars.<name>` (Top-Level `vars:`, per `--var` überschreibbar),
+//! `env.<NAME>` (Top-Level `env:` und Zeilen `KEY=VALUE`, die Shell-Schritte nach
+//! `$HAUSKI_ENV` schreiben) und `steps.<id>.output` bzw. `steps.<id>.outcome`.
+//! Ein `if:` pro Schritt überspringt ihn, wenn die Bedingung falsch ist.
+//!
+//! Ohne `needs:` wartet ein Schritt auf den vorherigen; mit `needs:` nur auf die
+//! genannten Schritte. Unabhängige Schritte laufen gleichzeitig, höchstens
+//! `--parallel` auf einmal, und am Ende steht ein gemeinsamer Bericht.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use hauski_core::{AllowlistedClient, EgressGuard, RoutingPolicy};
+use reqwest::Method;
+use serde::{de, Deserialize, Deserializer};
+use serde_json::{json, Value};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// Upper bound for `file_read`, to keep step output readable.
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_namespace() -> String {
+    "default".to_string()
+}
+
+fn default_k() -> usize {
+    5
+}
+
+/// Numeric or boolean input that also accepts the string a `${{ }}`
+/// expression leaves behind, e.g. `k: ${{ vars.k }}`. While the expression is
+/// still unresolved (parse time) it reads as the default; [`Step::resolve`]
+/// parses the step again after interpolation.
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + Deserialize<'de> + Default,
+    T::Err: Display,
+{
+    lenient_option(deserializer)?.ok_or_else(|| de::Error::custom("value must not be null"))
+}
+
+fn lenient_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + Deserialize<'de> + Default,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw<T> {
+        Typed(T),
+        Text(String),
+        Null(()),
+    }
+    match Raw::<T>::deserialize(deserializer)? {
+        Raw::Typed(value) => Ok(Some(value)),
+        Raw::Text(text) if text.contains("${{") => Ok(Some(T::default())),
+        Raw::Text(text) => text
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| de::Error::custom(format!("'{text}': {e}"))),
+        Raw::Null(()) => Ok(None),
+    }
+}
+
+/// Step keys that name a natively executed step, with their `action:` tag.
+const STEP_TYPES: &[(&str, &str)] = &[
+    ("http", "http_call"),
+    ("index_upsert", "index_upsert"),
+    ("memory_set", "memory_set"),
+    ("ask", "ask"),
+    ("file_read", "file_read"),
+];
+
+/// Declared, natively executed step action.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    HttpCall {
+        #[serde(default = "default_method")]
+        method: String,
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        #[serde(default)]
+        body: Option<Value>,
+    },
+    IndexUpsert {
+        doc_id: String,
+        #[serde(default = "default_namespace")]
+        namespace: String,
+        text: String,
+        #[serde(default)]
+        meta: Option<Value>,
+    },
+    MemorySet {
+        key: String,
+        value: String,
+        #[serde(default, deserialize_with = "lenient_option")]
+        ttl_sec: Option<i64>,
+    },
+    Ask {
+        query: String,
+        #[serde(default = "default_k", deserialize_with = "lenient")]
+        k: usize,
+        #[serde(default = "default_namespace")]
+        namespace: String,
+        /// Extractive answer from the top hits instead of hits only.
+        #[serde(default, deserialize_with = "lenient")]
+        answer: bool,
+    },
+    FileRead {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepKind {
+    Action(Action),
+    /// Raw `sh -c`; requires `--unsafe-shell`.
+    Shell(String),
+}
+
+impl StepKind {
+    fn describe(&self) -> String {
+        match self {
+            StepKind::Shell(cmd) => format!("shell: {cmd}"),
+            StepKind::Action(Action::HttpCall { method, url, .. }) => {
+                format!("http_call: {method} {url}")
+            }
+            StepKind::Action(Action::IndexUpsert {
+                doc_id, namespace, ..
+            }) => format!("index_upsert: {namespace}/{doc_id}"),
+            StepKind::Action(Action::MemorySet { key, .. }) => format!("memory_set: {key}"),
+            StepKind::Action(Action::Ask {
+                query, namespace, ..
+            }) => format!("ask: {namespace}: {query}"),
+            StepKind::Action(Action::FileRead { path }) => format!("file_read: {path}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub id: String,
+    /// Kind as written, before interpolation; used for up-front checks.
+    pub kind: StepKind,
+    /// `if:` expression; the step is skipped when it evaluates to false.
+    pub condition: Option<String>,
+    /// Steps that must be done first: `needs:` as written, otherwise the step
+    /// before this one.
+    pub needs: Vec<String>,
+    /// Raw step mapping, interpolated against the run's scope right before execution.
+    template: serde_yaml_ng::Value,
+}
+
+impl Step {
+    /// The step's kind with all `${{ }}` expressions replaced.
+    fn resolve(&self, scope: &Scope) -> Result<StepKind> {
+        let mut template = self.template.clone();
+        if let Some(map) = template.as_mapping_mut() {
+            map.remove("if");
+        }
+        scope.interpolate_value(&mut template)?;
+        Ok(parse_kind(&template)?.unwrap_or_else(|| self.kind.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Playbook {
+    pub vars: BTreeMap<String, String>,
+    /// Initial environment for `env.*` and shell steps; values may use `vars.*`.
+    pub env: BTreeMap<String, String>,
+    pub steps: Vec<Step>,
+}
+
+fn parse_kind(step: &serde_yaml_ng::Value) -> Result<Option<StepKind>> {
+    let typed: Vec<&(&str, &str)> = STEP_TYPES
+        .iter()
+        .filter(|(key, _)| step.get(key).is_some())
+        .collect();
+    let declared = typed.len()
+        + usize::from(step.get("action").is_some())
+        + usize::from(step.get("run").is_some());
+    if declared > 1 {
+        let keys: Vec<&str> = STEP_TYPES.iter().map(|(key, _)| *key).collect();
+        bail!(
+            "a step declares exactly one of `action`, `run`, `{}`",
+            keys.join("`, `")
+        );
+    }
+
+    if let Some((key, tag)) = typed.first() {
+        let mut fields = match &step[*key] {
+            serde_yaml_ng::Value::Mapping(fields) => fields.clone(),
+            // `http: https://…` is a plain GET.
+            serde_yaml_ng::Value::String(url) if *key == "http" => {
+                serde_yaml_ng::Mapping::from_iter([("url".into(), url.as_str().into())])
+            }
+            _ => bail!("`{key}` must be a mapping"),
+        };
+        fields.insert("action".into(), (*tag).into());
+        let action = serde_yaml_ng::from_value(serde_yaml_ng::Value::Mapping(fields))?;
+        return Ok(Some(StepKind::Action(action)));
+    }
+    if step.get("action").is_some() {
+        let action = serde_yaml_ng::from_value::<Action>(step.clone())?;
+        return Ok(Some(StepKind::Action(action)));
+    }
+    Ok(step
+        .get("run")
+        .and_then(|r| r.as_str())
+        .map(|cmd| StepKind::Shell(cmd.to_string())))
+}
+
+/// Reads a `vars`/`env` mapping; scalar values are kept as their YAML text.
+fn parse_scalars(playbook: &serde_yaml_ng::Value, key: &str) -> Result<BTreeMap<String, String>> {
+    let Some(entries) = playbook.get(key) else {
+        return Ok(BTreeMap::new());
+    };
+    let entries: BTreeMap<String, serde_yaml_ng::Value> =
+        serde_yaml_ng::from_value(entries.clone())
+            .map_err(|e| anyhow!("`{key}` must be a mapping: {e}"))?;
+    entries
+        .into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                serde_yaml_ng::Value::String(s) => s,
+                serde_yaml_ng::Value::Number(n) => n.to_string(),
+                serde_yaml_ng::Value::Bool(b) => b.to_string(),
+                serde_yaml_ng::Value::Null => String::new(),
+                _ => bail!("`{key}.{name}` must be a scalar"),
+            };
+            Ok((name, text))
+        })
+        .collect()
+}
+
+/// Parses a playbook. Steps without a step type, `action` or string `run`
+/// (e.g. prototype kinds like `github_comment`) are skipped with a warning.
+pub fn parse_playbook(content: &str) -> Result<Playbook> {
+    let playbook: serde_yaml_ng::Value = serde_yaml_ng::from_str(content)?;
+    let vars = parse_scalars(&playbook, "vars")?;
+    let env = parse_scalars(&playbook, "env")?;
+    let Some(steps) = playbook.get("steps").and_then(|s| s.as_sequence()) else {
+        return Ok(Playbook {
+            vars,
+            env,
+            steps: Vec::new(),
+        });
+    };
+
+    let mut parsed = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        let id = step
+            .get("id")
+            .and_then(|v| v.as_str())
+            .map_or_else(|| format!("step-{}", i + 1), str::to_string);
+        let kind = parse_kind(step)
+            .map_err(|e| anyhow!("Invalid action in step {} ('{id}'): {e}", i + 1))?;
+        let Some(kind) = kind else {
+            warn!(step = %id, "skipping playbook step without supported action");
+            continue;
+        };
+        let condition = match step.get("if") {
+            None => None,
+            Some(serde_yaml_ng::Value::String(expr)) => Some(expr.clone()),
+            Some(serde_yaml_ng::Value::Bool(b)) => Some(b.to_string()),
+            Some(_) => bail!("`if` in step {} ('{id}') must be a string", i + 1),
+        };
+        let needs = match step.get("needs") {
+            None => parsed
+                .last()
+                .map(|previous: &Step| vec![previous.id.clone()])
+                .unwrap_or_default(),
+            Some(serde_yaml_ng::Value::String(need)) => vec![need.clone()],
+            Some(serde_yaml_ng::Value::Sequence(needs)) => needs
+                .iter()
+                .map(|need| need.as_str().map(str::to_string))
+                .collect::<Option<_>>()
+                .ok_or_else(|| anyhow!("`needs` in step {} ('{id}') must list step ids", i + 1))?,
+            Some(_) => bail!("`needs` in step {} ('{id}') must list step ids", i + 1),
+        };
+        parsed.push(Step {
+            id,
+            kind,
+            condition,
+            needs,
+            template: step.clone(),
+        });
+    }
+    check_dependencies(&parsed)?;
+    Ok(Playbook {
+        vars,
+        env,
+        steps: parsed,
+    })
+}
+
+/// Rejects duplicate ids, unknown or cyclic `needs`, and `steps.<id>`
+/// references to steps that are not guaranteed to be done by then – with
+/// parallel steps that would be a race.
+fn check_dependencies(steps: &[Step]) -> Result<()> {
+    let mut index = BTreeMap::new();
+    for (i, step) in steps.iter().enumerate() {
+        if index.insert(step.id.as_str(), i).is_some() {
+            bail!("duplicate step id '{}'", step.id);
+        }
+    }
+    let mut deps = Vec::with_capacity(steps.len());
+    for step in steps {
+        let ids = step
+            .needs
+            .iter()
+            .map(|need| {
+                index
+                    .get(need.as_str())
+                    .copied()
+                    .ok_or_else(|| anyhow!("step '{}' needs unknown step '{need}'", step.id))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        deps.push(ids);
+    }
+
+    // Ancestors in topological order; a step left over sits on a cycle.
+    let mut ancestors: Vec<Option<BTreeSet<usize>>> = vec![None; steps.len()];
+    while ancestors.iter().any(Option::is_none) {
+        let ready = (0..steps.len()).find(|&i| {
+            ancestors[i].is_none() && deps[i].iter().all(|&dep| ancestors[dep].is_some())
+        });
+        let Some(i) = ready else {
+            let stuck: Vec<&str> = (0..steps.len())
+                .filter(|&i| ancestors[i].is_none())
+                .map(|i| steps[i].id.as_str())
+                .collect();
+            bail!("`needs` forms a cycle between: {}", stuck.join(", "));
+        };
+        let mut all = BTreeSet::new();
+        for &dep in &deps[i] {
+            all.insert(dep);
+            all.extend(ancestors[dep].iter().flatten().copied());
+        }
+        ancestors[i] = Some(all);
+    }
+
+    for (i, step) in steps.iter().enumerate() {
+        let mut text = step.condition.clone().unwrap_or_default();
+        collect_strings(&step.template, &mut text);
+        for (j, other) in steps.iter().enumerate() {
+            let done_before = ancestors[i].iter().flatten().any(|&a| a == j);
+            if !done_before && text.contains(&format!("steps.{}.", other.id)) {
+                bail!(
+                    "step '{}' uses steps.{} but does not need it (add it to `needs`)",
+                    step.id,
+                    other.id
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn collect_strings(value: &serde_yaml_ng::Value, out: &mut String) {
+    match value {
+        serde_yaml_ng::Value::String(text) => {
+            out.push('\n');
+            out.push_str(text);
+        }
+        serde_yaml_ng::Value::Sequence(items) => {
+            items.iter().for_each(|item| collect_strings(item, out));
+        }
+        serde_yaml_ng::Value::Mapping(map) => {
+            map.values().for_each(|item| collect_strings(item, out));
+        }
+        _ => {}
+    }
+}
+
+/// `--var KEY=VALUE` for clap.
+pub fn parse_var(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("erwartet KEY=VALUE, bekommen: {raw}")),
+    }
+}
+
+#[derive(Debug)]
+struct StepResult {
+    output: String,
+    /// Typed outputs of native steps; `Null` for shell and skipped steps.
+    outputs: Value,
+    outcome: &'static str,
+}
+
+/// What a step produced: its text output and, for native steps, typed outputs.
+#[derive(Debug)]
+struct StepOutput {
+    text: String,
+    data: Value,
+}
+
+/// `hits.0.doc_id` in `value`; an empty path is the whole value.
+fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => value.get(segment),
+        })
+}
+
+/// Values visible to `${{ }}` expressions while a playbook runs.
+#[derive(Debug, Default)]
+struct Scope {
+    vars: BTreeMap<String, String>,
+    /// Playbook `env:` plus everything shell steps captured via `$HAUSKI_ENV`.
+    /// The process environment is deliberately not readable here.
+    env: BTreeMap<String, String>,
+    steps: BTreeMap<String, StepResult>,
+}
+
+impl Scope {
+    fn new(playbook: &Playbook, overrides: &[(String, String)]) -> Result<Self> {
+        let mut scope = Scope {
+            vars: playbook.vars.clone(),
+            ..Scope::default()
+        };
+        scope.vars.extend(overrides.iter().cloned());
+        for (name, value) in &playbook.env {
+            let value = scope
+                .interpolate(value)
+                .map_err(|e| anyhow!("env.{name}: {e}"))?;
+            scope.env.insert(name.clone(), value);
+        }
+        Ok(scope)
+    }
+
+    fn lookup(&self, path: &str) -> Result<String> {
+        if let Some(name) = path.strip_prefix("vars.") {
+            return self
+                .vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown variable 'vars.{name}'"));
+        }
+        if let Some(name) = path.strip_prefix("env.") {
+            return self.env.get(name).cloned().ok_or_else(|| {
+                anyhow!("'env.{name}' is not set (only playbook `env:` and $HAUSKI_ENV captures)")
+            });
+        }
+        if let Some((id, field)) = path
+            .strip_prefix("steps.")
+            .and_then(|p| p.split_once(".outputs"))
+            .filter(|(_, field)| field.is_empty() || field.starts_with('.'))
+        {
+            let result = self.step(id)?;
+            return match json_path(&result.outputs, field) {
+                Some(Value::String(text)) => Ok(text.clone()),
+                Some(Value::Null) | None => bail!("step '{id}' has no output 'outputs{field}'"),
+                Some(other) => Ok(other.to_string()),
+            };
+        }
+        if let Some((id, field)) = path.strip_prefix("steps.").and_then(|p| p.rsplit_once('.')) {
+            let result = self.step(id)?;
+            return match field {
+                "output" => Ok(result.output.clone()),
+                "outcome" => Ok(result.outcome.to_string()),
+                other => {
+                    bail!("unknown step field '{other}' (expected output, outputs.* or outcome)")
+                }
+            };
+        }
+        bail!(
+            "unsupported expression '{path}' (vars.*, env.*, steps.<id>.output|outputs.*|outcome)"
+        )
+    }
+
+    fn step(&self, id: &str) -> Result<&StepResult> {
+        self.steps
+            .get(id)
+            .ok_or_else(|| anyhow!("step '{id}' has not run yet"))
+    }
+
+    /// Replaces every `${{ expr }}` in `text`; inserted values are not re-scanned.
+    fn interpolate(&self, text: &str) -> Result<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${{") {
+            out.push_str(&rest[..start]);
+            let expr = &rest[start + 3..];
+            let end = expr
+                .find("}}")
+                .ok_or_else(|| anyhow!("unterminated expression in '{text}'"))?;
+            out.push_str(&self.lookup(expr[..end].trim())?);
+            rest = &expr[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn interpolate_value(&self, value: &mut serde_yaml_ng::Value) -> Result<()> {
+        match value {
+            serde_yaml_ng::Value::String(s) => *s = self.interpolate(s)?,
+            serde_yaml_ng::Value::Sequence(items) => {
+                for item in items {
+                    self.interpolate_value(item)?;
+                }
+            }
+            serde_yaml_ng::Value::Mapping(map) => {
+                for (_, item) in map.iter_mut() {
+                    self.interpolate_value(item)?;
+                }
+            }
+            serde_yaml_ng::Value::Tagged(tagged) => self.interpolate_value(&mut tagged.value)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Evaluates an `if:` condition, optionally wrapped in `${{ }}`:
+    /// `a == b`, `a != b`, `contains(a, b)`, `!a` or a single operand checked
+    /// for truthiness (empty, `false`, `0`, `no` and `off` are false).
+    fn evaluate(&self, condition: &str) -> Result<bool> {
+        let mut expr = condition.trim();
+        if let Some(inner) = expr.strip_prefix("${{").and_then(|e| e.strip_suffix("}}")) {
+            if !inner.contains("}}") {
+                expr = inner.trim();
+            }
+        }
+        if let Some((left, right)) = split_outside_quotes(expr, "==") {
+            return Ok(self.operand(left)? == self.operand(right)?);
+        }
+        if let Some((left, right)) = split_outside_quotes(expr, "!=") {
+            return Ok(self.operand(left)? != self.operand(right)?);
+        }
+        if let Some(args) = expr
+            .strip_prefix("contains(")
+            .and_then(|e| e.strip_suffix(')'))
+        {
+            let (haystack, needle) = split_outside_quotes(args, ",")
+                .ok_or_else(|| anyhow!("contains() takes two arguments"))?;
+            return Ok(self.operand(haystack)?.contains(&self.operand(needle)?));
+        }
+        if let Some(inner) = expr.strip_prefix('!') {
+            return Ok(!truthy(&self.operand(inner)?));
+        }
+        Ok(truthy(&self.operand(expr)?))
+    }
+
+    /// A quoted literal, a `vars.`/`env.`/`steps.` reference or a bare literal.
+    fn operand(&self, token: &str) -> Result<String> {
+        let token = token.trim();
+        for quote in ['\'', '"'] {
+            if let Some(inner) = token
+                .strip_prefix(quote)
+                .and_then(|t| t.strip_suffix(quote))
+            {
+                return Ok(inner.to_string());
+            }
+        }
+        if ["vars.", "env.", "steps."]
+            .iter()
+            .any(|prefix| token.starts_with(prefix))
+        {
+            return self.lookup(token);
+        }
+        Ok(token.to_string())
+    }
+}
+
+fn split_outside_quotes<'a>(expr: &'a str, op: &str) -> Option<(&'a str, &'a str)> {
+    let mut quote = None;
+    for (i, c) in expr.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if expr[i..].starts_with(op) => return Some((&expr[..i], &expr[i + op.len()..])),
+            None => {}
+        }
+    }
+    None
+}
+
+fn truthy(value: &str) -> bool {
+    !matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "" | "false" | "0" | "no" | "off"
+    )
+}
+
+/// `KEY=VALUE` lines a shell step wrote to `$HAUSKI_ENV`.
+fn parse_env_capture(text: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    text.lines().filter_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        let key = key.trim();
+        let valid = !key.is_empty()
+            && !key.starts_with('#')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        valid.then(|| (key.to_string(), value.to_string()))
+    })
+}
+
+/// Operator-controlled settings; never taken from the playbook itself.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub yes: bool,
+    pub unsafe_shell: bool,
+    /// Base URL of the local HausKI core for `index_upsert`/`memory_set`.
+    pub base_url: String,
+    /// Roots for `file_read`; canonicalized before use.
+    pub read_roots: Vec<PathBuf>,
+    pub routing: RoutingPolicy,
+    pub memory_token: Option<String>,
+    /// API token of the core (`HAUSKI_TOKEN`); only ever sent to `base_url`.
+    pub api_token: Option<String>,
+    /// Print step outputs as one JSON array at the end.
+    pub json: bool,
+    /// `--var KEY=VALUE` overrides for the playbook's `vars`.
+    pub vars: Vec<(String, String)>,
+    /// Upper bound for steps running at the same time.
+    pub parallel: usize,
+}
+
+pub fn run_playbook(playbook_path: &str, opts: &RunOptions) -> Result<()> {
+    let content = fs::read_to_string(playbook_path)
+        .with_context(|| format!("Could not read playbook file: {playbook_path}"))?;
+    let playbook = parse_playbook(&content)
+        .map_err(|e| anyhow!("Could not parse playbook file {playbook_path}: {e}"))?;
+    let steps = &playbook.steps;
+
+    // Refuse before running anything, so a playbook never stops halfway on this.
+    if !opts.unsafe_shell {
+        if let Some(step) = steps.iter().find(|s| matches!(s.kind, StepKind::Shell(_))) {
+            bail!(
+                "Step '{}' uses raw shell (`run:`). Use declared actions or pass --unsafe-shell.",
+                step.id
+            );
+        }
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let client = reqwest::Client::new();
+    let guard = EgressGuard::from_policy(&opts.routing)
+        .map_err(|e| anyhow!("invalid egress policy: {e}"))?;
+    let egress = AllowlistedClient::build(reqwest::Client::builder(), guard)
+        .map_err(|e| anyhow!("egress client: {e}"))?;
+    let shared = Arc::new(opts.clone());
+    // Every step is confirmed on its own, so interactive runs stay sequential.
+    let limit = if opts.yes { opts.parallel.max(1) } else { 1 };
+
+    let mut scope = Scope::new(&playbook, &opts.vars)?;
+    let mut report: Vec<Option<Value>> = vec![None; steps.len()];
+    let mut pending = vec![true; steps.len()];
+    let mut failure: Option<anyhow::Error> = None;
+    let started = Instant::now();
+    runtime.block_on(async {
+        let mut running = JoinSet::new();
+        loop {
+            while failure.is_none() && running.len() < limit {
+                let ready = (0..steps.len()).find(|&i| {
+                    pending[i]
+                        && steps[i]
+                            .needs
+                            .iter()
+                            .all(|need| scope.steps.contains_key(need))
+                });
+                let Some(i) = ready else { break };
+                pending[i] = false;
+                let step = &steps[i];
+                match prepare_step(i, steps.len(), step, &scope, opts) {
+                    Ok(Some(kind)) => {
+                        let (id, env) = (step.id.clone(), scope.env.clone());
+                        let (opts, client, egress) =
+                            (shared.clone(), client.clone(), egress.clone());
+                        running.spawn(async move {
+                            let step_started = Instant::now();
+                            let output = match kind {
+                                StepKind::Shell(cmd) => {
+                                    tokio::task::spawn_blocking(move || run_shell(&cmd, &env))
+                                        .await
+                                        .map_err(|e| anyhow!("shell step panicked: {e}"))
+                                        .and_then(|result| result)
+                                }
+                                StepKind::Action(action) => {
+                                    run_action(&action, &id, &opts, &client, &egress)
+                                        .await
+                                        .map(|output| (output, Vec::new()))
+                                }
+                            };
+                            (i, output, step_started.elapsed())
+                        });
+                    }
+                    Ok(None) => {
+                        scope.steps.insert(
+                            step.id.clone(),
+                            StepResult {
+                                output: String::new(),
+                                outputs: Value::Null,
+                                outcome: "skipped",
+                            },
+                        );
+                        report[i] =
+                            Some(json!({"step": i + 1, "id": step.id, "outcome": "skipped"}));
+                    }
+                    Err(e) => {
+                        report[i] = Some(failed_entry(i, &step.id, &e, Duration::ZERO));
+                        failure = Some(e);
+                    }
+                }
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (i, output, elapsed) = match joined {
+                Ok(done) => done,
+                Err(e) => {
+                    failure.get_or_insert(anyhow!("step task failed: {e}"));
+                    continue;
+                }
+            };
+            let step = &steps[i];
+            let (output, captured) = match output {
+                Ok(done) => done,
+                Err(e) => {
+                    let e = anyhow!("Step {} ('{}') failed: {e}", i + 1, step.id);
+                    report[i] = Some(failed_entry(i, &step.id, &e, elapsed));
+                    failure.get_or_insert(e);
+                    continue;
+                }
+            };
+
+            let mut entry = json!({
+                "step": i + 1,
+                "id": step.id,
+                "outcome": "success",
+                "output": output.text,
+                "elapsed_ms": elapsed.as_millis(),
+            });
+            if !output.data.is_null() {
+                entry["outputs"] = output.data.clone();
+            }
+            report[i] = Some(entry);
+            if !opts.json && !output.text.is_empty() {
+                println!("{}", output.text);
+            }
+            scope.env.extend(captured);
+            scope.steps.insert(
+                step.id.clone(),
+                StepResult {
+                    output: output.text,
+                    outputs: output.data,
+                    outcome: "success",
+                },
+            );
+        }
+    });
+
+    let report: Vec<Value> = report
+        .into_iter()
+        .zip(steps)
+        .enumerate()
+        .map(|(i, (entry, step))| {
+            entry.unwrap_or_else(|| json!({"step": i + 1, "id": step.id, "outcome": "not_run"}))
+        })
+        .collect();
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if steps.len() > 1 {
+        print_report(&report, started.elapsed(), limit);
+    }
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Evaluates the step's `if:` and resolves its expressions; `None` means skipped.
+fn prepare_step(
+    i: usize,
+    total: usize,
+    step: &Step,
+    scope: &Scope,
+    opts: &RunOptions,
+) -> Result<Option<StepKind>> {
+    if let Some(condition) = &step.condition {
+        let run = scope.evaluate(condition).map_err(|e| {
+            anyhow!(
+                "Step {} ('{}'): invalid condition '{condition}': {e}",
+                i + 1,
+                step.id
+            )
+        })?;
+        if !run {
+            info!(
+                "Skipping step {} ({}): `if: {condition}` is false",
+                i + 1,
+                step.id
+            );
+            return Ok(None);
+        }
+    }
+
+    let kind = step
+        .resolve(scope)
+        .map_err(|e| anyhow!("Step {} ('{}'): {e}", i + 1, step.id))?;
+    let description = kind.describe();
+    if !opts.yes {
+        confirm_step(i, total, &description)?;
+    }
+    info!("Executing step {} ({}): {}", i + 1, step.id, description);
+    Ok(Some(kind))
+}
+
+fn failed_entry(i: usize, id: &str, error: &anyhow::Error, elapsed: Duration) -> Value {
+    json!({
+        "step": i + 1,
+        "id": id,
+        "outcome": "failed",
+        "error": error.to_string(),
+        "elapsed_ms": elapsed.as_millis(),
+    })
+}
+
+/// Combined report on stderr, so stdout keeps only the step outputs.
+fn print_report(report: &[Value], elapsed: Duration, parallel: usize) {
+    let width = report
+        .iter()
+        .map(|entry| entry["id"].as_str().unwrap_or_default().chars().count())
+        .max()
+        .unwrap_or_default();
+    eprintln!(
+        "\nRun report: {} steps in {:.1}s (up to {parallel} in parallel)",
+        report.len(),
+        elapsed.as_secs_f64()
+    );
+    for entry in report {
+        let id = entry["id"].as_str().unwrap_or_default();
+        let outcome = entry["outcome"].as_str().unwrap_or_default();
+        match entry["elapsed_ms"].as_u64() {
+            Some(ms) => eprintln!("  {id:<width$}  {outcome:<8}  {ms} ms"),
+            None => eprintln!("  {id:<width$}  {outcome}"),
+        }
+    }
+}
+
+fn confirm_step(i: usize, total: usize, description: &str) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        bail!(
+            "Confirmation required for step {}: '{}'. Use --yes to bypass (stdin/stderr not a TTY).",
+            i + 1,
+            description
+        );
+    }
+    eprintln!("\n--- Step {} (of {}):", i + 1, total);
+    eprintln!("{description}");
+    eprint!("Execute this step? [y/N] ");
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim().to_lowercase();
+    if input != "y" && input != "yes" {
+        bail!("Execution aborted by user at step {}.", i + 1);
+    }
+    Ok(())
+}
+
+/// Runs `cmd` with the captured environment; returns its output and the
+/// `KEY=VALUE` lines it appended to `$HAUSKI_ENV`, for the following steps.
+fn run_shell(
+    cmd: &str,
+    env: &BTreeMap<String, String>,
+) -> Result<(StepOutput, Vec<(String, String)>)> {
+    warn!("executing raw shell step (--unsafe-shell)");
+    let capture = tempfile::NamedTempFile::new().context("cannot create $HAUSKI_ENV file")?;
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .envs(env.iter())
+        .env("HAUSKI_ENV", capture.path())
+        .output()
+        .with_context(|| format!("Failed to execute command: {cmd}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut error_output = String::new();
+        if !stdout.is_empty() {
+            error_output.push_str("stdout:\n");
+            error_output.push_str(&stdout);
+        }
+        if !stderr.is_empty() {
+            if !error_output.is_empty() {
+                error_output.push('\n');
+            }
+            error_output.push_str("stderr:\n");
+            error_output.push_str(&stderr);
+        }
+        bail!("status {}:\n{}", output.status, error_output);
+    }
+    let captured = fs::read_to_string(capture.path()).unwrap_or_default();
+    let output = StepOutput {
+        text: stdout.trim_end().to_string(),
+        data: Value::Null,
+    };
+    Ok((output, parse_env_capture(&captured).collect()))
+}
+
+async fn run_action(
+    action: &Action,
+    step_id: &str,
+    opts: &RunOptions,
+    client: &reqwest::Client,
+    egress: &AllowlistedClient,
+) -> Result<StepOutput> {
+    match action {
+        Action::HttpCall {
+            method,
+            url,
+            headers,
+            body,
+        } => {
+            let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .map_err(|_| anyhow!("invalid HTTP method '{method}'"))?;
+            let mut request = egress
+                .request(method, url)
+                .map_err(|e| anyhow!("egress denied: {e}"))?;
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let response = egress.send(request).await?;
+            let status = response.status();
+            let text = response.text().await?;
+            if !status.is_success() {
+                bail!("HTTP {status}: {text}");
+            }
+            Ok(StepOutput {
+                data: json!({
+                    "status": status.as_u16(),
+                    "json": serde_json::from_str::<Value>(&text).unwrap_or(Value::Null),
+                    "body": text,
+                }),
+                text: format!("HTTP {status}\n{text}"),
+            })
+        }
+        Action::IndexUpsert {
+            doc_id,
+            namespace,
+            text,
+            meta,
+        } => {
+            let payload = json!({
+                "doc_id": doc_id,
+                "namespace": namespace,
+                "chunks": [{ "chunk_id": format!("{doc_id}#0"), "text": text }],
+                "meta": meta.clone().unwrap_or_else(|| json!({})),
+                "source_ref": {
+                    "origin": "tool",
+                    "id": format!("playbook:{step_id}"),
+                    "trust_level": "low",
+                    "injected_by": "hauski-cli assist"
+                }
+            });
+            let request = core_post(client, opts, "/index/upsert", opts.api_token.as_ref());
+            expect_success(request.json(&payload).send().await?).await
+        }
+        Action::MemorySet {
+            key,
+            value,
+            ttl_sec,
+        } => {
+            let mut payload = json!({ "key": key, "value": value });
+            if let Some(ttl) = ttl_sec {
+                payload["ttl_sec"] = json!(ttl);
+            }
+            let token = opts.memory_token.as_ref().or(opts.api_token.as_ref());
+            let request = core_post(client, opts, "/memory/set", token);
+            expect_success(request.json(&payload).send().await?).await
+        }
+        Action::Ask {
+            query,
+            k,
+            namespace,
+            answer,
+        } => {
+            let mut payload = json!({ "query": query, "k": k, "namespace": namespace });
+            if *answer {
+                payload["answer"] = json!({ "mode": "extractive" });
+            }
+            let request = core_post(client, opts, "/ask", opts.api_token.as_ref());
+            let mut output = expect_success(request.json(&payload).send().await?).await?;
+            // The answer if there is one, otherwise one line per hit.
+            output.text = match output.data["answer"].as_str() {
+                Some(answer) => answer.to_string(),
+                None => output.data["hits"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|hit| {
+                        format!(
+                            "{}: {}",
+                            hit["doc_id"].as_str().unwrap_or_default(),
+                            hit["snippet"].as_str().unwrap_or_default()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            Ok(output)
+        }
+        Action::FileRead { path } => {
+            let text = read_allowed_file(Path::new(path), &opts.read_roots)?;
+            Ok(StepOutput {
+                data: json!({ "path": path, "bytes": text.len() }),
+                text,
+            })
+        }
+    }
+}
+
+/// POST to the configured core; tokens are only ever sent there.
+fn core_post(
+    client: &reqwest::Client,
+    opts: &RunOptions,
+    path: &str,
+    token: Option<&String>,
+) -> reqwest::RequestBuilder {
+    let request = client.post(format!("{}{path}", opts.base_url.trim_end_matches('/')));
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+async fn expect_success(response: reqwest::Response) -> Result<StepOutput> {
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        bail!("HTTP {status}: {text}");
+    }
+    Ok(StepOutput {
+        data: serde_json::from_str(&text).unwrap_or(Value::Null),
+        text,
+    })
+}
+
+/// Reads `path` if it resolves (after symlinks) below one of `roots`.
+pub fn read_allowed_file(path: &Path, roots: &[PathBuf]) -> Result<String> {
+    let resolved = path
+        .canonicalize()
+        .with_context(|| format!("cannot resolve {}", path.display()))?;
+    let allowed = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if !allowed {
+        bail!(
+            "{} is outside the allowed read paths (see --allow-read)",
+            path.display()
+        );
+    }
+
+    let metadata = fs::metadata(&resolved)?;
+    if !metadata.is_file() {
+        bail!("{} is not a regular file", path.display());
+    }
+    if metadata.len() > MAX_READ_BYTES {
+        bail!(
+            "{} exceeds the file_read limit of {MAX_READ_BYTES} bytes",
+            path.display()
+        );
+    }
+    fs::read_to_string(&resolved).with_context(|| format!("cannot read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions_and_shell_steps() {
+        let steps = parse_playbook(
+            r#"
+steps:
+  - id: fetch
+    action: http_call
+    url: https://api.example/health
+  - id: remember
+    action: memory_set
+    key: ci:last
+    value: red
+  - run: echo hi
+  - id: comment
+    kind: github_comment
+"#,
+        )
+        .unwrap()
+        .steps;
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(
+            steps[0].kind,
+            StepKind::Action(Action::HttpCall {
+                method: "GET".into(),
+                url: "https://api.example/health".into(),
+                headers: BTreeMap::new(),
+                body: None,
+            })
+        );
+        assert_eq!(steps[1].kind.describe(), "memory_set: ci:last");
+        assert_eq!(steps[2].id, "step-3");
+        assert_eq!(steps[2].kind, StepKind::Shell("echo hi".into()));
+    }
+
+    #[test]
+    fn step_type_keys_parse_with_typed_inputs() {
+        let steps = parse_playbook(
+            r#"
+steps:
+  - id: ping
+    http: https://api.example/health
+  - id: q
+    ask:
+      query: Wo liegt das Runbook?
+      k: "3"
+      answer: true
+  - id: keep
+    memory_set: { key: ci:last, value: red, ttl_sec: 60 }
+"#,
+        )
+        .unwrap()
+        .steps;
+
+        assert_eq!(
+            steps[0].kind.describe(),
+            "http_call: GET https://api.example/health"
+        );
+        assert_eq!(
+            steps[1].kind,
+            StepKind::Action(Action::Ask {
+                query: "Wo liegt das Runbook?".into(),
+                k: 3,
+                namespace: "default".into(),
+                answer: true,
+            })
+        );
+        assert_eq!(
+            steps[2].kind,
+            StepKind::Action(Action::MemorySet {
+                key: "ci:last".into(),
+                value: "red".into(),
+                ttl_sec: Some(60),
+            })
+        );
+
+        let err =
+            parse_playbook("steps:\n  - http: https://a.example\n    run: echo\n").unwrap_err();
+        assert!(err.to_string().contains("exactly one of"));
+        let err = parse_playbook("steps:\n  - ask: { query: x, k: viele }\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid action"));
+    }
+
+    #[test]
+    fn native_steps_pass_typed_outputs_on() {
+        use axum::{routing::post, Json, Router};
+        use std::sync::{Arc, Mutex};
+
+        let upserts = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let seen = upserts.clone();
+        let app = Router::new()
+            .route(
+                "/ask",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({
+                        "query": body["query"],
+                        "k": body["k"],
+                        "hits": [{"doc_id": "runbook.md", "score": 0.9, "snippet": "Schritt 1"}],
+                    }))
+                }),
+            )
+            .route(
+                "/index/upsert",
+                post(move |Json(body): Json<Value>| {
+                    let seen = seen.clone();
+                    async move {
+                        seen.lock().unwrap().push(body);
+                        Json(json!({ "status": "queued" }))
+                    }
+                }),
+            );
+        let server = tokio::runtime::Runtime::new().unwrap();
+        let listener = server
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        server.spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let playbook = dir.path().join("pb.yml");
+        fs::write(
+            &playbook,
+            r#"
+vars:
+  k: 2
+steps:
+  - id: q
+    ask: { query: runbook, k: "${{ vars.k }}" }
+  - id: note
+    if: contains(steps.q.outputs.hits.0.doc_id, 'runbook')
+    index_upsert:
+      doc_id: "seen-${{ steps.q.outputs.hits.0.doc_id }}"
+      text: "${{ steps.q.output }}"
+"#,
+        )
+        .unwrap();
+        let opts = RunOptions {
+            yes: true,
+            unsafe_shell: false,
+            base_url,
+            read_roots: Vec::new(),
+            routing: RoutingPolicy::default(),
+            memory_token: None,
+            api_token: None,
+            json: true,
+            vars: Vec::new(),
+            parallel: 1,
+        };
+        run_playbook(playbook.to_str().unwrap(), &opts).unwrap();
+
+        let upserts = upserts.lock().unwrap();
+        assert_eq!(upserts.len(), 1);
+        assert_eq!(upserts[0]["doc_id"], "seen-runbook.md");
+        assert_eq!(upserts[0]["chunks"][0]["text"], "runbook.md: Schritt 1");
+    }
+
+    #[test]
+    fn needs_default_to_the_previous_step_and_are_checked() {
+        let steps = parse_playbook(
+            "steps:\n  - id: a\n    run: 'true'\n  - id: b\n    needs: []\n    run: 'true'\n  - id: c\n    run: 'true'\n  - id: d\n    needs: [a, c]\n    run: echo ${{ steps.b.output }}\n",
+        )
+        .unwrap()
+        .steps;
+        assert!(steps[0].needs.is_empty());
+        assert!(steps[1].needs.is_empty());
+        assert_eq!(steps[2].needs, ["b"]);
+        assert_eq!(steps[3].needs, ["a", "c"]);
+
+        for (playbook, message) in [
+            ("  - id: a\n    needs: x\n    run: 'true'\n", "unknown step 'x'"),
+            (
+                "  - id: a\n    needs: b\n    run: 'true'\n  - id: b\n    run: 'true'\n",
+                "cycle",
+            ),
+            (
+                "  - id: a\n    run: 'true'\n  - id: b\n    needs: []\n    run: echo ${{ steps.a.output }}\n",
+                "does not need it",
+            ),
+            ("  - id: a\n    run: 'true'\n  - id: a\n    run: 'true'\n", "duplicate"),
+        ] {
+            let err = parse_playbook(&format!("steps:\n{playbook}")).unwrap_err();
+            assert!(err.to_string().contains(message), "{err}");
+        }
+    }
+
+    #[test]
+    fn independent_steps_run_in_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let playbook = dir.path().join("pb.yml");
+        fs::write(
+            &playbook,
+            format!(
+                r#"
+steps:
+  - id: left
+    needs: []
+    run: sleep 0.4 && echo links
+  - id: right
+    needs: []
+    run: sleep 0.4 && echo rechts
+  - id: join
+    needs: [left, right]
+    run: echo "${{{{ steps.left.output }}}}+${{{{ steps.right.output }}}}" > {out}
+"#,
+                out = out.display()
+            ),
+        )
+        .unwrap();
+        let mut opts = RunOptions {
+            yes: true,
+            unsafe_shell: true,
+            base_url: "http://127.0.0.1:9".into(),
+            read_roots: Vec::new(),
+            routing: RoutingPolicy::default(),
+            memory_token: None,
+            api_token: None,
+            json: true,
+            vars: Vec::new(),
+            parallel: 2,
+        };
+
+        let started = Instant::now();
+        run_playbook(playbook.to_str().unwrap(), &opts).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(750));
+        assert_eq!(fs::read_to_string(&out).unwrap().trim(), "links+rechts");
+
+        opts.parallel = 1;
+        let started = Instant::now();
+        run_playbook(playbook.to_str().unwrap(), &opts).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(800));
+    }
+
+    #[test]
+    fn failed_step_lets_running_steps_finish_but_starts_no_dependents() {
+        let dir = tempfile::tempdir().unwrap();
+        let (slow, after) = (dir.path().join("slow"), dir.path().join("after"));
+        let playbook = dir.path().join("pb.yml");
+        fs::write(
+            &playbook,
+            format!(
+                "steps:\n  - id: broken\n    run: exit 3\n  - id: slow\n    needs: []\n    run: sleep 0.2 && touch {}\n  - id: after\n    needs: broken\n    run: touch {}\n",
+                slow.display(),
+                after.display()
+            ),
+        )
+        .unwrap();
+        let opts = RunOptions {
+            yes: true,
+            unsafe_shell: true,
+            base_url: "http://127.0.0.1:9".into(),
+            read_roots: Vec::new(),
+            routing: RoutingPolicy::default(),
+            memory_token: None,
+            api_token: None,
+            json: true,
+            vars: Vec::new(),
+            parallel: 4,
+        };
+
+        let err = run_playbook(playbook.to_str().unwrap(), &opts).unwrap_err();
+        assert!(err.to_string().contains("'broken'"), "{err}");
+        assert!(slow.exists());
+        assert!(!after.exists());
+    }
+
+    #[test]
+    fn unknown_action_is_an_error() {
+        let err = parse_playbook("steps:\n  - action: rm_rf\n    path: /\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid action"));
+    }
+
+    #[test]
+    fn shell_steps_require_unsafe_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let playbook = dir.path().join("pb.yml");
+        fs::write(
+            &playbook,
+            format!("steps:\n  - run: touch {}\n", marker.display()),
+        )
+        .unwrap();
+
+        let mut opts = RunOptions {
+            yes: true,
+            unsafe_shell: false,
+            base_url: "http://127.0.0.1:9".into(),
+            read_roots: vec![dir.path().to_path_buf()],
+            routing: RoutingPolicy::default(),
+            memory_token: None,
+            api_token: None,
+            json: false,
+            vars: Vec::new(),
+            parallel: 1,
+        };
+        let err = run_playbook(playbook.to_str().unwrap(), &opts).unwrap_err();
+        assert!(err.to_string().contains("--unsafe-shell"));
+        assert!(!marker.exists());
+
+        opts.unsafe_shell = true;
+        run_playbook(playbook.to_str().unwrap(), &opts).unwrap();
+        assert!(marker.exists());
+    }
+
+    #[test]
+    fn conditions_compare_interpolated_values() {
+        let mut scope = Scope::new(
+            &parse_playbook("vars:\n  mode: fast\n  retries: 3\n").unwrap(),
+            &[("mode".into(), "slow".into())],
+        )
+        .unwrap();
+        scope.steps.insert(
+            "check".into(),
+            StepResult {
+                output: "HTTP 200 OK\nall green".into(),
+                outputs: json!({"status": 200, "json": {"checks": [{"name": "db"}]}}),
+                outcome: "success",
+            },
+        );
+
+        assert!(scope.evaluate("vars.mode == 'slow'").unwrap());
+        assert!(scope.evaluate("${{ vars.retries != 2 }}").unwrap());
+        assert!(scope
+            .evaluate("contains(steps.check.output, 'green')")
+            .unwrap());
+        assert!(!scope
+            .evaluate("steps.check.outcome == \"skipped\"")
+            .unwrap());
+        assert!(!scope.evaluate("!vars.retries").unwrap());
+        assert!(scope.evaluate("vars.missing").is_err());
+        assert_eq!(
+            scope
+                .interpolate("${{ vars.mode }}/${{vars.retries}}")
+                .unwrap(),
+            "slow/3"
+        );
+        assert!(scope.interpolate("${{ vars.mode").is_err());
+        assert!(scope.evaluate("steps.check.outputs.status == 200").unwrap());
+        assert_eq!(
+            scope
+                .interpolate("${{ steps.check.outputs.json.checks.0.name }}")
+                .unwrap(),
+            "db"
+        );
+        assert!(scope
+            .interpolate("${{ steps.check.outputs.missing }}")
+            .is_err());
+    }
+
+    #[test]
+    fn steps_branch_on_earlier_results_and_captured_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let skipped = dir.path().join("skipped");
+        let playbook = dir.path().join("pb.yml");
+        fs::write(
+            &playbook,
+            format!(
+                r#"
+vars:
+  name: hauski
+steps:
+  - id: check
+    run: echo ok
+  - id: export
+    run: echo "BRANCH=main" >> "$HAUSKI_ENV"
+  - id: write
+    if: steps.check.output == 'ok'
+    run: echo "${{{{ env.BRANCH }}}}-${{{{ vars.name }}}}-$BRANCH" > {out}
+  - id: never
+    if: vars.name == 'other'
+    run: touch {skipped}
+"#,
+                out = out.display(),
+                skipped = skipped.display()
+            ),
+        )
+        .unwrap();
+
+        let opts = RunOptions {
+            yes: true,
+            unsafe_shell: true,
+            base_url: "http://127.0.0.1:9".into(),
+            read_roots: vec![dir.path().to_path_buf()],
+            routing: RoutingPolicy::default(),
+            memory_token: None,
+            api_token: None,
+            json: false,
+            vars: Vec::new(),
+            parallel: 1,
+        };
+        run_playbook(playbook.to_str().unwrap(), &opts).unwrap();
+        assert_eq!(fs::read_to_string(&out).unwrap().trim(), "main-hauski-main");
+        assert!(!skipped.exists());
+    }
+
+    #[test]
+    fn file_read_stays_within_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let inside_file = root.path().join("notes.md");
+        let outside_file = outside.path().join("secret.txt");
+        fs::write(&inside_file, "hello").unwrap();
+        fs::write(&outside_file, "secret").unwrap();
+        let roots = vec![root.path().to_path_buf()];
+
+        assert_eq!(read_allowed_file(&inside_file, &roots).unwrap(), "hello");
+        assert!(read_allowed_file(&outside_file, &roots).is_err());
+        // `..` must not lead out of the root, even though the path starts inside it.
+        let escape = root
+            .path()
+            .join("..")
+            .join(outside.path().file_name().unwrap());
+        assert!(read_allowed_file(&escape.join("secret.txt"), &roots).is_err());
+    }
+}
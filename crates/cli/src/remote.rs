@@ -0,0 +1,109 @@
+//! Verbindung zu einem laufenden HausKI-Core.
+//!
+//! Ziel ist in dieser Reihenfolge: `--base-url` des Kommandos, globales
+//! `--host`, `HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE`, http://127.0.0.1:8080.
+//! `--host`/`HAUSKI_HOST` darf verkürzt sein (`nas` → http://nas:8080).
+//! Verlangt der Core ein API-Token (`HAUSKI_API_TOKEN` auf dem Server), liest
+//! die CLI es aus `HAUSKI_TOKEN` und sendet es als Bearer-Token mit – bewusst
+//! nur per Umgebung, damit es nicht in der Prozessliste steht.
+
+use std::{env, sync::OnceLock};
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+pub(crate) const DEFAULT_BASE: &str = "http://127.0.0.1:8080";
+const DEFAULT_PORT: u16 = 8080;
+
+static HOST: OnceLock<String> = OnceLock::new();
+
+/// Remembers the global `--host` flag for [`base_url`].
+pub(crate) fn set_host(host: String) {
+    let _ = HOST.set(host);
+}
+
+/// Base URL of the core; `explicit` is a command's own `--base-url`.
+pub(crate) fn base_url(explicit: Option<String>) -> String {
+    if let Some(base) = explicit {
+        return base;
+    }
+    HOST.get()
+        .cloned()
+        .or_else(|| env::var("HAUSKI_HOST").ok())
+        .filter(|host| !host.trim().is_empty())
+        .map(|host| normalize_host(&host))
+        .or_else(|| env::var("HAUSKI_INTERNAL_BASE").ok())
+        .unwrap_or_else(|| DEFAULT_BASE.to_string())
+}
+
+/// `nas` → `http://nas:8080`, `nas:9000` → `http://nas:9000`; URLs stay as they are.
+fn normalize_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.contains("://") {
+        return host.to_string();
+    }
+    let has_port = match host.rsplit_once(':') {
+        // `[::1]:8080` or `nas:8080`, but not a bare IPv6 address
+        Some((name, port)) => {
+            port.parse::<u16>().is_ok() && (!name.contains(':') || name.ends_with(']'))
+        }
+        None => false,
+    };
+    if has_port {
+        format!("http://{host}")
+    } else {
+        format!("http://{host}:{DEFAULT_PORT}")
+    }
+}
+
+/// API token from `HAUSKI_TOKEN`.
+pub(crate) fn token() -> Option<String> {
+    env::var("HAUSKI_TOKEN")
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Client for requests to the core: sends the API token on every request.
+/// Only for the core – egress to other hosts needs a client without it.
+pub(crate) fn client_builder() -> Result<reqwest::ClientBuilder> {
+    let mut headers = HeaderMap::new();
+    if let Some(token) = token() {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))
+            .context("HAUSKI_TOKEN enthält ungültige Zeichen")?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    Ok(reqwest::Client::builder().default_headers(headers))
+}
+
+pub(crate) fn client() -> Result<reqwest::Client> {
+    client_builder()?
+        .build()
+        .context("HTTP-Client konnte nicht erzeugt werden")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_hosts_become_urls() {
+        assert_eq!(normalize_host("nas"), "http://nas:8080");
+        assert_eq!(normalize_host("nas:9000/"), "http://nas:9000");
+        assert_eq!(normalize_host("10.0.0.5"), "http://10.0.0.5:8080");
+        assert_eq!(normalize_host("[::1]:8081"), "http://[::1]:8081");
+        assert_eq!(
+            normalize_host("https://hauski.example"),
+            "https://hauski.example"
+        );
+    }
+
+    #[test]
+    fn explicit_base_url_wins() {
+        assert_eq!(
+            base_url(Some("http://other:1".into())),
+            "http://other:1".to_string()
+        );
+    }
+}
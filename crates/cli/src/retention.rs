@@ -0,0 +1,379 @@
+//! `hauski retention …`: Retention je Namespace ansehen, setzen und Decay vorab prüfen.
+//!
+//! `get` zeigt die gesetzten `RetentionConfig`s (`GET /index/retention`), `set`
+//! ändert einzelne Felder eines Namespaces und lässt die übrigen stehen
+//! (`PUT /index/retention/<ns>`), `--unset` entfernt ein Feld. `preview` zeigt
+//! per `POST /index/decay/preview`, wie stark die Dokumente eines Namespaces
+//! abgewertet werden – mit `--half-life` für einen Wert, bevor er gesetzt wird.
+//!
+//! Dauern als Zahl mit Einheit: `90s`, `30m`, `12h`, `30d`, `2w` (ohne Einheit:
+//! Sekunden).
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use hauski_indexd::{PurgeStrategy, RetentionConfig};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::{index::Backend, print_table, OutputArgs};
+
+#[derive(Args, Debug)]
+pub struct RetentionOptions {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long, global = true)]
+    pub base_url: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RetentionCmd {
+    /// Gesetzte Retention aller (oder eines) Namespaces anzeigen
+    Get { namespace: Option<String> },
+    /// Retention eines Namespaces ändern; nicht genannte Felder bleiben
+    Set {
+        namespace: String,
+        /// Halbwertszeit des Decays, z. B. `30d`
+        #[arg(long, value_parser = parse_duration)]
+        half_life: Option<u64>,
+        /// Höchstzahl Dokumente
+        #[arg(long)]
+        max_items: Option<usize>,
+        /// Höchstalter, z. B. `90d`
+        #[arg(long, value_parser = parse_duration)]
+        max_age: Option<u64>,
+        /// Was bei überschrittenen Limits zuerst geht
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Feld entfernen (mehrfach möglich)
+        #[arg(long, value_enum)]
+        unset: Vec<Field>,
+    },
+    /// Decay-Faktoren der Dokumente eines Namespaces vorab ansehen
+    Preview {
+        #[arg(default_value = "default")]
+        namespace: String,
+        /// Halbwertszeit ausprobieren statt der gesetzten, z. B. `7d`
+        #[arg(long, value_parser = parse_duration)]
+        half_life: Option<u64>,
+        /// Angezeigte Dokumente (am stärksten abgewertete zuerst)
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    Oldest,
+    LowestScore,
+}
+
+impl From<Strategy> for PurgeStrategy {
+    fn from(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::Oldest => PurgeStrategy::Oldest,
+            Strategy::LowestScore => PurgeStrategy::LowestScore,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    HalfLife,
+    MaxItems,
+    MaxAge,
+    Strategy,
+}
+
+/// `30d` → 2_592_000; a bare number is seconds.
+fn parse_duration(raw: &str) -> Result<u64, String> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("erwartet z. B. 30d oder 3600, bekommen: {raw}"))?;
+    let factor = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        other => return Err(format!("unbekannte Einheit '{other}' (s, m, h, d, w)")),
+    };
+    match number.checked_mul(factor) {
+        Some(0) => Err("Dauer muss größer als 0 sein".to_string()),
+        Some(seconds) => Ok(seconds),
+        None => Err(format!("Dauer zu groß: {raw}")),
+    }
+}
+
+/// 2_592_000 → `30d`; the largest unit that divides evenly.
+fn format_duration(seconds: u64) -> String {
+    [(604_800, "w"), (86_400, "d"), (3_600, "h"), (60, "m")]
+        .iter()
+        .find(|(unit, _)| seconds >= *unit && seconds.is_multiple_of(*unit))
+        .map_or_else(
+            || format!("{seconds}s"),
+            |(unit, suffix)| format!("{}{suffix}", seconds / unit),
+        )
+}
+
+pub fn run(opts: RetentionOptions, cmd: RetentionCmd) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let backend = Backend::remote(opts.base_url.clone())?;
+    runtime.block_on(run_async(&backend, cmd, opts.output.json))
+}
+
+async fn run_async(backend: &Backend, cmd: RetentionCmd, json: bool) -> Result<()> {
+    match cmd {
+        RetentionCmd::Get { namespace } => {
+            let mut configs = configs(backend).await?;
+            if let Some(namespace) = &namespace {
+                configs.retain(|name, _| name == namespace);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&configs)?);
+            } else if configs.is_empty() {
+                match namespace {
+                    Some(namespace) => println!(
+                        "Keine Retention für '{namespace}' gesetzt (Decay nach der Recency-Policy)."
+                    ),
+                    None => println!("Keine Retention gesetzt."),
+                }
+            } else {
+                print_configs(&configs);
+            }
+        }
+        RetentionCmd::Set {
+            namespace,
+            half_life,
+            max_items,
+            max_age,
+            strategy,
+            unset,
+        } => {
+            if half_life.is_none()
+                && max_items.is_none()
+                && max_age.is_none()
+                && strategy.is_none()
+                && unset.is_empty()
+            {
+                bail!("nichts zu ändern: --half-life, --max-items, --max-age, --strategy oder --unset angeben");
+            }
+            if max_items == Some(0) {
+                bail!("--max-items muss größer als 0 sein (ohne Limit: --unset max-items)");
+            }
+            let mut configs = configs(backend).await?;
+            let mut config = configs.remove(&namespace).unwrap_or(RetentionConfig {
+                half_life_seconds: None,
+                max_items: None,
+                max_age_seconds: None,
+                purge_strategy: None,
+            });
+            for field in unset {
+                match field {
+                    Field::HalfLife => config.half_life_seconds = None,
+                    Field::MaxItems => config.max_items = None,
+                    Field::MaxAge => config.max_age_seconds = None,
+                    Field::Strategy => config.purge_strategy = None,
+                }
+            }
+            config.half_life_seconds = half_life.or(config.half_life_seconds);
+            config.max_items = max_items.or(config.max_items);
+            config.max_age_seconds = max_age.or(config.max_age_seconds);
+            config.purge_strategy = strategy.map(Into::into).or(config.purge_strategy);
+
+            let path = format!("/index/retention/{}", encode(&namespace));
+            let response = backend
+                .call(Method::PUT, &path, Some(serde_json::to_value(&config)?))
+                .await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                println!("Retention für '{namespace}' gesetzt.");
+                print_configs(&HashMap::from([(namespace, config)]));
+            }
+        }
+        RetentionCmd::Preview {
+            namespace,
+            half_life,
+            limit,
+        } => {
+            let preview = backend
+                .call(
+                    Method::POST,
+                    "/index/decay/preview",
+                    Some(json!({ "namespace": namespace, "half_life_seconds": half_life })),
+                )
+                .await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&preview)?);
+            } else {
+                let max_age = configs(backend)
+                    .await?
+                    .get(&namespace)
+                    .and_then(|config| config.max_age_seconds);
+                print_preview(&preview, max_age, limit);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn configs(backend: &Backend) -> Result<HashMap<String, RetentionConfig>> {
+    let response = backend.call(Method::GET, "/index/retention", None).await?;
+    serde_json::from_value(response["configs"].clone()).context("ungültige Retention-Antwort")
+}
+
+fn encode(namespace: &str) -> String {
+    url::form_urlencoded::byte_serialize(namespace.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+fn print_configs(configs: &HashMap<String, RetentionConfig>) {
+    let mut names: Vec<&String> = configs.keys().collect();
+    names.sort();
+    let unset = || "-".to_string();
+    let rows = names
+        .into_iter()
+        .map(|name| {
+            let config = &configs[name];
+            [
+                name.clone(),
+                config.half_life_seconds.map_or_else(unset, format_duration),
+                config.max_items.map_or_else(unset, |n| n.to_string()),
+                config.max_age_seconds.map_or_else(unset, format_duration),
+                config
+                    .purge_strategy
+                    .map_or_else(unset, |strategy| match strategy {
+                        PurgeStrategy::Oldest => "oldest".to_string(),
+                        PurgeStrategy::LowestScore => "lowest_score".to_string(),
+                    }),
+            ]
+        })
+        .collect();
+    print_table(
+        [
+            "Namespace",
+            "Halbwertszeit",
+            "Max. Dokumente",
+            "Max. Alter",
+            "Strategie",
+        ],
+        rows,
+    );
+}
+
+fn print_preview(preview: &Value, max_age: Option<u64>, limit: usize) {
+    let items = preview["previews"].as_array().cloned().unwrap_or_default();
+    let namespace = preview["namespace"].as_str().unwrap_or_default();
+    let half_life = preview["half_life_seconds"]
+        .as_u64()
+        .map_or_else(|| "kein Decay".to_string(), format_duration);
+    println!(
+        "{namespace}: {} Dokumente, Halbwertszeit {half_life}",
+        items.len()
+    );
+    if items.is_empty() {
+        return;
+    }
+    let halved = items
+        .iter()
+        .filter(|item| item["decay_factor"].as_f64().unwrap_or(1.0) < 0.5)
+        .count();
+    println!("Unter Faktor 0.5: {halved}");
+    if let Some(max_age) = max_age {
+        let too_old = items
+            .iter()
+            .filter(|item| item["age_seconds"].as_u64().unwrap_or(0) > max_age)
+            .count();
+        println!(
+            "Älter als max. Alter ({}): {too_old}",
+            format_duration(max_age)
+        );
+    }
+
+    let rows = items
+        .iter()
+        .take(limit)
+        .map(|item| {
+            [
+                item["doc_id"].as_str().unwrap_or_default().to_string(),
+                format_age(item["age_seconds"].as_u64().unwrap_or(0)),
+                format!("{:.3}", item["decay_factor"].as_f64().unwrap_or(1.0)),
+            ]
+        })
+        .collect();
+    print_table(["Dokument", "Alter", "Faktor"], rows);
+    if items.len() > limit {
+        println!("… und {} weitere (-n erhöhen)", items.len() - limit);
+    }
+}
+
+/// Rounded down to the largest whole unit, e.g. `3d` for 3.5 days.
+fn format_age(seconds: u64) -> String {
+    [(86_400, "d"), (3_600, "h"), (60, "m")]
+        .iter()
+        .find(|(unit, _)| seconds >= *unit)
+        .map_or_else(
+            || format!("{seconds}s"),
+            |(unit, suffix)| format!("{}{suffix}", seconds / unit),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_round_trip() {
+        assert_eq!(parse_duration("30d"), Ok(2_592_000));
+        assert_eq!(parse_duration("3600"), Ok(3_600));
+        assert_eq!(parse_duration("2w"), Ok(1_209_600));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("3 Tage").is_err());
+        assert_eq!(format_duration(2_592_000), "30d");
+        assert_eq!(format_duration(5_400), "90m");
+        assert_eq!(format_duration(61), "61s");
+        assert_eq!(format_age(302_400), "3d");
+    }
+
+    #[tokio::test]
+    async fn set_merges_with_the_existing_config() {
+        let backend = Backend::offline();
+        let set = |half_life, strategy, unset| RetentionCmd::Set {
+            namespace: "chronik".into(),
+            half_life,
+            max_items: None,
+            max_age: Some(7_776_000),
+            strategy,
+            unset,
+        };
+        run_async(
+            &backend,
+            set(Some(86_400), Some(Strategy::Oldest), vec![]),
+            true,
+        )
+        .await
+        .unwrap();
+        run_async(&backend, set(None, None, vec![Field::Strategy]), true)
+            .await
+            .unwrap();
+
+        let config = &configs(&backend).await.unwrap()["chronik"];
+        assert_eq!(config.half_life_seconds, Some(86_400));
+        assert_eq!(config.max_age_seconds, Some(7_776_000));
+        assert_eq!(config.purge_strategy, None);
+        assert!(run_async(&backend, set(None, None, vec![]), true)
+            .await
+            .is_ok());
+    }
+}
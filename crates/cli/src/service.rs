@@ -0,0 +1,435 @@
+//! `hauski service …`: `hauski serve` als systemd-User-Dienst betreiben.
+//!
+//! `install` schreibt `~/.config/systemd/user/<name>.service` und – falls
+//! noch nicht vorhanden – eine Env-Datei (`~/.config/hauski/hauski.env`) mit
+//! den aktuell wirksamen `HAUSKI_*`-Pfaden als absolute Pfade. Die Unit
+//! startet bei Fehlern neu und läuft gehärtet: Dateisystem nur lesbar bis auf
+//! Arbeits-, State- und Datenverzeichnis, keine neuen Privilegien, nur
+//! IP-/Unix-Sockets. Danach folgen `daemon-reload` und `enable --now`
+//! (abschaltbar mit `--no-start`).
+//!
+//! `status`, `stop` und `logs` reichen an `systemctl --user` bzw.
+//! `journalctl --user` durch; mit `--json` liefert `status` die Properties
+//! aus `systemctl show` und `logs` das Journal als JSON-Zeilen.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use hauski_core::{PathDefault, CONFIG_PATH_VARS};
+use serde_json::{json, Value};
+
+use crate::{doctor::state_dir, say, OutputArgs};
+
+const DEFAULT_UNIT: &str = "hauski-core";
+
+/// Unit properties reported by `service status --json`.
+const STATUS_PROPERTIES: &str =
+    "Id,LoadState,ActiveState,SubState,MainPID,NRestarts,ExecMainStartTimestamp";
+
+#[derive(Args, Debug)]
+pub struct ServiceOptions {
+    /// Name der Unit (ohne `.service`)
+    #[arg(long, global = true, default_value = DEFAULT_UNIT)]
+    pub name: String,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ServiceCmd {
+    /// User-Unit für `hauski serve` erzeugen, aktivieren und starten
+    Install(InstallArgs),
+    /// `systemctl --user status`
+    Status,
+    /// `systemctl --user stop`
+    Stop,
+    /// Journal des Dienstes zeigen
+    Logs {
+        /// Anzahl Zeilen
+        #[arg(long, short = 'n', default_value_t = 100)]
+        lines: u32,
+        /// Neuen Einträgen folgen
+        #[arg(long, short, default_value_t = false)]
+        follow: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct InstallArgs {
+    /// Arbeitsverzeichnis des Dienstes (Default: aktuelles Verzeichnis)
+    #[arg(long)]
+    pub workdir: Option<PathBuf>,
+    /// Env-Datei (Default: ~/.config/hauski/hauski.env)
+    #[arg(long)]
+    pub env_file: Option<PathBuf>,
+    /// Bind-Adresse für `hauski serve`
+    #[arg(long)]
+    pub bind: Option<String>,
+    /// Zusätzlich beschreibbare Pfade (mehrfach möglich), z. B. der Index
+    #[arg(long = "read-write")]
+    pub read_write: Vec<PathBuf>,
+    /// Vorhandene Unit überschreiben
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    /// Nur schreiben, nicht aktivieren und starten
+    #[arg(long, default_value_t = false)]
+    pub no_start: bool,
+    /// Unit und Env-Datei nur ausgeben
+    #[arg(long, default_value_t = false)]
+    pub print: bool,
+}
+
+/// Everything the unit file is rendered from.
+#[derive(Debug)]
+struct UnitSpec {
+    exe: PathBuf,
+    workdir: PathBuf,
+    env_file: PathBuf,
+    bind: Option<String>,
+    read_write: Vec<PathBuf>,
+}
+
+fn render_unit(spec: &UnitSpec) -> String {
+    let mut exec = format!("{} serve", spec.exe.display());
+    if let Some(bind) = &spec.bind {
+        exec.push_str(&format!(" --bind {bind}"));
+    }
+    let read_write = spec
+        .read_write
+        .iter()
+        .map(|path| format!("-{}", path.display()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[Unit]
+Description=HausKI Core (Rust)
+After=network-online.target
+StartLimitIntervalSec=300
+StartLimitBurst=5
+
+[Service]
+Type=simple
+EnvironmentFile=-{env_file}
+WorkingDirectory={workdir}
+ExecStart={exec}
+Restart=on-failure
+RestartSec=5
+NoNewPrivileges=yes
+PrivateTmp=yes
+ProtectSystem=strict
+ProtectHome=read-only
+ReadWritePaths={read_write}
+ProtectKernelTunables=yes
+ProtectKernelModules=yes
+ProtectControlGroups=yes
+RestrictAddressFamilies=AF_UNIX AF_INET AF_INET6
+RestrictNamespaces=yes
+RestrictRealtime=yes
+RestrictSUIDSGID=yes
+LockPersonality=yes
+SystemCallArchitectures=native
+UMask=0077
+
+[Install]
+WantedBy=default.target
+",
+        env_file = spec.env_file.display(),
+        workdir = spec.workdir.display(),
+    )
+}
+
+/// Env file with the effective configuration paths of the loader, made
+/// absolute against `workdir`. Optional files are only listed when set or
+/// present.
+fn render_env(workdir: &Path, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let absolute = |value: &str| {
+        let path = Path::new(value);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            workdir.join(path.strip_prefix(".").unwrap_or(path))
+        }
+    };
+    let mut out = String::from("# HausKI-Core – geladen von systemd (EnvironmentFile)\n");
+    for (var, default) in CONFIG_PATH_VARS {
+        let path = match (
+            lookup(var).filter(|value| !value.trim().is_empty()),
+            default,
+        ) {
+            (Some(value), _) => absolute(&value),
+            (None, PathDefault::Path(path)) => absolute(path),
+            (None, PathDefault::IfExists(path)) if absolute(path).exists() => absolute(path),
+            (None, _) => continue,
+        };
+        out.push_str(&format!("{var}={}\n", path.display()));
+    }
+    for var in ["HAUSKI_ALLOWED_ORIGIN", "HAUSKI_MEMORY_TOKEN"] {
+        if let Some(value) = lookup(var) {
+            out.push_str(&format!("{var}={value}\n"));
+        }
+    }
+    out
+}
+
+fn systemd_user_dir() -> Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("systemd/user"))
+        .ok_or_else(|| anyhow!("kein Konfigurationsverzeichnis ($HOME) gefunden"))
+}
+
+fn unit_file_name(name: &str) -> String {
+    format!("{}.service", name.trim_end_matches(".service"))
+}
+
+pub fn run(opts: ServiceOptions, cmd: ServiceCmd) -> Result<()> {
+    let unit = unit_file_name(&opts.name);
+    match cmd {
+        ServiceCmd::Install(args) => install(&unit, args, opts.output.json),
+        ServiceCmd::Status if opts.output.json => {
+            let output = Command::new("systemctl")
+                .args(["--user", "show", &unit, "--property", STATUS_PROPERTIES])
+                .output()
+                .context("systemctl nicht ausführbar")?;
+            if !output.status.success() {
+                bail!(
+                    "systemctl --user show {unit} fehlgeschlagen: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let status = parse_show(&String::from_utf8_lossy(&output.stdout));
+            println!("{}", serde_json::to_string_pretty(&status)?);
+            Ok(())
+        }
+        ServiceCmd::Status => {
+            // `status` exits 3 for stopped units; the output says enough.
+            systemctl(&["status", &unit, "--no-pager"], false)
+        }
+        ServiceCmd::Stop => {
+            systemctl(&["stop", &unit], true)?;
+            if opts.output.json {
+                println!("{}", json!({"unit": unit, "stopped": true}));
+            }
+            Ok(())
+        }
+        ServiceCmd::Logs { lines, follow } => {
+            let lines = lines.to_string();
+            let mut argv = vec!["--user", "-u", &unit, "-n", &lines, "--no-pager"];
+            if follow {
+                argv.push("-f");
+            }
+            if opts.output.json {
+                // One JSON object per journal entry.
+                argv.extend(["-o", "json"]);
+            }
+            let status = Command::new("journalctl")
+                .args(&argv)
+                .status()
+                .context("journalctl nicht ausführbar")?;
+            if !status.success() {
+                bail!("journalctl {} fehlgeschlagen", argv.join(" "));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// `KEY=value` lines of `systemctl show` as a JSON object.
+fn parse_show(text: &str) -> Value {
+    let properties: serde_json::Map<String, Value> = text
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+        .collect();
+    Value::Object(properties)
+}
+
+fn install(unit: &str, args: InstallArgs, json: bool) -> Result<()> {
+    let workdir = match args.workdir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+    let workdir = workdir
+        .canonicalize()
+        .with_context(|| format!("Arbeitsverzeichnis {} fehlt", workdir.display()))?;
+    let env_file = match args.env_file {
+        Some(path) => path,
+        None => dirs::config_dir()
+            .ok_or_else(|| anyhow!("kein Konfigurationsverzeichnis ($HOME) gefunden"))?
+            .join("hauski/hauski.env"),
+    };
+    let mut read_write = vec![workdir.clone(), state_dir()];
+    read_write.extend(args.read_write);
+    let spec = UnitSpec {
+        exe: env::current_exe().context("Pfad der hauski-Binary unbekannt")?,
+        workdir,
+        env_file,
+        bind: args.bind,
+        read_write,
+    };
+    let unit_text = render_unit(&spec);
+    let env_text = render_env(&spec.workdir, |var| env::var(var).ok());
+
+    if args.print {
+        if json {
+            let result = json!({
+                "unit": unit,
+                "unit_file": unit_text,
+                "env_file": spec.env_file,
+                "env": env_text,
+            });
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        } else {
+            println!("# {unit}\n{unit_text}");
+            println!("# {}\n{env_text}", spec.env_file.display());
+        }
+        return Ok(());
+    }
+
+    let unit_path = systemd_user_dir()?.join(unit);
+    if unit_path.exists() && !args.force {
+        bail!(
+            "{} existiert bereits (--force zum Überschreiben)",
+            unit_path.display()
+        );
+    }
+    if let Some(parent) = unit_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&unit_path, unit_text)
+        .with_context(|| format!("{} nicht beschreibbar", unit_path.display()))?;
+    say(json, format!("{} geschrieben.", unit_path.display()));
+
+    let env_written = !spec.env_file.exists();
+    if env_written {
+        if let Some(parent) = spec.env_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(&spec.env_file, &env_text)?;
+        say(json, format!("{} geschrieben.", spec.env_file.display()));
+    } else {
+        say(
+            json,
+            format!("{} bleibt unverändert.", spec.env_file.display()),
+        );
+    }
+    fs::create_dir_all(state_dir())?;
+
+    systemctl(&["daemon-reload"], true)?;
+    if args.no_start {
+        say(
+            json,
+            format!("Starten mit: systemctl --user enable --now {unit}"),
+        );
+    } else {
+        systemctl(&["enable", "--now", unit], true)?;
+        say(json, format!("{unit} läuft; Logs: hauski service logs -f"));
+    }
+    if json {
+        let result = json!({
+            "unit": unit,
+            "unit_path": unit_path,
+            "env_file": spec.env_file,
+            "env_written": env_written,
+            "started": !args.no_start,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// The env file may carry `HAUSKI_MEMORY_TOKEN`, so it is owner-only.
+fn write_private(path: &Path, text: &str) -> Result<()> {
+    fs::write(path, text).with_context(|| format!("{} nicht beschreibbar", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+fn systemctl(args: &[&str], check: bool) -> Result<()> {
+    let status = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .context("systemctl nicht ausführbar")?;
+    if check && !status.success() {
+        bail!("systemctl --user {} fehlgeschlagen", args.join(" "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_is_hardened_and_restarts() {
+        let unit = render_unit(&UnitSpec {
+            exe: "/usr/local/bin/hauski".into(),
+            workdir: "/srv/hauski".into(),
+            env_file: "/home/u/.config/hauski/hauski.env".into(),
+            bind: Some("127.0.0.1:9090".into()),
+            read_write: vec!["/srv/hauski".into(), "/home/u/.local/state/hauski".into()],
+        });
+        assert!(unit.contains("ExecStart=/usr/local/bin/hauski serve --bind 127.0.0.1:9090\n"));
+        assert!(unit.contains("EnvironmentFile=-/home/u/.config/hauski/hauski.env\n"));
+        assert!(unit.contains("Restart=on-failure\n"));
+        assert!(unit.contains("ProtectSystem=strict\n"));
+        assert!(unit.contains("NoNewPrivileges=yes\n"));
+        assert!(
+            unit.contains("ReadWritePaths=-/srv/hauski -/home/u/.local/state/hauski\n"),
+            "{unit}"
+        );
+        assert_eq!(unit_file_name("hauski-core.service"), "hauski-core.service");
+    }
+
+    #[test]
+    fn parse_show_reads_properties() {
+        let status = parse_show("Id=hauski-core.service\nActiveState=active\nMainPID=42\n");
+        assert_eq!(status["ActiveState"], "active");
+        assert_eq!(status["MainPID"], "42");
+    }
+
+    #[test]
+    fn env_file_uses_absolute_paths() {
+        let env = render_env(Path::new("/srv/hauski"), |var| match var {
+            "HAUSKI_FLAGS" => Some("/etc/hauski/flags.yaml".into()),
+            "HAUSKI_MEMORY_TOKEN" => Some("secret".into()),
+            _ => None,
+        });
+        assert!(env.contains("HAUSKI_MODELS=/srv/hauski/configs/models.yml\n"));
+        assert!(env.contains("HAUSKI_FLAGS=/etc/hauski/flags.yaml\n"));
+        assert!(env.contains("HAUSKI_MEMORY_TOKEN=secret\n"));
+        assert!(!env.contains("HAUSKI_ALLOWED_ORIGIN"));
+    }
+
+    #[test]
+    fn env_file_carries_every_config_path_var() {
+        let workdir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(workdir.path().join("configs")).unwrap();
+        fs::write(workdir.path().join("configs/hauski.yml"), "version: 1\n").unwrap();
+        let env = render_env(workdir.path(), |var| match var {
+            "HAUSKI_TRUST_POLICY_PATH" => Some("./policies/trust.yaml".into()),
+            "HAUSKI_SYSTEM_CONFIG" => Some(String::new()),
+            _ => None,
+        });
+        let dir = workdir.path().display();
+        assert!(env.contains(&format!("HAUSKI_CONFIG={dir}/configs/hauski.yml\n")));
+        assert!(env.contains(&format!(
+            "HAUSKI_TRUST_POLICY_PATH={dir}/policies/trust.yaml\n"
+        )));
+        assert!(!env.contains("HAUSKI_CONTEXT_POLICY_PATH"));
+        // `/etc/hauski/hauski.yml` is only listed when it exists.
+        assert_eq!(
+            env.contains("HAUSKI_SYSTEM_CONFIG="),
+            Path::new(hauski_core::SYSTEM_CONFIG_PATH).exists()
+        );
+    }
+}
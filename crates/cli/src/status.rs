@@ -0,0 +1,334 @@
+//! `hauski status`: Kurzüberblick über einen laufenden HausKI-Core.
+//!
+//! Fragt `/ready`, `/version`, `/self/state` und `/metrics` ab und fasst
+//! zusammen: Bereitschaft, Version und Laufzeit, Indexgröße, Memory-Einträge,
+//! Einhaltung der Latenzbudgets aus `limits.yaml`, Request-Zähler und die
+//! Erreichbarkeit des Chat-Upstreams (aufgelöst wie im Server aus
+//! `HAUSKI_CHAT_UPSTREAM_URL` bzw. `flags.yaml`).
+//!
+//! Exit-Code wie bei `hauski doctor`: 0 = in Ordnung, 1 = eingeschränkt
+//! (nicht bereit, Budget überschritten, Upstream nicht erreichbar),
+//! 2 = Core nicht erreichbar.
+
+use std::{env, time::Duration};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use hauski_core::load_flags;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    doctor::{chat_upstream_url, probe},
+    remote, OutputArgs,
+};
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Status {
+    base_url: String,
+    reachable: bool,
+    ready: bool,
+    version: Option<String>,
+    uptime_seconds: Option<u64>,
+    safe_mode: Option<bool>,
+    index_documents: Option<u64>,
+    index_chunks: Option<u64>,
+    index_namespaces: Option<usize>,
+    memory_items: Option<u64>,
+    budgets: Vec<Budget>,
+    requests_total: u64,
+    server_errors_total: u64,
+    upstreams: Vec<Upstream>,
+    /// `ok`, `degraded` or `down`.
+    overall: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Budget {
+    name: String,
+    budget_ms: u64,
+    observed_p95_ms: Option<f64>,
+    within_budget: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct Upstream {
+    name: String,
+    url: String,
+    reachable: bool,
+    error: Option<String>,
+}
+
+pub fn run(args: StatusArgs) -> Result<i32> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let base_url = remote::base_url(args.base_url.clone());
+    let status = runtime.block_on(collect(&base_url))?;
+
+    if args.output.json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        print_status(&status);
+    }
+    Ok(match status.overall {
+        "ok" => 0,
+        "degraded" => 1,
+        _ => 2,
+    })
+}
+
+async fn collect(base_url: &str) -> Result<Status> {
+    let client = remote::client_builder()?
+        .timeout(Duration::from_secs(3))
+        .build()?;
+    let base = base_url.trim_end_matches('/');
+    let get = |path: &str| {
+        let request = client.get(format!("{base}{path}"));
+        async move {
+            let response = request.send().await.ok()?;
+            let ok = response.status().is_success();
+            Some((ok, response.text().await.ok()?))
+        }
+    };
+
+    let mut status = Status {
+        base_url: base.to_string(),
+        ..Status::default()
+    };
+    let Some((ready, _)) = get("/ready").await else {
+        status.overall = "down";
+        return Ok(status);
+    };
+    status.reachable = true;
+    status.ready = ready;
+
+    let json = |body: Option<(bool, String)>| {
+        body.filter(|(ok, _)| *ok)
+            .and_then(|(_, text)| serde_json::from_str::<Value>(&text).ok())
+    };
+    if let Some(version) = json(get("/version").await) {
+        status.version = version["version"].as_str().map(str::to_string);
+        status.uptime_seconds = version["uptime_seconds"].as_u64();
+    }
+    if let Some(state) = json(get("/self/state").await) {
+        apply_self_state(&mut status, &state);
+    }
+    if let Some((true, metrics)) = get("/metrics").await {
+        (status.requests_total, status.server_errors_total) = request_totals(&metrics);
+    }
+
+    let flags_path =
+        env::var("HAUSKI_FLAGS").unwrap_or_else(|_| "./configs/flags.yaml".to_string());
+    let flags = load_flags(&flags_path).ok();
+    if let Some(url) = chat_upstream_url(flags.as_ref()) {
+        let result = probe(&url).await;
+        status.upstreams.push(Upstream {
+            name: "chat".into(),
+            url,
+            reachable: result.is_ok(),
+            error: result.err(),
+        });
+    }
+
+    status.overall = overall(&status);
+    Ok(status)
+}
+
+fn apply_self_state(status: &mut Status, state: &Value) {
+    status.safe_mode = state["service"]["safe_mode"].as_bool();
+    status.index_documents = state["index"]["total_documents"].as_u64();
+    status.index_chunks = state["index"]["total_chunks"].as_u64();
+    status.index_namespaces = state["index"]["namespaces"].as_object().map(|n| n.len());
+    status.memory_items = state["memory"].as_object().map(|memory| {
+        memory["pinned"].as_u64().unwrap_or(0) + memory["unpinned"].as_u64().unwrap_or(0)
+    });
+    status.budgets = state["budgets"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|budget| Budget {
+            name: budget["name"].as_str().unwrap_or_default().to_string(),
+            budget_ms: budget["budget_ms"].as_u64().unwrap_or(0),
+            observed_p95_ms: budget["observed_p95_ms"].as_f64(),
+            within_budget: budget["within_budget"].as_bool(),
+        })
+        .collect();
+}
+
+/// Sums `http_requests_total` from the Prometheus text format: all requests
+/// and those answered with a 5xx status.
+fn request_totals(metrics: &str) -> (u64, u64) {
+    let mut total = 0;
+    let mut errors = 0;
+    for line in metrics.lines() {
+        let Some(rest) = line.strip_prefix("http_requests_total{") else {
+            continue;
+        };
+        let Some((labels, value)) = rest.split_once("} ") else {
+            continue;
+        };
+        let Ok(count) = value.trim().parse::<f64>() else {
+            continue;
+        };
+        let count = count as u64;
+        total += count;
+        if labels.contains("status=\"5") {
+            errors += count;
+        }
+    }
+    (total, errors)
+}
+
+fn overall(status: &Status) -> &'static str {
+    if !status.reachable {
+        return "down";
+    }
+    let over_budget = status
+        .budgets
+        .iter()
+        .any(|b| b.within_budget == Some(false));
+    let upstream_down = status.upstreams.iter().any(|u| !u.reachable);
+    if !status.ready || over_budget || upstream_down {
+        "degraded"
+    } else {
+        "ok"
+    }
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3_600 % 24, seconds / 60 % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes} min"),
+        (0, _) => format!("{hours} h {minutes} min"),
+        _ => format!("{days} d {hours} h"),
+    }
+}
+
+fn print_status(status: &Status) {
+    let label = match status.overall {
+        "ok" => "OK",
+        "degraded" => "EINGESCHRÄNKT",
+        _ => "NICHT ERREICHBAR",
+    };
+    println!("HausKI-Core {} – {label}", status.base_url);
+    if !status.reachable {
+        println!("  `hauski serve` bzw. `hauski service status` prüfen");
+        return;
+    }
+
+    let mut service = format!(
+        "{}, Version {}",
+        if status.ready {
+            "bereit"
+        } else {
+            "startet noch"
+        },
+        status.version.as_deref().unwrap_or("?")
+    );
+    if let Some(uptime) = status.uptime_seconds {
+        service.push_str(&format!(", läuft seit {}", format_uptime(uptime)));
+    }
+    if status.safe_mode == Some(true) {
+        service.push_str(", Safe-Mode");
+    }
+    println!("  Dienst    {service}");
+    if let Some(documents) = status.index_documents {
+        println!(
+            "  Index     {documents} Dokumente, {} Chunks, {} Namespaces",
+            status.index_chunks.unwrap_or(0),
+            status.index_namespaces.unwrap_or(0)
+        );
+    }
+    match status.memory_items {
+        Some(items) => println!("  Memory    {items} Einträge"),
+        None => println!("  Memory    nicht verfügbar"),
+    }
+    for budget in &status.budgets {
+        let observed = match (budget.observed_p95_ms, budget.within_budget) {
+            (Some(p95), Some(true)) => format!("p95 {p95:.0} ms ✓"),
+            (Some(p95), _) => format!("p95 {p95:.0} ms ✗ überschritten"),
+            (None, _) => "noch keine Messung".to_string(),
+        };
+        println!(
+            "  Budget    {}: {observed} (Limit {} ms)",
+            budget.name, budget.budget_ms
+        );
+    }
+    println!(
+        "  Requests  {} gesamt, {} mit 5xx",
+        status.requests_total, status.server_errors_total
+    );
+    for upstream in &status.upstreams {
+        match &upstream.error {
+            None => println!("  Upstream  {} {} erreichbar", upstream.name, upstream.url),
+            Some(err) => println!(
+                "  Upstream  {} {} nicht erreichbar: {err}",
+                upstream.name, upstream.url
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sums_requests_and_server_errors() {
+        let metrics = "# HELP http_requests Total\n\
+            http_requests_total{method=\"GET\",path=\"/health\",status=\"200\"} 4\n\
+            http_requests_total{method=\"POST\",path=\"/v1/chat\",status=\"503\"} 2\n\
+            http_request_duration_seconds_count{method=\"GET\",path=\"/health\"} 4\n";
+        assert_eq!(request_totals(metrics), (6, 2));
+    }
+
+    #[test]
+    fn budget_violation_degrades_status() {
+        let mut status = Status {
+            reachable: true,
+            ready: true,
+            ..Status::default()
+        };
+        apply_self_state(
+            &mut status,
+            &json!({
+                "service": {"safe_mode": false},
+                "index": {"total_documents": 3, "total_chunks": 7, "namespaces": {"default": 3}},
+                "memory": {"pinned": 1, "unpinned": 4, "expired_evictions_total": 0},
+                "budgets": [
+                    {"name": "llm_p95", "budget_ms": 1200, "observed_p95_ms": null, "within_budget": null},
+                    {"name": "index_topk20", "budget_ms": 60, "observed_p95_ms": 12.0, "within_budget": true}
+                ]
+            }),
+        );
+        assert_eq!(status.memory_items, Some(5));
+        assert_eq!(status.index_namespaces, Some(1));
+        assert_eq!(overall(&status), "ok");
+
+        status.budgets[1].within_budget = Some(false);
+        assert_eq!(overall(&status), "degraded");
+        status.reachable = false;
+        assert_eq!(overall(&status), "down");
+    }
+
+    #[test]
+    fn uptime_is_human_readable() {
+        assert_eq!(format_uptime(59), "0 min");
+        assert_eq!(format_uptime(3 * 3_600 + 120), "3 h 2 min");
+        assert_eq!(format_uptime(2 * 86_400 + 3_600), "2 d 1 h");
+    }
+}
@@ -0,0 +1,612 @@
+//! `hauski vault sync`: Obsidian-Vault inkrementell in den Index übernehmen.
+//!
+//! Jede Notiz (`*.md`, ohne versteckte Ordner wie `.obsidian` oder `.trash`)
+//! wird ein Dokument `<vault>/<relativer pfad>` im gewählten Namespace. Die
+//! Obsidian-Konventionen landen in `meta`: Titel, Tags (Frontmatter `tags` und
+//! Inline-`#tag`), Aliase, Wikilinks (`[[Notiz|Alias]]`), eingebettete Anhänge
+//! (`![[bild.png]]`, aufgelöst wie in Obsidian über den Dateinamen) und das
+//! übrige Frontmatter. Wikilinks werden im Text durch ihren Anzeigenamen ersetzt.
+//!
+//! Inkrementell: Der SHA-256 jeder Notiz wird unter
+//! `~/.local/state/hauski/vault/` gemerkt. Unveränderte Notizen werden
+//! übersprungen, gelöschte per `/index/forget` entfernt.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{
+    doctor::state_dir,
+    index::{chunk_text, Backend},
+    models::hex_digest,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum VaultCmd {
+    /// Notizen eines Vaults inkrementell in den Index übernehmen
+    Sync(SyncArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    /// Wurzelverzeichnis des Vaults
+    pub path: PathBuf,
+    /// Namespace im Index
+    #[arg(long = "ns", default_value = "vault")]
+    pub namespace: String,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Herkunft für `source_ref` (bestimmt den Trust-Level)
+    #[arg(long, default_value = "user")]
+    pub origin: String,
+    /// Alle Notizen neu ablegen, auch unveränderte
+    #[arg(long, default_value_t = false)]
+    pub full: bool,
+    /// Nur anzeigen, was sich ändern würde
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+pub fn run(cmd: VaultCmd, json: bool) -> Result<()> {
+    match cmd {
+        VaultCmd::Sync(args) => sync_command(&args, json),
+    }
+}
+
+fn sync_command(args: &SyncArgs, json: bool) -> Result<()> {
+    let vault = Vault::open(&args.path)?;
+    let state_path = state_file(&vault.root, &args.namespace);
+    let mut state = SyncState::load(&state_path)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    let report = runtime.block_on(sync(&backend, &vault, &mut state, args));
+    if !args.dry_run {
+        state.save(&state_path)?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let prefix = if args.dry_run { "(dry run) " } else { "" };
+        for doc_id in &report.upserted {
+            println!("{prefix}+ {doc_id}");
+        }
+        for doc_id in &report.forgotten {
+            println!("{prefix}- {doc_id}");
+        }
+        for doc_id in &report.failed {
+            println!("! {doc_id}");
+        }
+        println!(
+            "{prefix}{} abgelegt, {} vergessen, {} unverändert → Namespace '{}'",
+            report.upserted.len(),
+            report.forgotten.len(),
+            report.unchanged,
+            args.namespace
+        );
+    }
+    if !report.failed.is_empty() {
+        bail!(
+            "{} Notizen konnten nicht synchronisiert werden",
+            report.failed.len()
+        );
+    }
+    Ok(())
+}
+
+/// Notes and attachments found in a vault.
+struct Vault {
+    root: PathBuf,
+    name: String,
+    /// Relative paths of all notes, `/`-separated.
+    notes: Vec<String>,
+    /// Relative paths of all other files, `/`-separated.
+    files: BTreeSet<String>,
+}
+
+impl Vault {
+    fn open(path: &Path) -> Result<Self> {
+        let root = path
+            .canonicalize()
+            .with_context(|| format!("Vault {} nicht gefunden", path.display()))?;
+        if !root.is_dir() {
+            bail!("{} ist kein Verzeichnis", root.display());
+        }
+        let name = root
+            .file_name()
+            .map_or_else(|| "vault".to_string(), |n| n.to_string_lossy().into_owned());
+        let mut vault = Self {
+            root,
+            name,
+            notes: Vec::new(),
+            files: BTreeSet::new(),
+        };
+        let root = vault.root.clone();
+        vault.walk(&root)?;
+        vault.notes.sort();
+        Ok(vault)
+    }
+
+    fn walk(&mut self, dir: &Path) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                self.walk(&path)?;
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if path.extension().is_some_and(|ext| ext == "md") {
+                self.notes.push(rel);
+            } else {
+                self.files.insert(rel);
+            }
+        }
+        Ok(())
+    }
+
+    fn doc_id(&self, rel: &str) -> String {
+        format!("{}/{rel}", self.name)
+    }
+
+    /// Resolves an embed like Obsidian: exact vault path first, then by file name.
+    fn resolve_attachment(&self, target: &str) -> Option<&str> {
+        if let Some(path) = self.files.get(target) {
+            return Some(path);
+        }
+        let name = target.rsplit('/').next().unwrap_or(target);
+        self.files
+            .iter()
+            .find(|path| path.rsplit('/').next() == Some(name))
+            .map(String::as_str)
+    }
+}
+
+/// What an Obsidian note contributes to the index.
+#[derive(Debug, Default, PartialEq)]
+struct Note {
+    title: String,
+    tags: BTreeSet<String>,
+    aliases: Vec<String>,
+    links: BTreeSet<String>,
+    embeds: BTreeSet<String>,
+    frontmatter: Value,
+    /// Body with wikilinks replaced by their display text.
+    text: String,
+}
+
+fn parse_note(stem: &str, raw: &str) -> Note {
+    let (frontmatter, body) = split_frontmatter(raw);
+    let frontmatter: serde_yaml_ng::Value = match frontmatter.map(serde_yaml_ng::from_str) {
+        Some(Ok(value)) => value,
+        Some(Err(err)) => {
+            warn!("{stem}: Frontmatter ignoriert ({err})");
+            serde_yaml_ng::Value::Null
+        }
+        None => serde_yaml_ng::Value::Null,
+    };
+
+    let mut note = Note {
+        title: frontmatter
+            .get("title")
+            .and_then(|t| t.as_str())
+            .unwrap_or(stem)
+            .to_string(),
+        frontmatter: serde_json::to_value(&frontmatter).unwrap_or(Value::Null),
+        ..Note::default()
+    };
+    for key in ["tags", "tag"] {
+        note.tags.extend(
+            string_list(frontmatter.get(key))
+                .into_iter()
+                .map(|tag| tag.trim_start_matches('#').to_string()),
+        );
+    }
+    for key in ["aliases", "alias"] {
+        note.aliases.extend(string_list(frontmatter.get(key)));
+    }
+    note.tags.extend(inline_tags(body));
+    note.text = flatten_wikilinks(body, &mut note.links, &mut note.embeds);
+    note
+}
+
+/// Splits a leading `---` YAML block from the body.
+fn split_frontmatter(raw: &str) -> (Option<&str>, &str) {
+    let Some(rest) = raw
+        .strip_prefix("---\n")
+        .or_else(|| raw.strip_prefix("---\r\n"))
+    else {
+        return (None, raw);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, raw)
+}
+
+/// A YAML list of strings, or one string separated by commas/whitespace.
+fn string_list(value: Option<&serde_yaml_ng::Value>) -> Vec<String> {
+    match value {
+        Some(serde_yaml_ng::Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(str::to_string)
+            .collect(),
+        Some(serde_yaml_ng::Value::String(s)) => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `#tag` and `#nested/tag` outside code fences; pure numbers (`#123`) are no tags.
+fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut prev = ' ';
+        for (i, c) in line.char_indices() {
+            if c == '#' && prev.is_whitespace() {
+                let tag: String = line[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                if tag.chars().any(|c| !c.is_ascii_digit()) {
+                    tags.push(tag);
+                }
+            }
+            prev = c;
+        }
+    }
+    tags
+}
+
+/// Replaces `[[target#heading|alias]]` by its display text and collects link
+/// targets; `![[…]]` embeds are collected separately and dropped from the text.
+fn flatten_wikilinks(
+    body: &str,
+    links: &mut BTreeSet<String>,
+    embeds: &mut BTreeSet<String>,
+) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("[[") {
+        let Some(len) = rest[start + 2..].find("]]") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        let embed = rest[..start].ends_with('!');
+        out.push_str(&rest[..if embed { start - 1 } else { start }]);
+
+        let (target, alias) = inner.split_once('|').unwrap_or((inner, ""));
+        let target = target.split(['#', '^']).next().unwrap_or_default().trim();
+        if embed {
+            embeds.insert(target.to_string());
+        } else {
+            if !target.is_empty() {
+                links.insert(target.trim_end_matches(".md").to_string());
+            }
+            out.push_str(if alias.is_empty() { inner } else { alias });
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// SHA-256 per synced note, persisted between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    notes: BTreeMap<String, String>,
+}
+
+impl SyncState {
+    fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("{} ist beschädigt", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("{} nicht lesbar", path.display())),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("{} nicht schreibbar", path.display()))
+    }
+}
+
+/// One state file per vault and namespace.
+fn state_file(root: &Path, namespace: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(root.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    hasher.update(namespace.as_bytes());
+    let digest = hex_digest(hasher);
+    state_dir()
+        .join("vault")
+        .join(format!("{}.json", &digest[..16]))
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    upserted: Vec<String>,
+    forgotten: Vec<String>,
+    unchanged: usize,
+    failed: Vec<String>,
+    dry_run: bool,
+}
+
+async fn sync(backend: &Backend, vault: &Vault, state: &mut SyncState, args: &SyncArgs) -> Report {
+    let mut report = Report {
+        dry_run: args.dry_run,
+        ..Report::default()
+    };
+    let mut seen = BTreeSet::new();
+    for rel in &vault.notes {
+        let doc_id = vault.doc_id(rel);
+        seen.insert(doc_id.clone());
+        let raw = match fs::read_to_string(vault.root.join(rel)) {
+            Ok(raw) => raw,
+            Err(err) => {
+                warn!("{doc_id}: {err}");
+                report.failed.push(doc_id);
+                continue;
+            }
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(raw.as_bytes());
+        let digest = hex_digest(hasher);
+        if !args.full && state.notes.get(&doc_id) == Some(&digest) {
+            report.unchanged += 1;
+            continue;
+        }
+        if args.dry_run {
+            report.upserted.push(doc_id);
+            continue;
+        }
+        match upsert_note(backend, vault, rel, &doc_id, &raw, &digest, args).await {
+            Ok(()) => {
+                state.notes.insert(doc_id.clone(), digest);
+                report.upserted.push(doc_id);
+            }
+            Err(err) => {
+                warn!("{doc_id}: {err:#}");
+                report.failed.push(doc_id);
+            }
+        }
+    }
+
+    let gone: Vec<String> = state
+        .notes
+        .keys()
+        .filter(|doc_id| !seen.contains(*doc_id))
+        .cloned()
+        .collect();
+    for doc_id in gone {
+        if args.dry_run {
+            report.forgotten.push(doc_id);
+            continue;
+        }
+        let body = json!({
+            "filter": {"namespace": args.namespace, "doc_id": doc_id},
+            "reason": "hauski vault sync: Notiz entfernt",
+            "confirm": true,
+            "dry_run": false,
+        });
+        match backend
+            .call(Method::POST, "/index/forget", Some(body))
+            .await
+        {
+            Ok(_) => {
+                state.notes.remove(&doc_id);
+                report.forgotten.push(doc_id);
+            }
+            Err(err) => {
+                warn!("{doc_id}: {err:#}");
+                report.failed.push(doc_id);
+            }
+        }
+    }
+    report
+}
+
+async fn upsert_note(
+    backend: &Backend,
+    vault: &Vault,
+    rel: &str,
+    doc_id: &str,
+    raw: &str,
+    digest: &str,
+    args: &SyncArgs,
+) -> Result<()> {
+    let stem = rel
+        .rsplit('/')
+        .next()
+        .unwrap_or(rel)
+        .trim_end_matches(".md");
+    let note = parse_note(stem, raw);
+    let attachments: Vec<&str> = note
+        .embeds
+        .iter()
+        .filter(|target| {
+            !vault
+                .notes
+                .iter()
+                .any(|n| n.trim_end_matches(".md") == *target)
+        })
+        .map(|target| {
+            vault.resolve_attachment(target).unwrap_or_else(|| {
+                warn!("{doc_id}: Anhang '{target}' nicht im Vault gefunden");
+                target
+            })
+        })
+        .collect();
+
+    let text = format!("# {}\n\n{}", note.title, note.text);
+    let chunks: Vec<Value> = chunk_text(&text)
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| json!({"chunk_id": format!("{doc_id}#{i}"), "text": text}))
+        .collect();
+    let body = json!({
+        "doc_id": doc_id,
+        "namespace": args.namespace,
+        "chunks": chunks,
+        "meta": {
+            "path": vault.root.join(rel).to_string_lossy(),
+            "vault": vault.name,
+            "title": note.title,
+            "tags": note.tags,
+            "aliases": note.aliases,
+            "links": note.links,
+            "attachments": attachments,
+            "frontmatter": note.frontmatter,
+            "sha256": digest,
+        },
+        "source_ref": {
+            "origin": args.origin,
+            "id": doc_id,
+            "trust_level": hauski_indexd::TrustLevel::default_for_origin(&args.origin),
+            "injected_by": "hauski-cli vault",
+        },
+    });
+    backend
+        .call(Method::POST, "/index/upsert", Some(body))
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow!("Ablegen fehlgeschlagen: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_frontmatter_tags_links_and_embeds() {
+        let note = parse_note(
+            "Idee",
+            "---\ntitle: Große Idee\ntags: [projekt, \"#hausKI\"]\naliases: Idee\nstatus: offen\n---\n\
+             Siehe [[Plan#Ziele|den Plan]] und [[Notizen/Log.md]] #todo #42\n\
+             ![[skizze.png]]\n```\n#kein-tag\n```\n",
+        );
+        assert_eq!(note.title, "Große Idee");
+        assert_eq!(
+            note.tags.iter().collect::<Vec<_>>(),
+            ["hausKI", "projekt", "todo"]
+        );
+        assert_eq!(note.aliases, ["Idee"]);
+        assert_eq!(
+            note.links.iter().collect::<Vec<_>>(),
+            ["Notizen/Log", "Plan"]
+        );
+        assert_eq!(note.embeds.iter().collect::<Vec<_>>(), ["skizze.png"]);
+        assert_eq!(note.frontmatter["status"], "offen");
+        assert!(note
+            .text
+            .starts_with("Siehe den Plan und Notizen/Log.md #todo"));
+        assert!(!note.text.contains("skizze"));
+    }
+
+    #[test]
+    fn sync_is_incremental_and_forgets_deleted_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("Garten");
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::create_dir_all(root.join("bilder")).unwrap();
+        fs::write(root.join(".obsidian/app.json"), "{}").unwrap();
+        fs::write(root.join("bilder/beet.png"), [0u8, 1, 2]).unwrap();
+        fs::write(root.join("Beet.md"), "Tomaten ![[beet.png]] [[Kompost]]").unwrap();
+        fs::write(root.join("Kompost.md"), "#garten Laub").unwrap();
+
+        let args = SyncArgs {
+            path: root.clone(),
+            namespace: "garten".into(),
+            base_url: None,
+            origin: "user".into(),
+            full: false,
+            dry_run: false,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let backend = Backend::offline();
+            let vault = Vault::open(&root).unwrap();
+            let mut state = SyncState::default();
+
+            let first = sync(&backend, &vault, &mut state, &args).await;
+            assert_eq!(first.upserted, ["Garten/Beet.md", "Garten/Kompost.md"]);
+            let hits = backend
+                .call(
+                    Method::POST,
+                    "/index/search",
+                    Some(json!({"query": "Tomaten", "namespace": "garten", "k": 5})),
+                )
+                .await
+                .unwrap();
+            let hit = &hits["matches"][0];
+            assert_eq!(hit["doc_id"], "Garten/Beet.md");
+            assert_eq!(hit["meta"]["attachments"][0], "bilder/beet.png");
+            assert_eq!(hit["meta"]["links"][0], "Kompost");
+
+            let second = sync(&backend, &vault, &mut state, &args).await;
+            assert!(second.upserted.is_empty());
+            assert_eq!(second.unchanged, 2);
+
+            fs::remove_file(root.join("Kompost.md")).unwrap();
+            let vault = Vault::open(&root).unwrap();
+            let third = sync(&backend, &vault, &mut state, &args).await;
+            assert_eq!(third.forgotten, ["Garten/Kompost.md"]);
+            let stats = backend
+                .call(Method::GET, "/index/stats", None)
+                .await
+                .unwrap();
+            assert_eq!(stats["total_documents"], 1);
+        });
+    }
+}
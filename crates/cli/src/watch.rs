@@ -0,0 +1,387 @@
+//! `hauski watch <dir>`: Verzeichnis laufend in den Index spiegeln.
+//!
+//! Beim Start werden alle passenden Dateien abgelegt (abschaltbar mit
+//! `--no-initial`), danach meldet inotify (über `notify`) jede Änderung.
+//! Ereignisse werden gesammelt, bis `--debounce-ms` lang Ruhe herrscht; erst
+//! dann wird jede betroffene Datei einmal neu gechunkt und per
+//! `/index/upsert` abgelegt bzw. – wenn sie verschwunden ist – per
+//! `/index/forget` aus dem Namespace entfernt. Gelöschte Verzeichnisse
+//! nehmen alle darunter abgelegten Dokumente mit.
+//!
+//! Berücksichtigt werden nur Dateien mit den Endungen aus `--ext`
+//! (Default: md, markdown, txt); versteckte Pfade und Treffer der
+//! `--ignore`-Globs (relativ zum Verzeichnis, z. B. `archiv/**`) bleiben
+//! außen vor. `doc_id` ist wie bei `hauski index upsert` der Dateipfad.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{RecursiveMode, Watcher};
+use reqwest::Method;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{
+    index::{upsert_paths, Backend},
+    say, OutputArgs,
+};
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Zu beobachtendes Verzeichnis
+    pub dir: PathBuf,
+    /// Namespace im Index
+    #[arg(long = "ns", default_value = "default")]
+    pub namespace: String,
+    /// Basis-URL des HausKI-Cores
+    /// (Default: `--host`/`HAUSKI_HOST`, `HAUSKI_INTERNAL_BASE` oder http://127.0.0.1:8080)
+    #[arg(long)]
+    pub base_url: Option<String>,
+    /// Herkunft für `source_ref` (bestimmt den Trust-Level)
+    #[arg(long, default_value = "user")]
+    pub origin: String,
+    /// Dateiendungen (mehrfach oder kommagetrennt)
+    #[arg(long, value_delimiter = ',', default_value = "md,markdown,txt")]
+    pub ext: Vec<String>,
+    /// Glob relativ zum Verzeichnis, der ignoriert wird (mehrfach möglich)
+    #[arg(long)]
+    pub ignore: Vec<String>,
+    /// Ruhezeit in ms, bevor gesammelte Änderungen übernommen werden
+    #[arg(long, default_value_t = 500)]
+    pub debounce_ms: u64,
+    /// Vorhandene Dateien beim Start nicht ablegen
+    #[arg(long, default_value_t = false)]
+    pub no_initial: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// Mirrors one directory into one namespace.
+struct Mirror {
+    root: PathBuf,
+    namespace: String,
+    origin: String,
+    extensions: Vec<String>,
+    ignore: GlobSet,
+    /// Doc ids upserted so far, to forget them when their file goes away.
+    known: BTreeSet<String>,
+}
+
+/// What one batch changed.
+#[derive(Debug, Default, PartialEq)]
+struct Batch {
+    upserted: Vec<String>,
+    forgotten: Vec<String>,
+    failed: Vec<String>,
+}
+
+impl Mirror {
+    fn new(args: &WatchArgs) -> Result<Self> {
+        let root = args
+            .dir
+            .canonicalize()
+            .with_context(|| format!("Verzeichnis {} fehlt", args.dir.display()))?;
+        let mut ignore = GlobSetBuilder::new();
+        for pattern in &args.ignore {
+            ignore.add(Glob::new(pattern).map_err(|e| anyhow!("--ignore {pattern}: {e}"))?);
+        }
+        Ok(Self {
+            root,
+            namespace: args.namespace.clone(),
+            origin: args.origin.clone(),
+            extensions: args
+                .ext
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+            ignore: ignore.build()?,
+            known: BTreeSet::new(),
+        })
+    }
+
+    /// Whether `path` (below the root) is mirrored at all.
+    fn wanted(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let hidden = relative
+            .components()
+            .any(|part| part.as_os_str().to_string_lossy().starts_with('.'));
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        !hidden
+            && !self.ignore.is_match(relative)
+            && extension.is_some_and(|ext| self.extensions.contains(&ext))
+    }
+
+    /// Wanted files currently below the root.
+    fn scan(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                    if !hidden {
+                        dirs.push(path);
+                    }
+                } else if self.wanted(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    /// Upserts changed files and forgets vanished ones (including everything
+    /// known below a vanished directory).
+    async fn apply(&mut self, backend: &Backend, paths: BTreeSet<PathBuf>) -> Batch {
+        let mut batch = Batch::default();
+        for path in paths {
+            if path.is_file() {
+                if !self.wanted(&path) {
+                    continue;
+                }
+                let doc_id = path.to_string_lossy().into_owned();
+                match upsert_paths(
+                    backend,
+                    std::slice::from_ref(&path),
+                    &self.namespace,
+                    &self.origin,
+                )
+                .await
+                {
+                    Ok(results) if results.is_empty() => {
+                        // Emptied file: keep the index in step.
+                        self.forget(backend, &doc_id, &mut batch).await;
+                    }
+                    Ok(_) => {
+                        self.known.insert(doc_id.clone());
+                        batch.upserted.push(doc_id);
+                    }
+                    Err(err) => {
+                        warn!("{doc_id}: {err:#}");
+                        batch.failed.push(doc_id);
+                    }
+                }
+            } else if !path.exists() {
+                let prefix = format!("{}{}", path.to_string_lossy(), std::path::MAIN_SEPARATOR);
+                let gone: Vec<String> = self
+                    .known
+                    .iter()
+                    .filter(|doc_id| {
+                        **doc_id == *path.to_string_lossy() || doc_id.starts_with(&prefix)
+                    })
+                    .cloned()
+                    .collect();
+                for doc_id in gone {
+                    self.forget(backend, &doc_id, &mut batch).await;
+                }
+            }
+        }
+        batch
+    }
+
+    async fn forget(&mut self, backend: &Backend, doc_id: &str, batch: &mut Batch) {
+        let body = json!({
+            "filter": {"namespace": self.namespace, "doc_id": doc_id},
+            "reason": "hauski watch: Datei entfernt",
+            "confirm": true,
+            "dry_run": false,
+        });
+        match backend
+            .call(Method::POST, "/index/forget", Some(body))
+            .await
+        {
+            Ok(_) => {
+                self.known.remove(doc_id);
+                batch.forgotten.push(doc_id.to_string());
+            }
+            Err(err) => {
+                warn!("{doc_id}: {err:#}");
+                batch.failed.push(doc_id.to_string());
+            }
+        }
+    }
+}
+
+pub fn run(args: WatchArgs) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Tokio Runtime konnte nicht erzeugt werden")?;
+    let backend = Backend::remote(args.base_url.clone())?;
+    let mut mirror = Mirror::new(&args)?;
+
+    // Watch before the initial scan so nothing changed in between is missed.
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("inotify nicht verfügbar")?;
+    watcher
+        .watch(&mirror.root, RecursiveMode::Recursive)
+        .with_context(|| format!("{} kann nicht beobachtet werden", mirror.root.display()))?;
+
+    if args.no_initial {
+        // Still learn the doc ids, so deletions are mirrored.
+        mirror.known = mirror
+            .scan()
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+    } else {
+        let files = mirror.scan().into_iter().collect();
+        let batch = runtime.block_on(mirror.apply(&backend, files));
+        report(&batch, args.output.json)?;
+    }
+    say(
+        args.output.json,
+        format!(
+            "Beobachte {} → Namespace '{}' (Ctrl-C beendet)",
+            mirror.root.display(),
+            mirror.namespace
+        ),
+    );
+
+    let debounce = Duration::from_millis(args.debounce_ms);
+    loop {
+        let mut pending = BTreeSet::new();
+        let first = rx.recv().map_err(|_| anyhow!("Beobachtung beendet"))?;
+        collect(first, &mut pending);
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => collect(event, &mut pending),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!("Beobachtung beendet"))
+                }
+            }
+        }
+        let batch = runtime.block_on(mirror.apply(&backend, pending));
+        report(&batch, args.output.json)?;
+    }
+}
+
+fn collect(event: notify::Result<notify::Event>, pending: &mut BTreeSet<PathBuf>) {
+    match event {
+        // Access events do not change content.
+        Ok(event) if !event.kind.is_access() => pending.extend(event.paths),
+        Ok(_) => {}
+        Err(err) => warn!("inotify: {err}"),
+    }
+}
+
+/// One line per batch; in JSON mode one JSON object per line.
+fn report(batch: &Batch, json: bool) -> Result<()> {
+    if batch == &Batch::default() {
+        return Ok(());
+    }
+    if json {
+        let line: Value = json!({
+            "upserted": batch.upserted,
+            "forgotten": batch.forgotten,
+            "failed": batch.failed,
+        });
+        println!("{}", serde_json::to_string(&line)?);
+        return Ok(());
+    }
+    for doc_id in &batch.upserted {
+        println!("+ {doc_id}");
+    }
+    for doc_id in &batch.forgotten {
+        println!("- {doc_id}");
+    }
+    for doc_id in &batch.failed {
+        println!("! {doc_id}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    async fn total(backend: &Backend) -> u64 {
+        let stats = backend
+            .call(Method::GET, "/index/stats", None)
+            .await
+            .unwrap();
+        stats["total_documents"].as_u64().unwrap()
+    }
+
+    fn args(dir: &Path, ignore: &[&str]) -> WatchArgs {
+        WatchArgs {
+            dir: dir.to_path_buf(),
+            namespace: "notes".into(),
+            base_url: None,
+            origin: "user".into(),
+            ext: vec!["md".into(), "txt".into()],
+            ignore: ignore.iter().map(|p| p.to_string()).collect(),
+            debounce_ms: 10,
+            no_initial: false,
+            output: OutputArgs::default(),
+        }
+    }
+
+    #[test]
+    fn wanted_honours_extensions_hidden_paths_and_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let mirror = Mirror::new(&args(dir.path(), &["archiv/**"])).unwrap();
+        let root = &mirror.root;
+        assert!(mirror.wanted(&root.join("a.md")));
+        assert!(mirror.wanted(&root.join("sub/b.TXT")));
+        assert!(!mirror.wanted(&root.join("c.pdf")));
+        assert!(!mirror.wanted(&root.join(".obsidian/d.md")));
+        assert!(!mirror.wanted(&root.join("archiv/2020/e.md")));
+        assert!(!mirror.wanted(Path::new("/elsewhere/f.md")));
+    }
+
+    #[test]
+    fn apply_upserts_changes_and_forgets_removed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.md"), "Alpha").unwrap();
+        fs::write(dir.path().join("sub/b.md"), "Beta").unwrap();
+        fs::write(dir.path().join("skip.pdf"), "PDF").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let backend = Backend::offline();
+            let mut mirror = Mirror::new(&args(dir.path(), &[])).unwrap();
+
+            let files = mirror.scan().into_iter().collect();
+            let batch = mirror.apply(&backend, files).await;
+            assert_eq!(batch.upserted.len(), 2);
+            assert_eq!(total(&backend).await, 2);
+
+            fs::remove_dir_all(mirror.root.join("sub")).unwrap();
+            let batch = mirror
+                .apply(&backend, BTreeSet::from([mirror.root.join("sub")]))
+                .await;
+            assert_eq!(batch.forgotten.len(), 1);
+            assert!(batch.forgotten[0].ends_with("b.md"));
+            assert_eq!(total(&backend).await, 1);
+
+            fs::write(mirror.root.join("a.md"), "").unwrap();
+            let batch = mirror
+                .apply(&backend, BTreeSet::from([mirror.root.join("a.md")]))
+                .await;
+            assert_eq!(batch.forgotten.len(), 1);
+            assert_eq!(total(&backend).await, 0);
+        });
+    }
+}
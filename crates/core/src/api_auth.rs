@@ -0,0 +1,148 @@
+//! Optional bearer token for the whole HTTP API.
+//!
+//! With `api_token` (flags.yaml or `HAUSKI_API_TOKEN`) set, every request must
+//! send `Authorization: Bearer <api_token>`; that is what makes binding to a
+//! non-loopback address and `hauski --host` usable. Exempt are liveness and
+//! readiness probes and routes that carry their own token (`/memory/*`,
+//! `/events`). Without a token the API stays open as before.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::AppState;
+
+/// Probes that must keep working for systemd, load balancers and `hauski status`.
+const OPEN_PATHS: &[&str] = &["/health", "/healthz", "/ready"];
+
+/// Route prefixes that check their own token.
+const OWN_TOKEN_PREFIXES: &[&str] = &["/memory/", "/events"];
+
+pub(crate) fn bearer(req: &Request<Body>) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Compares a presented token with the configured one in constant time. Both
+/// are hashed first, so neither the content nor the length of the token leaks
+/// through the time the comparison takes.
+pub(crate) fn token_matches(provided: &str, expected: &str) -> bool {
+    let provided = Sha256::digest(provided.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    provided.as_slice().ct_eq(expected.as_slice()).into()
+}
+
+fn is_exempt(req: &Request<Body>) -> bool {
+    let path = req.uri().path();
+    req.method() == Method::OPTIONS
+        || OPEN_PATHS.contains(&path)
+        || OWN_TOKEN_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+pub(crate) async fn require_api_token(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(token) = state.flags().api_token else {
+        return next.run(req).await;
+    };
+    if is_exempt(&req) || bearer(&req).is_some_and(|provided| token_matches(provided, &token)) {
+        return next.run(req).await;
+    }
+    tracing::warn!(path = %req.uri().path(), "unauthorized API request");
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(json!({ "error": "unauthorized" })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::token_matches;
+    use crate::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+    use axum::{
+        body::Body,
+        http::{HeaderValue, Request, StatusCode},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn app(api_token: Option<&str>) -> Router {
+        let flags = FeatureFlags {
+            api_token: api_token.map(str::to_string),
+            ..FeatureFlags::default()
+        };
+        build_app_with_state(
+            Limits::default(),
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            flags,
+            false,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        )
+        .0
+    }
+
+    async fn status(app: &Router, path: &str, token: Option<&str>) -> StatusCode {
+        let mut req = Request::get(path);
+        if let Some(token) = token {
+            req = req.header("authorization", format!("Bearer {token}"));
+        }
+        app.clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn token_guards_api_but_not_probes() {
+        let app = app(Some("s3cret"));
+        assert_eq!(
+            status(&app, "/version", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&app, "/version", Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(&app, "/version", Some("s3cret")).await,
+            StatusCode::OK
+        );
+        assert_eq!(status(&app, "/health", None).await, StatusCode::OK);
+        assert_eq!(
+            status(&app, "/index/stats", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn without_token_the_api_stays_open() {
+        let app = app(None);
+        assert_eq!(status(&app, "/version", None).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn tokens_match_only_exactly() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cre", "s3cret"));
+        assert!(!token_matches("s3cret ", "s3cret"));
+        assert!(!token_matches("", "s3cret"));
+    }
+}
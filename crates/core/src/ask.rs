@@ -2,21 +2,27 @@ use std::time::Instant;
 
 use axum::{
     extract::{Query, State},
-    http::{Method, StatusCode},
+    http::{HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use hauski_indexd::SearchRequest;
+use hauski_indexd::{ContentFlag, SearchMatch, SearchRequest, TrustLevel};
 use serde::{Deserialize, Serialize};
 
 use utoipa::{IntoParams, ToSchema};
 
-use crate::AppState;
+use crate::{
+    ask_cache::{bypass_requested, CacheResult, CACHE_HEADER},
+    ask_session, AppState,
+};
 // Used by utoipa's #[schema(example = json!(...))] attribute macros
 #[allow(unused_imports)]
 use serde_json::json;
 
 /// Maximum number of matches returned by the `/ask` endpoint.
 const MAX_K: usize = 100;
+/// Default number of hits that feed into an extractive answer.
+const DEFAULT_ANSWER_HITS: usize = 3;
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[schema(
@@ -32,9 +38,32 @@ const MAX_K: usize = 100;
 pub struct AskHit {
     pub doc_id: String,
     pub namespace: String,
+    #[serde(default)]
+    pub chunk_id: String,
     pub score: f32,
     pub snippet: String,
     pub meta: serde_json::Value,
+    /// Weight breakdown (only with `include_weights` on `POST /ask`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<serde_json::Value>,
+}
+
+impl AskHit {
+    fn from_match(m: SearchMatch, max_snippet_chars: Option<usize>) -> Self {
+        let snippet = match max_snippet_chars {
+            Some(max) if m.text.chars().count() > max => m.text.chars().take(max).collect(),
+            _ => m.text,
+        };
+        Self {
+            doc_id: m.doc_id,
+            namespace: m.namespace,
+            chunk_id: m.chunk_id,
+            score: m.score,
+            snippet,
+            meta: m.meta,
+            weights: m.weights.and_then(|w| serde_json::to_value(w).ok()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -60,6 +89,138 @@ pub struct AskResponse {
     pub k: usize,
     pub namespace: String,
     pub hits: Vec<AskHit>,
+    /// Synthesized answer (only with `answer.mode != none` on `POST /ask`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// One citation per answer claim, in answer order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<AskCitation>,
+    /// Echo of the conversation the question belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Query used for retrieval after rewriting against earlier turns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewritten_query: Option<String>,
+}
+
+/// Links a claim of the synthesized answer back into its source chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[schema(
+    title = "AskCitation",
+    example = json!({
+        "claim": "Deploy steps are listed in the runbook.",
+        "doc_id": "doc-42",
+        "chunk_id": "doc-42#0",
+        "namespace": "default",
+        "start": 7,
+        "end": 46,
+        "trust_level": "high",
+        "origin": "chronik",
+        "score": 0.87,
+        "query_match": true
+    })
+)]
+pub struct AskCitation {
+    /// Claim text as it appears in the answer.
+    pub claim: String,
+    pub doc_id: String,
+    pub chunk_id: String,
+    pub namespace: String,
+    /// Start offset of the claim within the chunk text (Unicode scalar values, inclusive).
+    pub start: usize,
+    /// End offset of the claim within the chunk text (Unicode scalar values, exclusive).
+    pub end: usize,
+    /// Trust level of the supporting chunk's source (if known).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "high")]
+    pub trust_level: Option<TrustLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    pub score: f32,
+    /// `true` if the claim itself contains the query; `false` means the
+    /// sentence was picked as fallback and grounding is weaker.
+    pub query_match: bool,
+}
+
+/// How `POST /ask` should turn hits into an answer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AskAnswerMode {
+    /// Only return hits (same as `GET /ask`).
+    #[default]
+    None,
+    /// Assemble an answer from the best matching sentences of the top hits.
+    Extractive,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AskAnswerOptions {
+    #[serde(default)]
+    pub mode: AskAnswerMode,
+    /// Number of top hits used for the answer (default 3, clamped to `k`).
+    #[serde(default)]
+    pub max_hits: Option<usize>,
+}
+
+/// Full-featured `/ask` request mirroring the index search surface.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+#[schema(
+    title = "AskRequest",
+    example = json!({
+        "query": "deployment checklist",
+        "k": 5,
+        "namespace": "default",
+        "min_trust_level": "medium",
+        "exclude_flags": ["possible_prompt_injection"],
+        "context_profile": "incident_response",
+        "meta_filter": {"kind": "runbook"},
+        "answer": {"mode": "extractive", "max_hits": 3},
+        "session_id": "dashboard-7"
+    })
+)]
+pub struct AskRequest {
+    pub query: String,
+    #[serde(default = "default_k")]
+    #[schema(default = 5, minimum = 1, maximum = 100)]
+    pub k: usize,
+    #[serde(default = "default_ns")]
+    #[schema(default = "default")]
+    pub namespace: String,
+    /// Flags to exclude; omitted = index default (prompt-injection filtered).
+    #[serde(default)]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub exclude_flags: Option<Vec<ContentFlag>>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "medium")]
+    pub min_trust_level: Option<TrustLevel>,
+    #[serde(default)]
+    pub exclude_origins: Option<Vec<String>>,
+    #[serde(default)]
+    pub context_profile: Option<String>,
+    /// Equality filter on chunk/document metadata (all keys must match).
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub meta_filter: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    pub include_weights: bool,
+    #[serde(default)]
+    pub emit_decision_snapshot: bool,
+    /// Truncate snippets to this many characters.
+    #[serde(default)]
+    pub max_snippet_chars: Option<usize>,
+    #[serde(default)]
+    pub answer: AskAnswerOptions,
+    /// Conversation id; follow-up questions are rewritten using earlier turns.
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[schema(title = "AskErrorResponse", example = json!({"error":"query must not be empty"}))]
+pub struct AskErrorResponse {
+    pub error: String,
 }
 
 #[derive(Deserialize, Clone, IntoParams, ToSchema)]
@@ -98,13 +259,21 @@ fn default_ns() -> String {
 )]
 pub async fn ask_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(params): Query<AskParams>,
-) -> Json<AskResponse> {
+) -> Response {
     let AskParams { q, k, ns } = params;
     let started = Instant::now();
 
     let limit = k.clamp(1, MAX_K);
 
+    let key = cache_key(&("GET", &q, limit, &ns));
+    let lookup = CacheLookup::begin(&state, &headers, &ns, key, false);
+    if let Some(cached) = lookup.cached.clone() {
+        state.record_http_observation(Method::GET, "/ask", StatusCode::OK, started);
+        return with_cache_header((StatusCode::OK, Json(cached)), lookup.result);
+    }
+
     let request = SearchRequest {
         query: q.clone(),
         k: Some(limit),
@@ -115,26 +284,350 @@ pub async fn ask_handler(
         context_profile: None,
         include_weights: false,
         emit_decision_snapshot: false,
+        meta_filter: None,
     };
 
     let matches = state.index().search(&request).await;
     let hits = matches
         .into_iter()
-        .map(|m| AskHit {
-            doc_id: m.doc_id,
-            namespace: m.namespace,
-            score: m.score,
-            snippet: m.text,
-            meta: m.meta,
-        })
+        .map(|m| AskHit::from_match(m, None))
         .collect();
 
-    state.record_http_observation(Method::GET, "/ask", StatusCode::OK, started);
-
-    Json(AskResponse {
+    let response = AskResponse {
         query: q,
         k: limit,
         namespace: ns,
         hits,
-    })
+        answer: None,
+        citations: Vec::new(),
+        session_id: None,
+        rewritten_query: None,
+    };
+    lookup.store(&state, &response);
+
+    state.record_http_observation(Method::GET, "/ask", StatusCode::OK, started);
+
+    with_cache_header((StatusCode::OK, Json(response)), lookup.result)
+}
+
+#[utoipa::path(
+    post,
+    path = "/ask",
+    request_body = AskRequest,
+    responses(
+        (status = 200, description = "Top-k matches with optional answer", body = AskResponse),
+        (status = 400, description = "Invalid ask request", body = AskErrorResponse)
+    ),
+    tag = "core"
+)]
+pub async fn ask_post_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AskRequest>,
+) -> Response {
+    let started = Instant::now();
+
+    if req.query.trim().is_empty() {
+        let status = StatusCode::BAD_REQUEST;
+        state.record_http_observation(Method::POST, "/ask", status, started);
+        return (
+            status,
+            Json(AskErrorResponse {
+                error: "query must not be empty".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    if let Some(Err(error)) = req
+        .session_id
+        .as_deref()
+        .map(ask_session::validate_session_id)
+    {
+        let status = StatusCode::BAD_REQUEST;
+        state.record_http_observation(Method::POST, "/ask", status, started);
+        return (status, Json(AskErrorResponse { error })).into_response();
+    }
+
+    let limit = req.k.clamp(1, MAX_K);
+
+    let session = match req.session_id.as_deref() {
+        Some(id) => {
+            let session = ask_session::load(id).await;
+            let turn = session.next_turn(&req.query);
+            Some((id.to_string(), session, turn))
+        }
+        None => None,
+    };
+    let search_query = session
+        .as_ref()
+        .map(|(_, _, turn)| turn.rewritten.clone())
+        .unwrap_or_else(|| req.query.clone());
+
+    // Decision snapshots and session turns are side effects and must not be
+    // swallowed by the cache.
+    let key = cache_key(&("POST", &req));
+    let lookup = CacheLookup::begin(
+        &state,
+        &headers,
+        &req.namespace,
+        key,
+        req.emit_decision_snapshot || session.is_some(),
+    );
+    if let Some(cached) = lookup.cached.clone() {
+        state.record_http_observation(Method::POST, "/ask", StatusCode::OK, started);
+        return with_cache_header((StatusCode::OK, Json(cached)), lookup.result);
+    }
+
+    let request = SearchRequest {
+        query: search_query.clone(),
+        k: Some(limit),
+        namespace: Some(req.namespace.clone()),
+        exclude_flags: req.exclude_flags,
+        min_trust_level: req.min_trust_level,
+        exclude_origins: req.exclude_origins,
+        context_profile: req.context_profile,
+        include_weights: req.include_weights,
+        emit_decision_snapshot: req.emit_decision_snapshot,
+        meta_filter: req.meta_filter,
+    };
+
+    let matches = state.index().search(&request).await;
+
+    let (answer, citations) = match req.answer.mode {
+        AskAnswerMode::None => (None, Vec::new()),
+        AskAnswerMode::Extractive => {
+            let max_hits = req
+                .answer
+                .max_hits
+                .unwrap_or(DEFAULT_ANSWER_HITS)
+                .clamp(1, limit);
+            let (answer, citations) =
+                extractive_answer(&search_query, &matches[..matches.len().min(max_hits)]);
+            (Some(answer), citations)
+        }
+    };
+
+    let hits = matches
+        .into_iter()
+        .map(|m| AskHit::from_match(m, req.max_snippet_chars))
+        .collect();
+
+    let response = AskResponse {
+        query: req.query,
+        k: limit,
+        namespace: req.namespace,
+        hits,
+        answer,
+        citations,
+        session_id: None,
+        rewritten_query: None,
+    };
+    lookup.store(&state, &response);
+
+    let response = match session {
+        Some((id, mut session, turn)) => {
+            session.push(turn);
+            ask_session::store(&id, &session).await;
+            AskResponse {
+                session_id: Some(id),
+                rewritten_query: Some(search_query),
+                ..response
+            }
+        }
+        None => response,
+    };
+
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/ask", status, started);
+
+    with_cache_header((status, Json(response)), lookup.result)
+}
+
+/// Outcome of the cache lookup that precedes an `/ask` search.
+struct CacheLookup {
+    key: Option<String>,
+    /// Namespace generation read *before* the search, so concurrent
+    /// mutations invalidate the stored entry.
+    generation: u64,
+    result: CacheResult,
+    cached: Option<AskResponse>,
+}
+
+impl CacheLookup {
+    fn begin(
+        state: &AppState,
+        headers: &HeaderMap,
+        namespace: &str,
+        key: Option<String>,
+        force_bypass: bool,
+    ) -> Self {
+        let cache = state.ask_cache();
+        let generation = state.index().namespace_generation(namespace);
+        let (result, cached) = match key.as_deref() {
+            Some(key) if !force_bypass && cache.is_enabled() && !bypass_requested(headers) => {
+                match cache.get(key, generation) {
+                    Some(hit) => (CacheResult::Hit, Some(hit)),
+                    None => (CacheResult::Miss, None),
+                }
+            }
+            _ => (CacheResult::Bypass, None),
+        };
+        cache.observe(result);
+        Self {
+            key,
+            generation,
+            result,
+            cached,
+        }
+    }
+
+    fn store(&self, state: &AppState, response: &AskResponse) {
+        if let (CacheResult::Miss, Some(key)) = (self.result, &self.key) {
+            state
+                .ask_cache()
+                .insert(key.clone(), self.generation, response.clone());
+        }
+    }
+}
+
+/// Serialised request parameters; `None` (not serialisable) bypasses the cache.
+fn cache_key<T: Serialize>(parts: &T) -> Option<String> {
+    serde_json::to_string(parts).ok()
+}
+
+fn with_cache_header(response: impl IntoResponse, result: CacheResult) -> Response {
+    let mut response = response.into_response();
+    response
+        .headers_mut()
+        .insert(CACHE_HEADER, result.header_value());
+    response
+}
+
+/// Returns the byte range of the sentence in `text` that best supports `query`.
+///
+/// Sentences are split on `.`, `!`, `?` and line breaks. The first sentence
+/// containing the query (case-insensitive) wins; otherwise the first
+/// non-empty sentence is used.
+fn supporting_sentence(text: &str, query: &str) -> Option<(usize, usize, bool)> {
+    let query_lower = query.trim().to_lowercase();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (idx, ch) in text.char_indices() {
+        if matches!(ch, '.' | '!' | '?' | '\n') {
+            let end = idx + ch.len_utf8();
+            sentences.push((start, end));
+            start = end;
+        }
+    }
+    if start < text.len() {
+        sentences.push((start, text.len()));
+    }
+
+    // Trim whitespace while keeping byte offsets aligned with the original text.
+    let trimmed: Vec<(usize, usize)> = sentences
+        .into_iter()
+        .filter_map(|(s, e)| {
+            let slice = &text[s..e];
+            let lead = slice.len() - slice.trim_start().len();
+            let trail = slice.len() - slice.trim_end().len();
+            (s + lead < e - trail).then_some((s + lead, e - trail))
+        })
+        .collect();
+
+    trimmed
+        .iter()
+        .find(|(s, e)| text[*s..*e].to_lowercase().contains(&query_lower))
+        .map(|&(s, e)| (s, e, true))
+        .or_else(|| trimmed.first().map(|&(s, e)| (s, e, false)))
+}
+
+/// Builds the extractive answer and one citation per contributing hit.
+fn extractive_answer(query: &str, matches: &[SearchMatch]) -> (String, Vec<AskCitation>) {
+    let citations: Vec<AskCitation> = matches
+        .iter()
+        .filter_map(|m| {
+            let (start, end, query_match) = supporting_sentence(&m.text, query)?;
+            // Byte offsets → char offsets, so clients in any language can slice safely.
+            let char_start = m.text[..start].chars().count();
+            let claim = m.text[start..end].to_string();
+            Some(AskCitation {
+                end: char_start + claim.chars().count(),
+                start: char_start,
+                claim,
+                doc_id: m.doc_id.clone(),
+                chunk_id: m.chunk_id.clone(),
+                namespace: m.namespace.clone(),
+                trust_level: m.source_ref.as_ref().map(|r| r.trust_level),
+                origin: m.source_ref.as_ref().map(|r| r.origin.clone()),
+                score: m.score,
+                query_match,
+            })
+        })
+        .collect();
+
+    let answer = citations
+        .iter()
+        .map(|c| c.claim.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (answer, citations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supporting_sentence_prefers_query_match() {
+        let text = "Intro line. The deploy checklist lives here! Outro?";
+        let (s, e, matched) = supporting_sentence(text, "Deploy").expect("sentence");
+        assert_eq!(&text[s..e], "The deploy checklist lives here!");
+        assert!(matched);
+    }
+
+    #[test]
+    fn supporting_sentence_falls_back_to_first() {
+        let text = "  Erster Satz. Zweiter Satz.";
+        let (s, e, matched) = supporting_sentence(text, "fehlt").expect("sentence");
+        assert_eq!(&text[s..e], "Erster Satz.");
+        assert!(!matched);
+        assert!(supporting_sentence("   ", "x").is_none());
+    }
+
+    #[test]
+    fn citations_use_char_offsets() {
+        let text = "Größe zählt. Der Deploy läuft.".to_string();
+        let m = SearchMatch {
+            doc_id: "d".into(),
+            namespace: "default".into(),
+            chunk_id: "d#0".into(),
+            score: 0.5,
+            text: text.clone(),
+            meta: serde_json::Value::Null,
+            source_ref: None,
+            ingested_at: String::new(),
+            flags: Vec::new(),
+            weights: None,
+        };
+        let (answer, citations) = extractive_answer("deploy", &[m]);
+        assert_eq!(answer, "Der Deploy läuft.");
+        let c = &citations[0];
+        let sliced: String = text.chars().skip(c.start).take(c.end - c.start).collect();
+        assert_eq!(sliced, c.claim);
+        assert_eq!(c.start, 13);
+        assert!(c.query_match);
+        assert!(c.trust_level.is_none());
+    }
+
+    #[test]
+    fn ask_request_rejects_unknown_fields() {
+        let err = serde_json::from_value::<AskRequest>(json!({"query": "x", "bogus": 1}));
+        assert!(err.is_err());
+        let ok: AskRequest =
+            serde_json::from_value(json!({"query": "x", "min_trust_level": "high"})).unwrap();
+        assert_eq!(ok.k, 5);
+        assert_eq!(ok.min_trust_level, Some(TrustLevel::High));
+        assert_eq!(ok.answer.mode, AskAnswerMode::None);
+    }
 }
@@ -0,0 +1,260 @@
+//! Kurzlebiger Antwort-Cache für `/ask`.
+//!
+//! Identische Anfragen (Dashboards, Retries) innerhalb des TTL-Fensters werden
+//! aus dem Cache bedient. Der Schlüssel umfasst Query, Namespace und alle
+//! Optionen. Einträge werden ungültig, sobald sich die Generation des
+//! Namespaces im Index ändert (Upsert, Forget, Retention-Änderung) – die
+//! Generation wird vor der Suche gelesen, parallele Mutationen machen den
+//! Eintrag also sofort ungültig.
+//!
+//! Konfiguration:
+//!   HAUSKI_ASK_CACHE_TTL_MS      (Default 5000; 0 = Cache aus)
+//!   HAUSKI_ASK_CACHE_MAX_ENTRIES (Default 256)
+//!
+//! Bypass pro Request: `Cache-Control: no-cache`/`no-store` oder
+//! `X-HausKI-Cache: bypass`. Die Antwort trägt `X-HausKI-Cache: hit|miss|bypass`.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::http::{header, HeaderMap, HeaderValue};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family},
+};
+
+use crate::ask::AskResponse;
+
+pub const CACHE_HEADER: &str = "x-hauski-cache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheResult {
+    Hit,
+    Miss,
+    Bypass,
+}
+
+impl CacheResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheResult::Hit => "hit",
+            CacheResult::Miss => "miss",
+            CacheResult::Bypass => "bypass",
+        }
+    }
+
+    pub fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AskCacheLabels {
+    result: &'static str,
+}
+
+impl EncodeLabelSet for AskCacheLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("result", self.result).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    response: AskResponse,
+    /// Namespace-Generation zum Zeitpunkt der Suche.
+    generation: u64,
+    inserted: Instant,
+}
+
+#[derive(Debug)]
+pub struct AskCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    requests: Family<AskCacheLabels, Counter>,
+}
+
+impl AskCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            requests: Family::default(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let ttl_ms = crate::env_u64("HAUSKI_ASK_CACHE_TTL_MS", 5_000);
+        let max_entries = crate::env_u64("HAUSKI_ASK_CACHE_MAX_ENTRIES", 256);
+        Self::new(
+            Duration::from_millis(ttl_ms),
+            usize::try_from(max_entries).unwrap_or(usize::MAX),
+        )
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    pub fn requests(&self) -> Family<AskCacheLabels, Counter> {
+        self.requests.clone()
+    }
+
+    /// Zählt das Ergebnis eines Cache-Lookups für die Hit-Rate-Metrik.
+    pub fn observe(&self, result: CacheResult) {
+        self.requests
+            .get_or_create(&AskCacheLabels {
+                result: result.as_str(),
+            })
+            .inc();
+    }
+
+    /// Liefert einen gültigen Eintrag; veraltete Einträge werden dabei entfernt.
+    pub fn get(&self, key: &str, current_generation: u64) -> Option<AskResponse> {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let fresh = entries.get(key).map(|entry| {
+            entry.generation == current_generation && entry.inserted.elapsed() < self.ttl
+        })?;
+        if fresh {
+            entries.get(key).map(|entry| entry.response.clone())
+        } else {
+            entries.remove(key);
+            None
+        }
+    }
+
+    pub fn insert(&self, key: String, generation: u64, response: AskResponse) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, entry| entry.inserted.elapsed() < ttl);
+            if entries.len() >= self.max_entries {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.inserted)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            CacheEntry {
+                response,
+                generation,
+                inserted: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Prüft, ob der Client den Cache für diesen Request umgehen möchte.
+pub fn bypass_requested(headers: &HeaderMap) -> bool {
+    let explicit = headers
+        .get(CACHE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().eq_ignore_ascii_case("bypass"))
+        .unwrap_or(false);
+    let cache_control = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|d| {
+            let d = d.trim();
+            d.eq_ignore_ascii_case("no-cache") || d.eq_ignore_ascii_case("no-store")
+        });
+    explicit || cache_control
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(query: &str) -> AskResponse {
+        AskResponse {
+            query: query.to_string(),
+            k: 5,
+            namespace: "default".to_string(),
+            hits: Vec::new(),
+            answer: None,
+            citations: Vec::new(),
+            session_id: None,
+            rewritten_query: None,
+        }
+    }
+
+    #[test]
+    fn hit_requires_same_generation() {
+        let cache = AskCache::new(Duration::from_secs(60), 8);
+        cache.insert("k".into(), 3, response("q"));
+
+        assert_eq!(cache.get("k", 3).map(|r| r.query), Some("q".to_string()));
+        assert!(cache.get("k", 4).is_none());
+        // Stale entry was dropped, even for the old generation.
+        assert!(cache.get("k", 3).is_none());
+    }
+
+    #[test]
+    fn expired_entries_are_not_served() {
+        let cache = AskCache::new(Duration::from_millis(1), 8);
+        cache.insert("k".into(), 0, response("q"));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("k", 0).is_none());
+    }
+
+    #[test]
+    fn capacity_evicts_oldest() {
+        let cache = AskCache::new(Duration::from_secs(60), 2);
+        cache.insert("a".into(), 0, response("a"));
+        cache.insert("b".into(), 0, response("b"));
+        cache.insert("c".into(), 0, response("c"));
+
+        assert!(cache.get("a", 0).is_none());
+        assert!(cache.get("b", 0).is_some());
+        assert!(cache.get("c", 0).is_some());
+    }
+
+    #[test]
+    fn disabled_cache_stores_nothing() {
+        let cache = AskCache::new(Duration::ZERO, 8);
+        assert!(!cache.is_enabled());
+        cache.insert("k".into(), 0, response("q"));
+        assert!(cache.get("k", 0).is_none());
+    }
+
+    #[test]
+    fn bypass_headers_are_detected() {
+        let mut headers = HeaderMap::new();
+        assert!(!bypass_requested(&headers));
+        headers.insert(CACHE_HEADER, HeaderValue::from_static("BYPASS"));
+        assert!(bypass_requested(&headers));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("max-age=0, no-cache"),
+        );
+        assert!(bypass_requested(&headers));
+    }
+}
@@ -0,0 +1,248 @@
+//! Gesprächskontext für `/ask` (Multi-Turn).
+//!
+//! Mit `session_id` werden Folgefragen („and what about last week?“) vor dem
+//! Retrieval anhand der vorherigen Turns umgeschrieben. Die Umschreibung ist
+//! bewusst deterministisch: das Thema der letzten eigenständigen Frage wird
+//! mit dem Rest der Folgefrage (ohne Floskeln und Pronomen) kombiniert.
+//!
+//! Jede Session liegt als JSON unter `ask.session:<id>` im Memory-Store und
+//! enthält die komplette Rewrite-Kette (Original, Umschreibung, Thema,
+//! Zeitstempel) zur Nachvollziehbarkeit.
+//!
+//! Konfiguration:
+//!   HAUSKI_ASK_SESSION_TTL_SEC (Default 3600; 0 = ohne TTL)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the memory keys holding session transcripts.
+pub const SESSION_KEY_PREFIX: &str = "ask.session:";
+/// Maximum number of turns kept per session (oldest are dropped).
+const MAX_TURNS: usize = 20;
+const MAX_SESSION_ID_LEN: usize = 128;
+
+/// Leading phrases that mark a question as follow-up, longest first.
+const FOLLOW_UP_PREFIXES: &[&str] = &[
+    "and what about",
+    "and how about",
+    "wie sieht es mit",
+    "und was ist mit",
+    "what about",
+    "how about",
+    "was ist mit",
+    "und wie",
+    "und was",
+    "and",
+    "und",
+    "also",
+    "auch",
+];
+
+/// Words carrying no topic of their own; dropped from the follow-up rest.
+const REFERENCE_WORDS: &[&str] = &[
+    "it", "that", "this", "those", "these", "they", "them", "there", "es", "das", "dies", "diese",
+    "dazu", "davon", "damit", "dort",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AskTurn {
+    /// Question as sent by the client.
+    pub query: String,
+    /// Query that was actually used for retrieval.
+    pub rewritten: String,
+    /// Topic of the last standalone question the turn builds on.
+    pub topic: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AskSession {
+    #[serde(default)]
+    pub turns: Vec<AskTurn>,
+}
+
+impl AskSession {
+    /// Rewrites `query` against the session history and returns the new turn.
+    pub fn next_turn(&self, query: &str) -> AskTurn {
+        let query = query.trim();
+        let topic = self.turns.last().map(|turn| turn.topic.as_str());
+        let (rewritten, topic) = match (topic, follow_up_rest(query)) {
+            (Some(topic), Some(rest)) if rest.is_empty() => (topic.to_string(), topic.to_string()),
+            (Some(topic), Some(rest)) => (format!("{topic} {rest}"), topic.to_string()),
+            _ => (query.to_string(), query.to_string()),
+        };
+        AskTurn {
+            query: query.to_string(),
+            rewritten,
+            topic,
+            at: Utc::now(),
+        }
+    }
+
+    pub fn push(&mut self, turn: AskTurn) {
+        self.turns.push(turn);
+        if self.turns.len() > MAX_TURNS {
+            let overflow = self.turns.len() - MAX_TURNS;
+            self.turns.drain(..overflow);
+        }
+    }
+}
+
+/// Returns the topical rest of a follow-up question, or `None` for
+/// standalone questions.
+fn follow_up_rest(query: &str) -> Option<String> {
+    let lowered = query.to_lowercase();
+    let trimmed = lowered.trim_end_matches(['?', '!', '.', ' ']);
+
+    let prefix_rest = FOLLOW_UP_PREFIXES.iter().find_map(|prefix| {
+        let rest = trimmed.strip_prefix(prefix)?;
+        (rest.is_empty() || rest.starts_with([' ', ','])).then_some(rest)
+    });
+
+    let words: Vec<&str> = prefix_rest
+        .unwrap_or(trimmed)
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has_reference = words.iter().any(|w| REFERENCE_WORDS.contains(w));
+
+    if prefix_rest.is_none() && !has_reference {
+        return None;
+    }
+
+    Some(
+        words
+            .into_iter()
+            .filter(|w| !REFERENCE_WORDS.contains(w))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Session ids end up in memory keys; keep them short and boring.
+pub fn validate_session_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_SESSION_ID_LEN {
+        return Err(format!(
+            "session_id must be 1-{MAX_SESSION_ID_LEN} characters"
+        ));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err("session_id may only contain [A-Za-z0-9._-]".to_string());
+    }
+    Ok(())
+}
+
+fn session_key(id: &str) -> String {
+    format!("{SESSION_KEY_PREFIX}{id}")
+}
+
+/// Loads a session from memory; unknown or unreadable sessions start empty.
+pub async fn load(id: &str) -> AskSession {
+    let Some(store) = hauski_memory::try_global() else {
+        return AskSession::default();
+    };
+    match store.get(session_key(id)).await {
+        Ok(Some(item)) => serde_json::from_slice(&item.value).unwrap_or_else(|err| {
+            tracing::warn!(session_id = id, error = %err, "ask session unreadable – starting fresh");
+            AskSession::default()
+        }),
+        Ok(None) => AskSession::default(),
+        Err(err) => {
+            tracing::warn!(session_id = id, error = ?err, "ask session load failed");
+            AskSession::default()
+        }
+    }
+}
+
+/// Persists the session; failures are logged, the answer is still served.
+pub async fn store(id: &str, session: &AskSession) {
+    let Some(store) = hauski_memory::try_global() else {
+        tracing::warn!(
+            session_id = id,
+            "memory not initialized – ask session not stored"
+        );
+        return;
+    };
+    let value = match serde_json::to_vec(session) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(session_id = id, error = %err, "ask session serialization failed");
+            return;
+        }
+    };
+    let ttl = match crate::env_u64("HAUSKI_ASK_SESSION_TTL_SEC", 3_600) {
+        0 => hauski_memory::TtlUpdate::Clear,
+        secs => hauski_memory::TtlUpdate::Set(i64::try_from(secs).unwrap_or(i64::MAX)),
+    };
+    if let Err(err) = store.set(session_key(id), value, ttl, None).await {
+        tracing::warn!(session_id = id, error = ?err, "ask session store failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_with(query: &str) -> AskSession {
+        let mut session = AskSession::default();
+        let turn = session.next_turn(query);
+        session.push(turn);
+        session
+    }
+
+    #[test]
+    fn standalone_questions_are_not_rewritten() {
+        let session = session_with("deployment incidents");
+        let turn = session.next_turn("Which runbooks mention rollback?");
+        assert_eq!(turn.rewritten, "Which runbooks mention rollback?");
+        assert_eq!(turn.topic, turn.rewritten);
+    }
+
+    #[test]
+    fn follow_up_builds_on_topic() {
+        let mut session = session_with("deployment incidents");
+        let turn = session.next_turn("And what about last week?");
+        assert_eq!(turn.rewritten, "deployment incidents last week");
+        assert_eq!(turn.topic, "deployment incidents");
+        session.push(turn);
+
+        // Chains stay anchored on the topic instead of growing.
+        let turn = session.next_turn("und davon die kritischen?");
+        assert_eq!(turn.rewritten, "deployment incidents die kritischen");
+    }
+
+    #[test]
+    fn follow_up_without_history_stays_unchanged() {
+        let turn = AskSession::default().next_turn("and what about it?");
+        assert_eq!(turn.rewritten, "and what about it?");
+    }
+
+    #[test]
+    fn prefix_must_end_on_word_boundary() {
+        let session = session_with("deployment incidents");
+        let turn = session.next_turn("android release notes");
+        assert_eq!(turn.rewritten, "android release notes");
+    }
+
+    #[test]
+    fn session_keeps_bounded_history() {
+        let mut session = AskSession::default();
+        for i in 0..(MAX_TURNS + 5) {
+            let turn = session.next_turn(&format!("question {i}"));
+            session.push(turn);
+        }
+        assert_eq!(session.turns.len(), MAX_TURNS);
+        assert_eq!(session.turns[0].query, "question 5");
+    }
+
+    #[test]
+    fn session_ids_are_validated() {
+        assert!(validate_session_id("chat-42_a.b").is_ok());
+        assert!(validate_session_id("").is_err());
+        assert!(validate_session_id("a/b").is_err());
+        assert!(validate_session_id(&"x".repeat(MAX_SESSION_ID_LEN + 1)).is_err());
+    }
+}
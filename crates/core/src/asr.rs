@@ -0,0 +1,719 @@
+//! Spracherkennung über HTTP: `POST /asr/transcribe` und `GET /asr/models`.
+//!
+//! Die Transkription selbst übernimmt `hauski-asr` (whisper.cpp); dieses Modul
+//! nimmt Audio entgegen, wählt Modell und Gerät und legt das Ergebnis auf
+//! Wunsch in indexd ab.
+//!
+//! Eingaben:
+//!   multipart/form-data – Feld `file` mit der Aufnahme, dazu optional die
+//!                         Textfelder `model`, `language`, `device`, `index`,
+//!                         `namespace`, `doc_id`
+//!   application/json    – dieselben Felder, statt `file` ein `path` auf dem
+//!                         Host; erlaubt nur unterhalb von `HAUSKI_ASR_INPUT_DIRS`
+//!
+//! Gerät `auto` wird hier aufgelöst: CPU bei GPU-Drosselung, im
+//! Energiesparmodus oder wenn keine GPU genug freies VRAM für `vram_min_gb`
+//! des Modells hat, sonst GPU (ohne NVIDIA-GPU entscheidet whisper.cpp).
+//!
+//! Mit `index: true` wird das Transkript als Dokument upserted: ein Chunk pro
+//! Segment mit `start_ms`/`end_ms` in den Metadaten, Provenienz
+//! `source_ref.origin = "asr"`.
+//!
+//! Konfiguration (zusätzlich zu den `HAUSKI_ASR_*`-Variablen von `hauski-asr`):
+//!   HAUSKI_ASR_INPUT_DIRS      (ohne Default; Verzeichnisse für `path`, getrennt wie PATH)
+//!   HAUSKI_ASR_MAX_UPLOAD_MB   (Default 100)
+//!   HAUSKI_ASR_NAMESPACE       (Default `asr`; Namespace für `index: true`)
+//!
+//! Live-Audio über WebSocket behandelt `asr_stream`.
+
+use std::{env, path::PathBuf, time::Instant};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequest, Multipart, State},
+    http::{header, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use hauski_asr::{
+    select_model, AsrConfig, AsrError, Device, ModelRef, Segment, SegmenterConfig,
+    TranscribeOptions, Transcript,
+};
+use hauski_indexd::{ChunkPayload, SourceRef, TrustLevel, UpsertRequest};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::{
+    asr_stream::StreamMetrics,
+    input_dirs::InputDirs,
+    rejection::{internal, Rejection, MAX_JSON_BYTES},
+    system::SystemSignals,
+    AppState, ModelsFile,
+};
+
+/// `source_ref.origin` of indexed transcripts.
+pub(crate) const ORIGIN: &str = "asr";
+
+const INPUT_DIRS_VAR: &str = "HAUSKI_ASR_INPUT_DIRS";
+const DEFAULT_MAX_UPLOAD_MB: u64 = 100;
+const DEFAULT_NAMESPACE: &str = "asr";
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct TranscriptionLabels {
+    device: &'static str,
+    outcome: &'static str,
+}
+
+#[derive(Debug)]
+pub struct AsrService {
+    config: AsrConfig,
+    /// Directories `path` references must lie in.
+    input_dirs: InputDirs,
+    max_upload_bytes: u64,
+    namespace: String,
+    transcriptions: Family<TranscriptionLabels, Counter>,
+    /// Defaults for `/asr/stream`; `chunk_ms` can be overridden per stream.
+    pub(crate) stream: SegmenterConfig,
+    pub(crate) stream_metrics: StreamMetrics,
+}
+
+impl AsrService {
+    pub(crate) fn new(config: AsrConfig, input_dirs: Vec<PathBuf>, max_upload_bytes: u64) -> Self {
+        Self {
+            input_dirs: InputDirs::new(INPUT_DIRS_VAR, input_dirs),
+            max_upload_bytes,
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            transcriptions: Family::default(),
+            stream: SegmenterConfig {
+                silence_dbfs: config.silence_dbfs,
+                ..SegmenterConfig::default()
+            },
+            stream_metrics: StreamMetrics::default(),
+            config,
+        }
+    }
+
+    pub(crate) fn load_from_env() -> Self {
+        let max_upload_mb = crate::env_u64("HAUSKI_ASR_MAX_UPLOAD_MB", DEFAULT_MAX_UPLOAD_MB);
+        let mut service = Self::new(AsrConfig::from_env(), Vec::new(), max_upload_mb << 20);
+        service.input_dirs = InputDirs::from_env(INPUT_DIRS_VAR);
+        if let Some(namespace) = env::var("HAUSKI_ASR_NAMESPACE")
+            .ok()
+            .filter(|namespace| !namespace.trim().is_empty())
+        {
+            service.namespace = namespace.trim().to_string();
+        }
+        let stream = &mut service.stream;
+        stream.chunk_ms = crate::env_u64("HAUSKI_ASR_STREAM_CHUNK_MS", stream.chunk_ms);
+        stream.pause_ms = crate::env_u64("HAUSKI_ASR_STREAM_PAUSE_MS", stream.pause_ms);
+        stream.max_ms = crate::env_u64("HAUSKI_ASR_STREAM_MAX_MS", stream.max_ms);
+        service
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "asr_transcriptions",
+            "Total number of /asr/transcribe runs by device and outcome (ok/silent/error)",
+            self.transcriptions.clone(),
+        );
+        self.stream_metrics.register(registry);
+    }
+
+    pub(crate) fn config(&self) -> &AsrConfig {
+        &self.config
+    }
+
+    fn count(&self, device: Device, outcome: &'static str) {
+        self.transcriptions
+            .get_or_create(&TranscriptionLabels {
+                device: device.as_str(),
+                outcome,
+            })
+            .inc();
+    }
+
+    /// Checks that `path` is a file below one of the input directories.
+    fn resolve_path(&self, path: &str) -> Result<Input, Rejection> {
+        let path = self.input_dirs.resolve(path)?;
+        Ok(Input {
+            source: path.display().to_string(),
+            path,
+            _upload: None,
+        })
+    }
+}
+
+/// Options of a transcription; with JSON bodies `path` names the input.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TranscribeRequest {
+    /// Audio file on the host below `HAUSKI_ASR_INPUT_DIRS` (JSON only).
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Model id from `models.yml`; default: the first `whisper*` entry.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// ISO code like `de`; default: detect.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Default: `HAUSKI_ASR_DEVICE`.
+    #[serde(default)]
+    pub device: Option<Device>,
+    /// Upsert the transcript into indexd.
+    #[serde(default)]
+    pub index: bool,
+    /// Namespace for `index`; default: `HAUSKI_ASR_NAMESPACE`.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Document id for `index`; default: `asr-<ulid>`.
+    #[serde(default)]
+    pub doc_id: Option<String>,
+}
+
+impl TranscribeRequest {
+    /// Applies a multipart text field.
+    fn set(&mut self, name: &str, value: String) -> Result<(), String> {
+        match name {
+            "model" => self.model = Some(value),
+            "language" => self.language = Some(value),
+            "device" => self.device = Some(value.parse()?),
+            "index" => {
+                self.index = match value.trim() {
+                    "true" | "1" | "yes" => true,
+                    "false" | "0" | "no" | "" => false,
+                    other => return Err(format!("invalid index value '{other}'")),
+                }
+            }
+            "namespace" => self.namespace = Some(value),
+            "doc_id" => self.doc_id = Some(value),
+            "path" => return Err("path references need a JSON body".into()),
+            other => return Err(format!("unknown field '{other}'")),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscribeResponse {
+    pub model: String,
+    /// Device whisper.cpp ran on; `auto` when whisper.cpp chose.
+    pub device: Device,
+    pub language: Option<String>,
+    pub text: String,
+    /// Empty when the input stayed below the silence threshold.
+    pub segments: Vec<Segment>,
+    pub processing_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<IndexedTranscript>,
+    /// Set when `index` was requested but the upsert failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IndexedTranscript {
+    pub doc_id: String,
+    pub namespace: String,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AsrModel {
+    pub id: String,
+    /// The model file exists.
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vram_min_gb: Option<u64>,
+    /// Used when a request names no model.
+    pub default: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AsrModelsResponse {
+    pub models: Vec<AsrModel>,
+    /// Configured default device.
+    pub device: Device,
+    /// whisper.cpp's model-based VAD is configured.
+    pub vad: bool,
+    /// Energy threshold below which inputs are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub silence_dbfs: Option<f32>,
+}
+
+/// Audio to transcribe; uploads live in a temporary directory until dropped.
+#[derive(Debug)]
+struct Input {
+    path: PathBuf,
+    /// File name of the upload or canonical path, recorded as `source_ref.id`.
+    source: String,
+    _upload: Option<TempDir>,
+}
+
+pub(crate) fn model_refs(models: &ModelsFile) -> Vec<ModelRef> {
+    models
+        .models
+        .iter()
+        .map(|entry| ModelRef::new(&entry.id, &entry.path, entry.vram_min_gb))
+        .collect()
+}
+
+#[utoipa::path(
+    post,
+    path = "/asr/transcribe",
+    tag = "core",
+    request_body(
+        content = TranscribeRequest,
+        description = "JSON with `path`, or multipart/form-data with the audio in `file` and the other fields as text"
+    ),
+    responses(
+        (status = 200, description = "Transcript with timestamped segments", body = TranscribeResponse),
+        (status = 400, description = "Invalid request or unknown model"),
+        (status = 403, description = "Path outside HAUSKI_ASR_INPUT_DIRS"),
+        (status = 404, description = "Path not found"),
+        (status = 413, description = "Upload larger than HAUSKI_ASR_MAX_UPLOAD_MB"),
+        (status = 422, description = "Audio could not be converted or transcribed"),
+        (status = 503, description = "No whisper model or binary available")
+    )
+)]
+pub async fn transcribe_handler(State(state): State<AppState>, request: Request<Body>) -> Response {
+    let started = Instant::now();
+    let response = match transcribe(&state, request).await {
+        Ok(response) => Json(response).into_response(),
+        Err((status, error)) => (status, Json(json!({ "error": error }))).into_response(),
+    };
+    state.record_http_observation(Method::POST, "/asr/transcribe", response.status(), started);
+    response
+}
+
+async fn transcribe(
+    state: &AppState,
+    request: Request<Body>,
+) -> Result<TranscribeResponse, Rejection> {
+    let started = Instant::now();
+    let asr = state.asr();
+    let multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/form-data"));
+    let (params, input) = if multipart {
+        let multipart = Multipart::from_request(request, &())
+            .await
+            .map_err(|err| (err.status(), err.body_text()))?;
+        read_upload(&asr, multipart).await?
+    } else {
+        let body = to_bytes(request.into_body(), MAX_JSON_BYTES)
+            .await
+            .map_err(|err| (StatusCode::PAYLOAD_TOO_LARGE, err.to_string()))?;
+        let params: TranscribeRequest = serde_json::from_slice(&body)
+            .map_err(|err| (StatusCode::BAD_REQUEST, format!("invalid request: {err}")))?;
+        let path = params.path.as_deref().ok_or((
+            StatusCode::BAD_REQUEST,
+            "path or a multipart upload in `file` is required".to_string(),
+        ))?;
+        let input = asr.resolve_path(path)?;
+        (params, input)
+    };
+
+    let model =
+        select_model(&model_refs(&state.models()), params.model.as_deref()).map_err(rejection)?;
+    let signals = state.system_monitor().get_signals().ok();
+    let device = resolve_device(
+        params.device.unwrap_or(asr.config.device),
+        &model,
+        signals.as_ref(),
+    );
+    let options = TranscribeOptions {
+        language: params.language.clone(),
+        device,
+        threads: None,
+    };
+
+    let model_id = model.id.clone();
+    let config = asr.config.clone();
+    let path = input.path.clone();
+    let result =
+        tokio::task::spawn_blocking(move || config.transcribe(&path, &model, &options)).await;
+    let transcript = match result {
+        Ok(Ok(transcript)) => transcript,
+        Ok(Err(err)) => {
+            asr.count(device, "error");
+            tracing::warn!(model = %model_id, %device, error = %err, "transcription failed");
+            return Err(rejection(err));
+        }
+        Err(err) => {
+            asr.count(device, "error");
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string()));
+        }
+    };
+    asr.count(
+        device,
+        if transcript.segments.is_empty() {
+            "silent"
+        } else {
+            "ok"
+        },
+    );
+
+    let mut response = TranscribeResponse {
+        model: model_id,
+        device,
+        language: transcript.language.clone(),
+        text: transcript.text(),
+        segments: Vec::new(),
+        processing_ms: 0,
+        indexed: None,
+        index_error: None,
+    };
+    if params.index && !transcript.segments.is_empty() {
+        let doc_id = params
+            .doc_id
+            .clone()
+            .unwrap_or_else(|| format!("asr-{}", Ulid::new()));
+        let namespace = params
+            .namespace
+            .clone()
+            .unwrap_or_else(|| asr.namespace.clone());
+        let document = transcript_document(
+            &doc_id,
+            &namespace,
+            &input.source,
+            &response.model,
+            &transcript,
+        );
+        match state.index().upsert(document).await {
+            Ok(chunks) => {
+                response.indexed = Some(IndexedTranscript {
+                    doc_id,
+                    namespace,
+                    chunks,
+                })
+            }
+            Err(err) => {
+                tracing::warn!(%doc_id, error = %err.error, "indexing transcript failed");
+                response.index_error = Some(err.error);
+            }
+        }
+    }
+    response.segments = transcript.segments;
+    response.processing_ms = started.elapsed().as_millis() as u64;
+    Ok(response)
+}
+
+/// Streams the `file` field into a temporary file and collects the options.
+async fn read_upload(
+    asr: &AsrService,
+    mut multipart: Multipart,
+) -> Result<(TranscribeRequest, Input), Rejection> {
+    let bad_request = |err: String| (StatusCode::BAD_REQUEST, err);
+    let mut params = TranscribeRequest::default();
+    let mut input = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| (err.status(), err.body_text()))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name != "file" {
+            let value = field
+                .text()
+                .await
+                .map_err(|err| (err.status(), err.body_text()))?;
+            params.set(&name, value).map_err(bad_request)?;
+            continue;
+        }
+
+        let dir = tempfile::Builder::new()
+            .prefix("hauski-asr-upload-")
+            .tempdir()
+            .map_err(internal)?;
+        let path = dir.path().join("upload");
+        let source = field.file_name().unwrap_or("upload").to_string();
+        let mut file = tokio::fs::File::create(&path).await.map_err(internal)?;
+        let mut written = 0u64;
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|err| (err.status(), err.body_text()))?
+        {
+            written += chunk.len() as u64;
+            if written > asr.max_upload_bytes {
+                return Err((
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!(
+                        "upload exceeds {} MB (HAUSKI_ASR_MAX_UPLOAD_MB)",
+                        asr.max_upload_bytes >> 20
+                    ),
+                ));
+            }
+            file.write_all(&chunk).await.map_err(internal)?;
+        }
+        file.flush().await.map_err(internal)?;
+        input = Some(Input {
+            path,
+            source,
+            _upload: Some(dir),
+        });
+    }
+    let input = input.ok_or_else(|| bad_request("multipart upload without `file`".into()))?;
+    Ok((params, input))
+}
+
+/// Resolves [`Device::Auto`] from the system signals; explicit devices are
+/// kept.
+pub(crate) fn resolve_device(
+    requested: Device,
+    model: &ModelRef,
+    signals: Option<&SystemSignals>,
+) -> Device {
+    let Some(signals) = signals.filter(|_| requested == Device::Auto) else {
+        return requested;
+    };
+    if signals.throttled || signals.low_power {
+        return Device::Cpu;
+    }
+    if !signals.gpu_available {
+        return Device::Auto;
+    }
+    let fits = model.vram_min_gb.is_none_or(|gb| {
+        signals.gpus.iter().any(
+            |gpu| match (gpu.memory_total_bytes, gpu.memory_used_bytes) {
+                (Some(total), Some(used)) => total.saturating_sub(used) >= gb << 30,
+                _ => true,
+            },
+        )
+    });
+    if fits {
+        Device::Gpu
+    } else {
+        Device::Cpu
+    }
+}
+
+pub(crate) fn rejection(err: AsrError) -> Rejection {
+    let status = match &err {
+        AsrError::UnknownModel(_) => StatusCode::BAD_REQUEST,
+        AsrError::InputMissing(_) => StatusCode::NOT_FOUND,
+        AsrError::NoModel | AsrError::ModelMissing(_) | AsrError::Spawn { .. } => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        AsrError::Failed { .. } | AsrError::InvalidOutput(_) | AsrError::Audio(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        AsrError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, err.to_string())
+}
+
+/// One chunk per segment, with the timestamps in the chunk metadata.
+fn transcript_document(
+    doc_id: &str,
+    namespace: &str,
+    source: &str,
+    model: &str,
+    transcript: &Transcript,
+) -> UpsertRequest {
+    let chunks = transcript
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| ChunkPayload {
+            chunk_id: Some(format!("{doc_id}#{i}")),
+            text: Some(segment.text.clone()),
+            text_lower: None,
+            embedding: Vec::new(),
+            meta: json!({ "start_ms": segment.start_ms, "end_ms": segment.end_ms }),
+        })
+        .collect();
+    UpsertRequest {
+        doc_id: doc_id.to_string(),
+        namespace: namespace.to_string(),
+        chunks,
+        meta: json!({
+            "language": transcript.language,
+            "model": model,
+            "source": source,
+            "transcribed_at": Utc::now(),
+        }),
+        source_ref: Some(SourceRef {
+            origin: ORIGIN.to_string(),
+            id: source.to_string(),
+            offset: None,
+            trust_level: TrustLevel::default_for_origin(ORIGIN),
+            injected_by: None,
+        }),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/asr/models",
+    tag = "core",
+    responses(
+        (status = 200, description = "Configured whisper models and ASR settings", body = AsrModelsResponse)
+    )
+)]
+pub async fn models_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let asr = state.asr();
+    let models = model_refs(&state.models());
+    let default = select_model(&models, None).ok().map(|model| model.id);
+    let models = models
+        .into_iter()
+        .filter(ModelRef::is_whisper)
+        .map(|model| AsrModel {
+            available: model.is_available(),
+            vram_min_gb: model.vram_min_gb,
+            default: default.as_deref() == Some(model.id.as_str()),
+            id: model.id,
+        })
+        .collect();
+    let response = AsrModelsResponse {
+        models,
+        device: asr.config.device,
+        vad: asr.config.vad_model.is_some(),
+        silence_dbfs: asr.config.silence_dbfs,
+    };
+    state.record_http_observation(Method::GET, "/asr/models", StatusCode::OK, started);
+    Json(response).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::system::GpuSignals;
+
+    fn signals(throttled: bool, free_gb: u64) -> SystemSignals {
+        SystemSignals {
+            cpu_load: 10.0,
+            memory_pressure: 40.0,
+            raw: Default::default(),
+            gpu_available: true,
+            gpus: vec![GpuSignals {
+                index: 0,
+                name: "RTX".into(),
+                temperature_c: Some(60),
+                memory_used_bytes: Some((8 - free_gb) << 30),
+                memory_total_bytes: Some(8 << 30),
+                utilization_pct: None,
+                power_w: None,
+                over_temperature: throttled,
+                over_power: false,
+            }],
+            throttled,
+            disks: Vec::new(),
+            power: None,
+            low_power: false,
+            occurred_at: Utc::now(),
+            source: None,
+            host: None,
+        }
+    }
+
+    #[test]
+    fn auto_device_follows_throttle_and_free_vram() {
+        let model = ModelRef::new("whisper-medium", "/m/medium.bin", Some(4));
+        let auto = |signals: &SystemSignals| resolve_device(Device::Auto, &model, Some(signals));
+        assert_eq!(auto(&signals(false, 6)), Device::Gpu);
+        assert_eq!(auto(&signals(false, 2)), Device::Cpu);
+        assert_eq!(auto(&signals(true, 6)), Device::Cpu);
+        let mut without_gpu = signals(false, 6);
+        without_gpu.gpu_available = false;
+        assert_eq!(auto(&without_gpu), Device::Auto);
+        assert_eq!(
+            resolve_device(Device::Gpu, &model, Some(&signals(true, 0))),
+            Device::Gpu
+        );
+        assert_eq!(resolve_device(Device::Auto, &model, None), Device::Auto);
+    }
+
+    #[test]
+    fn path_references_stay_inside_the_input_dirs() {
+        let allowed = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let recording = allowed.path().join("memo.wav");
+        fs::write(&recording, b"RIFF").unwrap();
+        fs::write(outside.path().join("secret.wav"), b"RIFF").unwrap();
+        let service = AsrService::new(
+            AsrConfig::default(),
+            vec![fs::canonicalize(allowed.path()).unwrap()],
+            1 << 20,
+        );
+
+        let input = service.resolve_path(recording.to_str().unwrap()).unwrap();
+        assert_eq!(input.path, fs::canonicalize(&recording).unwrap());
+        let status = |path: PathBuf| service.resolve_path(path.to_str().unwrap()).unwrap_err().0;
+        assert_eq!(
+            status(allowed.path().join("missing.wav")),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(outside.path().join("secret.wav")),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(outside.path().join("missing.wav")),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(
+                allowed
+                    .path()
+                    .join("../")
+                    .join(outside.path().file_name().unwrap())
+                    .join("secret.wav")
+            ),
+            StatusCode::FORBIDDEN
+        );
+
+        let disabled = AsrService::new(AsrConfig::default(), Vec::new(), 1 << 20);
+        assert_eq!(
+            disabled
+                .resolve_path(recording.to_str().unwrap())
+                .unwrap_err()
+                .0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn transcripts_are_indexed_with_asr_provenance() {
+        let transcript = Transcript {
+            language: Some("de".into()),
+            segments: vec![
+                Segment {
+                    start_ms: 0,
+                    end_ms: 1_200,
+                    text: "Einkaufsliste".into(),
+                },
+                Segment {
+                    start_ms: 1_200,
+                    end_ms: 2_000,
+                    text: "Milch und Brot".into(),
+                },
+            ],
+        };
+        let document =
+            transcript_document("asr-1", "asr", "memo.m4a", "whisper-small", &transcript);
+        assert_eq!(document.chunks.len(), 2);
+        assert_eq!(document.chunks[1].chunk_id.as_deref(), Some("asr-1#1"));
+        assert_eq!(document.chunks[1].meta["start_ms"], 1_200);
+        assert_eq!(document.meta["language"], "de");
+        let source_ref = document.source_ref.unwrap();
+        assert_eq!(source_ref.origin, "asr");
+        assert_eq!(source_ref.id, "memo.m4a");
+        assert_eq!(source_ref.trust_level, TrustLevel::Medium);
+    }
+}
@@ -0,0 +1,534 @@
+//! Live-Transkription über WebSocket: `GET /asr/stream`.
+//!
+//! Der Client schickt Audio als Binärnachrichten und bekommt JSON-Ereignisse
+//! als Textnachrichten zurück:
+//!   ready   – nach dem Verbindungsaufbau: Modell, Gerät, Format, `chunk_ms`
+//!   partial – alle `chunk_ms` neuer Sprache die laufende Äußerung bisher
+//!   final   – die Äußerung nach einer Sprechpause oder bei Maximallänge
+//!   error   – danach wird die Verbindung geschlossen
+//! Segment-Zeitstempel zählen ab Beginn des Streams. Die Textnachricht `end`
+//! beendet die Eingabe; die laufende Äußerung kommt dann noch als `final`.
+//!
+//! Formate (Query `format`):
+//!   pcm_s16le – rohe Samples, little endian, mono, Rate `sample_rate`
+//!   opus      – Opus in Ogg oder WebM, wie es etwa MediaRecorder liefert
+//! Alles außer 16-kHz-PCM dekodiert ein `ffmpeg`-Prozess je Verbindung.
+//!
+//! Zerlegt wird mit dem `Segmenter` aus `hauski-asr`, transkribiert jede
+//! Äußerung einzeln. `latency_ms` misst vom Eintreffen des auslösenden Audios
+//! bis zum fertigen Ergebnis; die Werte laufen in
+//! `asr_stream_latency_seconds{kind}` und gegen `latency.asr_p95_ms` in das
+//! Budget `asr_p95` von `/self/state`.
+//!
+//! Konfiguration:
+//!   HAUSKI_ASR_STREAM_CHUNK_MS  (Default 2000; Query `chunk_ms` überschreibt)
+//!   HAUSKI_ASR_STREAM_PAUSE_MS  (Default 700; Pause, die eine Äußerung beendet)
+//!   HAUSKI_ASR_STREAM_MAX_MS    (Default 30000; längere Äußerungen werden geteilt)
+
+use std::{process::Stdio, time::Instant};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hauski_asr::{
+    select_model, AsrConfig, AsrError, Device, ModelRef, Segment, Segmenter, SegmenterConfig,
+    TranscribeOptions, Utterance, UtteranceKind, SAMPLE_RATE,
+};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    process::{Child, ChildStdin, Command},
+    sync::mpsc,
+};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    asr::{model_refs, rejection, resolve_device},
+    rejection::Rejection,
+    AppState,
+};
+
+/// Path under which stream latencies enter the `asr_p95` budget.
+const BUDGET_PATH: &str = "/asr/stream";
+const MIN_CHUNK_MS: u64 = 500;
+const MAX_CHUNK_MS: u64 = 30_000;
+const MIN_SAMPLE_RATE: u32 = 8_000;
+const MAX_SAMPLE_RATE: u32 = 192_000;
+const LATENCY_BUCKETS: [f64; 10] = [0.1, 0.25, 0.5, 0.75, 0.9, 1.5, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFormat {
+    #[default]
+    PcmS16le,
+    Opus,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+pub struct StreamQuery {
+    /// Audio format of the binary messages (default `pcm_s16le`).
+    #[param(inline)]
+    format: Option<StreamFormat>,
+    /// Sample rate of `pcm_s16le` input (default 16000).
+    sample_rate: Option<u32>,
+    /// Model ID from `configs/models.yml` (default: first whisper model).
+    model: Option<String>,
+    /// Language code; without it whisper.cpp detects the language.
+    language: Option<String>,
+    #[param(inline)]
+    device: Option<Device>,
+    /// Audio per partial result (500–30000 ms).
+    chunk_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StreamResult {
+    /// Running number of the utterance; partials share it with their final.
+    pub utterance: u64,
+    pub language: Option<String>,
+    pub text: String,
+    /// Timestamps from the start of the stream.
+    pub segments: Vec<Segment>,
+    /// From the arrival of the audio that completed the chunk to the result.
+    pub latency_ms: u64,
+}
+
+/// Text messages sent to the client.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StreamEvent {
+    Ready {
+        model: String,
+        device: Device,
+        format: StreamFormat,
+        sample_rate: u32,
+        chunk_ms: u64,
+    },
+    Partial(StreamResult),
+    Final(StreamResult),
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct StreamLabels {
+    kind: &'static str,
+}
+
+fn create_latency_histogram() -> Histogram {
+    Histogram::new(LATENCY_BUCKETS)
+}
+
+#[derive(Debug)]
+pub(crate) struct StreamMetrics {
+    latency: Family<StreamLabels, Histogram>,
+    over_budget: Family<StreamLabels, Counter>,
+    active: Gauge,
+}
+
+impl Default for StreamMetrics {
+    fn default() -> Self {
+        Self {
+            latency: Family::new_with_constructor(create_latency_histogram),
+            over_budget: Family::default(),
+            active: Gauge::default(),
+        }
+    }
+}
+
+impl StreamMetrics {
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "asr_stream_latency_seconds",
+            "Latency of /asr/stream results from audio arrival to transcript, by kind (partial/final)",
+            self.latency.clone(),
+        );
+        registry.register(
+            "asr_stream_over_budget",
+            "Total number of /asr/stream results slower than latency.asr_p95_ms, by kind",
+            self.over_budget.clone(),
+        );
+        registry.register(
+            "asr_streams_active",
+            "Number of open /asr/stream connections",
+            self.active.clone(),
+        );
+    }
+
+    fn observe(&self, kind: UtteranceKind, latency_ms: u64, budget_ms: u64) {
+        let labels = StreamLabels {
+            kind: match kind {
+                UtteranceKind::Partial => "partial",
+                UtteranceKind::Final => "final",
+            },
+        };
+        self.latency
+            .get_or_create(&labels)
+            .observe(latency_ms as f64 / 1_000.0);
+        if latency_ms > budget_ms {
+            self.over_budget.get_or_create(&labels).inc();
+        }
+    }
+}
+
+/// Everything checked before the upgrade.
+#[derive(Debug)]
+struct Session {
+    model: ModelRef,
+    options: TranscribeOptions,
+    format: StreamFormat,
+    sample_rate: u32,
+    segmenter: SegmenterConfig,
+}
+
+fn open_session(state: &AppState, query: StreamQuery) -> Result<Session, Rejection> {
+    let asr = state.asr();
+    let mut segmenter = asr.stream;
+    if let Some(chunk_ms) = query.chunk_ms {
+        if !(MIN_CHUNK_MS..=MAX_CHUNK_MS).contains(&chunk_ms) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("chunk_ms must be between {MIN_CHUNK_MS} and {MAX_CHUNK_MS}"),
+            ));
+        }
+        segmenter.chunk_ms = chunk_ms;
+    }
+    let sample_rate = query.sample_rate.unwrap_or(SAMPLE_RATE);
+    if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&sample_rate) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("sample_rate must be between {MIN_SAMPLE_RATE} and {MAX_SAMPLE_RATE}"),
+        ));
+    }
+
+    let model =
+        select_model(&model_refs(&state.models()), query.model.as_deref()).map_err(rejection)?;
+    if !model.is_available() {
+        return Err(rejection(AsrError::ModelMissing(model.path)));
+    }
+    let signals = state.system_monitor().get_signals().ok();
+    let device = resolve_device(
+        query.device.unwrap_or(asr.config().device),
+        &model,
+        signals.as_ref(),
+    );
+    Ok(Session {
+        model,
+        options: TranscribeOptions {
+            language: query.language,
+            device,
+            threads: None,
+        },
+        format: query.format.unwrap_or_default(),
+        sample_rate,
+        segmenter,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/asr/stream",
+    tag = "core",
+    params(StreamQuery),
+    responses(
+        (status = 101, description = "WebSocket: audio as binary messages, `end` as text; events as JSON text messages", body = StreamEvent),
+        (status = 400, description = "Invalid parameters or unknown model"),
+        (status = 503, description = "No whisper model available")
+    )
+)]
+pub async fn stream_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    match open_session(&state, query) {
+        Ok(session) => upgrade.on_upgrade(move |socket| run(state, socket, session)),
+        Err((status, error)) => (status, Json(json!({ "error": error }))).into_response(),
+    }
+}
+
+/// Why a stream ended early.
+enum Stop {
+    /// The client went away; nobody to tell.
+    Closed,
+    Failed(String),
+}
+
+async fn run(state: AppState, mut socket: WebSocket, session: Session) {
+    let asr = state.asr();
+    asr.stream_metrics.active.inc();
+    if let Err(Stop::Failed(error)) = stream(&state, &mut socket, session).await {
+        tracing::warn!(%error, "ASR stream failed");
+        let _ = send(&mut socket, &StreamEvent::Error { error }).await;
+    }
+    let _ = socket.send(Message::Close(None)).await;
+    asr.stream_metrics.active.dec();
+}
+
+async fn stream(state: &AppState, socket: &mut WebSocket, session: Session) -> Result<(), Stop> {
+    let asr = state.asr();
+    let (decoded_tx, mut decoded) = mpsc::unbounded_channel();
+    let mut decoder = Decoder::start(
+        &asr.config().ffmpeg_bin,
+        session.format,
+        session.sample_rate,
+        decoded_tx,
+    )
+    .map_err(Stop::Failed)?;
+    send(
+        socket,
+        &StreamEvent::Ready {
+            model: session.model.id.clone(),
+            device: session.options.device,
+            format: session.format,
+            sample_rate: session.sample_rate,
+            chunk_ms: session.segmenter.chunk_ms,
+        },
+    )
+    .await?;
+
+    let mut transcriber = Transcriber {
+        state: state.clone(),
+        config: asr.config().clone(),
+        segmenter: Segmenter::new(&session.segmenter),
+        model: session.model,
+        options: session.options,
+        budget_ms: state.limits().latency.asr_p95_ms,
+    };
+    loop {
+        tokio::select! {
+            message = socket.recv(), if decoder.is_open() => match message {
+                Some(Ok(Message::Binary(bytes))) => decoder.feed(&bytes).await.map_err(Stop::Failed)?,
+                Some(Ok(Message::Text(text))) if text.trim() == "end" => decoder.close(),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Err(Stop::Closed),
+                Some(Ok(_)) => {}
+            },
+            samples = decoded.recv() => match samples {
+                Some((received, samples)) => transcriber.push(socket, &samples, received).await?,
+                None => break,
+            },
+        }
+    }
+    if let Some(utterance) = transcriber.segmenter.finish() {
+        transcriber.emit(socket, utterance, Instant::now()).await?;
+    }
+    Ok(())
+}
+
+async fn send(socket: &mut WebSocket, event: &StreamEvent) -> Result<(), Stop> {
+    let text = serde_json::to_string(event).map_err(|err| Stop::Failed(err.to_string()))?;
+    socket
+        .send(Message::Text(text.into()))
+        .await
+        .map_err(|_| Stop::Closed)
+}
+
+struct Transcriber {
+    state: AppState,
+    config: AsrConfig,
+    segmenter: Segmenter,
+    model: ModelRef,
+    options: TranscribeOptions,
+    budget_ms: u64,
+}
+
+impl Transcriber {
+    async fn push(
+        &mut self,
+        socket: &mut WebSocket,
+        samples: &[i16],
+        received: Instant,
+    ) -> Result<(), Stop> {
+        for utterance in self.segmenter.push(samples) {
+            self.emit(socket, utterance, received).await?;
+        }
+        Ok(())
+    }
+
+    async fn emit(
+        &self,
+        socket: &mut WebSocket,
+        utterance: Utterance,
+        received: Instant,
+    ) -> Result<(), Stop> {
+        let config = self.config.clone();
+        let model = self.model.clone();
+        let options = self.options.clone();
+        let (utterance, transcript) = tokio::task::spawn_blocking(move || {
+            let transcript = config.transcribe_samples(&utterance.samples, &model, &options);
+            (utterance, transcript)
+        })
+        .await
+        .map_err(|err| Stop::Failed(err.to_string()))?;
+        let transcript = transcript.map_err(|err| Stop::Failed(err.to_string()))?;
+
+        let latency_ms = received.elapsed().as_millis() as u64;
+        self.state
+            .asr()
+            .stream_metrics
+            .observe(utterance.kind, latency_ms, self.budget_ms);
+        self.state
+            .latency_budgets()
+            .observe(BUDGET_PATH, latency_ms as f64);
+
+        let result = StreamResult {
+            utterance: utterance.index,
+            language: transcript.language.clone(),
+            text: transcript.text(),
+            segments: utterance.place(transcript.segments),
+            latency_ms,
+        };
+        let event = match utterance.kind {
+            UtteranceKind::Partial => StreamEvent::Partial(result),
+            UtteranceKind::Final => StreamEvent::Final(result),
+        };
+        send(socket, &event).await
+    }
+}
+
+type Decoded = mpsc::UnboundedSender<(Instant, Vec<i16>)>;
+
+/// Turns the binary messages into 16 kHz samples on the `Decoded` channel,
+/// directly or through ffmpeg. The channel closes once the input is closed
+/// and everything is decoded.
+enum Decoder {
+    Direct {
+        output: Option<Decoded>,
+        carry: Option<u8>,
+    },
+    Ffmpeg {
+        stdin: Option<ChildStdin>,
+        _child: Child,
+    },
+}
+
+impl Decoder {
+    fn start(
+        ffmpeg_bin: &str,
+        format: StreamFormat,
+        sample_rate: u32,
+        output: Decoded,
+    ) -> Result<Self, String> {
+        let mut command = Command::new(ffmpeg_bin);
+        command.args(["-nostdin", "-loglevel", "error"]);
+        match format {
+            StreamFormat::PcmS16le if sample_rate == SAMPLE_RATE => {
+                return Ok(Self::Direct {
+                    output: Some(output),
+                    carry: None,
+                })
+            }
+            StreamFormat::PcmS16le => {
+                command.args(["-f", "s16le", "-ar", &sample_rate.to_string(), "-ac", "1"]);
+            }
+            StreamFormat::Opus => {
+                command.args(["-fflags", "nobuffer"]);
+            }
+        }
+        command
+            .args(["-i", "pipe:0", "-f", "s16le", "-ac", "1"])
+            .args(["-ar", &SAMPLE_RATE.to_string(), "pipe:1"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        let mut child = command.spawn().map_err(|err| {
+            format!("{ffmpeg_bin} could not be started (set HAUSKI_FFMPEG_BIN): {err}")
+        })?;
+        let stdin = child.stdin.take();
+        let mut stdout = child.stdout.take().ok_or("ffmpeg without stdout")?;
+        tokio::spawn(async move {
+            let mut carry = None;
+            let mut buf = vec![0_u8; 16 * 1024];
+            while let Ok(read @ 1..) = stdout.read(&mut buf).await {
+                let samples = pcm_samples(&mut carry, &buf[..read]);
+                if output.send((Instant::now(), samples)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self::Ffmpeg {
+            stdin,
+            _child: child,
+        })
+    }
+
+    fn is_open(&self) -> bool {
+        match self {
+            Self::Direct { output, .. } => output.is_some(),
+            Self::Ffmpeg { stdin, .. } => stdin.is_some(),
+        }
+    }
+
+    async fn feed(&mut self, bytes: &[u8]) -> Result<(), String> {
+        match self {
+            Self::Direct { output, carry } => {
+                if let Some(output) = output {
+                    let _ = output.send((Instant::now(), pcm_samples(carry, bytes)));
+                }
+                Ok(())
+            }
+            Self::Ffmpeg { stdin, .. } => match stdin {
+                Some(stdin) => stdin
+                    .write_all(bytes)
+                    .await
+                    .map_err(|err| format!("ffmpeg stopped decoding: {err}")),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Ends the input; ffmpeg still flushes what it has buffered.
+    fn close(&mut self) {
+        match self {
+            Self::Direct { output, .. } => *output = None,
+            Self::Ffmpeg { stdin, .. } => *stdin = None,
+        }
+    }
+}
+
+/// Little-endian 16-bit samples; an odd trailing byte waits in `carry` for
+/// the next message.
+fn pcm_samples(carry: &mut Option<u8>, bytes: &[u8]) -> Vec<i16> {
+    let mut data = Vec::with_capacity(bytes.len() + 1);
+    data.extend(carry.take());
+    data.extend_from_slice(bytes);
+    if data.len() % 2 == 1 {
+        *carry = data.pop();
+    }
+    data.chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcm_bytes_split_across_messages_are_joined() {
+        let mut carry = None;
+        assert_eq!(pcm_samples(&mut carry, &[0x01, 0x00, 0xff]), vec![1]);
+        assert_eq!(carry, Some(0xff));
+        assert_eq!(
+            pcm_samples(&mut carry, &[0xff, 0x00, 0x80]),
+            vec![-1, i16::MIN]
+        );
+        assert_eq!(carry, None);
+    }
+}
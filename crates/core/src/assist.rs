@@ -1,8 +1,13 @@
-use axum::{http::StatusCode, Json};
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use utoipa::ToSchema;
 
+use crate::progress::{sse_response, wants_event_stream, ProgressEvent, ProgressSink};
 use crate::AppState;
 use axum::extract::State;
 use axum::http::Method;
@@ -185,35 +190,80 @@ async fn fetch_topk_citations(question: &str, client: &reqwest::Client) -> Vec<A
 }
 
 /// Minimaler Assist-Router (MVP): wählt "code" oder "knowledge" und liefert eine Stub-Antwort.
+///
+/// Mit `Accept: text/event-stream` kommt die Antwort als SSE: pro Schritt
+/// `started`/`stdout`/`completed`, zum Schluss `result` mit der [`AssistResponse`].
 #[utoipa::path(
     post,
     path = "/assist",
     tag = "core",
     request_body = AssistRequest,
     responses(
-        (status = 200, description = "Assist response (MVP)", body = AssistResponse)
+        (status = 200, description = "Assist response (MVP)", body = AssistResponse),
+        (status = 200, description = "Progress stream (Accept: text/event-stream)", content_type = "text/event-stream", body = ProgressEvent)
     )
 )]
 pub async fn assist_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<AssistRequest>,
-) -> (StatusCode, Json<AssistResponse>) {
+) -> Response {
     let started = Instant::now();
+
+    if wants_event_stream(&headers) {
+        let (progress, rx) = ProgressSink::channel();
+        let run_state = state.clone();
+        tokio::spawn(async move {
+            let response = run_assist(&run_state, &req, &progress).await;
+            progress.result(&response);
+        });
+        state.record_http_observation(Method::POST, "/assist", StatusCode::OK, started);
+        return sse_response(rx).into_response();
+    }
+
+    let response = run_assist(&state, &req, &ProgressSink::disabled()).await;
+    state.record_http_observation(Method::POST, "/assist", StatusCode::OK, started);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// Führt einen Assist-Lauf aus und meldet die Schritte an `progress`.
+async fn run_assist(
+    state: &AppState,
+    req: &AssistRequest,
+    progress: &ProgressSink,
+) -> AssistResponse {
+    let started = Instant::now();
+
+    let step = progress.step("router");
     let mode = route_mode(&req.question, &req.mode);
+    step.stdout(&format!("mode: {mode}"));
+    step.finish(true);
+
     let http_client = state.http_client();
     let tool_registry = state.tools();
 
     // Falls "code", führe Analyse-Tool aus. Sonst Knowledge oder Standard-Stub.
     let answer = if mode == "code" {
         if let Some(tool) = tool_registry.get("code_analysis") {
-            tool.execute(&req.question)
-                .await
-                .unwrap_or_else(|e| format!("Tool error: {}", e))
+            let step = progress.step("tool");
+            match tool.execute(&req.question).await {
+                Ok(output) => {
+                    step.stdout(&output);
+                    step.finish(true);
+                    output
+                }
+                Err(e) => {
+                    step.stdout(&e);
+                    step.finish(false);
+                    format!("Tool error: {}", e)
+                }
+            }
         } else {
             format!("Router wählte {mode}, aber kein Tool gefunden. (MVP-Stub)")
         }
     } else if mode == "insight.negation" {
         // PR3a: Ingest insight.negation
+        let step = progress.step("ingest");
 
         let (_insight_id, is_dup) =
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&req.question) {
@@ -239,29 +289,27 @@ pub async fn assist_handler(
                     }
                     (_insight_id, is_dup)
                 } else {
-                    return (
-                        StatusCode::OK,
-                        Json(AssistResponse {
-                            answer: "Ignored (Missing ID)".to_string(),
-                            citations: Vec::new(),
-                            trace: Vec::new(),
-                            latency_ms: started.elapsed().as_millis() as u64,
-                        }),
-                    );
-                }
-            } else {
-                return (
-                    StatusCode::OK,
-                    Json(AssistResponse {
-                        answer: "Ignored (Invalid)".to_string(),
+                    step.stdout("missing id");
+                    step.finish(false);
+                    return AssistResponse {
+                        answer: "Ignored (Missing ID)".to_string(),
                         citations: Vec::new(),
                         trace: Vec::new(),
                         latency_ms: started.elapsed().as_millis() as u64,
-                    }),
-                );
+                    };
+                }
+            } else {
+                step.stdout("invalid json");
+                step.finish(false);
+                return AssistResponse {
+                    answer: "Ignored (Invalid)".to_string(),
+                    citations: Vec::new(),
+                    trace: Vec::new(),
+                    latency_ms: started.elapsed().as_millis() as u64,
+                };
             };
 
-        if is_dup {
+        let answer = if is_dup {
             "Ignored (Duplicate)".to_string()
         } else {
             // 2. Emit reflection request
@@ -275,14 +323,23 @@ pub async fn assist_handler(
                 }),
             );
             "Accepted".to_string()
-        }
+        };
+        step.stdout(&answer);
+        step.finish(true);
+        answer
     } else {
         format!("Router wählte {mode}. (MVP-Stub)")
     };
 
     // Knowledge-Modus: versuche Top-K aus /index/search; bei Fehler → leere Liste (MVP-Fallback)
     let citations = if mode == "knowledge" {
-        fetch_topk_citations(&req.question, &http_client).await
+        let step = progress.step("retrieval");
+        let citations = fetch_topk_citations(&req.question, &http_client).await;
+        for citation in &citations {
+            step.stdout(&citation.title);
+        }
+        step.finish(true);
+        citations
     } else {
         Vec::new()
     };
@@ -319,16 +376,12 @@ pub async fn assist_handler(
         );
     }
 
-    state.record_http_observation(Method::POST, "/assist", StatusCode::OK, started);
-    (
-        StatusCode::OK,
-        Json(AssistResponse {
-            answer,
-            citations,
-            trace,
-            latency_ms: ms,
-        }),
-    )
+    AssistResponse {
+        answer,
+        citations,
+        trace,
+        latency_ms: ms,
+    }
 }
 
 #[cfg(test)]
@@ -371,14 +424,23 @@ mod tests {
         let routing = crate::RoutingPolicy::default();
         let flags = crate::FeatureFlags::default();
         let chat_cfg = std::sync::Arc::new(crate::chat::ChatCfg::new(None, None));
-        let state = AppState::new(limits, models, routing, flags, chat_cfg, false);
+        let state = AppState::new(
+            limits,
+            models,
+            routing,
+            flags,
+            &crate::IndexSection::default(),
+            &crate::MemorySection::default(),
+            chat_cfg,
+            false,
+        );
 
         let req = AssistRequest {
             question: "{invalid json".to_string(),
             mode: Some("insight.negation".to_string()),
         };
 
-        let (_status, Json(resp)) = assist_handler(State(state), Json(req)).await;
+        let resp = run_assist(&state, &req, &ProgressSink::disabled()).await;
         assert_eq!(resp.answer, "Ignored (Invalid)");
     }
 
@@ -390,14 +452,23 @@ mod tests {
         let routing = crate::RoutingPolicy::default();
         let flags = crate::FeatureFlags::default();
         let chat_cfg = std::sync::Arc::new(crate::chat::ChatCfg::new(None, None));
-        let state = AppState::new(limits, models, routing, flags, chat_cfg, false);
+        let state = AppState::new(
+            limits,
+            models,
+            routing,
+            flags,
+            &crate::IndexSection::default(),
+            &crate::MemorySection::default(),
+            chat_cfg,
+            false,
+        );
 
         let req = AssistRequest {
             question: r#"{"foo": "bar"}"#.to_string(),
             mode: Some("insight.negation".to_string()),
         };
 
-        let (_status, Json(resp)) = assist_handler(State(state), Json(req)).await;
+        let resp = run_assist(&state, &req, &ProgressSink::disabled()).await;
         assert_eq!(resp.answer, "Ignored (Missing ID)");
     }
 }
@@ -410,14 +481,23 @@ async fn assist_handler_uses_tool_for_code_mode() {
     let routing = crate::RoutingPolicy::default();
     let flags = crate::FeatureFlags::default();
     let chat_cfg = std::sync::Arc::new(crate::chat::ChatCfg::new(None, None));
-    let state = AppState::new(limits, models, routing, flags, chat_cfg, false);
+    let state = AppState::new(
+        limits,
+        models,
+        routing,
+        flags,
+        &crate::IndexSection::default(),
+        &crate::MemorySection::default(),
+        chat_cfg,
+        false,
+    );
 
     let req = AssistRequest {
         question: "some code question".to_string(),
         mode: Some("code".to_string()),
     };
 
-    let (_status, Json(resp)) = assist_handler(State(state), Json(req)).await;
+    let resp = run_assist(&state, &req, &ProgressSink::disabled()).await;
 
     assert_eq!(
         resp.answer,
@@ -0,0 +1,301 @@
+//! Audio-Profile über HTTP: `GET /audio/profiles`, `GET|POST /audio/profile`.
+//!
+//! Die Profile stehen in `audio/profiles.yaml` und werden bei jedem Aufruf
+//! frisch gelesen, Änderungen greifen also ohne Neustart. Umgeschaltet wird
+//! über `hauski-audio` (`wpctl`, `pw-metadata`): ganz oder gar nicht – scheitert
+//! ein Befehl mittendrin, stellt das Crate den vorherigen Zustand wieder her.
+//! Umschaltungen laufen nacheinander; eine zweite wartet, bis die erste fertig
+//! ist.
+//!
+//! `GET /audio/profile` liest den aktuellen Graph aus `pw-dump` und nennt die
+//! Profile, deren Einstellungen gerade alle gelten – auch wenn sie per CLI
+//! oder von Hand gesetzt wurden.
+
+use std::time::Instant;
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use hauski_audio::{plan, AudioConfig, AudioError};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::AppState;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct SwitchLabels {
+    profile: String,
+    outcome: &'static str,
+}
+
+#[derive(Debug)]
+pub struct AudioService {
+    config: AudioConfig,
+    /// Serialises switches; holds the profile last switched to.
+    last_switched: Mutex<Option<String>>,
+    switches: Family<SwitchLabels, Counter>,
+}
+
+impl AudioService {
+    pub(crate) fn new(config: AudioConfig) -> Self {
+        Self {
+            config,
+            last_switched: Mutex::new(None),
+            switches: Family::default(),
+        }
+    }
+
+    pub(crate) fn load_from_env() -> Self {
+        Self::new(AudioConfig::from_env())
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "audio_profile_switches",
+            "Total number of audio profile switches by profile and outcome (ok/rejected/rolled_back/failed)",
+            self.switches.clone(),
+        );
+    }
+
+    fn count(&self, profile: &str, outcome: &'static str) {
+        self.switches
+            .get_or_create(&SwitchLabels {
+                profile: profile.to_string(),
+                outcome,
+            })
+            .inc();
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AudioProfileSummary {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `node.name` pattern of the default output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sink: Option<String>,
+    /// `node.name` pattern of the default input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AudioProfilesResponse {
+    pub profiles: Vec<AudioProfileSummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AudioProfileState {
+    /// Profiles whose settings all hold in the current graph.
+    pub applied: Vec<String>,
+    /// Profile last switched to through `POST /audio/profile`.
+    pub last_switched: Option<String>,
+    pub default_sink: Option<String>,
+    pub default_source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AudioSwitchRequest {
+    /// Profile name from `audio/profiles.yaml`, e.g. `call`, `music` or `asr`.
+    pub profile: String,
+    /// Only plan the commands.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AudioSwitchResponse {
+    pub profile: String,
+    pub dry_run: bool,
+    /// `wpctl`/`pw-metadata` command lines, in order.
+    pub commands: Vec<String>,
+}
+
+type Rejection = (StatusCode, String);
+
+fn rejection(err: AudioError) -> Rejection {
+    let status = match &err {
+        AudioError::UnknownProfile { .. } => StatusCode::BAD_REQUEST,
+        AudioError::NoDevice(_)
+        | AudioError::NoNode { .. }
+        | AudioError::UnknownCardProfile { .. } => StatusCode::CONFLICT,
+        AudioError::Profiles { .. }
+        | AudioError::InvalidProfile { .. }
+        | AudioError::Spawn { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        AudioError::Failed { .. } | AudioError::InvalidDump(_) | AudioError::RolledBack(_) => {
+            StatusCode::BAD_GATEWAY
+        }
+        AudioError::RollbackFailed { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, err.to_string())
+}
+
+/// Runs blocking `hauski-audio` calls off the runtime.
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AudioError> + Send + 'static,
+) -> Result<T, Rejection> {
+    match tokio::task::spawn_blocking(task).await {
+        Ok(result) => result.map_err(rejection),
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    }
+}
+
+fn respond<T: Serialize>(
+    state: &AppState,
+    method: Method,
+    path: &'static str,
+    started: Instant,
+    result: Result<T, Rejection>,
+) -> Response {
+    let response = match result {
+        Ok(body) => Json(body).into_response(),
+        Err((status, error)) => (status, Json(json!({ "error": error }))).into_response(),
+    };
+    state.record_http_observation(method, path, response.status(), started);
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/audio/profiles",
+    tag = "core",
+    responses(
+        (status = 200, description = "Profiles from audio/profiles.yaml", body = AudioProfilesResponse),
+        (status = 503, description = "Profile file missing or invalid")
+    )
+)]
+pub async fn profiles_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let config = state.audio().config.clone();
+    let result = blocking(move || config.load_profiles())
+        .await
+        .map(|file| AudioProfilesResponse {
+            profiles: file
+                .profiles
+                .into_iter()
+                .map(|(name, profile)| AudioProfileSummary {
+                    name,
+                    description: profile.description,
+                    sink: profile.sink,
+                    source: profile.source,
+                })
+                .collect(),
+        });
+    respond(&state, Method::GET, "/audio/profiles", started, result)
+}
+
+#[utoipa::path(
+    get,
+    path = "/audio/profile",
+    tag = "core",
+    responses(
+        (status = 200, description = "Profiles the current PipeWire graph satisfies", body = AudioProfileState),
+        (status = 502, description = "pw-dump failed"),
+        (status = 503, description = "Profile file or pw-dump unavailable")
+    )
+)]
+pub async fn profile_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let audio = state.audio();
+    let last_switched = audio.last_switched.lock().await.clone();
+    let config = audio.config.clone();
+    let result = blocking(move || Ok((config.load_profiles()?, config.read_graph()?)))
+        .await
+        .map(|(profiles, graph)| AudioProfileState {
+            applied: profiles.applied(&graph),
+            last_switched,
+            default_sink: graph.default_sink,
+            default_source: graph.default_source,
+        });
+    respond(&state, Method::GET, "/audio/profile", started, result)
+}
+
+#[utoipa::path(
+    post,
+    path = "/audio/profile",
+    tag = "core",
+    request_body = AudioSwitchRequest,
+    responses(
+        (status = 200, description = "Profile applied (or planned with dry_run)", body = AudioSwitchResponse),
+        (status = 400, description = "Unknown profile"),
+        (status = 409, description = "No device or node matches the profile; nothing changed"),
+        (status = 502, description = "A command failed; the previous routing was restored"),
+        (status = 500, description = "A command failed and restoring the previous routing failed too"),
+        (status = 503, description = "Profile file, wpctl or pw-dump unavailable")
+    )
+)]
+pub async fn switch_handler(
+    State(state): State<AppState>,
+    Json(request): Json<AudioSwitchRequest>,
+) -> Response {
+    let started = Instant::now();
+    let result = switch(&state, request).await;
+    respond(&state, Method::POST, "/audio/profile", started, result)
+}
+
+async fn switch(
+    state: &AppState,
+    request: AudioSwitchRequest,
+) -> Result<AudioSwitchResponse, Rejection> {
+    let audio = state.audio();
+    let config = audio.config.clone();
+    let profiles = blocking(move || config.load_profiles()).await?;
+    let profile = profiles.get(&request.profile).map_err(rejection)?.clone();
+    let config = audio.config.clone();
+
+    if request.dry_run {
+        let commands = blocking(move || {
+            let actions = plan(&profile, &config.read_graph()?)?;
+            Ok(actions
+                .iter()
+                .map(|action| config.argv(action).join(" "))
+                .collect())
+        })
+        .await?;
+        return Ok(AudioSwitchResponse {
+            profile: request.profile,
+            dry_run: true,
+            commands,
+        });
+    }
+
+    let mut last_switched = audio.last_switched.lock().await;
+    match tokio::task::spawn_blocking(move || config.switch(&profile)).await {
+        Ok(Ok(commands)) => {
+            audio.count(&request.profile, "ok");
+            tracing::info!(profile = %request.profile, "audio profile switched");
+            *last_switched = Some(request.profile.clone());
+            Ok(AudioSwitchResponse {
+                profile: request.profile,
+                dry_run: false,
+                commands,
+            })
+        }
+        Ok(Err(err)) => {
+            let outcome = match &err {
+                AudioError::RolledBack(_) => "rolled_back",
+                AudioError::RollbackFailed { .. } => "failed",
+                _ => "rejected",
+            };
+            audio.count(&request.profile, outcome);
+            tracing::warn!(profile = %request.profile, error = %err, "audio profile switch failed");
+            if !err.is_unchanged() {
+                *last_switched = None;
+            }
+            Err(rejection(err))
+        }
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    }
+}
@@ -0,0 +1,822 @@
+//! Backup und Restore des ganzen HausKI-Zustands: `GET|POST /backup`,
+//! `GET /backup/{name}`, `POST /backup/{name}/restore`.
+//!
+//! Ein Backup ist ein Archiv aus `hauski-backup` mit
+//!   index/snapshot.json – alle Dokumente aus indexd
+//!   memory/memory.db    – Snapshot der Memory-DB (`VACUUM INTO`)
+//!   policy/state.json   – gelernter Zustand der Entscheidungs-Policy
+//!   configs/…, policies/… – Konfigurationsdateien, soweit vorhanden
+//!
+//! Archive liegen in `HAUSKI_BACKUP_DIR`; nach jedem Backup bleiben die
+//! `HAUSKI_BACKUP_KEEP` neuesten. Mit `HAUSKI_BACKUP_PASSPHRASE` werden neue
+//! Archive verschlüsselt; dieselbe Passphrase braucht der Restore.
+//!
+//! Restore braucht ein `api_token` (außer mit `dry_run`), weil es
+//! Konfigurationen samt Egress-Allowlist überschreiben kann. Es prüft zuerst
+//! alles: Format, Prüfsummen, JSON-Inhalte, die Schema-Version des
+//! Policy-Zustands, die Memory-DB (`integrity_check`) und die Anzahl der
+//! Dokumente und Memory-Einträge gegen das Manifest. Erst dann werden die
+//! Teile übernommen: memory (in einer Transaktion), index, policy und zuletzt
+//! die Konfigurationen, die vorher als temporäre Dateien daneben liegen und
+//! per `rename` ersetzt werden. Index-Dokumente mit gleicher ID werden ersetzt, neuere bleiben;
+//! die Memory-Einträge werden komplett ersetzt. Konfigurationen kommen nur
+//! zurück, wenn `configs` ausdrücklich angefordert ist, und greifen beim
+//! nächsten Reload bzw. Neustart. Backups und Restores laufen nacheinander
+//! und auch dann zu Ende, wenn die Anfrage vorher in den HTTP-Timeout läuft.
+
+use std::{
+    collections::BTreeMap, fmt, future::Future, path::PathBuf, sync::atomic::AtomicI64,
+    time::Instant,
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use hauski_backup::{
+    config_path, ArchiveInfo, BackupDir, BackupError, Bundle, Manifest, CONFIG_FILES, INDEX_ENTRY,
+    MEMORY_DB_ENTRY, POLICY_ENTRY,
+};
+use hauski_indexd::IndexSnapshot;
+use hauski_notify::{Notification, Priority};
+use policy::state::{EngineState, STATE_SCHEMA_VERSION};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::{
+    chronik, notify,
+    rejection::{internal, Rejection},
+    AppState,
+};
+
+const DEFAULT_KEEP: u64 = 7;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RunLabels {
+    operation: &'static str,
+    outcome: &'static str,
+}
+
+pub struct BackupService {
+    dir: BackupDir,
+    keep: usize,
+    passphrase: Option<String>,
+    /// Serialises backups and restores.
+    lock: Mutex<()>,
+    runs: Family<RunLabels, Counter>,
+    last_success: Gauge<i64, AtomicI64>,
+}
+
+impl fmt::Debug for BackupService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackupService")
+            .field("dir", &self.dir)
+            .field("keep", &self.keep)
+            .field("encrypted", &self.passphrase.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackupService {
+    pub(crate) fn new(dir: PathBuf, keep: usize, passphrase: Option<String>) -> Self {
+        Self {
+            dir: BackupDir::new(dir),
+            keep: keep.max(1),
+            passphrase,
+            lock: Mutex::new(()),
+            runs: Family::default(),
+            last_success: Gauge::default(),
+        }
+    }
+
+    pub(crate) fn load_from_env() -> Self {
+        let passphrase = std::env::var("HAUSKI_BACKUP_PASSPHRASE")
+            .ok()
+            .filter(|value| !value.is_empty());
+        Self::new(
+            crate::env_path("HAUSKI_BACKUP_DIR", "backups"),
+            crate::env_u64("HAUSKI_BACKUP_KEEP", DEFAULT_KEEP) as usize,
+            passphrase,
+        )
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "backup_runs",
+            "Total number of backup operations by operation (create/verify/restore) and outcome (ok/failed)",
+            self.runs.clone(),
+        );
+        registry.register(
+            "backup_last_success_timestamp_seconds",
+            "Unix time of the last successful backup",
+            self.last_success.clone(),
+        );
+    }
+
+    fn count(&self, operation: &'static str, ok: bool) {
+        let outcome = if ok { "ok" } else { "failed" };
+        self.runs
+            .get_or_create(&RunLabels { operation, outcome })
+            .inc();
+    }
+}
+
+fn rejection(err: BackupError) -> Rejection {
+    let status = match &err {
+        BackupError::InvalidName(_) => StatusCode::BAD_REQUEST,
+        BackupError::NotFound(_) => StatusCode::NOT_FOUND,
+        BackupError::Format(_)
+        | BackupError::UnsupportedFormat { .. }
+        | BackupError::MissingEntry(_)
+        | BackupError::Checksum(_)
+        | BackupError::PassphraseRequired
+        | BackupError::Decrypt => StatusCode::UNPROCESSABLE_ENTITY,
+        BackupError::Io { .. } | BackupError::Encrypt(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, err.to_string())
+}
+
+/// Runs blocking archive work off the runtime.
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, BackupError> + Send + 'static,
+) -> Result<T, Rejection> {
+    match tokio::task::spawn_blocking(task).await {
+        Ok(result) => result.map_err(rejection),
+        Err(err) => Err(internal(err)),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupCreated {
+    pub backup: ArchiveInfo,
+    pub manifest: Manifest,
+    /// Older archives deleted to keep the newest `keep`.
+    pub removed: Vec<String>,
+}
+
+/// Writes a backup into `dir` (default `HAUSKI_BACKUP_DIR`) and keeps the
+/// newest `keep` (default `HAUSKI_BACKUP_KEEP`) there.
+pub(crate) async fn create(
+    state: &AppState,
+    dir: Option<PathBuf>,
+    keep: Option<usize>,
+) -> Result<BackupCreated, Rejection> {
+    let service = state.backups();
+    let _guard = service.lock.lock().await;
+    let result = write_backup(state, dir, keep).await;
+    service.count("create", result.is_ok());
+    match &result {
+        Ok(created) => {
+            service
+                .last_success
+                .set(created.manifest.created_at.timestamp());
+            tracing::info!(backup = %created.backup.name, bytes = created.backup.bytes, removed = created.removed.len(), "backup written");
+            chronik::publish(
+                state,
+                chronik::SOURCE_BACKUP,
+                "backup.created",
+                &json!({ "backup": created.backup, "counts": created.manifest.counts }),
+            );
+        }
+        Err((_, error)) => {
+            tracing::warn!(error = %error, "backup failed");
+            notify::notify(
+                state,
+                Notification::new("backup.failed", "Backup fehlgeschlagen", Priority::High)
+                    .body(error.clone()),
+            );
+        }
+    }
+    result
+}
+
+async fn write_backup(
+    state: &AppState,
+    dir: Option<PathBuf>,
+    keep: Option<usize>,
+) -> Result<BackupCreated, Rejection> {
+    let service = state.backups();
+    let mut bundle = Bundle::default();
+    let mut counts = BTreeMap::new();
+
+    let snapshot = state.index().snapshot().await;
+    counts.insert("documents".to_string(), snapshot.documents.len() as u64);
+    bundle.insert(
+        INDEX_ENTRY,
+        serde_json::to_vec(&snapshot).map_err(internal)?,
+    );
+
+    if let Some(store) = hauski_memory::try_global() {
+        let scratch = tempfile::tempdir().map_err(internal)?;
+        let path = scratch.path().join("memory.db");
+        let items = store
+            .backup_to(path.clone())
+            .await
+            .map_err(|err| internal(format!("memory snapshot failed: {err}")))?;
+        counts.insert("memory_items".to_string(), items as u64);
+        bundle.insert(
+            MEMORY_DB_ENTRY,
+            tokio::fs::read(&path).await.map_err(internal)?,
+        );
+    }
+
+    let policy_state = state.policy().engine().state();
+    counts.insert(
+        "policy_kinds".to_string(),
+        policy_state.bandits.len() as u64,
+    );
+    bundle.insert(
+        POLICY_ENTRY,
+        serde_json::to_vec_pretty(&policy_state).map_err(internal)?,
+    );
+
+    let mut configs = 0;
+    for (name, env_var, default) in CONFIG_FILES {
+        let path = config_path(env_var, default);
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => {
+                bundle.insert(name, bytes);
+                configs += 1;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(internal(format!("{}: {err}", path.display()))),
+        }
+    }
+    counts.insert("configs".to_string(), configs);
+
+    let target = dir.map_or_else(|| service.dir.clone(), BackupDir::new);
+    let keep = keep.unwrap_or(service.keep);
+    let passphrase = service.passphrase.clone();
+    blocking(move || {
+        let (backup, manifest) = target.write(
+            &bundle,
+            env!("CARGO_PKG_VERSION"),
+            counts,
+            passphrase.as_deref(),
+        )?;
+        let removed = target.prune(keep)?;
+        Ok(BackupCreated {
+            backup,
+            manifest,
+            removed,
+        })
+    })
+    .await
+}
+
+/// Parts of a backup that can be restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupPart {
+    Index,
+    Policy,
+    Memory,
+    Configs,
+}
+
+impl BackupPart {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Index => "index",
+            Self::Policy => "policy",
+            Self::Memory => "memory",
+            Self::Configs => "configs",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BackupRestoreRequest {
+    /// Parts to restore; default: index, policy and memory, as far as the
+    /// backup contains them. `configs` overwrites the config files.
+    #[serde(default)]
+    pub parts: Option<Vec<BackupPart>>,
+    /// Only verify the backup and report what would be restored.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupRestoreResponse {
+    pub backup: String,
+    pub dry_run: bool,
+    /// Restored (or, with `dry_run`, restorable) amount per part: documents,
+    /// policy kinds, memory items and config files.
+    pub restored: BTreeMap<String, u64>,
+    /// Config files written.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub configs: Vec<String>,
+}
+
+/// Contents of a backup, checked and decoded before anything is restored.
+struct Verified {
+    index: Option<IndexSnapshot>,
+    policy: Option<EngineState>,
+    memory: Option<Vec<u8>>,
+    configs: Vec<(PathBuf, Vec<u8>)>,
+    counts: BTreeMap<String, u64>,
+}
+
+fn verify(
+    manifest: Manifest,
+    bundle: Bundle,
+    parts: &[BackupPart],
+) -> Result<Verified, BackupError> {
+    let missing = |name: &str| BackupError::MissingEntry(name.to_string());
+    let mut verified = Verified {
+        index: None,
+        policy: None,
+        memory: None,
+        configs: Vec::new(),
+        counts: manifest.counts,
+    };
+    for part in parts {
+        match part {
+            BackupPart::Index => {
+                verified.index = Some(
+                    bundle
+                        .json(INDEX_ENTRY)?
+                        .ok_or_else(|| missing(INDEX_ENTRY))?,
+                );
+            }
+            BackupPart::Policy => {
+                let state: EngineState = bundle
+                    .json(POLICY_ENTRY)?
+                    .ok_or_else(|| missing(POLICY_ENTRY))?;
+                if state.schema_version != STATE_SCHEMA_VERSION {
+                    return Err(BackupError::Format(format!(
+                        "{POLICY_ENTRY}: schema version {} (expected {STATE_SCHEMA_VERSION})",
+                        state.schema_version
+                    )));
+                }
+                verified.policy = Some(state);
+            }
+            BackupPart::Memory => {
+                verified.memory = Some(
+                    bundle
+                        .get(MEMORY_DB_ENTRY)
+                        .ok_or_else(|| missing(MEMORY_DB_ENTRY))?
+                        .to_vec(),
+                );
+            }
+            BackupPart::Configs => {
+                verified.configs = CONFIG_FILES
+                    .iter()
+                    .filter_map(|(name, env_var, default)| {
+                        let bytes = bundle.get(name)?.to_vec();
+                        Some((config_path(env_var, default), bytes))
+                    })
+                    .collect();
+            }
+        }
+    }
+    Ok(verified)
+}
+
+/// What `parts: null` restores: index, policy and memory where present.
+fn default_parts(manifest: &Manifest) -> Vec<BackupPart> {
+    [
+        (BackupPart::Index, INDEX_ENTRY),
+        (BackupPart::Policy, POLICY_ENTRY),
+        (BackupPart::Memory, MEMORY_DB_ENTRY),
+    ]
+    .into_iter()
+    .filter(|(_, entry)| manifest.entries.contains_key(*entry))
+    .map(|(part, _)| part)
+    .collect()
+}
+
+/// Compares an amount about to be restored with the manifest.
+fn check_count(counts: &BTreeMap<String, u64>, key: &str, found: u64) -> Result<u64, Rejection> {
+    match counts.get(key) {
+        Some(&expected) if expected != found => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("backup lists {expected} {key}, but contains {found}"),
+        )),
+        _ => Ok(found),
+    }
+}
+
+/// A config file written next to its target, renamed into place last.
+struct StagedConfig {
+    staged: PathBuf,
+    target: PathBuf,
+}
+
+/// Writes every config file to a temporary sibling; on error the ones
+/// already written are removed again.
+async fn stage_configs(configs: &[(PathBuf, Vec<u8>)]) -> Result<Vec<StagedConfig>, Rejection> {
+    let mut staged = Vec::with_capacity(configs.len());
+    for (target, bytes) in configs {
+        match stage_config(target, bytes).await {
+            Ok(path) => staged.push(StagedConfig {
+                staged: path,
+                target: target.clone(),
+            }),
+            Err(err) => {
+                discard_staged(&staged).await;
+                return Err(err);
+            }
+        }
+    }
+    Ok(staged)
+}
+
+async fn stage_config(target: &std::path::Path, bytes: &[u8]) -> Result<PathBuf, Rejection> {
+    let failed = |path: &std::path::Path, err: std::io::Error| {
+        internal(format!("{}: {err}", path.display()))
+    };
+    let parent = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    tokio::fs::create_dir_all(parent)
+        .await
+        .map_err(|err| failed(parent, err))?;
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| internal(format!("{} is not a file", target.display())))?;
+    let staged = parent.join(format!(".{}.restore", file_name.to_string_lossy()));
+    tokio::fs::write(&staged, bytes)
+        .await
+        .map_err(|err| failed(&staged, err))?;
+    Ok(staged)
+}
+
+async fn discard_staged(staged: &[StagedConfig]) {
+    for config in staged {
+        let _ = tokio::fs::remove_file(&config.staged).await;
+    }
+}
+
+async fn restore(
+    state: &AppState,
+    name: String,
+    request: BackupRestoreRequest,
+) -> Result<BackupRestoreResponse, Rejection> {
+    // Restore rewrites routing.yaml (egress allowlist) and the other configs.
+    if !request.dry_run && state.flags().api_token.is_none() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "restoring a backup requires api_token".to_string(),
+        ));
+    }
+    let service = state.backups();
+    let _guard = service.lock.lock().await;
+    let dir = service.dir.clone();
+    let passphrase = service.passphrase.clone();
+    let archive = name.clone();
+    let (manifest, bundle) = blocking(move || dir.read(&archive, passphrase.as_deref())).await?;
+    let mut parts = request.parts.unwrap_or_else(|| default_parts(&manifest));
+    parts.sort();
+    parts.dedup();
+    let verified = verify(manifest, bundle, &parts).map_err(rejection)?;
+
+    // Check every part, including the counts, before the first change.
+    let mut restored = BTreeMap::new();
+    let index = match verified.index {
+        Some(snapshot) => {
+            let prepared = state
+                .index()
+                .prepare_restore(snapshot.documents)
+                .map_err(|err| {
+                    (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("index: {} ({})", err.error, err.code),
+                    )
+                })?;
+            let documents = check_count(&verified.counts, "documents", prepared.len() as u64)?;
+            restored.insert("documents".into(), documents);
+            Some(prepared)
+        }
+        None => None,
+    };
+    if let Some(policy) = &verified.policy {
+        restored.insert("policy_kinds".into(), policy.bandits.len() as u64);
+    }
+    let memory_file = match &verified.memory {
+        Some(bytes) => {
+            let store = hauski_memory::try_global().ok_or_else(|| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "memory store not initialized".to_string(),
+                )
+            })?;
+            let scratch = tempfile::tempdir().map_err(internal)?;
+            let path = scratch.path().join("memory.db");
+            tokio::fs::write(&path, bytes).await.map_err(internal)?;
+            let items = hauski_memory::MemoryStore::inspect_snapshot(path.clone())
+                .await
+                .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("memory: {err}")))?;
+            let items = check_count(&verified.counts, "memory_items", items as u64)?;
+            restored.insert("memory_items".into(), items);
+            Some((store, scratch, path))
+        }
+        None => None,
+    };
+    if parts.contains(&BackupPart::Configs) {
+        restored.insert("configs".into(), verified.configs.len() as u64);
+    }
+    let configs: Vec<String> = verified
+        .configs
+        .iter()
+        .map(|(path, _)| path.display().to_string())
+        .collect();
+    if request.dry_run {
+        return Ok(BackupRestoreResponse {
+            backup: name,
+            dry_run: true,
+            restored,
+            configs,
+        });
+    }
+    let staged = stage_configs(&verified.configs).await?;
+
+    // Memory is the only part that can still fail; it runs in a transaction
+    // and first, so a failure leaves everything as it was.
+    if let Some((store, _scratch, path)) = memory_file {
+        if let Err(err) = store.restore_from(path).await {
+            discard_staged(&staged).await;
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("memory: {err}")));
+        }
+    }
+    if let Some(prepared) = index {
+        state.index().apply_restore(prepared).await;
+    }
+    if let Some(policy_state) = verified.policy {
+        let policy = state.policy();
+        let kinds = policy
+            .engine()
+            .restore(policy_state)
+            .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("policy: {err}")))?;
+        if let Err(err) = policy.save_state() {
+            tracing::warn!(error = %err, "restored policy state could not be saved");
+        }
+        // Kinds no longer configured start fresh, so no check against the manifest.
+        restored.insert("policy_kinds".into(), kinds.len() as u64);
+    }
+    for config in &staged {
+        tokio::fs::rename(&config.staged, &config.target)
+            .await
+            .map_err(|err| internal(format!("{}: {err}", config.target.display())))?;
+    }
+    tracing::info!(backup = %name, parts = ?parts.iter().map(|p| p.as_str()).collect::<Vec<_>>(), "backup restored");
+    chronik::publish(
+        state,
+        chronik::SOURCE_BACKUP,
+        "backup.restored",
+        &json!({ "backup": name, "restored": restored }),
+    );
+    Ok(BackupRestoreResponse {
+        backup: name,
+        dry_run: false,
+        restored,
+        configs,
+    })
+}
+
+/// Runs `task` on its own, so a request timeout cannot cut a backup or a
+/// restore short.
+async fn detached<T: Send + 'static>(
+    task: impl Future<Output = Result<T, Rejection>> + Send + 'static,
+) -> Result<T, Rejection> {
+    tokio::spawn(task)
+        .await
+        .unwrap_or_else(|err| Err(internal(err)))
+}
+
+fn respond<T: Serialize>(
+    state: &AppState,
+    method: Method,
+    path: &'static str,
+    started: Instant,
+    success: StatusCode,
+    result: Result<T, Rejection>,
+) -> Response {
+    let response = match result {
+        Ok(body) => (success, Json(body)).into_response(),
+        Err((status, error)) => (status, Json(json!({ "error": error }))).into_response(),
+    };
+    state.record_http_observation(method, path, response.status(), started);
+    response
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupListResponse {
+    pub dir: String,
+    pub keep: usize,
+    /// Whether new backups are encrypted (`HAUSKI_BACKUP_PASSPHRASE`).
+    pub encrypt: bool,
+    /// Oldest first.
+    pub backups: Vec<ArchiveInfo>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupVerifyResponse {
+    pub backup: ArchiveInfo,
+    pub manifest: Manifest,
+    /// Format, checksums and contents are intact.
+    pub verified: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/backup",
+    tag = "core",
+    responses(
+        (status = 200, description = "Backups in HAUSKI_BACKUP_DIR", body = BackupListResponse),
+        (status = 500, description = "Backup directory unreadable")
+    )
+)]
+pub async fn list_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let service = state.backups();
+    let dir = service.dir.clone();
+    let result = blocking(move || dir.list())
+        .await
+        .map(|backups| BackupListResponse {
+            dir: service.dir.path().display().to_string(),
+            keep: service.keep,
+            encrypt: service.passphrase.is_some(),
+            backups,
+        });
+    respond(
+        &state,
+        Method::GET,
+        "/backup",
+        started,
+        StatusCode::OK,
+        result,
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/backup",
+    tag = "core",
+    responses(
+        (status = 201, description = "Backup written; older ones pruned", body = BackupCreated),
+        (status = 500, description = "Backup failed")
+    )
+)]
+pub async fn create_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let task_state = state.clone();
+    let result = detached(async move { create(&task_state, None, None).await }).await;
+    respond(
+        &state,
+        Method::POST,
+        "/backup",
+        started,
+        StatusCode::CREATED,
+        result,
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/backup/{name}",
+    tag = "core",
+    params(("name" = String, Path, description = "Backup file name")),
+    responses(
+        (status = 200, description = "Backup read and verified", body = BackupVerifyResponse),
+        (status = 400, description = "Not a backup name"),
+        (status = 404, description = "Unknown backup"),
+        (status = 422, description = "Backup damaged, too new or not decryptable")
+    )
+)]
+pub async fn verify_handler(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let started = Instant::now();
+    let service = state.backups();
+    let dir = service.dir.clone();
+    let passphrase = service.passphrase.clone();
+    let result = blocking(move || {
+        let backup = dir.get(&name)?;
+        let (manifest, bundle) = dir.read(&name, passphrase.as_deref())?;
+        let parts = default_parts(&manifest);
+        verify(manifest.clone(), bundle, &parts)?;
+        Ok(BackupVerifyResponse {
+            backup,
+            manifest,
+            verified: true,
+        })
+    })
+    .await;
+    service.count("verify", result.is_ok());
+    respond(
+        &state,
+        Method::GET,
+        "/backup/{name}",
+        started,
+        StatusCode::OK,
+        result,
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/backup/{name}/restore",
+    tag = "core",
+    params(("name" = String, Path, description = "Backup file name")),
+    request_body = BackupRestoreRequest,
+    responses(
+        (status = 200, description = "Backup verified and restored (or checked with dry_run)", body = BackupRestoreResponse),
+        (status = 400, description = "Not a backup name"),
+        (status = 403, description = "No api_token configured"),
+        (status = 404, description = "Unknown backup"),
+        (status = 422, description = "Backup damaged, incomplete, not matching its manifest or not decryptable; nothing restored"),
+        (status = 500, description = "Restore failed")
+    )
+)]
+pub async fn restore_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<BackupRestoreRequest>,
+) -> Response {
+    let started = Instant::now();
+    let task_state = state.clone();
+    let result = detached(async move {
+        let result = restore(&task_state, name, request).await;
+        task_state.backups().count("restore", result.is_ok());
+        if let Err((_, error)) = &result {
+            tracing::warn!(error = %error, "backup restore failed");
+        }
+        result
+    })
+    .await;
+    respond(
+        &state,
+        Method::POST,
+        "/backup/{name}/restore",
+        started,
+        StatusCode::OK,
+        result,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn verify_checks_requested_parts_before_restoring() {
+        let mut bundle = Bundle::default();
+        bundle.insert(INDEX_ENTRY, br#"{"documents":[]}"#.to_vec());
+        bundle.insert(
+            POLICY_ENTRY,
+            serde_json::to_vec(&json!({
+                "schema_version": STATE_SCHEMA_VERSION + 1,
+                "saved_at": Utc::now(),
+                "bandits": {}
+            }))
+            .unwrap(),
+        );
+        let manifest = Manifest {
+            format: hauski_backup::FORMAT_VERSION,
+            hauski_version: "0.1.0".into(),
+            created_at: Utc::now(),
+            counts: BTreeMap::new(),
+            entries: [INDEX_ENTRY, POLICY_ENTRY]
+                .into_iter()
+                .map(|name| {
+                    let entry = hauski_backup::ManifestEntry {
+                        sha256: String::new(),
+                        bytes: 0,
+                    };
+                    (name.to_string(), entry)
+                })
+                .collect(),
+        };
+        assert_eq!(
+            default_parts(&manifest),
+            [BackupPart::Index, BackupPart::Policy]
+        );
+
+        let verified = verify(manifest.clone(), bundle.clone(), &[BackupPart::Index]).unwrap();
+        assert!(verified.index.unwrap().documents.is_empty());
+        let err = verify(manifest.clone(), bundle.clone(), &[BackupPart::Policy])
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("schema version"), "{err}");
+        let err = verify(manifest, bundle, &[BackupPart::Memory])
+            .err()
+            .unwrap();
+        assert!(matches!(err, BackupError::MissingEntry(name) if name == MEMORY_DB_ENTRY));
+
+        let counts = BTreeMap::from([("documents".to_string(), 2)]);
+        assert_eq!(check_count(&counts, "documents", 2).unwrap(), 2);
+        assert_eq!(check_count(&counts, "memory_items", 5).unwrap(), 5);
+        assert!(check_count(&counts, "documents", 1).is_err());
+    }
+}
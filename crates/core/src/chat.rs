@@ -1,25 +1,40 @@
-use std::{env, time::Instant};
+use std::{convert::Infallible, env, sync::Arc, time::Instant};
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, HeaderValue, Method, StatusCode},
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     Json,
 };
+use futures_util::stream;
 use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
 // Used by utoipa's #[schema(example = json!(...))] attribute macros
 #[allow(unused_imports)]
 use serde_json::json;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 use utoipa::ToSchema;
 
-use crate::{chat_upstream::call_ollama_chat, AppState};
+use crate::{
+    ask_session,
+    chat_session::{self, ChatSession},
+    chat_upstream::{call_ollama_chat, stream_ollama_chat, ChatStream, UpstreamUsage},
+    egress::{AllowlistedClient, CALLER_CHAT},
+    guardrail::GuardrailOutcome,
+    usage::{client_id_from_headers, ChatUsage},
+    AppState,
+};
 
 #[derive(Debug, Clone)]
 pub struct ChatCfg {
     pub upstream_url: Option<String>,
     pub model: Option<String>,
-    pub client: reqwest::Client,
+    /// Lets one request at a time through to the upstream while the thermal
+    /// throttle is active and no `throttle_model` is configured.
+    throttle_queue: Arc<Semaphore>,
 }
 
 impl ChatCfg {
@@ -27,7 +42,7 @@ impl ChatCfg {
         Self {
             upstream_url,
             model,
-            client: reqwest::Client::new(),
+            throttle_queue: Arc::new(Semaphore::new(1)),
         }
     }
 
@@ -83,12 +98,17 @@ pub struct ChatMessage {
 
 #[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(deny_unknown_fields)]
-#[schema(title = "ChatResponse", example = json!({"content":"Hallo! Wie kann ich helfen?","model":"llama3.1-8b-q4"}))]
+#[schema(title = "ChatResponse", example = json!({"content":"Hallo! Wie kann ich helfen?","model":"llama3.1-8b-q4","usage":{"prompt_tokens":12,"completion_tokens":8,"source":"upstream"}}))]
 pub struct ChatResponse {
     /// Assistant message content produced by the upstream model.
     pub content: String,
     /// Model identifier reported back to clients (best effort).
     pub model: String,
+    /// Token usage of this request (upstream-reported or estimated).
+    pub usage: ChatUsage,
+    /// Session the turn was stored in, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -97,6 +117,13 @@ pub struct ChatResponse {
 pub struct ChatRequest {
     /// Sequence of messages forming the current conversation turn.
     pub messages: Vec<ChatMessage>,
+    /// Continues the stored conversation with this id; history is prepended
+    /// and the turn is appended after a successful answer.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Overrides the configured model; must be listed in the model catalog.
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -235,6 +262,174 @@ fn validate_chat_request(req: &ChatRequest) -> Result<(), ChatStubResponse> {
     Ok(())
 }
 
+/// Why a chat request cannot be sent upstream.
+struct ChatRejection {
+    status: StatusCode,
+    payload: ChatStubResponse,
+}
+
+impl ChatRejection {
+    fn new(status: StatusCode, kind: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            payload: ChatStubResponse {
+                status: kind.to_string(),
+                message: message.into(),
+            },
+        }
+    }
+}
+
+impl IntoResponse for ChatRejection {
+    fn into_response(self) -> axum::response::Response {
+        let mut headers = HeaderMap::new();
+        if self.status == StatusCode::SERVICE_UNAVAILABLE {
+            headers.insert(
+                axum::http::header::RETRY_AFTER,
+                HeaderValue::from_static(RETRY_AFTER_SECS),
+            );
+        }
+        (self.status, headers, Json(self.payload)).into_response()
+    }
+}
+
+/// Validated request with upstream, model and session history resolved.
+struct PreparedChat {
+    client: AllowlistedClient,
+    base_url: String,
+    model: String,
+    /// Messages sent upstream (history plus request).
+    messages: Vec<ChatMessage>,
+    session: Option<(String, ChatSession)>,
+    /// Held until the upstream call is done while requests are queued.
+    _throttle_permit: Option<OwnedSemaphorePermit>,
+}
+
+impl PreparedChat {
+    /// Appends the turn to the session, if any.
+    async fn persist(self, request: Vec<ChatMessage>, answer: &str) -> Option<String> {
+        let (id, mut session) = self.session?;
+        session.extend(request);
+        session.extend([ChatMessage {
+            role: ChatRole::Assistant,
+            content: answer.to_string(),
+        }]);
+        chat_session::store(&id, &session).await;
+        Some(id)
+    }
+}
+
+async fn prepare_chat(state: &AppState, req: &ChatRequest) -> Result<PreparedChat, ChatRejection> {
+    if let Err(payload) = validate_chat_request(req) {
+        return Err(ChatRejection {
+            status: StatusCode::BAD_REQUEST,
+            payload,
+        });
+    }
+    if let Some(id) = req.session_id.as_deref() {
+        ask_session::validate_session_id(id).map_err(|message| {
+            ChatRejection::new(StatusCode::BAD_REQUEST, "bad_request", message)
+        })?;
+    }
+
+    let chat_cfg = state.chat_cfg();
+    let Some(base_url) = chat_cfg.upstream_url.clone() else {
+        warn!("chat request received but no chat upstream is configured");
+        return Err(ChatRejection::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "unavailable",
+            "chat pipeline not wired yet, please configure HAUSKI_CHAT_UPSTREAM_URL",
+        ));
+    };
+    let client = state.upstream_client(CALLER_CHAT).map_err(|err| {
+        warn!(error = %err, "chat upstream client unavailable");
+        ChatRejection::new(StatusCode::SERVICE_UNAVAILABLE, "unavailable", err)
+    })?;
+    // Under the thermal throttle the default model gives way to the smaller
+    // `throttle_model`; without one, requests wait for their turn below.
+    let throttled = state.system_monitor().throttled();
+    let throttle_model = state
+        .limits()
+        .thermal
+        .throttle_model
+        .filter(|_| throttled && req.model.is_none());
+    if let Some(model) = &throttle_model {
+        info!(model = %model, "thermal throttle active, using the throttle model");
+    }
+    // In low-power mode (on battery) it gives way to `low_power_model`.
+    let low_power_model = state
+        .limits()
+        .power
+        .low_power_model
+        .filter(|_| req.model.is_none() && state.system_monitor().low_power());
+    if let (None, Some(model)) = (&throttle_model, &low_power_model) {
+        info!(model = %model, "low-power mode active, using the low-power model");
+    }
+    let model = match &req.model {
+        Some(model) if state.models().models.iter().any(|entry| &entry.id == model) => {
+            model.clone()
+        }
+        Some(model) if chat_cfg.model.as_ref() == Some(model) => model.clone(),
+        Some(model) => {
+            return Err(ChatRejection::new(
+                StatusCode::BAD_REQUEST,
+                "unknown_model",
+                format!("model '{model}' is not configured"),
+            ))
+        }
+        None => match throttle_model
+            .or(low_power_model)
+            .or_else(|| chat_cfg.model.clone())
+        {
+            Some(model) => model,
+            None => {
+                warn!("chat request received but no chat model is configured");
+                return Err(ChatRejection::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "unavailable",
+                    "missing HAUSKI_CHAT_MODEL",
+                ));
+            }
+        },
+    };
+
+    let (messages, session) = match req.session_id.as_deref() {
+        Some(id) => {
+            let session = chat_session::load(id).await;
+            let messages = session.context(&req.messages, MAX_MESSAGES);
+            (messages, Some((id.to_string(), session)))
+        }
+        None => (req.messages.clone(), None),
+    };
+
+    let throttle_permit = if throttled && state.limits().thermal.throttle_model.is_none() {
+        debug!("thermal throttle active, queueing chat request");
+        chat_cfg.throttle_queue.clone().acquire_owned().await.ok()
+    } else {
+        None
+    };
+
+    Ok(PreparedChat {
+        client,
+        base_url,
+        model,
+        messages,
+        session,
+        _throttle_permit: throttle_permit,
+    })
+}
+
+fn record_usage(state: &AppState, headers: &HeaderMap, model: &str, usage: &ChatUsage) {
+    let client_id = client_id_from_headers(headers);
+    let price = state
+        .models()
+        .models
+        .iter()
+        .find(|entry| entry.id == model)
+        .and_then(|entry| entry.cost);
+    state.usage().record(model, &client_id, usage, price);
+}
+
 // Hinweis: Wir dokumentieren die `Retry-After`-Header für 503-Antworten.
 #[utoipa::path(
     post,
@@ -248,7 +443,12 @@ fn validate_chat_request(req: &ChatRequest) -> Result<(), ChatStubResponse> {
         ),
         (
             status = 400,
-            description = "Invalid chat request payload",
+            description = "Invalid chat request payload or unknown model",
+            body = ChatStubResponse
+        ),
+        (
+            status = 422,
+            description = "Upstream response blocked by the output guardrail",
             body = ChatStubResponse
         ),
         (
@@ -274,73 +474,343 @@ fn validate_chat_request(req: &ChatRequest) -> Result<(), ChatStubResponse> {
 )]
 pub async fn chat_handler(
     State(state): State<AppState>,
+    request_headers: HeaderMap,
     Json(chat_request): Json<ChatRequest>,
 ) -> axum::response::Response {
     let started = Instant::now();
 
-    if let Err(payload) = validate_chat_request(&chat_request) {
-        let status = StatusCode::BAD_REQUEST;
-        state.record_http_observation(Method::POST, "/v1/chat", status, started);
-        return (status, Json(payload)).into_response();
-    }
-
-    let chat_cfg = state.chat_cfg();
-    if let Some(base_url) = chat_cfg.upstream_url.clone() {
-        if let Some(model) = chat_cfg.model.clone() {
-            let client = chat_cfg.client.clone();
-
-            match call_ollama_chat(&client, &base_url, &model, &chat_request.messages).await {
-                Ok(content) => {
-                    let status = StatusCode::OK;
-                    state.record_http_observation(Method::POST, "/v1/chat", status, started);
-                    debug!(
-                        base_url = %base_url,
-                        status = %status,
-                        model = %model,
-                        "chat upstream succeeded"
-                    );
-                    return (status, Json(ChatResponse { content, model })).into_response();
+    let prepared = match prepare_chat(&state, &chat_request).await {
+        Ok(prepared) => prepared,
+        Err(rejection) => {
+            state.record_http_observation(Method::POST, "/v1/chat", rejection.status, started);
+            return rejection.into_response();
+        }
+    };
+    let base_url = prepared.base_url.clone();
+    let model = prepared.model.clone();
+
+    match call_ollama_chat(&prepared.client, &base_url, &model, &prepared.messages).await {
+        Ok(completion) => {
+            // Tokens fallen auch an, wenn der Guardrail die Antwort später verwirft.
+            let usage =
+                ChatUsage::resolve(completion.usage, &prepared.messages, &completion.content);
+            record_usage(&state, &request_headers, &model, &usage);
+
+            // Post-generation guardrail: nothing leaves the server unfiltered.
+            let content = match state.guardrail().apply(&completion.content) {
+                GuardrailOutcome::Allowed { content, applied } => {
+                    if !applied.is_empty() {
+                        debug!(rules = ?applied, "chat guardrail modified response");
+                    }
+                    content
                 }
-                Err(err) => {
-                    let status = StatusCode::BAD_GATEWAY;
+                GuardrailOutcome::Blocked { rule } => {
+                    let status = StatusCode::UNPROCESSABLE_ENTITY;
                     state.record_http_observation(Method::POST, "/v1/chat", status, started);
-                    debug!(base_url = %base_url, error = %err, "chat upstream failed");
+                    warn!(rule = %rule, model = %model, "chat guardrail blocked response");
                     let payload = ChatStubResponse {
-                        status: "upstream_error".to_string(),
-                        message: format!("chat upstream failed: {err}"),
+                        status: "blocked".to_string(),
+                        message: format!("response blocked by guardrail rule '{rule}'"),
                     };
                     return (status, Json(payload)).into_response();
                 }
-            }
+            };
+            let session_id = prepared.persist(chat_request.messages, &content).await;
+            let status = StatusCode::OK;
+            state.record_http_observation(Method::POST, "/v1/chat", status, started);
+            debug!(
+                base_url = %base_url,
+                status = %status,
+                model = %model,
+                "chat upstream succeeded"
+            );
+            (
+                status,
+                Json(ChatResponse {
+                    content,
+                    model,
+                    usage,
+                    session_id,
+                }),
+            )
+                .into_response()
         }
+        Err(err) => {
+            let status = StatusCode::BAD_GATEWAY;
+            state.record_http_observation(Method::POST, "/v1/chat", status, started);
+            debug!(base_url = %base_url, error = %err, "chat upstream failed");
+            let payload = ChatStubResponse {
+                status: "upstream_error".to_string(),
+                message: format!("chat upstream failed: {err}"),
+            };
+            (status, Json(payload)).into_response()
+        }
+    }
+}
 
-        warn!("chat request received but no chat model is configured");
-        let status = StatusCode::SERVICE_UNAVAILABLE;
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            axum::http::header::RETRY_AFTER,
-            HeaderValue::from_static(RETRY_AFTER_SECS),
-        );
-        state.record_http_observation(Method::POST, "/v1/chat", status, started);
-        let payload = ChatStubResponse {
-            status: "unavailable".to_string(),
-            message: "missing HAUSKI_CHAT_MODEL".to_string(),
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(title = "ChatStreamDone", example = json!({"model":"llama3.1-8b-q4","usage":{"prompt_tokens":12,"completion_tokens":8,"source":"upstream"},"session_id":"cli-1"}))]
+pub struct ChatStreamDone {
+    pub model: String,
+    pub usage: ChatUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/chat/stream",
+    request_body = ChatRequest,
+    responses(
+        (
+            status = 200,
+            description = "Answer as SSE: `delta` events (`{\"content\":\"...\"}`), then `done` (ChatStreamDone) or `error` (ChatStubResponse)",
+            content_type = "text/event-stream",
+            body = ChatStreamDone
+        ),
+        (status = 400, description = "Invalid chat request payload or unknown model", body = ChatStubResponse),
+        (status = 502, description = "Configured chat upstream returned an error", body = ChatStubResponse),
+        (status = 503, description = "Chat endpoint not currently available", body = ChatStubResponse)
+    ),
+    tag = "core"
+)]
+pub async fn chat_stream_handler(
+    State(state): State<AppState>,
+    request_headers: HeaderMap,
+    Json(chat_request): Json<ChatRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let path = "/v1/chat/stream";
+
+    let prepared = match prepare_chat(&state, &chat_request).await {
+        Ok(prepared) => prepared,
+        Err(rejection) => {
+            state.record_http_observation(Method::POST, path, rejection.status, started);
+            return rejection.into_response();
+        }
+    };
+    let upstream = match stream_ollama_chat(
+        &prepared.client,
+        &prepared.base_url,
+        &prepared.model,
+        &prepared.messages,
+    )
+    .await
+    {
+        Ok(upstream) => upstream,
+        Err(err) => {
+            let status = StatusCode::BAD_GATEWAY;
+            state.record_http_observation(Method::POST, path, status, started);
+            debug!(error = %err, "chat upstream stream failed");
+            let payload = ChatStubResponse {
+                status: "upstream_error".to_string(),
+                message: format!("chat upstream failed: {err}"),
+            };
+            return (status, Json(payload)).into_response();
+        }
+    };
+    state.record_http_observation(Method::POST, path, StatusCode::OK, started);
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(relay_stream(
+        state,
+        request_headers,
+        prepared,
+        chat_request.messages,
+        upstream,
+        tx,
+    ));
+    let events = stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        Some((Ok::<_, Infallible>(event), rx))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+const STREAM_BUFFER: usize = 32;
+
+/// Forwards upstream pieces as `delta` events. With guardrail rules, text is
+/// released line by line after filtering, but held back while a multi-line
+/// match (a rule's `open`) is still pending; a `block` rule ends the stream
+/// with an `error` event and the session is left untouched.
+async fn relay_stream(
+    state: AppState,
+    headers: HeaderMap,
+    prepared: PreparedChat,
+    request: Vec<ChatMessage>,
+    mut upstream: ChatStream,
+    tx: mpsc::Sender<Event>,
+) {
+    let guardrail = state.guardrail();
+    let filtered = guardrail.rule_count() > 0;
+    let mut raw = String::new();
+    let mut delivered = String::new();
+    let mut pending = String::new();
+    let mut upstream_usage = UpstreamUsage::default();
+
+    loop {
+        let delta = match upstream.next().await {
+            Ok(Some(delta)) => delta,
+            Ok(None) => break,
+            Err(err) => {
+                debug!(error = %err, "chat upstream stream broke off");
+                let payload = ChatStubResponse {
+                    status: "upstream_error".to_string(),
+                    message: format!("chat upstream failed: {err}"),
+                };
+                let _ = tx.send(json_event("error", &payload)).await;
+                return;
+            }
+        };
+        if let Some(usage) = delta.usage {
+            upstream_usage = usage;
+        }
+        raw.push_str(&delta.content);
+        let ready = if filtered {
+            pending.push_str(&delta.content);
+            match pending.rfind('\n') {
+                Some(pos) if !guardrail.holds_back(&pending[..=pos]) => {
+                    pending.drain(..=pos).collect()
+                }
+                _ => continue,
+            }
+        } else {
+            delta.content
         };
-        return (status, headers, Json(payload)).into_response();
+        if !send_guarded(&state, &tx, filtered, ready, &mut delivered).await {
+            return;
+        }
+    }
+    if !send_guarded(&state, &tx, filtered, pending, &mut delivered).await {
+        return;
+    }
+
+    let usage = ChatUsage::resolve(upstream_usage, &prepared.messages, &raw);
+    record_usage(&state, &headers, &prepared.model, &usage);
+    let model = prepared.model.clone();
+    let session_id = prepared.persist(request, &delivered).await;
+    let done = ChatStreamDone {
+        model,
+        usage,
+        session_id,
+    };
+    let _ = tx.send(json_event("done", &done)).await;
+}
+
+/// Sends `text` (filtered if `filtered`) as `delta`; `false` ends the stream.
+async fn send_guarded(
+    state: &AppState,
+    tx: &mpsc::Sender<Event>,
+    filtered: bool,
+    text: String,
+    delivered: &mut String,
+) -> bool {
+    let text = if filtered {
+        match state.guardrail().apply(&text) {
+            GuardrailOutcome::Allowed { content, .. } => content,
+            GuardrailOutcome::Blocked { rule } => {
+                warn!(rule = %rule, "chat guardrail blocked streamed response");
+                let payload = ChatStubResponse {
+                    status: "blocked".to_string(),
+                    message: format!("response blocked by guardrail rule '{rule}'"),
+                };
+                let _ = tx.send(json_event("error", &payload)).await;
+                return false;
+            }
+        }
+    } else {
+        text
+    };
+    if text.is_empty() {
+        return true;
+    }
+    delivered.push_str(&text);
+    tx.send(json_event("delta", &json!({ "content": text })))
+        .await
+        .is_ok()
+}
+
+fn json_event(name: &str, payload: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(payload)
+        .unwrap_or_else(|_| Event::default().event(name))
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(title = "ChatSessionResponse")]
+pub struct ChatSessionResponse {
+    pub session_id: String,
+    /// Stored history, oldest first.
+    pub messages: Vec<ChatMessage>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/chat/sessions/{id}",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Stored chat history (empty for unknown sessions)", body = ChatSessionResponse),
+        (status = 400, description = "Invalid session id", body = ChatStubResponse)
+    ),
+    tag = "core"
+)]
+pub async fn chat_session_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let path = "/v1/chat/sessions/{id}";
+    if let Err(message) = ask_session::validate_session_id(&id) {
+        let rejection = ChatRejection::new(StatusCode::BAD_REQUEST, "bad_request", message);
+        state.record_http_observation(Method::GET, path, rejection.status, started);
+        return rejection.into_response();
     }
+    let session = chat_session::load(&id).await;
+    state.record_http_observation(Method::GET, path, StatusCode::OK, started);
+    Json(ChatSessionResponse {
+        session_id: id,
+        messages: session.messages,
+    })
+    .into_response()
+}
 
-    warn!("chat request received but no chat upstream is configured");
-    let status = StatusCode::SERVICE_UNAVAILABLE;
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        axum::http::header::RETRY_AFTER,
-        HeaderValue::from_static(RETRY_AFTER_SECS),
-    );
-    state.record_http_observation(Method::POST, "/v1/chat", status, started);
-    let payload = ChatStubResponse {
-        status: "unavailable".to_string(),
-        message: "chat pipeline not wired yet, please configure HAUSKI_CHAT_UPSTREAM_URL"
-            .to_string(),
+#[utoipa::path(
+    delete,
+    path = "/v1/chat/sessions/{id}",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 204, description = "Session deleted (or did not exist)"),
+        (status = 400, description = "Invalid session id", body = ChatStubResponse),
+        (status = 500, description = "Memory store error", body = ChatStubResponse)
+    ),
+    tag = "core"
+)]
+pub async fn chat_session_delete_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let path = "/v1/chat/sessions/{id}";
+    let response = match ask_session::validate_session_id(&id) {
+        Err(message) => {
+            ChatRejection::new(StatusCode::BAD_REQUEST, "bad_request", message).into_response()
+        }
+        Ok(()) => match chat_session::delete(&id).await {
+            Ok(_) => StatusCode::NO_CONTENT.into_response(),
+            Err(err) => {
+                warn!(session_id = %id, error = ?err, "chat session delete failed");
+                ChatRejection::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal_error",
+                    "chat session could not be deleted",
+                )
+                .into_response()
+            }
+        },
     };
-    (status, headers, Json(payload)).into_response()
+    state.record_http_observation(Method::DELETE, path, response.status(), started);
+    response
 }
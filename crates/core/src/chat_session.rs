@@ -0,0 +1,141 @@
+//! Gesprächsverlauf für `/v1/chat` (Multi-Turn).
+//!
+//! Mit `session_id` stellt der Server den gespeicherten Verlauf den neuen
+//! Nachrichten voran und hängt nach erfolgreicher Antwort Anfrage und Antwort
+//! an. Clients (z. B. `hauski chat`) müssen den Verlauf daher nicht selbst
+//! mitschicken und können eine Sitzung später fortsetzen.
+//!
+//! Jede Session liegt als JSON unter `chat.session:<id>` im Memory-Store;
+//! `GET`/`DELETE /v1/chat/sessions/{id}` lesen bzw. löschen sie.
+//!
+//! Konfiguration:
+//!   HAUSKI_CHAT_SESSION_TTL_SEC (Default 86400; 0 = ohne TTL)
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatMessage, ChatRole};
+
+/// Prefix of the memory keys holding chat transcripts.
+pub const SESSION_KEY_PREFIX: &str = "chat.session:";
+/// Maximum number of messages kept per session (oldest are dropped).
+pub const MAX_SESSION_MESSAGES: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ChatSession {
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+}
+
+impl ChatSession {
+    pub fn extend(&mut self, messages: impl IntoIterator<Item = ChatMessage>) {
+        self.messages.extend(messages);
+        if self.messages.len() > MAX_SESSION_MESSAGES {
+            let overflow = self.messages.len() - MAX_SESSION_MESSAGES;
+            self.messages.drain(..overflow);
+        }
+    }
+
+    /// History followed by `messages`, cut to the newest `limit` messages.
+    /// System messages of the history are kept ahead of the cut.
+    pub fn context(&self, messages: &[ChatMessage], limit: usize) -> Vec<ChatMessage> {
+        let (system, rest): (Vec<&ChatMessage>, Vec<&ChatMessage>) = self
+            .messages
+            .iter()
+            .chain(messages)
+            .partition(|message| matches!(message.role, ChatRole::System));
+        let keep = limit.saturating_sub(system.len()).min(rest.len());
+        system
+            .into_iter()
+            .chain(rest[rest.len() - keep..].iter().copied())
+            .cloned()
+            .collect()
+    }
+}
+
+fn session_key(id: &str) -> String {
+    format!("{SESSION_KEY_PREFIX}{id}")
+}
+
+/// Loads a session from memory; unknown or unreadable sessions start empty.
+pub async fn load(id: &str) -> ChatSession {
+    let Some(store) = hauski_memory::try_global() else {
+        return ChatSession::default();
+    };
+    match store.get(session_key(id)).await {
+        Ok(Some(item)) => serde_json::from_slice(&item.value).unwrap_or_else(|err| {
+            tracing::warn!(session_id = id, error = %err, "chat session unreadable – starting fresh");
+            ChatSession::default()
+        }),
+        Ok(None) => ChatSession::default(),
+        Err(err) => {
+            tracing::warn!(session_id = id, error = ?err, "chat session load failed");
+            ChatSession::default()
+        }
+    }
+}
+
+/// Persists the session; failures are logged, the answer is still served.
+pub async fn store(id: &str, session: &ChatSession) {
+    let Some(store) = hauski_memory::try_global() else {
+        tracing::warn!(
+            session_id = id,
+            "memory not initialized – chat session not stored"
+        );
+        return;
+    };
+    let value = match serde_json::to_vec(session) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(session_id = id, error = %err, "chat session serialization failed");
+            return;
+        }
+    };
+    let ttl = match crate::env_u64("HAUSKI_CHAT_SESSION_TTL_SEC", 86_400) {
+        0 => hauski_memory::TtlUpdate::Clear,
+        secs => hauski_memory::TtlUpdate::Set(i64::try_from(secs).unwrap_or(i64::MAX)),
+    };
+    if let Err(err) = store.set(session_key(id), value, ttl, None).await {
+        tracing::warn!(session_id = id, error = ?err, "chat session store failed");
+    }
+}
+
+/// Deletes the session; returns whether it existed.
+pub async fn delete(id: &str) -> anyhow::Result<bool> {
+    match hauski_memory::try_global() {
+        Some(store) => store.evict(session_key(id)).await,
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: ChatRole, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn context_keeps_system_prompt_and_newest_messages() {
+        let mut session = ChatSession::default();
+        session.extend([message(ChatRole::System, "sei knapp")]);
+        session.extend((0..10).map(|i| message(ChatRole::User, &format!("m{i}"))));
+
+        let context = session.context(&[message(ChatRole::User, "neu")], 4);
+        let contents: Vec<&str> = context.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["sei knapp", "m8", "m9", "neu"]);
+    }
+
+    #[test]
+    fn session_keeps_bounded_history() {
+        let mut session = ChatSession::default();
+        session.extend(
+            (0..MAX_SESSION_MESSAGES + 3).map(|i| message(ChatRole::User, &format!("m{i}"))),
+        );
+        assert_eq!(session.messages.len(), MAX_SESSION_MESSAGES);
+        assert_eq!(session.messages[0].content, "m3");
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::{anyhow, Context, Result};
-use reqwest::Client;
+use reqwest::Response;
 use serde::{Deserialize, Serialize};
 
-use crate::chat::ChatMessage;
+use crate::{chat::ChatMessage, egress::AllowlistedClient};
 
 #[derive(Debug, Serialize)]
 struct OllamaChatRequest<'a> {
@@ -15,6 +15,18 @@ struct OllamaChatRequest<'a> {
 #[derive(Debug, Deserialize)]
 struct OllamaChatResponse {
     message: Option<OllamaMessage>,
+    /// Anzahl der Prompt-Tokens (Ollama: `prompt_eval_count`).
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    /// Anzahl der generierten Tokens (Ollama: `eval_count`).
+    #[serde(default)]
+    eval_count: Option<u64>,
+    /// Letzte Zeile eines Streams.
+    #[serde(default)]
+    done: bool,
+    /// Fehlermeldung mitten im Stream.
+    #[serde(default)]
+    error: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,40 +34,156 @@ struct OllamaMessage {
     content: String,
 }
 
-/// Call an Ollama-compatible `/api/chat` endpoint and return the first message.
-pub async fn call_ollama_chat(
-    client: &Client,
+/// Token counts as reported by the upstream, if available.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpstreamUsage {
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatCompletion {
+    pub content: String,
+    pub usage: UpstreamUsage,
+}
+
+async fn post_chat(
+    client: &AllowlistedClient,
     base_url: &str,
     model: &str,
     messages: &[ChatMessage],
-) -> Result<String> {
+    stream: bool,
+) -> Result<Response> {
     let url = format!("{}/api/chat", base_url.trim_end_matches('/'));
     let request = OllamaChatRequest {
         model,
         messages,
-        stream: Some(false),
+        stream: Some(stream),
     };
 
-    let response = client
+    let builder = client
         .post(&url)
-        .json(&request)
-        .send()
+        .map_err(|err| anyhow!("POST {url}: {err}"))?
+        .json(&request);
+    let response = client
+        .send(builder)
         .await
-        .with_context(|| format!("POST {url}"))?;
+        .map_err(|err| anyhow!("POST {url}: {err}"))?;
 
     if !response.status().is_success() {
         return Err(anyhow!("upstream status {}", response.status()));
     }
+    Ok(response)
+}
+
+/// Call an Ollama-compatible `/api/chat` endpoint and return the first message.
+pub async fn call_ollama_chat(
+    client: &AllowlistedClient,
+    base_url: &str,
+    model: &str,
+    messages: &[ChatMessage],
+) -> Result<ChatCompletion> {
+    let response = post_chat(client, base_url, model, messages, false).await?;
 
     let parsed: OllamaChatResponse = response
         .json()
         .await
         .context("parse upstream json response")?;
+    let usage = UpstreamUsage {
+        prompt_tokens: parsed.prompt_eval_count,
+        completion_tokens: parsed.eval_count,
+    };
     let reply = parsed
         .message
         .map(|m| m.content)
         .filter(|content| !content.is_empty())
         .unwrap_or_else(|| "(leer)".to_string());
 
-    Ok(reply)
+    Ok(ChatCompletion {
+        content: reply,
+        usage,
+    })
+}
+
+/// Piece of a streamed completion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatDelta {
+    pub content: String,
+    /// Set on the final piece.
+    pub usage: Option<UpstreamUsage>,
+}
+
+/// Streamed Ollama completion (`stream: true`, one JSON object per line).
+pub struct ChatStream {
+    response: Response,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+/// Start a streamed completion at an Ollama-compatible `/api/chat` endpoint.
+pub async fn stream_ollama_chat(
+    client: &AllowlistedClient,
+    base_url: &str,
+    model: &str,
+    messages: &[ChatMessage],
+) -> Result<ChatStream> {
+    let response = post_chat(client, base_url, model, messages, true).await?;
+    Ok(ChatStream {
+        response,
+        buffer: Vec::new(),
+        finished: false,
+    })
+}
+
+impl ChatStream {
+    /// Next piece of the answer; `None` once the upstream reported `done`
+    /// or closed the connection.
+    pub async fn next(&mut self) -> Result<Option<ChatDelta>> {
+        loop {
+            if self.finished {
+                return Ok(None);
+            }
+            let line = match self.buffer.iter().position(|b| *b == b'\n') {
+                Some(pos) => self.buffer.drain(..=pos).collect::<Vec<u8>>(),
+                None => match self
+                    .response
+                    .chunk()
+                    .await
+                    .context("read upstream stream")?
+                {
+                    Some(bytes) => {
+                        self.buffer.extend_from_slice(&bytes);
+                        continue;
+                    }
+                    None => {
+                        self.finished = true;
+                        std::mem::take(&mut self.buffer)
+                    }
+                },
+            };
+            if let Some(delta) = self.parse_line(&line)? {
+                return Ok(Some(delta));
+            }
+        }
+    }
+
+    fn parse_line(&mut self, line: &[u8]) -> Result<Option<ChatDelta>> {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+        let parsed: OllamaChatResponse =
+            serde_json::from_slice(line).context("parse upstream stream line")?;
+        if let Some(error) = parsed.error {
+            return Err(anyhow!("upstream error: {error}"));
+        }
+        let usage = parsed.done.then_some(UpstreamUsage {
+            prompt_tokens: parsed.prompt_eval_count,
+            completion_tokens: parsed.eval_count,
+        });
+        self.finished |= parsed.done;
+        Ok(Some(ChatDelta {
+            content: parsed.message.map(|m| m.content).unwrap_or_default(),
+            usage,
+        }))
+    }
 }
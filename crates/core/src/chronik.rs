@@ -0,0 +1,246 @@
+//! Anbindung des Ereignisbusses `hauski-chronik`: Index-Mutationen und
+//! Entscheidungen (indexd), Job-Ereignisse und periodische System-Signale
+//! landen als Envelopes auf einem gemeinsamen Bus.
+//!
+//! Quellen und Arten:
+//!   indexd – index.upserted, index.forgotten, index.retention_changed,
+//!            index.policy_reloaded, decision.recorded, decision.outcome
+//!   jobs   – job.queued, job.started, job.finished
+//!   system – system.signals (alle `HAUSKI_CHRONIK_SIGNALS_SEC` Sekunden),
+//!            system.cpu_high, system.memory_pressure_high, system.gpu_throttle,
+//!            system.disk_low (beim Über- und Unterschreiten der Schwellen)
+//!   policy – policy.decision, policy.feedback, policy.reset, policy.escalation
+//!   backup – backup.created, backup.restored
+//!
+//! Lesen: `GET /chronik/events` (letzte Ereignisse) und `GET /chronik/stream`
+//! (SSE), beide mit Filter `kind` (kommagetrennt, `job` umfasst `job.*`) und
+//! `source`.
+//!
+//! Konfiguration:
+//!   HAUSKI_CHRONIK_CAPACITY    (Default 1024; Ereignisse im Speicher)
+//!   HAUSKI_CHRONIK_DIR         (ohne Default; gesetzt = Persistenz als `<dir>/YYYY-MM.jsonl`)
+//!   HAUSKI_CHRONIK_SIGNALS_SEC (Default 60; 0 = keine System-Signale)
+
+use std::{
+    convert::Infallible,
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Query, State},
+    http::{Method, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures_util::stream;
+use hauski_chronik::{Bus, Envelope, EventFilter, JsonlSink};
+use hauski_indexd::IndexEvent;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{system::SignalEvent, AppState, AppStateInner};
+
+pub(crate) const SOURCE_INDEX: &str = "indexd";
+pub(crate) const SOURCE_JOBS: &str = "jobs";
+pub(crate) const SOURCE_SYSTEM: &str = "system";
+pub(crate) const SOURCE_POLICY: &str = "policy";
+pub(crate) const SOURCE_BACKUP: &str = "backup";
+
+const DEFAULT_CAPACITY: u64 = 1024;
+const DEFAULT_SIGNALS_SEC: u64 = 60;
+const DEFAULT_LIMIT: usize = 100;
+
+pub(crate) fn load_from_env() -> Bus {
+    let capacity = crate::env_u64("HAUSKI_CHRONIK_CAPACITY", DEFAULT_CAPACITY).max(1) as usize;
+    let bus = Bus::new(capacity);
+    match std::env::var("HAUSKI_CHRONIK_DIR")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(dir) => {
+            tracing::info!(%dir, "chronik events are persisted");
+            bus.with_sink(Arc::new(JsonlSink::new(dir)))
+        }
+        None => bus,
+    }
+}
+
+/// Connects the index and the system monitor's threshold events to the bus
+/// and starts the system signal sampler.
+pub(crate) fn attach(state: &AppState) {
+    let bus = state.chronik();
+    state.index().on_event(Arc::new(
+        move |event: &IndexEvent| match serde_json::to_value(event) {
+            Ok(payload) => {
+                bus.emit(SOURCE_INDEX, event.kind(), payload);
+            }
+            Err(err) => tracing::warn!(error = %err, "chronik: index event serialization failed"),
+        },
+    ));
+    let bus = state.chronik();
+    state.system_monitor().on_event(Arc::new(
+        move |event: &SignalEvent| match serde_json::to_value(event) {
+            Ok(payload) => {
+                bus.emit(SOURCE_SYSTEM, event.signal.event_kind(), payload);
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "chronik: system event serialization failed")
+            }
+        },
+    ));
+
+    let interval = crate::env_u64("HAUSKI_CHRONIK_SIGNALS_SEC", DEFAULT_SIGNALS_SEC);
+    if interval > 0 {
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(sample_signals(
+                Arc::downgrade(&state.0),
+                Duration::from_secs(interval),
+            ));
+        }
+    }
+}
+
+/// Publishes the system signals every `interval` until the state is dropped.
+async fn sample_signals(state: Weak<AppStateInner>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(inner) = state.upgrade() else {
+            return;
+        };
+        let state = AppState(inner);
+        if let Ok(signals) = state.system_monitor().get_signals() {
+            publish(&state, SOURCE_SYSTEM, "system.signals", &signals);
+        }
+    }
+}
+
+/// Serializes `payload` and publishes it; errors are logged.
+pub(crate) fn publish(state: &AppState, source: &str, kind: &str, payload: &impl Serialize) {
+    match serde_json::to_value(payload) {
+        Ok(payload) => {
+            state.chronik().emit(source, kind, payload);
+        }
+        Err(err) => tracing::warn!(kind, error = %err, "chronik: event serialization failed"),
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChronikQuery {
+    /// Comma-separated kinds; `job` also matches `job.started` etc.
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Maximum number of events (`/chronik/events` only).
+    #[serde(default)]
+    #[param(default = 100)]
+    pub limit: Option<usize>,
+}
+
+impl ChronikQuery {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            kinds: self
+                .kind
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|kind| !kind.is_empty())
+                .map(str::to_string)
+                .collect(),
+            source: self
+                .source
+                .clone()
+                .filter(|source| !source.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "ChronikEventsResponse")]
+pub struct ChronikEventsResponse {
+    /// True if events are also written to `HAUSKI_CHRONIK_DIR`.
+    pub persistent: bool,
+    /// Oldest first.
+    pub events: Vec<Envelope>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/chronik/events",
+    tag = "core",
+    params(ChronikQuery),
+    responses((status = 200, description = "Most recent events on the chronik bus", body = ChronikEventsResponse))
+)]
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ChronikQuery>,
+) -> Json<ChronikEventsResponse> {
+    let started = Instant::now();
+    let bus = state.chronik();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    let events = bus
+        .recent(&query.filter(), limit)
+        .into_iter()
+        .map(|envelope| (*envelope).clone())
+        .collect();
+    state.record_http_observation(Method::GET, "/chronik/events", StatusCode::OK, started);
+    Json(ChronikEventsResponse {
+        persistent: bus.is_persistent(),
+        events,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/chronik/stream",
+    tag = "core",
+    params(ChronikQuery),
+    responses((status = 200, description = "Live events as SSE (`event` = kind, `id` = envelope id)", content_type = "text/event-stream", body = Envelope))
+)]
+pub async fn stream_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ChronikQuery>,
+) -> Response {
+    let started = Instant::now();
+    let subscriber = state.chronik().subscribe(query.filter());
+    let events = stream::unfold(subscriber, |mut subscriber| async move {
+        let envelope = subscriber.recv().await?;
+        let event = Event::default()
+            .event(envelope.kind.as_str())
+            .id(envelope.id.as_str());
+        let event = event.json_data(&*envelope).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "chronik: SSE serialization failed");
+            Event::default().event(envelope.kind.as_str())
+        });
+        Some((Ok::<_, Infallible>(event), subscriber))
+    });
+    state.record_http_observation(Method::GET, "/chronik/stream", StatusCode::OK, started);
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_builds_filter_from_comma_separated_kinds() {
+        let query = ChronikQuery {
+            kind: Some("job, index.upserted,".into()),
+            source: Some(" ".into()),
+            limit: None,
+        };
+        let filter = query.filter();
+        assert_eq!(filter.kinds, ["job", "index.upserted"]);
+        assert!(filter.source.is_none());
+        assert!(ChronikQuery::default().filter().kinds.is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+//! Platzhalter `${VAR}` / `${VAR:-default}` in YAML-Konfigurationen.
+//!
+//! Ersetzt wird erst nach dem Parsen und nur in String-Werten: eingesetzte
+//! Werte können die Struktur des Dokuments nicht verändern, Kommentare und
+//! Schlüssel bleiben unberührt.
+
+use super::migrate::MigrationError;
+use super::secrets::{lookup_secret, resolve_secrets, SecretError};
+use serde::de::DeserializeOwned;
+use serde_yaml_ng::Value;
+use std::{collections::HashSet, env, path::Path};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InterpolationError {
+    #[error("unterminated placeholder in '{0}'")]
+    Unterminated(String),
+    #[error("invalid variable name '{name}' in '{value}'")]
+    InvalidName { name: String, value: String },
+    #[error("unknown type '{kind}' in '{value}' (int, float, bool)")]
+    UnknownType { kind: String, value: String },
+    #[error("'{found}' for '{value}' is not a valid {kind}")]
+    Type {
+        kind: &'static str,
+        found: String,
+        value: String,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum YamlError {
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml_ng::Error),
+    #[error(transparent)]
+    Interpolation(#[from] InterpolationError),
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+}
+
+/// Replaces placeholders in `input`.
+///
+/// `${VAR:-default}` uses `default` if `VAR` is unset or empty; `${VAR}` without
+/// a default becomes empty and `VAR` is pushed to `unset`. `$${` is a literal
+/// `${`; any other `$` is kept as is. A type (`${VAR:int}`, see
+/// [`interpolate_value`]) is checked but has no effect on text.
+pub fn interpolate(
+    input: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    unset: &mut Vec<String>,
+) -> Result<String, InterpolationError> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| InterpolationError::Unterminated(input.to_string()))?;
+            let Placeholder { name, default, .. } = Placeholder::parse(&after[..end], input)?;
+            match (lookup(name).filter(|value| !value.is_empty()), default) {
+                (Some(value), _) => out.push_str(&value),
+                (None, Some(default)) => out.push_str(default),
+                (None, None) => {
+                    if lookup(name).is_none() {
+                        unset.push(name.to_string());
+                    }
+                }
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Interpolates all string values below `value`.
+///
+/// Values stay strings, so a token `${TOKEN}` of `12345` is still text. A
+/// value that is a single placeholder may name a type instead:
+/// `port: ${PORT:int:-8080}` becomes a number, `${FLAG:bool}` a boolean,
+/// `${RATIO:float}` a float. A single placeholder that comes out empty
+/// becomes `null`, so an unset `token: ${TOKEN}` means no token.
+pub fn interpolate_value(
+    value: &mut Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+    unset: &mut Vec<String>,
+) -> Result<(), InterpolationError> {
+    match value {
+        Value::String(text) if text.contains('$') => {
+            let whole = single_placeholder(text);
+            let kind = match whole {
+                Some(inner) => Placeholder::parse(inner, text)?.kind,
+                None => None,
+            };
+            let replaced = interpolate(text, lookup, unset)?;
+            *value = match kind {
+                _ if whole.is_some() && replaced.is_empty() => Value::Null,
+                Some(kind) => kind.convert(&replaced, text)?,
+                None => Value::String(replaced),
+            };
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                interpolate_value(item, lookup, unset)?;
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                interpolate_value(item, lookup, unset)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_value(&mut tagged.value, lookup, unset)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parses `text`, interpolates it from the environment and deserializes it;
+/// also returns the variables that were referenced but unset.
+pub fn parse_yaml<T: DeserializeOwned>(text: &str) -> Result<(T, Vec<String>), YamlError> {
+    resolve(serde_yaml_ng::from_str(text)?)
+}
+
+/// Interpolates a parsed document from the environment, resolves its
+/// `secret://` references and deserializes it.
+pub(crate) fn resolve<T: DeserializeOwned>(
+    mut value: Value,
+) -> Result<(T, Vec<String>), YamlError> {
+    let mut unset = Vec::new();
+    interpolate_value(&mut value, &|name| env::var(name).ok(), &mut unset)?;
+    resolve_secrets(&mut value, &lookup_secret)?;
+    let mut seen = HashSet::new();
+    unset.retain(|variable| seen.insert(variable.clone()));
+    Ok((serde_yaml_ng::from_value(value)?, unset))
+}
+
+/// [`parse_yaml`] for optional policy files; unset variables are logged.
+pub fn from_yaml_str<T: DeserializeOwned>(text: &str, origin: &Path) -> Result<T, YamlError> {
+    let (parsed, unset) = parse_yaml(text)?;
+    for variable in unset {
+        tracing::warn!(
+            origin = %origin.display(),
+            %variable,
+            "environment variable referenced in config is not set"
+        );
+    }
+    Ok(parsed)
+}
+
+/// `${VAR} in <what> YAML at <path> is not set`, the message for strict validation.
+pub(crate) fn unset_message(variable: &str, what: &str, path: &Path) -> String {
+    format!("${{{variable}}} in {what} YAML at {path:?} is not set")
+}
+
+/// Target type of a single placeholder (`${VAR:int}`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Float,
+    Bool,
+}
+
+impl Kind {
+    fn convert(self, found: &str, value: &str) -> Result<Value, InterpolationError> {
+        let found = found.trim();
+        let converted = match self {
+            Kind::Int => found.parse::<i64>().ok().map(Value::from),
+            Kind::Float => found.parse::<f64>().ok().map(Value::from),
+            Kind::Bool => found.parse::<bool>().ok().map(Value::Bool),
+        };
+        converted.ok_or_else(|| InterpolationError::Type {
+            kind: match self {
+                Kind::Int => "int",
+                Kind::Float => "float",
+                Kind::Bool => "bool",
+            },
+            found: found.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// The inside of `${NAME[:TYPE][:-DEFAULT]}`.
+struct Placeholder<'a> {
+    name: &'a str,
+    kind: Option<Kind>,
+    default: Option<&'a str>,
+}
+
+impl<'a> Placeholder<'a> {
+    fn parse(inner: &'a str, value: &str) -> Result<Self, InterpolationError> {
+        let (spec, default) = match inner.split_once(":-") {
+            Some((spec, default)) => (spec, Some(default)),
+            None => (inner, None),
+        };
+        let (name, kind) = match spec.split_once(':') {
+            Some((name, "int")) => (name, Some(Kind::Int)),
+            Some((name, "float")) => (name, Some(Kind::Float)),
+            Some((name, "bool")) => (name, Some(Kind::Bool)),
+            Some((_, kind)) => {
+                return Err(InterpolationError::UnknownType {
+                    kind: kind.to_string(),
+                    value: value.to_string(),
+                })
+            }
+            None => (spec, None),
+        };
+        if !is_var_name(name) {
+            return Err(InterpolationError::InvalidName {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+        Ok(Self {
+            name,
+            kind,
+            default,
+        })
+    }
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// The inside of `text` if it is exactly one placeholder.
+fn single_placeholder(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix("${")?;
+    (inner.find('}') == Some(inner.len() - 1)).then(|| &inner[..inner.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("db.local".into()),
+            "PORT" => Some("5432".into()),
+            "TOKEN" => Some("12345".into()),
+            "DEBUG" => Some("true".into()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    fn run(input: &str) -> (String, Vec<String>) {
+        let mut unset = Vec::new();
+        let out = interpolate(input, &lookup, &mut unset).unwrap();
+        (out, unset)
+    }
+
+    #[test]
+    fn placeholders_defaults_and_escapes() {
+        assert_eq!(run("http://${HOST}:${PORT}/x").0, "http://db.local:5432/x");
+        assert_eq!(run("${MISSING:-fallback}").0, "fallback");
+        assert_eq!(run("${EMPTY:-fallback}").0, "fallback");
+        assert_eq!(run("${HOST:-fallback}").0, "db.local");
+        assert_eq!(run("${MISSING:-}").0, "");
+        assert_eq!(
+            run("cost $5, literal $${HOST}").0,
+            "cost $5, literal ${HOST}"
+        );
+        assert_eq!(run("$HOME stays").0, "$HOME stays");
+    }
+
+    #[test]
+    fn unset_variables_are_reported_but_empty_ones_are_not() {
+        assert_eq!(
+            run("a${MISSING}b${EMPTY}c"),
+            ("abc".into(), vec!["MISSING".into()])
+        );
+    }
+
+    #[test]
+    fn malformed_placeholders_are_errors() {
+        let mut unset = Vec::new();
+        assert!(matches!(
+            interpolate("${HOST", &lookup, &mut unset),
+            Err(InterpolationError::Unterminated(_))
+        ));
+        assert!(matches!(
+            interpolate("${1X}", &lookup, &mut unset),
+            Err(InterpolationError::InvalidName { .. })
+        ));
+    }
+
+    #[test]
+    fn values_stay_strings_unless_typed() {
+        let mut value: Value = serde_yaml_ng::from_str(
+            "port: ${PORT:int}\nurl: \"${HOST}:${PORT}\"\ntoken: ${MISSING}\nflag: ${MISSING:bool:-true}\nratio: ${MISSING:float:-0.5}\nname: ${HOST}\nsecret: ${PORT}\ndebug: ${DEBUG}\nlist: [\"${HOST}\"]\n",
+        )
+        .unwrap();
+        let mut unset = Vec::new();
+        interpolate_value(&mut value, &lookup, &mut unset).unwrap();
+        assert_eq!(value["port"], Value::from(5432));
+        assert_eq!(value["url"], Value::from("db.local:5432"));
+        assert_eq!(value["token"], Value::Null);
+        assert_eq!(value["flag"], Value::Bool(true));
+        assert_eq!(value["ratio"], Value::from(0.5));
+        assert_eq!(value["name"], Value::from("db.local"));
+        assert_eq!(value["secret"], Value::from("5432"));
+        assert_eq!(value["debug"], Value::from("true"));
+        assert_eq!(value["list"][0], Value::from("db.local"));
+        assert_eq!(unset, ["MISSING"]);
+    }
+
+    #[test]
+    fn numeric_looking_secrets_deserialize_as_strings() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Flags {
+            api_token: Option<String>,
+            ntfy_topic: String,
+            port: u16,
+        }
+
+        let mut value: Value = serde_yaml_ng::from_str(
+            "api_token: ${TOKEN}\nntfy_topic: ${DEBUG}\nport: ${PORT:int:-8080}\n",
+        )
+        .unwrap();
+        interpolate_value(&mut value, &lookup, &mut Vec::new()).unwrap();
+        let flags: Flags = serde_yaml_ng::from_value(value).unwrap();
+        assert_eq!(flags.api_token.as_deref(), Some("12345"));
+        assert_eq!(flags.ntfy_topic, "true");
+        assert_eq!(flags.port, 5432);
+    }
+
+    #[test]
+    fn typed_placeholders_reject_bad_values_and_types() {
+        let mut unset = Vec::new();
+        let mut value = Value::from("${HOST:int}");
+        assert!(matches!(
+            interpolate_value(&mut value, &lookup, &mut unset),
+            Err(InterpolationError::Type { kind: "int", .. })
+        ));
+        let mut value = Value::from("${HOST:url}");
+        assert!(matches!(
+            interpolate_value(&mut value, &lookup, &mut unset),
+            Err(InterpolationError::UnknownType { .. })
+        ));
+        assert_eq!(
+            interpolate("http://${HOST}:${PORT:int}", &lookup, &mut unset).unwrap(),
+            "http://db.local:5432"
+        );
+    }
+}
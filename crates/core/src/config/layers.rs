@@ -0,0 +1,734 @@
+//! Schichten der Server-Konfiguration und ihre Reihenfolge.
+//!
+//! Von schwach nach stark: eingebaute Defaults < Systemdatei
+//! (`HAUSKI_SYSTEM_CONFIG`, sonst `/etc/hauski/hauski.yml`) < Benutzerdatei
+//! (`HAUSKI_CONFIG`, sonst `./configs/hauski.yml`) < Umgebungsvariablen <
+//! CLI (`--set abschnitt.schlüssel=wert`). Die Einzeldateien (`HAUSKI_LIMITS`
+//! usw.) füllen Abschnitte, die in keiner der beiden Dateien stehen.
+//!
+//! Mappings werden schlüsselweise zusammengeführt, alle anderen Werte (auch
+//! Listen) ersetzt. Für jeden Schlüssel merkt sich der Loader die Schicht, aus
+//! der er stammt; `hauski config effective` und `GET /config/effective` zeigen
+//! das Ergebnis.
+
+use super::loader::parse_env_bool;
+use super::secrets::{lookup_secret, redact_value, resolve_secrets, REDACTED};
+use super::types::RoutingPolicy;
+use super::unified::{FallbackPaths, DEFAULT_CONFIG_PATH};
+use serde::{Serialize, Serializer};
+use serde_yaml_ng::{Mapping, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fmt,
+    path::PathBuf,
+};
+
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/hauski/hauski.yml";
+
+/// What a configuration path variable falls back to when unset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathDefault {
+    /// This path.
+    Path(&'static str),
+    /// This path if the file exists, otherwise none.
+    IfExists(&'static str),
+    /// None; the configuration files decide.
+    Unset,
+}
+
+/// Every environment variable naming a configuration file, with its default.
+/// The loader reads its paths through this list, and `hauski service install`
+/// carries it into the unit's env file.
+pub const CONFIG_PATH_VARS: [(&str, PathDefault); 8] = [
+    (
+        "HAUSKI_SYSTEM_CONFIG",
+        PathDefault::IfExists(SYSTEM_CONFIG_PATH),
+    ),
+    ("HAUSKI_CONFIG", PathDefault::IfExists(DEFAULT_CONFIG_PATH)),
+    ("HAUSKI_LIMITS", PathDefault::Path("./policies/limits.yaml")),
+    ("HAUSKI_MODELS", PathDefault::Path("./configs/models.yml")),
+    (
+        "HAUSKI_ROUTING",
+        PathDefault::Path("./policies/routing.yaml"),
+    ),
+    ("HAUSKI_FLAGS", PathDefault::Path("./configs/flags.yaml")),
+    ("HAUSKI_TRUST_POLICY_PATH", PathDefault::Unset),
+    ("HAUSKI_CONTEXT_POLICY_PATH", PathDefault::Unset),
+];
+
+/// Path from `var`, else its default from [`CONFIG_PATH_VARS`]. Empty values
+/// count as unset.
+pub(crate) fn config_path(var: &str) -> Option<PathBuf> {
+    if let Some(path) = env::var(var).ok().filter(|path| !path.trim().is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let default = CONFIG_PATH_VARS
+        .iter()
+        .find(|(name, _)| *name == var)
+        .map_or(PathDefault::Unset, |(_, default)| *default);
+    match default {
+        PathDefault::Path(path) => Some(PathBuf::from(path)),
+        PathDefault::IfExists(path) => Some(PathBuf::from(path)).filter(|path| path.exists()),
+        PathDefault::Unset => None,
+    }
+}
+
+/// Prefix of generic overrides: `HAUSKI__FLAGS__SAFE_MODE=true` sets `flags.safe_mode`.
+pub const ENV_PREFIX: &str = "HAUSKI__";
+
+/// Top-level sections the server reads; overrides must name one of them.
+pub const SECTIONS: [&str; 7] = [
+    "limits",
+    "models",
+    "embeddings",
+    "routing",
+    "flags",
+    "index",
+    "memory",
+];
+
+/// The layer a value of the effective configuration comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Built-in default of the field.
+    Default,
+    System(PathBuf),
+    User(PathBuf),
+    /// A separate section file such as `HAUSKI_LIMITS`.
+    File(PathBuf),
+    /// The environment variable that set the value.
+    Env(String),
+    /// The key of a `--set` override.
+    Cli(String),
+    /// Changed while the server was running, e.g. through the allowlist API.
+    Runtime,
+}
+
+impl Origin {
+    /// Whether the value was set by the environment or `--set` rather than
+    /// read from a file.
+    pub fn is_override(&self) -> bool {
+        matches!(self, Origin::Env(_) | Origin::Cli(_))
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => f.write_str("default"),
+            Origin::System(path) => write!(f, "system:{}", path.display()),
+            Origin::User(path) => write!(f, "user:{}", path.display()),
+            Origin::File(path) => write!(f, "file:{}", path.display()),
+            Origin::Env(var) => write!(f, "env:{var}"),
+            Origin::Cli(key) => write!(f, "cli:--set {key}"),
+            Origin::Runtime => f.write_str("runtime"),
+        }
+    }
+}
+
+impl Serialize for Origin {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Origin of every value set by a layer, keyed by dotted path (`flags.safe_mode`).
+pub type Origins = BTreeMap<String, Origin>;
+
+/// The inputs of a layered load.
+#[derive(Debug, Clone)]
+pub struct ConfigLayers {
+    pub system: Option<PathBuf>,
+    pub user: Option<PathBuf>,
+    pub fallbacks: FallbackPaths,
+    /// `section.key=value` overrides from the command line, applied last.
+    pub overrides: Vec<String>,
+}
+
+impl ConfigLayers {
+    /// Files the way `main` finds them.
+    ///
+    /// `HAUSKI_SYSTEM_CONFIG` and `HAUSKI_CONFIG` must exist when set; the
+    /// default locations are skipped when missing.
+    pub fn from_env() -> Self {
+        Self {
+            system: config_path("HAUSKI_SYSTEM_CONFIG"),
+            user: config_path("HAUSKI_CONFIG"),
+            fallbacks: FallbackPaths::from_env(),
+            overrides: Vec::new(),
+        }
+    }
+
+    pub fn with_overrides(mut self, overrides: Vec<String>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+}
+
+/// One value set by the environment or the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Override {
+    pub path: Vec<String>,
+    pub value: Value,
+    pub origin: Origin,
+}
+
+/// How a dedicated `HAUSKI_*` variable is read.
+#[derive(Clone, Copy)]
+enum EnvValue {
+    Bool,
+    U64,
+    /// Empty unsets the value.
+    Text,
+}
+
+/// Dedicated variables that predate the generic `HAUSKI__` overrides. Later
+/// entries win, so `HAUSKI_CHAT_UPSTREAM_URL` beats `CHAT_UPSTREAM_URL`.
+const DEDICATED_ENV: [(&str, &str, EnvValue); 12] = [
+    ("HAUSKI_SAFE_MODE", "flags.safe_mode", EnvValue::Bool),
+    (
+        "CHAT_UPSTREAM_URL",
+        "flags.chat_upstream_url",
+        EnvValue::Text,
+    ),
+    (
+        "HAUSKI_CHAT_UPSTREAM_URL",
+        "flags.chat_upstream_url",
+        EnvValue::Text,
+    ),
+    ("HAUSKI_CHAT_MODEL", "flags.chat_model", EnvValue::Text),
+    ("HAUSKI_EVENTS_TOKEN", "flags.events_token", EnvValue::Text),
+    ("HAUSKI_MEMORY_API", "flags.memory_api", EnvValue::Bool),
+    ("HAUSKI_MEMORY_TOKEN", "flags.memory_token", EnvValue::Text),
+    ("HAUSKI_API_TOKEN", "flags.api_token", EnvValue::Text),
+    (
+        "HAUSKI_TRUST_POLICY_PATH",
+        "index.trust_policy",
+        EnvValue::Text,
+    ),
+    (
+        "HAUSKI_CONTEXT_POLICY_PATH",
+        "index.context_policy",
+        EnvValue::Text,
+    ),
+    (
+        "HAUSKI_INDEX_POLICY_WATCH_SEC",
+        "index.policy_watch_sec",
+        EnvValue::U64,
+    ),
+    (
+        "HAUSKI_MEMORY_MAX_POOL_SIZE",
+        "memory.max_pool_size",
+        EnvValue::U64,
+    ),
+];
+
+/// The environment layer: dedicated variables first, then `HAUSKI__…` sorted
+/// by name. Malformed generic variables are pushed to `errors`.
+pub(crate) fn env_overrides(errors: &mut Vec<String>) -> Vec<Override> {
+    let vars = env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+    env_overrides_from(vars, errors)
+}
+
+fn env_overrides_from(
+    vars: impl IntoIterator<Item = (String, String)>,
+    errors: &mut Vec<String>,
+) -> Vec<Override> {
+    let vars: BTreeMap<String, String> = vars.into_iter().collect();
+    let mut overrides = Vec::new();
+
+    for (var, key, kind) in DEDICATED_ENV {
+        let Some(raw) = vars.get(var) else {
+            continue;
+        };
+        let value = match kind {
+            EnvValue::Bool => parse_env_bool(raw).map(Value::Bool),
+            EnvValue::U64 => raw.trim().parse::<u64>().ok().map(Value::from),
+            EnvValue::Text if raw.trim().is_empty() => Some(Value::Null),
+            EnvValue::Text => Some(Value::String(raw.clone())),
+        };
+        match value {
+            Some(value) => {
+                if let Some(value) = resolved(value, var, errors) {
+                    overrides.push(Override {
+                        path: key.split('.').map(str::to_string).collect(),
+                        value,
+                        origin: Origin::Env(var.to_string()),
+                    });
+                }
+            }
+            None => {
+                let expected = match kind {
+                    EnvValue::Bool => "boolean",
+                    _ => "number",
+                };
+                tracing::warn!(
+                    invalid_value = %raw,
+                    "invalid {expected} for {var}, keeping configured value"
+                );
+            }
+        }
+    }
+
+    for (var, raw) in &vars {
+        let Some(name) = var.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let path: Vec<String> = name.split("__").map(str::to_ascii_lowercase).collect();
+        match check_path(&path) {
+            Ok(()) => {
+                if let Some(value) = resolved(parse_scalar(raw), var, errors) {
+                    overrides.push(Override {
+                        path,
+                        value,
+                        origin: Origin::Env(var.clone()),
+                    });
+                }
+            }
+            Err(problem) => errors.push(format!("{var}: {problem}")),
+        }
+    }
+    overrides
+}
+
+/// The command-line layer from `section.key=value` arguments.
+pub(crate) fn cli_overrides(sets: &[String], errors: &mut Vec<String>) -> Vec<Override> {
+    let mut overrides = Vec::new();
+    for set in sets {
+        let Some((key, raw)) = set.split_once('=') else {
+            errors.push(format!("--set {set}: expected section.key=value"));
+            continue;
+        };
+        let key = key.trim();
+        let path: Vec<String> = key.split('.').map(str::to_string).collect();
+        match check_path(&path) {
+            Ok(()) => {
+                if let Some(value) = resolved(parse_scalar(raw), &format!("--set {key}"), errors) {
+                    overrides.push(Override {
+                        path,
+                        value,
+                        origin: Origin::Cli(key.to_string()),
+                    });
+                }
+            }
+            Err(problem) => errors.push(format!("--set {set}: {problem}")),
+        }
+    }
+    overrides
+}
+
+fn check_path(path: &[String]) -> Result<(), String> {
+    if path.iter().any(|segment| segment.is_empty()) {
+        return Err("empty key segment".to_string());
+    }
+    if !SECTIONS.contains(&path[0].as_str()) {
+        return Err(format!(
+            "unknown section '{}' (expected {})",
+            path[0],
+            SECTIONS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// `value` with its `secret://` references resolved; a missing secret is
+/// reported as a problem of `source`.
+fn resolved(mut value: Value, source: &str, errors: &mut Vec<String>) -> Option<Value> {
+    match resolve_secrets(&mut value, &lookup_secret) {
+        Ok(()) => Some(value),
+        Err(err) => {
+            errors.push(format!("{source}: {err}"));
+            None
+        }
+    }
+}
+
+/// Reads an override like an unquoted YAML value; `[a, b]` is a list, empty is `null`.
+fn parse_scalar(raw: &str) -> Value {
+    serde_yaml_ng::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Merges `overlay` into `base` and records `origin` for every value it sets.
+pub(crate) fn merge(
+    base: &mut Value,
+    overlay: Value,
+    prefix: &str,
+    origin: &Origin,
+    origins: &mut Origins,
+) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let path = join(prefix, &key);
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value, &path, origin, origins),
+                    None => {
+                        let mut slot = Value::Null;
+                        forget(origins, &path);
+                        merge(&mut slot, value, &path, origin, origins);
+                        base.insert(key, slot);
+                    }
+                }
+            }
+        }
+        (base, Value::Mapping(overlay)) => {
+            *base = Value::Mapping(Mapping::new());
+            forget(origins, prefix);
+            merge(base, Value::Mapping(overlay), prefix, origin, origins);
+        }
+        (base, overlay) => {
+            forget(origins, prefix);
+            origins.insert(prefix.to_string(), origin.clone());
+            *base = overlay;
+        }
+    }
+}
+
+/// Applies `overrides` in order on top of `tree`.
+pub(crate) fn apply_overrides(tree: &mut Value, overrides: Vec<Override>, origins: &mut Origins) {
+    for Override {
+        path,
+        value,
+        origin,
+    } in overrides
+    {
+        let nested = path.iter().rev().fold(value, |value, key| {
+            let mut mapping = Mapping::new();
+            mapping.insert(Value::String(key.clone()), value);
+            Value::Mapping(mapping)
+        });
+        merge(tree, nested, "", &origin, origins);
+    }
+}
+
+fn join(prefix: &str, key: &Value) -> String {
+    let key = match key {
+        Value::String(key) => key.clone(),
+        other => serde_yaml_ng::to_string(other)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    };
+    if prefix.is_empty() {
+        key
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Drops the origins of `path` and everything below it.
+fn forget(origins: &mut Origins, path: &str) {
+    let below = format!("{path}.");
+    origins.retain(|key, _| key != path && !key.starts_with(&below));
+}
+
+/// The merged configuration with the origin of each value; token values are
+/// redacted.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub config: Value,
+    pub entries: Vec<EffectiveEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveEntry {
+    pub key: String,
+    pub value: Value,
+    pub origin: Origin,
+}
+
+impl EffectiveConfig {
+    /// Lists every leaf of `config` (mappings are walked, lists are single
+    /// values) with the origin recorded for it or its closest parent.
+    pub(crate) fn new(mut config: Value, origins: &Origins) -> Self {
+        redact(&mut config, "");
+        redact_value(&mut config);
+        let entries = leaves(&config)
+            .into_iter()
+            .map(|(key, value)| EffectiveEntry {
+                origin: origin_of(origins, &key),
+                key,
+                value,
+            })
+            .collect();
+        Self { config, entries }
+    }
+}
+
+/// The configuration as the files alone set it and as the server started
+/// with it, kept unredacted so the two can be compared.
+#[derive(Debug, Clone)]
+pub struct ConfigSnapshot {
+    files: Value,
+    effective: Value,
+    origins: Origins,
+}
+
+/// Values that differ from the files, with the layer responsible.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiff {
+    pub entries: Vec<ConfigDiffEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiffEntry {
+    pub key: String,
+    /// The value the files set (or the default); `null` if absent.
+    pub file: Value,
+    /// The value in use; `null` if absent.
+    pub effective: Value,
+    pub origin: Origin,
+}
+
+impl ConfigSnapshot {
+    pub(crate) fn new(files: Value, effective: Value, origins: Origins) -> Self {
+        Self {
+            files,
+            effective,
+            origins,
+        }
+    }
+
+    pub fn effective(&self) -> EffectiveConfig {
+        EffectiveConfig::new(self.effective.clone(), &self.origins)
+    }
+
+    /// Keys whose value in use differs between `self` and `next`.
+    pub(crate) fn changed_keys(&self, next: &ConfigSnapshot) -> Vec<String> {
+        let old: BTreeMap<String, Value> = leaves(&self.effective).into_iter().collect();
+        let new: BTreeMap<String, Value> = leaves(&next.effective).into_iter().collect();
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        keys.into_iter()
+            .filter(|key| old.get(*key) != new.get(*key))
+            .cloned()
+            .collect()
+    }
+
+    /// Compares the files with the configuration in use, where `routing` is
+    /// the current routing policy (the allowlist API may have changed it).
+    ///
+    /// Differences not explained by the environment or `--set` are reported
+    /// as [`Origin::Runtime`].
+    pub fn diff(&self, routing: &RoutingPolicy) -> ConfigDiff {
+        let mut current = self.effective.clone();
+        if let Some(mapping) = current.as_mapping_mut() {
+            let routing = serde_yaml_ng::to_value(routing).unwrap_or_default();
+            mapping.insert("routing".into(), routing);
+        }
+        let files: BTreeMap<String, Value> = leaves(&self.files).into_iter().collect();
+        let current: BTreeMap<String, Value> = leaves(&current).into_iter().collect();
+
+        let keys: BTreeSet<&String> = files.keys().chain(current.keys()).collect();
+        let mut entries = Vec::new();
+        for key in keys {
+            let file = files.get(key).cloned().unwrap_or(Value::Null);
+            let effective = current.get(key).cloned().unwrap_or(Value::Null);
+            if file == effective {
+                continue;
+            }
+            let origin = match origin_of(&self.origins, key) {
+                origin if origin.is_override() => origin,
+                _ => Origin::Runtime,
+            };
+            let name = key.rsplit('.').next().unwrap_or_default();
+            let [file, effective] = [file, effective].map(|mut value| {
+                redact(&mut value, name);
+                redact_value(&mut value);
+                value
+            });
+            entries.push(ConfigDiffEntry {
+                key: key.clone(),
+                file,
+                effective,
+                origin,
+            });
+        }
+        ConfigDiff { entries }
+    }
+}
+
+/// Every leaf of `value` by dotted path; mappings are walked, lists are single
+/// values.
+fn leaves(value: &Value) -> Vec<(String, Value)> {
+    let mut out = Vec::new();
+    collect_leaves(value, "", &mut out);
+    out
+}
+
+fn collect_leaves(value: &Value, path: &str, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Mapping(mapping) if !mapping.is_empty() => {
+            for (key, item) in mapping {
+                collect_leaves(item, &join(path, key), out);
+            }
+        }
+        _ => out.push((path.to_string(), value.clone())),
+    }
+}
+
+fn origin_of(origins: &Origins, path: &str) -> Origin {
+    let mut current = path;
+    loop {
+        if let Some(origin) = origins.get(current) {
+            return origin.clone();
+        }
+        match current.rfind('.') {
+            Some(dot) => current = &current[..dot],
+            None => return Origin::Default,
+        }
+    }
+}
+
+fn redact(value: &mut Value, key: &str) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (name, item) in mapping.iter_mut() {
+                redact(item, name.as_str().unwrap_or_default());
+            }
+        }
+        Value::Null => {}
+        _ if key == "token" || key.ends_with("_token") => *value = Value::from(REDACTED),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml_ng::from_str(text).unwrap()
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn later_layers_win_key_by_key_and_replace_lists() {
+        let system = Origin::System("/etc/hauski/hauski.yml".into());
+        let user = Origin::User("./configs/hauski.yml".into());
+        let mut origins = Origins::new();
+        let mut tree = Value::Mapping(Mapping::new());
+        merge(
+            &mut tree,
+            yaml("limits: {latency: {llm_p95_ms: 300, index_topk20_ms: 50}}\nmodels: [a, b]\n"),
+            "",
+            &system,
+            &mut origins,
+        );
+        merge(
+            &mut tree,
+            yaml("limits: {latency: {llm_p95_ms: 250}}\nmodels: [c]\n"),
+            "",
+            &user,
+            &mut origins,
+        );
+
+        assert_eq!(
+            tree,
+            yaml("limits: {latency: {llm_p95_ms: 250, index_topk20_ms: 50}}\nmodels: [c]\n")
+        );
+        assert_eq!(origins["limits.latency.llm_p95_ms"], user);
+        assert_eq!(origins["limits.latency.index_topk20_ms"], system);
+        assert_eq!(origins["models"], user);
+    }
+
+    #[test]
+    fn env_layer_reads_dedicated_and_generic_variables() {
+        let mut errors = Vec::new();
+        let overrides = env_overrides_from(
+            vars(&[
+                ("HAUSKI__FLAGS__CHAT_MODEL", "generic"),
+                ("HAUSKI_CHAT_MODEL", "dedicated"),
+                ("HAUSKI_SAFE_MODE", "maybe"),
+                ("HAUSKI_API_TOKEN", ""),
+                ("HAUSKI__LIMITS__LATENCY__LLM_P95_MS", "250"),
+                ("HAUSKI__SERVR__PORT", "1"),
+                ("HAUSKI_HTTP_TIMEOUT_MS", "5"),
+            ]),
+            &mut errors,
+        );
+
+        let mut tree = yaml("flags: {chat_model: file, safe_mode: true}\n");
+        let mut origins = Origins::new();
+        apply_overrides(&mut tree, overrides, &mut origins);
+        assert_eq!(
+            tree,
+            yaml(
+                "flags: {chat_model: generic, safe_mode: true, api_token: null}\nlimits: {latency: {llm_p95_ms: 250}}\n"
+            )
+        );
+        assert_eq!(
+            origins["flags.chat_model"],
+            Origin::Env("HAUSKI__FLAGS__CHAT_MODEL".into())
+        );
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("HAUSKI__SERVR__PORT: unknown section 'servr'"));
+    }
+
+    #[test]
+    fn cli_overrides_parse_yaml_values_and_reject_unknown_sections() {
+        let mut errors = Vec::new();
+        let overrides = cli_overrides(
+            &[
+                "flags.safe_mode=true".into(),
+                "embeddings.fallback=[a, b]".into(),
+                "server.port=1".into(),
+                "flags.safe_mode".into(),
+            ],
+            &mut errors,
+        );
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[0].value, Value::Bool(true));
+        assert_eq!(overrides[1].value, yaml("[a, b]"));
+        assert_eq!(
+            overrides[1].origin.to_string(),
+            "cli:--set embeddings.fallback"
+        );
+        assert_eq!(errors.len(), 2, "{errors:?}");
+    }
+
+    #[test]
+    fn effective_entries_fall_back_to_parent_origins_and_redact_tokens() {
+        let file = Origin::File("flags.yaml".into());
+        let origins = Origins::from([("flags.api_token".to_string(), file.clone())]);
+        let effective = EffectiveConfig::new(
+            yaml("flags: {api_token: secret, memory_token: null, safe_mode: false}\nindex: {}\n"),
+            &origins,
+        );
+
+        let entries: Vec<_> = effective
+            .entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.origin.to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("flags.api_token", "file:flags.yaml".to_string()),
+                ("flags.memory_token", "default".to_string()),
+                ("flags.safe_mode", "default".to_string()),
+                ("index", "default".to_string()),
+            ]
+        );
+        assert_eq!(effective.config["flags"]["api_token"], Value::from("***"));
+        assert_eq!(effective.config["flags"]["memory_token"], Value::Null);
+    }
+
+    #[test]
+    fn dedicated_path_variables_are_config_path_vars() {
+        for (var, _, _) in DEDICATED_ENV {
+            if var.ends_with("_PATH") {
+                assert!(
+                    CONFIG_PATH_VARS.iter().any(|(name, _)| *name == var),
+                    "{var} missing from CONFIG_PATH_VARS"
+                );
+            }
+        }
+    }
+}
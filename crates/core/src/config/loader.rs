@@ -1,8 +1,13 @@
+use super::interpolate::unset_message;
+use super::layers::{apply_overrides, env_overrides, Origins, Override};
+use super::migrate::{parse_config, ConfigKind};
+use super::secrets::redact;
 use super::types::*;
 use crate::error::{HauskiError, Result};
-use std::{env, fs, path::Path};
+use serde::de::DeserializeOwned;
+use std::{fs, path::Path};
 
-fn parse_env_bool(value: &str) -> Option<bool> {
+pub(crate) fn parse_env_bool(value: &str) -> Option<bool> {
     match value.trim().to_ascii_lowercase().as_str() {
         "1" | "true" | "yes" | "on" => Some(true),
         "0" | "false" | "no" | "off" => Some(false),
@@ -10,96 +15,86 @@ fn parse_env_bool(value: &str) -> Option<bool> {
     }
 }
 
-pub fn load_limits<P: AsRef<Path>>(path: P) -> Result<Limits> {
-    let path = path.as_ref();
+/// Reads a YAML config, upgrades older schema versions, interpolates `${VAR}`
+/// placeholders and deserializes it; also returns the variables that were
+/// referenced but unset.
+pub(crate) fn load_yaml<T: DeserializeOwned>(
+    path: &Path,
+    kind: ConfigKind,
+) -> Result<(T, Vec<String>)> {
+    let what = kind.name();
     let content = fs::read_to_string(path).map_err(|e| {
-        HauskiError::Config(format!("failed to read limits YAML at {:?}: {}", path, e))
+        HauskiError::Config(format!("failed to read {what} YAML at {:?}: {}", path, e))
     })?;
 
-    let limits = serde_yaml_ng::from_str(&content).map_err(|e| {
-        HauskiError::Config(format!("failed to parse limits YAML at {:?}: {}", path, e))
+    let (value, unset, migrated) = parse_config(&content, kind).map_err(|e| {
+        HauskiError::Config(redact(&format!(
+            "failed to parse {what} YAML at {:?}: {}",
+            path, e
+        )))
     })?;
-
-    Ok(limits)
+    if migrated.is_upgrade() {
+        tracing::info!(
+            path = %path.display(),
+            from = migrated.from,
+            to = migrated.to,
+            "upgraded {what} config in memory; `hauski config migrate --write` updates the file"
+        );
+    }
+    Ok((value, unset))
 }
 
-pub fn load_models<P: AsRef<Path>>(path: P) -> Result<ModelsFile> {
-    let path = path.as_ref();
-    let content = fs::read_to_string(path).map_err(|e| {
-        HauskiError::Config(format!("failed to read models YAML at {:?}: {}", path, e))
-    })?;
+/// [`load_yaml`], logging unset variables instead of returning them.
+fn load_yaml_logged<T: DeserializeOwned>(path: &Path, kind: ConfigKind) -> Result<T> {
+    let (value, unset) = load_yaml(path, kind)?;
+    for variable in unset {
+        tracing::warn!("{}", unset_message(&variable, kind.name(), path));
+    }
+    Ok(value)
+}
 
-    let models = serde_yaml_ng::from_str(&content).map_err(|e| {
-        HauskiError::Config(format!("failed to parse models YAML at {:?}: {}", path, e))
-    })?;
+pub fn load_limits<P: AsRef<Path>>(path: P) -> Result<Limits> {
+    load_yaml_logged(path.as_ref(), ConfigKind::Limits)
+}
 
-    Ok(models)
+pub fn load_models<P: AsRef<Path>>(path: P) -> Result<ModelsFile> {
+    load_yaml_logged(path.as_ref(), ConfigKind::Models)
 }
 
 pub fn load_routing<P: AsRef<Path>>(path: P) -> Result<RoutingPolicy> {
-    let path = path.as_ref();
-    let content = fs::read_to_string(path).map_err(|e| {
-        HauskiError::Config(format!("failed to read routing YAML at {:?}: {}", path, e))
-    })?;
-
-    let routing = serde_yaml_ng::from_str(&content).map_err(|e| {
-        HauskiError::Config(format!("failed to parse routing YAML at {:?}: {}", path, e))
-    })?;
-
-    Ok(routing)
+    load_yaml_logged(path.as_ref(), ConfigKind::Routing)
 }
 
 pub fn load_flags<P: AsRef<Path>>(path: P) -> Result<FeatureFlags> {
-    let path = path.as_ref();
-    let content = fs::read_to_string(path).map_err(|e| {
-        HauskiError::Config(format!("failed to read flags YAML at {:?}: {}", path, e))
-    })?;
-
-    let mut flags: FeatureFlags = serde_yaml_ng::from_str(&content).map_err(|e| {
-        HauskiError::Config(format!("failed to parse flags YAML at {:?}: {}", path, e))
-    })?;
-
-    if let Ok(value) = env::var("HAUSKI_SAFE_MODE") {
-        match parse_env_bool(&value) {
-            Some(parsed) => {
-                flags.safe_mode = parsed;
-            }
-            None => {
-                tracing::warn!(
-                    invalid_value = %value,
-                    "invalid boolean for HAUSKI_SAFE_MODE, keeping configured value"
-                );
-            }
-        }
-    }
+    let mut flags: FeatureFlags = load_yaml_logged(path.as_ref(), ConfigKind::Flags)?;
+    apply_flag_env_overrides(&mut flags);
+    Ok(flags)
+}
 
-    let upstream_env =
-        env::var("HAUSKI_CHAT_UPSTREAM_URL").or_else(|_| env::var("CHAT_UPSTREAM_URL"));
-    if let Ok(url) = upstream_env {
-        if url.trim().is_empty() {
-            flags.chat_upstream_url = None;
-        } else {
-            flags.chat_upstream_url = Some(url);
-        }
+/// Applies the environment layer (`HAUSKI_SAFE_MODE`, `HAUSKI__FLAGS__…`, …)
+/// on top of configured flags.
+fn apply_flag_env_overrides(flags: &mut FeatureFlags) {
+    let overrides: Vec<Override> = env_overrides(&mut Vec::new())
+        .into_iter()
+        .filter(|o| o.path[0] == "flags")
+        .map(|mut o| {
+            o.path.remove(0);
+            o
+        })
+        .collect();
+    if overrides.is_empty() {
+        return;
     }
-
-    if let Ok(model) = env::var("HAUSKI_CHAT_MODEL") {
-        if model.trim().is_empty() {
-            flags.chat_model = None;
-        } else {
-            flags.chat_model = Some(model);
+    let Ok(mut value) = serde_yaml_ng::to_value(&*flags) else {
+        return;
+    };
+    apply_overrides(&mut value, overrides, &mut Origins::new());
+    match serde_yaml_ng::from_value(value) {
+        Ok(updated) => *flags = updated,
+        Err(err) => {
+            tracing::warn!(%err, "invalid flag overrides in the environment, keeping configured flags")
         }
     }
-
-    if let Ok(token) = env::var("HAUSKI_EVENTS_TOKEN") {
-        if token.trim().is_empty() {
-            flags.events_token = None;
-        } else {
-            flags.events_token = Some(token);
-        }
-    }
-
-    Ok(flags)
 }
 
 #[cfg(test)]
@@ -160,23 +155,41 @@ mod tests {
     }
 
     #[test]
-    fn routing_policy_with_explicit_default_and_no_rules() {
+    fn namespace_budgets_are_read_from_limits() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "namespaces:\n  notes:\n    max_docs: 100\n    default_trust: low\n    retention:\n      max_age_seconds: 60"
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let limits = load_limits(file.path()).unwrap();
+        let notes = &limits.namespaces["notes"];
+        assert_eq!(notes.max_docs, Some(100));
+        assert_eq!(notes.max_bytes, None);
+        assert_eq!(notes.default_trust, Some(hauski_indexd::TrustLevel::Low));
+        assert_eq!(notes.retention.as_ref().unwrap().max_age_seconds, Some(60));
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "namespaces:\n  notes:\n    max_doc: 100").unwrap();
+        file.flush().unwrap();
+        assert!(load_limits(file.path()).is_err());
+    }
+
+    #[test]
+    fn routing_policy_keys_outside_the_schema() {
+        // Version 1 ignored a top-level `default`; the migration drops it.
         let mut file = NamedTempFile::new().unwrap();
         writeln!(file, "default: deny").unwrap();
         file.flush().unwrap();
+        assert_eq!(load_routing(file.path()).unwrap(), RoutingPolicy::default());
 
-        let routing = load_routing(file.path()).unwrap();
-        let mapping = routing
-            .0
-            .as_mapping()
-            .expect("routing policy should be a mapping");
-        let default_key = serde_yaml_ng::Value::String("default".into());
-        let allow_key = serde_yaml_ng::Value::String("allow".into());
-        assert_eq!(
-            mapping.get(&default_key),
-            Some(&serde_yaml_ng::Value::String("deny".into()))
-        );
-        assert!(!mapping.contains_key(&allow_key));
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "version: 2\ndefault: deny").unwrap();
+        file.flush().unwrap();
+        let err = load_routing(file.path()).unwrap_err();
+        assert!(err.to_string().contains("unknown field `default`"), "{err}");
     }
 
     #[test]
@@ -195,6 +208,15 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn shipped_models_file_includes_embedder_registry() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../configs/models.yml");
+        let models = load_models(path).unwrap();
+        let registry = hauski_embeddings::EmbedderRegistry::new(models.embedders).unwrap();
+        assert_eq!(registry.default_spec().unwrap().id, "nomic-ollama");
+        assert_eq!(registry.get("minilm-local").unwrap().dimension, 384);
+    }
+
     #[test]
     fn invalid_routing_yaml_returns_error() {
         let mut file = NamedTempFile::new().unwrap();
@@ -205,6 +227,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn version_key_is_accepted_and_future_versions_are_rejected() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "version: 1\nlatency:\n  llm_p95_ms: 250").unwrap();
+        file.flush().unwrap();
+        assert_eq!(load_limits(file.path()).unwrap().latency.llm_p95_ms, 250);
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "version: 7\nlatency:\n  llm_p95_ms: 250").unwrap();
+        file.flush().unwrap();
+        let err = load_limits(file.path()).unwrap_err().to_string();
+        assert!(
+            err.contains("version 7 is newer than this build supports (up to 1)"),
+            "{err}"
+        );
+    }
+
     #[serial]
     #[test]
     fn missing_flags_file_returns_error() {
@@ -325,6 +364,50 @@ mod tests {
         assert_eq!(flags.chat_model, None);
     }
 
+    #[serial]
+    #[test]
+    fn memory_api_env_overrides_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "memory_api: false\nmemory_token: from-file").unwrap();
+        file.flush().unwrap();
+
+        let _api_guard = EnvVarGuard::removed("HAUSKI_MEMORY_API");
+        let _token_guard = EnvVarGuard::removed("HAUSKI_MEMORY_TOKEN");
+        env::set_var("HAUSKI_MEMORY_API", "on");
+        env::set_var("HAUSKI_MEMORY_TOKEN", " ");
+
+        let flags = load_flags(file.path()).unwrap();
+        assert!(flags.memory_api);
+        assert_eq!(flags.memory_token, None);
+    }
+
+    #[serial]
+    #[test]
+    fn placeholders_are_interpolated_from_the_environment() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "chat_upstream_url: \"http://${{HAUSKI_TEST_LLM_HOST}}:${{HAUSKI_TEST_LLM_PORT:-8081}}\"\nevents_token: ${{HAUSKI_TEST_UNSET_TOKEN}}"
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let _upstream_guard = EnvVarGuard::removed("HAUSKI_CHAT_UPSTREAM_URL");
+        let _legacy_guard = EnvVarGuard::removed("CHAT_UPSTREAM_URL");
+        let _events_guard = EnvVarGuard::removed("HAUSKI_EVENTS_TOKEN");
+        let _host_guard = EnvVarGuard::removed("HAUSKI_TEST_LLM_HOST");
+        let _port_guard = EnvVarGuard::removed("HAUSKI_TEST_LLM_PORT");
+        env::set_var("HAUSKI_TEST_LLM_HOST", "llm.local");
+
+        let (flags, unset) = load_yaml::<FeatureFlags>(file.path(), ConfigKind::Flags).unwrap();
+        assert_eq!(
+            flags.chat_upstream_url.as_deref(),
+            Some("http://llm.local:8081")
+        );
+        assert_eq!(flags.events_token, None);
+        assert_eq!(unset, ["HAUSKI_TEST_UNSET_TOKEN"]);
+    }
+
     #[test]
     fn parse_env_bool_accepts_common_truthy_and_falsy_values() {
         for truthy in ["1", "true", "TRUE", " yes ", "On"] {
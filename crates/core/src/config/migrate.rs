@@ -0,0 +1,477 @@
+//! Schema-Versionen der Konfigurationsdateien.
+//!
+//! Jede Datei (`hauski.yml`, Limits, Modelle, Routing, Flags) darf oben
+//! `version: <n>` tragen; ohne Angabe gilt Version 1. Ältere Versionen hebt der
+//! Loader im Speicher auf die aktuelle (`hauski config migrate --write` schreibt
+//! das Ergebnis mit Backup zurück), neuere lehnt er mit klarer Meldung ab.
+
+use super::interpolate::{resolve, YamlError};
+use crate::error::{HauskiError, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_yaml_ng::{Mapping, Value};
+use std::{fs, path::Path};
+use thiserror::Error;
+
+pub const VERSION_KEY: &str = "version";
+
+/// The versioned config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKind {
+    /// `hauski.yml`
+    Config,
+    Limits,
+    Models,
+    Routing,
+    Flags,
+}
+
+impl ConfigKind {
+    pub const ALL: [ConfigKind; 5] = [
+        ConfigKind::Config,
+        ConfigKind::Limits,
+        ConfigKind::Models,
+        ConfigKind::Routing,
+        ConfigKind::Flags,
+    ];
+
+    /// Name used in messages, e.g. `failed to parse limits YAML`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigKind::Config => "config",
+            ConfigKind::Limits => "limits",
+            ConfigKind::Models => "models",
+            ConfigKind::Routing => "routing",
+            ConfigKind::Flags => "flags",
+        }
+    }
+
+    /// The version this build reads without migration.
+    pub fn current_version(self) -> u32 {
+        current_version_in(self, MIGRATIONS)
+    }
+}
+
+/// Upgrades a file of `kind` from version `from` to `from + 1`.
+pub struct Migration {
+    pub kind: ConfigKind,
+    pub from: u32,
+    pub description: &'static str,
+    /// Works on the top-level mapping, without the `version` key.
+    pub apply: fn(&mut Mapping) -> std::result::Result<(), String>,
+}
+
+/// All known migrations. Adding one for a kind raises its current version;
+/// files written before keep loading through it.
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        kind: ConfigKind::Config,
+        from: 1,
+        description: "typed routing section",
+        apply: typed_routing_section,
+    },
+    Migration {
+        kind: ConfigKind::Routing,
+        from: 1,
+        description: "typed routing policy",
+        apply: typed_routing_policy,
+    },
+];
+
+/// Keys of the routing policy as of version 2, per section.
+const ROUTING_KEYS: &[(&str, &[&str])] = &[
+    ("", &["egress", "routing"]),
+    (
+        "egress",
+        &[
+            "default",
+            "allow",
+            "block_private",
+            "allow_private",
+            "proxy",
+            "audit",
+            "max_redirects",
+        ],
+    ),
+    (
+        "routing",
+        &["prefer_local", "quality_target", "cloud_fallback"],
+    ),
+    (
+        "routing.cloud_fallback",
+        &[
+            "enabled",
+            "only_if",
+            "intents",
+            "blocked_flags",
+            "min_budget_ms",
+            "require_consent",
+        ],
+    ),
+];
+
+/// Version 1 read the routing policy leniently: unknown keys were ignored and
+/// `egress.default` was case-insensitive, falling back to `allow` when it was
+/// not a string. Version 2 is typed, so drop what version 1 ignored.
+fn typed_routing_policy(mapping: &mut Mapping) -> std::result::Result<(), String> {
+    for (section, known) in ROUTING_KEYS {
+        let target = section
+            .split('.')
+            .filter(|key| !key.is_empty())
+            .try_fold(&mut *mapping, |mapping, key| {
+                mapping.get_mut(key).and_then(Value::as_mapping_mut)
+            });
+        let Some(target) = target else { continue };
+        let unknown: Vec<Value> = target
+            .keys()
+            .filter(|key| !key.as_str().is_some_and(|key| known.contains(&key)))
+            .cloned()
+            .collect();
+        for key in unknown {
+            let name = key
+                .as_str()
+                .map_or_else(|| format!("{key:?}"), str::to_string);
+            let path = if section.is_empty() {
+                name
+            } else {
+                format!("{section}.{name}")
+            };
+            tracing::warn!(key = %path, "dropping unknown routing policy key");
+            target.remove(&key);
+        }
+    }
+
+    let Some(egress) = mapping.get_mut("egress").and_then(Value::as_mapping_mut) else {
+        return Ok(());
+    };
+    match egress.get("default") {
+        // Placeholders are resolved after migration.
+        Some(Value::String(default)) if !default.contains('$') => {
+            let default = default.to_ascii_lowercase();
+            egress.insert("default".into(), default.into());
+        }
+        Some(Value::String(_)) | Some(Value::Null) | None => {}
+        Some(_) => {
+            egress.insert("default".into(), "allow".into());
+        }
+    }
+    Ok(())
+}
+
+/// [`typed_routing_policy`] for the `routing` section of `hauski.yml`.
+fn typed_routing_section(mapping: &mut Mapping) -> std::result::Result<(), String> {
+    match mapping.get_mut("routing").and_then(Value::as_mapping_mut) {
+        Some(routing) => typed_routing_policy(routing),
+        None => Ok(()),
+    }
+}
+
+/// What [`migrate`] did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Migrated {
+    pub from: u32,
+    pub to: u32,
+    /// Descriptions of the applied migrations, oldest first.
+    pub applied: Vec<&'static str>,
+}
+
+impl Migrated {
+    pub fn is_upgrade(&self) -> bool {
+        self.from < self.to
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("version must be a positive integer, got '{0}'")]
+    InvalidVersion(String),
+    #[error(
+        "version {version} is newer than this build supports (up to {supported}) – update HausKI"
+    )]
+    FutureVersion { version: u32, supported: u32 },
+    #[error("migration from version {from} ({description}) failed: {reason}")]
+    Failed {
+        from: u32,
+        description: &'static str,
+        reason: String,
+    },
+    #[error("no migration from version {0}")]
+    Missing(u32),
+}
+
+/// Removes `version` from the top-level mapping of `value` and upgrades the
+/// rest to the current version of `kind`.
+pub fn migrate(
+    kind: ConfigKind,
+    value: &mut Value,
+) -> std::result::Result<Migrated, MigrationError> {
+    migrate_with(kind, value, MIGRATIONS)
+}
+
+fn migrate_with(
+    kind: ConfigKind,
+    value: &mut Value,
+    migrations: &[Migration],
+) -> std::result::Result<Migrated, MigrationError> {
+    let current = current_version_in(kind, migrations);
+    // Empty files have nothing to upgrade.
+    let Some(mapping) = value.as_mapping_mut() else {
+        return Ok(Migrated {
+            from: current,
+            to: current,
+            applied: Vec::new(),
+        });
+    };
+
+    let version = match mapping.remove(VERSION_KEY) {
+        None => 1,
+        Some(raw) => raw
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version > 0)
+            .ok_or_else(|| {
+                MigrationError::InvalidVersion(
+                    serde_yaml_ng::to_string(&raw)
+                        .unwrap_or_default()
+                        .trim_end()
+                        .to_string(),
+                )
+            })?,
+    };
+    if version > current {
+        return Err(MigrationError::FutureVersion {
+            version,
+            supported: current,
+        });
+    }
+
+    let mut applied = Vec::new();
+    for from in version..current {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.kind == kind && migration.from == from)
+            .ok_or(MigrationError::Missing(from))?;
+        (migration.apply)(mapping).map_err(|reason| MigrationError::Failed {
+            from,
+            description: migration.description,
+            reason,
+        })?;
+        applied.push(migration.description);
+    }
+
+    Ok(Migrated {
+        from: version,
+        to: current,
+        applied,
+    })
+}
+
+fn current_version_in(kind: ConfigKind, migrations: &[Migration]) -> u32 {
+    migrations
+        .iter()
+        .filter(|migration| migration.kind == kind)
+        .map(|migration| migration.from + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Parses a config file of `kind`: migrates it, interpolates `${VAR}`
+/// placeholders and deserializes it. Also returns the unset variables and
+/// what the migration did.
+pub fn parse_config<T: DeserializeOwned>(
+    text: &str,
+    kind: ConfigKind,
+) -> std::result::Result<(T, Vec<String>, Migrated), YamlError> {
+    let mut value: Value = serde_yaml_ng::from_str(text)?;
+    let migrated = migrate(kind, &mut value)?;
+    let (parsed, unset) = resolve(value)?;
+    Ok((parsed, unset, migrated))
+}
+
+/// `value` (a migrated mapping) with `version: <current>` as its first key,
+/// ready to be written back.
+pub fn with_version(kind: ConfigKind, value: &Value) -> Value {
+    let mut stamped = Mapping::new();
+    stamped.insert(VERSION_KEY.into(), kind.current_version().into());
+    if let Some(mapping) = value.as_mapping() {
+        for (key, item) in mapping {
+            stamped.insert(key.clone(), item.clone());
+        }
+    }
+    Value::Mapping(stamped)
+}
+
+/// Migrates the file at `path`; with `write`, an upgraded file is saved back
+/// after copying the original to `<path>.bak`.
+///
+/// Placeholders are kept as written; comments are lost on rewrite.
+pub fn migrate_file(path: &Path, kind: ConfigKind, write: bool) -> Result<Migrated> {
+    let what = kind.name();
+    let content = fs::read_to_string(path).map_err(|e| {
+        HauskiError::Config(format!("failed to read {what} YAML at {:?}: {}", path, e))
+    })?;
+    let mut value: Value = serde_yaml_ng::from_str(&content).map_err(|e| {
+        HauskiError::Config(format!("failed to parse {what} YAML at {:?}: {}", path, e))
+    })?;
+    let migrated = migrate(kind, &mut value).map_err(|e| {
+        HauskiError::Config(format!(
+            "failed to migrate {what} YAML at {:?}: {}",
+            path, e
+        ))
+    })?;
+
+    if write && migrated.is_upgrade() {
+        let sibling = |suffix: &str| {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(suffix);
+            path.with_file_name(name)
+        };
+        fs::copy(path, sibling(".bak"))?;
+        let yaml = serde_yaml_ng::to_string(&with_version(kind, &value))
+            .map_err(|e| HauskiError::Config(format!("failed to serialize {what} YAML: {e}")))?;
+        let tmp = sibling(".tmp");
+        fs::write(&tmp, yaml)?;
+        fs::rename(&tmp, path)?;
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_budget(mapping: &mut Mapping) -> std::result::Result<(), String> {
+        if let Some(value) = mapping.remove("budget_ms") {
+            mapping.insert("llm_p95_ms".into(), value);
+        }
+        Ok(())
+    }
+
+    fn require_name(mapping: &mut Mapping) -> std::result::Result<(), String> {
+        mapping
+            .contains_key("name")
+            .then_some(())
+            .ok_or_else(|| "name missing".to_string())
+    }
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            kind: ConfigKind::Limits,
+            from: 1,
+            description: "rename budget_ms",
+            apply: rename_budget,
+        },
+        Migration {
+            kind: ConfigKind::Limits,
+            from: 2,
+            description: "require name",
+            apply: require_name,
+        },
+    ];
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml_ng::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn unversioned_files_are_version_one_and_upgraded_step_by_step() {
+        let mut value = yaml("budget_ms: 300\nname: a\n");
+        let migrated = migrate_with(ConfigKind::Limits, &mut value, TEST_MIGRATIONS).unwrap();
+        assert_eq!(
+            migrated,
+            Migrated {
+                from: 1,
+                to: 3,
+                applied: vec!["rename budget_ms", "require name"],
+            }
+        );
+        assert_eq!(value, yaml("name: a\nllm_p95_ms: 300\n"));
+    }
+
+    #[test]
+    fn current_files_only_lose_their_version_key() {
+        let mut value = yaml("version: 3\nname: a\n");
+        let migrated = migrate_with(ConfigKind::Limits, &mut value, TEST_MIGRATIONS).unwrap();
+        assert!(!migrated.is_upgrade());
+        assert_eq!(value, yaml("name: a\n"));
+
+        // Other kinds have no migrations and stay at version 1.
+        let mut value = yaml("version: 1\ndefault: deny\n");
+        let migrated = migrate_with(ConfigKind::Routing, &mut value, TEST_MIGRATIONS).unwrap();
+        assert_eq!((migrated.from, migrated.to), (1, 1));
+    }
+
+    #[test]
+    fn future_invalid_and_failing_versions_are_errors() {
+        let mut value = yaml("version: 4\n");
+        assert!(matches!(
+            migrate_with(ConfigKind::Limits, &mut value, TEST_MIGRATIONS),
+            Err(MigrationError::FutureVersion {
+                version: 4,
+                supported: 3
+            })
+        ));
+        let mut value = yaml("version: two\n");
+        let err = migrate_with(ConfigKind::Limits, &mut value, TEST_MIGRATIONS).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "version must be a positive integer, got 'two'"
+        );
+        let mut value = yaml("version: 2\n");
+        assert!(matches!(
+            migrate_with(ConfigKind::Limits, &mut value, TEST_MIGRATIONS),
+            Err(MigrationError::Failed { from: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn with_version_puts_the_current_version_first() {
+        let stamped = with_version(ConfigKind::Routing, &yaml("egress:\n  default: deny\n"));
+        assert_eq!(
+            serde_yaml_ng::to_string(&stamped).unwrap(),
+            "version: 2\negress:\n  default: deny\n"
+        );
+    }
+
+    #[test]
+    fn version_one_routing_drops_what_it_ignored() {
+        let mut value = yaml(
+            "egress:\n  default: DENY\n  alow: [a.example]\nrouting:\n  prefer_local: true\n  cloud_fallback:\n    enabled: false\n    budget: 3\nnotes: old\n",
+        );
+        let migrated = migrate(ConfigKind::Routing, &mut value).unwrap();
+        assert_eq!((migrated.from, migrated.to), (1, 2));
+        assert_eq!(
+            value,
+            yaml("egress:\n  default: deny\nrouting:\n  prefer_local: true\n  cloud_fallback:\n    enabled: false\n")
+        );
+
+        let mut value = yaml("routing:\n  egress:\n    default: 1\n    default_allow: true\n");
+        migrate(ConfigKind::Config, &mut value).unwrap();
+        assert_eq!(value, yaml("routing:\n  egress:\n    default: allow\n"));
+
+        // Version 2 files are typed: unknown keys are load errors.
+        let parsed = parse_config::<crate::RoutingPolicy>(
+            "version: 2\negress:\n  alow: [a.example]\n",
+            ConfigKind::Routing,
+        );
+        assert!(parsed
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `alow`"));
+    }
+
+    #[test]
+    fn migrate_file_rejects_future_versions_and_leaves_current_files_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("routing.yaml");
+        fs::write(&path, "# comment\nversion: 2\negress: {default: deny}\n").unwrap();
+        let migrated = migrate_file(&path, ConfigKind::Routing, true).unwrap();
+        assert!(!migrated.is_upgrade());
+        assert!(fs::read_to_string(&path).unwrap().starts_with("# comment"));
+        assert!(!dir.path().join("routing.yaml.bak").exists());
+
+        fs::write(&path, "version: 99\n").unwrap();
+        let err = migrate_file(&path, ConfigKind::Routing, false).unwrap_err();
+        assert!(err.to_string().contains("version 99 is newer"), "{err}");
+    }
+}
@@ -1,8 +1,29 @@
+pub mod interpolate;
+pub mod layers;
 pub mod loader;
+pub mod migrate;
+pub mod secrets;
+pub mod strict;
 pub mod types;
+pub mod unified;
 
+pub use interpolate::{from_yaml_str, parse_yaml, InterpolationError, YamlError};
+pub use layers::{
+    ConfigDiff, ConfigDiffEntry, ConfigLayers, ConfigSnapshot, EffectiveConfig, EffectiveEntry,
+    Origin, Origins, PathDefault, CONFIG_PATH_VARS, SYSTEM_CONFIG_PATH,
+};
 pub use loader::{load_flags, load_limits, load_models, load_routing};
+pub use migrate::{migrate_file, parse_config, with_version, ConfigKind, Migrated, MigrationError};
+pub use secrets::{redact, redact_json, SecretError, SecretRef};
+pub use strict::{strict_mode, strict_problems, ConfigErrors};
 pub use types::{
-    Asr, FeatureFlags, Latency, Limits, ModelEntry, ModelsFile, RoutingDecision, RoutingPolicy,
-    RoutingRule, Thermal,
+    AllowItem, Asr, CloudFallback, DiskLimits, EgressDefault, EgressPolicy, FeatureFlags, Latency,
+    LimitedTarget, Limits, ModelCost, ModelEntry, ModelsFile, MonitorLimits, PowerLimits,
+    PressureLimits, RoutingDecision, RoutingPolicy, RoutingRule, RoutingRules, SheddingLimits,
+    Thermal,
+};
+pub use unified::{
+    collect_config_from, collect_layered, load_config, load_config_from, load_layered,
+    read_config_file, ConfigFile, ConfigSource, ConfigSources, EmbeddingsSection, FallbackPaths,
+    IndexSection, MemorySection, UnifiedConfig, DEFAULT_CONFIG_PATH,
 };
@@ -0,0 +1,267 @@
+//! Geheimnis-Referenzen in Konfigurationswerten.
+//!
+//! Ein String-Wert `secret://env/<VAR>` oder `secret://keyring/<name>` wird
+//! beim Laden durch das Geheimnis ersetzt – aus der Umgebungsvariable bzw. aus
+//! dem Schlüsselbund der Sitzung (Secret Service, gelesen über `secret-tool`
+//! mit den Attributen `service hauski` und `account <name>`). Fehlt ein
+//! Geheimnis, ist das ein Konfigurationsfehler. Aufgelöste Werte merkt sich der
+//! Prozess, damit `/config/*`, `hauski config effective` und Fehlermeldungen
+//! sie nur als `***` zeigen.
+
+use once_cell::sync::Lazy;
+use serde_yaml_ng::Value;
+use std::{
+    cmp::Reverse,
+    collections::BTreeSet,
+    env, fmt,
+    process::Command,
+    sync::{PoisonError, RwLock},
+};
+use thiserror::Error;
+
+pub const SECRET_PREFIX: &str = "secret://";
+/// `service` attribute of HausKI entries in the keyring.
+pub const KEYRING_SERVICE: &str = "hauski";
+/// Replacement for secrets and tokens in responses and messages.
+pub const REDACTED: &str = "***";
+
+/// Every secret resolved by this process, for redaction.
+static RESOLVED: Lazy<RwLock<BTreeSet<String>>> = Lazy::new(Default::default);
+
+/// Where a `secret://` reference points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    Env(String),
+    Keyring(String),
+}
+
+impl SecretRef {
+    /// `None` if `text` is no `secret://` reference.
+    pub fn parse(text: &str) -> Option<Result<SecretRef, SecretError>> {
+        let rest = text.strip_prefix(SECRET_PREFIX)?;
+        let parsed = match rest.split_once('/') {
+            Some(("env", name)) if is_env_name(name) => Ok(SecretRef::Env(name.to_string())),
+            Some(("keyring", name)) if !name.is_empty() => Ok(SecretRef::Keyring(name.to_string())),
+            _ => Err(SecretError::Invalid(text.to_string())),
+        };
+        Some(parsed)
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretRef::Env(name) => write!(f, "{SECRET_PREFIX}env/{name}"),
+            SecretRef::Keyring(name) => write!(f, "{SECRET_PREFIX}keyring/{name}"),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SecretError {
+    #[error(
+        "invalid secret reference '{0}' (expected secret://env/<VAR> or secret://keyring/<name>)"
+    )]
+    Invalid(String),
+    #[error("{reference} is not available: {reason}")]
+    Unavailable { reference: String, reason: String },
+}
+
+/// Looks a reference up in the environment or the session keyring.
+pub fn lookup_secret(reference: &SecretRef) -> Result<String, SecretError> {
+    let found = match reference {
+        SecretRef::Env(name) => env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "environment variable is not set".to_string()),
+        SecretRef::Keyring(name) => keyring_lookup(name),
+    };
+    found.map_err(|reason| SecretError::Unavailable {
+        reference: reference.to_string(),
+        reason,
+    })
+}
+
+fn keyring_lookup(name: &str) -> Result<String, String> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", KEYRING_SERVICE, "account", name])
+        .output()
+        .map_err(|e| format!("secret-tool could not be started ({e}); install libsecret-tools"))?;
+    let secret = String::from_utf8(output.stdout)
+        .map_err(|_| "keyring entry is not valid UTF-8".to_string())?;
+    let secret = secret.strip_suffix('\n').unwrap_or(&secret);
+    if output.status.success() && !secret.is_empty() {
+        return Ok(secret.to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.trim() {
+        "" => Err(format!(
+            "no keyring entry with service {KEYRING_SERVICE}, account {name}"
+        )),
+        message => Err(format!("secret-tool failed: {message}")),
+    }
+}
+
+/// Replaces every string below `value` that is a `secret://` reference with
+/// the secret returned by `lookup` and remembers it for [`redact`].
+pub fn resolve_secrets(
+    value: &mut Value,
+    lookup: &impl Fn(&SecretRef) -> Result<String, SecretError>,
+) -> Result<(), SecretError> {
+    match value {
+        Value::String(text) => {
+            if let Some(reference) = SecretRef::parse(text) {
+                let secret = lookup(&reference?)?;
+                RESOLVED
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(secret.clone());
+                *value = Value::String(secret);
+            }
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                resolve_secrets(item, lookup)?;
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                resolve_secrets(item, lookup)?;
+            }
+        }
+        Value::Tagged(tagged) => resolve_secrets(&mut tagged.value, lookup)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `text` with every resolved secret replaced by `***`.
+pub fn redact(text: &str) -> String {
+    let resolved = RESOLVED.read().unwrap_or_else(PoisonError::into_inner);
+    let mut secrets: Vec<&String> = resolved.iter().collect();
+    // Longer secrets first, so one containing another is not left half visible.
+    secrets.sort_by_key(|secret| Reverse(secret.len()));
+    let mut redacted = text.to_string();
+    for secret in secrets {
+        if redacted.contains(secret.as_str()) {
+            redacted = redacted.replace(secret.as_str(), REDACTED);
+        }
+    }
+    redacted
+}
+
+/// [`redact`] for all strings below a YAML value.
+pub(crate) fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = redact(text),
+        Value::Sequence(items) => items.iter_mut().for_each(redact_value),
+        Value::Mapping(mapping) => mapping.iter_mut().for_each(|(_, item)| redact_value(item)),
+        Value::Tagged(tagged) => redact_value(&mut tagged.value),
+        _ => {}
+    }
+}
+
+/// [`redact`] for all strings below a JSON value.
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => *text = redact(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        serde_json::Value::Object(map) => map.values_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Whether any string below `value` contains a resolved secret.
+pub(crate) fn contains_secret(value: &Value) -> bool {
+    match value {
+        Value::String(text) => redact(text) != *text,
+        Value::Sequence(items) => items.iter().any(contains_secret),
+        Value::Mapping(mapping) => mapping.iter().any(|(_, item)| contains_secret(item)),
+        Value::Tagged(tagged) => contains_secret(&tagged.value),
+        _ => false,
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(reference: &SecretRef) -> Result<String, SecretError> {
+        match reference {
+            SecretRef::Env(name) if name == "SECRETS_TEST_WEBHOOK" => Ok("wh-4711".into()),
+            SecretRef::Keyring(name) if name == "openai" => Ok("sk-secrets-test-42".into()),
+            _ => Err(SecretError::Unavailable {
+                reference: reference.to_string(),
+                reason: "missing".into(),
+            }),
+        }
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml_ng::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn references_are_parsed_strictly() {
+        assert_eq!(SecretRef::parse("https://example.org"), None);
+        assert_eq!(
+            SecretRef::parse("secret://env/API_KEY"),
+            Some(Ok(SecretRef::Env("API_KEY".into())))
+        );
+        assert_eq!(
+            SecretRef::parse("secret://keyring/openai/prod"),
+            Some(Ok(SecretRef::Keyring("openai/prod".into())))
+        );
+        for invalid in ["secret://env/1X", "secret://vault/x", "secret://keyring/"] {
+            assert!(
+                matches!(
+                    SecretRef::parse(invalid),
+                    Some(Err(SecretError::Invalid(_)))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn whole_string_references_are_resolved_and_redacted() {
+        let mut value = yaml(
+            "api_token: secret://keyring/openai\nhooks: [secret://env/SECRETS_TEST_WEBHOOK]\nnote: see secret://keyring/openai\n",
+        );
+        resolve_secrets(&mut value, &lookup).unwrap();
+        assert_eq!(
+            value,
+            yaml("api_token: sk-secrets-test-42\nhooks: [wh-4711]\nnote: see secret://keyring/openai\n")
+        );
+
+        assert!(contains_secret(&value));
+        assert_eq!(
+            redact("Bearer sk-secrets-test-42 rejected"),
+            "Bearer *** rejected"
+        );
+        let mut json = serde_json::json!({"url": "https://hooks.local/wh-4711", "n": 1});
+        redact_json(&mut json);
+        assert_eq!(
+            json,
+            serde_json::json!({"url": "https://hooks.local/***", "n": 1})
+        );
+    }
+
+    #[test]
+    fn missing_secrets_are_errors() {
+        let mut value = yaml("token: secret://env/SECRETS_TEST_MISSING\n");
+        let err = resolve_secrets(&mut value, &lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "secret://env/SECRETS_TEST_MISSING is not available: missing"
+        );
+    }
+}
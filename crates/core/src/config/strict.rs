@@ -0,0 +1,200 @@
+use super::loader::parse_env_bool;
+use super::unified::UnifiedConfig;
+use crate::egress::EgressGuard;
+use crate::error::HauskiError;
+use hauski_embeddings::EmbedderRegistry;
+use hauski_indexd::IndexState;
+use std::{collections::HashSet, env, fmt};
+
+/// All problems found while loading or checking a configuration, one message each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_slice() {
+            [single] => f.write_str(single),
+            errors => {
+                write!(f, "{} configuration errors:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  - {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl From<ConfigErrors> for HauskiError {
+    fn from(errors: ConfigErrors) -> Self {
+        HauskiError::Config(errors.to_string())
+    }
+}
+
+/// The message of a loader error, without the `Config error:` prefix.
+pub(crate) fn config_message(err: HauskiError) -> String {
+    match err {
+        HauskiError::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// `HAUSKI_CONFIG_STRICT`; invalid values keep strict mode off with a warning.
+pub fn strict_mode() -> bool {
+    let Ok(value) = env::var("HAUSKI_CONFIG_STRICT") else {
+        return false;
+    };
+    parse_env_bool(&value).unwrap_or_else(|| {
+        tracing::warn!(
+            invalid_value = %value,
+            "invalid boolean for HAUSKI_CONFIG_STRICT, strict mode stays off"
+        );
+        false
+    })
+}
+
+/// Problems the server would otherwise paper over with defaults or warnings:
+/// unset `${VAR}` placeholders, an egress policy that disables guarded
+/// requests, broken embedder or voice settings, zero namespace budgets,
+/// percentage thresholds above 100, monitor sampling outside its bounds and
+/// index policies that fail to load.
+pub fn strict_problems(config: &UnifiedConfig) -> Vec<String> {
+    let mut problems = config.unset_variables.clone();
+
+    if let Err(err) = EgressGuard::from_policy(&config.routing) {
+        problems.push(err.to_string());
+    }
+
+    let mut ids = HashSet::new();
+    for model in &config.models.models {
+        if !ids.insert(model.id.as_str()) {
+            problems.push(format!("duplicate model id '{}'", model.id));
+        }
+    }
+    match EmbedderRegistry::new(config.models.embedders.clone()) {
+        Ok(registry) => {
+            if let Err(err) = registry.chain(&config.models.embedder_fallback) {
+                problems.push(format!("embedder_fallback: {err}"));
+            }
+        }
+        Err(err) => problems.push(format!("embedders: {err}")),
+    }
+    if let Err(err) = hauski_tts::VoiceRegistry::new(config.models.voices.clone()) {
+        problems.push(format!("voices: {err}"));
+    }
+
+    for (namespace, budget) in &config.limits.namespaces {
+        if let Err(err) = budget.validate() {
+            problems.push(format!("limits.namespaces.{namespace}: {err}"));
+        }
+    }
+    if let Err(err) = config.limits.pressure.validate() {
+        problems.push(format!("limits.pressure.{err}"));
+    }
+    if let Err(err) = config.limits.monitor.validate() {
+        problems.push(format!("limits.monitor.{err}"));
+    }
+    if config.limits.disk.min_free_pct > 100 {
+        problems.push(format!(
+            "limits.disk.min_free_pct: {} is above 100",
+            config.limits.disk.min_free_pct
+        ));
+    }
+
+    problems.extend(IndexState::check_policy_files(
+        &config.index.trust_policy_path(),
+        &config.index.context_policy_path(),
+    ));
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        parse_config, ConfigKind, ConfigSource, ConfigSources, FeatureFlags, IndexSection, Limits,
+        MemorySection, ModelEntry, ModelsFile,
+    };
+    use std::path::PathBuf;
+
+    fn config(routing: &str) -> UnifiedConfig {
+        let (routing, _, _) = parse_config(routing, ConfigKind::Routing).unwrap();
+        let policies = concat!(env!("CARGO_MANIFEST_DIR"), "/../../policies");
+        let file = || ConfigSource::File(PathBuf::from("test"));
+        UnifiedConfig {
+            path: None,
+            limits: Limits::default(),
+            models: ModelsFile::default(),
+            routing,
+            flags: FeatureFlags::default(),
+            index: IndexSection {
+                path: None,
+                trust_policy: Some(PathBuf::from(policies).join("trust.yaml")),
+                context_policy: Some(PathBuf::from(policies).join("context.yaml")),
+                policy_watch_sec: None,
+            },
+            memory: MemorySection::default(),
+            sources: ConfigSources {
+                limits: file(),
+                models: file(),
+                routing: file(),
+                flags: file(),
+            },
+            origins: Default::default(),
+            files: Default::default(),
+            unset_variables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn shipped_routing_policy_has_no_problems() {
+        let routing = include_str!("../../../../policies/routing.yaml");
+        assert_eq!(strict_problems(&config(routing)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tolerated_errors_are_all_reported() {
+        let mut config =
+            config("version: 2\negress:\n  default: deny\n  allow: [\"https://*.\"]\n");
+        let model = ModelEntry {
+            id: "m".into(),
+            path: "/m".into(),
+            vram_min_gb: None,
+            canary: None,
+            cost: None,
+            url: None,
+            sha256: None,
+        };
+        config.models.models = vec![model.clone(), model];
+        config.models.embedder_fallback = vec!["missing".into()];
+        config.index.context_policy = Some(PathBuf::from("/does/not/exist/context.yaml"));
+        config.limits.monitor.memory_alpha = Some(0.0);
+
+        let problems = strict_problems(&config);
+        for expected in [
+            "invalid host in allow entry 'https://*.'",
+            "duplicate model id 'm'",
+            "embedder_fallback",
+            "context policy /does/not/exist/context.yaml",
+            "limits.monitor.memory_alpha: 0 is outside (0, 1]",
+        ] {
+            assert!(
+                problems.iter().any(|problem| problem.contains(expected)),
+                "missing {expected:?} in {problems:#?}"
+            );
+        }
+    }
+
+    #[test]
+    fn several_errors_are_listed_one_per_line() {
+        let errors = ConfigErrors(vec!["first".into(), "second".into()]);
+        assert_eq!(
+            errors.to_string(),
+            "2 configuration errors:\n  - first\n  - second"
+        );
+        assert_eq!(ConfigErrors(vec!["only".into()]).to_string(), "only");
+    }
+}
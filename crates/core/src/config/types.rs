@@ -1,4 +1,7 @@
+use hauski_indexd::NamespaceBudget;
+use policy::escalation::EscalationConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub const fn default_llm_p95_ms() -> u64 {
     400
@@ -8,6 +11,10 @@ pub const fn default_index_topk20_ms() -> u64 {
     60
 }
 
+pub const fn default_asr_p95_ms() -> u64 {
+    900
+}
+
 pub const fn default_gpu_max_c() -> u64 {
     80
 }
@@ -16,10 +23,66 @@ pub const fn default_dgpu_power_w() -> u64 {
     220
 }
 
+pub const fn default_hysteresis_c() -> u64 {
+    5
+}
+
+pub const fn default_hysteresis_w() -> u64 {
+    20
+}
+
 pub const fn default_wer_max_pct() -> u64 {
     10
 }
 
+pub const fn default_min_free_bytes() -> u64 {
+    2 * 1024 * 1024 * 1024
+}
+
+pub const fn default_min_free_pct() -> u64 {
+    5
+}
+
+pub const fn default_high_pct() -> u64 {
+    90
+}
+
+pub const fn default_pressure_hysteresis_pct() -> u64 {
+    10
+}
+
+pub const fn default_pause_schedules() -> bool {
+    true
+}
+
+pub const fn default_pause_jobs() -> bool {
+    true
+}
+
+pub const fn default_watch_interval_factor() -> u64 {
+    4
+}
+
+pub const fn default_shedding_enabled() -> bool {
+    true
+}
+
+pub const fn default_shedding_sustain_sec() -> u64 {
+    30
+}
+
+pub const fn default_shedding_retry_after_sec() -> u64 {
+    30
+}
+
+pub const fn default_monitor_interval_ms() -> u64 {
+    2_000
+}
+
+pub const fn default_monitor_alpha() -> f32 {
+    0.1
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Limits {
@@ -29,6 +92,19 @@ pub struct Limits {
     pub thermal: Thermal,
     #[serde(default)]
     pub asr: Asr,
+    #[serde(default)]
+    pub disk: DiskLimits,
+    #[serde(default)]
+    pub pressure: PressureLimits,
+    #[serde(default)]
+    pub power: PowerLimits,
+    #[serde(default)]
+    pub monitor: MonitorLimits,
+    #[serde(default)]
+    pub shedding: SheddingLimits,
+    /// Index budgets by namespace; namespaces without an entry are unlimited.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, NamespaceBudget>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +114,8 @@ pub struct Latency {
     pub llm_p95_ms: u64,
     #[serde(default = "default_index_topk20_ms")]
     pub index_topk20_ms: u64,
+    #[serde(default = "default_asr_p95_ms")]
+    pub asr_p95_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +125,16 @@ pub struct Thermal {
     pub gpu_max_c: u64,
     #[serde(default = "default_dgpu_power_w")]
     pub dgpu_power_w: u64,
+    /// The throttle releases once temperatures are this far below `gpu_max_c`.
+    #[serde(default = "default_hysteresis_c")]
+    pub hysteresis_c: u64,
+    /// The throttle releases once power draw is this far below `dgpu_power_w`.
+    #[serde(default = "default_hysteresis_w")]
+    pub hysteresis_w: u64,
+    /// Chat model used instead of the default while throttled; without one,
+    /// chat requests are queued and sent one at a time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle_model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +144,18 @@ pub struct Asr {
     pub wer_max_pct: u64,
 }
 
+/// Free space below which the disks holding memory.db, the index and the
+/// models count as low; `/ready` then reports the core as degraded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiskLimits {
+    #[serde(default = "default_min_free_bytes")]
+    pub min_free_bytes: u64,
+    /// Percent of the disk's capacity (0-100).
+    #[serde(default = "default_min_free_pct")]
+    pub min_free_pct: u64,
+}
+
 // NOTE: We keep a manual `Default` implementation here instead of using
 // `#[derive(Default)]`. All nested structs provide custom defaults and we want
 // this type to stay resilient even if new fields that lack `Default`
@@ -68,6 +168,12 @@ impl Default for Limits {
             latency: Latency::default(),
             thermal: Thermal::default(),
             asr: Asr::default(),
+            disk: DiskLimits::default(),
+            pressure: PressureLimits::default(),
+            power: PowerLimits::default(),
+            monitor: MonitorLimits::default(),
+            shedding: SheddingLimits::default(),
+            namespaces: BTreeMap::new(),
         }
     }
 }
@@ -77,6 +183,7 @@ impl Default for Latency {
         Self {
             llm_p95_ms: default_llm_p95_ms(),
             index_topk20_ms: default_index_topk20_ms(),
+            asr_p95_ms: default_asr_p95_ms(),
         }
     }
 }
@@ -86,6 +193,9 @@ impl Default for Thermal {
         Self {
             gpu_max_c: default_gpu_max_c(),
             dgpu_power_w: default_dgpu_power_w(),
+            hysteresis_c: default_hysteresis_c(),
+            hysteresis_w: default_hysteresis_w(),
+            throttle_model: None,
         }
     }
 }
@@ -98,10 +208,193 @@ impl Default for Asr {
     }
 }
 
+/// CPU load and memory pressure (smoothed, in percent) above which the
+/// monitor publishes `system.cpu_high` and `system.memory_pressure_high`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PressureLimits {
+    #[serde(default = "default_high_pct")]
+    pub cpu_high_pct: u64,
+    #[serde(default = "default_high_pct")]
+    pub memory_high_pct: u64,
+    /// A raised signal clears once the value is this far below its threshold.
+    #[serde(default = "default_pressure_hysteresis_pct")]
+    pub hysteresis_pct: u64,
+}
+
+impl PressureLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        for (key, value) in [
+            ("cpu_high_pct", self.cpu_high_pct),
+            ("memory_high_pct", self.memory_high_pct),
+        ] {
+            if value > 100 {
+                return Err(format!("{key}: {value} is above 100"));
+            }
+            if self.hysteresis_pct > value {
+                return Err(format!(
+                    "hysteresis_pct: {} is above {key} ({value})",
+                    self.hysteresis_pct
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for PressureLimits {
+    fn default() -> Self {
+        Self {
+            cpu_high_pct: default_high_pct(),
+            memory_high_pct: default_high_pct(),
+            hysteresis_pct: default_pressure_hysteresis_pct(),
+        }
+    }
+}
+
+/// Low-power mode while a laptop runs on battery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PowerLimits {
+    /// Enter low-power mode whenever the system runs on battery.
+    #[serde(default)]
+    pub low_power_on_battery: bool,
+    /// Chat model used instead of the default in low-power mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_power_model: Option<String>,
+    /// Scheduled tasks skip their runs in low-power mode; manual triggers
+    /// still run.
+    #[serde(default = "default_pause_schedules")]
+    pub pause_schedules: bool,
+    /// Background jobs (`POST /jobs`) wait as `queued` in low-power mode and
+    /// start once it ends; running attempts finish.
+    #[serde(default = "default_pause_jobs")]
+    pub pause_jobs: bool,
+    /// The config watcher polls this many times less often in low-power mode.
+    #[serde(default = "default_watch_interval_factor")]
+    pub watch_interval_factor: u64,
+}
+
+/// Rejection of batch requests while CPU, memory or GPU pressure lasts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SheddingLimits {
+    #[serde(default = "default_shedding_enabled")]
+    pub enabled: bool,
+    /// Seconds a pressure signal must stay raised before shedding starts.
+    #[serde(default = "default_shedding_sustain_sec")]
+    pub sustain_sec: u64,
+    /// Value of the `Retry-After` header on shed requests.
+    #[serde(default = "default_shedding_retry_after_sec")]
+    pub retry_after_sec: u64,
+}
+
+impl Default for SheddingLimits {
+    fn default() -> Self {
+        Self {
+            enabled: default_shedding_enabled(),
+            sustain_sec: default_shedding_sustain_sec(),
+            retry_after_sec: default_shedding_retry_after_sec(),
+        }
+    }
+}
+
+/// Sampling interval and EMA smoothing of the system monitor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorLimits {
+    #[serde(default = "default_monitor_interval_ms")]
+    pub interval_ms: u64,
+    /// Weight of the newest sample (0 < alpha <= 1; 1 disables smoothing).
+    #[serde(default = "default_monitor_alpha")]
+    pub alpha: f32,
+    /// Overrides `alpha` for the CPU load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_alpha: Option<f32>,
+    /// Overrides `alpha` for the memory pressure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_alpha: Option<f32>,
+}
+
+impl MonitorLimits {
+    pub const MIN_INTERVAL_MS: u64 = 100;
+    pub const MAX_INTERVAL_MS: u64 = 60_000;
+
+    pub fn cpu_alpha(&self) -> f32 {
+        self.cpu_alpha.unwrap_or(self.alpha)
+    }
+
+    pub fn memory_alpha(&self) -> f32 {
+        self.memory_alpha.unwrap_or(self.alpha)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(Self::MIN_INTERVAL_MS..=Self::MAX_INTERVAL_MS).contains(&self.interval_ms) {
+            return Err(format!(
+                "interval_ms: {} is outside {}..={}",
+                self.interval_ms,
+                Self::MIN_INTERVAL_MS,
+                Self::MAX_INTERVAL_MS
+            ));
+        }
+        for (key, value) in [
+            ("alpha", Some(self.alpha)),
+            ("cpu_alpha", self.cpu_alpha),
+            ("memory_alpha", self.memory_alpha),
+        ] {
+            if let Some(value) = value.filter(|value| !(*value > 0.0 && *value <= 1.0)) {
+                return Err(format!("{key}: {value} is outside (0, 1]"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for MonitorLimits {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_monitor_interval_ms(),
+            alpha: default_monitor_alpha(),
+            cpu_alpha: None,
+            memory_alpha: None,
+        }
+    }
+}
+
+impl Default for PowerLimits {
+    fn default() -> Self {
+        Self {
+            low_power_on_battery: false,
+            low_power_model: None,
+            pause_schedules: default_pause_schedules(),
+            pause_jobs: default_pause_jobs(),
+            watch_interval_factor: default_watch_interval_factor(),
+        }
+    }
+}
+
+impl Default for DiskLimits {
+    fn default() -> Self {
+        Self {
+            min_free_bytes: default_min_free_bytes(),
+            min_free_pct: default_min_free_pct(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ModelsFile {
     pub models: Vec<ModelEntry>,
+    /// Embedder-Registry (Provider, Dimension, Token-Limit, Normalisierung).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedders: Vec<hauski_embeddings::EmbedderSpec>,
+    /// Fallback-Kette aus Embedder-IDs; leer = nur der Default-Embedder.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedder_fallback: Vec<String>,
+    /// Piper-Stimmen für `/tts`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub voices: Vec<hauski_tts::VoiceSpec>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,11 +404,185 @@ pub struct ModelEntry {
     pub path: String,
     pub vram_min_gb: Option<u64>,
     pub canary: Option<bool>,
+    /// Optionale Preisangabe für Kosten-Accounting (`/usage`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<ModelCost>,
+    /// Download-Quelle für `hauski models pull`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Erwartete SHA256-Prüfsumme (hex) der Modelldatei.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Kosten pro 1000 Tokens in einer frei wählbaren Währungseinheit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ModelCost {
+    #[serde(default)]
+    pub prompt_per_1k: f64,
+    #[serde(default)]
+    pub completion_per_1k: f64,
+}
+
+/// `policies/routing.yaml` or the `routing` section of `hauski.yml`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingPolicy {
+    /// Outbound HTTP rules; without this section egress is not restricted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress: Option<EgressPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<RoutingRules>,
+}
+
+impl RoutingPolicy {
+    /// A policy that denies all egress.
+    pub fn deny_all() -> Self {
+        Self {
+            egress: Some(EgressPolicy {
+                default: EgressDefault::Deny,
+                ..EgressPolicy::default()
+            }),
+            routing: None,
+        }
+    }
+
+    /// `routing.cloud_fallback`, or the defaults (disabled).
+    pub fn cloud_fallback(&self) -> CloudFallback {
+        self.routing
+            .as_ref()
+            .and_then(|routing| routing.cloud_fallback.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// The `egress` section; entries are validated by `EgressGuard::from_policy`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EgressPolicy {
+    #[serde(default)]
+    pub default: EgressDefault,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<AllowItem>,
+    /// Deny loopback, private and link-local targets, also under `default: allow`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub block_private: bool,
+    /// Local upstreams exempt from `block_private`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_private: Vec<String>,
+    /// Gateway for all guarded requests, e.g. `socks5h://gateway:1080`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub audit: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EgressDefault {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// One `egress.allow` item: a plain entry or one with limits.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AllowItem {
+    Target(String),
+    Limited(LimitedTarget),
+}
+
+impl AllowItem {
+    pub fn target(&self) -> &str {
+        match self {
+            AllowItem::Target(target) => target.trim(),
+            AllowItem::Limited(limited) => limited.target.trim(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AllowItem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Untagged enums only report "did not match any variant"; keep the
+        // error of the mapping form instead.
+        match serde_yaml_ng::Value::deserialize(deserializer)? {
+            serde_yaml_ng::Value::String(target) => Ok(AllowItem::Target(target)),
+            value @ serde_yaml_ng::Value::Mapping(_) => serde_yaml_ng::from_value(value)
+                .map(AllowItem::Limited)
+                .map_err(serde::de::Error::custom),
+            _ => Err(serde::de::Error::custom(
+                "egress.allow items must be strings or { target, ... } mappings",
+            )),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LimitedTarget {
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+}
+
+/// The `routing` section.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
-#[serde(transparent)]
-pub struct RoutingPolicy(pub serde_yaml_ng::Value);
+#[serde(deny_unknown_fields)]
+pub struct RoutingRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer_local: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cloud_fallback: Option<CloudFallback>,
+}
+
+/// `routing.cloud_fallback`: when a request may escalate to a cloud provider
+/// (`POST /policy/escalate`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct CloudFallback {
+    pub enabled: bool,
+    /// Conditions like `task == 'ocr' && size_mb > 200`; not evaluated yet.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub only_if: Vec<String>,
+    pub intents: Vec<String>,
+    pub blocked_flags: Vec<String>,
+    pub min_budget_ms: u64,
+    pub require_consent: bool,
+}
+
+impl Default for CloudFallback {
+    fn default() -> Self {
+        let defaults = EscalationConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            only_if: Vec::new(),
+            intents: defaults.intents,
+            blocked_flags: defaults.blocked_flags,
+            min_budget_ms: defaults.min_budget_ms,
+            require_consent: defaults.require_consent,
+        }
+    }
+}
+
+impl From<&CloudFallback> for EscalationConfig {
+    fn from(fallback: &CloudFallback) -> Self {
+        Self {
+            enabled: fallback.enabled,
+            intents: fallback.intents.clone(),
+            blocked_flags: fallback.blocked_flags.clone(),
+            min_budget_ms: fallback.min_budget_ms,
+            require_consent: fallback.require_consent,
+        }
+    }
+}
 
 pub type RoutingRule = serde_yaml_ng::Value;
 pub type RoutingDecision = serde_yaml_ng::Value;
@@ -127,4 +594,10 @@ pub struct FeatureFlags {
     pub chat_upstream_url: Option<String>,
     pub chat_model: Option<String>,
     pub events_token: Option<String>,
+    /// Mounts `/memory/*` (requires `memory_token`).
+    pub memory_api: bool,
+    /// Bearer token for `/memory/*`; without it the memory API answers 403.
+    pub memory_token: Option<String>,
+    /// Bearer token for the whole API (see `api_auth`); unset = open.
+    pub api_token: Option<String>,
 }
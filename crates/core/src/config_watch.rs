@@ -0,0 +1,308 @@
+//! Überwachung der Konfigurationsdateien im laufenden Server.
+//!
+//! Alle `HAUSKI_CONFIG_WATCH_SEC` Sekunden (Default 10, `0` schaltet ab) vergleicht der Server
+//! die Änderungszeiten von `hauski.yml` (System und Benutzer) und den Einzeldateien. Nach einer
+//! Änderung lädt er alle Schichten neu und prüft sie wie beim Start, inklusive Strikt-Modus.
+//! Ungültige Dateien werden geloggt und verworfen, die laufende Konfiguration bleibt.
+//!
+//! Die Routing-Policy wird im Betrieb getauscht. Alles andere – und `routing.egress.proxy` –
+//! liest der Server nur beim Start; solche Änderungen meldet `/ready` als „config drift
+//! detected“ mit den betroffenen Schlüsseln, `config_drift` steht dann auf 1.
+
+use crate::{
+    config::{load_layered, ConfigLayers, ConfigSnapshot},
+    redact, AppState, EgressGuard,
+};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use std::{
+    convert::Infallible,
+    fmt,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// Default polling interval in seconds.
+const DEFAULT_WATCH_SEC: u64 = 10;
+/// Routing keys the egress client only reads at startup.
+const RESTART_ROUTING_KEYS: &[&str] = &["routing.egress.proxy"];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct ReloadLabels {
+    result: &'static str,
+}
+
+impl EncodeLabelSet for ReloadLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("result", self.result).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+/// Reloads after a file change by result, and whether a restart is pending.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConfigWatchMetrics {
+    reloads: Family<ReloadLabels, Counter>,
+    drift: Gauge,
+}
+
+impl ConfigWatchMetrics {
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "config_reloads",
+            "Total number of configuration reloads after a file change by result (applied/restart_required/invalid)",
+            self.reloads.clone(),
+        );
+        registry.register(
+            "config_drift",
+            "1 while changed configuration files need a restart to take effect",
+            self.drift.clone(),
+        );
+    }
+
+    fn record(&self, result: &'static str) {
+        self.reloads.get_or_create(&ReloadLabels { result }).inc();
+    }
+}
+
+/// What a reload did with the changed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Reload {
+    /// The files load to the configuration already in place.
+    Unchanged,
+    /// Everything that changed is in use.
+    Applied,
+    /// These keys changed but only take effect after a restart.
+    RestartRequired(Vec<String>),
+    /// The files do not load; the running configuration stays.
+    Invalid(String),
+}
+
+/// Reloads `layers` after its files changed.
+pub(crate) struct ConfigWatcher {
+    layers: ConfigLayers,
+    /// The configuration the files set at the last successful load.
+    last: Option<ConfigSnapshot>,
+}
+
+impl ConfigWatcher {
+    pub(crate) fn new(state: &AppState, layers: ConfigLayers) -> Self {
+        Self {
+            layers,
+            last: state.config_snapshot(),
+        }
+    }
+
+    /// Every file a layered load reads, whether it exists or not.
+    fn files(&self) -> Vec<PathBuf> {
+        let fallbacks = &self.layers.fallbacks;
+        self.layers
+            .system
+            .iter()
+            .chain(&self.layers.user)
+            .chain([
+                &fallbacks.limits,
+                &fallbacks.models,
+                &fallbacks.routing,
+                &fallbacks.flags,
+            ])
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn reload(&mut self, state: &AppState) -> Reload {
+        let reload = self.load(state);
+        match &reload {
+            Reload::Unchanged => {}
+            Reload::Applied => {
+                tracing::info!("configuration files changed, new configuration applied");
+                state.0.config_watch.record("applied");
+            }
+            Reload::RestartRequired(keys) => {
+                tracing::warn!(keys = ?keys, "configuration files changed, restart to apply");
+                state.0.config_watch.record("restart_required");
+            }
+            Reload::Invalid(error) => {
+                tracing::warn!(%error, "configuration files changed but are invalid, keeping the running configuration");
+                state.0.config_watch.record("invalid");
+            }
+        }
+        let drift = !state.config_drift().is_empty();
+        state.0.config_watch.drift.set(i64::from(drift));
+        reload
+    }
+
+    fn load(&mut self, state: &AppState) -> Reload {
+        let config = match load_layered(&self.layers) {
+            Ok(config) => config,
+            Err(err) => return Reload::Invalid(redact(&err.to_string())),
+        };
+        if let Err(err) = EgressGuard::from_policy(&config.routing) {
+            return Reload::Invalid(redact(&format!("routing: {err}")));
+        }
+        let next = config.snapshot();
+        let changed = match &self.last {
+            Some(last) => last.changed_keys(&next),
+            None => Vec::new(),
+        };
+        if self.last.is_some() && changed.is_empty() {
+            return Reload::Unchanged;
+        }
+
+        // Only routing changes in the files replace the policy, so runtime
+        // allowlist edits survive changes elsewhere.
+        if changed.iter().any(|key| hot_swappable(key)) {
+            state
+                .update_routing(|_| Ok::<_, Infallible>(config.routing.clone()))
+                .unwrap_or_else(|never| match never {});
+        }
+        self.last = Some(next.clone());
+
+        let drift: Vec<String> = match state.config_snapshot() {
+            Some(running) => running
+                .changed_keys(&next)
+                .into_iter()
+                .filter(|key| !hot_swappable(key))
+                .collect(),
+            None => Vec::new(),
+        };
+        if drift.is_empty() {
+            state.replace_config_snapshot(next);
+        }
+        state.set_config_drift(drift.clone());
+        if drift.is_empty() {
+            Reload::Applied
+        } else {
+            Reload::RestartRequired(drift)
+        }
+    }
+}
+
+fn hot_swappable(key: &str) -> bool {
+    key.starts_with("routing.") && !RESTART_ROUTING_KEYS.contains(&key)
+}
+
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Polls the files of `layers` until the state is dropped; see the module docs.
+pub(crate) fn watch(state: &AppState, layers: ConfigLayers) {
+    let interval = crate::env_u64("HAUSKI_CONFIG_WATCH_SEC", DEFAULT_WATCH_SEC);
+    if interval == 0 {
+        return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let mut watcher = ConfigWatcher::new(state, layers);
+    let files = watcher.files();
+    let inner = Arc::downgrade(&state.0);
+    runtime.spawn(async move {
+        let mut last = modified(&files);
+        let mut sleep_sec = interval;
+        loop {
+            tokio::time::sleep(Duration::from_secs(sleep_sec)).await;
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            let state = AppState(inner);
+            sleep_sec = if state.system_monitor().low_power() {
+                interval.saturating_mul(state.limits().power.watch_interval_factor.max(1))
+            } else {
+                interval
+            };
+            let current = modified(&files);
+            if current == last {
+                continue;
+            }
+            last = current;
+            watcher.reload(&state);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_app_from_config, collect_layered, FallbackPaths};
+    use axum::http::HeaderValue;
+    use serial_test::serial;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn routing_is_swapped_other_changes_need_a_restart() {
+        let dir = TempDir::new().unwrap();
+        let layers = ConfigLayers {
+            system: None,
+            user: None,
+            fallbacks: FallbackPaths {
+                limits: write(&dir, "limits.yaml", "latency:\n  llm_p95_ms: 111\n"),
+                models: write(&dir, "models.yml", "models: []\n"),
+                routing: write(&dir, "routing.yaml", "egress:\n  default: deny\n"),
+                flags: write(&dir, "flags.yaml", "{}\n"),
+            },
+            overrides: Vec::new(),
+        };
+        let config = collect_layered(&layers).unwrap();
+        let (_app, state) =
+            build_app_from_config(config, false, HeaderValue::from_static("http://x"));
+        let mut watcher = ConfigWatcher::new(&state, layers.clone());
+        assert_eq!(watcher.reload(&state), Reload::Unchanged);
+
+        write(
+            &dir,
+            "routing.yaml",
+            "egress:\n  default: deny\n  allow: [api.example]\n",
+        );
+        assert_eq!(watcher.reload(&state), Reload::Applied);
+        assert!(state
+            .egress_guard(crate::egress::CALLER_WEBHOOKS)
+            .unwrap()
+            .ensure_allowed("https://api.example/")
+            .is_ok());
+
+        write(&dir, "routing.yaml", "egress:\n  default: [deny\n");
+        assert!(matches!(watcher.reload(&state), Reload::Invalid(_)));
+        assert_eq!(state.routing().egress.unwrap().allow.len(), 1);
+        write(
+            &dir,
+            "routing.yaml",
+            "egress:\n  default: deny\n  allow: [api.example]\n",
+        );
+        assert_eq!(watcher.reload(&state), Reload::Unchanged);
+
+        write(&dir, "limits.yaml", "latency:\n  llm_p95_ms: 222\n");
+        assert_eq!(
+            watcher.reload(&state),
+            Reload::RestartRequired(vec!["limits.latency.llm_p95_ms".into()])
+        );
+        assert_eq!(state.limits().latency.llm_p95_ms, 111);
+        assert_eq!(state.config_drift(), ["limits.latency.llm_p95_ms"]);
+        assert_eq!(state.0.config_watch.drift.get(), 1);
+
+        // Reverting the file clears the drift.
+        write(&dir, "limits.yaml", "latency:\n  llm_p95_ms: 111\n");
+        assert_eq!(watcher.reload(&state), Reload::Applied);
+        assert!(state.config_drift().is_empty());
+        assert_eq!(state.0.config_watch.drift.get(), 0);
+    }
+}
@@ -0,0 +1,454 @@
+//! Egress-Allowlist zur Laufzeit und Egress-Audit.
+//!
+//! - `GET /egress/allowlist` zeigt die wirksame Allowlist aus
+//!   `policies/routing.yaml`.
+//! - `POST /egress/allowlist` und `DELETE /egress/allowlist?entry=…` ändern
+//!   `egress.allow` ohne Neustart und schreiben die Datei zurück (Kommentare
+//!   gehen dabei verloren). Beides setzt ein `api_token` voraus; jede Änderung
+//!   landet als `egress_allowlist` im Audit-Log.
+//! - Mit `egress.audit: true` schreibt jeder Guard seine Entscheidungen
+//!   (erlaubt, verweigert, gedrosselt) als Ereignis `egress` ins Audit-Log der
+//!   Policy-Datenbank: Aufrufer, URL ohne Query, angewandte Regel und Ergebnis.
+//!   `GET /egress/audit` liest sie nach Host und Zeitraum gefiltert zurück.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, OnceLock, Weak},
+    time::Instant,
+};
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use hauski_embeddings::{EmbedError, HttpTransport, TransportFuture};
+use policy::audit::{AuditEvent, EventQuery};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::egress::{
+    self, AllowlistEditError, AllowlistEntry, EgressAudit, EgressDecision, EgressGuard,
+    EntryLimits, GuardedRequestError, CALLER_EMBEDDINGS,
+};
+use crate::{with_version, AppState, AppStateInner, ConfigKind, RoutingPolicy};
+
+/// Event name of egress decisions in the audit log.
+const EVENT_EGRESS: &str = "egress";
+/// Event name of runtime allowlist changes in the audit log.
+const EVENT_ALLOWLIST: &str = "egress_allowlist";
+const DEFAULT_AUDIT_LIMIT: usize = 50;
+
+/// Hook that appends decisions to the policy audit log; the caller is stored
+/// as the event kind.
+pub(crate) fn audit_hook(state: &AppState) -> EgressAudit {
+    let log = state.policy().log();
+    EgressAudit::new(move |decision: &EgressDecision| {
+        let event = AuditEvent {
+            id: ulid::Ulid::new().to_string(),
+            ts: Utc::now(),
+            event: EVENT_EGRESS.to_string(),
+            kind: Some(decision.caller.to_string()),
+            detail: json!(decision),
+        };
+        if let Err(err) = log.record_event(&event) {
+            tracing::warn!(error = %err, host = %decision.host, "egress decision could not be audited");
+        }
+    })
+}
+
+/// Embedder transport that sends through [`AppState::upstream_client`].
+/// Embedders are built before the state, so it is bound afterwards; until
+/// then, and once the state is gone, requests are denied.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EgressTransport {
+    state: Arc<OnceLock<Weak<AppStateInner>>>,
+}
+
+impl EgressTransport {
+    pub(crate) fn bind(&self, state: &AppState) {
+        // Weak, so embedders held by the state do not keep it alive.
+        let _ = self.state.set(Arc::downgrade(&state.0));
+    }
+}
+
+impl HttpTransport for EgressTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let url = request.url().as_str().to_string();
+            let denied = |reason: String| EmbedError::Denied {
+                url: url.clone(),
+                reason,
+            };
+            let state = self
+                .state
+                .get()
+                .and_then(Weak::upgrade)
+                .map(AppState)
+                .ok_or_else(|| denied("service state unavailable".to_string()))?;
+            let client = state.upstream_client(CALLER_EMBEDDINGS).map_err(&denied)?;
+            client.execute(request).await.map_err(|err| match err {
+                GuardedRequestError::Guard(err) => denied(err.to_string()),
+                GuardedRequestError::Http(source) => EmbedError::Request {
+                    url: url.clone(),
+                    source,
+                },
+            })
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "EgressErrorResponse")]
+pub struct EgressErrorResponse {
+    pub error: String,
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, Json(EgressErrorResponse { error })).into_response()
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(
+    title = "EgressAllowlistResponse",
+    example = json!({
+        "enforced": true,
+        "block_private": false,
+        "entries": [{"target": "https://*.example.org:443"}, {"target": "https://api.matrix.example:443", "requests_per_minute": 60}]
+    })
+)]
+pub struct EgressAllowlistResponse {
+    /// `egress.default: deny`; otherwise the allowlist only feeds limits and audit rules.
+    pub enforced: bool,
+    pub block_private: bool,
+    /// Sorted by target.
+    #[schema(value_type = Vec<Object>)]
+    pub entries: Vec<AllowlistEntry>,
+}
+
+impl EgressAllowlistResponse {
+    fn from_guard(guard: &EgressGuard) -> Self {
+        Self {
+            enforced: guard.is_enforced(),
+            block_private: guard.blocks_private(),
+            entries: guard.entries(),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/egress/allowlist",
+    tag = "core",
+    responses(
+        (status = 200, description = "Effective egress allowlist", body = EgressAllowlistResponse),
+        (status = 500, description = "Routing policy invalid", body = EgressErrorResponse)
+    )
+)]
+pub async fn egress_allowlist_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let (status, response) = match EgressGuard::from_policy(&state.routing()) {
+        Ok(guard) => (
+            StatusCode::OK,
+            Json(EgressAllowlistResponse::from_guard(&guard)).into_response(),
+        ),
+        Err(err) => {
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            (status, error(status, err.to_string()))
+        }
+    };
+    state.record_http_observation(Method::GET, "/egress/allowlist", status, started);
+    response
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(
+    title = "EgressAllowRequest",
+    example = json!({"entry": "https://api.matrix.example", "requests_per_minute": 60, "reason": "matrix bridge"})
+)]
+pub struct EgressAllowRequest {
+    /// Allow entry as in `egress.allow`, e.g. `https://*.example.org:443`.
+    pub entry: String,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub max_concurrent: Option<u32>,
+    /// Stored in the audit log.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EgressRevokeQuery {
+    /// Entry to remove; matches regardless of case and an explicit default port.
+    pub entry: String,
+    /// Stored in the audit log.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "EgressAllowlistChangeResponse")]
+pub struct EgressAllowlistChangeResponse {
+    /// Id of the `egress_allowlist` audit event.
+    pub audit_id: String,
+    /// Whether the change was written back to the routing file.
+    pub persisted: bool,
+    #[serde(flatten)]
+    pub allowlist: EgressAllowlistResponse,
+}
+
+#[derive(Debug)]
+enum ChangeError {
+    Edit(AllowlistEditError),
+    Persist(anyhow::Error),
+}
+
+impl From<AllowlistEditError> for ChangeError {
+    fn from(err: AllowlistEditError) -> Self {
+        Self::Edit(err)
+    }
+}
+
+/// Writes `routing` to `path` via a temporary file, so readers never see a
+/// partial policy.
+fn write_routing(path: &Path, routing: &RoutingPolicy) -> anyhow::Result<()> {
+    let routing = serde_yaml_ng::to_value(routing).context("serialize routing policy")?;
+    let yaml = serde_yaml_ng::to_string(&with_version(ConfigKind::Routing, &routing))
+        .context("serialize routing policy")?;
+    let tmp = path.with_extension("yaml.tmp");
+    fs::write(&tmp, yaml).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(())
+}
+
+/// Applies `edit` to the routing policy, persists it and audits the change.
+async fn change_allowlist(
+    state: AppState,
+    method: Method,
+    action: &'static str,
+    detail: Value,
+    edit: impl FnOnce(&RoutingPolicy) -> Result<RoutingPolicy, AllowlistEditError> + Send + 'static,
+) -> Response {
+    let started = Instant::now();
+    let response = change_allowlist_inner(&state, action, detail, edit).await;
+    state.record_http_observation(method, "/egress/allowlist", response.status(), started);
+    response
+}
+
+async fn change_allowlist_inner(
+    state: &AppState,
+    action: &'static str,
+    mut detail: Value,
+    edit: impl FnOnce(&RoutingPolicy) -> Result<RoutingPolicy, AllowlistEditError> + Send + 'static,
+) -> Response {
+    if state.flags().api_token.is_none() {
+        return error(
+            StatusCode::FORBIDDEN,
+            "changing the egress allowlist requires api_token".to_string(),
+        );
+    }
+
+    let path = state.routing_path();
+    let persisted = path.is_some();
+    let updated = {
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || {
+            state.update_routing(|current| {
+                let next = edit(current)?;
+                if let Some(path) = &path {
+                    write_routing(path, &next).map_err(ChangeError::Persist)?;
+                }
+                Ok(next)
+            })
+        })
+        .await
+    };
+    let routing = match updated {
+        Ok(Ok(routing)) => routing,
+        Ok(Err(ChangeError::Edit(err))) => {
+            let status = match err {
+                AllowlistEditError::Duplicate(_) => StatusCode::CONFLICT,
+                AllowlistEditError::NotFound(_) => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            return error(status, err.to_string());
+        }
+        Ok(Err(ChangeError::Persist(err))) => {
+            tracing::error!(error = %err, "egress allowlist could not be persisted");
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "routing policy could not be written".to_string(),
+            );
+        }
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+
+    detail["action"] = json!(action);
+    detail["persisted"] = json!(persisted);
+    let event = AuditEvent {
+        id: ulid::Ulid::new().to_string(),
+        ts: Utc::now(),
+        event: EVENT_ALLOWLIST.to_string(),
+        kind: None,
+        detail,
+    };
+    let log = state.policy().log();
+    let stored = {
+        let event = event.clone();
+        tokio::task::spawn_blocking(move || log.record_event(&event))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    if let Err(err) = stored {
+        // The allowlist already changed; the missing entry must not go unnoticed.
+        tracing::error!(id = %event.id, error = %err, "egress allowlist change could not be audited");
+        return error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "audit entry could not be recorded".to_string(),
+        );
+    }
+    tracing::info!(action, detail = %event.detail, "egress allowlist changed");
+
+    let guard = match EgressGuard::from_policy(&routing) {
+        Ok(guard) => guard,
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+    Json(EgressAllowlistChangeResponse {
+        audit_id: event.id,
+        persisted,
+        allowlist: EgressAllowlistResponse::from_guard(&guard),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/egress/allowlist",
+    tag = "core",
+    request_body = EgressAllowRequest,
+    responses(
+        (status = 200, description = "Entry added", body = EgressAllowlistChangeResponse),
+        (status = 400, description = "Invalid entry or limits", body = EgressErrorResponse),
+        (status = 403, description = "No api_token configured", body = EgressErrorResponse),
+        (status = 409, description = "Entry already allowed", body = EgressErrorResponse),
+        (status = 500, description = "Routing file or audit log not writable", body = EgressErrorResponse)
+    )
+)]
+pub async fn egress_allow_handler(
+    State(state): State<AppState>,
+    Json(req): Json<EgressAllowRequest>,
+) -> Response {
+    let detail = json!({
+        "entry": req.entry.trim(),
+        "requests_per_minute": req.requests_per_minute,
+        "max_concurrent": req.max_concurrent,
+        "reason": req.reason,
+    });
+    let limits = EntryLimits {
+        requests_per_minute: req.requests_per_minute,
+        max_concurrent: req.max_concurrent,
+    };
+    change_allowlist(state, Method::POST, "add", detail, move |routing| {
+        egress::add_allow_entry(routing, &req.entry, limits)
+    })
+    .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/egress/allowlist",
+    tag = "core",
+    params(EgressRevokeQuery),
+    responses(
+        (status = 200, description = "Entry removed", body = EgressAllowlistChangeResponse),
+        (status = 400, description = "Invalid entry", body = EgressErrorResponse),
+        (status = 403, description = "No api_token configured", body = EgressErrorResponse),
+        (status = 404, description = "Entry not in the allowlist", body = EgressErrorResponse),
+        (status = 500, description = "Routing file or audit log not writable", body = EgressErrorResponse)
+    )
+)]
+pub async fn egress_revoke_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EgressRevokeQuery>,
+) -> Response {
+    let detail = json!({"entry": query.entry.trim(), "reason": query.reason});
+    change_allowlist(state, Method::DELETE, "remove", detail, move |routing| {
+        egress::remove_allow_entry(routing, &query.entry)
+    })
+    .await
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EgressAuditQuery {
+    /// Normalized destination host, e.g. `api.example.org`.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Only decisions at or after this time.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only decisions before this time.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[param(default = 50, maximum = 500)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "EgressAuditResponse")]
+pub struct EgressAuditResponse {
+    /// Newest first; `detail` holds `url`, `host`, `rule` and `outcome`.
+    #[schema(value_type = Vec<Object>)]
+    pub events: Vec<AuditEvent>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/egress/audit",
+    tag = "core",
+    params(EgressAuditQuery),
+    responses(
+        (status = 200, description = "Audited egress decisions", body = EgressAuditResponse),
+        (status = 500, description = "Audit log unreadable", body = EgressErrorResponse)
+    )
+)]
+pub async fn egress_audit_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EgressAuditQuery>,
+) -> Response {
+    let started = Instant::now();
+    let query = EventQuery {
+        event: Some(EVENT_EGRESS.to_string()),
+        host: query.host.map(|host| host.trim().to_ascii_lowercase()),
+        since: query.since,
+        until: query.until,
+        limit: query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT),
+    };
+    let log = state.policy().log();
+    let result = tokio::task::spawn_blocking(move || log.query_events(&query))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    let (status, response) = match result {
+        Ok(events) => (
+            StatusCode::OK,
+            Json(EgressAuditResponse { events }).into_response(),
+        ),
+        Err(err) => {
+            tracing::warn!(error = %err, "egress audit query failed");
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            (status, error(status, "audit log unreadable".to_string()))
+        }
+    };
+    state.record_http_observation(Method::GET, "/egress/audit", status, started);
+    response
+}
@@ -0,0 +1,151 @@
+//! Entscheidung lokal vs. Cloud (`POST /policy/escalate`).
+//!
+//! Prüft mit `policy::escalation`, ob eine Anfrage an einen Cloud-Anbieter
+//! eskalieren darf: Intent, Sensitivitäts-Flags, Latenzbudget und Zustimmung
+//! des Nutzers. Im Safe-Mode wird jede Eskalation verweigert. Die Antwort
+//! enthält das Ziel samt Begründung je Prüfung; jede Entscheidung geht als
+//! `policy.escalation` auf den Chronik-Bus.
+//!
+//! Konfiguration in `policies/routing.yaml` unter `routing.cloud_fallback`
+//! (`enabled`, `intents`, `blocked_flags`, `min_budget_ms`, `require_consent`).
+
+use std::time::Instant;
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use policy::escalation::{self, EscalationCheck, EscalationConfig, EscalationRequest};
+use serde::{Deserialize, Serialize};
+// Used by utoipa's #[schema(example = json!(...))] attribute macros
+#[allow(unused_imports)]
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{chronik, AppState};
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(
+    title = "EscalateRequest",
+    example = json!({"intent": "chat", "sensitivity": [], "latency_budget_ms": 3000, "consent": true})
+)]
+pub struct EscalateRequest {
+    /// Intent from `POST /intent`, e.g. `chat`.
+    #[serde(default)]
+    pub intent: Option<String>,
+    /// Content sensitivity flags, e.g. `pii`, `secret`, `health`.
+    #[serde(default)]
+    pub sensitivity: Vec<String>,
+    #[serde(default)]
+    pub latency_budget_ms: Option<u64>,
+    /// Whether the user agreed to send this request to a cloud provider.
+    #[serde(default)]
+    pub consent: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(
+    title = "EscalateResponse",
+    example = json!({
+        "target": "local",
+        "why": "staying local: user consent missing",
+        "safe_mode": false,
+        "checks": [{"check": "consent", "passed": false, "detail": "user consent missing"}]
+    })
+)]
+pub struct EscalateResponse {
+    /// `local` or `cloud`.
+    pub target: String,
+    pub why: String,
+    pub safe_mode: bool,
+    /// Every check in evaluation order.
+    #[schema(value_type = Vec<Object>)]
+    pub checks: Vec<EscalationCheckView>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EscalationCheckView {
+    pub check: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl From<EscalationCheck> for EscalationCheckView {
+    fn from(check: EscalationCheck) -> Self {
+        Self {
+            check: check.check.to_string(),
+            passed: check.passed,
+            detail: check.detail,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/policy/escalate",
+    request_body = EscalateRequest,
+    responses(
+        (status = 200, description = "Whether the request may escalate to a cloud provider, with rationale", body = EscalateResponse)
+    ),
+    tag = "core"
+)]
+pub async fn escalate_handler(
+    State(state): State<AppState>,
+    Json(req): Json<EscalateRequest>,
+) -> Response {
+    let started = Instant::now();
+    let safe_mode = state.safe_mode();
+    let request = EscalationRequest {
+        intent: req.intent,
+        sensitivity: req.sensitivity,
+        latency_budget_ms: req.latency_budget_ms,
+        consent: req.consent,
+        safe_mode,
+    };
+    let decision = escalation::decide(
+        &EscalationConfig::from(&state.routing().cloud_fallback()),
+        &request,
+    );
+    chronik::publish(
+        &state,
+        chronik::SOURCE_POLICY,
+        "policy.escalation",
+        &json!({"request": request, "decision": decision}),
+    );
+
+    let response = EscalateResponse {
+        target: decision.target.as_str().to_string(),
+        why: decision.why,
+        safe_mode,
+        checks: decision.checks.into_iter().map(Into::into).collect(),
+    };
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/policy/escalate", status, started);
+    (status, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RoutingPolicy;
+
+    #[test]
+    fn reads_cloud_fallback_section() {
+        let policy: RoutingPolicy = serde_yaml_ng::from_str(
+            "routing:\n  cloud_fallback:\n    enabled: true\n    only_if: []\n    intents: [chat]\n",
+        )
+        .unwrap();
+        let config = EscalationConfig::from(&policy.cloud_fallback());
+        assert!(config.enabled);
+        assert_eq!(config.intents, ["chat"]);
+        assert!(config.require_consent);
+
+        assert!(serde_yaml_ng::from_str::<RoutingPolicy>(
+            "routing:\n  cloud_fallback:\n    enabled: maybe\n"
+        )
+        .is_err());
+        assert!(!EscalationConfig::from(&RoutingPolicy::default().cloud_fallback()).enabled);
+    }
+}
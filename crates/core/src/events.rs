@@ -59,7 +59,7 @@ pub async fn event_handler(
         let valid = match auth_header {
             Some(h) if h.starts_with("Bearer ") => {
                 let provided = h.trim_start_matches("Bearer ");
-                provided == token
+                crate::api_auth::token_matches(provided, token)
             }
             _ => false,
         };
@@ -83,7 +83,7 @@ pub async fn event_handler(
         return StatusCode::BAD_REQUEST;
     }
 
-    match crate::EgressGuard::from_policy(&state.routing()) {
+    match state.egress_guard(crate::egress::CALLER_EVENTS) {
         Ok(guard) => {
             if let Err(e) = guard.ensure_allowed(&event.payload.url) {
                 tracing::warn!(
@@ -217,7 +217,9 @@ egress:
         };
 
         let yaml = r#"
-egress: "not-a-map"
+egress:
+  default: deny
+  allow: ["https://*."]
 "#;
         let routing: RoutingPolicy = serde_yaml_ng::from_str(yaml).unwrap();
         let (app, _state) = test_app_with_routing(flags, routing);
@@ -247,6 +249,67 @@ egress: "not-a-map"
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_egress_denials_are_audited_when_enabled() {
+        use http_body_util::BodyExt;
+
+        let flags = FeatureFlags {
+            events_token: Some("secret123".into()),
+            ..FeatureFlags::default()
+        };
+        let yaml = r#"
+egress:
+  default: deny
+  audit: true
+  allow: []
+"#;
+        let routing: RoutingPolicy = serde_yaml_ng::from_str(yaml).unwrap();
+        let (app, _state) = test_app_with_routing(flags, routing);
+        let host = format!("audit-{}.example", ulid::Ulid::new()).to_ascii_lowercase();
+
+        let event_payload = json!({
+            "type": "knowledge.observatory.published.v1",
+            "payload": {
+                "url": format!("https://{host}/obs.json?token=secret"),
+                "generated_at": "2023-10-27T10:00:00Z"
+            }
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/events")
+                    .method(Method::POST)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::AUTHORIZATION, "Bearer secret123")
+                    .body(Body::from(event_payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .oneshot(
+                Request::get(format!("/egress/audit?host={host}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let audit: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let events = audit["events"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["kind"], "events");
+        let detail = &events[0]["detail"];
+        assert_eq!(detail["url"], format!("https://{host}/obs.json"));
+        assert_eq!(detail["rule"], "default deny");
+        assert_eq!(detail["outcome"], "denied");
+    }
+
     #[tokio::test]
     #[serial_test::serial]
     async fn test_https_enforcement_returns_bad_request() {
@@ -0,0 +1,477 @@
+//! Dokumente einlesen: `POST /ingest/file`.
+//!
+//! Text und Gliederung extrahiert `hauski-ingest` (PDF, HTML, DOCX, EPUB);
+//! dieses Modul nimmt die Datei entgegen, zerlegt sie in Chunks und übergibt
+//! das Dokument als `ingest`-Job an indexd (Embeddings inklusive, Verlauf
+//! über `/jobs/{id}`).
+//!
+//! Eingaben:
+//!   multipart/form-data – Feld `file` mit dem Dokument, dazu optional die
+//!                         Textfelder `format`, `namespace`, `doc_id`,
+//!                         `chunk_chars`, `dry_run`, `webhook_url`
+//!   application/json    – dieselben Felder, statt `file` ein `path` auf dem
+//!                         Host; erlaubt nur unterhalb von `HAUSKI_INGEST_INPUT_DIRS`
+//!
+//! Das Format ergibt sich aus dem Inhalt, ersatzweise aus der Dateiendung.
+//! Jeder Chunk trägt Section, Überschriften, Seite (PDF) und Zeichen-Offsets
+//! in den Metadaten; Provenienz ist `source_ref.origin = "ingest"` mit
+//! Dateiname bzw. Pfad. Ohne `doc_id` leitet sich die Id aus dem Inhalt ab,
+//! erneutes Einlesen derselben Datei ersetzt das Dokument.
+//!
+//! Mit `dry_run: true` wird nichts indexiert; die Antwort enthält die Chunks.
+//!
+//! Konfiguration:
+//!   HAUSKI_INGEST_INPUT_DIRS     (ohne Default; Verzeichnisse für `path`, getrennt wie PATH)
+//!   HAUSKI_INGEST_MAX_UPLOAD_MB  (Default 50)
+//!   HAUSKI_INGEST_NAMESPACE      (Default `docs`)
+//!   HAUSKI_INGEST_CHUNK_CHARS    (Default 1200)
+
+use std::{env, ops::RangeInclusive, path::PathBuf, time::Instant};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequest, Multipart, State},
+    http::{header, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use hauski_ingest::{Chunk, Format, IngestError};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{
+    input_dirs::InputDirs,
+    jobs::{self, JobCreateRequest, JobKind, JobRecord},
+    rejection::{internal, Rejection, MAX_JSON_BYTES},
+    AppState,
+};
+
+const INPUT_DIRS_VAR: &str = "HAUSKI_INGEST_INPUT_DIRS";
+const DEFAULT_MAX_UPLOAD_MB: u64 = 50;
+/// Accepted `chunk_chars`.
+const CHUNK_CHARS: RangeInclusive<usize> = 100..=16_000;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct FileLabels {
+    format: &'static str,
+    outcome: &'static str,
+}
+
+#[derive(Debug)]
+pub struct IngestService {
+    /// Directories `path` references must lie in.
+    input_dirs: InputDirs,
+    max_upload_bytes: u64,
+    namespace: String,
+    chunk_chars: usize,
+    files: Family<FileLabels, Counter>,
+}
+
+impl IngestService {
+    pub(crate) fn new(input_dirs: Vec<PathBuf>, max_upload_bytes: u64) -> Self {
+        Self {
+            input_dirs: InputDirs::new(INPUT_DIRS_VAR, input_dirs),
+            max_upload_bytes,
+            namespace: hauski_ingest::DEFAULT_NAMESPACE.to_string(),
+            chunk_chars: hauski_ingest::DEFAULT_CHUNK_CHARS,
+            files: Family::default(),
+        }
+    }
+
+    pub(crate) fn load_from_env() -> Self {
+        let max_upload_mb = crate::env_u64("HAUSKI_INGEST_MAX_UPLOAD_MB", DEFAULT_MAX_UPLOAD_MB);
+        let mut service = Self::new(Vec::new(), max_upload_mb << 20);
+        service.input_dirs = InputDirs::from_env(INPUT_DIRS_VAR);
+        if let Some(namespace) = env::var("HAUSKI_INGEST_NAMESPACE")
+            .ok()
+            .filter(|namespace| !namespace.trim().is_empty())
+        {
+            service.namespace = namespace.trim().to_string();
+        }
+        let chunk_chars = crate::env_u64("HAUSKI_INGEST_CHUNK_CHARS", service.chunk_chars as u64);
+        if CHUNK_CHARS.contains(&(chunk_chars as usize)) {
+            service.chunk_chars = chunk_chars as usize;
+        } else {
+            tracing::warn!(
+                chunk_chars,
+                "ignoring HAUSKI_INGEST_CHUNK_CHARS outside {}..={}",
+                CHUNK_CHARS.start(),
+                CHUNK_CHARS.end()
+            );
+        }
+        service
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "ingest_files",
+            "Total number of /ingest/file requests by format and outcome (queued/dry_run/rejected)",
+            self.files.clone(),
+        );
+    }
+
+    fn count(&self, format: Option<Format>, outcome: &'static str) {
+        self.files
+            .get_or_create(&FileLabels {
+                format: format.map_or("unknown", Format::as_str),
+                outcome,
+            })
+            .inc();
+    }
+}
+
+/// Options of an ingestion; with JSON bodies `path` names the file.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IngestFileRequest {
+    /// Document on the host below `HAUSKI_INGEST_INPUT_DIRS` (JSON only).
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Default: detected from the content, then the file extension.
+    #[serde(default)]
+    pub format: Option<Format>,
+    /// Default: `HAUSKI_INGEST_NAMESPACE`.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Default: `ingest-` and a hash of the content.
+    #[serde(default)]
+    pub doc_id: Option<String>,
+    /// Maximum chunk size in characters; default: `HAUSKI_INGEST_CHUNK_CHARS`.
+    #[serde(default)]
+    pub chunk_chars: Option<usize>,
+    /// Return the chunks instead of indexing them.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Called with the final job record, as with `POST /jobs`.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+impl IngestFileRequest {
+    /// Applies a multipart text field.
+    fn set(&mut self, name: &str, value: String) -> Result<(), String> {
+        match name {
+            "format" => self.format = Some(value.parse()?),
+            "namespace" => self.namespace = Some(value),
+            "doc_id" => self.doc_id = Some(value),
+            "chunk_chars" => {
+                self.chunk_chars = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid chunk_chars value '{value}'"))?,
+                )
+            }
+            "dry_run" => {
+                self.dry_run = match value.trim() {
+                    "true" | "1" | "yes" => true,
+                    "false" | "0" | "no" | "" => false,
+                    other => return Err(format!("invalid dry_run value '{other}'")),
+                }
+            }
+            "webhook_url" => self.webhook_url = Some(value),
+            "path" => return Err("path references need a JSON body".into()),
+            other => return Err(format!("unknown field '{other}'")),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IngestFileResponse {
+    pub doc_id: String,
+    pub namespace: String,
+    pub format: Format,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Page count (PDF only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<u32>,
+    pub sections: usize,
+    /// Characters of extracted text.
+    pub chars: usize,
+    pub chunks: usize,
+    /// The `ingest` job indexing the chunks; absent with `dry_run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job: Option<JobRecord>,
+    /// The chunks, with `dry_run` only.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preview: Vec<Chunk>,
+}
+
+/// File to ingest with the name recorded as `source_ref.id`.
+struct Input {
+    source: String,
+    bytes: Vec<u8>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/ingest/file",
+    tag = "core",
+    request_body(
+        content = IngestFileRequest,
+        description = "JSON with `path`, or multipart/form-data with the document in `file` and the other fields as text"
+    ),
+    responses(
+        (status = 200, description = "Dry run: extracted document and its chunks", body = IngestFileResponse),
+        (status = 202, description = "Document extracted, ingest job queued", body = IngestFileResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Path outside HAUSKI_INGEST_INPUT_DIRS"),
+        (status = 404, description = "Path not found"),
+        (status = 413, description = "File larger than HAUSKI_INGEST_MAX_UPLOAD_MB or an archive entry too large"),
+        (status = 415, description = "Not a PDF, HTML, DOCX or EPUB file"),
+        (status = 422, description = "Malformed, encrypted or without extractable text")
+    )
+)]
+pub async fn ingest_file_handler(
+    State(state): State<AppState>,
+    request: Request<Body>,
+) -> Response {
+    let started = Instant::now();
+    let response = match ingest_file(&state, request).await {
+        Ok(response) => {
+            let status = if response.job.is_some() {
+                StatusCode::ACCEPTED
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(response)).into_response()
+        }
+        Err((status, error)) => (status, Json(json!({ "error": error }))).into_response(),
+    };
+    state.record_http_observation(Method::POST, "/ingest/file", response.status(), started);
+    response
+}
+
+async fn ingest_file(
+    state: &AppState,
+    request: Request<Body>,
+) -> Result<IngestFileResponse, Rejection> {
+    let service = state.ingest();
+    let multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/form-data"));
+    let (params, input) = if multipart {
+        let multipart = Multipart::from_request(request, &())
+            .await
+            .map_err(|err| (err.status(), err.body_text()))?;
+        read_upload(&service, multipart).await?
+    } else {
+        let body = to_bytes(request.into_body(), MAX_JSON_BYTES)
+            .await
+            .map_err(|err| (StatusCode::PAYLOAD_TOO_LARGE, err.to_string()))?;
+        let params: IngestFileRequest = serde_json::from_slice(&body)
+            .map_err(|err| (StatusCode::BAD_REQUEST, format!("invalid request: {err}")))?;
+        let path = params.path.as_deref().ok_or((
+            StatusCode::BAD_REQUEST,
+            "path or a multipart upload in `file` is required".to_string(),
+        ))?;
+        let path = service.input_dirs.resolve(path)?;
+        let size = tokio::fs::metadata(&path).await.map_err(internal)?.len();
+        if size > service.max_upload_bytes {
+            return Err(too_large(&service));
+        }
+        let bytes = tokio::fs::read(&path).await.map_err(internal)?;
+        let input = Input {
+            source: path.display().to_string(),
+            bytes,
+        };
+        (params, input)
+    };
+
+    let chunk_chars = params.chunk_chars.unwrap_or(service.chunk_chars);
+    if !CHUNK_CHARS.contains(&chunk_chars) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "chunk_chars must be within {}..={}",
+                CHUNK_CHARS.start(),
+                CHUNK_CHARS.end()
+            ),
+        ));
+    }
+    let doc_id = match params.doc_id.as_deref().map(str::trim) {
+        Some("") => return Err((StatusCode::BAD_REQUEST, "doc_id is empty".to_string())),
+        Some(doc_id) => doc_id.to_string(),
+        None => hauski_ingest::doc_id(&input.bytes),
+    };
+    let namespace = params
+        .namespace
+        .clone()
+        .unwrap_or_else(|| service.namespace.clone());
+
+    let Input { source, bytes } = input;
+    let name = source.clone();
+    let requested = params.format;
+    let extracted = tokio::task::spawn_blocking(move || {
+        let format = match requested {
+            Some(format) => format,
+            None => Format::detect(Some(&name), &bytes)?,
+        };
+        let document = hauski_ingest::extract(&bytes, format)?;
+        let chunks = document.chunks(chunk_chars);
+        Ok::<_, IngestError>((document, chunks))
+    })
+    .await
+    .map_err(internal)?;
+    let (document, chunks) = match extracted {
+        Ok(extracted) => extracted,
+        Err(err) => {
+            service.count(err.format().or(requested), "rejected");
+            tracing::info!(%source, error = %err, "document rejected");
+            return Err(rejection(err));
+        }
+    };
+
+    let mut response = IngestFileResponse {
+        doc_id,
+        namespace,
+        format: document.format,
+        title: document.title.clone(),
+        pages: document.pages,
+        sections: document.sections.len(),
+        chars: document.chars(),
+        chunks: chunks.len(),
+        job: None,
+        preview: Vec::new(),
+    };
+    if params.dry_run {
+        service.count(Some(document.format), "dry_run");
+        response.preview = chunks;
+        return Ok(response);
+    }
+
+    let upsert = document.upsert_request(
+        &chunks,
+        &response.doc_id,
+        &response.namespace,
+        hauski_ingest::source_ref(&source),
+    );
+    let job = jobs::submit(
+        state,
+        JobCreateRequest {
+            kind: JobKind::Ingest,
+            documents: vec![serde_json::to_value(upsert).map_err(internal)?],
+            namespace: None,
+            dry_run: false,
+            webhook_url: params.webhook_url,
+        },
+    )
+    .await
+    .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+    service.count(Some(document.format), "queued");
+    response.job = Some(job);
+    Ok(response)
+}
+
+/// Reads the `file` field (up to the upload limit) and collects the options.
+async fn read_upload(
+    service: &IngestService,
+    mut multipart: Multipart,
+) -> Result<(IngestFileRequest, Input), Rejection> {
+    let mut params = IngestFileRequest::default();
+    let mut input = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| (err.status(), err.body_text()))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name != "file" {
+            let value = field
+                .text()
+                .await
+                .map_err(|err| (err.status(), err.body_text()))?;
+            params
+                .set(&name, value)
+                .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+            continue;
+        }
+
+        let source = field.file_name().unwrap_or("upload").to_string();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|err| (err.status(), err.body_text()))?
+        {
+            if (bytes.len() + chunk.len()) as u64 > service.max_upload_bytes {
+                return Err(too_large(service));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        input = Some(Input { source, bytes });
+    }
+    let input = input.ok_or((
+        StatusCode::BAD_REQUEST,
+        "multipart upload without `file`".to_string(),
+    ))?;
+    Ok((params, input))
+}
+
+fn too_large(service: &IngestService) -> Rejection {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "file exceeds {} MB (HAUSKI_INGEST_MAX_UPLOAD_MB)",
+            service.max_upload_bytes >> 20
+        ),
+    )
+}
+
+fn rejection(err: IngestError) -> Rejection {
+    let status = match &err {
+        IngestError::Unsupported(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        IngestError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        IngestError::Malformed { .. } | IngestError::Encrypted(_) | IngestError::Empty(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+    };
+    (status, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipart_fields_are_validated() {
+        let mut params = IngestFileRequest::default();
+        params.set("format", "HTM".into()).unwrap();
+        params.set("chunk_chars", " 800".into()).unwrap();
+        params.set("dry_run", "yes".into()).unwrap();
+        assert_eq!(params.format, Some(Format::Html));
+        assert_eq!(params.chunk_chars, Some(800));
+        assert!(params.dry_run);
+        assert!(params.set("format", "odt".into()).is_err());
+        assert!(params.set("dry_run", "maybe".into()).is_err());
+        assert!(params.set("path", "/etc/passwd".into()).is_err());
+        assert!(params.set("index", "true".into()).is_err());
+    }
+
+    #[test]
+    fn extraction_errors_map_to_statuses() {
+        let status = |err| rejection(err).0;
+        assert_eq!(
+            status(IngestError::Unsupported("notes.md".into())),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+        assert_eq!(
+            status(IngestError::Encrypted(Format::Pdf)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(IngestError::Empty(Format::Pdf)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Dateien auf dem Host als Eingabe: JSON-Anfragen mit `path` statt Upload
+//! sind nur unterhalb der Verzeichnisse einer `HAUSKI_*_INPUT_DIRS`-Variable
+//! erlaubt (getrennt wie PATH, ohne Default).
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use axum::http::StatusCode;
+
+#[derive(Debug)]
+pub(crate) struct InputDirs {
+    /// Variable the directories come from, named in rejections.
+    var: &'static str,
+    /// Canonical directories `path` references must lie in.
+    dirs: Vec<PathBuf>,
+}
+
+impl InputDirs {
+    pub(crate) fn new(var: &'static str, dirs: Vec<PathBuf>) -> Self {
+        Self { var, dirs }
+    }
+
+    /// Reads `var`; directories that do not exist are skipped with a warning.
+    pub(crate) fn from_env(var: &'static str) -> Self {
+        let dirs = env::var_os(var)
+            .map(|dirs| env::split_paths(&dirs).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .filter_map(|dir| match fs::canonicalize(&dir) {
+                Ok(dir) => Some(dir),
+                Err(err) => {
+                    tracing::warn!(var, dir = %dir.display(), error = %err, "ignoring input dir");
+                    None
+                }
+            })
+            .collect();
+        Self::new(var, dirs)
+    }
+
+    /// Canonical path of the file `path`, which must lie below one of the
+    /// directories. Paths outside them are rejected before revealing whether
+    /// they exist.
+    pub(crate) fn resolve(&self, path: &str) -> Result<PathBuf, (StatusCode, String)> {
+        if self.dirs.is_empty() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("path references are disabled (set {})", self.var),
+            ));
+        }
+        let requested = PathBuf::from(shellexpand::tilde(path).as_ref());
+        let allowed = |path: &Path| self.dirs.iter().any(|dir| path.starts_with(dir));
+        let forbidden = || {
+            (
+                StatusCode::FORBIDDEN,
+                format!("{path} is outside {}", self.var),
+            )
+        };
+        let not_found = || (StatusCode::NOT_FOUND, format!("{path} not found"));
+        match fs::canonicalize(&requested) {
+            Ok(canonical) if !allowed(&canonical) => Err(forbidden()),
+            Ok(canonical) if canonical.is_file() => Ok(canonical),
+            Ok(_) => Err(not_found()),
+            Err(_) if requested.is_absolute() && allowed(&requested) => Err(not_found()),
+            Err(_) => Err(forbidden()),
+        }
+    }
+}
@@ -0,0 +1,510 @@
+//! Intent-Klassifikation für Freitext (`POST /intent`).
+//!
+//! Brücke zwischen Sprache und Policy: Freitext wird in eine kleine,
+//! konfigurierbare Taxonomie (Default: remind, search, execute, chat)
+//! eingeordnet. Ist ein lokales Modell konfiguriert (`HAUSKI_CHAT_UPSTREAM_URL`
+//! und `HAUSKI_CHAT_MODEL`), liefert es die Scores; sonst – oder wenn die
+//! Modellantwort unbrauchbar ist – greift eine Keyword-Heuristik.
+//!
+//! Die Antwort enthält neben den Scores die `features`, die unverändert an die
+//! Policy-Schicht (`/v1/policy/decide`) übergeben werden können.
+//!
+//! Konfiguration:
+//!   HAUSKI_INTENT_TAXONOMY_PATH (Default ./policies/intents.yaml)
+
+use std::{collections::BTreeMap, fs, path::Path, time::Instant};
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+// Used by utoipa's #[schema(example = json!(...))] attribute macros
+#[allow(unused_imports)]
+use serde_json::json;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::{
+    chat::{ChatMessage, ChatRole},
+    chat_upstream::call_ollama_chat,
+    egress::CALLER_CHAT,
+    AppState,
+};
+
+const DEFAULT_TAXONOMY_PATH: &str = "policies/intents.yaml";
+const MAX_TEXT_CHARS: usize = 4_000;
+/// Smoothing so that unmatched intents keep a non-zero score.
+const HEURISTIC_SMOOTHING: f32 = 0.1;
+const FALLBACK_BONUS: f32 = 0.5;
+/// Shorter keywords must match a whole word ("run" must not hit "runbook").
+const MIN_PREFIX_KEYWORD_LEN: usize = 4;
+
+const TIME_WORDS: &[&str] = &[
+    "today",
+    "tomorrow",
+    "tonight",
+    "next week",
+    "o'clock",
+    "heute",
+    "morgen",
+    "übermorgen",
+    "nächste woche",
+    "uhr",
+    "montag",
+    "monday",
+    "friday",
+    "freitag",
+];
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct IntentDefinition {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct IntentTaxonomy {
+    pub intents: Vec<IntentDefinition>,
+    #[serde(default)]
+    pub fallback: Option<String>,
+}
+
+impl Default for IntentTaxonomy {
+    fn default() -> Self {
+        let intent = |id: &str, description: &str, keywords: &[&str]| IntentDefinition {
+            id: id.to_string(),
+            description: description.to_string(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        };
+        Self {
+            intents: vec![
+                intent(
+                    "remind",
+                    "Erinnerungen, Termine, Fristen",
+                    &["remind", "erinner", "termin", "deadline"],
+                ),
+                intent(
+                    "search",
+                    "Wissen oder Dokumente finden",
+                    &["find", "search", "such", "where", "welche"],
+                ),
+                intent(
+                    "execute",
+                    "Aktion auf dem System ausführen",
+                    &["run", "execute", "restart", "deploy", "starte"],
+                ),
+                intent("chat", "Freie Unterhaltung", &["hallo", "hello", "danke"]),
+            ],
+            fallback: Some("chat".to_string()),
+        }
+    }
+}
+
+impl IntentTaxonomy {
+    pub fn load_from_env() -> Self {
+        let path = std::env::var("HAUSKI_INTENT_TAXONOMY_PATH")
+            .ok()
+            .unwrap_or_else(|| DEFAULT_TAXONOMY_PATH.to_string());
+        load_taxonomy(Path::new(&path))
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.intents.iter().map(|intent| intent.id.as_str())
+    }
+
+    fn fallback(&self) -> Option<&str> {
+        self.fallback
+            .as_deref()
+            .filter(|id| self.ids().any(|known| known == *id))
+    }
+
+    /// Keyword-based scores, normalised to sum 1.
+    pub fn heuristic_scores(&self, text: &str) -> BTreeMap<String, f32> {
+        let lowered = text.to_lowercase();
+        let words: Vec<&str> = lowered
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|w| !w.is_empty())
+            .collect();
+        let fallback = self.fallback();
+
+        let raw: BTreeMap<String, f32> = self
+            .intents
+            .iter()
+            .map(|intent| {
+                let hits = intent
+                    .keywords
+                    .iter()
+                    .filter(|keyword| keyword_matches(&lowered, &words, keyword))
+                    .count() as f32;
+                let bonus = if Some(intent.id.as_str()) == fallback {
+                    FALLBACK_BONUS
+                } else {
+                    0.0
+                };
+                (intent.id.clone(), hits + HEURISTIC_SMOOTHING + bonus)
+            })
+            .collect();
+        normalise(raw).unwrap_or_default()
+    }
+
+    /// Restricts model scores to known intents and normalises them.
+    fn model_scores(&self, reply: &str) -> Option<BTreeMap<String, f32>> {
+        #[derive(Deserialize)]
+        struct ModelReply {
+            scores: BTreeMap<String, f32>,
+        }
+        let start = reply.find('{')?;
+        let end = reply.rfind('}')?;
+        let parsed: ModelReply = serde_json::from_str(reply.get(start..=end)?).ok()?;
+        let raw = self
+            .ids()
+            .map(|id| {
+                let score = parsed.scores.get(id).copied().unwrap_or(0.0);
+                (
+                    id.to_string(),
+                    if score.is_finite() {
+                        score.max(0.0)
+                    } else {
+                        0.0
+                    },
+                )
+            })
+            .collect();
+        normalise(raw)
+    }
+
+    fn model_prompt(&self) -> String {
+        let mut prompt = String::from(
+            "Classify the user's text into the following intents. Reply with JSON only, \
+             in the form {\"scores\": {\"<intent>\": <probability 0..1>}}.\n",
+        );
+        for intent in &self.intents {
+            prompt.push_str(&format!("- {}: {}\n", intent.id, intent.description));
+        }
+        prompt
+    }
+}
+
+fn keyword_matches(lowered: &str, words: &[&str], keyword: &str) -> bool {
+    let keyword = keyword.to_lowercase();
+    if keyword.contains(char::is_whitespace) {
+        lowered.contains(&keyword)
+    } else {
+        words.iter().any(|word| {
+            *word == keyword
+                || (keyword.chars().count() >= MIN_PREFIX_KEYWORD_LEN && word.starts_with(&keyword))
+        })
+    }
+}
+
+fn normalise(raw: BTreeMap<String, f32>) -> Option<BTreeMap<String, f32>> {
+    let sum: f32 = raw.values().sum();
+    if sum <= 0.0 {
+        return None;
+    }
+    Some(raw.into_iter().map(|(id, v)| (id, v / sum)).collect())
+}
+
+fn load_taxonomy(path: &Path) -> IntentTaxonomy {
+    if !path.exists() {
+        return IntentTaxonomy::default();
+    }
+    let parsed = match fs::read_to_string(path) {
+        Ok(text) => match crate::config::from_yaml_str::<IntentTaxonomy>(&text, path) {
+            Ok(taxonomy) => taxonomy,
+            Err(err) => {
+                tracing::warn!("intent taxonomy parse failed: {err} – using defaults");
+                return IntentTaxonomy::default();
+            }
+        },
+        Err(err) => {
+            tracing::warn!("intent taxonomy read failed: {err} – using defaults");
+            return IntentTaxonomy::default();
+        }
+    };
+    if parsed.intents.is_empty() {
+        tracing::warn!("intent taxonomy is empty – using defaults");
+        return IntentTaxonomy::default();
+    }
+    parsed
+}
+
+// ---------------------- HTTP ----------------------
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(title = "IntentRequest", example = json!({"text":"Erinnere mich morgen um 9 an das Standup"}))]
+pub struct IntentRequest {
+    pub text: String,
+    /// Ask the local model (if configured); `false` forces the heuristic.
+    #[serde(default = "default_use_model")]
+    pub use_model: bool,
+}
+
+fn default_use_model() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IntentSource {
+    Model,
+    Heuristic,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(
+    title = "IntentResponse",
+    example = json!({
+        "intent": "remind",
+        "confidence": 0.71,
+        "scores": {"chat": 0.12, "execute": 0.05, "remind": 0.71, "search": 0.12},
+        "source": "heuristic",
+        "features": {
+            "intent": "remind",
+            "intent_confidence": 0.71,
+            "intent_scores": {"chat": 0.12, "execute": 0.05, "remind": 0.71, "search": 0.12},
+            "word_count": 8,
+            "is_question": false,
+            "has_time_reference": true
+        }
+    })
+)]
+pub struct IntentResponse {
+    pub intent: String,
+    pub confidence: f32,
+    pub scores: BTreeMap<String, f32>,
+    pub source: IntentSource,
+    /// Feature object for the policy layer (`features` of `/v1/policy/decide`).
+    #[schema(value_type = Object)]
+    pub features: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "IntentErrorResponse", example = json!({"error":"text must not be empty"}))]
+pub struct IntentErrorResponse {
+    pub error: String,
+}
+
+/// Builds the policy feature object from text and scores.
+fn policy_features(
+    text: &str,
+    intent: &str,
+    confidence: f32,
+    scores: &BTreeMap<String, f32>,
+) -> Value {
+    let lowered = text.to_lowercase();
+    let trimmed = lowered.trim();
+    json!({
+        "intent": intent,
+        "intent_confidence": confidence,
+        "intent_scores": scores,
+        "word_count": trimmed.split_whitespace().count(),
+        "is_question": trimmed.ends_with('?'),
+        "has_time_reference": TIME_WORDS.iter().any(|w| trimmed.contains(w))
+            || trimmed.chars().any(|c| c.is_ascii_digit()),
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/intent",
+    request_body = IntentRequest,
+    responses(
+        (status = 200, description = "Intent scores and policy features", body = IntentResponse),
+        (status = 400, description = "Invalid intent request", body = IntentErrorResponse)
+    ),
+    tag = "core"
+)]
+pub async fn intent_handler(
+    State(state): State<AppState>,
+    Json(req): Json<IntentRequest>,
+) -> Response {
+    let started = Instant::now();
+    let text = req.text.trim();
+
+    let invalid = if text.is_empty() {
+        Some("text must not be empty".to_string())
+    } else if text.chars().count() > MAX_TEXT_CHARS {
+        Some(format!("text must not exceed {MAX_TEXT_CHARS} characters"))
+    } else {
+        None
+    };
+    if let Some(error) = invalid {
+        let status = StatusCode::BAD_REQUEST;
+        state.record_http_observation(Method::POST, "/intent", status, started);
+        return (status, Json(IntentErrorResponse { error })).into_response();
+    }
+
+    let taxonomy = state.intents();
+    let model_scores = if req.use_model {
+        classify_with_model(&state, &taxonomy, text).await
+    } else {
+        None
+    };
+    let (scores, source) = match model_scores {
+        Some(scores) => (scores, IntentSource::Model),
+        None => (taxonomy.heuristic_scores(text), IntentSource::Heuristic),
+    };
+
+    // BTreeMap order makes ties deterministic (alphabetical first wins).
+    let (intent, confidence) = scores
+        .iter()
+        .fold(None::<(&String, f32)>, |best, (id, score)| match best {
+            Some((_, best_score)) if best_score >= *score => best,
+            _ => Some((id, *score)),
+        })
+        .map(|(id, score)| (id.clone(), score))
+        .unwrap_or_default();
+
+    let features = policy_features(text, &intent, confidence, &scores);
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/intent", status, started);
+
+    (
+        status,
+        Json(IntentResponse {
+            intent,
+            confidence,
+            scores,
+            source,
+            features,
+        }),
+    )
+        .into_response()
+}
+
+async fn classify_with_model(
+    state: &AppState,
+    taxonomy: &IntentTaxonomy,
+    text: &str,
+) -> Option<BTreeMap<String, f32>> {
+    let chat_cfg = state.chat_cfg();
+    let (base_url, model) = (
+        chat_cfg.upstream_url.as_deref()?,
+        chat_cfg.model.as_deref()?,
+    );
+    let messages = [
+        ChatMessage {
+            role: ChatRole::System,
+            content: taxonomy.model_prompt(),
+        },
+        ChatMessage {
+            role: ChatRole::User,
+            content: text.to_string(),
+        },
+    ];
+    let client = state
+        .upstream_client(CALLER_CHAT)
+        .inspect_err(|err| tracing::debug!(error = %err, "intent model unreachable"))
+        .ok()?;
+    match call_ollama_chat(&client, base_url, model, &messages).await {
+        Ok(completion) => {
+            let scores = taxonomy.model_scores(&completion.content);
+            if scores.is_none() {
+                tracing::debug!(
+                    model,
+                    "intent model reply unusable – falling back to heuristic"
+                );
+            }
+            scores
+        }
+        Err(err) => {
+            tracing::debug!(model, error = %err, "intent model call failed – falling back to heuristic");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best(scores: &BTreeMap<String, f32>) -> &str {
+        scores
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(id, _)| id.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn heuristic_picks_matching_intent() {
+        let taxonomy = IntentTaxonomy::default();
+        assert_eq!(
+            best(&taxonomy.heuristic_scores("Erinnere mich morgen an den Termin")),
+            "remind"
+        );
+        assert_eq!(
+            best(&taxonomy.heuristic_scores("Where can I find the backup runbook?")),
+            "search"
+        );
+        assert_eq!(
+            best(&taxonomy.heuristic_scores("restart the indexd service")),
+            "execute"
+        );
+    }
+
+    #[test]
+    fn heuristic_falls_back_without_keywords() {
+        let taxonomy = IntentTaxonomy::default();
+        let scores = taxonomy.heuristic_scores("Das Wetter ist schön");
+        assert_eq!(best(&scores), "chat");
+        let sum: f32 = scores.values().sum();
+        assert!((sum - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn short_keywords_match_whole_words_only() {
+        let taxonomy = IntentTaxonomy::default();
+        // "runbook" starts with "run" but must not count as execute.
+        let scores = taxonomy.heuristic_scores("runbook plans");
+        assert_eq!(best(&scores), "chat");
+    }
+
+    #[test]
+    fn model_reply_is_restricted_to_known_intents() {
+        let taxonomy = IntentTaxonomy::default();
+        let scores = taxonomy
+            .model_scores(
+                "Sure! {\"scores\": {\"remind\": 0.6, \"search\": 0.2, \"bogus\": 5.0}} done",
+            )
+            .unwrap();
+        assert_eq!(scores.len(), 4);
+        assert!(!scores.contains_key("bogus"));
+        assert!((scores["remind"] - 0.75).abs() < 1e-5);
+        assert_eq!(scores["chat"], 0.0);
+
+        assert!(taxonomy.model_scores("no json here").is_none());
+        assert!(taxonomy
+            .model_scores("{\"scores\": {\"remind\": 0}}")
+            .is_none());
+    }
+
+    #[test]
+    fn features_describe_text() {
+        let scores = BTreeMap::from([("remind".to_string(), 1.0)]);
+        let features = policy_features("Call mom tomorrow?", "remind", 1.0, &scores);
+        assert_eq!(features["intent"], "remind");
+        assert_eq!(features["word_count"], 3);
+        assert_eq!(features["is_question"], true);
+        assert_eq!(features["has_time_reference"], true);
+    }
+
+    #[test]
+    fn repo_taxonomy_parses() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../policies/intents.yaml");
+        let taxonomy = load_taxonomy(&path);
+        assert_eq!(
+            taxonomy.ids().collect::<Vec<_>>(),
+            ["remind", "search", "execute", "chat"]
+        );
+        assert_eq!(taxonomy.fallback(), Some("chat"));
+        assert!(taxonomy.intents.iter().all(|i| !i.keywords.is_empty()));
+    }
+}
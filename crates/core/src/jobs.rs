@@ -0,0 +1,1562 @@
+//! Hintergrund-Jobs: `POST /jobs`, `GET /jobs`, `GET /jobs/{id}`,
+//! `GET /jobs/{id}/events` (SSE) und `DELETE /jobs/{id}` (Abbruch).
+//!
+//! Jobs laufen als Tokio-Tasks mit eigenem `CancellationToken` und melden ihren
+//! Fortschritt in Prozent. Jeder Statuswechsel wird als JSON unter `job:<id>`
+//! im Memory-Store abgelegt, damit Job-Records einen Neustart überdauern.
+//!
+//! Ausführung ist at-least-once: Jeder Job steht zusätzlich in der dauerhaften
+//! Task-Queue des Memory-Stores (`task_queue`), bis er abgeschlossen ist.
+//! Fehlgeschlagene Versuche (Fehler oder Panic) werden mit exponentiellem
+//! Backoff wiederholt; nach `HAUSKI_JOB_MAX_ATTEMPTS` landet der Job als Dead
+//! Letter in der Queue (`GET /jobs/queue`). Beim Serverstart nimmt
+//! [`resume_queued`] unterbrochene und noch wartende Jobs wieder auf.
+//!
+//! Unterstützte Arten:
+//!   ingest          – Bulk-Upsert von Dokumenten (Format wie `/index/upsert`); Chunks
+//!                     ohne `embedding` bettet der Default-Embedder parallel ein
+//!   retention_sweep – löscht Dokumente älter als `max_age_seconds` der Retention-Config
+//!
+//! Im Stromsparmodus (`limits.power.pause_jobs`, Default an) beginnt kein
+//! neuer Versuch: Jobs bleiben `queued`, bis der Modus endet.
+//!
+//! Nach Abschluss geht der Job-Record als `job.completed` über die
+//! Webhook-Outbox (siehe `outbox`) an `webhook_url` und alle Abonnenten
+//! (nur Ziele, die die Egress-Policy erlaubt).
+//!
+//! Konfiguration:
+//!   HAUSKI_JOB_RECORD_TTL_SEC (Default 604800 = 7 Tage; 0 = ohne TTL)
+//!   HAUSKI_JOB_MAX_ATTEMPTS   (Default 3)
+//!   HAUSKI_JOB_RETRY_BASE_MS  (Default 1000; verdoppelt sich je Versuch, max. 10 Minuten)
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use hauski_embeddings::{embed_parallel, EmbedderChain};
+use hauski_indexd::{ForgetFilter, IndexState, UpsertRequest};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family},
+};
+use serde::{Deserialize, Serialize};
+// Used by utoipa's #[schema(example = json!(...))] attribute macros
+#[allow(unused_imports)]
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::{
+    chronik, egress, notify, outbox,
+    progress::{sse_response, ProgressEvent, ProgressSink},
+    task_queue::{QueueEntry, RetryPolicy},
+    AppState,
+};
+
+/// Prefix of the memory keys holding job records.
+pub const JOB_KEY_PREFIX: &str = "job:";
+/// Upper bound for `GET /jobs`.
+const MAX_LISTED_JOBS: usize = 100;
+/// Upper bound for documents per ingest job.
+const MAX_INGEST_DOCUMENTS: usize = 10_000;
+/// Upper bound for dead letters in `GET /jobs/queue`.
+const MAX_LISTED_DEAD_LETTERS: usize = 50;
+/// Lane of the durable task queue holding jobs.
+const JOBS_QUEUE: &str = "jobs";
+/// How often a job paused by low-power mode checks whether it may start.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Ingest,
+    RetentionSweep,
+}
+
+impl JobKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Ingest => "ingest",
+            Self::RetentionSweep => "retention_sweep",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(title = "JobCreateRequest", example = json!({
+    "kind": "retention_sweep",
+    "namespace": "chronik",
+    "dry_run": true,
+    "webhook_url": "https://hooks.example/hauski"
+}))]
+pub struct JobCreateRequest {
+    pub kind: JobKind,
+    /// `ingest`: documents in `/index/upsert` format.
+    #[serde(default)]
+    pub documents: Vec<serde_json::Value>,
+    /// `retention_sweep`: restrict to one namespace (default: all with retention config).
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// `retention_sweep`: only report what would be forgotten.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Called with the final job record (POST, JSON); must pass the egress policy.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(title = "JobRecord")]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// 0-100.
+    pub progress: f32,
+    /// Attempts started so far (retries included).
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub max_attempts: u32,
+    /// Kind-specific summary, set once the job finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "JobListResponse")]
+pub struct JobListResponse {
+    /// Newest first.
+    pub jobs: Vec<JobRecord>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "JobDeadLetter")]
+pub struct JobDeadLetter {
+    pub id: String,
+    pub kind: String,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub failed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "JobQueueResponse")]
+pub struct JobQueueResponse {
+    /// False if the memory store is unavailable; jobs then only live in-process.
+    pub durable: bool,
+    pub queued: u64,
+    pub running: u64,
+    pub dead: u64,
+    /// Most recent dead letters first.
+    pub dead_letters: Vec<JobDeadLetter>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "JobErrorResponse", example = json!({"error":"job not found"}))]
+pub struct JobErrorResponse {
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct JobLabels {
+    kind: &'static str,
+    status: &'static str,
+}
+
+impl EncodeLabelSet for JobLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("kind", self.kind).encode(encoder.encode_label())?;
+        ("status", self.status).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+/// Outcome of a single job attempt, for `job_attempts_total`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct JobAttemptLabels {
+    kind: &'static str,
+    outcome: &'static str,
+}
+
+impl EncodeLabelSet for JobAttemptLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("kind", self.kind).encode(encoder.encode_label())?;
+        ("outcome", self.outcome).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+struct JobEntry {
+    record: JobRecord,
+    cancel: CancellationToken,
+    /// Fired once the job reached a final state.
+    done: CancellationToken,
+    subscribers: Vec<ProgressSink>,
+}
+
+/// In-process registry of jobs started by this process.
+#[derive(Default)]
+pub struct JobManager {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    finished: Family<JobLabels, Counter>,
+    attempts: Family<JobAttemptLabels, Counter>,
+}
+
+impl fmt::Debug for JobManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobManager").finish_non_exhaustive()
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counter of finished jobs by kind and terminal status (for `/metrics`).
+    pub fn finished(&self) -> Family<JobLabels, Counter> {
+        self.finished.clone()
+    }
+
+    /// Counter of job attempts by kind and outcome (`ok`, `retry`,
+    /// `dead_letter`, `cancelled`).
+    pub fn attempts(&self) -> Family<JobAttemptLabels, Counter> {
+        self.attempts.clone()
+    }
+
+    fn count_attempt(&self, kind: JobKind, outcome: &'static str) {
+        self.attempts
+            .get_or_create(&JobAttemptLabels {
+                kind: kind.as_str(),
+                outcome,
+            })
+            .inc();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, JobEntry>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert(&self, record: JobRecord) -> CancellationToken {
+        let cancel = CancellationToken::new();
+        self.lock().insert(
+            record.id.clone(),
+            JobEntry {
+                record,
+                cancel: cancel.clone(),
+                done: CancellationToken::new(),
+                subscribers: Vec::new(),
+            },
+        );
+        cancel
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobRecord> {
+        self.lock().get(id).map(|entry| entry.record.clone())
+    }
+
+    /// Waits until the job finished; `None` for unknown jobs.
+    pub(crate) async fn wait(&self, id: &str) -> Option<JobRecord> {
+        let done = self.lock().get(id)?.done.clone();
+        done.cancelled().await;
+        self.get(id)
+    }
+
+    fn records(&self) -> Vec<JobRecord> {
+        self.lock()
+            .values()
+            .map(|entry| entry.record.clone())
+            .collect()
+    }
+
+    /// Applies `change` and returns the updated record (for persistence).
+    fn update(&self, id: &str, change: impl FnOnce(&mut JobRecord)) -> Option<JobRecord> {
+        let mut jobs = self.lock();
+        let entry = jobs.get_mut(id)?;
+        change(&mut entry.record);
+        Some(entry.record.clone())
+    }
+
+    fn emit(&self, id: &str, event: ProgressEvent) {
+        let mut jobs = self.lock();
+        if let Some(entry) = jobs.get_mut(id) {
+            for sink in &entry.subscribers {
+                sink.emit(event.clone());
+            }
+        }
+    }
+
+    /// Subscribes to progress events; `None` for unknown or finished jobs.
+    fn subscribe(&self, id: &str) -> Option<mpsc::UnboundedReceiver<ProgressEvent>> {
+        let mut jobs = self.lock();
+        let entry = jobs.get_mut(id)?;
+        if entry.record.status.is_terminal() {
+            return None;
+        }
+        let (sink, rx) = ProgressSink::channel();
+        sink.emit(ProgressEvent::Progress {
+            step: entry.record.kind.as_str().to_string(),
+            percent: entry.record.progress,
+        });
+        entry.subscribers.push(sink);
+        Some(rx)
+    }
+
+    /// Requests cancellation; returns the current record.
+    fn cancel(&self, id: &str) -> Option<JobRecord> {
+        let jobs = self.lock();
+        let entry = jobs.get(id)?;
+        entry.cancel.cancel();
+        Some(entry.record.clone())
+    }
+
+    /// Marks the job finished, sends the final event and closes all streams.
+    fn finish(&self, id: &str, change: impl FnOnce(&mut JobRecord)) -> Option<JobRecord> {
+        let mut jobs = self.lock();
+        let entry = jobs.get_mut(id)?;
+        change(&mut entry.record);
+        entry.record.finished_at = Some(Utc::now());
+        let record = entry.record.clone();
+        for sink in entry.subscribers.drain(..) {
+            sink.result(&record);
+        }
+        entry.done.cancel();
+        self.finished
+            .get_or_create(&JobLabels {
+                kind: record.kind.as_str(),
+                status: record.status.as_str(),
+            })
+            .inc();
+        Some(record)
+    }
+}
+
+fn job_key(id: &str) -> String {
+    format!("{JOB_KEY_PREFIX}{id}")
+}
+
+async fn persist(record: &JobRecord) {
+    let Some(store) = hauski_memory::try_global() else {
+        return;
+    };
+    let value = match serde_json::to_vec(record) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!(job_id = %record.id, error = %err, "job record serialization failed");
+            return;
+        }
+    };
+    let ttl = match crate::env_u64("HAUSKI_JOB_RECORD_TTL_SEC", 7 * 24 * 3_600) {
+        0 => hauski_memory::TtlUpdate::Clear,
+        secs => hauski_memory::TtlUpdate::Set(i64::try_from(secs).unwrap_or(i64::MAX)),
+    };
+    if let Err(err) = store.set(job_key(&record.id), value, ttl, None).await {
+        tracing::warn!(job_id = %record.id, error = ?err, "job record store failed");
+    }
+}
+
+/// Records of earlier processes; unfinished ones were interrupted by a restart.
+fn from_previous_run(mut record: JobRecord) -> JobRecord {
+    if !record.status.is_terminal() {
+        record.status = JobStatus::Failed;
+        record.error = Some("interrupted (process restarted)".to_string());
+    }
+    record
+}
+
+async fn load_record(id: &str) -> Option<JobRecord> {
+    let store = hauski_memory::try_global()?;
+    let item = store.get(job_key(id)).await.ok()??;
+    serde_json::from_slice(&item.value).ok()
+}
+
+async fn load_persisted(id: &str) -> Option<JobRecord> {
+    load_record(id).await.map(from_previous_run)
+}
+
+async fn list_persisted() -> Vec<JobRecord> {
+    let Some(store) = hauski_memory::try_global() else {
+        return Vec::new();
+    };
+    let page = store
+        .list(hauski_memory::ListOptions {
+            prefix: JOB_KEY_PREFIX.to_string(),
+            after: None,
+            limit: MAX_LISTED_JOBS * 10,
+            include_values: true,
+        })
+        .await;
+    match page {
+        Ok(page) => page
+            .items
+            .into_iter()
+            .filter_map(|item| serde_json::from_slice(&item.value?).ok())
+            .map(from_previous_run)
+            .collect(),
+        Err(err) => {
+            tracing::warn!(error = ?err, "listing persisted jobs failed");
+            Vec::new()
+        }
+    }
+}
+
+fn error_response(status: StatusCode, error: impl Into<String>) -> Response {
+    (
+        status,
+        Json(JobErrorResponse {
+            error: error.into(),
+        }),
+    )
+        .into_response()
+}
+
+/// Outcome of a job attempt: a result summary, or an error message. Errors are
+/// retried, so per-document problems belong into the summary instead.
+type JobOutcome = Result<serde_json::Value, String>;
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::from_env(
+        "HAUSKI_JOB_MAX_ATTEMPTS",
+        3,
+        "HAUSKI_JOB_RETRY_BASE_MS",
+        1_000,
+    )
+}
+
+#[derive(Clone)]
+struct JobContext {
+    id: String,
+    kind: JobKind,
+    manager: Arc<JobManager>,
+    cancel: CancellationToken,
+    /// Durable queue entry; without memory store attempts are only counted
+    /// in-process.
+    entry: QueueEntry,
+    /// How often a paused job checks whether it may start.
+    pause_poll: Duration,
+}
+
+impl JobContext {
+    /// Sleeps for `delay`; false if the job was cancelled meanwhile.
+    async fn wait(&self, delay: Duration) -> bool {
+        tokio::select! {
+            () = self.cancel.cancelled() => false,
+            () = tokio::time::sleep(delay) => true,
+        }
+    }
+
+    /// Waits while `paused` holds; false if the job was cancelled meanwhile.
+    async fn wait_until_resumed(&self, paused: &impl Fn() -> bool) -> bool {
+        if !paused() {
+            return true;
+        }
+        self.log("low-power mode: waiting before the next attempt".to_string());
+        while paused() {
+            if !self.wait(self.pause_poll).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn progress(&self, done: usize, total: usize) {
+        let percent = if total == 0 {
+            100.0
+        } else {
+            (done as f32 / total as f32 * 100.0).min(100.0)
+        };
+        if let Some(record) = self.manager.update(&self.id, |r| r.progress = percent) {
+            self.manager.emit(
+                &self.id,
+                ProgressEvent::Progress {
+                    step: record.kind.as_str().to_string(),
+                    percent,
+                },
+            );
+        }
+    }
+
+    fn log(&self, line: String) {
+        self.manager.emit(
+            &self.id,
+            ProgressEvent::Stdout {
+                step: self.kind.as_str().to_string(),
+                line,
+            },
+        );
+    }
+}
+
+async fn run_ingest(
+    ctx: &JobContext,
+    index: &IndexState,
+    embedder: Option<&EmbedderChain>,
+    documents: Vec<serde_json::Value>,
+) -> JobOutcome {
+    let total = documents.len();
+    let mut requests: Vec<Result<UpsertRequest, String>> = documents
+        .into_iter()
+        .enumerate()
+        .map(|(i, document)| {
+            serde_json::from_value::<UpsertRequest>(document)
+                .map_err(|err| format!("document {i}: {err}"))
+        })
+        .collect();
+    let embedding = match embedder {
+        Some(embedder) if !ctx.cancel.is_cancelled() => {
+            embed_missing_chunks(ctx, embedder, &mut requests).await
+        }
+        _ => None,
+    };
+
+    let (mut ingested, mut chunks, mut failed) = (0usize, 0usize, Vec::new());
+    for (i, request) in requests.into_iter().enumerate() {
+        if ctx.cancel.is_cancelled() {
+            break;
+        }
+        let outcome = match request {
+            Ok(request) => {
+                let doc_id = request.doc_id.clone();
+                index
+                    .upsert(request)
+                    .await
+                    .map(|n| (doc_id.clone(), n))
+                    .map_err(|err| format!("{doc_id}: {err:?}"))
+            }
+            Err(err) => Err(err),
+        };
+        match outcome {
+            Ok((doc_id, n)) => {
+                ingested += 1;
+                chunks += n;
+                ctx.log(format!("ingested {doc_id} ({n} chunks)"));
+            }
+            Err(err) => {
+                ctx.log(format!("failed {err}"));
+                failed.push(err);
+            }
+        }
+        ctx.progress(i + 1, total).await;
+    }
+    let mut result = serde_json::json!({
+        "ingested": ingested,
+        "chunks": chunks,
+        "failed": failed,
+    });
+    if let Some(embedding) = embedding {
+        result["embedding"] = embedding;
+    }
+    Ok(result)
+}
+
+/// Embeds all chunks that carry text but no vector, in parallel batches up to
+/// the primary embedder's `concurrency`. Batches may be answered by a fallback
+/// embedder; each document takes the embedder of its pin (`meta.embedder`) or
+/// of its first embedded chunk, and vectors from any other embedder are
+/// dropped. Documents pinned outside the chain are left alone. Chunks without
+/// a vector stay searchable by keyword. Returns the throughput figures and the
+/// affected documents, or `None` if nothing was embedded.
+async fn embed_missing_chunks(
+    ctx: &JobContext,
+    embedder: &EmbedderChain,
+    requests: &mut [Result<UpsertRequest, String>],
+) -> Option<serde_json::Value> {
+    let mut targets = Vec::new();
+    let mut texts = Vec::new();
+    for (doc, request) in requests.iter().enumerate() {
+        let Ok(request) = request else { continue };
+        let pinned = request
+            .meta
+            .get("embedder")
+            .and_then(serde_json::Value::as_str);
+        if pinned.is_some_and(|id| !embedder.contains(id)) {
+            continue;
+        }
+        for (chunk, payload) in request.chunks.iter().enumerate() {
+            match &payload.text {
+                Some(text) if payload.embedding.is_empty() && !text.trim().is_empty() => {
+                    targets.push((doc, chunk));
+                    texts.push(text.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+    if texts.is_empty() {
+        return None;
+    }
+
+    let (embeddings, throughput) = embed_parallel(embedder, texts, embedder.concurrency()).await;
+    ctx.log(format!(
+        "embedded {} of {} chunks with {} in {} batches ({:.1} chunks/s, concurrency {})",
+        throughput.texts,
+        targets.len(),
+        embedder.primary().id(),
+        throughput.batches,
+        throughput.texts_per_second(),
+        throughput.concurrency,
+    ));
+    let mut unembedded: Vec<String> = Vec::new();
+    let mut produced: BTreeMap<String, usize> = BTreeMap::new();
+    let slots = embeddings.vectors.into_iter().zip(embeddings.produced_by);
+    for (position, ((doc, chunk), (vector, producer))) in targets.into_iter().zip(slots).enumerate()
+    {
+        let Ok(request) = &mut requests[doc] else {
+            continue;
+        };
+        let assigned = request
+            .meta
+            .get("embedder")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string);
+        let error = match (vector, producer) {
+            (Some(vector), Some(producer))
+                if assigned.as_deref().is_none_or(|id| id == producer) =>
+            {
+                request.chunks[chunk].embedding = vector;
+                if !request.meta.is_object() {
+                    request.meta = serde_json::json!({});
+                }
+                request.meta["embedder"] = serde_json::json!(producer);
+                *produced.entry(producer).or_default() += 1;
+                continue;
+            }
+            (Some(_), producer) => format!(
+                "embedded by {}, document uses {}",
+                producer.as_deref().unwrap_or("unknown embedder"),
+                assigned.as_deref().unwrap_or("none"),
+            ),
+            (None, _) => embeddings
+                .failures
+                .iter()
+                .find(|failure| failure.inputs.contains(&position))
+                .map(|failure| failure.error.to_string())
+                .unwrap_or_default(),
+        };
+        if unembedded.last() != Some(&request.doc_id) {
+            ctx.log(format!(
+                "embedding failed for {}, ingesting without vectors: {error}",
+                request.doc_id
+            ));
+            unembedded.push(request.doc_id.clone());
+        }
+    }
+    let mut report = serde_json::to_value(&throughput).ok()?;
+    report["unembedded"] = serde_json::json!(unembedded);
+    report["embedders"] = serde_json::json!(produced);
+    Some(report)
+}
+
+async fn run_retention_sweep(
+    ctx: &JobContext,
+    index: &IndexState,
+    namespace: Option<String>,
+    dry_run: bool,
+) -> JobOutcome {
+    let mut targets: Vec<(String, u64)> = index
+        .get_retention_configs()
+        .await
+        .into_iter()
+        .filter(|(ns, _)| namespace.as_ref().is_none_or(|wanted| wanted == ns))
+        .filter_map(|(ns, config)| config.max_age_seconds.map(|age| (ns, age)))
+        .collect();
+    targets.sort();
+
+    let total = targets.len();
+    let mut forgotten_total = 0usize;
+    let mut namespaces = serde_json::Map::new();
+    for (i, (ns, max_age)) in targets.into_iter().enumerate() {
+        if ctx.cancel.is_cancelled() {
+            break;
+        }
+        let cutoff = Utc::now()
+            - chrono::Duration::seconds(i64::try_from(max_age).unwrap_or(i64::MAX / 1_000));
+        let result = index
+            .forget(
+                ForgetFilter {
+                    namespace: Some(ns.clone()),
+                    older_than: Some(cutoff),
+                    source_ref_origin: None,
+                    doc_id: None,
+                    allow_namespace_wipe: false,
+                },
+                dry_run,
+            )
+            .await;
+        ctx.log(format!(
+            "{ns}: {} documents older than {max_age}s",
+            result.forgotten_count
+        ));
+        forgotten_total += result.forgotten_count;
+        namespaces.insert(ns, serde_json::json!(result.forgotten_count));
+        ctx.progress(i + 1, total).await;
+    }
+    Ok(serde_json::json!({
+        "dry_run": dry_run,
+        "forgotten_count": forgotten_total,
+        "namespaces": namespaces,
+    }))
+}
+
+async fn execute(state: AppState, ctx: JobContext, request: JobCreateRequest) -> JobOutcome {
+    let index = state.index();
+    match request.kind {
+        JobKind::Ingest => {
+            let embedder = state.embedder();
+            run_ingest(&ctx, &index, embedder.as_ref(), request.documents).await
+        }
+        JobKind::RetentionSweep => {
+            run_retention_sweep(&ctx, &index, request.namespace, request.dry_run).await
+        }
+    }
+}
+
+/// Runs attempts until one succeeds, the job is cancelled or it is
+/// dead-lettered. Each attempt runs in its own task so that a panic counts
+/// as a failed attempt; no attempt starts while `paused` holds. Returns the
+/// final outcome and whether the job was dead-lettered.
+async fn run_attempts<F, Fut>(
+    ctx: &JobContext,
+    not_before: Duration,
+    paused: impl Fn() -> bool,
+    mut attempt_fn: F,
+) -> (JobOutcome, bool)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = JobOutcome> + Send + 'static,
+{
+    let mut attempt = ctx.manager.get(&ctx.id).map_or(0, |record| record.attempts);
+    let mut delay = not_before;
+    let mut last_error = None;
+    loop {
+        let ready =
+            (delay.is_zero() || ctx.wait(delay).await) && ctx.wait_until_resumed(&paused).await;
+        if !ready {
+            ctx.manager.count_attempt(ctx.kind, "cancelled");
+            let error = last_error.unwrap_or_else(|| "cancelled before start".to_string());
+            return (Err(error), false);
+        }
+        attempt = ctx.entry.begin_attempt(attempt).await;
+        if let Some(record) = ctx.manager.update(&ctx.id, |r| {
+            r.status = JobStatus::Running;
+            r.attempts = attempt;
+            r.started_at.get_or_insert_with(Utc::now);
+        }) {
+            persist(&record).await;
+        }
+
+        let outcome = tokio::spawn(attempt_fn())
+            .await
+            .unwrap_or_else(|err| Err(format!("job attempt panicked: {err}")));
+        let err = match outcome {
+            Ok(result) => {
+                let outcome = if ctx.cancel.is_cancelled() {
+                    "cancelled"
+                } else {
+                    "ok"
+                };
+                ctx.manager.count_attempt(ctx.kind, outcome);
+                return (Ok(result), false);
+            }
+            Err(err) if ctx.cancel.is_cancelled() => {
+                ctx.manager.count_attempt(ctx.kind, "cancelled");
+                return (Err(err), false);
+            }
+            Err(err) => err,
+        };
+
+        let Some(retry_in) = ctx.entry.fail_attempt(attempt, &err).await else {
+            ctx.manager.count_attempt(ctx.kind, "dead_letter");
+            return (
+                Err(format!("dead-lettered after {attempt} attempts: {err}")),
+                true,
+            );
+        };
+        ctx.manager.count_attempt(ctx.kind, "retry");
+        ctx.log(format!(
+            "attempt {attempt} failed: {err}; retrying in {}ms",
+            retry_in.as_millis()
+        ));
+        if let Some(record) = ctx.manager.update(&ctx.id, |r| {
+            r.status = JobStatus::Queued;
+            r.progress = 0.0;
+            r.error = Some(err.clone());
+        }) {
+            persist(&record).await;
+        }
+        last_error = Some(err);
+        delay = retry_in;
+    }
+}
+
+async fn run_job(
+    state: AppState,
+    ctx: JobContext,
+    request: JobCreateRequest,
+    not_before: Duration,
+) {
+    let kind = request.kind;
+    let started = Instant::now();
+    ctx.manager.emit(
+        &ctx.id,
+        ProgressEvent::Started {
+            step: kind.as_str().to_string(),
+        },
+    );
+    if let Some(record) = ctx.manager.get(&ctx.id) {
+        chronik::publish(&state, chronik::SOURCE_JOBS, "job.started", &record);
+    }
+
+    let paused = || state.limits().power.pause_jobs && state.system_monitor().low_power();
+    let (outcome, dead_lettered) = run_attempts(&ctx, not_before, paused, || {
+        execute(state.clone(), ctx.clone(), request.clone())
+    })
+    .await;
+
+    let cancelled = ctx.cancel.is_cancelled();
+    ctx.manager.emit(
+        &ctx.id,
+        ProgressEvent::Completed {
+            step: kind.as_str().to_string(),
+            ok: outcome.is_ok() && !cancelled,
+            duration_ms: started.elapsed().as_millis() as u64,
+        },
+    );
+    let Some(record) = ctx.manager.finish(&ctx.id, |r| {
+        r.status = match (&outcome, cancelled) {
+            (_, true) => JobStatus::Cancelled,
+            (Ok(_), false) => JobStatus::Succeeded,
+            (Err(_), false) => JobStatus::Failed,
+        };
+        match outcome {
+            Ok(result) => {
+                r.result = Some(result);
+                r.error = None;
+            }
+            Err(err) => r.error = Some(err),
+        }
+    }) else {
+        return;
+    };
+    tracing::info!(job_id = %record.id, kind = kind.as_str(), status = record.status.as_str(), attempts = record.attempts, "job finished");
+    persist(&record).await;
+    // Dead letters stay in the queue for inspection.
+    if !dead_lettered {
+        ctx.entry.settle().await;
+    }
+    chronik::publish(&state, chronik::SOURCE_JOBS, "job.finished", &record);
+    notify::notify(&state, notify::job_notification(&record));
+    notify_webhook(&state, &record).await;
+}
+
+/// Registers the job and starts it after `not_before`.
+async fn spawn_job(
+    state: &AppState,
+    record: JobRecord,
+    request: JobCreateRequest,
+    retry: RetryPolicy,
+    queue: Option<&'static hauski_memory::MemoryStore>,
+    not_before: Duration,
+) {
+    let manager = state.jobs();
+    let cancel = manager.insert(record.clone());
+    persist(&record).await;
+    let ctx = JobContext {
+        entry: QueueEntry::new(record.id.clone(), retry, queue),
+        id: record.id,
+        kind: record.kind,
+        manager,
+        cancel,
+        pause_poll: PAUSE_POLL_INTERVAL,
+    };
+    tokio::spawn(run_job(state.clone(), ctx, request, not_before));
+}
+
+/// Picks up jobs left in the durable queue by an earlier process (interrupted
+/// or waiting for a retry). Call once at server start; returns the number of
+/// resumed jobs.
+pub async fn resume_queued(state: &AppState) -> usize {
+    let Some(store) = hauski_memory::try_global() else {
+        return 0;
+    };
+    let tasks = match store.recover_tasks(JOBS_QUEUE).await {
+        Ok(tasks) => tasks,
+        Err(err) => {
+            tracing::warn!(error = ?err, "recovering queued jobs failed");
+            return 0;
+        }
+    };
+    let mut resumed = 0;
+    for task in tasks {
+        let request: JobCreateRequest = match serde_json::from_slice(&task.payload) {
+            Ok(request) => request,
+            Err(err) => {
+                tracing::warn!(job_id = %task.id, error = %err, "dropping unreadable queued job");
+                if let Err(err) = store.complete_task(task.id).await {
+                    tracing::warn!(error = ?err, "job queue update failed");
+                }
+                continue;
+            }
+        };
+        let mut record = load_record(&task.id)
+            .await
+            .unwrap_or_else(|| new_record(task.id.clone(), &request, task.created_ts));
+        record.status = JobStatus::Queued;
+        record.progress = 0.0;
+        record.attempts = task.attempts;
+        record.max_attempts = task.max_attempts;
+        record.finished_at = None;
+        let retry = RetryPolicy {
+            max_attempts: task.max_attempts,
+            ..retry_policy()
+        };
+        let not_before = (task.run_after - Utc::now()).to_std().unwrap_or_default();
+        spawn_job(state, record, request, retry, Some(store), not_before).await;
+        resumed += 1;
+    }
+    if resumed > 0 {
+        tracing::info!(resumed, "resumed queued jobs");
+    }
+    resumed
+}
+
+/// Announces the finished job to `webhook_url` and all `job.completed`
+/// subscriptions via the outbox.
+async fn notify_webhook(state: &AppState, record: &JobRecord) {
+    let body = match serde_json::to_value(record) {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::warn!(job_id = %record.id, error = %err, "job record serialization failed");
+            return;
+        }
+    };
+    if let Some(url) = record.webhook_url.as_deref() {
+        outbox::send(state, url, outbox::EVENT_JOB_COMPLETED, body.clone()).await;
+    }
+    outbox::publish(state, outbox::EVENT_JOB_COMPLETED, body).await;
+}
+
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    tag = "core",
+    request_body = JobCreateRequest,
+    responses(
+        (status = 202, description = "Job accepted", body = JobRecord),
+        (status = 400, description = "Invalid job request", body = JobErrorResponse)
+    )
+)]
+pub async fn create_job_handler(
+    State(state): State<AppState>,
+    Json(request): Json<JobCreateRequest>,
+) -> Response {
+    let started = Instant::now();
+    let response = create_job(&state, request).await;
+    state.record_http_observation(Method::POST, "/jobs", response.status(), started);
+    response
+}
+
+async fn create_job(state: &AppState, request: JobCreateRequest) -> Response {
+    match submit(state, request).await {
+        Ok(record) => (StatusCode::ACCEPTED, Json(record)).into_response(),
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+/// Validates and starts a job; errors describe the invalid request.
+pub(crate) async fn submit(
+    state: &AppState,
+    request: JobCreateRequest,
+) -> Result<JobRecord, String> {
+    match request.kind {
+        JobKind::Ingest if request.documents.is_empty() => {
+            return Err("ingest requires documents".to_string());
+        }
+        JobKind::Ingest if request.documents.len() > MAX_INGEST_DOCUMENTS => {
+            return Err(format!(
+                "ingest accepts at most {MAX_INGEST_DOCUMENTS} documents"
+            ));
+        }
+        _ => {}
+    }
+    if let Some(url) = request.webhook_url.as_deref() {
+        state
+            .egress_guard(egress::CALLER_WEBHOOKS)
+            .map_err(|err| err.to_string())
+            .and_then(|guard| guard.ensure_allowed(url).map_err(|err| err.to_string()))
+            .map_err(|err| format!("webhook_url rejected: {err}"))?;
+    }
+
+    let retry = retry_policy();
+    let mut record = new_record(Ulid::new().to_string(), &request, Utc::now());
+    record.max_attempts = retry.max_attempts;
+    let queue = enqueue(&record, &request).await;
+    chronik::publish(state, chronik::SOURCE_JOBS, "job.queued", &record);
+    spawn_job(state, record.clone(), request, retry, queue, Duration::ZERO).await;
+    Ok(record)
+}
+
+fn new_record(id: String, request: &JobCreateRequest, created_at: DateTime<Utc>) -> JobRecord {
+    JobRecord {
+        id,
+        kind: request.kind,
+        status: JobStatus::Queued,
+        progress: 0.0,
+        attempts: 0,
+        max_attempts: 0,
+        result: None,
+        error: None,
+        webhook_url: request.webhook_url.clone(),
+        created_at,
+        started_at: None,
+        finished_at: None,
+    }
+}
+
+/// Adds the job to the durable queue; without memory store (or if that fails)
+/// the job still runs, but does not survive a restart.
+async fn enqueue(
+    record: &JobRecord,
+    request: &JobCreateRequest,
+) -> Option<&'static hauski_memory::MemoryStore> {
+    let store = hauski_memory::try_global()?;
+    let payload = match serde_json::to_vec(request) {
+        Ok(payload) => payload,
+        Err(err) => {
+            tracing::warn!(job_id = %record.id, error = %err, "job payload serialization failed");
+            return None;
+        }
+    };
+    match store
+        .enqueue_task(
+            JOBS_QUEUE,
+            record.id.clone(),
+            record.kind.as_str().to_string(),
+            payload,
+            record.max_attempts,
+        )
+        .await
+    {
+        Ok(()) => Some(store),
+        Err(err) => {
+            tracing::warn!(job_id = %record.id, error = ?err, "job enqueue failed; running without durability");
+            None
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    tag = "core",
+    responses((status = 200, description = "Recent jobs, newest first", body = JobListResponse))
+)]
+pub async fn list_jobs_handler(State(state): State<AppState>) -> Json<JobListResponse> {
+    let started = Instant::now();
+    let mut by_id: HashMap<String, JobRecord> = list_persisted()
+        .await
+        .into_iter()
+        .map(|record| (record.id.clone(), record))
+        .collect();
+    // Live records of this process win over persisted snapshots.
+    for record in state.jobs().records() {
+        by_id.insert(record.id.clone(), record);
+    }
+    let mut jobs: Vec<JobRecord> = by_id.into_values().collect();
+    jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    jobs.truncate(MAX_LISTED_JOBS);
+
+    state.record_http_observation(Method::GET, "/jobs", StatusCode::OK, started);
+    Json(JobListResponse { jobs })
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/queue",
+    tag = "core",
+    responses((status = 200, description = "Durable job queue: depth and dead letters", body = JobQueueResponse))
+)]
+pub async fn job_queue_handler(State(state): State<AppState>) -> Json<JobQueueResponse> {
+    let started = Instant::now();
+    let mut response = JobQueueResponse {
+        durable: false,
+        queued: 0,
+        running: 0,
+        dead: 0,
+        dead_letters: Vec::new(),
+    };
+    if let Some(store) = hauski_memory::try_global() {
+        match (
+            store.queue_stats(JOBS_QUEUE).await,
+            store.dead_tasks(JOBS_QUEUE, MAX_LISTED_DEAD_LETTERS).await,
+        ) {
+            (Ok(stats), Ok(dead)) => {
+                response.durable = true;
+                response.queued = stats.queued;
+                response.running = stats.running;
+                response.dead = stats.dead;
+                response.dead_letters = dead
+                    .into_iter()
+                    .map(|task| JobDeadLetter {
+                        id: task.id,
+                        kind: task.kind,
+                        attempts: task.attempts,
+                        last_error: task.last_error,
+                        failed_at: task.updated_ts,
+                    })
+                    .collect();
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                tracing::warn!(error = ?err, "reading job queue failed");
+            }
+        }
+    }
+    state.record_http_observation(Method::GET, "/jobs/queue", StatusCode::OK, started);
+    Json(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "core",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Job record", body = JobRecord),
+        (status = 404, description = "Unknown job", body = JobErrorResponse)
+    )
+)]
+pub async fn get_job_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let started = Instant::now();
+    let record = match state.jobs().get(&id) {
+        Some(record) => Some(record),
+        None => load_persisted(&id).await,
+    };
+    let response = match record {
+        Some(record) => (StatusCode::OK, Json(record)).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, "job not found"),
+    };
+    state.record_http_observation(Method::GET, "/jobs/{id}", response.status(), started);
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}/events",
+    tag = "core",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Progress stream; ends with `result` carrying the final JobRecord", content_type = "text/event-stream", body = ProgressEvent),
+        (status = 404, description = "Unknown or already finished job", body = JobErrorResponse)
+    )
+)]
+pub async fn job_events_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let started = Instant::now();
+    let response = match state.jobs().subscribe(&id) {
+        Some(rx) => sse_response(rx).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, "job not found or already finished"),
+    };
+    state.record_http_observation(Method::GET, "/jobs/{id}/events", response.status(), started);
+    response
+}
+
+#[utoipa::path(
+    delete,
+    path = "/jobs/{id}",
+    tag = "core",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 202, description = "Cancellation requested", body = JobRecord),
+        (status = 404, description = "Unknown job", body = JobErrorResponse),
+        (status = 409, description = "Job already finished", body = JobErrorResponse)
+    )
+)]
+pub async fn cancel_job_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let started = Instant::now();
+    let response = match state.jobs().cancel(&id) {
+        Some(record) if record.status.is_terminal() => error_response(
+            StatusCode::CONFLICT,
+            format!("job already {}", record.status.as_str()),
+        ),
+        Some(record) => (StatusCode::ACCEPTED, Json(record)).into_response(),
+        None => match load_persisted(&id).await {
+            Some(record) => error_response(
+                StatusCode::CONFLICT,
+                format!("job already {}", record.status.as_str()),
+            ),
+            None => error_response(StatusCode::NOT_FOUND, "job not found"),
+        },
+    };
+    state.record_http_observation(Method::DELETE, "/jobs/{id}", response.status(), started);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: JobStatus) -> JobRecord {
+        JobRecord {
+            id: Ulid::new().to_string(),
+            kind: JobKind::Ingest,
+            status,
+            progress: 10.0,
+            attempts: 1,
+            max_attempts: 3,
+            result: None,
+            error: None,
+            webhook_url: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    fn context(manager: &Arc<JobManager>, max_attempts: u32) -> JobContext {
+        let job = record(JobStatus::Queued);
+        let id = job.id.clone();
+        let cancel = manager.insert(JobRecord { attempts: 0, ..job });
+        let retry = RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+        };
+        JobContext {
+            entry: QueueEntry::new(id.clone(), retry, None),
+            id,
+            kind: JobKind::Ingest,
+            manager: manager.clone(),
+            cancel,
+            pause_poll: Duration::from_millis(10),
+        }
+    }
+
+    fn attempt_count(manager: &JobManager, outcome: &'static str) -> u64 {
+        manager
+            .attempts()
+            .get_or_create(&JobAttemptLabels {
+                kind: "ingest",
+                outcome,
+            })
+            .get()
+    }
+
+    #[test]
+    fn retry_backoff_doubles_and_is_capped() {
+        let retry = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+        };
+        assert_eq!(retry.delay(1), Duration::from_millis(500));
+        assert_eq!(retry.delay(3), Duration::from_millis(2_000));
+        assert_eq!(retry.delay(40), crate::task_queue::MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn failed_attempts_are_retried_until_success() {
+        let manager = Arc::new(JobManager::new());
+        let ctx = context(&manager, 3);
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let (outcome, dead_lettered) = run_attempts(
+            &ctx,
+            Duration::ZERO,
+            || false,
+            || {
+                let calls = calls.clone();
+                async move {
+                    match calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                        0 => Err("transient".to_string()),
+                        1 => panic!("worker crashed"),
+                        _ => Ok(json!({"ingested": 1})),
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(outcome.unwrap()["ingested"], 1);
+        assert!(!dead_lettered);
+        assert_eq!(manager.get(&ctx.id).unwrap().attempts, 3);
+        assert_eq!(attempt_count(&manager, "retry"), 2);
+        assert_eq!(attempt_count(&manager, "ok"), 1);
+    }
+
+    #[tokio::test]
+    async fn exhausted_attempts_are_dead_lettered() {
+        let manager = Arc::new(JobManager::new());
+        let ctx = context(&manager, 2);
+
+        let (outcome, dead_lettered) = run_attempts(
+            &ctx,
+            Duration::ZERO,
+            || false,
+            || async { Err::<serde_json::Value, _>("index unavailable".to_string()) },
+        )
+        .await;
+
+        let err = outcome.unwrap_err();
+        assert!(err.starts_with("dead-lettered after 2 attempts"));
+        assert!(err.contains("index unavailable"));
+        assert!(dead_lettered);
+        assert_eq!(attempt_count(&manager, "retry"), 1);
+        assert_eq!(attempt_count(&manager, "dead_letter"), 1);
+    }
+
+    #[tokio::test]
+    async fn paused_jobs_stay_queued_until_low_power_ends() {
+        use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        let manager = Arc::new(JobManager::new());
+        let ctx = context(&manager, 3);
+        let low_power = Arc::new(AtomicBool::new(true));
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let run = tokio::spawn({
+            let (ctx, low_power, calls) = (ctx.clone(), low_power.clone(), calls.clone());
+            async move {
+                run_attempts(
+                    &ctx,
+                    Duration::ZERO,
+                    || low_power.load(Ordering::SeqCst),
+                    || {
+                        let calls = calls.clone();
+                        async move {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            Ok(json!({"ingested": 1}))
+                        }
+                    },
+                )
+                .await
+            }
+        });
+        tokio::time::sleep(ctx.pause_poll * 5).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let record = manager.get(&ctx.id).unwrap();
+        assert_eq!(record.status, JobStatus::Queued);
+        assert_eq!(record.attempts, 0);
+
+        low_power.store(false, Ordering::SeqCst);
+        let (outcome, _) = run.await.unwrap();
+        assert_eq!(outcome.unwrap()["ingested"], 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Cancelling a paused job ends it without an attempt.
+        let ctx = context(&manager, 3);
+        ctx.cancel.cancel();
+        let (outcome, _) =
+            run_attempts(&ctx, Duration::ZERO, || true, || async { Ok(json!({})) }).await;
+        assert_eq!(outcome.unwrap_err(), "cancelled before start");
+    }
+
+    /// Ollama stand-in: `[len(text), 1.0]` per input; model `mock-embed`
+    /// rejects batches containing `boom`.
+    async fn mock_ollama() -> String {
+        use axum::{routing::post, Json, Router};
+
+        let router = Router::new().route(
+            "/api/embed",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let input: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap();
+                if body["model"] == "mock-embed" && input.iter().any(|text| text == "boom") {
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+                let embeddings: Vec<Vec<f32>> = input
+                    .iter()
+                    .map(|text| vec![text.len() as f32, 1.0])
+                    .collect();
+                Ok(Json(json!({ "embeddings": embeddings })))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn mock_embedder(id: &str, model: &str, url: &str) -> hauski_embeddings::ConfiguredEmbedder {
+        serde_json::from_value::<hauski_embeddings::EmbedderSpec>(json!({
+            "id": id,
+            "provider": "ollama",
+            "model": model,
+            "url": url,
+            "dimension": 2,
+            "max_tokens": 512,
+            "batch_size": 2,
+            "concurrency": 3,
+            "retry": { "max_attempts": 1 },
+        }))
+        .unwrap()
+        .build()
+        .unwrap()
+    }
+
+    fn ingest_document(id: &str, texts: &[&str], meta: serde_json::Value) -> serde_json::Value {
+        let chunks: Vec<_> = texts.iter().map(|text| json!({ "text": text })).collect();
+        json!({
+            "doc_id": id,
+            "chunks": chunks,
+            "meta": meta,
+            "source_ref": { "origin": "test", "id": id, "trust_level": "high" },
+        })
+    }
+
+    #[tokio::test]
+    async fn ingest_embeds_missing_chunks_in_parallel() {
+        let url = mock_ollama().await;
+        let embedder = EmbedderChain::new(vec![mock_embedder("mock", "mock-embed", &url)]).unwrap();
+
+        let manager = Arc::new(JobManager::new());
+        let ctx = context(&manager, 1);
+        let index = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        // Batches of two: [one, two], [three, four], [boom].
+        let documents = vec![
+            ingest_document("a", &["one", "two", "three"], json!({})),
+            ingest_document("b", &["four", "boom"], json!({})),
+            ingest_document("c", &["five"], json!({ "embedder": "other" })),
+        ];
+
+        let result = run_ingest(&ctx, &index, Some(&embedder), documents)
+            .await
+            .unwrap();
+
+        assert_eq!(result["ingested"], 3);
+        assert_eq!(result["embedding"]["unembedded"], json!(["b"]));
+        assert_eq!(result["embedding"]["texts"], 4);
+        assert_eq!(result["embedding"]["batches"], 3);
+        assert_eq!(result["embedding"]["concurrency"], 3);
+        assert_eq!(result["embedding"]["failed_batches"], 1);
+        let embedders = index.stats().await.embedders;
+        assert_eq!(embedders.get("mock"), Some(&2));
+    }
+
+    #[tokio::test]
+    async fn ingest_tags_vectors_with_the_fallback_embedder() {
+        let url = mock_ollama().await;
+        let embedder = EmbedderChain::new(vec![
+            mock_embedder("mock", "mock-embed", &url),
+            mock_embedder("backup", "backup-embed", &url),
+        ])
+        .unwrap();
+        let manager = Arc::new(JobManager::new());
+        let ctx = context(&manager, 1);
+        let index = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        // Batches of two: [one, two], [boom, three], [four, five].
+        let documents = vec![
+            ingest_document("a", &["one", "two"], json!({})),
+            ingest_document("b", &["boom", "three"], json!({})),
+            ingest_document("c", &["four", "five"], json!({ "embedder": "mock" })),
+        ];
+
+        let result = run_ingest(&ctx, &index, Some(&embedder), documents)
+            .await
+            .unwrap();
+
+        assert_eq!(result["ingested"], 3);
+        assert_eq!(result["embedding"]["failed_batches"], 0);
+        assert_eq!(
+            result["embedding"]["embedders"],
+            json!({ "mock": 4, "backup": 2 })
+        );
+        assert_eq!(result["embedding"]["unembedded"], json!([]));
+        let embedders = index.stats().await.embedders;
+        assert_eq!(embedders.get("mock"), Some(&2));
+        assert_eq!(embedders.get("backup"), Some(&1));
+    }
+
+    #[test]
+    fn unfinished_records_from_previous_runs_are_failed() {
+        let interrupted = from_previous_run(record(JobStatus::Running));
+        assert_eq!(interrupted.status, JobStatus::Failed);
+        assert!(interrupted.error.unwrap().contains("restarted"));
+
+        let done = from_previous_run(record(JobStatus::Succeeded));
+        assert_eq!(done.status, JobStatus::Succeeded);
+        assert!(done.error.is_none());
+    }
+
+    #[test]
+    fn finish_closes_streams_and_counts() {
+        let manager = JobManager::new();
+        let job = record(JobStatus::Running);
+        let id = job.id.clone();
+        manager.insert(job);
+        let mut rx = manager.subscribe(&id).unwrap();
+
+        manager.finish(&id, |r| r.status = JobStatus::Cancelled);
+        assert!(manager.subscribe(&id).is_none());
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(matches!(events[0], ProgressEvent::Progress { .. }));
+        assert!(matches!(events.last(), Some(ProgressEvent::Result { .. })));
+        assert_eq!(
+            manager
+                .finished()
+                .get_or_create(&JobLabels {
+                    kind: "ingest",
+                    status: "cancelled"
+                })
+                .get(),
+            1
+        );
+    }
+}
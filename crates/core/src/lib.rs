@@ -1,5 +1,5 @@
 use axum::error_handling::HandleErrorLayer;
-use axum::extract::FromRef;
+use axum::extract::{DefaultBodyLimit, FromRef};
 use axum::{
     body::Body,
     extract::State,
@@ -9,7 +9,8 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use hauski_indexd::{router as index_router, IndexState};
+use hauski_embeddings::{EmbedderChain, EmbedderRegistry, EmbeddingMetrics, Normalization};
+use hauski_indexd::{router as index_router, IndexState, QuarantineNotice, VectorSpec};
 use hauski_memory as memory;
 use once_cell::sync::OnceCell;
 use prometheus_client::metrics::counter::Counter as PromCounter;
@@ -21,10 +22,10 @@ use prometheus_client::{
 };
 use std::{
     env, fmt,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, RwLock,
     },
     time::{Duration, Instant},
 };
@@ -32,29 +33,69 @@ use tower::{limit::ConcurrencyLimitLayer, timeout::TimeoutLayer, BoxError, Servi
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+mod api_auth;
 mod ask;
+mod ask_cache;
+mod ask_session;
+mod asr;
+mod asr_stream;
 mod assist;
+mod audio;
+mod backup;
 mod chat;
+mod chat_session;
 mod chat_upstream;
+mod chronik;
 mod cloud;
 mod config;
+mod config_watch;
 mod egress;
+mod egress_api;
 pub mod error;
+mod escalation_api;
 pub mod events;
 #[cfg(test)]
 mod events_tests;
+mod guardrail;
+mod ingest;
+mod input_dirs;
 pub mod intent;
+mod intent_api;
+mod jobs;
 mod memory_api;
+mod notify;
+mod outbox;
 mod plugins;
+mod policy_api;
+mod progress;
+mod rejection;
+mod request_log;
+mod schedules;
+mod self_state;
+mod shedding;
 pub mod system;
+mod task_queue;
 pub mod tools;
+mod tts;
+mod usage;
 pub use config::{
-    load_flags, load_limits, load_models, load_routing, Asr, FeatureFlags, Latency, Limits,
-    ModelEntry, ModelsFile, RoutingDecision, RoutingPolicy, RoutingRule, Thermal,
+    collect_config_from, collect_layered, from_yaml_str, load_config, load_config_from, load_flags,
+    load_layered, load_limits, load_models, load_routing, migrate_file, parse_config, parse_yaml,
+    read_config_file, redact, redact_json, strict_mode, strict_problems, with_version, AllowItem,
+    Asr, CloudFallback, ConfigDiff, ConfigDiffEntry, ConfigErrors, ConfigFile, ConfigKind,
+    ConfigLayers, ConfigSnapshot, ConfigSource, ConfigSources, DiskLimits, EffectiveConfig,
+    EffectiveEntry, EgressDefault, EgressPolicy, EmbeddingsSection, FallbackPaths, FeatureFlags,
+    IndexSection, InterpolationError, Latency, LimitedTarget, Limits, MemorySection, Migrated,
+    MigrationError, ModelCost, ModelEntry, ModelsFile, MonitorLimits, Origin, Origins, PathDefault,
+    PowerLimits, PressureLimits, RoutingDecision, RoutingPolicy, RoutingRule, RoutingRules,
+    SecretError, SecretRef, SheddingLimits, Thermal, UnifiedConfig, YamlError, CONFIG_PATH_VARS,
+    DEFAULT_CONFIG_PATH, SYSTEM_CONFIG_PATH,
 };
 pub use egress::{
-    AllowlistedClient, EgressGuard, EgressGuardError, GuardError, GuardedRequestError,
+    AllowlistEntry, AllowlistedClient, EgressAudit, EgressDecision, EgressGuard, EgressGuardError,
+    EgressLimiter, EgressMetrics, GuardError, GuardedRequestError,
 };
+pub use request_log::{init_tracing, REQUEST_ID_HEADER};
 
 const LATENCY_BUCKETS: [f64; 8] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
 const CORE_SERVICE_NAME: &str = "core";
@@ -65,27 +106,156 @@ type MetricsCallback = dyn Fn(Method, &'static str, StatusCode, Instant) + Send
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        health, healthz, ready,
-        ask::ask_handler, chat::chat_handler,
-        memory_api::memory_get_handler, memory_api::memory_set_handler, memory_api::memory_evict_handler,
+        health, healthz, ready, version,
+        ask::ask_handler, ask::ask_post_handler, chat::chat_handler, chat::chat_stream_handler,
+        chat::chat_session_handler, chat::chat_session_delete_handler,
+        memory_api::memory_get_handler, memory_api::memory_set_handler, memory_api::memory_evict_handler, memory_api::memory_list_handler, memory_api::memory_stats_handler,
         assist::assist_handler,
+        intent_api::intent_handler,
+        policy_api::policy_decide_handler, policy_api::policy_decisions_handler, policy_api::policy_decision_handler,
+        policy_api::policy_feedback_handler, policy_api::policy_report_handler,
+        policy_api::policy_backtest_handler,
+        policy_api::policy_reset_handler, policy_api::policy_audit_handler,
+        egress_api::egress_allowlist_handler, egress_api::egress_allow_handler,
+        egress_api::egress_revoke_handler, egress_api::egress_audit_handler,
+        escalation_api::escalate_handler,
+        usage::usage_handler,
+        self_state::self_state_handler,
+        shedding::shedding_handler,
+        asr::transcribe_handler, asr::models_handler, asr_stream::stream_handler,
+        tts::synthesize_handler, tts::voices_handler,
+        audio::profiles_handler, audio::profile_handler, audio::switch_handler,
+        notify::notify_handler, notify::channels_handler,
+        backup::list_handler, backup::create_handler, backup::verify_handler, backup::restore_handler,
+        ingest::ingest_file_handler,
+        schedules::schedules_handler,
+        jobs::create_job_handler, jobs::list_jobs_handler, jobs::get_job_handler,
+        jobs::job_events_handler, jobs::cancel_job_handler, jobs::job_queue_handler,
+        outbox::outbox_handler,
+        chronik::events_handler, chronik::stream_handler,
         plugins::list_plugins_handler, plugins::get_plugin_handler
     ),
     components(
         schemas(
+            VersionInfo,
             ask::AskResponse,
             ask::AskHit,
+            ask::AskRequest,
+            ask::AskAnswerMode,
+            ask::AskAnswerOptions,
+            ask::AskErrorResponse,
+            ask::AskCitation,
             chat::ChatRequest,
             chat::ChatMessage,
             chat::ChatStubResponse,
             chat::ChatResponse,
+            chat::ChatStreamDone,
+            chat::ChatSessionResponse,
+            usage::ChatUsage,
+            usage::UsageSource,
+            usage::UsageSummary,
+            usage::UsageTotals,
             memory_api::MemoryGetRequest, memory_api::MemoryGetResponse,
             memory_api::MemorySetRequest, memory_api::MemorySetResponse,
             memory_api::MemoryEvictRequest, memory_api::MemoryEvictResponse,
+            memory_api::MemoryListRequest, memory_api::MemoryListItem, memory_api::MemoryListResponse,
+            memory_api::MemoryStatsResponse,
             assist::AssistRequest,
             assist::AssistResponse,
+            progress::ProgressEvent,
+            intent_api::IntentRequest,
+            intent_api::IntentResponse,
+            policy_api::PolicyDecideRequest,
+            policy_api::PolicyDecideResponse,
+            policy_api::PolicyErrorResponse,
+            policy_api::PolicyDecisionsResponse,
+            policy_api::PolicyFeedbackRequest,
+            policy_api::PolicyFeedbackResponse,
+            policy_api::PolicyReportResponse,
+            policy_api::PolicyBacktestRequest,
+            policy_api::PolicyBacktestResponse,
+            policy_api::PolicyResetRequest,
+            policy_api::PolicyResetResponse,
+            policy_api::PolicyAuditResponse,
+            egress_api::EgressErrorResponse,
+            egress_api::EgressAllowlistResponse,
+            egress_api::EgressAllowRequest,
+            egress_api::EgressAllowlistChangeResponse,
+            egress_api::EgressAuditResponse,
+            escalation_api::EscalateRequest,
+            escalation_api::EscalateResponse,
+            intent_api::IntentSource,
+            intent_api::IntentErrorResponse,
             plugins::Plugin,
-            system::SystemSignals
+            system::SystemSignals,
+            system::GpuSignals,
+            system::DiskSignals,
+            system::PowerSignals,
+            system::RawSample,
+            system::SignalKind,
+            shedding::SheddingStatus,
+            asr::TranscribeRequest,
+            asr::TranscribeResponse,
+            asr::IndexedTranscript,
+            asr::AsrModel,
+            asr::AsrModelsResponse,
+            asr_stream::StreamFormat,
+            asr_stream::StreamEvent,
+            asr_stream::StreamResult,
+            hauski_asr::Segment,
+            hauski_asr::Device,
+            tts::TtsRequest,
+            tts::TtsVoice,
+            tts::TtsVoicesResponse,
+            hauski_tts::AudioFormat,
+            audio::AudioProfileSummary,
+            audio::AudioProfilesResponse,
+            audio::AudioProfileState,
+            audio::AudioSwitchRequest,
+            audio::AudioSwitchResponse,
+            notify::NotifyRequest,
+            notify::NotifyResponse,
+            notify::NotifyRouted,
+            notify::NotifyChannel,
+            notify::NotifyChannelsResponse,
+            hauski_notify::Priority,
+            hauski_notify::ChannelKind,
+            backup::BackupListResponse,
+            backup::BackupCreated,
+            backup::BackupVerifyResponse,
+            backup::BackupRestoreRequest,
+            backup::BackupRestoreResponse,
+            backup::BackupPart,
+            hauski_backup::ArchiveInfo,
+            hauski_backup::Manifest,
+            hauski_backup::ManifestEntry,
+            ingest::IngestFileRequest,
+            ingest::IngestFileResponse,
+            hauski_ingest::Format,
+            hauski_ingest::Chunk,
+            self_state::SelfState,
+            schedules::ScheduleListResponse,
+            hauski_scheduler::ScheduleStatus,
+            hauski_scheduler::RunRecord,
+            hauski_scheduler::RunTrigger,
+            self_state::SelfService,
+            self_state::SelfIndexState,
+            self_state::SelfMemoryState,
+            self_state::SelfPolicyState,
+            self_state::DecisionSummary,
+            self_state::BudgetStatus,
+            jobs::JobCreateRequest,
+            jobs::JobRecord,
+            jobs::JobKind,
+            jobs::JobStatus,
+            jobs::JobListResponse,
+            jobs::JobQueueResponse,
+            jobs::JobDeadLetter,
+            outbox::OutboxResponse,
+            outbox::OutboxDeadLetter,
+            chronik::ChronikEventsResponse,
+            hauski_chronik::Envelope,
+            jobs::JobErrorResponse
         )
     ),
     tags(
@@ -127,7 +297,15 @@ struct MetricsKeepalive {
 struct AppStateInner {
     limits: Limits,
     models: ModelsFile,
-    routing: RoutingPolicy,
+    /// Replaced at runtime by the egress allowlist API.
+    routing: RwLock<RoutingPolicy>,
+    /// File runtime routing changes are written back to; see `set_routing_path`.
+    routing_path: OnceCell<PathBuf>,
+    /// Files-only and merged configuration in use; see `set_config_snapshot`.
+    config_snapshot: RwLock<Option<ConfigSnapshot>>,
+    /// Changed keys that need a restart, see `config_watch`.
+    config_drift: RwLock<Vec<String>>,
+    config_watch: config_watch::ConfigWatchMetrics,
     flags: FeatureFlags,
     chat_cfg: Arc<chat::ChatCfg>,
     // This field holds the metric families alive for the prometheus registry.
@@ -135,6 +313,9 @@ struct AppStateInner {
     _metrics_keepalive: MetricsKeepalive,
     metrics_recorder: Arc<MetricsCallback>,
     index: IndexState,
+    /// Embedder fallback chain from `models.yml` (default embedder first unless
+    /// `embedder_fallback` says otherwise); computes missing vectors in ingest jobs.
+    embedder: Option<EmbedderChain>,
     registry: Mutex<Registry>,
     /// HTTP-Client für ausgehende Anfragen (z. B. /assist, Plugins).
     http_client: reqwest::Client,
@@ -144,12 +325,55 @@ struct AppStateInner {
     /// Only set to `true` if you understand the security implications.
     expose_config: bool,
     ready: AtomicBool,
+    started_at: chrono::DateTime<chrono::Utc>,
     /// Tool registry for assist code mode.
     tools: Arc<tools::ToolRegistry>,
     /// Registry for managed plugins.
     plugins: Arc<plugins::PluginRegistry>,
     /// System resource monitor.
     system_monitor: system::SystemMonitor,
+    /// Rejects batch requests under sustained pressure.
+    shedder: Arc<shedding::LoadShedder>,
+    /// whisper.cpp settings and limits for `/asr/*`.
+    asr: Arc<asr::AsrService>,
+    /// Piper voices and limits for `/tts`.
+    tts: Arc<tts::TtsService>,
+    /// PipeWire profiles for `/audio/*`.
+    audio: Arc<audio::AudioService>,
+    /// Desktop, ntfy and Matrix channels for notifications.
+    notifications: Arc<notify::NotifyService>,
+    /// Archive directory, retention and passphrase for `/backup`.
+    backups: Arc<backup::BackupService>,
+    /// Upload limit, namespace and chunk size for `/ingest/file`.
+    ingest: Arc<ingest::IngestService>,
+    /// Post-generation filter for chat responses.
+    guardrail: Arc<guardrail::OutputGuardrail>,
+    /// Token and cost accounting for chat requests.
+    usage: Arc<usage::UsageTracker>,
+    /// Short-lived response cache for `/ask`.
+    ask_cache: Arc<ask_cache::AskCache>,
+    /// Taxonomy for free-text intent classification.
+    intents: Arc<intent_api::IntentTaxonomy>,
+    policy: Arc<policy_api::DecisionPolicy>,
+    /// Sliding latency windows for the budgets in `limits.yaml`.
+    latency_budgets: Arc<self_state::LatencyBudgets>,
+    jobs: Arc<jobs::JobManager>,
+    /// Recurring tasks from `HAUSKI_SCHEDULES`; started by `start_scheduler`.
+    scheduler: Arc<hauski_scheduler::Scheduler>,
+    /// Webhook subscriptions and delivery metrics.
+    outbox: Arc<outbox::Outbox>,
+    /// Allowed/denied outbound requests per host and caller.
+    egress_metrics: EgressMetrics,
+    /// Rate and concurrency state for limited allow entries.
+    egress_limiter: EgressLimiter,
+    /// Client for guarded outbound requests, routed through `egress.proxy` and
+    /// sharing DNS pins; `None` if the routing policy at startup was invalid.
+    egress_http: Option<AllowlistedClient>,
+    /// Like `egress_http`, without the request timeout, for chat and embedding
+    /// upstreams.
+    upstream_http: Option<AllowlistedClient>,
+    /// Internal event bus (index mutations, decisions, jobs, system signals).
+    chronik: Arc<hauski_chronik::Bus>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -168,11 +392,14 @@ impl EncodeLabelSet for BuildInfoLabels {
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         limits: Limits,
         models: ModelsFile,
         routing: RoutingPolicy,
         flags: FeatureFlags,
+        index_cfg: &IndexSection,
+        memory_cfg: &MemorySection,
         chat_cfg: Arc<chat::ChatCfg>,
         expose_config: bool,
     ) -> Self {
@@ -206,9 +433,11 @@ impl AppState {
             http_latency.clone(),
         );
 
+        let latency_budgets = Arc::new(self_state::LatencyBudgets::new());
         let metrics_recorder: Arc<MetricsCallback> = {
             let http_requests = http_requests.clone();
             let http_latency = http_latency.clone();
+            let latency_budgets = latency_budgets.clone();
             Arc::new(move |method, path, status, started| {
                 let counter_labels = HttpLabels::new(method.clone(), path, status);
                 let duration_labels = HttpDurationLabels::new(method, path);
@@ -217,6 +446,7 @@ impl AppState {
                 http_latency
                     .get_or_create(&duration_labels)
                     .observe(elapsed);
+                latency_budgets.observe(path, elapsed * 1_000.0);
             })
         };
 
@@ -224,20 +454,70 @@ impl AppState {
         // This ensures they are properly namespaced and collected
         let mut index_sub_registry = registry.sub_registry_with_prefix("index");
 
-        // Load policies from standard locations or environment override
-        let trust_policy_path = env::var("HAUSKI_TRUST_POLICY_PATH")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from("policies/trust.yaml"));
-        let context_policy_path = env::var("HAUSKI_CONTEXT_POLICY_PATH")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from("policies/context.yaml"));
+        // Load policies from the `index` section (with its env overrides) or the
+        // standard locations
+        let trust_policy_path = index_cfg.trust_policy_path();
+        let context_policy_path = index_cfg.context_policy_path();
 
         let index = IndexState::new(
             limits.latency.index_topk20_ms,
             metrics_recorder.clone(),
             Some(&mut index_sub_registry),
             Some((trust_policy_path, context_policy_path)),
-        );
+        )
+        .with_namespace_budgets(limits.namespaces.clone());
+        let policy_watch_sec = index_cfg.policy_watch_sec.unwrap_or(0);
+        if policy_watch_sec > 0 {
+            index.watch_policies(Duration::from_secs(policy_watch_sec));
+        }
+        let embedding_metrics = EmbeddingMetrics::default();
+        embedding_metrics.register(&mut registry);
+        // Bound to the state below; remote embedders send through `upstream_client`.
+        let embedder_transport = egress_api::EgressTransport::default();
+        let embedder = match EmbedderRegistry::new(models.embedders.clone()) {
+            Ok(embedders) => {
+                index.register_embedders(
+                    embedders.iter().map(|spec| VectorSpec {
+                        embedder: spec.id.clone(),
+                        dimension: spec.dimension,
+                        normalize: spec.normalization == Normalization::L2,
+                    }),
+                    embedders.default_spec().map(|spec| spec.id.clone()),
+                );
+                let members = match embedders.chain(&models.embedder_fallback) {
+                    Ok(members) => members,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "invalid embedder_fallback in models.yml, using the default embedder");
+                        embedders.default_spec().into_iter().collect()
+                    }
+                };
+                let members = members
+                    .into_iter()
+                    .filter_map(|spec| match spec.build() {
+                        Ok(embedder) => Some(
+                            embedder
+                                .with_metrics(embedding_metrics.clone())
+                                .with_transport(Arc::new(embedder_transport.clone())),
+                        ),
+                        Err(err) => {
+                            tracing::warn!(embedder = %spec.id, error = %err, "embedder unavailable, skipped in the fallback chain");
+                            None
+                        }
+                    })
+                    .collect();
+                let chain = EmbedderChain::new(members);
+                if chain.is_none() && !embedders.is_empty() {
+                    tracing::warn!(
+                        "no embedder available, ingest jobs keep chunks without embeddings"
+                    );
+                }
+                chain
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "invalid embedders in models.yml, vectors stay unchecked");
+                None
+            }
+        };
 
         let http_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(15))
@@ -256,7 +536,114 @@ impl AppState {
         tool_registry.register(Arc::new(tools::CodeAnalysisTool));
 
         let plugin_registry = plugins::PluginRegistry::new();
-        let system_monitor = system::SystemMonitor::new();
+        let system_monitor = system::SystemMonitor::with_limits(
+            &limits,
+            system::storage_dirs(&models, index_cfg, memory_cfg),
+        );
+        system_monitor.register(&mut registry);
+        let shedder = shedding::LoadShedder::new(limits.shedding.clone());
+        shedder.register_metrics(&mut registry);
+        let asr = asr::AsrService::load_from_env();
+        asr.register_metrics(&mut registry);
+        let tts = tts::TtsService::load_from_env(models.voices.clone());
+        tts.register_metrics(&mut registry);
+        let audio = audio::AudioService::load_from_env();
+        audio.register_metrics(&mut registry);
+        let notifications = notify::NotifyService::load_from_env();
+        notifications.register_metrics(&mut registry);
+        let backups = backup::BackupService::load_from_env();
+        backups.register_metrics(&mut registry);
+        let ingest = ingest::IngestService::load_from_env();
+        ingest.register_metrics(&mut registry);
+
+        let guardrail = guardrail::OutputGuardrail::load_from_env();
+        tracing::info!(
+            rules = guardrail.rule_count(),
+            "chat output guardrail loaded"
+        );
+        registry.register(
+            "chat_guardrail_interventions",
+            "Total number of chat output guardrail interventions per rule",
+            guardrail.interventions(),
+        );
+
+        let usage = usage::UsageTracker::new();
+        registry.register(
+            "chat_tokens",
+            "Total number of chat tokens per model, client and kind (prompt/completion)",
+            usage.token_counter(),
+        );
+        registry.register(
+            "chat_cost",
+            "Accumulated chat cost per model and client (units per models.yml)",
+            usage.cost_counter(),
+        );
+
+        let ask_cache = ask_cache::AskCache::from_env();
+        registry.register(
+            "ask_cache_requests",
+            "Total number of /ask cache lookups by result (hit/miss/bypass)",
+            ask_cache.requests(),
+        );
+
+        let intents = intent_api::IntentTaxonomy::load_from_env();
+        tracing::info!(intents = intents.intents.len(), "intent taxonomy loaded");
+
+        let policy = policy_api::DecisionPolicy::load_from_env();
+        policy.register_metrics(&mut registry);
+        tracing::info!(
+            kinds = policy.engine().config().kinds.len(),
+            "decision policy loaded"
+        );
+
+        let jobs = jobs::JobManager::new();
+        registry.register(
+            "jobs_finished",
+            "Total number of finished background jobs by kind and status",
+            jobs.finished(),
+        );
+        registry.register(
+            "job_attempts",
+            "Total number of background job attempts by kind and outcome",
+            jobs.attempts(),
+        );
+
+        let scheduler = schedules::load_from_env();
+        scheduler.register_metrics(&mut registry);
+
+        let outbox = outbox::Outbox::load_from_env();
+        outbox.register_metrics(&mut registry);
+        tracing::info!(
+            subscriptions = outbox.subscription_count(),
+            "webhook subscriptions loaded"
+        );
+
+        let chronik = chronik::load_from_env();
+        chronik.register_metrics(&mut registry);
+
+        let egress_metrics = EgressMetrics::default();
+        egress_metrics.register(&mut registry);
+
+        let config_watch = config_watch::ConfigWatchMetrics::default();
+        config_watch.register(&mut registry);
+        // The proxy is taken from the policy at startup; changing it needs a restart.
+        // The clients do not follow redirects; `AllowlistedClient` checks each hop.
+        let guarded_client = |builder: reqwest::ClientBuilder| {
+            EgressGuard::from_policy(&routing)
+                .map_err(|err| err.to_string())
+                .and_then(|guard| {
+                    AllowlistedClient::build(builder, guard).map_err(|err| err.to_string())
+                })
+                .inspect_err(|err| {
+                    tracing::warn!(error = %err, "egress client unavailable, guarded requests disabled");
+                })
+                .ok()
+        };
+        let egress_http =
+            guarded_client(reqwest::Client::builder().timeout(Duration::from_secs(15)));
+        // Chat answers and embedding batches take their own time; embedders set
+        // a timeout per request.
+        let upstream_http = guarded_client(reqwest::Client::builder());
 
         let metrics_keepalive = MetricsKeepalive {
             http_requests,
@@ -264,23 +651,80 @@ impl AppState {
             build_info,
         };
 
-        Self(Arc::new(AppStateInner {
+        let state = Self(Arc::new(AppStateInner {
             limits,
             models,
-            routing,
+            routing: RwLock::new(routing),
+            routing_path: OnceCell::new(),
+            config_snapshot: RwLock::new(None),
+            config_drift: RwLock::new(Vec::new()),
+            config_watch,
             flags,
             chat_cfg,
             _metrics_keepalive: metrics_keepalive,
             metrics_recorder,
             index,
+            embedder,
             registry: Mutex::new(registry),
             http_client,
             expose_config,
             ready: AtomicBool::new(false),
+            started_at: chrono::Utc::now(),
             tools: Arc::new(tool_registry),
             plugins: Arc::new(plugin_registry),
             system_monitor,
-        }))
+            shedder: Arc::new(shedder),
+            asr: Arc::new(asr),
+            tts: Arc::new(tts),
+            audio: Arc::new(audio),
+            notifications: Arc::new(notifications),
+            backups: Arc::new(backups),
+            ingest: Arc::new(ingest),
+            guardrail: Arc::new(guardrail),
+            usage: Arc::new(usage),
+            ask_cache: Arc::new(ask_cache),
+            intents: Arc::new(intents),
+            policy: Arc::new(policy),
+            latency_budgets,
+            jobs: Arc::new(jobs),
+            scheduler: Arc::new(scheduler),
+            outbox: Arc::new(outbox),
+            chronik: Arc::new(chronik),
+            egress_metrics,
+            egress_limiter: EgressLimiter::default(),
+            egress_http,
+            upstream_http,
+        }));
+        embedder_transport.bind(&state);
+
+        // Weak, so the index does not keep the state alive.
+        let weak = Arc::downgrade(&state.0);
+        state
+            .0
+            .index
+            .on_quarantine(Arc::new(move |notice: QuarantineNotice| {
+                let Some(inner) = weak.upgrade() else {
+                    return;
+                };
+                let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                    return;
+                };
+                let state = AppState(inner);
+                notify::notify(&state, notify::quarantine_notification(&notice));
+                runtime.spawn(async move {
+                    match serde_json::to_value(&notice) {
+                        Ok(body) => outbox::publish(&state, outbox::EVENT_QUARANTINE, body).await,
+                        Err(err) => {
+                            tracing::warn!(error = %err, "quarantine notice serialization failed")
+                        }
+                    }
+                });
+            }));
+        chronik::attach(&state);
+        shedding::attach(&state);
+        notify::attach(&state);
+        policy_api::attach(&state);
+        state
     }
 
     fn limits(&self) -> Limits {
@@ -292,7 +736,108 @@ impl AppState {
     }
 
     pub(crate) fn routing(&self) -> RoutingPolicy {
-        self.0.routing.clone()
+        self.0
+            .routing
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the routing policy with the result of `update`, holding the
+    /// lock throughout so concurrent updates cannot drop each other's changes.
+    pub(crate) fn update_routing<E>(
+        &self,
+        update: impl FnOnce(&RoutingPolicy) -> Result<RoutingPolicy, E>,
+    ) -> Result<RoutingPolicy, E> {
+        let mut routing = self
+            .0
+            .routing
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let next = update(&routing)?;
+        *routing = next.clone();
+        Ok(next)
+    }
+
+    /// Persists runtime routing changes (e.g. `POST /egress/allowlist`) to
+    /// `path`; without it they only last until the next restart. Call once at
+    /// server start.
+    pub fn set_routing_path(&self, path: impl Into<PathBuf>) {
+        if self.0.routing_path.set(path.into()).is_err() {
+            tracing::warn!("routing path already set, ignoring");
+        }
+    }
+
+    pub(crate) fn routing_path(&self) -> Option<PathBuf> {
+        self.0.routing_path.get().cloned()
+    }
+
+    /// Stores the layered configuration served by `/config/effective` and
+    /// `/config/diff`.
+    pub fn set_config_snapshot(&self, snapshot: ConfigSnapshot) {
+        let mut current = self
+            .0
+            .config_snapshot
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.is_some() {
+            tracing::warn!("config snapshot already set, ignoring");
+            return;
+        }
+        *current = Some(snapshot);
+    }
+
+    pub(crate) fn replace_config_snapshot(&self, snapshot: ConfigSnapshot) {
+        *self
+            .0
+            .config_snapshot
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(snapshot);
+    }
+
+    pub(crate) fn config_snapshot(&self) -> Option<ConfigSnapshot> {
+        self.0
+            .config_snapshot
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn effective_config(&self) -> Option<EffectiveConfig> {
+        self.config_snapshot()
+            .as_ref()
+            .map(ConfigSnapshot::effective)
+    }
+
+    /// Differences between the config files and the configuration in use,
+    /// including runtime routing changes.
+    pub fn config_diff(&self) -> Option<ConfigDiff> {
+        let snapshot = self.config_snapshot()?;
+        Some(snapshot.diff(&self.routing()))
+    }
+
+    /// Reloads the configuration whenever the files of `layers` change (see
+    /// `HAUSKI_CONFIG_WATCH_SEC`). Call once at server start, after
+    /// [`build_app_from_config`].
+    pub fn watch_config(&self, layers: ConfigLayers) {
+        config_watch::watch(self, layers);
+    }
+
+    /// Keys changed in the files since startup that need a restart.
+    pub fn config_drift(&self) -> Vec<String> {
+        self.0
+            .config_drift
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn set_config_drift(&self, keys: Vec<String>) {
+        *self
+            .0
+            .config_drift
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = keys;
     }
 
     pub fn flags(&self) -> FeatureFlags {
@@ -307,6 +852,10 @@ impl AppState {
         self.0.index.clone()
     }
 
+    pub fn embedder(&self) -> Option<EmbedderChain> {
+        self.0.embedder.clone()
+    }
+
     pub fn safe_mode(&self) -> bool {
         self.0.flags.safe_mode
     }
@@ -360,6 +909,145 @@ impl AppState {
     pub fn system_monitor(&self) -> system::SystemMonitor {
         self.0.system_monitor.clone()
     }
+
+    pub(crate) fn shedder(&self) -> Arc<shedding::LoadShedder> {
+        self.0.shedder.clone()
+    }
+
+    pub(crate) fn asr(&self) -> Arc<asr::AsrService> {
+        self.0.asr.clone()
+    }
+
+    pub(crate) fn tts(&self) -> Arc<tts::TtsService> {
+        self.0.tts.clone()
+    }
+
+    pub(crate) fn audio(&self) -> Arc<audio::AudioService> {
+        self.0.audio.clone()
+    }
+
+    pub(crate) fn notifications(&self) -> Arc<notify::NotifyService> {
+        self.0.notifications.clone()
+    }
+
+    pub(crate) fn backups(&self) -> Arc<backup::BackupService> {
+        self.0.backups.clone()
+    }
+
+    pub(crate) fn ingest(&self) -> Arc<ingest::IngestService> {
+        self.0.ingest.clone()
+    }
+
+    pub(crate) fn guardrail(&self) -> Arc<guardrail::OutputGuardrail> {
+        self.0.guardrail.clone()
+    }
+
+    pub(crate) fn usage(&self) -> Arc<usage::UsageTracker> {
+        self.0.usage.clone()
+    }
+
+    pub(crate) fn ask_cache(&self) -> Arc<ask_cache::AskCache> {
+        self.0.ask_cache.clone()
+    }
+
+    pub(crate) fn intents(&self) -> Arc<intent_api::IntentTaxonomy> {
+        self.0.intents.clone()
+    }
+
+    pub(crate) fn policy(&self) -> Arc<policy_api::DecisionPolicy> {
+        self.0.policy.clone()
+    }
+
+    pub(crate) fn latency_budgets(&self) -> Arc<self_state::LatencyBudgets> {
+        self.0.latency_budgets.clone()
+    }
+
+    pub(crate) fn jobs(&self) -> Arc<jobs::JobManager> {
+        self.0.jobs.clone()
+    }
+
+    pub(crate) fn scheduler(&self) -> Arc<hauski_scheduler::Scheduler> {
+        self.0.scheduler.clone()
+    }
+
+    /// Starts the timers of all configured schedules; call once at server start.
+    pub fn start_scheduler(&self) {
+        let scheduler = self.scheduler();
+        if !scheduler.is_empty() {
+            scheduler.start(schedules::CoreTaskRunner::new(self.clone()));
+        }
+    }
+
+    /// Resumes jobs left in the durable queue by an earlier process; call once
+    /// at server start.
+    pub async fn resume_jobs(&self) -> usize {
+        jobs::resume_queued(self).await
+    }
+
+    pub(crate) fn outbox(&self) -> Arc<outbox::Outbox> {
+        self.0.outbox.clone()
+    }
+
+    pub(crate) fn chronik(&self) -> Arc<hauski_chronik::Bus> {
+        self.0.chronik.clone()
+    }
+
+    /// Egress guard for the current routing policy, counting into `egress_attempts`
+    /// under `caller`, sharing rate limits with all other callers and auditing
+    /// decisions if `egress.audit` is set.
+    pub(crate) fn egress_guard(
+        &self,
+        caller: &'static str,
+    ) -> Result<EgressGuard, EgressGuardError> {
+        Ok(EgressGuard::from_policy(&self.routing())?
+            .with_metrics(self.0.egress_metrics.clone(), caller)
+            .with_limiter(self.0.egress_limiter.clone())
+            .with_audit(egress_api::audit_hook(self), caller))
+    }
+
+    /// [`Self::egress_guard`] with the startup client that goes through `egress.proxy`
+    /// and pins validated addresses.
+    pub(crate) fn egress_client(&self, caller: &'static str) -> Result<AllowlistedClient, String> {
+        self.guarded_client(self.0.egress_http.as_ref(), caller)
+    }
+
+    /// [`Self::egress_client`] without its 15 s request timeout, for chat and
+    /// embedding upstreams.
+    pub(crate) fn upstream_client(
+        &self,
+        caller: &'static str,
+    ) -> Result<AllowlistedClient, String> {
+        self.guarded_client(self.0.upstream_http.as_ref(), caller)
+    }
+
+    fn guarded_client(
+        &self,
+        client: Option<&AllowlistedClient>,
+        caller: &'static str,
+    ) -> Result<AllowlistedClient, String> {
+        let guard = self.egress_guard(caller).map_err(|err| err.to_string())?;
+        let client = client.ok_or_else(|| "egress client unavailable".to_string())?;
+        Ok(client.with_guard(guard))
+    }
+
+    /// Resumes webhook deliveries left in the outbox by an earlier process;
+    /// call once at server start.
+    pub async fn resume_outbox(&self) -> usize {
+        outbox::resume_pending(self).await
+    }
+
+    /// Restores the learned policy state and starts saving it periodically;
+    /// call once at server start.
+    pub fn start_policy_persistence(&self) {
+        policy_api::start_persistence(&self.0.policy);
+    }
+
+    /// Writes the learned policy state; call on shutdown.
+    pub fn save_policy_state(&self) {
+        if let Err(err) = self.policy().save_state() {
+            tracing::warn!(error = %err, "policy state could not be saved");
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -426,30 +1114,69 @@ impl FromRef<AppState> for reqwest::Client {
     }
 }
 
-async fn get_limits(State(state): State<AppState>) -> Json<Limits> {
+/// `value` as JSON with resolved `secret://` references replaced by `***`.
+fn redacted(value: impl serde::Serialize) -> Json<serde_json::Value> {
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    redact_json(&mut value);
+    Json(value)
+}
+
+async fn get_limits(State(state): State<AppState>) -> Json<serde_json::Value> {
     let started = Instant::now();
     let status = StatusCode::OK;
-    let response = Json(state.limits());
+    let response = redacted(state.limits());
     state.record_http_observation(Method::GET, "/config/limits", status, started);
     response
 }
 
-async fn get_models(State(state): State<AppState>) -> Json<ModelsFile> {
+async fn get_models(State(state): State<AppState>) -> Json<serde_json::Value> {
     let started = Instant::now();
     let status = StatusCode::OK;
-    let response = Json(state.models());
+    let response = redacted(state.models());
     state.record_http_observation(Method::GET, "/config/models", status, started);
     response
 }
 
-async fn get_routing(State(state): State<AppState>) -> Json<RoutingPolicy> {
+async fn get_routing(State(state): State<AppState>) -> Json<serde_json::Value> {
     let started = Instant::now();
     let status = StatusCode::OK;
-    let response = Json(state.routing());
+    let response = redacted(state.routing());
     state.record_http_observation(Method::GET, "/config/routing", status, started);
     response
 }
 
+/// Merged configuration as loaded at startup, with the origin of each value.
+async fn get_effective_config(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let response = match state.effective_config() {
+        Some(effective) => Json(effective).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "configuration was not loaded from layers"})),
+        )
+            .into_response(),
+    };
+    let status = response.status();
+    state.record_http_observation(Method::GET, "/config/effective", status, started);
+    response
+}
+
+/// Values that differ from the config files, and which layer changed them.
+async fn get_config_diff(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let response = match state.config_diff() {
+        Some(diff) => Json(diff).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "configuration was not loaded from layers"})),
+        )
+            .into_response(),
+    };
+    let status = response.status();
+    state.record_http_observation(Method::GET, "/config/diff", status, started);
+    response
+}
+
 #[utoipa::path(
     get,
     path = "/health",
@@ -480,22 +1207,70 @@ async fn healthz(State(state): State<AppState>) -> &'static str {
     get,
     path = "/ready",
     responses(
-        (status = 200, description = "Service ready"),
+        (status = 200, description = "Service ready; reports disks low on space and keys that need a restart after a config change"),
         (status = 503, description = "Service starting")
     ),
     tag = "core"
 )]
-async fn ready(State(state): State<AppState>) -> (StatusCode, &'static str) {
+async fn ready(State(state): State<AppState>) -> (StatusCode, String) {
     let started = Instant::now();
-    let (status, body) = if state.is_ready() {
-        (StatusCode::OK, "ok")
+    let (status, body) = if !state.is_ready() {
+        (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string())
     } else {
-        (StatusCode::SERVICE_UNAVAILABLE, "starting")
+        let mut notes = Vec::new();
+        let low_space = state.system_monitor().low_space_disks();
+        if !low_space.is_empty() {
+            let disks: Vec<String> = low_space
+                .iter()
+                .map(|disk| format!("{} ({})", disk.label, disk.mount_point))
+                .collect();
+            notes.push(format!("degraded: low disk space for {}", disks.join(", ")));
+        }
+        let drift = state.config_drift();
+        if !drift.is_empty() {
+            notes.push(format!(
+                "config drift detected: restart to apply {}",
+                drift.join(", ")
+            ));
+        }
+        if notes.is_empty() {
+            (StatusCode::OK, "ok".to_string())
+        } else {
+            (StatusCode::OK, notes.join("; "))
+        }
     };
     state.record_http_observation(Method::GET, "/ready", status, started);
     (status, body)
 }
 
+/// Build and uptime of the running core, for `hauski status`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+pub struct VersionInfo {
+    pub service: String,
+    pub version: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub uptime_seconds: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses((status = 200, description = "Version and uptime", body = VersionInfo)),
+    tag = "core"
+)]
+async fn version(State(state): State<AppState>) -> Json<VersionInfo> {
+    let started = Instant::now();
+    let started_at = state.0.started_at;
+    let info = VersionInfo {
+        service: "hauski-core".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at,
+        uptime_seconds: (chrono::Utc::now() - started_at).num_seconds().max(0) as u64,
+    };
+    state.record_http_observation(Method::GET, "/version", StatusCode::OK, started);
+    Json(info)
+}
+
 async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
     let started = Instant::now();
     let encoded_metrics = state.encode_metrics();
@@ -523,6 +1298,38 @@ async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+/// `$var`, or `file` in the HausKI state directory.
+pub(crate) fn env_path(var: &str, file: &str) -> PathBuf {
+    env::var(var)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            dirs::state_dir()
+                .unwrap_or_else(|| {
+                    dirs::home_dir()
+                        .unwrap_or_else(|| PathBuf::from("."))
+                        .join(".local/state")
+                })
+                .join("hauski")
+                .join(file)
+        })
+}
+
+/// Reads a `u64` from the environment, warning and falling back on parse errors.
+pub(crate) fn env_u64(key: &str, default: u64) -> u64 {
+    match env::var(key) {
+        Ok(v) => v.parse::<u64>().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Invalid value for {key}='{}' – falling back to {default}",
+                v
+            );
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
 pub fn build_app(
     limits: Limits,
     models: ModelsFile,
@@ -549,31 +1356,82 @@ pub fn build_app_with_state(
     flags: FeatureFlags,
     expose_config: bool,
     allowed_origin: HeaderValue,
+) -> (Router, AppState) {
+    build_app_with_sections(
+        limits,
+        models,
+        routing,
+        flags,
+        &IndexSection::default(),
+        &MemorySection::default(),
+        expose_config,
+        allowed_origin,
+    )
+}
+
+/// Builds the app from a loaded `hauski.yml` (see [`load_config`]).
+///
+/// The routing file is only registered for allowlist persistence when routing
+/// did not come from `hauski.yml` itself.
+pub fn build_app_from_config(
+    config: UnifiedConfig,
+    expose_config: bool,
+    allowed_origin: HeaderValue,
+) -> (Router, AppState) {
+    let routing_file = config.routing_file().map(Path::to_path_buf);
+    let snapshot = config.snapshot();
+    let (app, state) = build_app_with_sections(
+        config.limits,
+        config.models,
+        config.routing,
+        config.flags,
+        &config.index,
+        &config.memory,
+        expose_config,
+        allowed_origin,
+    );
+    if let Some(path) = routing_file {
+        state.set_routing_path(path);
+    }
+    state.set_config_snapshot(snapshot);
+    (app, state)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_app_with_sections(
+    limits: Limits,
+    models: ModelsFile,
+    routing: RoutingPolicy,
+    flags: FeatureFlags,
+    index_cfg: &IndexSection,
+    memory_cfg: &MemorySection,
+    expose_config: bool,
+    allowed_origin: HeaderValue,
 ) -> (Router, AppState) {
     let chat_cfg = Arc::new(chat::ChatCfg::from_env_and_flags(
         flags.chat_upstream_url.clone(),
         flags.chat_model.clone(),
     ));
-    let state = AppState::new(limits, models, routing, flags, chat_cfg, expose_config);
+    let state = AppState::new(
+        limits,
+        models,
+        routing,
+        flags,
+        index_cfg,
+        memory_cfg,
+        chat_cfg,
+        expose_config,
+    );
     let allowed_origin = Arc::new(allowed_origin);
 
     // --- Request guards ------------------------------------------------------
-    // Defaults: 1500ms timeout, 512 concurrent requests – configurable via ENV:
+    // Defaults: 1500ms timeout, 10min for long-running routes, 512 concurrent
+    // requests – configurable via ENV:
     //   HAUSKI_HTTP_TIMEOUT_MS (u64; 0 = disabled)
+    //   HAUSKI_HTTP_LONG_TIMEOUT_MS (u64; 0 = disabled)
     //   HAUSKI_HTTP_CONCURRENCY (u64; 0 = disabled)
-    fn env_u64(key: &str, default: u64) -> u64 {
-        match env::var(key) {
-            Ok(v) => v.parse::<u64>().unwrap_or_else(|_| {
-                tracing::warn!(
-                    "Invalid value for {key}='{}' – falling back to {default}",
-                    v
-                );
-                default
-            }),
-            Err(_) => default,
-        }
-    }
     let timeout_ms = env_u64("HAUSKI_HTTP_TIMEOUT_MS", 1500);
+    let long_timeout_ms = env_u64("HAUSKI_HTTP_LONG_TIMEOUT_MS", 600_000);
     let concurrency = env_u64("HAUSKI_HTTP_CONCURRENCY", 512);
 
     // Apply a timeout and concurrency limit before executing handlers so that
@@ -583,7 +1441,10 @@ pub fn build_app_with_state(
         .nest("/index", index_router::<AppState>());
 
     // Initialize memory subsystem. This is fallible, so we capture the result.
-    let raw_pool_size = env_u64("HAUSKI_MEMORY_MAX_POOL_SIZE", 4);
+    let memory_defaults = hauski_memory::MemoryConfig::default();
+    let raw_pool_size = memory_cfg
+        .max_pool_size
+        .unwrap_or(u64::from(memory_defaults.max_pool_size));
     let max_pool_size = raw_pool_size.clamp(1, 64) as u32;
     if raw_pool_size != max_pool_size as u64 {
         tracing::warn!(
@@ -594,8 +1455,11 @@ pub fn build_app_with_state(
     }
 
     let memory_config = hauski_memory::MemoryConfig {
+        db_path: memory_cfg.db_path.clone(),
+        janitor_interval_secs: memory_cfg
+            .janitor_interval_secs
+            .unwrap_or(memory_defaults.janitor_interval_secs),
         max_pool_size,
-        ..Default::default()
     };
 
     let memory_initialized = hauski_memory::init_with(memory_config)
@@ -618,16 +1482,25 @@ pub fn build_app_with_state(
         app = app.merge(plugin_routes()).merge(cloud_routes());
     }
 
-    if memory_initialized {
-        app = app.merge(memory_routes());
+    // Data-plane memory API: own flag and token, independent of expose_config.
+    if memory_initialized && state.flags().memory_api {
+        if state.flags().memory_token.is_none() {
+            tracing::warn!("memory_api enabled without memory_token – /memory/* will answer 403");
+        }
+        app = app.merge(memory_routes().route_layer(from_fn_with_state(
+            state.clone(),
+            memory_api::require_memory_token,
+        )));
     }
 
-    let timeout_layer = if timeout_ms > 0 {
-        Some(TimeoutLayer::new(Duration::from_millis(timeout_ms)))
-    } else {
-        tracing::info!("HAUSKI_HTTP_TIMEOUT_MS=0 → request timeout disabled");
-        None
-    };
+    // Long-running routes answer only once their work is done, so they get
+    // their own timeout instead of the global one.
+    let app = with_timeout(app, timeout_ms, "HAUSKI_HTTP_TIMEOUT_MS").merge(with_timeout(
+        long_running_routes(),
+        long_timeout_ms,
+        "HAUSKI_HTTP_LONG_TIMEOUT_MS",
+    ));
+
     let concurrency_layer = if concurrency > 0 {
         let c = std::cmp::min(concurrency, usize::MAX as u64) as usize;
         Some(ConcurrencyLimitLayer::new(c))
@@ -637,17 +1510,12 @@ pub fn build_app_with_state(
     };
 
     let request_guards = ServiceBuilder::new()
-        .layer(HandleErrorLayer::new(|err: BoxError| async move {
-            if err.is::<tower::timeout::error::Elapsed>() {
-                (StatusCode::REQUEST_TIMEOUT, "request timed out")
-            } else {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "service temporarily unavailable",
-                )
-            }
+        .layer(HandleErrorLayer::new(|_: BoxError| async move {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "service temporarily unavailable",
+            )
         }))
-        .option_layer(timeout_layer)
         .option_layer(concurrency_layer)
         // Map the router's infallible error type to a BoxError before it
         // hits the optional layers. This is required for `option_layer`'s
@@ -660,8 +1528,17 @@ pub fn build_app_with_state(
     // The readiness flag is set by the caller once the listener is bound.
     let app = app
         .with_state(state.clone())
+        .layer(from_fn_with_state(
+            state.clone(),
+            shedding::shed_low_priority,
+        ))
+        .layer(from_fn_with_state(
+            state.clone(),
+            api_auth::require_api_token,
+        ))
         .layer(from_fn_with_state(allowed_origin.clone(), cors_middleware))
-        .layer(request_guards);
+        .layer(request_guards)
+        .layer(axum::middleware::from_fn(request_log::request_log));
 
     // ---- Memory metrics registration & poller -------------------------------
     if memory_initialized {
@@ -742,12 +1619,114 @@ fn core_routes() -> Router<AppState> {
         .route("/health", get(health))
         .route("/healthz", get(healthz))
         .route("/ready", get(ready))
+        .route("/version", get(version))
         .route("/metrics", get(metrics))
-        .route("/ask", get(ask::ask_handler))
+        .route("/ask", get(ask::ask_handler).post(ask::ask_post_handler))
         .route("/assist", post(assist::assist_handler))
+        .route("/intent", post(intent_api::intent_handler))
+        .route("/policy/decide", post(policy_api::policy_decide_handler))
+        .route(
+            "/policy/decisions",
+            get(policy_api::policy_decisions_handler),
+        )
+        .route(
+            "/policy/decisions/{id}",
+            get(policy_api::policy_decision_handler),
+        )
+        .route(
+            "/policy/feedback",
+            post(policy_api::policy_feedback_handler),
+        )
+        .route("/policy/report", get(policy_api::policy_report_handler))
+        .route(
+            "/policy/backtest",
+            post(policy_api::policy_backtest_handler),
+        )
+        .route("/policy/reset", post(policy_api::policy_reset_handler))
+        .route("/policy/audit", get(policy_api::policy_audit_handler))
+        .route(
+            "/egress/allowlist",
+            get(egress_api::egress_allowlist_handler)
+                .post(egress_api::egress_allow_handler)
+                .delete(egress_api::egress_revoke_handler),
+        )
+        .route("/egress/audit", get(egress_api::egress_audit_handler))
+        .route("/policy/escalate", post(escalation_api::escalate_handler))
         .route("/v1/chat", post(chat::chat_handler))
+        .route("/v1/chat/stream", post(chat::chat_stream_handler))
+        .route(
+            "/v1/chat/sessions/{id}",
+            get(chat::chat_session_handler).delete(chat::chat_session_delete_handler),
+        )
+        .route("/usage", get(usage::usage_handler))
         .route("/events", post(events::event_handler))
         .route("/system/signals", get(system::system_signals_handler))
+        .route(
+            "/system/signals/history",
+            get(system::system_signals_history_handler),
+        )
+        .route("/system/shedding", get(shedding::shedding_handler))
+        .route("/self/state", get(self_state::self_state_handler))
+        .route("/asr/stream", get(asr_stream::stream_handler))
+        .route("/asr/models", get(asr::models_handler))
+        .route("/tts", post(tts::synthesize_handler))
+        .route("/tts/voices", get(tts::voices_handler))
+        .route("/audio/profiles", get(audio::profiles_handler))
+        .route(
+            "/audio/profile",
+            get(audio::profile_handler).post(audio::switch_handler),
+        )
+        .route("/notify", post(notify::notify_handler))
+        .route("/notify/channels", get(notify::channels_handler))
+        .route("/scheduler/schedules", get(schedules::schedules_handler))
+        .route(
+            "/jobs",
+            post(jobs::create_job_handler).get(jobs::list_jobs_handler),
+        )
+        .route(
+            "/jobs/{id}",
+            get(jobs::get_job_handler).delete(jobs::cancel_job_handler),
+        )
+        .route("/jobs/queue", get(jobs::job_queue_handler))
+        .route("/jobs/{id}/events", get(jobs::job_events_handler))
+        .route("/webhooks/outbox", get(outbox::outbox_handler))
+        .route("/chronik/events", get(chronik::events_handler))
+        .route("/chronik/stream", get(chronik::stream_handler))
+}
+
+/// Routes that answer only after transcribing, archiving or extracting; they
+/// run under `HAUSKI_HTTP_LONG_TIMEOUT_MS` instead of the global timeout.
+fn long_running_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/asr/transcribe",
+            post(asr::transcribe_handler).layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/backup",
+            get(backup::list_handler).post(backup::create_handler),
+        )
+        .route("/backup/{name}", get(backup::verify_handler))
+        .route("/backup/{name}/restore", post(backup::restore_handler))
+        .route(
+            "/ingest/file",
+            post(ingest::ingest_file_handler).layer(DefaultBodyLimit::disable()),
+        )
+}
+
+/// Answers `408` once a request on `router` takes longer than `timeout_ms`.
+fn with_timeout(router: Router<AppState>, timeout_ms: u64, var: &str) -> Router<AppState> {
+    if timeout_ms == 0 {
+        tracing::info!("{var}=0 → request timeout disabled");
+        return router;
+    }
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(|_: BoxError| async move {
+                (StatusCode::REQUEST_TIMEOUT, "request timed out")
+            }))
+            .layer(TimeoutLayer::new(Duration::from_millis(timeout_ms))),
+    )
 }
 
 fn memory_routes() -> Router<AppState> {
@@ -755,6 +1734,8 @@ fn memory_routes() -> Router<AppState> {
         .route("/memory/get", post(memory_api::memory_get_handler))
         .route("/memory/set", post(memory_api::memory_set_handler))
         .route("/memory/evict", post(memory_api::memory_evict_handler))
+        .route("/memory/list", post(memory_api::memory_list_handler))
+        .route("/memory/stats", post(memory_api::memory_stats_handler))
 }
 
 fn config_routes() -> Router<AppState> {
@@ -762,6 +1743,8 @@ fn config_routes() -> Router<AppState> {
         .route("/config/limits", get(get_limits))
         .route("/config/models", get(get_models))
         .route("/config/routing", get(get_routing))
+        .route("/config/effective", get(get_effective_config))
+        .route("/config/diff", get(get_config_diff))
 }
 
 fn plugin_routes() -> Router<AppState> {
@@ -903,19 +1886,33 @@ mod tests {
             latency: crate::config::Latency {
                 llm_p95_ms: 400,
                 index_topk20_ms: 60,
+                asr_p95_ms: 900,
             },
             thermal: crate::config::Thermal {
                 gpu_max_c: 80,
                 dgpu_power_w: 220,
+                ..Default::default()
             },
             asr: crate::config::Asr { wer_max_pct: 10 },
+            disk: Default::default(),
+            pressure: Default::default(),
+            power: Default::default(),
+            monitor: Default::default(),
+            shedding: Default::default(),
+            namespaces: Default::default(),
         };
         let models = ModelsFile {
+            embedders: Vec::new(),
+            embedder_fallback: Vec::new(),
+            voices: Vec::new(),
             models: vec![crate::config::ModelEntry {
                 id: "llama3.1-8b-q4".into(),
                 path: "/opt/models/llama3.1-8b-q4.gguf".into(),
                 vram_min_gb: Some(6),
                 canary: Some(false),
+                cost: None,
+                url: None,
+                sha256: None,
             }],
         };
         let routing = RoutingPolicy::default();
@@ -1068,26 +2065,258 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn ask_route_clamps_k_to_100() {
+    async fn ask_post_applies_filters_and_extractive_answer() {
         let app = demo_app(false);
 
-        let upsert_payload = json!({
-            "doc_id": "ask-doc-large",
-            "namespace": "default",
-            "chunks": [
-                {"chunk_id": "ask-doc-large#0", "text": "Hallo Hauski", "embedding": []}
-            ],
-            "meta": {"kind": "markdown"},
-            "source_ref": {
-                "origin": "test",
-                "id": "test-ask-doc-large",
-                "trust_level": "high"
-            }
-        });
-
-        let upsert_res = app
-            .clone()
-            .oneshot(
+        for (doc_id, kind, trust) in [
+            ("runbook", "runbook", "high"),
+            ("note", "note", "high"),
+            ("rumor", "runbook", "low"),
+        ] {
+            let upsert_payload = json!({
+                "doc_id": doc_id,
+                "namespace": "default",
+                "chunks": [
+                    {"text": format!("Intro. Deploy steps for {doc_id}. Outro."), "embedding": []}
+                ],
+                "meta": {"kind": kind},
+                "source_ref": {"origin": "test", "id": doc_id, "trust_level": trust}
+            });
+            let res = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/index/upsert")
+                        .method("POST")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(upsert_payload.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let ask_payload = json!({
+            "query": "deploy",
+            "min_trust_level": "medium",
+            "meta_filter": {"kind": "runbook"},
+            "include_weights": true,
+            "answer": {"mode": "extractive"}
+        });
+        let res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/ask")
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(ask_payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let response: AskResponse = from_slice(&body).unwrap();
+        assert_eq!(response.hits.len(), 1);
+        assert_eq!(response.hits[0].doc_id, "runbook");
+        assert!(response.hits[0].weights.is_some());
+        assert_eq!(
+            response.answer.as_deref(),
+            Some("Deploy steps for runbook.")
+        );
+        assert_eq!(response.citations.len(), 1);
+        let citation = &response.citations[0];
+        assert_eq!(citation.doc_id, "runbook");
+        assert_eq!(citation.chunk_id, "runbook#0");
+        assert_eq!((citation.start, citation.end), (7, 32));
+        assert_eq!(citation.trust_level, Some(hauski_indexd::TrustLevel::High));
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/ask")
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"query": "  "}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn ask_cache_hits_until_namespace_changes() {
+        let app = demo_app(false);
+
+        let upsert = |doc_id: &str| {
+            Request::builder()
+                .uri("/index/upsert")
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({
+                        "doc_id": doc_id,
+                        "namespace": "default",
+                        "chunks": [{"text": "Hallo Cache", "embedding": []}],
+                        "meta": {},
+                        "source_ref": {"origin": "test", "id": doc_id, "trust_level": "high"}
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let ask = |bypass: bool| {
+            let mut builder = Request::builder().uri("/ask?q=cache&k=5&ns=default");
+            if bypass {
+                builder = builder.header("x-hauski-cache", "bypass");
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        let cache_result = |res: &axum::response::Response| {
+            res.headers()
+                .get("x-hauski-cache")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let res = app.clone().oneshot(upsert("cache-a")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app.clone().oneshot(ask(false)).await.unwrap();
+        assert_eq!(cache_result(&res).as_deref(), Some("miss"));
+        let res = app.clone().oneshot(ask(false)).await.unwrap();
+        assert_eq!(cache_result(&res).as_deref(), Some("hit"));
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let cached: AskResponse = from_slice(&body).unwrap();
+        assert_eq!(cached.hits.len(), 1);
+
+        let res = app.clone().oneshot(ask(true)).await.unwrap();
+        assert_eq!(cache_result(&res).as_deref(), Some("bypass"));
+
+        let res = app.clone().oneshot(upsert("cache-b")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app.clone().oneshot(ask(false)).await.unwrap();
+        assert_eq!(cache_result(&res).as_deref(), Some("miss"));
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let fresh: AskResponse = from_slice(&body).unwrap();
+        assert_eq!(fresh.hits.len(), 2);
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        assert!(metrics.contains("ask_cache_requests_total{result=\"hit\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn ask_session_rewrites_follow_up_and_records_chain() {
+        let _ = hauski_memory::init_with(hauski_memory::MemoryConfig {
+            db_path: Some(
+                std::env::temp_dir().join(format!("hauski_ask_session_{}.db", std::process::id())),
+            ),
+            ..Default::default()
+        });
+        let app = demo_app(false);
+        let session_id = format!("test-{}", ulid::Ulid::new());
+
+        let ask = |query: &str| {
+            Request::builder()
+                .uri("/ask")
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({"query": query, "session_id": session_id}).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(ask("deployment incidents"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let first: AskResponse = from_slice(&body).unwrap();
+        assert_eq!(
+            first.rewritten_query.as_deref(),
+            Some("deployment incidents")
+        );
+
+        let res = app
+            .clone()
+            .oneshot(ask("And what about last week?"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let second: AskResponse = from_slice(&body).unwrap();
+        assert_eq!(second.query, "And what about last week?");
+        assert_eq!(
+            second.rewritten_query.as_deref(),
+            Some("deployment incidents last week")
+        );
+        assert_eq!(second.session_id.as_deref(), Some(session_id.as_str()));
+
+        let session = ask_session::load(&session_id).await;
+        let chain: Vec<_> = session.turns.iter().map(|t| t.rewritten.as_str()).collect();
+        assert_eq!(
+            chain,
+            ["deployment incidents", "deployment incidents last week"]
+        );
+        let _ = hauski_memory::global()
+            .evict(format!("{}{session_id}", ask_session::SESSION_KEY_PREFIX))
+            .await;
+
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/ask")
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({"query": "x", "session_id": "../etc"}).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn ask_route_clamps_k_to_100() {
+        let app = demo_app(false);
+
+        let upsert_payload = json!({
+            "doc_id": "ask-doc-large",
+            "namespace": "default",
+            "chunks": [
+                {"chunk_id": "ask-doc-large#0", "text": "Hallo Hauski", "embedding": []}
+            ],
+            "meta": {"kind": "markdown"},
+            "source_ref": {
+                "origin": "test",
+                "id": "test-ask-doc-large",
+                "trust_level": "high"
+            }
+        });
+
+        let upsert_res = app
+            .clone()
+            .oneshot(
                 Request::builder()
                     .uri("/index/upsert")
                     .method("POST")
@@ -1298,6 +2527,114 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn effective_config_lists_origins_and_redacts_tokens() {
+        let (app, state) = demo_app_with_origin_and_flags(
+            true,
+            FeatureFlags::default(),
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/config/effective")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let origins = Origins::from([(
+            "flags.api_token".to_string(),
+            Origin::Env("HAUSKI_API_TOKEN".into()),
+        )]);
+        let config: serde_yaml_ng::Value =
+            serde_yaml_ng::from_str("flags: {api_token: secret, safe_mode: false}").unwrap();
+        state.set_config_snapshot(ConfigSnapshot::new(config.clone(), config, origins));
+        let res = app
+            .oneshot(
+                Request::get("/config/effective")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["config"]["flags"]["api_token"], "***");
+        assert_eq!(
+            json["entries"][0],
+            serde_json::json!({"key": "flags.api_token", "value": "***", "origin": "env:HAUSKI_API_TOKEN"})
+        );
+        assert_eq!(json["entries"][1]["origin"], "default");
+    }
+
+    #[tokio::test]
+    async fn config_diff_names_overrides_and_runtime_changes() {
+        let (app, state) = demo_app_with_origin_and_flags(
+            true,
+            FeatureFlags::default(),
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        let routing = RoutingPolicy::deny_all();
+        state
+            .update_routing(|_| Ok::<_, ()>(routing.clone()))
+            .unwrap();
+        let layout = |safe_mode: bool, token: &str| {
+            let mut config: serde_yaml_ng::Value = serde_yaml_ng::from_str(&format!(
+                "flags: {{safe_mode: {safe_mode}, api_token: {token}}}"
+            ))
+            .unwrap();
+            config["routing"] = serde_yaml_ng::to_value(&routing).unwrap();
+            config
+        };
+        let origins = Origins::from([
+            (
+                "flags.safe_mode".to_string(),
+                Origin::Env("HAUSKI_SAFE_MODE".into()),
+            ),
+            (
+                "flags.api_token".to_string(),
+                Origin::Cli("flags.api_token".into()),
+            ),
+        ]);
+        state.set_config_snapshot(ConfigSnapshot::new(
+            layout(false, "from-file"),
+            layout(true, "from-cli"),
+            origins,
+        ));
+        state
+            .update_routing(|_| {
+                Ok::<_, ()>(
+                    serde_yaml_ng::from_str("egress: {default: deny, allow: [example.org]}")
+                        .unwrap(),
+                )
+            })
+            .unwrap();
+
+        let res = app
+            .oneshot(Request::get("/config/diff").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["entries"],
+            json!([
+                {"key": "flags.api_token", "file": "***", "effective": "***", "origin": "cli:--set flags.api_token"},
+                {"key": "flags.safe_mode", "file": false, "effective": true, "origin": "env:HAUSKI_SAFE_MODE"},
+                {"key": "routing.egress.allow", "file": null, "effective": ["example.org"], "origin": "runtime"},
+            ])
+        );
+    }
+
     #[tokio::test]
     async fn cors_allows_configured_origin() {
         let origin = HeaderValue::from_static("http://127.0.0.1:8080");
@@ -1390,6 +2727,20 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn version_reports_build_and_uptime() {
+        let app = demo_app(false);
+        let res = app
+            .oneshot(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let info: VersionInfo = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(info.uptime_seconds < 60);
+    }
+
     #[tokio::test]
     async fn healthz_ok() {
         let app = demo_app(false);
@@ -1401,75 +2752,1123 @@ mod tests {
     }
 
     #[tokio::test]
-    #[serial]
-    async fn chat_stub_returns_service_unavailable_when_unconfigured() {
-        let _env = ChatEnvGuard::cleared();
+    async fn intent_route_classifies_and_returns_policy_features() {
         let app = demo_app(false);
-        let payload = json!({
-            "messages": [
-                {"role": "user", "content": "Hallo HausKI?"}
-            ]
-        });
+
+        let intent_request = |body: serde_json::Value| {
+            Request::builder()
+                .uri("/intent")
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
 
         let res = app
-            .oneshot(
-                Request::post("/v1/chat")
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(payload.to_string()))
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(intent_request(json!({
+                "text": "Erinnere mich morgen an den Termin",
+                "use_model": false
+            })))
             .await
             .unwrap();
-
-        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.status(), StatusCode::OK);
         let body = res.into_body().collect().await.unwrap().to_bytes();
-        let stub: ChatStubResponse = serde_json::from_slice(&body).unwrap();
-        assert_eq!(stub.status, "unavailable");
+        let response: intent_api::IntentResponse = from_slice(&body).unwrap();
+        assert_eq!(response.intent, "remind");
+        assert_eq!(response.source, intent_api::IntentSource::Heuristic);
+        assert_eq!(response.features["intent"], "remind");
+        assert_eq!(response.features["has_time_reference"], true);
+        assert!(response.scores.contains_key("execute"));
+
+        let res = app
+            .oneshot(intent_request(json!({"text": "   "})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    #[serial]
-    async fn chat_stub_unavailable_payload_matches_message() {
-        let _env = ChatEnvGuard::cleared();
-        let app = demo_app(false);
-        let payload = json!({
-            "messages": [
-                {"role": "user", "content": "Hallo HausKI?"}
-            ]
-        });
+    async fn policy_decide_enforces_intent_budgets() {
+        let (app, state) = build_app_with_state(
+            Limits::default(),
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            FeatureFlags::default(),
+            false,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        let config: policy::engine::PolicyConfig = serde_yaml_ng::from_str(
+            "mode: live\nbudgets:\n  answer: { latency_ms: 500 }\nkinds:\n  routing:\n    actions: [cloud, local]\n    intent: answer\n    strategy: { kind: lin_ucb, alpha: 0.0 }\n    costs:\n      cloud: { latency_ms: 900 }\n      local: { latency_ms: 300 }\n",
+        )
+        .unwrap();
+        *state.policy().engine() = policy::engine::PolicyEngine::new(config);
+
+        let decide_request = |body: serde_json::Value| {
+            Request::builder()
+                .uri("/policy/decide")
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
 
         let res = app
-            .oneshot(
-                Request::post("/v1/chat")
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(payload.to_string()))
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(decide_request(json!({"kind": "routing"})))
             .await
             .unwrap();
-
-        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.status(), StatusCode::OK);
         let body = res.into_body().collect().await.unwrap().to_bytes();
-        let stub: ChatStubResponse = serde_json::from_slice(&body).unwrap();
-        assert_eq!(stub.status, "unavailable");
-        assert_eq!(
-            stub.message,
-            "chat pipeline not wired yet, please configure HAUSKI_CHAT_UPSTREAM_URL"
-        );
+        let decision: policy_api::PolicyDecideResponse = from_slice(&body).unwrap();
+        assert_eq!(decision.action, "local");
+        let budget = decision.budget.unwrap();
+        assert_eq!(budget["intent"], "answer");
+        assert_eq!(budget["downgraded_from"], "cloud");
+
+        {
+            let policy = state.policy();
+            let mut engine = policy.engine();
+            let mut config = engine.config().clone();
+            config.budgets.get_mut("answer").unwrap().latency_ms = Some(100.0);
+            *engine = policy::engine::PolicyEngine::new(config);
+        }
+        let res = app
+            .clone()
+            .oneshot(decide_request(json!({"kind": "routing"})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let metrics = state.encode_metrics().unwrap();
+        assert!(metrics
+            .contains("policy_budget_decisions_total{intent=\"answer\",outcome=\"downgraded\"} 1"));
+        assert!(metrics
+            .contains("policy_budget_decisions_total{intent=\"answer\",outcome=\"rejected\"} 1"));
+        assert!(metrics.contains(
+            "policy_budget_utilization_count{intent=\"answer\",resource=\"latency_ms\"} 1"
+        ));
     }
 
     #[tokio::test]
-    async fn safe_mode_flag_is_reflected_in_state() {
-        let (_app, state) = demo_app_with_origin_and_flags(
+    async fn policy_decide_returns_decisions_and_records_them() {
+        let (app, state) = build_app_with_state(
+            Limits::default(),
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            FeatureFlags::default(),
             false,
-            FeatureFlags {
-                safe_mode: true,
-                ..FeatureFlags::default()
-            },
             HeaderValue::from_static("http://127.0.0.1:8080"),
         );
-        assert!(state.safe_mode());
-        assert!(state.flags().safe_mode);
+
+        let decide_request = |body: serde_json::Value| {
+            Request::builder()
+                .uri("/policy/decide")
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(decide_request(json!({
+                "kind": "reminder",
+                "features": {"intent": "remind", "load": 0.3}
+            })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let shadow: policy_api::PolicyDecideResponse = from_slice(&body).unwrap();
+        assert_eq!(shadow.mode, "shadow");
+        assert_eq!(shadow.action, "notify");
+        assert_eq!(shadow.baseline, "notify");
+        assert!(["notify", "snooze"].contains(&shadow.proposed.as_str()));
+        assert!(shadow.why.contains("lin_ucb"));
+
+        let res = app
+            .clone()
+            .oneshot(decide_request(json!({"kind": "reminder", "mode": "live"})))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let live: policy_api::PolicyDecideResponse = from_slice(&body).unwrap();
+        assert_eq!(live.mode, "live");
+        assert_eq!(live.action, live.proposed);
+
+        let recorded = state
+            .chronik()
+            .recent(&hauski_chronik::EventFilter::default(), 10);
+        assert_eq!(
+            recorded
+                .iter()
+                .filter(|event| event.kind == "policy.decision")
+                .count(),
+            2
+        );
+
+        let metrics = state.encode_metrics().unwrap();
+        assert!(metrics.contains(
+            r#"policy_decisions_total{kind="reminder",action="notify",mode="shadow"} 1"#
+        ));
+        assert!(metrics.contains(&format!(
+            r#"policy_decisions_total{{kind="reminder",action="{}",mode="live"}} 1"#,
+            live.action
+        )));
+        assert!(metrics.contains(r#"policy_decision_score_count{kind="reminder",action="notify"}"#));
+        assert!(metrics.contains(r#"policy_estimated_regret_total{kind="reminder"}"#));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get(format!("/policy/decisions/{}", shadow.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let stored: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(stored["mode"], "shadow");
+        assert_eq!(stored["parameters_hash"], shadow.parameters_hash.as_str());
+        assert_eq!(stored["context"], json!({"intent": "remind", "load": 0.3}));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get(format!(
+                    "/policy/decisions?kind=reminder&mode=live&limit=500&since={}",
+                    shadow.ts.to_rfc3339().replace('+', "%2B")
+                ))
+                .body(Body::empty())
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let listed: policy_api::PolicyDecisionsResponse = from_slice(&body).unwrap();
+        assert!(listed.decisions.iter().any(|record| record.id == live.id));
+        assert!(listed.decisions.iter().all(|record| record.id != shadow.id));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/policy/decisions/missing")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = app
+            .clone()
+            .oneshot(decide_request(json!({"kind": "dance"})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let res = app
+            .oneshot(decide_request(json!({"kind": "reminder", "features": [1]})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn policy_feedback_updates_the_bandit_once_per_decision() {
+        let (app, state) = build_app_with_state(
+            Limits::default(),
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            FeatureFlags::default(),
+            false,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        let post_json = |uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(post_json(
+                "/policy/decide",
+                json!({"kind": "reminder", "features": {"load": 0.3}}),
+            ))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let decision: policy_api::PolicyDecideResponse = from_slice(&body).unwrap();
+
+        let feedback = json!({"decision_id": decision.id, "reward": 1.0});
+        let res = app
+            .clone()
+            .oneshot(post_json("/policy/feedback", feedback.clone()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let applied: policy_api::PolicyFeedbackResponse = from_slice(&body).unwrap();
+        assert_eq!(applied.action, "notify");
+        assert!(!applied.duplicate);
+        let plays = |state: &AppState| {
+            state
+                .policy()
+                .engine()
+                .bandit("reminder")
+                .and_then(|bandit| bandit.arm("notify"))
+                .map(|arm| arm.plays)
+        };
+        assert_eq!(plays(&state), Some(1));
+
+        let res = app
+            .clone()
+            .oneshot(post_json("/policy/feedback", feedback))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let repeated: policy_api::PolicyFeedbackResponse = from_slice(&body).unwrap();
+        assert!(repeated.duplicate);
+        assert_eq!(plays(&state), Some(1));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/policy/report?kind=reminder")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let report: serde_json::Value = from_slice(&body).unwrap();
+        let reminder = &report["reports"][0];
+        assert_eq!(reminder["kind"], "reminder");
+        assert!(reminder["with_feedback"].as_u64().unwrap() >= 1);
+        assert!(reminder["recommendation"].is_string());
+
+        let res = app
+            .clone()
+            .oneshot(post_json("/policy/backtest", json!({"kind": "reminder"})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let backtest: serde_json::Value = from_slice(&body).unwrap();
+        let reminder = &backtest["reports"][0];
+        assert!(reminder["replayed"].as_u64().unwrap() >= 1);
+        // The active shadow config applies `notify`, as recorded.
+        assert_eq!(reminder["matched"], reminder["replayed"]);
+        assert!(reminder["expected_reward"].is_number());
+
+        let res = app
+            .clone()
+            .oneshot(post_json(
+                "/policy/backtest",
+                json!({"config": {"kinds": {"reminder": {"actions": []}}}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let metrics = state.encode_metrics().unwrap();
+        assert!(
+            metrics.contains(r#"policy_feedback_reward_count{kind="reminder",action="notify"} 1"#)
+        );
+        assert!(metrics
+            .contains(r#"policy_feedback_duplicates_total{kind="reminder",action="notify"} 1"#));
+
+        for (body, status) in [
+            (
+                json!({"decision_id": "missing", "reward": 1.0}),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                json!({"decision_id": decision.id, "reward": 1.0, "context": [1]}),
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(post_json("/policy/feedback", body))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+        }
+
+        let res = app
+            .clone()
+            .oneshot(post_json(
+                "/policy/reset",
+                json!({"kind": "reminder", "reason": "test"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let reset: policy_api::PolicyResetResponse = from_slice(&body).unwrap();
+        assert_eq!(reset.reset, ["reminder"]);
+        assert_eq!(plays(&state), Some(0));
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/policy/audit").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let audit: policy_api::PolicyAuditResponse = from_slice(&body).unwrap();
+        assert!(audit
+            .events
+            .iter()
+            .any(|event| event.id == reset.audit_id && event.detail["reason"] == "test"));
+
+        let res = app
+            .oneshot(post_json("/policy/reset", json!({"kind": "dance"})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn egress_allowlist_changes_apply_persist_and_audit() {
+        let flags = FeatureFlags {
+            api_token: Some("admin".into()),
+            ..FeatureFlags::default()
+        };
+        let (app, state) = demo_app_with_origin_and_flags(
+            false,
+            flags,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("routing.yaml");
+        state.set_routing_path(&path);
+        let reason = format!("done-{}", ulid::Ulid::new());
+        let request = |method: Method, uri: &str, body: Option<serde_json::Value>| {
+            let builder = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, "Bearer admin");
+            match body {
+                Some(body) => builder
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+                None => builder.body(Body::empty()).unwrap(),
+            }
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/egress/allowlist",
+                Some(json!({"entry": "https://API.Example.org", "requests_per_minute": 5, "reason": "test"})),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let added: egress_api::EgressAllowlistChangeResponse = from_slice(&body).unwrap();
+        assert!(added.persisted);
+        assert_eq!(
+            added.allowlist.entries[0].target,
+            "https://api.example.org:443"
+        );
+        assert_eq!(added.allowlist.entries[0].requests_per_minute, Some(5));
+        assert!(state
+            .egress_guard(egress::CALLER_WEBHOOKS)
+            .unwrap()
+            .ensure_allowed("https://api.example.org/x")
+            .is_ok());
+        let stored = load_routing(&path).unwrap();
+        assert_eq!(
+            stored.egress.unwrap().allow[0],
+            AllowItem::Limited(LimitedTarget {
+                target: "https://API.Example.org".into(),
+                requests_per_minute: Some(5),
+                max_concurrent: None,
+            })
+        );
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/egress/allowlist",
+                Some(json!({"entry": "https://api.example.org"})),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                Method::DELETE,
+                &format!("/egress/allowlist?entry=https://API.example.org:443&reason={reason}"),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = app
+            .clone()
+            .oneshot(request(Method::GET, "/egress/allowlist", None))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let listed: egress_api::EgressAllowlistResponse = from_slice(&body).unwrap();
+        assert!(listed.entries.is_empty());
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                Method::DELETE,
+                "/egress/allowlist?entry=https://api.example.org",
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let events = state.policy().log().events(500).unwrap();
+        let changes: Vec<_> = events
+            .iter()
+            .filter(|event| event.event == "egress_allowlist")
+            .filter(|event| event.id == added.audit_id || event.detail["reason"] == reason)
+            .map(|event| event.detail["action"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(changes, ["remove", "add"]);
+    }
+
+    #[tokio::test]
+    async fn egress_allowlist_changes_require_api_token() {
+        let res = demo_app(false)
+            .oneshot(
+                Request::post("/egress/allowlist")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"entry": "example.org"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn index_forget_is_audited_with_reason() {
+        let app = demo_app(false);
+        let post_json = |uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let doc_id = format!("forget-audit-{}", ulid::Ulid::new());
+        let res = app
+            .clone()
+            .oneshot(post_json(
+                "/index/upsert",
+                json!({
+                    "doc_id": doc_id,
+                    "namespace": "default",
+                    "chunks": [{"chunk_id": format!("{doc_id}#0"), "text": "weg damit", "embedding": []}],
+                    "meta": {},
+                    "source_ref": {"origin": "test", "id": doc_id, "trust_level": "high"},
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .clone()
+            .oneshot(post_json(
+                "/index/forget",
+                json!({
+                    "filter": {"doc_id": doc_id},
+                    "reason": "Testdaten entfernen",
+                    "confirm": true,
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .oneshot(Request::get("/policy/audit").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let audit: policy_api::PolicyAuditResponse = from_slice(&body).unwrap();
+        assert!(audit
+            .events
+            .iter()
+            .any(|event| event.event == "index_forget"
+                && event.detail["doc_ids"] == json!([doc_id])
+                && event.detail["reason"] == "Testdaten entfernen"));
+    }
+
+    #[tokio::test]
+    async fn self_state_matches_contract_and_tracks_budgets() {
+        let app = demo_app(false);
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/ask?q=budget&k=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app
+            .oneshot(Request::get("/self/state").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let document: serde_json::Value = from_slice(&body).unwrap();
+
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../../../contracts/self_state.schema.json"))
+                .unwrap();
+        assert_eq!(document["$schema"], schema["$id"]);
+        for key in schema["required"].as_array().unwrap() {
+            let key = key.as_str().unwrap();
+            assert!(document.get(key).is_some(), "missing key {key}");
+        }
+        let object = document.as_object().unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert!(object.keys().all(|key| properties.contains_key(key)));
+
+        let index_budget = document["budgets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|b| b["name"] == "index_topk20")
+            .unwrap();
+        assert!(index_budget["samples"].as_u64().unwrap() >= 1);
+        assert!(index_budget["within_budget"].is_boolean());
+    }
+
+    #[tokio::test]
+    async fn assist_streams_step_progress_over_sse() {
+        let app = demo_app(false);
+        let payload = json!({"question": "cargo build fails", "mode": "code"});
+
+        let res = app
+            .oneshot(
+                Request::post("/assist")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::ACCEPT, "text/event-stream")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/event-stream"));
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "started",
+                "stdout",
+                "completed",
+                "started",
+                "stdout",
+                "completed",
+                "result"
+            ]
+        );
+        let result = text
+            .lines()
+            .rev()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_str(result).unwrap();
+        assert_eq!(result["type"], "result");
+        assert!(result["data"]["answer"]
+            .as_str()
+            .unwrap()
+            .starts_with("Code analysis tool"));
+    }
+
+    #[tokio::test]
+    async fn sustained_pressure_sheds_batch_requests_with_retry_after() {
+        let limits = Limits {
+            shedding: SheddingLimits {
+                sustain_sec: 0,
+                retry_after_sec: 7,
+                ..Default::default()
+            },
+            ..Limits::default()
+        };
+        let (app, state) = build_app_with_state(
+            limits,
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            FeatureFlags::default(),
+            false,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        state.shedder().observe(&system::SignalEvent {
+            schema_version: system::SIGNAL_EVENT_SCHEMA_VERSION,
+            signal: system::SignalKind::MemoryPressureHigh,
+            state: system::CrossingState::Raised,
+            value: 97.0,
+            threshold: 90.0,
+            subject: None,
+            occurred_at: chrono::Utc::now(),
+        });
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::post("/jobs")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"kind": "retention_sweep"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers()[header::RETRY_AFTER], "7");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(body["reasons"], json!(["memory_pressure_high"]));
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/jobs").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut status = serde_json::Value::Null;
+        for _ in 0..50 {
+            let res = app
+                .clone()
+                .oneshot(
+                    Request::get("/system/shedding")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            status = from_slice(&body).unwrap();
+            if !status["events"].as_array().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(status["active"], true);
+        assert_eq!(status["events"][0]["kind"], "/jobs");
+        assert_eq!(status["events"][0]["detail"]["retry_after_sec"], 7);
+    }
+
+    #[tokio::test]
+    async fn asr_transcribe_accepts_uploads_and_guards_path_references() {
+        let models: ModelsFile = serde_yaml_ng::from_str(
+            "models:\n  - id: llama\n    path: /m/llama.gguf\n  - id: whisper-tiny\n    path: /nonexistent/ggml-tiny.bin\n",
+        )
+        .unwrap();
+        let (app, _state) = build_app_with_state(
+            Limits::default(),
+            models,
+            RoutingPolicy::default(),
+            FeatureFlags::default(),
+            false,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/asr/models").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(
+            body["models"],
+            json!([{"id": "whisper-tiny", "available": false, "default": true}])
+        );
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::post("/asr/transcribe")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"path": "/etc/hostname"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let upload = |fields: &str| {
+            let body = format!(
+                "--b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"memo.wav\"\r\n\r\nRIFF\r\n{fields}--b--\r\n"
+            );
+            Request::post("/asr/transcribe")
+                .header(header::CONTENT_TYPE, "multipart/form-data; boundary=b")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let res = app
+            .clone()
+            .oneshot(upload(
+                "--b\r\nContent-Disposition: form-data; name=\"language\"\r\n\r\nde\r\n",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = from_slice(&body).unwrap();
+        assert!(body["error"].as_str().unwrap().contains("ggml-tiny.bin"));
+
+        let res = app
+            .oneshot(upload(
+                "--b\r\nContent-Disposition: form-data; name=\"speed\"\r\n\r\n2\r\n",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn jobs_ingest_in_background_and_reject_late_cancel() {
+        let app = demo_app(false);
+        let post_job = |payload: serde_json::Value| {
+            Request::post("/jobs")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(post_job(json!({"kind": "ingest", "documents": []})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let documents: Vec<_> = (0..3)
+            .map(|i| {
+                json!({
+                    "doc_id": format!("job-doc-{i}"),
+                    "namespace": "jobs",
+                    "chunks": [{"chunk_id": format!("job-doc-{i}#0"), "text": "background ingestion works"}],
+                    "source_ref": {"origin": "test", "id": format!("job-doc-{i}"), "trust_level": "high"}
+                })
+            })
+            .chain(std::iter::once(json!({"namespace": "jobs"})))
+            .collect();
+        let res = app
+            .clone()
+            .oneshot(post_job(json!({"kind": "ingest", "documents": documents})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let created: serde_json::Value = from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let mut record = serde_json::Value::Null;
+        for _ in 0..100 {
+            let res = app
+                .clone()
+                .oneshot(
+                    Request::get(format!("/jobs/{id}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            record = from_slice(&body).unwrap();
+            if record["status"] == "succeeded" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(record["status"], "succeeded");
+        assert_eq!(record["progress"], 100.0);
+        assert_eq!(record["result"]["ingested"], 3);
+        assert_eq!(record["result"]["failed"].as_array().unwrap().len(), 1);
+        assert_eq!(record["attempts"], 1);
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/jobs/queue").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let queue: serde_json::Value = from_slice(&body).unwrap();
+        assert!(queue["dead_letters"].is_array());
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/jobs").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let listing: serde_json::Value = from_slice(&body).unwrap();
+        assert!(listing["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|job| job["id"] == id.as_str()));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::delete(format!("/jobs/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = app
+            .oneshot(
+                Request::delete("/jobs/01UNKNOWNJOB")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn scheduler_lists_nothing_without_config() {
+        let app = demo_app(false);
+        let res = app
+            .oneshot(
+                Request::get("/scheduler/schedules")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let listing: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(listing["schedules"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn webhook_outbox_reports_subscriptions_and_dead_letters() {
+        let app = demo_app(false);
+        let res = app
+            .oneshot(
+                Request::get("/webhooks/outbox")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let outbox: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(outbox["subscriptions"], 0);
+        assert!(outbox["dead_letters"].is_array());
+    }
+
+    #[tokio::test]
+    async fn chronik_events_record_index_upserts() {
+        let app = demo_app(false);
+        let upsert_payload = json!({
+            "doc_id": "chronik-doc",
+            "namespace": "default",
+            "chunks": [
+                {"chunk_id": "chronik-doc#0", "text": "Hallo Chronik", "embedding": []}
+            ],
+            "meta": {"kind": "markdown"},
+            "source_ref": {
+                "origin": "test",
+                "id": "test-chronik-doc",
+                "trust_level": "high"
+            }
+        });
+        let upsert_res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/index/upsert")
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(upsert_payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(upsert_res.status(), StatusCode::OK);
+
+        let res = app
+            .oneshot(
+                Request::get("/chronik/events?kind=index&source=indexd")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let chronik: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(chronik["persistent"], false);
+        let events = chronik["events"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["kind"], "index.upserted");
+        assert_eq!(events[0]["payload"]["doc_id"], "chronik-doc");
+    }
+
+    #[tokio::test]
+    async fn usage_summary_starts_empty() {
+        let app = demo_app(false);
+        let res = app
+            .oneshot(Request::get("/usage").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let summary: serde_json::Value = from_slice(&body).unwrap();
+        assert_eq!(summary["totals"]["requests"], 0);
+        assert!(summary["by_model"].as_object().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn chat_stub_returns_service_unavailable_when_unconfigured() {
+        let _env = ChatEnvGuard::cleared();
+        let app = demo_app(false);
+        let payload = json!({
+            "messages": [
+                {"role": "user", "content": "Hallo HausKI?"}
+            ]
+        });
+
+        let res = app
+            .oneshot(
+                Request::post("/v1/chat")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let stub: ChatStubResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stub.status, "unavailable");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn chat_stub_unavailable_payload_matches_message() {
+        let _env = ChatEnvGuard::cleared();
+        let app = demo_app(false);
+        let payload = json!({
+            "messages": [
+                {"role": "user", "content": "Hallo HausKI?"}
+            ]
+        });
+
+        let res = app
+            .oneshot(
+                Request::post("/v1/chat")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let stub: ChatStubResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stub.status, "unavailable");
+        assert_eq!(
+            stub.message,
+            "chat pipeline not wired yet, please configure HAUSKI_CHAT_UPSTREAM_URL"
+        );
+    }
+
+    #[tokio::test]
+    async fn safe_mode_flag_is_reflected_in_state() {
+        let (_app, state) = demo_app_with_origin_and_flags(
+            false,
+            FeatureFlags {
+                safe_mode: true,
+                ..FeatureFlags::default()
+            },
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        assert!(state.safe_mode());
+        assert!(state.flags().safe_mode);
+    }
+
+    #[tokio::test]
+    async fn escalation_is_refused_under_safe_mode() {
+        let routing: RoutingPolicy = serde_yaml_ng::from_str(
+            "routing:\n  cloud_fallback:\n    enabled: true\n    require_consent: false\n",
+        )
+        .unwrap();
+        let request = || {
+            Request::post("/policy/escalate")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({"intent": "chat"}).to_string()))
+                .unwrap()
+        };
+        for (safe_mode, target) in [(false, "cloud"), (true, "local")] {
+            let (app, _state) = build_app_with_state(
+                Limits::default(),
+                ModelsFile::default(),
+                routing.clone(),
+                FeatureFlags {
+                    safe_mode,
+                    ..FeatureFlags::default()
+                },
+                false,
+                HeaderValue::from_static("http://127.0.0.1:8080"),
+            );
+            let res = app.oneshot(request()).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let decision: escalation_api::EscalateResponse = from_slice(&body).unwrap();
+            assert_eq!(decision.target, target);
+            assert_eq!(decision.safe_mode, safe_mode);
+            assert_eq!(decision.checks[0].passed, !safe_mode);
+        }
     }
 
     #[tokio::test]
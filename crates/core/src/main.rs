@@ -1,21 +1,17 @@
 use axum::http::HeaderValue;
-use hauski_core::{build_app_with_state, load_flags, load_limits, load_models, load_routing};
+use hauski_core::{build_app_from_config, init_tracing, load_layered, ConfigLayers};
 use std::{env, net::SocketAddr};
 use tokio::{net::TcpListener, signal};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    init_tracing();
 
-    let limits_path = env::var("HAUSKI_LIMITS").unwrap_or_else(|_| "./policies/limits.yaml".into());
-    let models_path = env::var("HAUSKI_MODELS").unwrap_or_else(|_| "./configs/models.yml".into());
-    let routing_path =
-        env::var("HAUSKI_ROUTING").unwrap_or_else(|_| "./policies/routing.yaml".into());
-    let flags_path = env::var("HAUSKI_FLAGS").unwrap_or_else(|_| "./configs/flags.yaml".into());
+    let layers = ConfigLayers::from_env();
+    let config = load_layered(&layers)?;
+    if let Some(path) = &config.path {
+        tracing::info!(path = %path.display(), sources = ?config.sources, "loaded hauski.yml");
+    }
     let expose_config = env::var("HAUSKI_EXPOSE_CONFIG")
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
         .unwrap_or(false);
@@ -26,22 +22,24 @@ async fn main() -> anyhow::Result<()> {
         anyhow::anyhow!("invalid HAUSKI_ALLOWED_ORIGIN '{}': {}", allowed_origin, e)
     })?;
 
-    let (app, state) = build_app_with_state(
-        load_limits(limits_path)?,
-        load_models(models_path)?,
-        load_routing(routing_path)?,
-        load_flags(flags_path)?,
-        expose_config,
-        allowed_origin_header,
-    );
+    let (app, state) = build_app_from_config(config, expose_config, allowed_origin_header);
+    state.watch_config(layers);
 
     let addr = resolve_bind_addr(expose_config)?;
+    if !addr.ip().is_loopback() && state.flags().api_token.is_none() {
+        tracing::warn!(%addr, "non-loopback bind without HAUSKI_API_TOKEN – API is unauthenticated");
+    }
     tracing::info!(%addr, expose_config, "starting server");
     let listener = TcpListener::bind(addr).await?;
     state.set_ready();
+    state.resume_jobs().await;
+    state.resume_outbox().await;
+    state.start_scheduler();
+    state.start_policy_persistence();
     axum::serve(listener, app)
         .with_graceful_shutdown(shutdown_signal())
         .await?;
+    state.save_policy_state();
     Ok(())
 }
 
@@ -1,6 +1,8 @@
 use axum::{
+    body::Body,
     extract::State,
-    http::StatusCode,
+    http::{Request, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
@@ -65,6 +67,66 @@ pub struct MemoryEvictResponse {
     pub ok: bool,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "MemoryStatsResponse", example = json!({"total":12,"pinned":2,"unpinned":10,"expired_evictions_total":3}))]
+pub struct MemoryStatsResponse {
+    pub total: u64,
+    pub pinned: u64,
+    pub unpinned: u64,
+    /// TTL evictions by the janitor since process start.
+    pub expired_evictions_total: u64,
+}
+
+const DEFAULT_LIST_LIMIT: usize = 50;
+const MAX_LIST_LIMIT: usize = 500;
+
+fn default_list_limit() -> usize {
+    DEFAULT_LIST_LIMIT
+}
+
+fn default_include_values() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(title = "MemoryListRequest", example = json!({"namespace":"ask.session","prefix":"","limit":50,"include_values":false}))]
+pub struct MemoryListRequest {
+    /// Literal key prefix (no wildcards). Combined with `namespace` if both are set.
+    #[serde(default)]
+    pub prefix: String,
+    /// Restricts the listing to keys of the form `<namespace>:...`.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// `next_cursor` of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Page size (1-500, default 50).
+    #[serde(default = "default_list_limit")]
+    pub limit: usize,
+    /// Set to false to list keys and metadata only.
+    #[serde(default = "default_include_values")]
+    pub include_values: bool,
+}
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "MemoryListItem")]
+pub struct MemoryListItem {
+    pub key: String,
+    /// Omitted if the request set `include_values=false`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub ttl_sec: Option<i64>,
+    pub pinned: bool,
+    pub created_ts: String,
+    pub updated_ts: String,
+}
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "MemoryListResponse", example = json!({"items":[{"key":"ask.session:chat-1","ttl_sec":3600,"pinned":false,"created_ts":"2025-01-01T00:00:00Z","updated_ts":"2025-01-01T00:00:00Z"}],"next_cursor":"ask.session:chat-1"}))]
+pub struct MemoryListResponse {
+    pub items: Vec<MemoryListItem>,
+    /// Pass as `cursor` to fetch the next page; `null` on the last page.
+    pub next_cursor: Option<String>,
+}
+
 // ---------------------- Policy ----------------------
 #[derive(Debug, Clone, Default, Deserialize)]
 struct MemoryPolicy {
@@ -88,7 +150,7 @@ fn policy_load_once() -> &'static MemoryPolicy {
         let p = Path::new(&path);
         if p.exists() {
             match fs::read_to_string(p) {
-                Ok(text) => match serde_yaml_ng::from_str::<MemoryPolicy>(&text) {
+                Ok(text) => match crate::config::from_yaml_str::<MemoryPolicy>(&text, p) {
                     Ok(cfg) => cfg,
                     Err(err) => {
                         tracing::warn!("memory policy parse failed: {err} – using defaults");
@@ -120,6 +182,48 @@ fn is_pin_allowed(key: &str, allowlist: &[String]) -> bool {
     false
 }
 
+// ---------------------- Auth ----------------------
+
+/// Route layer for `/memory/*`: requires `Authorization: Bearer <memory_token>`.
+///
+/// Without a configured token the memory API stays closed (403), analog zu `/events`.
+/// The API-wide `api_token` is accepted as well, so one remote client token suffices.
+pub async fn require_memory_token(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(token) = state.flags().memory_token else {
+        tracing::warn!("memory API is disabled (HAUSKI_MEMORY_TOKEN not set)");
+        return (
+            StatusCode::FORBIDDEN,
+            Json(MemoryErrorResponse {
+                error: "memory API requires a configured token".to_string(),
+            }),
+        )
+            .into_response();
+    };
+
+    let api_token = state.flags().api_token;
+    let authorized = crate::api_auth::bearer(&req).is_some_and(|provided| {
+        crate::api_auth::token_matches(provided, &token)
+            || api_token
+                .is_some_and(|api_token| crate::api_auth::token_matches(provided, &api_token))
+    });
+    if !authorized {
+        tracing::warn!(path = %req.uri().path(), "unauthorized memory API request");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(MemoryErrorResponse {
+                error: "unauthorized".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
 // ---------------------- Handlers ----------------------
 
 #[utoipa::path(
@@ -268,3 +372,111 @@ pub async fn memory_evict_handler(
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/memory/list",
+    tag = "core",
+    request_body = MemoryListRequest,
+    responses(
+        (status=200, body=MemoryListResponse),
+        (status=400, body=MemoryErrorResponse, description="invalid request"),
+        (status=500, body=MemoryErrorResponse, description="internal error")
+    )
+)]
+pub async fn memory_list_handler(
+    _state: State<AppState>,
+    Json(req): Json<MemoryListRequest>,
+) -> Response {
+    if req.limit == 0 || req.limit > MAX_LIST_LIMIT {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(MemoryErrorResponse {
+                error: format!("limit must be between 1 and {MAX_LIST_LIMIT}"),
+            }),
+        )
+            .into_response();
+    }
+
+    let prefix = match req.namespace.as_deref() {
+        Some(ns) if !ns.is_empty() => format!("{ns}:{}", req.prefix),
+        _ => req.prefix,
+    };
+
+    let result = mem::global()
+        .list(mem::ListOptions {
+            prefix,
+            after: req.cursor,
+            limit: req.limit,
+            include_values: req.include_values,
+        })
+        .await;
+
+    match result {
+        Ok(page) => {
+            let items = page
+                .items
+                .into_iter()
+                .map(|item| MemoryListItem {
+                    key: item.key,
+                    value: item
+                        .value
+                        .map(|value| String::from_utf8_lossy(&value).into_owned()),
+                    ttl_sec: item.ttl_sec,
+                    pinned: item.pinned,
+                    created_ts: item.created_ts.to_rfc3339(),
+                    updated_ts: item.updated_ts.to_rfc3339(),
+                })
+                .collect();
+            (
+                StatusCode::OK,
+                Json(MemoryListResponse {
+                    items,
+                    next_cursor: page.next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "failed to list memory items");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(MemoryErrorResponse {
+                    error: "internal error".to_string(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/memory/stats",
+    tag = "core",
+    responses((status=200, body=MemoryStatsResponse), (status=500, body=MemoryErrorResponse, description="internal error"))
+)]
+pub async fn memory_stats_handler(_state: State<AppState>) -> Response {
+    match mem::global().stats().await {
+        Ok(stats) => (
+            StatusCode::OK,
+            Json(MemoryStatsResponse {
+                total: stats.pinned + stats.unpinned,
+                pinned: stats.pinned,
+                unpinned: stats.unpinned,
+                expired_evictions_total: stats.expired_evictions_total,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!(error = ?e, "failed to read memory stats");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(MemoryErrorResponse {
+                    error: "internal error".to_string(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
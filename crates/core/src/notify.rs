@@ -0,0 +1,485 @@
+//! Benachrichtigungen: interne API [`notify`] für Subsysteme plus
+//! `POST /notify` und `GET /notify/channels`.
+//!
+//! Kanäle (Desktop, ntfy, Matrix) stehen in `HAUSKI_NOTIFICATIONS` (Default
+//! `./configs/notifications.yaml`); Abos, Ruhezeiten, Rate-Limits und Vorlagen
+//! wertet `hauski-notify` aus. ntfy und Matrix laufen über den Egress-Client
+//! (Aufrufer `notify`), ihre Ziele müssen also in der Egress-Policy stehen.
+//! Zugestellt wird einmal und im Hintergrund; Fehlschläge landen im Log und in
+//! `notifications_total{outcome="failed"}`.
+//!
+//! Ereignisse aus dem Core:
+//!   job.completed  – ein Hintergrund-Job ist fertig (fehlgeschlagen = `high`)
+//!   quarantine     – indexd hat ein Dokument in Quarantäne verschoben (`high`)
+//!   system.*       – Schwellen der Systemsignale, z. B. `system.gpu_throttle`
+//!                    (ausgelöst `high` bzw. `normal`, aufgehoben `low`)
+
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Local;
+use hauski_indexd::QuarantineNotice;
+use hauski_notify::{
+    ChannelKind, ChannelSpec, Decision, Delivery, Message, Notification, NotificationsFile,
+    Notifier, Priority,
+};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::{
+    egress,
+    jobs::{JobRecord, JobStatus},
+    system::{CrossingState, SignalEvent, SignalKind},
+    AppState,
+};
+
+const DEFAULT_NOTIFICATIONS_PATH: &str = "./configs/notifications.yaml";
+/// Upper bound for a `notify-send` call.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct DeliveryLabels {
+    channel: String,
+    outcome: &'static str,
+}
+
+#[derive(Debug)]
+pub struct NotifyService {
+    notifier: Notifier,
+    deliveries: Family<DeliveryLabels, Counter>,
+}
+
+impl NotifyService {
+    pub(crate) fn new(notifier: Notifier) -> Self {
+        Self {
+            notifier,
+            deliveries: Family::default(),
+        }
+    }
+
+    /// A missing file means no channels; an invalid one is logged and
+    /// disables all channels.
+    pub(crate) fn load_from_env() -> Self {
+        let path = std::env::var("HAUSKI_NOTIFICATIONS")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_NOTIFICATIONS_PATH.to_string());
+        let notifier = Notifier::new(load_channels(Path::new(&path))).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "invalid notification channels – notifications disabled");
+            Notifier::default()
+        });
+        Self::new(notifier)
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "notifications",
+            "Total number of notifications by channel and outcome (sent/failed/quiet/rate_limited)",
+            self.deliveries.clone(),
+        );
+    }
+
+    fn count(&self, channel: &str, outcome: &'static str) {
+        self.deliveries
+            .get_or_create(&DeliveryLabels {
+                channel: channel.to_string(),
+                outcome,
+            })
+            .inc();
+    }
+}
+
+fn load_channels(path: &Path) -> Vec<ChannelSpec> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match std::fs::read_to_string(path) {
+        Ok(text) => match crate::config::from_yaml_str::<NotificationsFile>(&text, path) {
+            Ok(file) => file.channels,
+            Err(err) => {
+                tracing::warn!("notifications parse failed: {err} – no channels");
+                Vec::new()
+            }
+        },
+        Err(err) => {
+            tracing::warn!("notifications read failed: {err} – no channels");
+            Vec::new()
+        }
+    }
+}
+
+/// Hands `notification` to every interested channel and returns the
+/// decision per channel; deliveries run in the background.
+pub(crate) fn notify(state: &AppState, notification: Notification) -> Vec<(String, Decision)> {
+    let service = state.notifications();
+    let routed: Vec<(ChannelSpec, Decision)> = service
+        .notifier
+        .route(&notification, Instant::now(), Local::now().time())
+        .into_iter()
+        .map(|(channel, decision)| (channel.clone(), decision))
+        .collect();
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    for (channel, decision) in &routed {
+        let Decision::Send(message) = decision else {
+            service.count(&channel.id, decision.as_str());
+            continue;
+        };
+        let Some(runtime) = &runtime else {
+            service.count(&channel.id, "failed");
+            continue;
+        };
+        let (state, channel, message) = (state.clone(), channel.clone(), message.clone());
+        runtime.spawn(async move {
+            let outcome = match deliver(&state, &channel, &message).await {
+                Ok(()) => "sent",
+                Err(err) => {
+                    tracing::warn!(channel = %channel.id, event = %message.event, error = %err, "notification failed");
+                    "failed"
+                }
+            };
+            state.notifications().count(&channel.id, outcome);
+        });
+    }
+    routed
+        .into_iter()
+        .map(|(channel, decision)| (channel.id, decision))
+        .collect()
+}
+
+async fn deliver(state: &AppState, channel: &ChannelSpec, message: &Message) -> Result<(), String> {
+    let txn_id = Ulid::new().to_string();
+    let service = state.notifications();
+    let delivery = channel
+        .delivery(message, service.notifier.notify_send_bin(), &txn_id)
+        .map_err(|err| err.to_string())?;
+    match delivery {
+        Delivery::Command(command) => {
+            let bin = command.get_program().to_string_lossy().into_owned();
+            let mut command = tokio::process::Command::from(command);
+            command.kill_on_drop(true);
+            let output = tokio::time::timeout(COMMAND_TIMEOUT, command.output())
+                .await
+                .map_err(|_| format!("{bin} timed out"))?
+                .map_err(|err| {
+                    format!("{bin} could not be started (set HAUSKI_NOTIFY_SEND_BIN): {err}")
+                })?;
+            if !output.status.success() {
+                return Err(format!(
+                    "{bin} failed ({}): {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            Ok(())
+        }
+        Delivery::Http(request) => {
+            let client = state.egress_client(egress::CALLER_NOTIFY)?;
+            let method = reqwest::Method::from_bytes(request.method.as_bytes())
+                .map_err(|err| err.to_string())?;
+            let mut builder = client
+                .request(method, &request.url)
+                .map_err(|err| err.to_string())?;
+            for (name, value) in &request.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            let response = client
+                .send(builder.body(request.body))
+                .await
+                .map_err(|err| err.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("{} answered {}", request.url, response.status()));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Notification for a finished background job.
+pub(crate) fn job_notification(record: &JobRecord) -> Notification {
+    let (title, priority) = match record.status {
+        JobStatus::Failed => ("Job fehlgeschlagen", Priority::High),
+        JobStatus::Cancelled => ("Job abgebrochen", Priority::Low),
+        _ => ("Job fertig", Priority::Normal),
+    };
+    Notification::new(
+        "job.completed",
+        format!("{title}: {}", record.kind.as_str()),
+        priority,
+    )
+    .body(record.error.clone().unwrap_or_default())
+    .field("job_id", record.id.clone())
+    .field("kind", record.kind.as_str())
+    .field("status", record.status.as_str())
+}
+
+/// Notification for a document indexd moved to quarantine.
+pub(crate) fn quarantine_notification(notice: &QuarantineNotice) -> Notification {
+    Notification::new(
+        "quarantine",
+        format!("Dokument in Quarantäne: {}", notice.doc_id),
+        Priority::High,
+    )
+    .body(format!(
+        "Herkunft {}, gedacht für '{}'",
+        notice.origin, notice.original_namespace
+    ))
+    .field("doc_id", notice.doc_id.clone())
+    .field("origin", notice.origin.clone())
+    .field("namespace", notice.original_namespace.clone())
+}
+
+/// Notification for a threshold crossing of the system signals.
+fn signal_notification(event: &SignalEvent) -> Notification {
+    let raised = event.state == CrossingState::Raised;
+    let (title, body, priority) = match (event.signal, raised) {
+        (SignalKind::GpuThrottle, true) => (
+            "GPU gedrosselt".to_string(),
+            format!("{:.0} °C, Limit {:.0} °C", event.value, event.threshold),
+            Priority::High,
+        ),
+        (SignalKind::GpuThrottle, false) => (
+            "GPU wieder unter dem Limit".to_string(),
+            format!("{:.0} °C", event.value),
+            Priority::Low,
+        ),
+        (SignalKind::CpuHigh, _) => (
+            if raised {
+                "CPU-Last hoch"
+            } else {
+                "CPU-Last normal"
+            }
+            .to_string(),
+            format!("{:.0} %, Schwelle {:.0} %", event.value, event.threshold),
+            if raised {
+                Priority::Normal
+            } else {
+                Priority::Low
+            },
+        ),
+        (SignalKind::MemoryPressureHigh, _) => (
+            if raised {
+                "Speicherdruck hoch"
+            } else {
+                "Speicherdruck normal"
+            }
+            .to_string(),
+            format!("{:.0} %, Schwelle {:.0} %", event.value, event.threshold),
+            if raised {
+                Priority::Normal
+            } else {
+                Priority::Low
+            },
+        ),
+        (SignalKind::DiskLow, _) => {
+            let disk = event.subject.as_deref().unwrap_or("?");
+            (
+                if raised {
+                    format!("Wenig Speicherplatz: {disk}")
+                } else {
+                    format!("Speicherplatz wieder frei: {disk}")
+                },
+                format!("{:.1} GiB frei", event.value / f64::from(1_u32 << 30)),
+                if raised {
+                    Priority::High
+                } else {
+                    Priority::Low
+                },
+            )
+        }
+    };
+    let state = if raised { "raised" } else { "cleared" };
+    Notification::new(event.signal.event_kind(), title, priority)
+        .body(body)
+        .field("state", state)
+}
+
+/// Forwards system threshold crossings; quarantine and jobs call [`notify`]
+/// themselves.
+pub(crate) fn attach(state: &AppState) {
+    // Weak, so the monitor does not keep the state alive.
+    let weak = Arc::downgrade(&state.0);
+    state
+        .system_monitor()
+        .on_event(Arc::new(move |event: &SignalEvent| {
+            if let Some(inner) = weak.upgrade() {
+                notify(&AppState(inner), signal_notification(event));
+            }
+        }));
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyRequest {
+    /// Event name channels subscribe to (default `manual`).
+    #[serde(default = "default_event")]
+    pub event: String,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Extra values for `{{name}}` in channel templates.
+    #[serde(default)]
+    pub fields: std::collections::BTreeMap<String, String>,
+}
+
+fn default_event() -> String {
+    "manual".into()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotifyRouted {
+    pub channel: String,
+    /// `send`, `quiet` or `rate_limited`.
+    pub decision: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotifyResponse {
+    /// Channels interested in the notification; empty if none is.
+    pub channels: Vec<NotifyRouted>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotifyChannel {
+    pub id: String,
+    pub kind: ChannelKind,
+    pub events: Vec<String>,
+    pub min_priority: Priority,
+    /// Whether the channel is in its quiet hours right now.
+    pub quiet_now: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotifyChannelsResponse {
+    pub channels: Vec<NotifyChannel>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/notify",
+    tag = "core",
+    request_body = NotifyRequest,
+    responses(
+        (status = 202, description = "Notification handed to the interested channels", body = NotifyResponse),
+        (status = 400, description = "Empty title or event")
+    )
+)]
+pub async fn notify_handler(
+    State(state): State<AppState>,
+    Json(request): Json<NotifyRequest>,
+) -> Response {
+    let started = Instant::now();
+    let response = if request.title.trim().is_empty() || request.event.trim().is_empty() {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "title and event must not be empty" })),
+        )
+            .into_response()
+    } else {
+        let notification = Notification {
+            event: request.event,
+            title: request.title,
+            body: request.body,
+            priority: request.priority,
+            fields: request.fields,
+        };
+        let channels = notify(&state, notification)
+            .into_iter()
+            .map(|(channel, decision)| NotifyRouted {
+                channel,
+                decision: decision.as_str().to_string(),
+            })
+            .collect();
+        (StatusCode::ACCEPTED, Json(NotifyResponse { channels })).into_response()
+    };
+    state.record_http_observation(Method::POST, "/notify", response.status(), started);
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/notify/channels",
+    tag = "core",
+    responses(
+        (status = 200, description = "Configured notification channels", body = NotifyChannelsResponse)
+    )
+)]
+pub async fn channels_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let now = Local::now().time();
+    let channels = state
+        .notifications()
+        .notifier
+        .channels()
+        .iter()
+        .map(|channel| NotifyChannel {
+            id: channel.id.clone(),
+            kind: channel.kind,
+            events: channel.events.clone(),
+            min_priority: channel.min_priority,
+            quiet_now: channel
+                .quiet_hours
+                .as_ref()
+                .is_some_and(|quiet| quiet.contains(now)),
+        })
+        .collect();
+    state.record_http_observation(Method::GET, "/notify/channels", StatusCode::OK, started);
+    Json(NotifyChannelsResponse { channels }).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn signal_events_become_notifications() {
+        let event = |signal, state, value, subject: Option<&str>| SignalEvent {
+            schema_version: 1,
+            signal,
+            state,
+            value,
+            threshold: 85.0,
+            subject: subject.map(str::to_string),
+            occurred_at: Utc::now(),
+        };
+        let throttle = signal_notification(&event(
+            SignalKind::GpuThrottle,
+            CrossingState::Raised,
+            91.0,
+            None,
+        ));
+        assert_eq!(throttle.event, "system.gpu_throttle");
+        assert_eq!(throttle.title, "GPU gedrosselt");
+        assert_eq!(throttle.body, "91 °C, Limit 85 °C");
+        assert_eq!(throttle.priority, Priority::High);
+
+        let disk = signal_notification(&event(
+            SignalKind::DiskLow,
+            CrossingState::Cleared,
+            f64::from(3_u32 << 30),
+            Some("models"),
+        ));
+        assert_eq!(disk.title, "Speicherplatz wieder frei: models");
+        assert_eq!(disk.body, "3.0 GiB frei");
+        assert_eq!(disk.priority, Priority::Low);
+        assert_eq!(disk.fields["state"], "cleared");
+    }
+}
@@ -0,0 +1,756 @@
+//! Webhook-Outbox: ausgehende Ereignisse werden zuerst in der dauerhaften
+//! Task-Queue (Lane `outbox`) abgelegt und dann zugestellt. Fehlgeschlagene
+//! Zustellungen (Netzwerkfehler, 408, 429, 5xx) werden mit exponentiellem
+//! Backoff wiederholt; andere 4xx-Antworten und von der Egress-Policy
+//! abgewiesene Ziele landen sofort als Dead Letter (`GET /webhooks/outbox`).
+//! Beim Serverstart nimmt [`resume_pending`] offene Zustellungen wieder auf.
+//!
+//! Ereignisse:
+//!   quarantine    – ein Dokument wurde von indexd automatisch in Quarantäne verschoben
+//!   job.completed – ein Hintergrund-Job ist abgeschlossen (auch an `webhook_url` des Jobs)
+//!
+//! Abonnements stehen in `HAUSKI_WEBHOOKS` (Default `./configs/webhooks.yaml`):
+//!
+//! ```yaml
+//! webhooks:
+//!   - url: https://hooks.example.org/hauski
+//!     events: [quarantine, job.completed]   # leer = alle Ereignisse
+//! ```
+//!
+//! Jede Zustellung trägt `X-HausKI-Event`, `X-HausKI-Delivery`,
+//! `X-HausKI-Attempt` und `X-HausKI-Timestamp`. Ist `HAUSKI_WEBHOOK_SECRET`
+//! gesetzt, kommt `X-HausKI-Signature: sha256=<hex>` hinzu – HMAC-SHA256 über
+//! `<timestamp>.<body>`.
+//!
+//! Konfiguration:
+//!   HAUSKI_WEBHOOK_MAX_ATTEMPTS  (Default 8)
+//!   HAUSKI_WEBHOOK_RETRY_BASE_MS (Default 2000; verdoppelt sich je Versuch, max. 10 Minuten)
+
+use std::{
+    fmt, fs,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family, histogram::Histogram},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::{
+    egress,
+    task_queue::{QueueEntry, RetryPolicy},
+    AllowlistedClient, AppState, GuardError, GuardedRequestError,
+};
+
+pub(crate) const EVENT_QUARANTINE: &str = "quarantine";
+pub(crate) const EVENT_JOB_COMPLETED: &str = "job.completed";
+
+const DEFAULT_WEBHOOKS_PATH: &str = "./configs/webhooks.yaml";
+/// Lane of the durable task queue holding webhook deliveries.
+const OUTBOX_QUEUE: &str = "outbox";
+/// Upper bound for dead letters in `GET /webhooks/outbox`.
+const MAX_LISTED_DEAD_LETTERS: usize = 50;
+const DELIVERY_BUCKETS: [f64; 9] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 15.0];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhooksFile {
+    #[serde(default)]
+    webhooks: Vec<Subscription>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Subscription {
+    url: String,
+    /// Subscribed event names; empty means all events.
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+impl Subscription {
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+/// Queue payload of a single delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Delivery {
+    url: String,
+    event: String,
+    body: Value,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DeliveryLabels {
+    endpoint: String,
+    outcome: &'static str,
+}
+
+impl EncodeLabelSet for DeliveryLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("endpoint", self.endpoint.as_str()).encode(encoder.encode_label())?;
+        ("outcome", self.outcome).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EndpointLabels {
+    endpoint: String,
+}
+
+impl EncodeLabelSet for EndpointLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("endpoint", self.endpoint.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+fn create_delivery_histogram() -> Histogram {
+    Histogram::new(DELIVERY_BUCKETS)
+}
+
+/// Metrics label for a target URL: `scheme://host:port`, without path or
+/// credentials.
+fn endpoint_label(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| "invalid".to_string())
+}
+
+/// Subscriptions, retry settings and delivery metrics.
+pub struct Outbox {
+    subscriptions: Vec<Subscription>,
+    retry: RetryPolicy,
+    secret: Option<String>,
+    deliveries: Family<DeliveryLabels, Counter>,
+    durations: Family<EndpointLabels, Histogram>,
+}
+
+impl Outbox {
+    fn new(subscriptions: Vec<Subscription>, retry: RetryPolicy, secret: Option<String>) -> Self {
+        Self {
+            subscriptions,
+            retry,
+            secret,
+            deliveries: Family::default(),
+            durations: Family::new_with_constructor(create_delivery_histogram),
+        }
+    }
+
+    pub fn load_from_env() -> Self {
+        let path = std::env::var("HAUSKI_WEBHOOKS")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_WEBHOOKS_PATH.to_string());
+        let retry = RetryPolicy::from_env(
+            "HAUSKI_WEBHOOK_MAX_ATTEMPTS",
+            8,
+            "HAUSKI_WEBHOOK_RETRY_BASE_MS",
+            2_000,
+        );
+        let secret = std::env::var("HAUSKI_WEBHOOK_SECRET")
+            .ok()
+            .filter(|value| !value.is_empty());
+        Self::new(load_subscriptions(Path::new(&path)), retry, secret)
+    }
+
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "webhook_deliveries",
+            "Total number of webhook delivery attempts by endpoint and outcome",
+            self.deliveries.clone(),
+        );
+        registry.register(
+            "webhook_delivery_duration_seconds",
+            "Duration of webhook delivery attempts by endpoint",
+            self.durations.clone(),
+        );
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    fn count(&self, url: &str, outcome: &'static str) {
+        self.deliveries
+            .get_or_create(&DeliveryLabels {
+                endpoint: endpoint_label(url),
+                outcome,
+            })
+            .inc();
+    }
+}
+
+fn load_subscriptions(path: &Path) -> Vec<Subscription> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    let file = match fs::read_to_string(path) {
+        Ok(text) => match crate::config::from_yaml_str::<WebhooksFile>(&text, path) {
+            Ok(file) => file,
+            Err(err) => {
+                tracing::warn!("webhooks parse failed: {err} – no subscriptions");
+                return Vec::new();
+            }
+        },
+        Err(err) => {
+            tracing::warn!("webhooks read failed: {err} – no subscriptions");
+            return Vec::new();
+        }
+    };
+    file.webhooks
+        .into_iter()
+        .filter(|sub| match url::Url::parse(&sub.url) {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::warn!(url = %sub.url, "invalid webhook url: {err} – subscription ignored");
+                false
+            }
+        })
+        .collect()
+}
+
+/// HMAC-SHA256 (RFC 2104) as lowercase hex.
+fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        let digest = Sha256::digest(key);
+        block[..digest.len()].copy_from_slice(&digest);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+
+    let mut inner = Sha256::new();
+    inner.update(pad(0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(pad(0x5c));
+    outer.update(inner.finalize());
+    outer
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut out, byte| {
+            use std::fmt::Write as _;
+            write!(&mut out, "{byte:02x}")
+                .expect("writing hexadecimal bytes to String cannot fail");
+            out
+        })
+}
+
+/// Why a delivery attempt failed.
+#[derive(Debug)]
+enum SendFailure {
+    /// Worth retrying (network error, 408, 429, 5xx).
+    Transient(String),
+    /// The receiver refused the delivery for good.
+    Permanent(String),
+    /// The egress policy does not allow the target.
+    Rejected(String),
+}
+
+async fn send_once(
+    outbox: &Outbox,
+    client: &AllowlistedClient,
+    id: &str,
+    attempt: u32,
+    delivery: &Delivery,
+) -> Result<(), SendFailure> {
+    let body = serde_json::to_string(&delivery.body)
+        .map_err(|err| SendFailure::Permanent(format!("body serialization failed: {err}")))?;
+    let timestamp = Utc::now().timestamp().to_string();
+    let mut request = client
+        .post(&delivery.url)
+        .map_err(|err| SendFailure::Rejected(err.to_string()))?
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-HausKI-Event", delivery.event.as_str())
+        .header("X-HausKI-Delivery", id)
+        .header("X-HausKI-Attempt", attempt.to_string())
+        .header("X-HausKI-Timestamp", timestamp.as_str());
+    if let Some(secret) = outbox.secret.as_deref() {
+        let signature =
+            hmac_sha256_hex(secret.as_bytes(), format!("{timestamp}.{body}").as_bytes());
+        request = request.header("X-HausKI-Signature", format!("sha256={signature}"));
+    }
+
+    let started = Instant::now();
+    let result = client.send(request.body(body)).await;
+    outbox
+        .durations
+        .get_or_create(&EndpointLabels {
+            endpoint: endpoint_label(&delivery.url),
+        })
+        .observe(started.elapsed().as_secs_f64());
+
+    let status = match result {
+        Ok(response) => response.status(),
+        // A rate limit clears up by itself; everything else the guard says does not.
+        Err(GuardedRequestError::Guard(err @ GuardError::RateLimited { .. })) => {
+            return Err(SendFailure::Transient(err.to_string()));
+        }
+        Err(GuardedRequestError::Guard(err)) => return Err(SendFailure::Rejected(err.to_string())),
+        Err(err) => return Err(SendFailure::Transient(err.to_string())),
+    };
+    if status.is_success() {
+        Ok(())
+    } else if status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+    {
+        Err(SendFailure::Transient(format!(
+            "receiver answered {status}"
+        )))
+    } else {
+        Err(SendFailure::Permanent(format!(
+            "receiver answered {status}"
+        )))
+    }
+}
+
+/// Delivers until success, a permanent failure or the last attempt; returns
+/// the final outcome label.
+async fn deliver(
+    outbox: Arc<Outbox>,
+    client: AllowlistedClient,
+    entry: QueueEntry,
+    delivery: Delivery,
+    previous_attempts: u32,
+    not_before: Duration,
+) -> &'static str {
+    let mut attempt = previous_attempts;
+    let mut delay = not_before;
+    loop {
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        attempt = entry.begin_attempt(attempt).await;
+        let err = match send_once(&outbox, &client, &entry.id, attempt, &delivery).await {
+            Ok(()) => {
+                outbox.count(&delivery.url, "delivered");
+                entry.settle().await;
+                return "delivered";
+            }
+            Err(SendFailure::Transient(err)) => err,
+            Err(SendFailure::Permanent(err)) => {
+                tracing::warn!(delivery_id = %entry.id, event = %delivery.event, error = %err, "webhook delivery refused");
+                outbox.count(&delivery.url, "dead_letter");
+                entry.bury(&err).await;
+                return "dead_letter";
+            }
+            Err(SendFailure::Rejected(err)) => {
+                tracing::warn!(delivery_id = %entry.id, event = %delivery.event, error = %err, "webhook target rejected by egress policy");
+                outbox.count(&delivery.url, "rejected");
+                entry.bury(&err).await;
+                return "rejected";
+            }
+        };
+        let Some(retry_in) = entry.fail_attempt(attempt, &err).await else {
+            tracing::warn!(delivery_id = %entry.id, event = %delivery.event, attempts = attempt, error = %err, "webhook delivery dead-lettered");
+            outbox.count(&delivery.url, "dead_letter");
+            return "dead_letter";
+        };
+        outbox.count(&delivery.url, "retry");
+        delay = retry_in;
+    }
+}
+
+fn allowlisted_client(state: &AppState) -> Option<AllowlistedClient> {
+    match state.egress_client(egress::CALLER_WEBHOOKS) {
+        Ok(client) => Some(client),
+        Err(err) => {
+            tracing::warn!(error = %err, "invalid egress policy – webhooks disabled");
+            None
+        }
+    }
+}
+
+/// Queues `body` for delivery to `url` as `event`; targets the egress policy
+/// rejects are counted and dropped right away.
+pub(crate) async fn send(state: &AppState, url: &str, event: &str, body: Value) {
+    let outbox = state.outbox();
+    let allowed = state
+        .egress_guard(egress::CALLER_WEBHOOKS)
+        .map_err(|err| err.to_string())
+        .and_then(|guard| guard.ensure_allowed(url).map_err(|err| err.to_string()));
+    if let Err(err) = allowed {
+        tracing::warn!(%url, event, error = %err, "webhook target rejected by egress policy");
+        outbox.count(url, "rejected");
+        return;
+    }
+    let Some(client) = allowlisted_client(state) else {
+        return;
+    };
+
+    let id = Ulid::new().to_string();
+    let delivery = Delivery {
+        url: url.to_string(),
+        event: event.to_string(),
+        body,
+    };
+    let store = match (hauski_memory::try_global(), serde_json::to_vec(&delivery)) {
+        (Some(store), Ok(payload)) => match store
+            .enqueue_task(
+                OUTBOX_QUEUE,
+                id.clone(),
+                event.to_string(),
+                payload,
+                outbox.retry.max_attempts,
+            )
+            .await
+        {
+            Ok(()) => Some(store),
+            Err(err) => {
+                tracing::warn!(delivery_id = %id, error = ?err, "webhook enqueue failed; delivering without durability");
+                None
+            }
+        },
+        _ => None,
+    };
+    let entry = QueueEntry::new(id, outbox.retry, store);
+    tokio::spawn(deliver(outbox, client, entry, delivery, 0, Duration::ZERO));
+}
+
+/// Queues `body` for every subscription of `event`.
+pub(crate) async fn publish(state: &AppState, event: &str, body: Value) {
+    let urls: Vec<String> = state
+        .outbox()
+        .subscriptions
+        .iter()
+        .filter(|sub| sub.wants(event))
+        .map(|sub| sub.url.clone())
+        .collect();
+    for url in urls {
+        send(state, &url, event, body.clone()).await;
+    }
+}
+
+/// Picks up deliveries left in the outbox by an earlier process. Call once at
+/// server start; returns the number of resumed deliveries.
+pub async fn resume_pending(state: &AppState) -> usize {
+    let Some(store) = hauski_memory::try_global() else {
+        return 0;
+    };
+    let tasks = match store.recover_tasks(OUTBOX_QUEUE).await {
+        Ok(tasks) => tasks,
+        Err(err) => {
+            tracing::warn!(error = ?err, "recovering webhook outbox failed");
+            return 0;
+        }
+    };
+    let Some(client) = allowlisted_client(state) else {
+        return 0;
+    };
+    let outbox = state.outbox();
+    let mut resumed = 0;
+    for task in tasks {
+        let delivery: Delivery = match serde_json::from_slice(&task.payload) {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                tracing::warn!(delivery_id = %task.id, error = %err, "dropping unreadable webhook delivery");
+                if let Err(err) = store.complete_task(task.id).await {
+                    tracing::warn!(error = ?err, "webhook outbox update failed");
+                }
+                continue;
+            }
+        };
+        let retry = RetryPolicy {
+            max_attempts: task.max_attempts,
+            ..outbox.retry
+        };
+        let not_before = (task.run_after - Utc::now()).to_std().unwrap_or_default();
+        tokio::spawn(deliver(
+            outbox.clone(),
+            client.clone(),
+            QueueEntry::new(task.id, retry, Some(store)),
+            delivery,
+            task.attempts,
+            not_before,
+        ));
+        resumed += 1;
+    }
+    if resumed > 0 {
+        tracing::info!(resumed, "resumed webhook deliveries");
+    }
+    resumed
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "OutboxDeadLetter")]
+pub struct OutboxDeadLetter {
+    pub id: String,
+    pub event: String,
+    /// `scheme://host:port` of the target.
+    pub endpoint: String,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub failed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "OutboxResponse")]
+pub struct OutboxResponse {
+    /// False if the memory store is unavailable; deliveries then only live in-process.
+    pub durable: bool,
+    pub subscriptions: usize,
+    pub queued: u64,
+    pub running: u64,
+    pub dead: u64,
+    /// Most recent dead letters first.
+    pub dead_letters: Vec<OutboxDeadLetter>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/outbox",
+    tag = "core",
+    responses((status = 200, description = "Webhook outbox: pending deliveries and dead letters", body = OutboxResponse))
+)]
+pub async fn outbox_handler(State(state): State<AppState>) -> Json<OutboxResponse> {
+    let started = Instant::now();
+    let mut response = OutboxResponse {
+        durable: false,
+        subscriptions: state.outbox().subscription_count(),
+        queued: 0,
+        running: 0,
+        dead: 0,
+        dead_letters: Vec::new(),
+    };
+    if let Some(store) = hauski_memory::try_global() {
+        match (
+            store.queue_stats(OUTBOX_QUEUE).await,
+            store
+                .dead_tasks(OUTBOX_QUEUE, MAX_LISTED_DEAD_LETTERS)
+                .await,
+        ) {
+            (Ok(stats), Ok(dead)) => {
+                response.durable = true;
+                response.queued = stats.queued;
+                response.running = stats.running;
+                response.dead = stats.dead;
+                response.dead_letters = dead
+                    .into_iter()
+                    .map(|task| OutboxDeadLetter {
+                        endpoint: serde_json::from_slice::<Delivery>(&task.payload)
+                            .map(|delivery| endpoint_label(&delivery.url))
+                            .unwrap_or_else(|_| "invalid".to_string()),
+                        id: task.id,
+                        event: task.kind,
+                        attempts: task.attempts,
+                        last_error: task.last_error,
+                        failed_at: task.updated_ts,
+                    })
+                    .collect();
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                tracing::warn!(error = ?err, "reading webhook outbox failed");
+            }
+        }
+    }
+    state.record_http_observation(Method::GET, "/webhooks/outbox", StatusCode::OK, started);
+    Json(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::post, Router};
+    use std::sync::Mutex;
+
+    fn outbox(secret: Option<&str>) -> Arc<Outbox> {
+        let retry = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        Arc::new(Outbox::new(Vec::new(), retry, secret.map(str::to_string)))
+    }
+
+    fn delivery(url: String) -> Delivery {
+        Delivery {
+            url,
+            event: EVENT_QUARANTINE.to_string(),
+            body: serde_json::json!({"doc_id": "doc-1"}),
+        }
+    }
+
+    fn outcome_count(outbox: &Outbox, url: &str, outcome: &'static str) -> u64 {
+        outbox
+            .deliveries
+            .get_or_create(&DeliveryLabels {
+                endpoint: endpoint_label(url),
+                outcome,
+            })
+            .get()
+    }
+
+    /// Local receiver answering with `statuses` in order (last one repeats)
+    /// and recording the request headers.
+    async fn receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<(HeaderMap, String)>>>) {
+        let seen: Arc<Mutex<Vec<(HeaderMap, String)>>> = Arc::default();
+        let log = seen.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| {
+                let log = log.clone();
+                let statuses = statuses.clone();
+                async move {
+                    let mut log = log.lock().unwrap();
+                    let status = statuses[log.len().min(statuses.len() - 1)];
+                    log.push((headers, body));
+                    StatusCode::from_u16(status).unwrap()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, seen)
+    }
+
+    fn client() -> AllowlistedClient {
+        AllowlistedClient::new(reqwest::Client::new(), crate::EgressGuard::allow_all())
+    }
+
+    #[test]
+    fn hmac_matches_rfc4231_vector() {
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn subscriptions_filter_by_event_and_labels_drop_paths() {
+        let all = Subscription {
+            url: "https://hooks.example.org/a".into(),
+            events: Vec::new(),
+        };
+        let jobs = Subscription {
+            url: "https://hooks.example.org/b".into(),
+            events: vec![EVENT_JOB_COMPLETED.into()],
+        };
+        assert!(all.wants(EVENT_QUARANTINE));
+        assert!(!jobs.wants(EVENT_QUARANTINE));
+        assert!(jobs.wants(EVENT_JOB_COMPLETED));
+        assert_eq!(
+            endpoint_label("https://user:pw@hooks.example.org/a?token=1"),
+            "https://hooks.example.org"
+        );
+        assert_eq!(
+            endpoint_label("http://127.0.0.1:9000/hook"),
+            "http://127.0.0.1:9000"
+        );
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_with_signed_requests() {
+        let (url, seen) = receiver(vec![503, 200]).await;
+        let outbox = outbox(Some("s3cret"));
+        let entry = QueueEntry::new("d1".into(), outbox.retry, None);
+
+        let outcome = deliver(
+            outbox.clone(),
+            client(),
+            entry,
+            delivery(url.clone()),
+            0,
+            Duration::ZERO,
+        )
+        .await;
+
+        assert_eq!(outcome, "delivered");
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        let (headers, body) = &seen[1];
+        assert_eq!(headers["x-hauski-event"], EVENT_QUARANTINE);
+        assert_eq!(headers["x-hauski-delivery"], "d1");
+        assert_eq!(headers["x-hauski-attempt"], "2");
+        let timestamp = headers["x-hauski-timestamp"].to_str().unwrap();
+        let expected = hmac_sha256_hex(b"s3cret", format!("{timestamp}.{body}").as_bytes());
+        assert_eq!(headers["x-hauski-signature"], format!("sha256={expected}"));
+        assert_eq!(outcome_count(&outbox, &url, "retry"), 1);
+        assert_eq!(outcome_count(&outbox, &url, "delivered"), 1);
+    }
+
+    #[tokio::test]
+    async fn client_errors_and_denied_targets_are_not_retried() {
+        let (url, seen) = receiver(vec![404]).await;
+        let outbox = outbox(None);
+
+        let outcome = deliver(
+            outbox.clone(),
+            client(),
+            QueueEntry::new("d2".into(), outbox.retry, None),
+            delivery(url.clone()),
+            0,
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(outcome, "dead_letter");
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert!(!seen.lock().unwrap()[0].0.contains_key("x-hauski-signature"));
+
+        let denied = AllowlistedClient::from_routing_policy(
+            reqwest::Client::new(),
+            &crate::RoutingPolicy::deny_all(),
+        )
+        .unwrap();
+        let outcome = deliver(
+            outbox.clone(),
+            denied,
+            QueueEntry::new("d3".into(), outbox.retry, None),
+            delivery(url.clone()),
+            0,
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(outcome, "rejected");
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert_eq!(outcome_count(&outbox, &url, "rejected"), 1);
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_are_dead_lettered() {
+        let (url, seen) = receiver(vec![500]).await;
+        let outbox = outbox(None);
+
+        let outcome = deliver(
+            outbox.clone(),
+            client(),
+            QueueEntry::new("d4".into(), outbox.retry, None),
+            delivery(url.clone()),
+            0,
+            Duration::ZERO,
+        )
+        .await;
+
+        assert_eq!(outcome, "dead_letter");
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        assert_eq!(outcome_count(&outbox, &url, "retry"), 2);
+        assert_eq!(outcome_count(&outbox, &url, "dead_letter"), 1);
+    }
+}
@@ -0,0 +1,1318 @@
+//! Policy-Entscheidungen über HTTP (`POST /policy/decide`).
+//!
+//! Andere Heimgewebe-Dienste schicken einen Kontext (`kind`, `features`) und
+//! erhalten die anzuwendende Aktion samt Score und Begründung. Pro `kind`
+//! lernt ein kontextueller Bandit aus `crates/policy`; im Shadow-Modus
+//! (Default) wird die Baseline angewandt und der Vorschlag nur mitgeliefert.
+//! Jede Entscheidung wird samt Kontext und Parameter-Hash im SQLite-Protokoll
+//! abgelegt (`GET /policy/decisions`) und als `policy.decision` auf dem
+//! Chronik-Bus veröffentlicht.
+//!
+//! Budgets je Intent (`budgets` in `decisions.yaml`) begrenzen Latenz, Tokens
+//! und Energie der angewandten Aktion: zu teure Aktionen werden auf die beste
+//! passende herabgestuft, passt keine, antwortet `/policy/decide` mit 422.
+//!
+//! Rückmeldungen kommen über `POST /policy/feedback` (`decision_id`, `reward`,
+//! optional `context`) und aktualisieren den Bandit für die angewandte Aktion.
+//! Pro Entscheidung zählt nur die erste Rückmeldung.
+//!
+//! `GET /policy/report` vergleicht die protokollierten Shadow-Entscheidungen
+//! mit ihrer Baseline (Divergenz, hypothetische Reward-Differenz, Konfidenz),
+//! als Grundlage für den Wechsel in den Live-Modus. `POST /policy/backtest`
+//! spielt die protokollierten Kontexte und Rewards gegen eine Kandidaten-
+//! Konfiguration ab und schätzt deren erwarteten Reward, bevor sie in
+//! `decisions.yaml` landet.
+//!
+//! Der gelernte Zustand wird beim Start geladen (nur bei passender
+//! `schema_version`; inkompatible Dateien werden nach `*.bak` verschoben),
+//! periodisch sowie beim Herunterfahren gespeichert. `POST /policy/reset`
+//! verwirft ihn für eine oder alle Arten und hinterlässt einen Audit-Eintrag
+//! (`GET /policy/audit`). Dort landen auch Neuladungen der Trust-/Kontext-
+//! Policies des Index (`POST /index/policy/reload`) und ausgeführte
+//! `POST /index/forget` samt Begründung (`index_forget`) sowie aus der
+//! Quarantäne freigegebene Dokumente (`index_quarantine_release`).
+//!
+//! Konfiguration:
+//!   HAUSKI_DECISION_POLICY_PATH (Default ./policies/decisions.yaml)
+//!   HAUSKI_POLICY_DB_PATH       (Default <state_dir>/hauski/policy_decisions.db)
+//!   HAUSKI_POLICY_STATE_PATH    (Default <state_dir>/hauski/policy_state.json)
+//!   HAUSKI_POLICY_SAVE_SEC      (Default 300; 0 = nur beim Herunterfahren)
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path as UrlPath, Query, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use hauski_indexd::IndexEvent;
+use policy::{
+    audit::{AuditEvent, DecisionLog, DecisionQuery, DecisionRecord, FeedbackRecord},
+    backtest::{backtest, BacktestReport},
+    engine::{Mode, PolicyConfig, PolicyDecision, PolicyEngine, PolicyError},
+    remind_bandit::DecisionContext,
+    report::{shadow_reports, ShadowReport},
+    state::EngineState,
+};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet, LabelSetEncoder},
+    metrics::{counter::Counter, family::Family, histogram::Histogram},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+// Used by utoipa's #[schema(example = json!(...))] attribute macros
+#[allow(unused_imports)]
+use serde_json::json;
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{chronik, AppState};
+
+const DEFAULT_CONFIG_PATH: &str = "policies/decisions.yaml";
+const MAX_KIND_CHARS: usize = 64;
+const DEFAULT_DECISIONS_LIMIT: usize = 50;
+const DEFAULT_REPORT_LIMIT: usize = 1_000;
+const DEFAULT_BACKTEST_LIMIT: usize = 10_000;
+const DEFAULT_AUDIT_LIMIT: usize = 50;
+const DEFAULT_SAVE_SEC: u64 = 300;
+
+/// Reward buckets; rewards are expected roughly in `[-1, 1]`.
+const REWARD_BUCKETS: [f64; 9] = [-1.0, -0.5, -0.25, 0.0, 0.25, 0.5, 0.75, 1.0, 2.0];
+/// Decision score buckets; scores are reward estimates plus exploration.
+const SCORE_BUCKETS: [f64; 10] = [-1.0, -0.5, 0.0, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0];
+/// Budget utilization buckets (`1.0` = budget fully used).
+const UTILIZATION_BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 0.75, 0.9, 1.0, 1.5, 2.0];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ActionLabels {
+    kind: String,
+    action: String,
+}
+
+impl EncodeLabelSet for ActionLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("kind", self.kind.as_str()).encode(encoder.encode_label())?;
+        ("action", self.action.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+fn create_reward_histogram() -> Histogram {
+    Histogram::new(REWARD_BUCKETS)
+}
+
+fn create_score_histogram() -> Histogram {
+    Histogram::new(SCORE_BUCKETS)
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DecisionLabels {
+    kind: String,
+    action: String,
+    mode: &'static str,
+}
+
+impl EncodeLabelSet for DecisionLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("kind", self.kind.as_str()).encode(encoder.encode_label())?;
+        ("action", self.action.as_str()).encode(encoder.encode_label())?;
+        ("mode", self.mode).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct KindLabels {
+    kind: String,
+}
+
+impl EncodeLabelSet for KindLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("kind", self.kind.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BudgetLabels {
+    intent: String,
+    resource: &'static str,
+}
+
+impl EncodeLabelSet for BudgetLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("intent", self.intent.as_str()).encode(encoder.encode_label())?;
+        ("resource", self.resource).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BudgetOutcomeLabels {
+    intent: String,
+    /// `within`, `downgraded` or `rejected`.
+    outcome: &'static str,
+}
+
+impl EncodeLabelSet for BudgetOutcomeLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("intent", self.intent.as_str()).encode(encoder.encode_label())?;
+        ("outcome", self.outcome).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+fn create_utilization_histogram() -> Histogram {
+    Histogram::new(UTILIZATION_BUCKETS)
+}
+
+/// Learning policy and its decision log, shared by all handlers.
+#[derive(Debug)]
+pub struct DecisionPolicy {
+    engine: Mutex<PolicyEngine>,
+    log: Arc<DecisionLog>,
+    decisions: Family<DecisionLabels, Counter>,
+    scores: Family<ActionLabels, Histogram>,
+    regret: Family<KindLabels, Counter<f64, AtomicU64>>,
+    rewards: Family<ActionLabels, Histogram>,
+    duplicates: Family<ActionLabels, Counter>,
+    budget_utilization: Family<BudgetLabels, Histogram>,
+    budget_outcomes: Family<BudgetOutcomeLabels, Counter>,
+    state_path: PathBuf,
+    /// Set by [`start_persistence`]; until then the state file is not touched.
+    persistent: AtomicBool,
+}
+
+impl DecisionPolicy {
+    pub fn new(config: PolicyConfig, log: DecisionLog, state_path: PathBuf) -> Self {
+        Self {
+            engine: Mutex::new(PolicyEngine::new(config)),
+            log: Arc::new(log),
+            decisions: Family::default(),
+            scores: Family::new_with_constructor(create_score_histogram),
+            regret: Family::default(),
+            rewards: Family::new_with_constructor(create_reward_histogram),
+            duplicates: Family::default(),
+            budget_utilization: Family::new_with_constructor(create_utilization_histogram),
+            budget_outcomes: Family::default(),
+            state_path,
+            persistent: AtomicBool::new(false),
+        }
+    }
+
+    pub fn load_from_env() -> Self {
+        let path = std::env::var("HAUSKI_DECISION_POLICY_PATH")
+            .ok()
+            .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+        let state_path = crate::env_path("HAUSKI_POLICY_STATE_PATH", "policy_state.json");
+        Self::new(load_config(Path::new(&path)), open_log(), state_path)
+    }
+
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "policy_decisions",
+            "Total number of policy decisions by kind, applied action and mode",
+            self.decisions.clone(),
+        );
+        registry.register(
+            "policy_decision_score",
+            "Distribution of bandit scores of applied policy actions by kind and action",
+            self.scores.clone(),
+        );
+        registry.register(
+            "policy_estimated_regret",
+            "Sum of estimated reward lost against the best-estimated action by kind",
+            self.regret.clone(),
+        );
+        registry.register(
+            "policy_feedback_reward",
+            "Distribution of accepted policy feedback rewards by kind and action",
+            self.rewards.clone(),
+        );
+        registry.register(
+            "policy_feedback_duplicates",
+            "Total number of discarded repeated policy feedback by kind and action",
+            self.duplicates.clone(),
+        );
+        registry.register(
+            "policy_budget_utilization",
+            "Share of the intent budget used by applied policy actions by intent and resource",
+            self.budget_utilization.clone(),
+        );
+        registry.register(
+            "policy_budget_decisions",
+            "Total number of budgeted policy decisions by intent and outcome",
+            self.budget_outcomes.clone(),
+        );
+    }
+
+    fn observe_decision(&self, decision: &PolicyDecision) {
+        self.decisions
+            .get_or_create(&DecisionLabels {
+                kind: decision.kind.clone(),
+                action: decision.action.clone(),
+                mode: decision.mode.as_str(),
+            })
+            .inc();
+        let applied = decision.scores.get(&decision.action);
+        if let Some(applied) = applied {
+            self.scores
+                .get_or_create(&ActionLabels {
+                    kind: decision.kind.clone(),
+                    action: decision.action.clone(),
+                })
+                .observe(applied.score);
+            // Model estimate only: what the best-estimated action would
+            // have earned over the applied one.
+            let best = decision
+                .scores
+                .values()
+                .map(|score| score.estimate)
+                .fold(applied.estimate, f64::max);
+            self.regret
+                .get_or_create(&KindLabels {
+                    kind: decision.kind.clone(),
+                })
+                .inc_by(best - applied.estimate);
+        }
+        self.observe_budget(decision);
+    }
+
+    fn observe_budget(&self, decision: &PolicyDecision) {
+        let Some(budget) = &decision.budget else {
+            return;
+        };
+        for (resource, share) in &budget.utilization {
+            self.budget_utilization
+                .get_or_create(&BudgetLabels {
+                    intent: budget.intent.clone(),
+                    resource,
+                })
+                .observe(*share);
+        }
+        let outcome = if budget.downgraded_from.is_some() {
+            "downgraded"
+        } else {
+            "within"
+        };
+        self.budget_outcomes
+            .get_or_create(&BudgetOutcomeLabels {
+                intent: budget.intent.clone(),
+                outcome,
+            })
+            .inc();
+    }
+
+    pub fn log(&self) -> Arc<DecisionLog> {
+        self.log.clone()
+    }
+
+    pub fn engine(&self) -> MutexGuard<'_, PolicyEngine> {
+        self.engine
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Loads the saved state into the engine. An incompatible or unreadable
+    /// file is moved aside, so the next save does not overwrite it.
+    fn restore_state(&self) {
+        let path = &self.state_path;
+        let restored = EngineState::load(path).and_then(|state| match state {
+            Some(state) => Ok(Some(self.engine().restore(state)?)),
+            None => Ok(None),
+        });
+        match restored {
+            Ok(Some(kinds)) => {
+                tracing::info!(path = %path.display(), ?kinds, "policy state restored")
+            }
+            Ok(None) => {}
+            Err(err) => {
+                let backup = path.with_extension("json.bak");
+                tracing::warn!(path = %path.display(), backup = %backup.display(), error = %err, "policy state not restored – starting fresh");
+                if let Err(err) = fs::rename(path, &backup) {
+                    tracing::warn!(error = %err, "policy state could not be moved aside");
+                }
+            }
+        }
+    }
+
+    /// Writes the learned state if persistence was started.
+    pub fn save_state(&self) -> anyhow::Result<()> {
+        if !self.persistent.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let state = self.engine().state();
+        state.save(&self.state_path)
+    }
+}
+
+/// Restores the saved policy state and saves it every
+/// `HAUSKI_POLICY_SAVE_SEC` seconds; call once at server start.
+pub(crate) fn start_persistence(policy: &Arc<DecisionPolicy>) {
+    policy.restore_state();
+    policy.persistent.store(true, Ordering::Release);
+
+    let interval = crate::env_u64("HAUSKI_POLICY_SAVE_SEC", DEFAULT_SAVE_SEC);
+    if interval > 0 {
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(save_periodically(
+                Arc::downgrade(policy),
+                Duration::from_secs(interval),
+            ));
+        }
+    }
+}
+
+/// Saves the state every `interval` until the policy is dropped.
+async fn save_periodically(policy: Weak<DecisionPolicy>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(policy) = policy.upgrade() else {
+            return;
+        };
+        let saved = tokio::task::spawn_blocking(move || policy.save_state())
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+        if let Err(err) = saved {
+            tracing::warn!(error = %err, "policy state could not be saved");
+        }
+    }
+}
+
+/// Records reloads of the index policies, forget operations and quarantine
+/// releases in the audit log.
+pub(crate) fn attach(state: &AppState) {
+    let log = state.policy().log();
+    state.index().on_event(Arc::new(move |event: &IndexEvent| {
+        let (name, detail) = match event {
+            IndexEvent::PolicyReloaded(reload) => ("index_policy_reload", json!(reload)),
+            IndexEvent::Forgotten {
+                namespace,
+                doc_ids,
+                reason,
+            } => (
+                "index_forget",
+                json!({"namespace": namespace, "doc_ids": doc_ids, "reason": reason}),
+            ),
+            IndexEvent::QuarantineReleased {
+                doc_id,
+                namespace,
+                flags,
+            } => (
+                "index_quarantine_release",
+                json!({"doc_id": doc_id, "namespace": namespace, "flags": flags}),
+            ),
+            _ => return,
+        };
+        let event = AuditEvent {
+            id: ulid::Ulid::new().to_string(),
+            ts: Utc::now(),
+            event: name.to_string(),
+            kind: None,
+            detail,
+        };
+        if let Err(err) = log.record_event(&event) {
+            tracing::warn!(error = %err, event = name, "index event could not be audited");
+        }
+    }));
+}
+
+fn open_log() -> DecisionLog {
+    let path = crate::env_path("HAUSKI_POLICY_DB_PATH", "policy_decisions.db");
+    match DecisionLog::open(&path) {
+        Ok(log) => log,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "policy decision log unavailable – keeping decisions in memory only");
+            DecisionLog::in_memory().expect("in-memory sqlite database")
+        }
+    }
+}
+
+fn load_config(path: &Path) -> PolicyConfig {
+    if !path.exists() {
+        return PolicyConfig::default();
+    }
+    let parsed = match fs::read_to_string(path) {
+        Ok(text) => match crate::config::from_yaml_str::<PolicyConfig>(&text, path) {
+            Ok(config) => config,
+            Err(err) => {
+                tracing::warn!("decision policy parse failed: {err} – using defaults");
+                return PolicyConfig::default();
+            }
+        },
+        Err(err) => {
+            tracing::warn!("decision policy read failed: {err} – using defaults");
+            return PolicyConfig::default();
+        }
+    };
+    if let Err(err) = parsed.validate() {
+        tracing::warn!("{err} – using defaults");
+        return PolicyConfig::default();
+    }
+    parsed
+}
+
+// ---------------------- HTTP ----------------------
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(
+    title = "PolicyDecideRequest",
+    example = json!({"kind": "reminder", "features": {"intent": "remind", "load": 0.3}})
+)]
+pub struct PolicyDecideRequest {
+    pub kind: String,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub features: Value,
+    /// Overrides the configured mode for this decision.
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "live")]
+    pub mode: Option<Mode>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(
+    title = "PolicyDecideResponse",
+    example = json!({
+        "id": "01J9Z5W3Q8K6TQ2V3M4N5P6R7S",
+        "ts": "2026-10-17T08:00:00Z",
+        "kind": "reminder",
+        "mode": "shadow",
+        "action": "notify",
+        "proposed": "snooze",
+        "baseline": "notify",
+        "score": 1.12,
+        "strategy": "lin_ucb",
+        "why": "shadow mode, applying baseline 'notify'; lin_ucb chose 'snooze' …",
+        "parameters_hash": "3f9a0c1d2e4b5a69",
+        "scores": {"notify": {"score": 1.05, "estimate": 0.1, "exploration": 0.95}}
+    })
+)]
+pub struct PolicyDecideResponse {
+    pub id: String,
+    pub ts: DateTime<Utc>,
+    pub kind: String,
+    /// `shadow` or `live`.
+    pub mode: String,
+    /// Action the caller should apply.
+    pub action: String,
+    /// Action proposed by the learning policy.
+    pub proposed: String,
+    pub baseline: String,
+    pub score: f64,
+    pub strategy: String,
+    pub why: String,
+    /// Hash of the model parameters the decision was made with.
+    pub parameters_hash: String,
+    /// Score, estimate and exploration per action.
+    #[schema(value_type = Object)]
+    pub scores: Value,
+    /// Budget of the decision's intent, if one is configured: utilization
+    /// of the applied action and the action it replaced, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub budget: Option<Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "PolicyErrorResponse", example = json!({"error":"unknown decision kind 'dance'"}))]
+pub struct PolicyErrorResponse {
+    pub error: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/policy/decide",
+    request_body = PolicyDecideRequest,
+    responses(
+        (status = 200, description = "Decision with rationale", body = PolicyDecideResponse),
+        (status = 400, description = "Invalid decision request", body = PolicyErrorResponse),
+        (status = 404, description = "Unknown decision kind", body = PolicyErrorResponse),
+        (status = 422, description = "No action fits the budget of the intent", body = PolicyErrorResponse),
+        (status = 500, description = "Decision could not be recorded", body = PolicyErrorResponse)
+    ),
+    tag = "core"
+)]
+pub async fn policy_decide_handler(
+    State(state): State<AppState>,
+    Json(req): Json<PolicyDecideRequest>,
+) -> Response {
+    let started = Instant::now();
+    let error = |status: StatusCode, error: String| {
+        state.record_http_observation(Method::POST, "/policy/decide", status, started);
+        (status, Json(PolicyErrorResponse { error })).into_response()
+    };
+
+    let kind = req.kind.trim();
+    if kind.is_empty() || kind.chars().count() > MAX_KIND_CHARS {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("kind must have 1 to {MAX_KIND_CHARS} characters"),
+        );
+    }
+    let features = match req.features {
+        Value::Null => json!({}),
+        features @ Value::Object(_) => features,
+        _ => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "features must be an object".to_string(),
+            )
+        }
+    };
+    let ctx = DecisionContext {
+        kind: kind.to_string(),
+        features,
+    };
+
+    let policy = state.policy();
+    let decision = policy.engine().decide(&ctx, req.mode);
+    let decision = match decision {
+        Ok(decision) => decision,
+        Err(err @ PolicyError::UnknownKind(_)) => {
+            return error(StatusCode::NOT_FOUND, err.to_string())
+        }
+        Err(PolicyError::BudgetExceeded {
+            kind,
+            intent,
+            resources,
+        }) => {
+            let err = PolicyError::BudgetExceeded {
+                kind,
+                intent: intent.clone(),
+                resources,
+            };
+            policy
+                .budget_outcomes
+                .get_or_create(&BudgetOutcomeLabels {
+                    intent,
+                    outcome: "rejected",
+                })
+                .inc();
+            return error(StatusCode::UNPROCESSABLE_ENTITY, err.to_string());
+        }
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+    policy.observe_decision(&decision);
+    let budget = decision
+        .budget
+        .as_ref()
+        .and_then(|budget| serde_json::to_value(budget).ok());
+
+    let record = DecisionRecord {
+        id: ulid::Ulid::new().to_string(),
+        ts: Utc::now(),
+        kind: decision.kind,
+        mode: decision.mode,
+        action: decision.action,
+        proposed: decision.proposed,
+        baseline: decision.baseline,
+        score: decision.score,
+        strategy: decision.strategy.to_string(),
+        why: decision.why,
+        parameters_hash: decision.parameters_hash,
+        context: ctx.features,
+        scores: serde_json::to_value(&decision.scores).unwrap_or_else(|_| json!({})),
+    };
+    let log = state.policy().log();
+    let stored = {
+        let record = record.clone();
+        tokio::task::spawn_blocking(move || log.record(&record)).await
+    };
+    if let Err(err) = stored
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result)
+    {
+        tracing::warn!(id = %record.id, error = %err, "policy decision could not be recorded");
+        return error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "decision could not be recorded".to_string(),
+        );
+    }
+    chronik::publish(&state, chronik::SOURCE_POLICY, "policy.decision", &record);
+
+    let response = PolicyDecideResponse {
+        id: record.id,
+        ts: record.ts,
+        kind: record.kind,
+        mode: record.mode.as_str().to_string(),
+        action: record.action,
+        proposed: record.proposed,
+        baseline: record.baseline,
+        score: record.score,
+        strategy: record.strategy,
+        why: record.why,
+        parameters_hash: record.parameters_hash,
+        scores: record.scores,
+        budget,
+    };
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/policy/decide", status, started);
+    (status, Json(response)).into_response()
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PolicyDecisionsQuery {
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// `shadow` or `live`.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub mode: Option<Mode>,
+    /// RFC 3339 timestamp; only decisions at or after it.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[param(default = 50, maximum = 500)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "PolicyDecisionsResponse")]
+pub struct PolicyDecisionsResponse {
+    /// Newest first, each with context, mode, scores and parameter hash.
+    #[schema(value_type = Vec<Object>)]
+    pub decisions: Vec<DecisionRecord>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/policy/decisions",
+    tag = "core",
+    params(PolicyDecisionsQuery),
+    responses(
+        (status = 200, description = "Recorded policy decisions", body = PolicyDecisionsResponse),
+        (status = 500, description = "Decision log unreadable", body = PolicyErrorResponse)
+    )
+)]
+pub async fn policy_decisions_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PolicyDecisionsQuery>,
+) -> Response {
+    let started = Instant::now();
+    let query = DecisionQuery {
+        kind: query.kind.filter(|kind| !kind.trim().is_empty()),
+        mode: query.mode,
+        since: query.since,
+        limit: query.limit.unwrap_or(DEFAULT_DECISIONS_LIMIT),
+    };
+    let log = state.policy().log();
+    let result = tokio::task::spawn_blocking(move || log.query(&query))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    let (status, response) = match result {
+        Ok(decisions) => (
+            StatusCode::OK,
+            Json(PolicyDecisionsResponse { decisions }).into_response(),
+        ),
+        Err(err) => {
+            tracing::warn!(error = %err, "policy decision log query failed");
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            let error = "decision log unreadable".to_string();
+            (
+                status,
+                (status, Json(PolicyErrorResponse { error })).into_response(),
+            )
+        }
+    };
+    state.record_http_observation(Method::GET, "/policy/decisions", status, started);
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/policy/decisions/{id}",
+    tag = "core",
+    params(("id" = String, Path, description = "Decision id from `/policy/decide`")),
+    responses(
+        (status = 200, description = "Recorded decision", body = Object),
+        (status = 404, description = "Unknown decision id", body = PolicyErrorResponse),
+        (status = 500, description = "Decision log unreadable", body = PolicyErrorResponse)
+    )
+)]
+pub async fn policy_decision_handler(
+    State(state): State<AppState>,
+    UrlPath(id): UrlPath<String>,
+) -> Response {
+    let started = Instant::now();
+    let log = state.policy().log();
+    let result = {
+        let id = id.clone();
+        tokio::task::spawn_blocking(move || log.get(&id))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    let (status, response) = match result {
+        Ok(Some(record)) => (StatusCode::OK, Json(record).into_response()),
+        Ok(None) => {
+            let status = StatusCode::NOT_FOUND;
+            let error = format!("unknown decision '{id}'");
+            (
+                status,
+                (status, Json(PolicyErrorResponse { error })).into_response(),
+            )
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "policy decision log lookup failed");
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            let error = "decision log unreadable".to_string();
+            (
+                status,
+                (status, Json(PolicyErrorResponse { error })).into_response(),
+            )
+        }
+    };
+    state.record_http_observation(Method::GET, "/policy/decisions/{id}", status, started);
+    response
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(
+    title = "PolicyFeedbackRequest",
+    example = json!({"decision_id": "01J9Z5W3Q8K6TQ2V3M4N5P6R7S", "reward": 1.0})
+)]
+pub struct PolicyFeedbackRequest {
+    pub decision_id: String,
+    /// Observed reward for the applied action, roughly in `[-1, 1]`.
+    pub reward: f64,
+    /// Features to learn from; defaults to the context of the decision.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub context: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "PolicyFeedbackResponse")]
+pub struct PolicyFeedbackResponse {
+    pub decision_id: String,
+    pub kind: String,
+    /// Action the reward was credited to.
+    pub action: String,
+    pub reward: f64,
+    /// True if the decision already had feedback; the bandit was not updated.
+    pub duplicate: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/policy/feedback",
+    request_body = PolicyFeedbackRequest,
+    responses(
+        (status = 200, description = "Feedback applied or discarded as duplicate", body = PolicyFeedbackResponse),
+        (status = 400, description = "Invalid feedback", body = PolicyErrorResponse),
+        (status = 404, description = "Unknown decision or decision kind", body = PolicyErrorResponse),
+        (status = 500, description = "Decision log unavailable", body = PolicyErrorResponse)
+    ),
+    tag = "core"
+)]
+pub async fn policy_feedback_handler(
+    State(state): State<AppState>,
+    Json(req): Json<PolicyFeedbackRequest>,
+) -> Response {
+    let started = Instant::now();
+    let error = |status: StatusCode, error: String| {
+        state.record_http_observation(Method::POST, "/policy/feedback", status, started);
+        (status, Json(PolicyErrorResponse { error })).into_response()
+    };
+
+    if !req.reward.is_finite() {
+        return error(StatusCode::BAD_REQUEST, "reward must be finite".to_string());
+    }
+    if req
+        .context
+        .as_ref()
+        .is_some_and(|context| !context.is_object())
+    {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "context must be an object".to_string(),
+        );
+    }
+
+    let policy = state.policy();
+    let log = policy.log();
+    let decision_id = req.decision_id.trim().to_string();
+    let decision = {
+        let log = log.clone();
+        let decision_id = decision_id.clone();
+        tokio::task::spawn_blocking(move || log.get(&decision_id))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    let decision = match decision {
+        Ok(Some(decision)) => decision,
+        Ok(None) => {
+            return error(
+                StatusCode::NOT_FOUND,
+                format!("unknown decision '{decision_id}'"),
+            )
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "policy decision log lookup failed");
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "decision log unavailable".to_string(),
+            );
+        }
+    };
+    if policy.engine().bandit(&decision.kind).is_none() {
+        return error(
+            StatusCode::NOT_FOUND,
+            PolicyError::UnknownKind(decision.kind).to_string(),
+        );
+    }
+
+    let feedback = FeedbackRecord {
+        decision_id: decision.id.clone(),
+        ts: Utc::now(),
+        action: decision.action.clone(),
+        reward: req.reward,
+        context: req.context.unwrap_or_else(|| decision.context.clone()),
+    };
+    let stored = {
+        let feedback = feedback.clone();
+        tokio::task::spawn_blocking(move || log.record_feedback(&feedback))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    let is_new = match stored {
+        Ok(is_new) => is_new,
+        Err(err) => {
+            tracing::warn!(error = %err, "policy feedback could not be recorded");
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "feedback could not be recorded".to_string(),
+            );
+        }
+    };
+
+    let labels = ActionLabels {
+        kind: decision.kind.clone(),
+        action: feedback.action.clone(),
+    };
+    if is_new {
+        let ctx = DecisionContext {
+            kind: decision.kind.clone(),
+            features: feedback.context.clone(),
+        };
+        if let Err(err) = policy
+            .engine()
+            .update(&ctx, &feedback.action, feedback.reward)
+        {
+            // The kind was removed between the check and the update.
+            tracing::warn!(error = %err, "policy feedback not applied");
+        }
+        policy
+            .rewards
+            .get_or_create(&labels)
+            .observe(feedback.reward);
+        chronik::publish(&state, chronik::SOURCE_POLICY, "policy.feedback", &feedback);
+    } else {
+        policy.duplicates.get_or_create(&labels).inc();
+    }
+
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/policy/feedback", status, started);
+    (
+        status,
+        Json(PolicyFeedbackResponse {
+            decision_id: feedback.decision_id,
+            kind: decision.kind,
+            action: feedback.action,
+            reward: feedback.reward,
+            duplicate: !is_new,
+        }),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PolicyReportQuery {
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// RFC 3339 timestamp; only decisions at or after it.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Most recent shadow decisions to evaluate.
+    #[serde(default)]
+    #[param(default = 1000, maximum = 10000)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(
+    title = "PolicyReportResponse",
+    example = json!({
+        "reports": [{
+            "kind": "reminder",
+            "decisions": 240,
+            "divergent": 61,
+            "divergence_rate": 0.254,
+            "with_feedback": 180,
+            "baseline_mean_reward": 0.41,
+            "policy_mean_reward": 0.47,
+            "reward_difference": 0.06,
+            "confidence_interval": [0.02, 0.1],
+            "recommendation": "switch_to_live"
+        }]
+    })
+)]
+pub struct PolicyReportResponse {
+    /// One report per kind with shadow decisions.
+    #[schema(value_type = Vec<Object>)]
+    pub reports: Vec<ShadowReport>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/policy/report",
+    tag = "core",
+    params(PolicyReportQuery),
+    responses(
+        (status = 200, description = "Shadow vs. baseline comparison per decision kind", body = PolicyReportResponse),
+        (status = 500, description = "Decision log unreadable", body = PolicyErrorResponse)
+    )
+)]
+pub async fn policy_report_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PolicyReportQuery>,
+) -> Response {
+    let started = Instant::now();
+    let query = DecisionQuery {
+        kind: query.kind.filter(|kind| !kind.trim().is_empty()),
+        mode: Some(Mode::Shadow),
+        since: query.since,
+        limit: query.limit.unwrap_or(DEFAULT_REPORT_LIMIT),
+    };
+    let log = state.policy().log();
+    let result = tokio::task::spawn_blocking(move || log.history(&query))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    let (status, response) = match result {
+        Ok(history) => (
+            StatusCode::OK,
+            Json(PolicyReportResponse {
+                reports: shadow_reports(&history),
+            })
+            .into_response(),
+        ),
+        Err(err) => {
+            tracing::warn!(error = %err, "policy decision log query failed");
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            let error = "decision log unreadable".to_string();
+            (
+                status,
+                (status, Json(PolicyErrorResponse { error })).into_response(),
+            )
+        }
+    };
+    state.record_http_observation(Method::GET, "/policy/report", status, started);
+    response
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(
+    title = "PolicyBacktestRequest",
+    example = json!({
+        "config": {
+            "mode": "live",
+            "kinds": {"reminder": {"actions": ["notify", "snooze"], "strategy": {"kind": "lin_ucb", "alpha": 0.5}}}
+        },
+        "kind": "reminder"
+    })
+)]
+pub struct PolicyBacktestRequest {
+    /// Candidate configuration in the shape of `decisions.yaml`; defaults to
+    /// the active one.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub config: Option<PolicyConfig>,
+    /// Overrides the modes of the candidate.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub mode: Option<Mode>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// RFC 3339 timestamp; only decisions at or after it.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Most recent decisions to replay.
+    #[serde(default)]
+    #[schema(default = 10000, maximum = 10000)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(
+    title = "PolicyBacktestResponse",
+    example = json!({
+        "reports": [{
+            "kind": "reminder",
+            "replayed": 180,
+            "matched": 96,
+            "match_rate": 0.533,
+            "rejected": 0,
+            "logged_mean_reward": 0.41,
+            "expected_reward": 0.46,
+            "reward_difference": 0.05,
+            "confidence_interval": [0.38, 0.54],
+            "reliable": true
+        }]
+    })
+)]
+pub struct PolicyBacktestResponse {
+    /// One report per kind of the candidate with recorded feedback.
+    #[schema(value_type = Vec<Object>)]
+    pub reports: Vec<BacktestReport>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/policy/backtest",
+    tag = "core",
+    request_body = PolicyBacktestRequest,
+    responses(
+        (status = 200, description = "Expected reward of the candidate configuration per decision kind", body = PolicyBacktestResponse),
+        (status = 400, description = "Invalid candidate configuration", body = PolicyErrorResponse),
+        (status = 500, description = "Decision log unreadable", body = PolicyErrorResponse)
+    )
+)]
+pub async fn policy_backtest_handler(
+    State(state): State<AppState>,
+    Json(req): Json<PolicyBacktestRequest>,
+) -> Response {
+    let started = Instant::now();
+    let error = |status: StatusCode, error: String| {
+        state.record_http_observation(Method::POST, "/policy/backtest", status, started);
+        (status, Json(PolicyErrorResponse { error })).into_response()
+    };
+
+    let config = match req.config {
+        Some(config) => config,
+        None => state.policy().engine().config().clone(),
+    };
+    if let Err(err) = config.validate() {
+        return error(StatusCode::BAD_REQUEST, err.to_string());
+    }
+    let query = DecisionQuery {
+        kind: req.kind.filter(|kind| !kind.trim().is_empty()),
+        mode: None,
+        since: req.since,
+        limit: req.limit.unwrap_or(DEFAULT_BACKTEST_LIMIT),
+    };
+    let log = state.policy().log();
+    let mode = req.mode;
+    let result = tokio::task::spawn_blocking(move || {
+        log.history(&query)
+            .map(|history| backtest(&config, mode, &history))
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
+    let reports = match result {
+        Ok(reports) => reports,
+        Err(err) => {
+            tracing::warn!(error = %err, "policy decision log query failed");
+            return error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "decision log unreadable".to_string(),
+            );
+        }
+    };
+
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/policy/backtest", status, started);
+    (status, Json(PolicyBacktestResponse { reports })).into_response()
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[schema(
+    title = "PolicyResetRequest",
+    example = json!({"kind": "reminder", "reason": "reward definition changed"})
+)]
+pub struct PolicyResetRequest {
+    /// Kind to reset; all kinds if omitted.
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Stored in the audit entry.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "PolicyResetResponse")]
+pub struct PolicyResetResponse {
+    /// Kinds whose learned state was discarded.
+    pub reset: Vec<String>,
+    /// Id of the audit entry (`GET /policy/audit`).
+    pub audit_id: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/policy/reset",
+    request_body = PolicyResetRequest,
+    responses(
+        (status = 200, description = "Learned state discarded", body = PolicyResetResponse),
+        (status = 404, description = "Unknown decision kind", body = PolicyErrorResponse),
+        (status = 500, description = "Audit entry could not be recorded", body = PolicyErrorResponse)
+    ),
+    tag = "core"
+)]
+pub async fn policy_reset_handler(
+    State(state): State<AppState>,
+    Json(req): Json<PolicyResetRequest>,
+) -> Response {
+    let started = Instant::now();
+    let error = |status: StatusCode, error: String| {
+        state.record_http_observation(Method::POST, "/policy/reset", status, started);
+        (status, Json(PolicyErrorResponse { error })).into_response()
+    };
+
+    let kind = req
+        .kind
+        .as_deref()
+        .map(str::trim)
+        .filter(|kind| !kind.is_empty());
+    let policy = state.policy();
+    let (kinds, previous) = {
+        let mut engine = policy.engine();
+        let previous: BTreeMap<String, String> = engine
+            .config()
+            .kinds
+            .keys()
+            .filter(|known| kind.is_none_or(|kind| kind == known.as_str()))
+            .filter_map(|known| {
+                let hash = engine.bandit(known)?.parameters_hash();
+                Some((known.clone(), hash))
+            })
+            .collect();
+        match engine.reset(kind) {
+            Ok(kinds) => (kinds, previous),
+            Err(err) => return error(StatusCode::NOT_FOUND, err.to_string()),
+        }
+    };
+
+    let event = AuditEvent {
+        id: ulid::Ulid::new().to_string(),
+        ts: Utc::now(),
+        event: "reset".to_string(),
+        kind: kind.map(str::to_string),
+        detail: json!({
+            "kinds": kinds,
+            "reason": req.reason,
+            "previous_parameters_hash": previous,
+        }),
+    };
+    let log = policy.log();
+    let stored = {
+        let event = event.clone();
+        tokio::task::spawn_blocking(move || log.record_event(&event))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+    };
+    if let Err(err) = stored {
+        // The reset already happened; the missing entry must not go unnoticed.
+        tracing::error!(id = %event.id, error = %err, "policy reset could not be audited");
+        return error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "audit entry could not be recorded".to_string(),
+        );
+    }
+    chronik::publish(&state, chronik::SOURCE_POLICY, "policy.reset", &event);
+
+    let saved = tokio::task::spawn_blocking(move || policy.save_state())
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    if let Err(err) = saved {
+        tracing::warn!(error = %err, "policy state could not be saved after reset");
+    }
+
+    let status = StatusCode::OK;
+    state.record_http_observation(Method::POST, "/policy/reset", status, started);
+    (
+        status,
+        Json(PolicyResetResponse {
+            reset: kinds,
+            audit_id: event.id,
+        }),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PolicyAuditQuery {
+    #[serde(default)]
+    #[param(default = 50, maximum = 500)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(title = "PolicyAuditResponse")]
+pub struct PolicyAuditResponse {
+    /// Newest first.
+    #[schema(value_type = Vec<Object>)]
+    pub events: Vec<AuditEvent>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/policy/audit",
+    tag = "core",
+    params(PolicyAuditQuery),
+    responses(
+        (status = 200, description = "Administrative changes of the policy state", body = PolicyAuditResponse),
+        (status = 500, description = "Decision log unreadable", body = PolicyErrorResponse)
+    )
+)]
+pub async fn policy_audit_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PolicyAuditQuery>,
+) -> Response {
+    let started = Instant::now();
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    let log = state.policy().log();
+    let result = tokio::task::spawn_blocking(move || log.events(limit))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    let (status, response) = match result {
+        Ok(events) => (
+            StatusCode::OK,
+            Json(PolicyAuditResponse { events }).into_response(),
+        ),
+        Err(err) => {
+            tracing::warn!(error = %err, "policy audit query failed");
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            let error = "decision log unreadable".to_string();
+            (
+                status,
+                (status, Json(PolicyErrorResponse { error })).into_response(),
+            )
+        }
+    };
+    state.record_http_observation(Method::GET, "/policy/audit", status, started);
+    response
+}
@@ -0,0 +1,229 @@
+//! Fortschritts-Events für lang laufende Requests (SSE).
+//!
+//! Ein Lauf meldet pro Schritt `started`, beliebig viele `stdout`-Zeilen (und bei
+//! Jobs `progress` in Prozent) und `completed`; am Ende folgt genau ein `result`
+//! mit der regulären Antwort.
+//! Clients fordern den Stream mit `Accept: text/event-stream` an. Ohne Stream
+//! ist der [`ProgressSink`] deaktiviert und alle Meldungen sind No-ops.
+
+use std::{convert::Infallible, time::Instant};
+
+use axum::{
+    http::{header, HeaderMap},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
+
+/// One progress event; the SSE event name is the `type` tag.
+#[derive(Debug, Clone, Serialize, ToSchema, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Started {
+        step: String,
+    },
+    Stdout {
+        step: String,
+        line: String,
+    },
+    /// Completion percentage (0-100) of a long step, e.g. a background job.
+    Progress {
+        step: String,
+        percent: f32,
+    },
+    Completed {
+        step: String,
+        ok: bool,
+        duration_ms: u64,
+    },
+    /// Final response body of the run.
+    Result {
+        data: serde_json::Value,
+    },
+}
+
+impl ProgressEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Started { .. } => "started",
+            Self::Stdout { .. } => "stdout",
+            Self::Progress { .. } => "progress",
+            Self::Completed { .. } => "completed",
+            Self::Result { .. } => "result",
+        }
+    }
+
+    fn to_sse(&self) -> Event {
+        let event = Event::default().event(self.name());
+        match event.json_data(self) {
+            Ok(event) => event,
+            Err(err) => {
+                tracing::warn!(error = %err, "progress event serialization failed");
+                Event::default().event(self.name())
+            }
+        }
+    }
+}
+
+/// Sender side of a progress stream; cheap to clone, disabled by default.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressSink {
+    tx: Option<mpsc::UnboundedSender<ProgressEvent>>,
+}
+
+impl ProgressSink {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx: Some(tx) }, rx)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    /// Sends an event; a disconnected client is not an error for the run.
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Emits `started` and returns a handle for output and completion.
+    pub fn step(&self, name: &str) -> ProgressStep {
+        self.emit(ProgressEvent::Started {
+            step: name.to_string(),
+        });
+        ProgressStep {
+            sink: self.clone(),
+            name: name.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn result<T: Serialize>(&self, body: &T) {
+        if !self.is_enabled() {
+            return;
+        }
+        match serde_json::to_value(body) {
+            Ok(data) => self.emit(ProgressEvent::Result { data }),
+            Err(err) => tracing::warn!(error = %err, "progress result serialization failed"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProgressStep {
+    sink: ProgressSink,
+    name: String,
+    started: Instant,
+}
+
+impl ProgressStep {
+    /// Emits one `stdout` event per line of `text`.
+    pub fn stdout(&self, text: &str) {
+        for line in text.lines() {
+            self.sink.emit(ProgressEvent::Stdout {
+                step: self.name.clone(),
+                line: line.to_string(),
+            });
+        }
+    }
+
+    pub fn finish(self, ok: bool) {
+        self.sink.emit(ProgressEvent::Completed {
+            step: self.name,
+            ok,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+/// True if the client asked for a `text/event-stream` response.
+pub fn wants_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media| {
+            media
+                .split(';')
+                .next()
+                .is_some_and(|m| m.trim().eq_ignore_ascii_case("text/event-stream"))
+        })
+}
+
+/// Turns a progress channel into an SSE response; the stream ends once all
+/// senders are dropped.
+pub fn sse_response(
+    rx: mpsc::UnboundedReceiver<ProgressEvent>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        Some((Ok(event.to_sse()), rx))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn accept_header_selects_stream() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_event_stream(&headers));
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json, text/event-stream;q=0.9"),
+        );
+        assert!(wants_event_stream(&headers));
+    }
+
+    #[test]
+    fn step_reports_lines_and_completion() {
+        let (sink, mut rx) = ProgressSink::channel();
+        let step = sink.step("tool");
+        step.stdout("one\ntwo");
+        step.finish(true);
+        drop(sink);
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            ProgressEvent::Started {
+                step: "tool".into()
+            }
+        );
+        assert_eq!(
+            events[2],
+            ProgressEvent::Stdout {
+                step: "tool".into(),
+                line: "two".into()
+            }
+        );
+        assert!(matches!(
+            events[3],
+            ProgressEvent::Completed { ok: true, .. }
+        ));
+    }
+
+    #[test]
+    fn disabled_sink_is_silent() {
+        let sink = ProgressSink::disabled();
+        assert!(!sink.is_enabled());
+        sink.step("router").finish(true);
+        sink.result(&serde_json::json!({"answer": "x"}));
+    }
+}
@@ -0,0 +1,16 @@
+//! Fehlerantworten der Datei-Handler (ASR, Backup, Ingest): Status plus
+//! Klartext, dazu das gemeinsame Limit für JSON-Anfragen neben Uploads.
+
+use std::fmt;
+
+use axum::http::StatusCode;
+
+pub(crate) type Rejection = (StatusCode, String);
+
+/// Limit for JSON bodies of routes that also take uploads; they only carry a
+/// path and options.
+pub(crate) const MAX_JSON_BYTES: usize = 64 * 1024;
+
+pub(crate) fn internal(err: impl fmt::Display) -> Rejection {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
@@ -0,0 +1,175 @@
+//! Request-Logging und Log-Ausgabe des Servers.
+//!
+//! Jede Anfrage läuft in einem `request`-Span mit `request_id`, `method` und
+//! `route`; am Ende protokolliert er Status und Latenz. Die Request-ID kommt
+//! aus dem Header `x-request-id` (falls gültig) oder wird erzeugt und in der
+//! Antwort zurückgegeben. `hauski logs tail --request-id …` filtert darauf.
+//!
+//! [`init_tracing`] richtet die Ausgabe ein: Filter aus `RUST_LOG`, Text oder
+//! mit `HAUSKI_LOG_FORMAT=json` JSON-Zeilen, nach stdout oder angehängt an
+//! `HAUSKI_LOG_FILE`.
+
+use std::{
+    env,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use once_cell::sync::Lazy;
+use tracing::Instrument;
+use tracing_subscriber::{
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, Layer,
+};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request id that is taken over.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+static PROCESS_TAG: Lazy<u64> = Lazy::new(|| {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+});
+static NEXT_REQUEST: AtomicU64 = AtomicU64::new(1);
+
+/// Unique per process start and request, e.g. `66f1c2a0-1f`.
+fn new_request_id() -> String {
+    format!(
+        "{:x}-{:x}",
+        *PROCESS_TAG,
+        NEXT_REQUEST.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Client ids end up in logs, so only short, plain tokens are accepted.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+pub(crate) async fn request_log(req: Request<Body>, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map_or_else(new_request_id, str::to_string);
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        route = %req.uri().path(),
+    );
+
+    let started = Instant::now();
+    let mut response = next.run(req).instrument(span.clone()).await;
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    span.in_scope(|| {
+        if status >= 500 {
+            tracing::warn!(status, latency_ms, "request failed");
+        } else {
+            tracing::info!(status, latency_ms, "request finished");
+        }
+    });
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Installs the global subscriber; a no-op if one is already set.
+pub fn init_tracing() {
+    let json =
+        env::var("HAUSKI_LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+    let file = env::var("HAUSKI_LOG_FILE")
+        .ok()
+        .filter(|path| !path.trim().is_empty());
+
+    let (writer, ansi) = match &file {
+        Some(path) => {
+            let path = Path::new(path);
+            let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            let appender = tracing_appender::rolling::never(dir.unwrap_or(Path::new(".")), name);
+            (BoxMakeWriter::new(appender), false)
+        }
+        None => (BoxMakeWriter::new(std::io::stdout), true),
+    };
+    let output = if json {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(writer)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(ansi)
+            .with_writer(writer)
+            .boxed()
+    };
+    tracing_subscriber::registry()
+        .with(output)
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn only_plain_request_ids_are_accepted() {
+        assert!(is_valid_request_id("ci-run_42.3"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("a b"));
+        assert!(!is_valid_request_id("x\ninjected"));
+        assert!(!is_valid_request_id(&"a".repeat(65)));
+        assert_ne!(new_request_id(), new_request_id());
+    }
+
+    #[tokio::test]
+    async fn request_id_is_echoed_or_generated() {
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(from_fn(request_log));
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/ping")
+                    .header(REQUEST_ID_HEADER, "abc-123")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.headers()[REQUEST_ID_HEADER], "abc-123");
+
+        let res = app
+            .oneshot(
+                Request::get("/ping")
+                    .header(REQUEST_ID_HEADER, "not valid")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let generated = res.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert_ne!(generated, "not valid");
+        assert!(is_valid_request_id(generated));
+    }
+}
@@ -0,0 +1,416 @@
+//! Wiederkehrende Aufgaben über `hauski-scheduler`: Zeitpläne aus
+//! `HAUSKI_SCHEDULES` (Default `./configs/schedules.yaml`), Status unter
+//! `GET /scheduler/schedules`.
+//!
+//! Unterstützte Tasks:
+//!   retention_sweep – startet einen `retention_sweep`-Job (`namespace`, `dry_run`)
+//!   vault_scan      – liest Notizen unter `path` (Endungen `extensions`) und
+//!                     indexiert sie per `ingest`-Job in `namespace` (Default `vault`)
+//!   backup          – vollständiges Backup (siehe `backup`) nach `dir`, behält die
+//!                     `keep` neuesten (Defaults `HAUSKI_BACKUP_DIR`/`HAUSKI_BACKUP_KEEP`)
+//!
+//! Ungültige Einträge werden beim Laden verworfen (mit Warnung); fehlt die
+//! Datei, bleibt der Scheduler leer.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use axum::{
+    extract::State,
+    http::{Method, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use hauski_scheduler::{
+    ScheduleDef, ScheduleStatus, Scheduler, SchedulesFile, TaskFuture, TaskRunner,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::{
+    jobs::{self, JobCreateRequest, JobKind, JobStatus},
+    AppState,
+};
+
+const DEFAULT_SCHEDULES_PATH: &str = "./configs/schedules.yaml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetentionSweepParams {
+    #[serde(default)]
+    namespace: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VaultScanParams {
+    path: PathBuf,
+    #[serde(default = "default_vault_namespace")]
+    namespace: String,
+    #[serde(default = "default_vault_extensions")]
+    extensions: Vec<String>,
+    /// Larger files are skipped.
+    #[serde(default = "default_max_file_bytes")]
+    max_file_bytes: u64,
+}
+
+fn default_vault_namespace() -> String {
+    "vault".to_string()
+}
+
+fn default_vault_extensions() -> Vec<String> {
+    vec!["md".to_string()]
+}
+
+fn default_max_file_bytes() -> u64 {
+    1024 * 1024
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackupParams {
+    #[serde(default)]
+    dir: Option<PathBuf>,
+    #[serde(default)]
+    keep: Option<usize>,
+}
+
+#[derive(Debug)]
+enum ScheduledTask {
+    RetentionSweep(RetentionSweepParams),
+    VaultScan(VaultScanParams),
+    Backup(BackupParams),
+}
+
+impl ScheduledTask {
+    fn parse(def: &ScheduleDef) -> Result<Self, String> {
+        match def.task.as_str() {
+            "retention_sweep" => params(def).map(Self::RetentionSweep),
+            "vault_scan" => params(def).map(Self::VaultScan),
+            "backup" => params(def).map(Self::Backup),
+            other => Err(format!("unknown task '{other}'")),
+        }
+    }
+}
+
+fn params<T: DeserializeOwned>(def: &ScheduleDef) -> Result<T, String> {
+    let raw = if def.params.is_null() {
+        json!({})
+    } else {
+        def.params.clone()
+    };
+    serde_json::from_value(raw).map_err(|err| format!("invalid params for '{}': {err}", def.task))
+}
+
+pub fn load_from_env() -> Scheduler {
+    let path = std::env::var("HAUSKI_SCHEDULES")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SCHEDULES_PATH.to_string());
+    load_scheduler(Path::new(&path))
+}
+
+/// Like `hauski_scheduler::load_schedules`, with `${VAR}` interpolation.
+fn read_schedules(path: &Path) -> Result<SchedulesFile, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read schedules {}: {e}", path.display()))?;
+    crate::config::from_yaml_str(&content, path)
+        .map_err(|e| format!("failed to parse schedules {}: {e}", path.display()))
+}
+
+fn load_scheduler(path: &Path) -> Scheduler {
+    if !path.exists() {
+        return Scheduler::empty();
+    }
+    let file = match read_schedules(path) {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::warn!("{err} – scheduler disabled");
+            return Scheduler::empty();
+        }
+    };
+    let schedules = file
+        .schedules
+        .into_iter()
+        .filter(|def| match ScheduledTask::parse(def) {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::warn!(schedule = %def.id, "{err} – schedule ignored");
+                false
+            }
+        })
+        .collect();
+    match Scheduler::new(SchedulesFile { schedules }) {
+        Ok(scheduler) => scheduler,
+        Err(err) => {
+            tracing::warn!("{err} – scheduler disabled");
+            Scheduler::empty()
+        }
+    }
+}
+
+/// Runs schedule tasks against the live application state.
+pub(crate) struct CoreTaskRunner {
+    state: AppState,
+}
+
+impl CoreTaskRunner {
+    pub(crate) fn new(state: AppState) -> Arc<Self> {
+        Arc::new(Self { state })
+    }
+}
+
+impl TaskRunner for CoreTaskRunner {
+    fn run(&self, schedule: &ScheduleDef) -> TaskFuture {
+        let state = self.state.clone();
+        let task = ScheduledTask::parse(schedule);
+        Box::pin(async move {
+            match task? {
+                ScheduledTask::RetentionSweep(params) => {
+                    run_job_to_end(
+                        &state,
+                        JobCreateRequest {
+                            kind: JobKind::RetentionSweep,
+                            documents: Vec::new(),
+                            namespace: params.namespace,
+                            dry_run: params.dry_run,
+                            webhook_url: None,
+                        },
+                    )
+                    .await
+                }
+                ScheduledTask::VaultScan(params) => vault_scan(&state, params).await,
+                ScheduledTask::Backup(params) => backup(&state, params).await,
+            }
+        })
+    }
+
+    fn paused(&self) -> bool {
+        self.state.limits().power.pause_schedules && self.state.system_monitor().low_power()
+    }
+}
+
+/// Starts a background job and waits for it, so overlap protection covers
+/// the whole job.
+async fn run_job_to_end(state: &AppState, request: JobCreateRequest) -> Result<Value, String> {
+    let record = jobs::submit(state, request).await?;
+    let finished = state
+        .jobs()
+        .wait(&record.id)
+        .await
+        .ok_or_else(|| format!("job {} disappeared", record.id))?;
+    match finished.status {
+        JobStatus::Succeeded => Ok(json!({
+            "job_id": finished.id,
+            "result": finished.result,
+        })),
+        status => Err(format!(
+            "job {} {}: {}",
+            finished.id,
+            status.as_str(),
+            finished.error.unwrap_or_default()
+        )),
+    }
+}
+
+async fn vault_scan(state: &AppState, params: VaultScanParams) -> Result<Value, String> {
+    let (documents, skipped) = tokio::task::spawn_blocking(move || collect_notes(&params))
+        .await
+        .map_err(|err| format!("vault scan failed: {err}"))??;
+    if documents.is_empty() {
+        return Ok(json!({ "documents": 0, "skipped": skipped }));
+    }
+    let count = documents.len();
+    let mut summary = run_job_to_end(
+        state,
+        JobCreateRequest {
+            kind: JobKind::Ingest,
+            documents,
+            namespace: None,
+            dry_run: false,
+            webhook_url: None,
+        },
+    )
+    .await?;
+    summary["documents"] = json!(count);
+    summary["skipped"] = json!(skipped);
+    Ok(summary)
+}
+
+/// Reads all matching notes below `params.path` as `/index/upsert` documents;
+/// returns them together with the number of skipped files.
+fn collect_notes(params: &VaultScanParams) -> Result<(Vec<Value>, usize), String> {
+    let root = params
+        .path
+        .canonicalize()
+        .map_err(|err| format!("vault path {}: {err}", params.path.display()))?;
+    let mut documents = Vec::new();
+    let mut skipped = 0usize;
+    for entry in walkdir::WalkDir::new(&root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+    {
+        let Ok(entry) = entry else {
+            skipped += 1;
+            continue;
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let matches = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                params
+                    .extensions
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(ext))
+            });
+        if !matches {
+            continue;
+        }
+        let metadata = entry.metadata().ok();
+        if metadata
+            .as_ref()
+            .is_none_or(|meta| meta.len() > params.max_file_bytes)
+        {
+            skipped += 1;
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(path) else {
+            skipped += 1;
+            continue;
+        };
+        let relative = path
+            .strip_prefix(&root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let modified = metadata
+            .and_then(|meta| meta.modified().ok())
+            .map(|time| chrono::DateTime::<Utc>::from(time).to_rfc3339());
+        documents.push(json!({
+            "doc_id": relative,
+            "namespace": params.namespace,
+            "chunks": [{ "chunk_id": format!("{relative}#0"), "text": text }],
+            "meta": { "path": relative, "modified": modified },
+            "source_ref": { "origin": "vault", "id": relative, "trust_level": "medium" },
+        }));
+    }
+    Ok((documents, skipped))
+}
+
+async fn backup(state: &AppState, params: BackupParams) -> Result<Value, String> {
+    let created = crate::backup::create(state, params.dir, params.keep)
+        .await
+        .map_err(|(_, error)| error)?;
+    Ok(json!({
+        "backup": created.backup.name,
+        "bytes": created.backup.bytes,
+        "removed": created.removed.len(),
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(title = "ScheduleListResponse")]
+pub struct ScheduleListResponse {
+    pub schedules: Vec<ScheduleStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/scheduler/schedules",
+    tag = "core",
+    responses((status = 200, description = "Configured schedules with next and last run", body = ScheduleListResponse))
+)]
+pub async fn schedules_handler(State(state): State<AppState>) -> Json<ScheduleListResponse> {
+    let started = Instant::now();
+    let schedules = state.scheduler().statuses();
+    state.record_http_observation(Method::GET, "/scheduler/schedules", StatusCode::OK, started);
+    Json(ScheduleListResponse { schedules })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(task: &str, params: Value) -> ScheduleDef {
+        ScheduleDef {
+            id: format!("{task}-test"),
+            cron: "0 0 * * * *".into(),
+            task: task.into(),
+            params,
+            jitter_sec: 0,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn invalid_schedules_are_dropped_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules.yaml");
+        std::fs::write(
+            &path,
+            r#"
+schedules:
+  - id: retention-nightly
+    cron: "0 30 3 * * *"
+    task: retention_sweep
+  - id: mystery
+    cron: "0 0 * * * *"
+    task: format_disk
+  - id: backup-with-typo
+    cron: "0 0 4 * * Sun"
+    task: backup
+    params:
+      directory: /srv/backups
+"#,
+        )
+        .unwrap();
+        let statuses = load_scheduler(&path).statuses();
+        let ids: Vec<_> = statuses.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["retention-nightly"]);
+        assert!(load_scheduler(&dir.path().join("missing.yaml")).is_empty());
+    }
+
+    #[test]
+    fn vault_notes_become_upsert_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("projects")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".obsidian")).unwrap();
+        std::fs::write(dir.path().join("projects/hauski.md"), "# HausKI\nnotes").unwrap();
+        std::fs::write(dir.path().join("todo.MD"), "- [ ] backup").unwrap();
+        std::fs::write(dir.path().join("image.png"), [0u8, 1, 2]).unwrap();
+        std::fs::write(dir.path().join(".obsidian/workspace.md"), "ignored").unwrap();
+        std::fs::write(dir.path().join("huge.md"), "x".repeat(64)).unwrap();
+
+        let task = ScheduledTask::parse(&def(
+            "vault_scan",
+            json!({"path": dir.path(), "max_file_bytes": 32}),
+        ))
+        .unwrap();
+        let ScheduledTask::VaultScan(params) = task else {
+            panic!("expected vault_scan");
+        };
+        let (mut documents, skipped) = collect_notes(&params).unwrap();
+        documents.sort_by(|a, b| a["doc_id"].as_str().cmp(&b["doc_id"].as_str()));
+        assert_eq!(skipped, 1);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["doc_id"], "projects/hauski.md");
+        assert_eq!(documents[0]["namespace"], "vault");
+        assert_eq!(documents[0]["source_ref"]["origin"], "vault");
+        assert_eq!(documents[1]["chunks"][0]["text"], "- [ ] backup");
+    }
+}
@@ -0,0 +1,318 @@
+//! Heimgeist-Selbstbild: `GET /self/state`.
+//!
+//! Bündelt System-Signale, Index- und Memory-Statistiken, die letzten
+//! Policy-Entscheidungen (Decision Snapshots des Index) und die Einhaltung der
+//! Latenzbudgets aus `limits.yaml` in einem Dokument. Das Format ist in
+//! `contracts/self_state.schema.json` versioniert (`schema_version`).
+//!
+//! Für die Budgets hält [`LatencyBudgets`] ein gleitendes Fenster der letzten
+//! Request-Latenzen je überwachtem Pfad; gespeist wird es vom gemeinsamen
+//! HTTP-Metrik-Recorder.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{system::SystemSignals, AppState, Limits};
+
+pub const SCHEMA_ID: &str = "https://heimgewebe.github.io/hausKI/contracts/self_state.schema.json";
+pub const SCHEMA_VERSION: &str = "1.0.0";
+
+/// Number of latency samples kept per path.
+const WINDOW: usize = 512;
+/// Number of decision snapshots reported in `policy.recent_decisions`.
+const RECENT_DECISIONS: usize = 10;
+
+type BudgetLimit = fn(&Limits) -> u64;
+
+/// Budget name, budget source in `limits.yaml` and the paths it covers.
+const BUDGETS: &[(&str, BudgetLimit, &[&str])] = &[
+    ("llm_p95", |limits| limits.latency.llm_p95_ms, &["/v1/chat"]),
+    (
+        "index_topk20",
+        |limits| limits.latency.index_topk20_ms,
+        &["/index/search", "/ask"],
+    ),
+    (
+        "asr_p95",
+        |limits| limits.latency.asr_p95_ms,
+        &["/asr/stream"],
+    ),
+];
+
+/// Sliding window of request latencies for the budgeted paths.
+#[derive(Debug, Default)]
+pub struct LatencyBudgets {
+    samples: Mutex<HashMap<&'static str, VecDeque<f64>>>,
+}
+
+impl LatencyBudgets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_tracked(path: &str) -> bool {
+        BUDGETS.iter().any(|(_, _, paths)| paths.contains(&path))
+    }
+
+    /// Records a latency sample; untracked paths are ignored.
+    pub fn observe(&self, path: &'static str, elapsed_ms: f64) {
+        if !Self::is_tracked(path) {
+            return;
+        }
+        let mut samples = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let window = samples.entry(path).or_default();
+        if window.len() == WINDOW {
+            window.pop_front();
+        }
+        window.push_back(elapsed_ms);
+    }
+
+    /// p95 over all samples of `paths` (nearest-rank), plus sample count.
+    fn p95(&self, paths: &[&str]) -> (Option<f64>, usize) {
+        let samples = self
+            .samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut values: Vec<f64> = paths
+            .iter()
+            .filter_map(|path| samples.get(path))
+            .flat_map(|window| window.iter().copied())
+            .collect();
+        if values.is_empty() {
+            return (None, 0);
+        }
+        values.sort_by(f64::total_cmp);
+        let rank = ((values.len() as f64) * 0.95).ceil() as usize;
+        (Some(values[rank.saturating_sub(1)]), values.len())
+    }
+
+    pub fn report(&self, limits: &Limits) -> Vec<BudgetStatus> {
+        BUDGETS
+            .iter()
+            .map(|(name, limit, paths)| {
+                let budget_ms = limit(limits);
+                let (observed_p95_ms, samples) = self.p95(paths);
+                BudgetStatus {
+                    name: name.to_string(),
+                    paths: paths.iter().map(|p| p.to_string()).collect(),
+                    budget_ms,
+                    observed_p95_ms,
+                    samples,
+                    within_budget: observed_p95_ms.map(|p95| p95 <= budget_ms as f64),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BudgetStatus {
+    pub name: String,
+    pub paths: Vec<String>,
+    pub budget_ms: u64,
+    /// `null` until at least one request was observed.
+    pub observed_p95_ms: Option<f64>,
+    pub samples: usize,
+    /// `null` without samples.
+    pub within_budget: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SelfService {
+    pub version: String,
+    pub ready: bool,
+    pub safe_mode: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SelfIndexState {
+    pub total_documents: usize,
+    pub total_chunks: usize,
+    pub namespaces: BTreeMap<String, usize>,
+    pub policy_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SelfMemoryState {
+    pub pinned: u64,
+    pub unpinned: u64,
+    pub expired_evictions_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DecisionSummary {
+    pub decision_id: String,
+    pub intent: String,
+    pub namespace: String,
+    pub timestamp: String,
+    pub selected_id: Option<String>,
+    pub candidates: usize,
+    /// Reported outcome (`success`/`failure`/`neutral`), if any.
+    pub outcome: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SelfPolicyState {
+    pub snapshots_total: usize,
+    pub outcomes_total: usize,
+    /// Newest first.
+    pub recent_decisions: Vec<DecisionSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(title = "SelfState")]
+pub struct SelfState {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub schema_version: String,
+    pub generated_at: DateTime<Utc>,
+    pub service: SelfService,
+    /// `null` if the system monitor has no sample yet.
+    pub system: Option<SystemSignals>,
+    pub index: SelfIndexState,
+    /// `null` if the memory store is not initialized or unreadable.
+    pub memory: Option<SelfMemoryState>,
+    pub policy: SelfPolicyState,
+    pub budgets: Vec<BudgetStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/self/state",
+    responses(
+        (status = 200, description = "Aggregated self-state document", body = SelfState)
+    ),
+    tag = "core"
+)]
+pub async fn self_state_handler(State(state): State<AppState>) -> Json<SelfState> {
+    let started = std::time::Instant::now();
+    let index = state.index();
+
+    let stats = index.stats().await;
+    let snapshots = index.list_decision_snapshots().await;
+    let outcomes: HashMap<String, String> = index
+        .list_decision_outcomes()
+        .await
+        .into_iter()
+        .map(|outcome| (outcome.decision_id, outcome.outcome.to_string()))
+        .collect();
+
+    let memory = match hauski_memory::try_global() {
+        Some(store) => match store.stats().await {
+            Ok(stats) => Some(SelfMemoryState {
+                pinned: stats.pinned,
+                unpinned: stats.unpinned,
+                expired_evictions_total: stats.expired_evictions_total,
+            }),
+            Err(err) => {
+                tracing::warn!(error = ?err, "self state: memory stats unavailable");
+                None
+            }
+        },
+        None => None,
+    };
+
+    let recent_decisions = snapshots
+        .iter()
+        .rev()
+        .take(RECENT_DECISIONS)
+        .map(|snapshot| DecisionSummary {
+            decision_id: snapshot.decision_id.clone(),
+            intent: snapshot.intent.clone(),
+            namespace: snapshot.namespace.clone(),
+            timestamp: snapshot.timestamp.clone(),
+            selected_id: snapshot.selected_id.clone(),
+            candidates: snapshot.candidates.len(),
+            outcome: outcomes.get(&snapshot.decision_id).cloned(),
+        })
+        .collect();
+
+    let document = SelfState {
+        schema: SCHEMA_ID.to_string(),
+        schema_version: SCHEMA_VERSION.to_string(),
+        generated_at: Utc::now(),
+        service: SelfService {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            ready: state.is_ready(),
+            safe_mode: state.safe_mode(),
+        },
+        system: state.system_monitor().get_signals().ok(),
+        index: SelfIndexState {
+            total_documents: stats.total_documents,
+            total_chunks: stats.total_chunks,
+            namespaces: stats.namespaces.into_iter().collect(),
+            policy_hash: stats.policy_hash,
+        },
+        memory,
+        policy: SelfPolicyState {
+            snapshots_total: snapshots.len(),
+            outcomes_total: outcomes.len(),
+            recent_decisions,
+        },
+        budgets: state.latency_budgets().report(&state.limits()),
+    };
+
+    state.record_http_observation(
+        axum::http::Method::GET,
+        "/self/state",
+        axum::http::StatusCode::OK,
+        started,
+    );
+    Json(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untracked_paths_are_ignored() {
+        let budgets = LatencyBudgets::new();
+        budgets.observe("/health", 5.0);
+        let report = budgets.report(&Limits::default());
+        assert!(report.iter().all(|b| b.samples == 0));
+        assert!(report.iter().all(|b| b.within_budget.is_none()));
+    }
+
+    #[test]
+    fn p95_compares_against_limits() {
+        let budgets = LatencyBudgets::new();
+        for i in 1..=100 {
+            budgets.observe("/v1/chat", f64::from(i) * 5.0);
+        }
+        budgets.observe("/ask", 10.0);
+        budgets.observe("/index/search", 500.0);
+
+        let limits = Limits::default();
+        let report = budgets.report(&limits);
+        let llm = report.iter().find(|b| b.name == "llm_p95").unwrap();
+        assert_eq!(llm.samples, 100);
+        assert_eq!(llm.observed_p95_ms, Some(475.0));
+        assert_eq!(llm.budget_ms, limits.latency.llm_p95_ms);
+        assert_eq!(llm.within_budget, Some(false));
+
+        let index = report.iter().find(|b| b.name == "index_topk20").unwrap();
+        assert_eq!(index.samples, 2);
+        assert_eq!(index.observed_p95_ms, Some(500.0));
+    }
+
+    #[test]
+    fn window_is_bounded() {
+        let budgets = LatencyBudgets::new();
+        for _ in 0..(WINDOW + 10) {
+            budgets.observe("/ask", 1.0);
+        }
+        let (_, samples) = budgets.p95(&["/ask"]);
+        assert_eq!(samples, WINDOW);
+    }
+}
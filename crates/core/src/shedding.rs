@@ -0,0 +1,376 @@
+//! Load shedding under sustained pressure.
+//!
+//! The controller follows the threshold events of the system monitor
+//! (`system.cpu_high`, `system.memory_pressure_high`, `system.gpu_throttle`).
+//! Once one of them has been raised for `limits.shedding.sustain_sec`, batch
+//! requests – ingest jobs, index upserts and snapshot restores – are answered
+//! with `503` and `Retry-After` until every signal has cleared again.
+//! Interactive routes (chat, ask, search) are never shed.
+//!
+//! Every rejection is written as event `load_shedding` to the audit log of the
+//! policy database and counted in `load_shedding_rejections_total`;
+//! `GET /system/shedding` shows the current state and the latest decisions.
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use policy::audit::{AuditEvent, EventQuery};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    config::SheddingLimits,
+    system::{CrossingState, SignalEvent, SignalKind},
+    AppState,
+};
+
+/// Event name of shedding decisions in the audit log.
+const EVENT_SHEDDING: &str = "load_shedding";
+const DEFAULT_AUDIT_LIMIT: usize = 50;
+
+/// Routes answered with 503 while shedding; matched on method and exact path.
+pub const LOW_PRIORITY_ROUTES: &[(Method, &str)] = &[
+    (Method::POST, "/jobs"),
+    (Method::POST, "/index/upsert"),
+    (Method::POST, "/index/snapshot"),
+    (Method::POST, "/backup"),
+    (Method::POST, "/ingest/file"),
+];
+
+fn low_priority_route(method: &Method, path: &str) -> Option<&'static str> {
+    LOW_PRIORITY_ROUTES
+        .iter()
+        .find(|(route_method, route)| route_method == method && *route == path)
+        .map(|(_, route)| *route)
+}
+
+#[derive(Debug, Default)]
+struct Pressure {
+    raised: BTreeSet<SignalKind>,
+    since: Option<(Instant, DateTime<Utc>)>,
+    /// A request was shed since the pressure began; for the log transitions.
+    shedding: bool,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ShedLabels {
+    route: &'static str,
+}
+
+#[derive(Debug)]
+pub struct LoadShedder {
+    limits: SheddingLimits,
+    pressure: Mutex<Pressure>,
+    rejections: Family<ShedLabels, Counter>,
+}
+
+/// Why a request was shed.
+#[derive(Debug, Clone, PartialEq)]
+struct Verdict {
+    reasons: Vec<SignalKind>,
+    pressure_since: DateTime<Utc>,
+}
+
+impl LoadShedder {
+    pub fn new(limits: SheddingLimits) -> Self {
+        Self {
+            limits,
+            pressure: Mutex::default(),
+            rejections: Family::default(),
+        }
+    }
+
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "load_shedding_rejections",
+            "Total number of low-priority requests rejected under sustained pressure, by route",
+            self.rejections.clone(),
+        );
+    }
+
+    /// Tracks the pressure signals; disk space does not count as pressure.
+    pub fn observe(&self, event: &SignalEvent) {
+        if event.signal == SignalKind::DiskLow {
+            return;
+        }
+        let mut pressure = self
+            .pressure
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match event.state {
+            CrossingState::Raised => {
+                pressure.raised.insert(event.signal);
+                pressure
+                    .since
+                    .get_or_insert((Instant::now(), event.occurred_at));
+            }
+            CrossingState::Cleared => {
+                pressure.raised.remove(&event.signal);
+                if pressure.raised.is_empty() {
+                    pressure.since = None;
+                    if std::mem::take(&mut pressure.shedding) {
+                        tracing::info!("pressure cleared, load shedding ended");
+                    }
+                }
+            }
+        }
+    }
+
+    fn sustained(&self, pressure: &Pressure, now: Instant) -> bool {
+        self.limits.enabled
+            && pressure.since.is_some_and(|(started, _)| {
+                now.saturating_duration_since(started)
+                    >= Duration::from_secs(self.limits.sustain_sec)
+            })
+    }
+
+    fn verdict(&self, now: Instant) -> Option<Verdict> {
+        let mut pressure = self
+            .pressure
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !self.sustained(&pressure, now) {
+            return None;
+        }
+        let pressure_since = pressure.since.map(|(_, at)| at)?;
+        let reasons: Vec<SignalKind> = pressure.raised.iter().copied().collect();
+        if !std::mem::replace(&mut pressure.shedding, true) {
+            tracing::warn!(
+                ?reasons,
+                "sustained pressure, shedding low-priority requests"
+            );
+        }
+        Some(Verdict {
+            reasons,
+            pressure_since,
+        })
+    }
+
+    pub fn status(&self) -> SheddingStatus {
+        let pressure = self
+            .pressure
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        SheddingStatus {
+            enabled: self.limits.enabled,
+            active: self.sustained(&pressure, Instant::now()),
+            pressure: pressure.raised.iter().copied().collect(),
+            pressure_since: pressure.since.map(|(_, at)| at),
+            sustain_sec: self.limits.sustain_sec,
+            retry_after_sec: self.limits.retry_after_sec,
+            low_priority_routes: LOW_PRIORITY_ROUTES
+                .iter()
+                .map(|(method, route)| format!("{method} {route}"))
+                .collect(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// Feeds the threshold events of the system monitor into the shedder.
+pub(crate) fn attach(state: &AppState) {
+    let shedder = state.shedder();
+    state
+        .system_monitor()
+        .on_event(Arc::new(move |event: &SignalEvent| shedder.observe(event)));
+}
+
+/// Middleware: rejects low-priority requests while the shedder is active.
+pub(crate) async fn shed_low_priority(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(route) = low_priority_route(req.method(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let shedder = state.shedder();
+    let Some(verdict) = shedder.verdict(Instant::now()) else {
+        return next.run(req).await;
+    };
+
+    shedder
+        .rejections
+        .get_or_create(&ShedLabels { route })
+        .inc();
+    let retry_after_sec = shedder.limits.retry_after_sec;
+    let event = AuditEvent {
+        id: ulid::Ulid::new().to_string(),
+        ts: Utc::now(),
+        event: EVENT_SHEDDING.to_string(),
+        kind: Some(route.to_string()),
+        detail: json!({
+            "method": req.method().as_str(),
+            "route": route,
+            "reasons": verdict.reasons,
+            "pressure_since": verdict.pressure_since,
+            "retry_after_sec": retry_after_sec,
+        }),
+    };
+    let log = state.policy().log();
+    tokio::task::spawn_blocking(move || {
+        if let Err(err) = log.record_event(&event) {
+            tracing::warn!(error = %err, "load shedding decision could not be audited");
+        }
+    });
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, HeaderValue::from(retry_after_sec))],
+        Json(json!({
+            "error": "overloaded, low-priority requests are shed",
+            "reasons": verdict.reasons,
+            "retry_after_sec": retry_after_sec,
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SheddingStatus {
+    /// `limits.shedding.enabled`.
+    pub enabled: bool,
+    /// Low-priority requests are rejected right now.
+    pub active: bool,
+    /// Raised pressure signals.
+    pub pressure: Vec<SignalKind>,
+    /// Since when at least one pressure signal is raised.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure_since: Option<DateTime<Utc>>,
+    pub sustain_sec: u64,
+    pub retry_after_sec: u64,
+    /// `METHOD /path` of the routes that are shed.
+    pub low_priority_routes: Vec<String>,
+    /// Latest shedding decisions, newest first.
+    #[schema(value_type = Vec<Object>)]
+    pub events: Vec<AuditEvent>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SheddingQuery {
+    /// Only decisions at or after this time.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[param(default = 50, maximum = 500)]
+    pub limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/system/shedding",
+    tag = "core",
+    params(SheddingQuery),
+    responses(
+        (status = 200, description = "Load shedding state and latest decisions", body = SheddingStatus)
+    )
+)]
+pub async fn shedding_handler(
+    State(state): State<AppState>,
+    Query(query): Query<SheddingQuery>,
+) -> Response {
+    let started = Instant::now();
+    let mut status = state.shedder().status();
+    let query = EventQuery {
+        event: Some(EVENT_SHEDDING.to_string()),
+        since: query.since,
+        limit: query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT),
+        ..EventQuery::default()
+    };
+    let log = state.policy().log();
+    match tokio::task::spawn_blocking(move || log.query_events(&query)).await {
+        Ok(Ok(events)) => status.events = events,
+        Ok(Err(err)) => tracing::warn!(error = %err, "load shedding audit query failed"),
+        Err(err) => tracing::warn!(error = %err, "load shedding audit query failed"),
+    }
+    state.record_http_observation(Method::GET, "/system/shedding", StatusCode::OK, started);
+    Json(status).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::SIGNAL_EVENT_SCHEMA_VERSION;
+
+    fn event(signal: SignalKind, state: CrossingState) -> SignalEvent {
+        SignalEvent {
+            schema_version: SIGNAL_EVENT_SCHEMA_VERSION,
+            signal,
+            state,
+            value: 95.0,
+            threshold: 90.0,
+            subject: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn sustained_pressure_sheds_until_every_signal_clears() {
+        let shedder = LoadShedder::new(SheddingLimits::default()); // 30 s
+        let now = Instant::now();
+        let later = now + Duration::from_secs(31);
+        assert_eq!(shedder.verdict(later), None);
+
+        shedder.observe(&event(SignalKind::DiskLow, CrossingState::Raised));
+        assert_eq!(shedder.verdict(later), None);
+
+        shedder.observe(&event(SignalKind::CpuHigh, CrossingState::Raised));
+        shedder.observe(&event(SignalKind::GpuThrottle, CrossingState::Raised));
+        assert_eq!(shedder.verdict(now), None);
+        let verdict = shedder.verdict(later).unwrap();
+        assert_eq!(
+            verdict.reasons,
+            [SignalKind::CpuHigh, SignalKind::GpuThrottle]
+        );
+
+        shedder.observe(&event(SignalKind::CpuHigh, CrossingState::Cleared));
+        assert!(shedder.verdict(later).is_some());
+        shedder.observe(&event(SignalKind::GpuThrottle, CrossingState::Cleared));
+        assert_eq!(shedder.verdict(later), None);
+        assert!(!shedder.status().active);
+    }
+
+    #[test]
+    fn disabled_shedder_never_sheds() {
+        let shedder = LoadShedder::new(SheddingLimits {
+            enabled: false,
+            ..SheddingLimits::default()
+        });
+        shedder.observe(&event(
+            SignalKind::MemoryPressureHigh,
+            CrossingState::Raised,
+        ));
+        assert_eq!(
+            shedder.verdict(Instant::now() + Duration::from_secs(3_600)),
+            None
+        );
+    }
+
+    #[test]
+    fn only_batch_routes_are_low_priority() {
+        assert_eq!(low_priority_route(&Method::POST, "/jobs"), Some("/jobs"));
+        assert!(low_priority_route(&Method::POST, "/index/upsert").is_some());
+        assert_eq!(low_priority_route(&Method::GET, "/jobs"), None);
+        assert_eq!(low_priority_route(&Method::POST, "/v1/chat"), None);
+        assert_eq!(low_priority_route(&Method::POST, "/index/search"), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! Free space and I/O of the disks holding memory.db, the index and models.
+//!
+//! Each watched directory is mapped to the disk with the longest matching
+//! mount point. Directories that do not exist yet resolve via their nearest
+//! existing ancestor, so a fresh install is covered before its first write.
+
+use super::DiskSignals;
+use crate::config::{DiskLimits, IndexSection, MemorySection, ModelsFile};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sysinfo::{DiskRefreshKind, Disks};
+
+/// Directories the core writes to or reads models from, with a label.
+pub(crate) fn storage_dirs(
+    models: &ModelsFile,
+    index_cfg: &IndexSection,
+    memory_cfg: &MemorySection,
+) -> Vec<(String, PathBuf)> {
+    let memory = hauski_memory::MemoryConfig {
+        db_path: memory_cfg.db_path.clone(),
+        ..Default::default()
+    };
+    let mut dirs = Vec::new();
+    if let Some(parent) = memory.resolved_db_path().parent() {
+        dirs.push(("state".to_string(), parent.to_path_buf()));
+    }
+    if let Some(path) = &index_cfg.path {
+        dirs.push(("index".to_string(), expand(path)));
+    }
+    for model in &models.models {
+        if let Some(parent) = expand(&model.path).parent() {
+            let entry = ("models".to_string(), parent.to_path_buf());
+            if !dirs.contains(&entry) {
+                dirs.push(entry);
+            }
+        }
+    }
+    dirs
+}
+
+fn expand(path: &str) -> PathBuf {
+    PathBuf::from(
+        shellexpand::full(path)
+            .map(|expanded| expanded.into_owned())
+            .unwrap_or_else(|_| path.to_string()),
+    )
+}
+
+pub(super) struct DiskSampler {
+    disks: Disks,
+    dirs: Vec<(String, PathBuf)>,
+    limits: DiskLimits,
+    last_refresh: Instant,
+}
+
+impl DiskSampler {
+    pub(super) fn new(dirs: Vec<(String, PathBuf)>, limits: DiskLimits) -> Self {
+        let disks = if dirs.is_empty() {
+            Disks::new()
+        } else {
+            Disks::new_with_refreshed_list_specifics(refresh_kind())
+        };
+        Self {
+            disks,
+            dirs,
+            limits,
+            last_refresh: Instant::now(),
+        }
+    }
+
+    /// One reading per watched directory; directories on unknown mounts are
+    /// skipped.
+    pub(super) fn sample(&mut self) -> Vec<DiskSignals> {
+        if self.dirs.is_empty() {
+            return Vec::new();
+        }
+        self.disks.refresh_specifics(true, refresh_kind());
+        let elapsed = self.last_refresh.elapsed().as_secs_f64().max(1e-3);
+        self.last_refresh = Instant::now();
+
+        self.dirs
+            .iter()
+            .filter_map(|(label, path)| {
+                let existing = existing_ancestor(path);
+                let disk = self
+                    .disks
+                    .list()
+                    .iter()
+                    .filter(|disk| existing.starts_with(disk.mount_point()))
+                    .max_by_key(|disk| disk.mount_point().as_os_str().len())?;
+                let usage = disk.usage();
+                let signals = DiskSignals {
+                    label: label.clone(),
+                    path: path.display().to_string(),
+                    mount_point: disk.mount_point().display().to_string(),
+                    total_bytes: disk.total_space(),
+                    available_bytes: disk.available_space(),
+                    read_bytes_per_sec: (usage.read_bytes as f64 / elapsed) as u64,
+                    written_bytes_per_sec: (usage.written_bytes as f64 / elapsed) as u64,
+                    low_space: false,
+                };
+                Some(signals.with_limits(&self.limits))
+            })
+            .collect()
+    }
+}
+
+fn refresh_kind() -> DiskRefreshKind {
+    DiskRefreshKind::nothing().with_storage().with_io_usage()
+}
+
+fn existing_ancestor(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    absolute
+        .ancestors()
+        .find(|candidate| candidate.exists())
+        .map(Path::to_path_buf)
+        .unwrap_or(absolute)
+}
+
+impl DiskSignals {
+    /// Sets `low_space` from the free-space thresholds.
+    pub(super) fn with_limits(mut self, limits: &DiskLimits) -> Self {
+        let below_pct = u128::from(self.available_bytes) * 100
+            < u128::from(limits.min_free_pct) * u128::from(self.total_bytes);
+        self.low_space =
+            self.total_bytes > 0 && (self.available_bytes < limits.min_free_bytes || below_pct);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelEntry;
+
+    fn reading(total_bytes: u64, available_bytes: u64) -> DiskSignals {
+        DiskSignals {
+            label: "state".into(),
+            path: "/var/lib/hauski".into(),
+            mount_point: "/".into(),
+            total_bytes,
+            available_bytes,
+            read_bytes_per_sec: 0,
+            written_bytes_per_sec: 0,
+            low_space: false,
+        }
+    }
+
+    #[test]
+    fn free_space_is_compared_against_both_thresholds() {
+        let limits = DiskLimits {
+            min_free_bytes: 1_000,
+            min_free_pct: 10,
+        };
+        assert!(!reading(10_000, 1_000).with_limits(&limits).low_space);
+        assert!(reading(10_000, 999).with_limits(&limits).low_space);
+        assert!(reading(100_000, 9_999).with_limits(&limits).low_space);
+        assert!(!reading(0, 0).with_limits(&limits).low_space);
+    }
+
+    #[test]
+    fn storage_dirs_cover_state_index_and_model_parents() {
+        let models = ModelsFile {
+            models: ["a.gguf", "b.gguf"]
+                .into_iter()
+                .map(|file| ModelEntry {
+                    id: file.into(),
+                    path: format!("/opt/models/{file}"),
+                    vram_min_gb: None,
+                    canary: None,
+                    cost: None,
+                    url: None,
+                    sha256: None,
+                })
+                .collect(),
+            ..ModelsFile::default()
+        };
+        let index = IndexSection {
+            path: Some("/srv/hauski/index".into()),
+            ..IndexSection::default()
+        };
+        let memory = MemorySection {
+            db_path: Some(PathBuf::from("/var/lib/hauski/memory.db")),
+            ..MemorySection::default()
+        };
+
+        assert_eq!(
+            storage_dirs(&models, &index, &memory),
+            vec![
+                ("state".to_string(), PathBuf::from("/var/lib/hauski")),
+                ("index".to_string(), PathBuf::from("/srv/hauski/index")),
+                ("models".to_string(), PathBuf::from("/opt/models")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Threshold crossings of the system signals, published on the chronik bus.
+//!
+//! Each signal is reported once when it crosses its threshold (`raised`) and
+//! once when it recovers (`cleared`), not on every sample. CPU and memory
+//! clear `limits.pressure.hysteresis_pct` below their threshold, the GPU
+//! throttle follows [`SystemSignals::throttled`] and disks follow
+//! [`DiskSignals::low_space`](super::DiskSignals::low_space).
+//!
+//! The payload is described by `contracts/system_signal_event.schema.json`.
+
+use super::SystemSignals;
+use crate::config::{Limits, PressureLimits};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use utoipa::ToSchema;
+
+/// Version of the [`SignalEvent`] payload; bumped on incompatible changes.
+pub const SIGNAL_EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Observer for threshold crossings, see [`SystemMonitor::on_event`](super::SystemMonitor::on_event).
+pub type SignalObserver = dyn Fn(&SignalEvent) + Send + Sync;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    CpuHigh,
+    MemoryPressureHigh,
+    GpuThrottle,
+    DiskLow,
+}
+
+impl SignalKind {
+    /// Chronik kind, e.g. `system.cpu_high`.
+    pub fn event_kind(self) -> &'static str {
+        match self {
+            Self::CpuHigh => "system.cpu_high",
+            Self::MemoryPressureHigh => "system.memory_pressure_high",
+            Self::GpuThrottle => "system.gpu_throttle",
+            Self::DiskLow => "system.disk_low",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossingState {
+    Raised,
+    Cleared,
+}
+
+/// Payload of the `system.*` threshold events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SignalEvent {
+    pub schema_version: u32,
+    pub signal: SignalKind,
+    pub state: CrossingState,
+    /// Percent for CPU and memory, °C of the hottest GPU for the throttle,
+    /// available bytes for disks.
+    pub value: f64,
+    /// Limit the value is compared against, in the unit of `value`.
+    pub threshold: f64,
+    /// Disk label (`state`, `index`, `models`) for `disk_low`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Remembers which signals are raised and reports changes.
+#[derive(Debug, Default)]
+pub(super) struct CrossingDetector {
+    cpu_high: bool,
+    memory_high: bool,
+    throttled: bool,
+    low_disks: BTreeSet<String>,
+}
+
+impl CrossingDetector {
+    pub(super) fn update(&mut self, signals: &SystemSignals, limits: &Limits) -> Vec<SignalEvent> {
+        let at = signals.occurred_at;
+        let pressure = &limits.pressure;
+        let mut events = Vec::new();
+
+        for (signal, active, value, threshold) in [
+            (
+                SignalKind::CpuHigh,
+                &mut self.cpu_high,
+                signals.cpu_load,
+                pressure.cpu_high_pct,
+            ),
+            (
+                SignalKind::MemoryPressureHigh,
+                &mut self.memory_high,
+                signals.memory_pressure,
+                pressure.memory_high_pct,
+            ),
+        ] {
+            let value = f64::from(value);
+            let threshold = threshold as f64;
+            if let Some(state) = percent_crossing(active, value, threshold, pressure) {
+                events.push(event(signal, state, value, threshold, None, at));
+            }
+        }
+
+        if signals.throttled != self.throttled {
+            self.throttled = signals.throttled;
+            let hottest = signals
+                .gpus
+                .iter()
+                .filter_map(|gpu| gpu.temperature_c)
+                .max()
+                .unwrap_or_default();
+            events.push(event(
+                SignalKind::GpuThrottle,
+                raised_or_cleared(signals.throttled),
+                f64::from(hottest),
+                limits.thermal.gpu_max_c as f64,
+                None,
+                at,
+            ));
+        }
+
+        for disk in &signals.disks {
+            let was_low = self.low_disks.contains(&disk.label);
+            if disk.low_space == was_low {
+                continue;
+            }
+            if disk.low_space {
+                self.low_disks.insert(disk.label.clone());
+            } else {
+                self.low_disks.remove(&disk.label);
+            }
+            let pct_floor = disk.total_bytes / 100 * limits.disk.min_free_pct;
+            events.push(event(
+                SignalKind::DiskLow,
+                raised_or_cleared(disk.low_space),
+                disk.available_bytes as f64,
+                limits.disk.min_free_bytes.max(pct_floor) as f64,
+                Some(disk.label.clone()),
+                at,
+            ));
+        }
+        events
+    }
+}
+
+fn percent_crossing(
+    active: &mut bool,
+    value: f64,
+    threshold: f64,
+    pressure: &PressureLimits,
+) -> Option<CrossingState> {
+    if !*active && value >= threshold {
+        *active = true;
+        Some(CrossingState::Raised)
+    } else if *active && value < threshold - pressure.hysteresis_pct as f64 {
+        *active = false;
+        Some(CrossingState::Cleared)
+    } else {
+        None
+    }
+}
+
+fn raised_or_cleared(active: bool) -> CrossingState {
+    if active {
+        CrossingState::Raised
+    } else {
+        CrossingState::Cleared
+    }
+}
+
+fn event(
+    signal: SignalKind,
+    state: CrossingState,
+    value: f64,
+    threshold: f64,
+    subject: Option<String>,
+    occurred_at: DateTime<Utc>,
+) -> SignalEvent {
+    SignalEvent {
+        schema_version: SIGNAL_EVENT_SCHEMA_VERSION,
+        signal,
+        state,
+        value,
+        threshold,
+        subject,
+        occurred_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::DiskSignals;
+
+    fn signals(cpu_load: f32, throttled: bool, low_space: bool) -> SystemSignals {
+        SystemSignals {
+            cpu_load,
+            memory_pressure: 40.0,
+            raw: Default::default(),
+            gpu_available: false,
+            gpus: Vec::new(),
+            throttled,
+            disks: vec![DiskSignals {
+                label: "state".into(),
+                path: "/var/lib/hauski".into(),
+                mount_point: "/".into(),
+                total_bytes: 100 << 30,
+                available_bytes: if low_space { 1 << 30 } else { 50 << 30 },
+                read_bytes_per_sec: 0,
+                written_bytes_per_sec: 0,
+                low_space,
+            }],
+            power: None,
+            low_power: false,
+            occurred_at: Utc::now(),
+            source: None,
+            host: None,
+        }
+    }
+
+    fn states(events: &[SignalEvent]) -> Vec<(SignalKind, CrossingState)> {
+        events.iter().map(|e| (e.signal, e.state)).collect()
+    }
+
+    #[test]
+    fn crossings_are_reported_once_and_cleared_below_the_hysteresis() {
+        let limits = Limits::default(); // 90 %, hysteresis 10
+        let mut detector = CrossingDetector::default();
+
+        assert!(detector
+            .update(&signals(50.0, false, false), &limits)
+            .is_empty());
+        let raised = detector.update(&signals(95.0, true, true), &limits);
+        assert_eq!(
+            states(&raised),
+            [
+                (SignalKind::CpuHigh, CrossingState::Raised),
+                (SignalKind::GpuThrottle, CrossingState::Raised),
+                (SignalKind::DiskLow, CrossingState::Raised),
+            ]
+        );
+        assert_eq!(raised[2].subject.as_deref(), Some("state"));
+        assert_eq!(raised[0].schema_version, SIGNAL_EVENT_SCHEMA_VERSION);
+
+        assert!(detector
+            .update(&signals(85.0, true, true), &limits)
+            .is_empty());
+        assert_eq!(
+            states(&detector.update(&signals(79.0, false, false), &limits)),
+            [
+                (SignalKind::CpuHigh, CrossingState::Cleared),
+                (SignalKind::GpuThrottle, CrossingState::Cleared),
+                (SignalKind::DiskLow, CrossingState::Cleared),
+            ]
+        );
+    }
+
+    #[test]
+    fn payload_matches_the_contract() {
+        let schema: serde_json::Value = serde_json::from_str(include_str!(
+            "../../../../contracts/system_signal_event.schema.json"
+        ))
+        .unwrap();
+        let mut detector = CrossingDetector::default();
+        let events = detector.update(&signals(95.0, false, true), &Limits::default());
+        let properties = schema["properties"].as_object().unwrap();
+        for event in &events {
+            let payload = serde_json::to_value(event).unwrap();
+            let object = payload.as_object().unwrap();
+            assert!(object.keys().all(|key| properties.contains_key(key)));
+            for key in schema["required"].as_array().unwrap() {
+                assert!(object.contains_key(key.as_str().unwrap()));
+            }
+            assert_eq!(
+                payload["schema_version"],
+                properties["schema_version"]["const"]
+            );
+        }
+        assert_eq!(events.len(), 2);
+    }
+}
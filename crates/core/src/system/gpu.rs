@@ -0,0 +1,244 @@
+//! GPU sensors read via NVML when built with the `nvml` feature.
+//!
+//! NVML is loaded at runtime (`libnvidia-ml.so`); without the library or
+//! without the feature the sampler reports no GPUs and the monitor falls back
+//! to the `nvidia-smi` availability check.
+
+use super::GpuSignals;
+use crate::config::Thermal;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use std::sync::atomic::AtomicU64;
+
+pub(super) struct GpuSampler {
+    #[cfg(feature = "nvml")]
+    nvml: Option<nvml_wrapper::Nvml>,
+}
+
+impl GpuSampler {
+    #[cfg(feature = "nvml")]
+    pub(super) fn new() -> Self {
+        let nvml = match nvml_wrapper::Nvml::init() {
+            Ok(nvml) => Some(nvml),
+            Err(err) => {
+                tracing::debug!(error = %err, "NVML unavailable, GPU sensors disabled");
+                None
+            }
+        };
+        Self { nvml }
+    }
+
+    #[cfg(not(feature = "nvml"))]
+    pub(super) fn new() -> Self {
+        Self {}
+    }
+
+    /// Whether NVML is loaded and reports at least one device.
+    #[cfg(feature = "nvml")]
+    pub(super) fn has_devices(&self) -> bool {
+        self.nvml
+            .as_ref()
+            .and_then(|nvml| nvml.device_count().ok())
+            .is_some_and(|count| count > 0)
+    }
+
+    #[cfg(not(feature = "nvml"))]
+    pub(super) fn has_devices(&self) -> bool {
+        false
+    }
+
+    /// One reading per device; sensors a device does not support stay `None`.
+    #[cfg(feature = "nvml")]
+    pub(super) fn sample(&self, thermal: &Thermal) -> Vec<GpuSignals> {
+        use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+
+        let Some(nvml) = &self.nvml else {
+            return Vec::new();
+        };
+        let count = nvml.device_count().unwrap_or(0);
+        (0..count)
+            .filter_map(|index| {
+                let device = nvml.device_by_index(index).ok()?;
+                let memory = device.memory_info().ok();
+                let signals = GpuSignals {
+                    index,
+                    name: device.name().unwrap_or_default(),
+                    temperature_c: device.temperature(TemperatureSensor::Gpu).ok(),
+                    memory_used_bytes: memory.as_ref().map(|m| m.used),
+                    memory_total_bytes: memory.as_ref().map(|m| m.total),
+                    utilization_pct: device.utilization_rates().ok().map(|u| u.gpu),
+                    power_w: device
+                        .power_usage()
+                        .ok()
+                        .map(|milliwatts| milliwatts as f32 / 1000.0),
+                    over_temperature: false,
+                    over_power: false,
+                };
+                Some(signals.with_limits(thermal))
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "nvml"))]
+    pub(super) fn sample(&self, _thermal: &Thermal) -> Vec<GpuSignals> {
+        Vec::new()
+    }
+}
+
+impl GpuSignals {
+    /// Sets `over_temperature` and `over_power` from the thermal limits.
+    #[cfg_attr(not(feature = "nvml"), allow(dead_code))]
+    pub(super) fn with_limits(mut self, thermal: &Thermal) -> Self {
+        self.over_temperature = self
+            .temperature_c
+            .is_some_and(|celsius| u64::from(celsius) > thermal.gpu_max_c);
+        self.over_power = self
+            .power_w
+            .is_some_and(|watts| f64::from(watts) > thermal.dgpu_power_w as f64);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct GpuLabels {
+    gpu: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct GpuLimitLabels {
+    gpu: String,
+    limit: &'static str,
+}
+
+/// Prometheus gauges mirroring the latest GPU readings.
+#[derive(Debug, Clone, Default)]
+pub(super) struct GpuMetrics {
+    temperature: Family<GpuLabels, Gauge>,
+    memory_used: Family<GpuLabels, Gauge>,
+    memory_total: Family<GpuLabels, Gauge>,
+    utilization: Family<GpuLabels, Gauge>,
+    power: Family<GpuLabels, Gauge<f64, AtomicU64>>,
+    over_limit: Family<GpuLimitLabels, Gauge>,
+    throttle: Gauge,
+}
+
+impl GpuMetrics {
+    pub(super) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "gpu_temperature_celsius",
+            "GPU core temperature",
+            self.temperature.clone(),
+        );
+        registry.register(
+            "gpu_memory_used_bytes",
+            "Used GPU memory (VRAM)",
+            self.memory_used.clone(),
+        );
+        registry.register(
+            "gpu_memory_total_bytes",
+            "Total GPU memory (VRAM)",
+            self.memory_total.clone(),
+        );
+        registry.register(
+            "gpu_utilization_percent",
+            "GPU utilization over the last sample period",
+            self.utilization.clone(),
+        );
+        registry.register("gpu_power_watts", "GPU power draw", self.power.clone());
+        registry.register(
+            "gpu_over_limit",
+            "1 while the GPU exceeds limits.thermal (limit=temperature|power)",
+            self.over_limit.clone(),
+        );
+        registry.register(
+            "gpu_thermal_throttle",
+            "1 while the thermal throttle is active (released with hysteresis)",
+            self.throttle.clone(),
+        );
+    }
+
+    pub(super) fn observe(&self, gpus: &[GpuSignals], throttled: bool) {
+        self.throttle.set(i64::from(throttled));
+        for gpu in gpus {
+            let labels = GpuLabels {
+                gpu: gpu.index.to_string(),
+            };
+            if let Some(celsius) = gpu.temperature_c {
+                self.temperature
+                    .get_or_create(&labels)
+                    .set(i64::from(celsius));
+            }
+            if let Some(used) = gpu.memory_used_bytes {
+                self.memory_used.get_or_create(&labels).set(used as i64);
+            }
+            if let Some(total) = gpu.memory_total_bytes {
+                self.memory_total.get_or_create(&labels).set(total as i64);
+            }
+            if let Some(percent) = gpu.utilization_pct {
+                self.utilization
+                    .get_or_create(&labels)
+                    .set(i64::from(percent));
+            }
+            if let Some(watts) = gpu.power_w {
+                self.power.get_or_create(&labels).set(f64::from(watts));
+            }
+            for (limit, over) in [
+                ("temperature", gpu.over_temperature),
+                ("power", gpu.over_power),
+            ] {
+                self.over_limit
+                    .get_or_create(&GpuLimitLabels {
+                        gpu: labels.gpu.clone(),
+                        limit,
+                    })
+                    .set(i64::from(over));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(temperature_c: u32, power_w: f32) -> GpuSignals {
+        GpuSignals {
+            index: 0,
+            name: "RTX".into(),
+            temperature_c: Some(temperature_c),
+            memory_used_bytes: Some(1 << 30),
+            memory_total_bytes: Some(8 << 30),
+            utilization_pct: Some(40),
+            power_w: Some(power_w),
+            over_temperature: false,
+            over_power: false,
+        }
+    }
+
+    #[test]
+    fn readings_are_compared_against_the_thermal_limits() {
+        let thermal = Thermal::default();
+        let cool = reading(80, 220.0).with_limits(&thermal);
+        assert!(!cool.over_temperature && !cool.over_power);
+        let hot = reading(81, 220.5).with_limits(&thermal);
+        assert!(hot.over_temperature && hot.over_power);
+
+        let metrics = GpuMetrics::default();
+        metrics.observe(&[hot], true);
+        let labels = GpuLabels { gpu: "0".into() };
+        assert_eq!(metrics.temperature.get_or_create(&labels).get(), 81);
+        assert_eq!(
+            metrics
+                .over_limit
+                .get_or_create(&GpuLimitLabels {
+                    gpu: "0".into(),
+                    limit: "power",
+                })
+                .get(),
+            1
+        );
+    }
+}
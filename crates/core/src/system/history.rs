@@ -0,0 +1,224 @@
+//! Ring buffer of past system signals for `/system/signals/history`.
+//!
+//! The monitor records one point every `HAUSKI_SIGNALS_HISTORY_STEP_SEC`
+//! (default 30 s) and keeps `HAUSKI_SIGNALS_HISTORY_SEC` (default 24 h;
+//! `0` disables the history). Queries may ask for coarser steps; points are
+//! then merged into buckets aligned to the step.
+
+use super::SystemSignals;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_RETENTION_SEC: u64 = 24 * 3_600;
+const DEFAULT_RESOLUTION_SEC: u64 = 30;
+
+/// One recorded (or downsampled) sample.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct SignalPoint {
+    /// Sample time; for downsampled points the start of the bucket.
+    pub occurred_at: DateTime<Utc>,
+    /// Smoothed CPU load; averaged over the bucket.
+    pub cpu_load: f32,
+    /// Smoothed memory pressure; averaged over the bucket.
+    pub memory_pressure: f32,
+    /// Hottest GPU; maximum over the bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_temperature_c: Option<u32>,
+    /// Thermal throttle was active at any point of the bucket.
+    pub throttled: bool,
+    /// Least free space among the watched disks; minimum over the bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_available_bytes: Option<u64>,
+    /// A watched disk was low on space at any point of the bucket.
+    pub low_space: bool,
+}
+
+impl From<&SystemSignals> for SignalPoint {
+    fn from(signals: &SystemSignals) -> Self {
+        Self {
+            occurred_at: signals.occurred_at,
+            cpu_load: signals.cpu_load,
+            memory_pressure: signals.memory_pressure,
+            gpu_temperature_c: signals
+                .gpus
+                .iter()
+                .filter_map(|gpu| gpu.temperature_c)
+                .max(),
+            throttled: signals.throttled,
+            disk_available_bytes: signals.disks.iter().map(|disk| disk.available_bytes).min(),
+            low_space: signals.disks.iter().any(|disk| disk.low_space),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct SignalHistory {
+    resolution_sec: u64,
+    capacity: usize,
+    points: VecDeque<SignalPoint>,
+}
+
+impl SignalHistory {
+    pub(super) fn new(retention_sec: u64, resolution_sec: u64) -> Self {
+        let resolution_sec = resolution_sec.max(1);
+        let capacity = usize::try_from(retention_sec / resolution_sec).unwrap_or(usize::MAX);
+        Self {
+            resolution_sec,
+            capacity,
+            points: VecDeque::new(),
+        }
+    }
+
+    pub(super) fn from_env() -> Self {
+        Self::new(
+            crate::env_u64("HAUSKI_SIGNALS_HISTORY_SEC", DEFAULT_RETENTION_SEC),
+            crate::env_u64("HAUSKI_SIGNALS_HISTORY_STEP_SEC", DEFAULT_RESOLUTION_SEC),
+        )
+    }
+
+    pub(super) fn resolution_sec(&self) -> u64 {
+        self.resolution_sec
+    }
+
+    /// Records `signals` unless the last point is younger than the resolution.
+    pub(super) fn record(&mut self, signals: &SystemSignals) {
+        if self.capacity == 0 {
+            return;
+        }
+        let due = self.points.back().is_none_or(|last| {
+            signals.occurred_at - last.occurred_at >= TimeDelta::seconds(self.resolution_sec as i64)
+        });
+        if !due {
+            return;
+        }
+        if self.points.len() == self.capacity {
+            self.points.pop_front();
+        }
+        self.points.push_back(SignalPoint::from(signals));
+    }
+
+    /// Points since `since` (oldest first), merged into `step_sec` buckets
+    /// when the step is coarser than the resolution.
+    pub(super) fn query(&self, since: Option<DateTime<Utc>>, step_sec: u64) -> Vec<SignalPoint> {
+        let points = self
+            .points
+            .iter()
+            .filter(|point| since.is_none_or(|since| point.occurred_at >= since));
+        if step_sec <= self.resolution_sec {
+            return points.cloned().collect();
+        }
+
+        let step = step_sec as i64;
+        let mut buckets: Vec<(i64, Vec<&SignalPoint>)> = Vec::new();
+        for point in points {
+            let bucket = point.occurred_at.timestamp().div_euclid(step);
+            match buckets.last_mut() {
+                Some((current, members)) if *current == bucket => members.push(point),
+                _ => buckets.push((bucket, vec![point])),
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, members)| merge(bucket * step, &members))
+            .collect()
+    }
+}
+
+fn merge(start: i64, members: &[&SignalPoint]) -> SignalPoint {
+    let count = members.len() as f32;
+    SignalPoint {
+        occurred_at: DateTime::from_timestamp(start, 0).unwrap_or_default(),
+        cpu_load: members.iter().map(|point| point.cpu_load).sum::<f32>() / count,
+        memory_pressure: members
+            .iter()
+            .map(|point| point.memory_pressure)
+            .sum::<f32>()
+            / count,
+        gpu_temperature_c: members
+            .iter()
+            .filter_map(|point| point.gpu_temperature_c)
+            .max(),
+        throttled: members.iter().any(|point| point.throttled),
+        disk_available_bytes: members
+            .iter()
+            .filter_map(|point| point.disk_available_bytes)
+            .min(),
+        low_space: members.iter().any(|point| point.low_space),
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SignalHistoryQuery {
+    /// Only points at or after this time (RFC3339).
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Bucket size in seconds; values below the resolution return raw points.
+    #[serde(default)]
+    pub step_sec: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SignalHistoryResponse {
+    /// Interval between recorded points.
+    pub resolution_sec: u64,
+    /// Effective bucket size of `points`.
+    pub step_sec: u64,
+    /// Oldest first.
+    pub points: Vec<SignalPoint>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signals(at: i64, cpu_load: f32, throttled: bool) -> SystemSignals {
+        SystemSignals {
+            cpu_load,
+            memory_pressure: 50.0,
+            raw: Default::default(),
+            gpu_available: false,
+            gpus: Vec::new(),
+            throttled,
+            disks: Vec::new(),
+            power: None,
+            low_power: false,
+            occurred_at: DateTime::from_timestamp(at, 0).unwrap(),
+            source: None,
+            host: None,
+        }
+    }
+
+    #[test]
+    fn history_keeps_one_point_per_resolution_within_retention() {
+        let mut history = SignalHistory::new(90, 30);
+        for at in (0..=120).step_by(2) {
+            history.record(&signals(at, at as f32, false));
+        }
+        let points = history.query(None, 0);
+        let times: Vec<i64> = points.iter().map(|p| p.occurred_at.timestamp()).collect();
+        assert_eq!(times, [60, 90, 120]);
+
+        let since = DateTime::from_timestamp(90, 0);
+        assert_eq!(history.query(since, 30).len(), 2);
+        assert!(SignalHistory::new(0, 30).query(None, 0).is_empty());
+    }
+
+    #[test]
+    fn coarser_steps_merge_points_into_aligned_buckets() {
+        let mut history = SignalHistory::new(3_600, 30);
+        history.record(&signals(0, 10.0, false));
+        history.record(&signals(30, 30.0, true));
+        history.record(&signals(60, 50.0, false));
+
+        let points = history.query(None, 60);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].occurred_at.timestamp(), 0);
+        assert!((points[0].cpu_load - 20.0).abs() < f32::EPSILON);
+        assert!(points[0].throttled);
+        assert_eq!(points[1].occurred_at.timestamp(), 60);
+        assert!(!points[1].throttled);
+    }
+}
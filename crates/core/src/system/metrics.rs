@@ -0,0 +1,199 @@
+//! Prometheus gauges mirroring [`SystemSignals`], so dashboards and alerts
+//! scrape `/metrics` instead of polling `/system/signals`.
+//!
+//! GPU readings have their own gauges (see `gpu.rs`); this covers CPU,
+//! memory, disks and power. All gauges are updated with every sample.
+
+use super::SystemSignals;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use std::sync::atomic::AtomicU64;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct DiskLabels {
+    disk: String,
+    mount_point: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct SignalMetrics {
+    cpu_load: Gauge<f64, AtomicU64>,
+    cpu_load_raw: Gauge<f64, AtomicU64>,
+    memory_pressure: Gauge<f64, AtomicU64>,
+    memory_pressure_raw: Gauge<f64, AtomicU64>,
+    gpu_available: Gauge,
+    disk_total: Family<DiskLabels, Gauge>,
+    disk_available: Family<DiskLabels, Gauge>,
+    disk_read: Family<DiskLabels, Gauge>,
+    disk_written: Family<DiskLabels, Gauge>,
+    disk_low_space: Family<DiskLabels, Gauge>,
+    on_battery: Gauge,
+    battery: Gauge,
+    low_power: Gauge,
+}
+
+impl SignalMetrics {
+    pub(super) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "system_cpu_load_percent",
+            "Global CPU load, smoothed (EMA)",
+            self.cpu_load.clone(),
+        );
+        registry.register(
+            "system_cpu_load_raw_percent",
+            "Global CPU load of the latest sample",
+            self.cpu_load_raw.clone(),
+        );
+        registry.register(
+            "system_memory_pressure_percent",
+            "Used share of the memory, smoothed (EMA)",
+            self.memory_pressure.clone(),
+        );
+        registry.register(
+            "system_memory_pressure_raw_percent",
+            "Used share of the memory of the latest sample",
+            self.memory_pressure_raw.clone(),
+        );
+        registry.register(
+            "system_gpu_available",
+            "1 if an NVIDIA GPU was detected at startup",
+            self.gpu_available.clone(),
+        );
+        registry.register(
+            "disk_total_bytes",
+            "Capacity of the disks holding memory.db, the index and models",
+            self.disk_total.clone(),
+        );
+        registry.register(
+            "disk_available_bytes",
+            "Free space of the watched disks",
+            self.disk_available.clone(),
+        );
+        registry.register(
+            "disk_read_bytes_per_second",
+            "Bytes read from the watched disks since the previous sample",
+            self.disk_read.clone(),
+        );
+        registry.register(
+            "disk_written_bytes_per_second",
+            "Bytes written to the watched disks since the previous sample",
+            self.disk_written.clone(),
+        );
+        registry.register(
+            "disk_low_space",
+            "1 while the free space is below limits.disk",
+            self.disk_low_space.clone(),
+        );
+        registry.register(
+            "power_on_battery",
+            "1 while no mains supply is online (0 without a system battery)",
+            self.on_battery.clone(),
+        );
+        registry.register(
+            "power_battery_percent",
+            "Charge of the system battery",
+            self.battery.clone(),
+        );
+        registry.register(
+            "power_low_power_mode",
+            "1 while low-power mode is active",
+            self.low_power.clone(),
+        );
+    }
+
+    pub(super) fn observe(&self, signals: &SystemSignals) {
+        self.cpu_load.set(f64::from(signals.cpu_load));
+        self.cpu_load_raw.set(f64::from(signals.raw.cpu_load));
+        self.memory_pressure.set(f64::from(signals.memory_pressure));
+        self.memory_pressure_raw
+            .set(f64::from(signals.raw.memory_pressure));
+        self.gpu_available.set(i64::from(signals.gpu_available));
+        for disk in &signals.disks {
+            let labels = DiskLabels {
+                disk: disk.label.clone(),
+                mount_point: disk.mount_point.clone(),
+            };
+            for (family, value) in [
+                (&self.disk_total, disk.total_bytes),
+                (&self.disk_available, disk.available_bytes),
+                (&self.disk_read, disk.read_bytes_per_sec),
+                (&self.disk_written, disk.written_bytes_per_sec),
+                (&self.disk_low_space, u64::from(disk.low_space)),
+            ] {
+                family
+                    .get_or_create(&labels)
+                    .set(i64::try_from(value).unwrap_or(i64::MAX));
+            }
+        }
+        let power = signals.power.as_ref();
+        self.on_battery
+            .set(i64::from(power.is_some_and(|power| power.on_battery)));
+        if let Some(pct) = power.and_then(|power| power.battery_pct) {
+            self.battery.set(i64::from(pct));
+        }
+        self.low_power.set(i64::from(signals.low_power));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{DiskSignals, PowerSignals, RawSample};
+    use chrono::Utc;
+
+    #[test]
+    fn signals_are_encoded_as_gauges() {
+        let signals = SystemSignals {
+            cpu_load: 12.5,
+            memory_pressure: 40.0,
+            raw: RawSample {
+                cpu_load: 80.0,
+                memory_pressure: 41.0,
+            },
+            gpu_available: true,
+            gpus: Vec::new(),
+            throttled: false,
+            disks: vec![DiskSignals {
+                label: "state".into(),
+                path: "/var/lib/hauski".into(),
+                mount_point: "/var".into(),
+                total_bytes: 100,
+                available_bytes: 4,
+                read_bytes_per_sec: 0,
+                written_bytes_per_sec: 7,
+                low_space: true,
+            }],
+            power: Some(PowerSignals {
+                on_battery: true,
+                battery_pct: Some(64),
+                profile: None,
+            }),
+            low_power: false,
+            occurred_at: Utc::now(),
+            source: None,
+            host: None,
+        };
+        let metrics = SignalMetrics::default();
+        metrics.observe(&signals);
+
+        let mut registry = Registry::default();
+        metrics.register(&mut registry);
+        let mut text = String::new();
+        prometheus_client::encoding::text::encode(&mut text, &registry).unwrap();
+        for expected in [
+            "system_cpu_load_percent 12.5",
+            "system_cpu_load_raw_percent 80.0",
+            "system_gpu_available 1",
+            r#"disk_available_bytes{disk="state",mount_point="/var"} 4"#,
+            r#"disk_low_space{disk="state",mount_point="/var"} 1"#,
+            "power_on_battery 1",
+            "power_battery_percent 64",
+            "power_low_power_mode 0",
+        ] {
+            assert!(text.contains(expected), "missing {expected:?} in {text}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! AC/battery state and power profile from sysfs (Linux).
+//!
+//! `/sys/class/power_supply` tells whether mains power is online and how full
+//! the system battery is; peripheral batteries (`scope` = `Device`) are
+//! ignored. The profile is the ACPI platform profile that power-profiles-daemon
+//! and the desktop switch (`low-power`, `balanced`, `performance`). Machines
+//! without a system battery report no power signals.
+
+use super::PowerSignals;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+const PLATFORM_PROFILE: &str = "/sys/firmware/acpi/platform_profile";
+
+pub(super) struct PowerSampler {
+    supplies: PathBuf,
+    profile: PathBuf,
+}
+
+impl PowerSampler {
+    pub(super) fn new() -> Self {
+        Self::with_paths(POWER_SUPPLY_DIR, PLATFORM_PROFILE)
+    }
+
+    fn with_paths(supplies: impl Into<PathBuf>, profile: impl Into<PathBuf>) -> Self {
+        Self {
+            supplies: supplies.into(),
+            profile: profile.into(),
+        }
+    }
+
+    pub(super) fn sample(&self) -> Option<PowerSignals> {
+        let mut mains = Vec::new();
+        let mut battery: Option<(Option<u8>, Option<String>)> = None;
+        for entry in fs::read_dir(&self.supplies).ok()?.flatten() {
+            let dir = entry.path();
+            match read(&dir, "type").as_deref() {
+                Some("Mains") => mains.push(read(&dir, "online").as_deref() == Some("1")),
+                Some("Battery")
+                    if battery.is_none() && read(&dir, "scope").as_deref() != Some("Device") =>
+                {
+                    let capacity = read(&dir, "capacity").and_then(|v| v.parse().ok());
+                    battery = Some((capacity, read(&dir, "status")));
+                }
+                _ => {}
+            }
+        }
+        let (battery_pct, status) = battery?;
+        let on_battery = if mains.is_empty() {
+            status.as_deref() == Some("Discharging")
+        } else {
+            !mains.iter().any(|online| *online)
+        };
+        Some(PowerSignals {
+            on_battery,
+            battery_pct,
+            profile: fs::read_to_string(&self.profile)
+                .ok()
+                .map(|profile| profile.trim().to_string())
+                .filter(|profile| !profile.is_empty()),
+        })
+    }
+}
+
+fn read(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn supply(root: &TempDir, name: &str, files: &[(&str, &str)]) {
+        let dir = root.path().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), format!("{content}\n")).unwrap();
+        }
+    }
+
+    #[test]
+    fn battery_and_mains_are_read_from_sysfs() {
+        let root = TempDir::new().unwrap();
+        let profile = root.path().join("platform_profile");
+        let sampler = PowerSampler::with_paths(root.path(), &profile);
+        assert_eq!(sampler.sample(), None);
+
+        supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(
+            &root,
+            "hid-mouse-battery",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+        );
+        assert_eq!(sampler.sample(), None);
+
+        supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("capacity", "64"),
+                ("status", "Unknown"),
+            ],
+        );
+        fs::write(&profile, "low-power\n").unwrap();
+        assert_eq!(
+            sampler.sample(),
+            Some(PowerSignals {
+                on_battery: true,
+                battery_pct: Some(64),
+                profile: Some("low-power".into()),
+            })
+        );
+
+        supply(&root, "AC", &[("online", "1")]);
+        assert!(!sampler.sample().unwrap().on_battery);
+    }
+}
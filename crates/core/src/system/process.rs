@@ -0,0 +1,220 @@
+//! CPU and RSS attributed to the core and the processes it spawned.
+//!
+//! The core process counts as `core`. Descendants (direct children and
+//! their children) are grouped by executable name: whisper runs as `asr`,
+//! llama.cpp/Ollama/vLLM as `model_server`, piper as `tts`, everything else
+//! as `other`. The label set is fixed, so vanished components drop to 0
+//! instead of leaving stale series behind.
+
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+const COMPONENTS: [&str; 5] = ["core", "asr", "model_server", "tts", "other"];
+
+/// Child processes nested deeper than this are not followed.
+const MAX_DEPTH: usize = 16;
+
+/// Summed usage of all processes of one component.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct ComponentUsage {
+    pub(super) processes: u64,
+    /// Percent of one core, so several busy processes exceed 100.
+    pub(super) cpu_pct: f64,
+    pub(super) rss_bytes: u64,
+}
+
+/// One process as seen by the sampler.
+struct ProcessInfo<'a> {
+    pid: Pid,
+    parent: Option<Pid>,
+    name: &'a str,
+    cpu_pct: f32,
+    rss_bytes: u64,
+}
+
+pub(super) struct ProcessSampler {
+    sys: System,
+    own_pid: Option<Pid>,
+}
+
+impl ProcessSampler {
+    pub(super) fn new() -> Self {
+        Self {
+            sys: System::new(),
+            own_pid: sysinfo::get_current_pid().ok(),
+        }
+    }
+
+    /// Usage per component since the previous call (CPU is 0 on the first).
+    pub(super) fn sample(&mut self) -> HashMap<&'static str, ComponentUsage> {
+        let Some(own_pid) = self.own_pid else {
+            return HashMap::new();
+        };
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .without_tasks(),
+        );
+        let processes: Vec<ProcessInfo<'_>> = self
+            .sys
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .map(|process| ProcessInfo {
+                pid: process.pid(),
+                parent: process.parent(),
+                name: process.name().to_str().unwrap_or_default(),
+                cpu_pct: process.cpu_usage(),
+                rss_bytes: process.memory(),
+            })
+            .collect();
+        attribute(own_pid, &processes)
+    }
+}
+
+fn attribute(own_pid: Pid, processes: &[ProcessInfo<'_>]) -> HashMap<&'static str, ComponentUsage> {
+    let parents: HashMap<Pid, Option<Pid>> = processes
+        .iter()
+        .map(|process| (process.pid, process.parent))
+        .collect();
+    let mut usage: HashMap<&'static str, ComponentUsage> = HashMap::new();
+    for process in processes {
+        let component = if process.pid == own_pid {
+            "core"
+        } else if descends_from(process.pid, own_pid, &parents) {
+            component_for(process.name)
+        } else {
+            continue;
+        };
+        let entry = usage.entry(component).or_default();
+        entry.processes += 1;
+        entry.cpu_pct += f64::from(process.cpu_pct);
+        entry.rss_bytes += process.rss_bytes;
+    }
+    usage
+}
+
+fn descends_from(pid: Pid, ancestor: Pid, parents: &HashMap<Pid, Option<Pid>>) -> bool {
+    let mut current = pid;
+    for _ in 0..MAX_DEPTH {
+        match parents.get(&current).copied().flatten() {
+            Some(parent) if parent == ancestor => return true,
+            Some(parent) => current = parent,
+            None => return false,
+        }
+    }
+    false
+}
+
+fn component_for(name: &str) -> &'static str {
+    let name = name.to_ascii_lowercase();
+    if name.contains("whisper") {
+        "asr"
+    } else if ["llama", "ollama", "vllm", "koboldcpp"]
+        .iter()
+        .any(|server| name.contains(server))
+    {
+        "model_server"
+    } else if name.contains("piper") {
+        "tts"
+    } else {
+        "other"
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ComponentLabels {
+    component: &'static str,
+}
+
+/// Prometheus gauges with the latest usage per component.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ProcessMetrics {
+    cpu: Family<ComponentLabels, Gauge<f64, AtomicU64>>,
+    rss: Family<ComponentLabels, Gauge>,
+    processes: Family<ComponentLabels, Gauge>,
+}
+
+impl ProcessMetrics {
+    pub(super) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "component_cpu_percent",
+            "CPU of the core and its child processes by component (percent of one core)",
+            self.cpu.clone(),
+        );
+        registry.register(
+            "component_resident_memory_bytes",
+            "Resident memory of the core and its child processes by component",
+            self.rss.clone(),
+        );
+        registry.register(
+            "component_processes",
+            "Running processes by component",
+            self.processes.clone(),
+        );
+    }
+
+    pub(super) fn observe(&self, usage: &HashMap<&'static str, ComponentUsage>) {
+        for component in COMPONENTS {
+            let current = usage.get(component).cloned().unwrap_or_default();
+            let labels = ComponentLabels { component };
+            self.cpu.get_or_create(&labels).set(current.cpu_pct);
+            self.rss
+                .get_or_create(&labels)
+                .set(i64::try_from(current.rss_bytes).unwrap_or(i64::MAX));
+            self.processes
+                .get_or_create(&labels)
+                .set(i64::try_from(current.processes).unwrap_or(i64::MAX));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: usize, parent: usize, name: &str, cpu_pct: f32) -> ProcessInfo<'_> {
+        ProcessInfo {
+            pid: Pid::from(pid),
+            parent: Some(Pid::from(parent)),
+            name,
+            cpu_pct,
+            rss_bytes: 1_000,
+        }
+    }
+
+    #[test]
+    fn descendants_are_grouped_by_component() {
+        let processes = [
+            process(10, 1, "hauski", 20.0),
+            process(11, 10, "whisper-cli", 150.0),
+            process(12, 10, "sh", 0.0),
+            process(13, 12, "llama-server", 80.0),
+            process(14, 13, "whisper-cli", 50.0),
+            process(20, 1, "ollama", 300.0),
+        ];
+        let usage = attribute(Pid::from(10), &processes);
+
+        assert_eq!(usage["core"].processes, 1);
+        assert_eq!(usage["asr"].processes, 2);
+        assert!((usage["asr"].cpu_pct - 200.0).abs() < f64::EPSILON);
+        assert_eq!(usage["asr"].rss_bytes, 2_000);
+        assert_eq!(usage["model_server"].processes, 1);
+        assert_eq!(usage["other"].processes, 1);
+        assert!(!usage.contains_key("tts"));
+
+        let metrics = ProcessMetrics::default();
+        metrics.observe(&usage);
+        let tts = ComponentLabels { component: "tts" };
+        assert_eq!(metrics.processes.get_or_create(&tts).get(), 0);
+    }
+}
@@ -0,0 +1,79 @@
+//! Thermal throttle derived from the GPU readings.
+//!
+//! The throttle engages as soon as one GPU exceeds `limits.thermal` and only
+//! releases once every GPU is `hysteresis_c`/`hysteresis_w` below both limits,
+//! so a GPU hovering around the limit does not flap between models.
+
+use super::GpuSignals;
+use crate::config::Thermal;
+
+#[derive(Debug, Default)]
+pub(super) struct ThermalThrottle {
+    active: bool,
+}
+
+impl ThermalThrottle {
+    /// Updates the state from a new sample and returns whether it is active.
+    pub(super) fn update(&mut self, gpus: &[GpuSignals], thermal: &Thermal) -> bool {
+        let over = gpus
+            .iter()
+            .any(|gpu| gpu.over_temperature || gpu.over_power);
+        if over {
+            if !self.active {
+                tracing::warn!(
+                    gpu_max_c = thermal.gpu_max_c,
+                    dgpu_power_w = thermal.dgpu_power_w,
+                    "GPU over its thermal limits, throttling"
+                );
+            }
+            self.active = true;
+        } else if self.active && gpus.iter().all(|gpu| cooled_down(gpu, thermal)) {
+            tracing::info!("GPU back below its thermal limits, throttle released");
+            self.active = false;
+        }
+        self.active
+    }
+}
+
+fn cooled_down(gpu: &GpuSignals, thermal: &Thermal) -> bool {
+    let max_c = thermal.gpu_max_c.saturating_sub(thermal.hysteresis_c);
+    let max_w = thermal.dgpu_power_w.saturating_sub(thermal.hysteresis_w) as f64;
+    gpu.temperature_c
+        .is_none_or(|celsius| u64::from(celsius) <= max_c)
+        && gpu.power_w.is_none_or(|watts| f64::from(watts) <= max_w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(temperature_c: u32, power_w: f32, thermal: &Thermal) -> GpuSignals {
+        GpuSignals {
+            index: 0,
+            name: "RTX".into(),
+            temperature_c: Some(temperature_c),
+            memory_used_bytes: None,
+            memory_total_bytes: None,
+            utilization_pct: None,
+            power_w: Some(power_w),
+            over_temperature: false,
+            over_power: false,
+        }
+        .with_limits(thermal)
+    }
+
+    #[test]
+    fn throttle_releases_only_below_the_hysteresis() {
+        let thermal = Thermal::default(); // 80 °C, 220 W, hysteresis 5 °C / 20 W
+        let mut throttle = ThermalThrottle::default();
+
+        assert!(!throttle.update(&[gpu(80, 100.0, &thermal)], &thermal));
+        assert!(throttle.update(&[gpu(81, 100.0, &thermal)], &thermal));
+        assert!(throttle.update(&[gpu(76, 100.0, &thermal)], &thermal));
+        assert!(throttle.update(&[gpu(75, 201.0, &thermal)], &thermal));
+        assert!(!throttle.update(&[gpu(75, 200.0, &thermal)], &thermal));
+
+        assert!(throttle.update(&[gpu(60, 221.0, &thermal)], &thermal));
+        assert!(!throttle.update(&[], &thermal));
+    }
+}
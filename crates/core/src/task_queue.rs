@@ -0,0 +1,107 @@
+//! Gemeinsame Retry-Logik für Verbraucher der dauerhaften Task-Queue
+//! (`hauski_memory::MemoryStore`, Lanes `jobs` und `outbox`).
+//!
+//! Ohne Memory-Store werden Versuche nur im Prozess gezählt; die Backoff-Regeln
+//! bleiben dieselben.
+
+use std::time::Duration;
+
+use hauski_memory::{MemoryStore, RetryDecision};
+
+/// Upper bound for a single retry backoff.
+pub(crate) const MAX_RETRY_DELAY: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Reads the attempt limit (clamped to 1..=100) and backoff base from the
+    /// given environment variables.
+    pub(crate) fn from_env(
+        attempts_key: &str,
+        default_attempts: u64,
+        base_key: &str,
+        default_base_ms: u64,
+    ) -> Self {
+        Self {
+            max_attempts: crate::env_u64(attempts_key, default_attempts).clamp(1, 100) as u32,
+            base_delay: Duration::from_millis(crate::env_u64(base_key, default_base_ms)),
+        }
+    }
+
+    /// Backoff after the `attempt`-th failure: base, 2×base, 4×base, …
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(MAX_RETRY_DELAY)
+    }
+}
+
+/// A task in the durable queue, or an in-process stand-in when `store` is `None`.
+#[derive(Clone)]
+pub(crate) struct QueueEntry {
+    pub(crate) id: String,
+    pub(crate) retry: RetryPolicy,
+    store: Option<&'static MemoryStore>,
+}
+
+impl QueueEntry {
+    pub(crate) fn new(id: String, retry: RetryPolicy, store: Option<&'static MemoryStore>) -> Self {
+        Self { id, retry, store }
+    }
+
+    /// Marks the start of an attempt and returns its number.
+    pub(crate) async fn begin_attempt(&self, previous: u32) -> u32 {
+        let Some(store) = self.store else {
+            return previous + 1;
+        };
+        match store.start_task_attempt(self.id.clone()).await {
+            Ok(Some(attempt)) => attempt,
+            Ok(None) => previous + 1,
+            Err(err) => {
+                tracing::warn!(task_id = %self.id, error = ?err, "task queue update failed");
+                previous + 1
+            }
+        }
+    }
+
+    /// Books a failed attempt; returns the retry delay, or `None` once the task
+    /// is dead-lettered.
+    pub(crate) async fn fail_attempt(&self, attempt: u32, error: &str) -> Option<Duration> {
+        let delay = self.retry.delay(attempt);
+        if let Some(store) = self.store {
+            match store
+                .fail_task(self.id.clone(), error.to_string(), delay)
+                .await
+            {
+                Ok(Some(RetryDecision::Retry { .. })) => return Some(delay),
+                Ok(Some(RetryDecision::Dead { .. })) => return None,
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!(task_id = %self.id, error = ?err, "task queue update failed");
+                }
+            }
+        }
+        (attempt < self.retry.max_attempts).then_some(delay)
+    }
+
+    /// Dead-letters the task right away, without further attempts.
+    pub(crate) async fn bury(&self, error: &str) {
+        if let Some(store) = self.store {
+            if let Err(err) = store.bury_task(self.id.clone(), error.to_string()).await {
+                tracing::warn!(task_id = %self.id, error = ?err, "task queue update failed");
+            }
+        }
+    }
+
+    /// Removes the queue entry once the task reached a final state.
+    pub(crate) async fn settle(&self) {
+        if let Some(store) = self.store {
+            if let Err(err) = store.complete_task(self.id.clone()).await {
+                tracing::warn!(task_id = %self.id, error = ?err, "task queue update failed");
+            }
+        }
+    }
+}
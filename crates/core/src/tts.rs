@@ -0,0 +1,353 @@
+//! Sprachausgabe über HTTP: `POST /tts` und `GET /tts/voices`.
+//!
+//! Die Synthese übernimmt Piper über `hauski-tts`; die Stimmen stehen im
+//! Abschnitt `voices` von `models.yml`. `/tts` antwortet, sobald Piper den
+//! ersten Satz erzeugt hat, und streamt den Rest hinterher – eine lange
+//! Antwort des Assistenten ist so nach dem ersten Satz hörbar.
+//!
+//! Formate: `wav` (16 Bit mono, Rate der Stimme; der Header trägt die
+//! Streaming-Maximalgröße) oder `opus` (Ogg, über `ffmpeg`).
+//!
+//! Scheitert Piper, bevor Audio kommt, antwortet die Route mit Fehlerstatus
+//! und stderr; bricht Piper später ab, endet der Strom vorzeitig.
+//!
+//! Konfiguration (zusätzlich zu den Variablen von `hauski-tts`):
+//!   HAUSKI_TTS_MAX_CHARS  (Default 5000; längere Texte → 413)
+
+use std::{process::Stdio, time::Instant};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::stream;
+use hauski_tts::{
+    prepare_text, wav_header, AudioFormat, SynthOptions, TtsConfig, TtsError, VoiceRegistry,
+    VoiceSpec,
+};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, histogram::Histogram},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::{Child, Command},
+    task::JoinHandle,
+};
+use utoipa::ToSchema;
+
+use crate::AppState;
+
+const DEFAULT_MAX_CHARS: u64 = 5_000;
+/// Bytes per body chunk.
+const CHUNK_BYTES: usize = 16 * 1024;
+/// Tail of Piper's stderr kept for error messages.
+const STDERR_BYTES: usize = 4 * 1024;
+const FIRST_AUDIO_BUCKETS: [f64; 9] = [0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct SynthesisLabels {
+    format: &'static str,
+    outcome: &'static str,
+}
+
+fn create_first_audio_histogram() -> Histogram {
+    Histogram::new(FIRST_AUDIO_BUCKETS)
+}
+
+#[derive(Debug)]
+pub struct TtsService {
+    config: TtsConfig,
+    voices: VoiceRegistry,
+    max_chars: usize,
+    syntheses: Family<SynthesisLabels, Counter>,
+    first_audio: Histogram,
+}
+
+impl TtsService {
+    pub(crate) fn new(config: TtsConfig, voices: VoiceRegistry, max_chars: usize) -> Self {
+        Self {
+            config,
+            voices,
+            max_chars,
+            syntheses: Family::default(),
+            first_audio: create_first_audio_histogram(),
+        }
+    }
+
+    /// Invalid voice lists are logged and leave `/tts` without voices.
+    pub(crate) fn load_from_env(voices: Vec<VoiceSpec>) -> Self {
+        let voices = VoiceRegistry::new(voices).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "invalid voices in models.yml, /tts stays disabled");
+            VoiceRegistry::default()
+        });
+        let max_chars = crate::env_u64("HAUSKI_TTS_MAX_CHARS", DEFAULT_MAX_CHARS);
+        Self::new(TtsConfig::from_env(), voices, max_chars as usize)
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "tts_syntheses",
+            "Total number of /tts requests by format and outcome (ok/error)",
+            self.syntheses.clone(),
+        );
+        registry.register(
+            "tts_first_audio_seconds",
+            "Time from a /tts request to the first synthesized audio",
+            self.first_audio.clone(),
+        );
+    }
+
+    fn count(&self, format: AudioFormat, outcome: &'static str) {
+        self.syntheses
+            .get_or_create(&SynthesisLabels {
+                format: format.as_str(),
+                outcome,
+            })
+            .inc();
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TtsRequest {
+    pub text: String,
+    /// Voice ID from the `voices` section of `models.yml` (default: default voice).
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub format: AudioFormat,
+    /// Speaker of a multi-speaker voice.
+    #[serde(default)]
+    pub speaker: Option<u32>,
+    /// Speaking rate relative to the voice (0.25–4).
+    #[serde(default)]
+    pub speed: Option<f32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TtsVoice {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    /// Model and config file exist.
+    pub available: bool,
+    pub default: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TtsVoicesResponse {
+    pub voices: Vec<TtsVoice>,
+}
+
+type Rejection = (StatusCode, String);
+
+#[utoipa::path(
+    post,
+    path = "/tts",
+    tag = "core",
+    request_body = TtsRequest,
+    responses(
+        (status = 200, description = "Audio, streamed while it is synthesized (`audio/wav` or `audio/ogg`)", content_type = "audio/wav"),
+        (status = 400, description = "Empty text, unknown voice or invalid speed"),
+        (status = 413, description = "Text longer than HAUSKI_TTS_MAX_CHARS"),
+        (status = 422, description = "Piper produced no audio"),
+        (status = 503, description = "No voice, voice files or binaries available")
+    )
+)]
+pub async fn synthesize_handler(
+    State(state): State<AppState>,
+    Json(request): Json<TtsRequest>,
+) -> Response {
+    let started = Instant::now();
+    let tts = state.tts();
+    let format = request.format;
+    let response = match synthesize(&tts, request).await {
+        Ok(audio) => {
+            tts.count(format, "ok");
+            tts.first_audio.observe(started.elapsed().as_secs_f64());
+            ([(header::CONTENT_TYPE, format.content_type())], audio).into_response()
+        }
+        Err((status, error)) => {
+            tts.count(format, "error");
+            (status, Json(json!({ "error": error }))).into_response()
+        }
+    };
+    state.record_http_observation(Method::POST, "/tts", response.status(), started);
+    response
+}
+
+/// Starts Piper (and ffmpeg) and waits for the first audio, so failures
+/// still get an error status.
+async fn synthesize(tts: &TtsService, request: TtsRequest) -> Result<Body, Rejection> {
+    let text = prepare_text(&request.text).map_err(rejection)?;
+    if text.chars().count() > tts.max_chars {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("text is longer than {} characters", tts.max_chars),
+        ));
+    }
+    let options = SynthOptions {
+        speaker: request.speaker,
+        speed: request.speed,
+    };
+    options.validate().map_err(rejection)?;
+    let voice = tts
+        .voices
+        .select(request.voice.as_deref())
+        .map_err(rejection)?;
+    voice.check_files().map_err(rejection)?;
+    let sample_rate = voice.sample_rate().map_err(rejection)?;
+
+    let mut piper = spawn(
+        tts.config.piper_command(voice, &options),
+        &tts.config.piper_bin,
+        "HAUSKI_PIPER_BIN",
+    )?;
+    let mut stdin = piper.stdin.take().expect("piped stdin");
+    tokio::spawn(async move {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    });
+    let stderr = collect_stderr(&mut piper);
+    let raw = piper.stdout.take().expect("piped stdout");
+
+    let mut children = vec![piper];
+    let mut output: Box<dyn AsyncRead + Send + Unpin> =
+        match tts.config.encoder_command(request.format, sample_rate) {
+            None => Box::new(raw),
+            Some(command) => {
+                let mut encoder = spawn(command, &tts.config.ffmpeg_bin, "HAUSKI_FFMPEG_BIN")?;
+                let mut encoder_in = encoder.stdin.take().expect("piped stdin");
+                let mut raw = raw;
+                tokio::spawn(async move {
+                    let _ = tokio::io::copy(&mut raw, &mut encoder_in).await;
+                });
+                let encoded = encoder.stdout.take().expect("piped stdout");
+                children.push(encoder);
+                Box::new(encoded)
+            }
+        };
+
+    let mut first = vec![0_u8; CHUNK_BYTES];
+    let read = output
+        .read(&mut first)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    if read == 0 {
+        let status = children[0].wait().await.ok();
+        let stderr = stderr.await.unwrap_or_default();
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            match status {
+                Some(status) if !status.success() => format!("piper failed ({status}): {stderr}"),
+                _ => "piper produced no audio".to_string(),
+            },
+        ));
+    }
+    first.truncate(read);
+    if request.format == AudioFormat::Wav {
+        first.splice(0..0, wav_header(sample_rate, None));
+    }
+
+    // The children travel with the body: a client that hangs up kills them.
+    let rest = stream::unfold((output, children), |(mut output, children)| async move {
+        let mut chunk = vec![0_u8; CHUNK_BYTES];
+        match output.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(read) => {
+                chunk.truncate(read);
+                Some((Ok(Bytes::from(chunk)), (output, children)))
+            }
+            Err(err) => Some((Err(err), (output, children))),
+        }
+    });
+    let first = stream::iter([Ok::<_, std::io::Error>(Bytes::from(first))]);
+    Ok(Body::from_stream(futures_util::StreamExt::chain(
+        first, rest,
+    )))
+}
+
+fn spawn(command: std::process::Command, bin: &str, env: &'static str) -> Result<Child, Rejection> {
+    let mut command = Command::from(command);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    command.spawn().map_err(|err| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{bin} could not be started (set {env}): {err}"),
+        )
+    })
+}
+
+/// Drains stderr so a chatty process never blocks; keeps the tail.
+fn collect_stderr(child: &mut Child) -> JoinHandle<String> {
+    let stderr = child.stderr.take();
+    tokio::spawn(async move {
+        let Some(mut stderr) = stderr else {
+            return String::new();
+        };
+        let mut tail = Vec::new();
+        let mut buf = [0_u8; 1024];
+        while let Ok(read @ 1..) = stderr.read(&mut buf).await {
+            tail.extend_from_slice(&buf[..read]);
+            if tail.len() > STDERR_BYTES {
+                tail.drain(..tail.len() - STDERR_BYTES);
+            }
+        }
+        String::from_utf8_lossy(&tail).trim().to_string()
+    })
+}
+
+fn rejection(err: TtsError) -> Rejection {
+    let status = match &err {
+        TtsError::UnknownVoice(_) | TtsError::EmptyText | TtsError::InvalidSpeed => {
+            StatusCode::BAD_REQUEST
+        }
+        TtsError::NoVoice | TtsError::VoiceMissing(_) | TtsError::InvalidVoice { .. } => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        TtsError::DuplicateVoice(_) | TtsError::MultipleDefaults => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, err.to_string())
+}
+
+#[utoipa::path(
+    get,
+    path = "/tts/voices",
+    tag = "core",
+    responses(
+        (status = 200, description = "Configured Piper voices", body = TtsVoicesResponse)
+    )
+)]
+pub async fn voices_handler(State(state): State<AppState>) -> Response {
+    let started = Instant::now();
+    let tts = state.tts();
+    let default = tts.voices.default_voice().map(|voice| voice.id.clone());
+    let voices = tts
+        .voices
+        .voices()
+        .iter()
+        .map(|voice| TtsVoice {
+            id: voice.id.clone(),
+            language: voice.language.clone(),
+            speaker: voice.speaker,
+            available: voice.is_available(),
+            default: default.as_deref() == Some(voice.id.as_str()),
+        })
+        .collect();
+    state.record_http_observation(Method::GET, "/tts/voices", StatusCode::OK, started);
+    Json(TtsVoicesResponse { voices }).into_response()
+}
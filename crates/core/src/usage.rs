@@ -0,0 +1,394 @@
+//! Token- und Kosten-Accounting für Chat-Anfragen.
+//!
+//! Pro Request werden Prompt-/Completion-Tokens erfasst – bevorzugt aus den
+//! Usage-Feldern des Upstreams, sonst über eine lokale Schätzung. Die Werte
+//! landen in Prometheus-Countern (pro Modell und Client) und in einer
+//! In-Memory-Aggregation, die `/usage` für Budgetierung ausliefert.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{atomic::AtomicU64, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, Method, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family},
+};
+use serde::Serialize;
+// Used by utoipa's #[schema(example = json!(...))] attribute macros
+#[allow(unused_imports)]
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{chat::ChatMessage, chat_upstream::UpstreamUsage, AppState, ModelCost};
+
+/// Header, über den sich Clients für das Accounting identifizieren.
+pub const CLIENT_HEADER: &str = "x-hauski-client";
+const ANONYMOUS_CLIENT: &str = "anonymous";
+const MAX_CLIENT_LEN: usize = 64;
+/// Grobe Heuristik der lokalen Schätzung: ~4 Zeichen pro Token.
+const CHARS_PER_TOKEN: u64 = 4;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TokenLabels {
+    model: String,
+    client: String,
+    kind: &'static str,
+}
+
+impl EncodeLabelSet for TokenLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("model", self.model.as_str()).encode(encoder.encode_label())?;
+        ("client", self.client.as_str()).encode(encoder.encode_label())?;
+        ("kind", self.kind).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct CostLabels {
+    model: String,
+    client: String,
+}
+
+impl EncodeLabelSet for CostLabels {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), fmt::Error> {
+        ("model", self.model.as_str()).encode(encoder.encode_label())?;
+        ("client", self.client.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+/// Woher die Token-Zahlen eines Requests stammen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageSource {
+    Upstream,
+    Estimated,
+}
+
+/// Token-Verbrauch eines einzelnen Chat-Requests.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[schema(title = "ChatUsage", example = json!({"prompt_tokens":12,"completion_tokens":48,"source":"upstream"}))]
+pub struct ChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub source: UsageSource,
+}
+
+impl ChatUsage {
+    /// Übernimmt Upstream-Werte; fehlende Felder werden lokal geschätzt.
+    pub fn resolve(upstream: UpstreamUsage, messages: &[ChatMessage], completion: &str) -> Self {
+        match (upstream.prompt_tokens, upstream.completion_tokens) {
+            (Some(prompt_tokens), Some(completion_tokens)) => Self {
+                prompt_tokens,
+                completion_tokens,
+                source: UsageSource::Upstream,
+            },
+            (prompt, completion_tokens) => Self {
+                prompt_tokens: prompt
+                    .unwrap_or_else(|| messages.iter().map(|m| estimate_tokens(&m.content)).sum()),
+                completion_tokens: completion_tokens.unwrap_or_else(|| estimate_tokens(completion)),
+                source: UsageSource::Estimated,
+            },
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Lokale Token-Schätzung (kein echter Tokenizer, aber stabil und billig).
+pub fn estimate_tokens(text: &str) -> u64 {
+    let chars = text.chars().count() as u64;
+    chars.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Liest die Client-ID aus dem Header und begrenzt sie auf ein label-taugliches Format.
+pub fn client_id_from_headers(headers: &HeaderMap) -> String {
+    headers
+        .get(CLIENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| {
+            !v.is_empty()
+                && v.len() <= MAX_CLIENT_LEN
+                && v.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+        .unwrap_or(ANONYMOUS_CLIENT)
+        .to_string()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Geschätzte Kosten laut `cost` in `models.yml` (0, wenn nicht gepflegt).
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &ChatUsage, cost: f64) {
+        self.requests += 1;
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+        self.total_tokens += usage.total_tokens();
+        self.cost += cost;
+    }
+
+    fn merge(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cost += other.cost;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[schema(title = "UsageSummary", example = json!({
+    "since": "2025-01-01T00:00:00Z",
+    "totals": {"requests": 2, "prompt_tokens": 40, "completion_tokens": 90, "total_tokens": 130, "cost": 0.0},
+    "by_model": {"llama3.1-8b-q4": {"requests": 2, "prompt_tokens": 40, "completion_tokens": 90, "total_tokens": 130, "cost": 0.0}},
+    "by_client": {"anonymous": {"requests": 2, "prompt_tokens": 40, "completion_tokens": 90, "total_tokens": 130, "cost": 0.0}}
+}))]
+pub struct UsageSummary {
+    /// Start des Aggregationsfensters (Prozessstart).
+    pub since: DateTime<Utc>,
+    pub totals: UsageTotals,
+    pub by_model: BTreeMap<String, UsageTotals>,
+    pub by_client: BTreeMap<String, UsageTotals>,
+}
+
+#[derive(Debug)]
+pub struct UsageTracker {
+    tokens: Family<TokenLabels, Counter>,
+    cost: Family<CostLabels, Counter<f64, AtomicU64>>,
+    totals: Mutex<HashMap<(String, String), UsageTotals>>,
+    since: DateTime<Utc>,
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self {
+            tokens: Family::default(),
+            cost: Family::default(),
+            totals: Mutex::new(HashMap::new()),
+            since: Utc::now(),
+        }
+    }
+
+    pub fn token_counter(&self) -> Family<TokenLabels, Counter> {
+        self.tokens.clone()
+    }
+
+    pub fn cost_counter(&self) -> Family<CostLabels, Counter<f64, AtomicU64>> {
+        self.cost.clone()
+    }
+
+    /// Verbucht einen Request und liefert die berechneten Kosten zurück.
+    pub fn record(
+        &self,
+        model: &str,
+        client: &str,
+        usage: &ChatUsage,
+        price: Option<ModelCost>,
+    ) -> f64 {
+        let cost = price
+            .map(|p| {
+                (usage.prompt_tokens as f64 / 1000.0) * p.prompt_per_1k
+                    + (usage.completion_tokens as f64 / 1000.0) * p.completion_per_1k
+            })
+            .unwrap_or(0.0);
+
+        for (kind, value) in [
+            ("prompt", usage.prompt_tokens),
+            ("completion", usage.completion_tokens),
+        ] {
+            self.tokens
+                .get_or_create(&TokenLabels {
+                    model: model.to_string(),
+                    client: client.to_string(),
+                    kind,
+                })
+                .inc_by(value);
+        }
+        if cost > 0.0 {
+            self.cost
+                .get_or_create(&CostLabels {
+                    model: model.to_string(),
+                    client: client.to_string(),
+                })
+                .inc_by(cost);
+        }
+
+        let mut totals = self
+            .totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        totals
+            .entry((model.to_string(), client.to_string()))
+            .or_default()
+            .add(usage, cost);
+
+        cost
+    }
+
+    pub fn summary(&self) -> UsageSummary {
+        let totals = self
+            .totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut summary = UsageSummary {
+            since: self.since,
+            totals: UsageTotals::default(),
+            by_model: BTreeMap::new(),
+            by_client: BTreeMap::new(),
+        };
+        for ((model, client), entry) in totals.iter() {
+            summary.totals.merge(entry);
+            summary
+                .by_model
+                .entry(model.clone())
+                .or_default()
+                .merge(entry);
+            summary
+                .by_client
+                .entry(client.clone())
+                .or_default()
+                .merge(entry);
+        }
+        summary
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/usage",
+    responses((status = 200, description = "Aggregated chat token usage and cost", body = UsageSummary)),
+    tag = "core"
+)]
+pub async fn usage_handler(State(state): State<AppState>) -> Json<UsageSummary> {
+    let started = Instant::now();
+    let summary = state.usage().summary();
+    state.record_http_observation(Method::GET, "/usage", StatusCode::OK, started);
+    Json(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::ChatRole;
+    use axum::http::HeaderValue;
+
+    fn user(content: &str) -> ChatMessage {
+        ChatMessage {
+            role: ChatRole::User,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn resolve_prefers_upstream_counts() {
+        let usage = ChatUsage::resolve(
+            UpstreamUsage {
+                prompt_tokens: Some(7),
+                completion_tokens: Some(11),
+            },
+            &[user("hallo")],
+            "antwort",
+        );
+        assert_eq!(usage.prompt_tokens, 7);
+        assert_eq!(usage.completion_tokens, 11);
+        assert_eq!(usage.source, UsageSource::Upstream);
+    }
+
+    #[test]
+    fn resolve_estimates_missing_counts() {
+        let usage = ChatUsage::resolve(
+            UpstreamUsage {
+                prompt_tokens: None,
+                completion_tokens: Some(3),
+            },
+            &[user("12345678"), user("1")],
+            "ignored",
+        );
+        // 8 chars -> 2 tokens, 1 char -> 1 token
+        assert_eq!(usage.prompt_tokens, 3);
+        assert_eq!(usage.completion_tokens, 3);
+        assert_eq!(usage.source, UsageSource::Estimated);
+    }
+
+    #[test]
+    fn client_id_is_sanitized() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_id_from_headers(&headers), "anonymous");
+
+        headers.insert(CLIENT_HEADER, HeaderValue::from_static("vscode-ext_1.2"));
+        assert_eq!(client_id_from_headers(&headers), "vscode-ext_1.2");
+
+        headers.insert(CLIENT_HEADER, HeaderValue::from_static("evil client\"}"));
+        assert_eq!(client_id_from_headers(&headers), "anonymous");
+    }
+
+    #[test]
+    fn record_aggregates_per_model_and_client() {
+        let tracker = UsageTracker::new();
+        let usage = ChatUsage {
+            prompt_tokens: 1000,
+            completion_tokens: 500,
+            source: UsageSource::Upstream,
+        };
+        let price = ModelCost {
+            prompt_per_1k: 0.5,
+            completion_per_1k: 2.0,
+        };
+
+        let cost = tracker.record("m1", "a", &usage, Some(price));
+        assert!((cost - 1.5).abs() < f64::EPSILON);
+        tracker.record("m1", "b", &usage, None);
+        tracker.record("m2", "a", &usage, None);
+
+        let summary = tracker.summary();
+        assert_eq!(summary.totals.requests, 3);
+        assert_eq!(summary.totals.total_tokens, 4500);
+        assert_eq!(summary.by_model["m1"].requests, 2);
+        assert_eq!(summary.by_client["a"].requests, 2);
+        assert!((summary.by_client["a"].cost - 1.5).abs() < f64::EPSILON);
+
+        let prompt = tracker
+            .token_counter()
+            .get_or_create(&TokenLabels {
+                model: "m1".into(),
+                client: "a".into(),
+                kind: "prompt",
+            })
+            .get();
+        assert_eq!(prompt, 1000);
+    }
+}
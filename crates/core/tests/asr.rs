@@ -0,0 +1,79 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use axum::http::{HeaderValue, StatusCode};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use serde_json::json;
+
+use common::{call, post_json, script};
+
+/// `whisper-cli` stand-in that needs two seconds, longer than the default
+/// `HAUSKI_HTTP_TIMEOUT_MS`, to hear "Hallo".
+fn slow_whisper(dir: &Path) -> String {
+    let body = format!(
+        "sleep 2\nwhile [ $# -gt 0 ]; do\n  [ \"$1\" = -of ] && out=\"$2\"\n  shift\ndone\nprintf '%s' '{json}' > \"$out.json\"\n",
+        json = r#"{"result":{"language":"de"},"transcription":[{"offsets":{"from":0,"to":900},"text":" Hallo"}]}"#,
+    );
+    script(dir, "whisper-cli", &body)
+}
+
+/// One second of a 16 kHz mono PCM square wave, loud enough for the energy gate.
+fn wav(path: &Path) {
+    let samples: Vec<u8> = (0..16_000)
+        .flat_map(|i: i16| (if i % 20 < 10 { 8_000i16 } else { -8_000 }).to_le_bytes())
+        .collect();
+    let mut bytes = Vec::with_capacity(44 + samples.len());
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&16_000u32.to_le_bytes());
+    bytes.extend_from_slice(&32_000u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&samples);
+    fs::write(path, bytes).unwrap();
+}
+
+#[tokio::test]
+async fn transcriptions_outlast_the_global_request_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+    std::env::set_var("HAUSKI_WHISPER_BIN", slow_whisper(path));
+    std::env::set_var("HAUSKI_ASR_INPUT_DIRS", path);
+    let model = path.join("ggml-tiny.bin");
+    fs::write(&model, b"ggml").unwrap();
+    wav(&path.join("memo.wav"));
+    let models: ModelsFile = serde_yaml_ng::from_str(&format!(
+        "models:\n  - id: whisper-tiny\n    path: {}\n",
+        model.display()
+    ))
+    .unwrap();
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        models,
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let (status, transcript) = call(
+        &app,
+        post_json(
+            "/asr/transcribe",
+            &json!({ "path": path.join("memo.wav"), "device": "cpu" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{transcript}");
+    assert_eq!(transcript["text"], "Hallo");
+    assert_eq!(transcript["language"], "de");
+}
@@ -0,0 +1,109 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use axum::http::HeaderValue;
+use futures_util::{SinkExt, StreamExt};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+use common::script;
+
+/// `whisper-cli` stand-in that always hears "Hallo" in the first 900 ms.
+fn fake_whisper(dir: &Path) -> String {
+    let body = format!(
+        "while [ $# -gt 0 ]; do\n  [ \"$1\" = -of ] && out=\"$2\"\n  shift\ndone\nprintf '%s' '{json}' > \"$out.json\"\n",
+        json = r#"{"result":{"language":"de"},"transcription":[{"offsets":{"from":0,"to":900},"text":" Hallo"}]}"#,
+    );
+    script(dir, "whisper-cli", &body)
+}
+
+/// 16 kHz PCM, 100 ms per message: `silence` then `speech` messages.
+fn pcm(silence: usize, speech: usize) -> Vec<Vec<u8>> {
+    let message = |amplitude: i16| -> Vec<u8> {
+        (0..1_600)
+            .flat_map(|i| (if i % 2 == 0 { amplitude } else { -amplitude }).to_le_bytes())
+            .collect()
+    };
+    let mut messages = vec![message(0); silence];
+    messages.extend(std::iter::repeat_n(message(8_000), speech));
+    messages
+}
+
+async fn next_event(
+    socket: &mut (impl StreamExt<Item = tokio_tungstenite::tungstenite::Result<Message>> + Unpin),
+) -> Option<Value> {
+    loop {
+        match socket.next().await? {
+            Ok(Message::Text(text)) => return Some(serde_json::from_str(&text).unwrap()),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn pcm_stream_yields_partials_and_offset_finals() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("HAUSKI_WHISPER_BIN", fake_whisper(dir.path()));
+    std::env::set_var("HAUSKI_ASR_STREAM_PAUSE_MS", "300");
+    let model = dir.path().join("ggml-tiny.bin");
+    fs::write(&model, b"ggml").unwrap();
+    let models: ModelsFile = serde_yaml_ng::from_str(&format!(
+        "models:\n  - id: whisper-tiny\n    path: {}\n",
+        model.display()
+    ))
+    .unwrap();
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        models,
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let url = format!("ws://{addr}/asr/stream?chunk_ms=500&language=de&device=cpu");
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    let ready = next_event(&mut socket).await.unwrap();
+    assert_eq!(ready["type"], "ready");
+    assert_eq!(ready["model"], "whisper-tiny");
+    assert_eq!(ready["device"], "cpu");
+    assert_eq!(ready["chunk_ms"], 500);
+
+    // 1 s silence, 1.2 s speech, 0.5 s pause, 0.3 s speech, then `end`.
+    let mut messages = pcm(10, 12);
+    messages.extend(pcm(5, 3));
+    for message in messages {
+        socket.send(Message::Binary(message.into())).await.unwrap();
+    }
+    socket.send(Message::Text("end".into())).await.unwrap();
+
+    let mut events = Vec::new();
+    while let Some(event) = next_event(&mut socket).await {
+        events.push(event);
+    }
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|event| event["type"].as_str().unwrap())
+        .collect();
+    assert!(kinds.contains(&"partial"), "{kinds:?}");
+    assert!(!kinds.contains(&"error"), "{events:?}");
+    let finals: Vec<_> = events
+        .iter()
+        .filter(|event| event["type"] == "final")
+        .collect();
+    assert_eq!(finals.len(), 2, "{events:?}");
+    assert_eq!(finals[0]["utterance"], 0);
+    assert_eq!(finals[0]["text"], "Hallo");
+    // Speech starts in the 30 ms frame at 990 ms; 300 ms pre-roll before it.
+    assert_eq!(finals[0]["segments"][0]["start_ms"], 690);
+    assert_eq!(finals[1]["utterance"], 1);
+    assert!(finals[1]["latency_ms"].is_u64());
+}
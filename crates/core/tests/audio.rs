@@ -0,0 +1,195 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request, StatusCode},
+    Router,
+};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{call, post_json, script};
+
+const PROFILES: &str = r#"
+profiles:
+  call:
+    sink: "bluez_output.*"
+    source: "bluez_input.*"
+    volume: 0.6
+    mute_source: false
+  asr:
+    source: "alsa_input.*"
+    source_volume: 1.0
+    mute_source: false
+  music:
+    volume: 0.5
+  broken:
+    sink: "hdmi_output.*"
+"#;
+
+/// Default sink 51 at wpctl volume 0.5, muted default source 53, a headset.
+const DUMP: &str = r#"[
+  {"id": 40, "type": "PipeWire:Interface:Metadata", "props": {"metadata.name": "default"},
+   "metadata": [{"key": "default.audio.sink", "value": {"name": "alsa_output.pci-0.analog"}},
+                {"key": "default.audio.source", "value": {"name": "alsa_input.usb-mic"}}]},
+  {"id": 51, "type": "PipeWire:Interface:Node",
+   "info": {"props": {"node.name": "alsa_output.pci-0.analog", "media.class": "Audio/Sink"},
+            "params": {"Props": [{"mute": false, "channelVolumes": [0.125]}]}}},
+  {"id": 53, "type": "PipeWire:Interface:Node",
+   "info": {"props": {"node.name": "alsa_input.usb-mic", "media.class": "Audio/Source"},
+            "params": {"Props": [{"mute": true, "channelVolumes": [1.0]}]}}},
+  {"id": 61, "type": "PipeWire:Interface:Node",
+   "info": {"props": {"node.name": "bluez_output.headset", "media.class": "Audio/Sink"}}},
+  {"id": 62, "type": "PipeWire:Interface:Node",
+   "info": {"props": {"node.name": "bluez_input.headset", "media.class": "Audio/Source"}}}
+]"#;
+
+/// `wpctl`/`pw-metadata` stand-in logging its arguments; the headset
+/// refuses volume changes.
+fn fake_tool(dir: &Path, name: &str) -> String {
+    let log = dir.join("commands.log");
+    script(
+        dir,
+        name,
+        &format!(
+            "echo \"{name} $*\" >> {}\ncase \"$*\" in \"set-volume 61 \"*) echo 'volume rejected' >&2; exit 1;; esac\n",
+            log.display()
+        ),
+    )
+}
+
+fn take_log(dir: &Path) -> Vec<String> {
+    let log = dir.join("commands.log");
+    let lines = fs::read_to_string(&log)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let _ = fs::remove_file(log);
+    lines
+}
+
+async fn switch(app: &Router, body: Value) -> (StatusCode, Value) {
+    call(app, post_json("/audio/profile", &body)).await
+}
+
+#[tokio::test]
+async fn profiles_switch_as_a_whole_or_roll_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+    fs::write(path.join("profiles.yaml"), PROFILES).unwrap();
+    fs::write(path.join("dump.json"), DUMP).unwrap();
+    std::env::set_var("HAUSKI_AUDIO_PROFILES", path.join("profiles.yaml"));
+    std::env::set_var(
+        "HAUSKI_PW_DUMP_BIN",
+        script(
+            path,
+            "pw-dump",
+            &format!("cat {}\n", path.join("dump.json").display()),
+        ),
+    );
+    std::env::set_var("HAUSKI_WPCTL_BIN", fake_tool(path, "wpctl"));
+    std::env::set_var("HAUSKI_PW_METADATA_BIN", fake_tool(path, "pw-metadata"));
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let (status, profiles) = call(
+        &app,
+        Request::get("/audio/profiles").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = profiles["profiles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|profile| profile["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["asr", "broken", "call", "music"]);
+
+    let (status, current) = call(
+        &app,
+        Request::get("/audio/profile").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(current["applied"], json!(["music"]));
+    assert_eq!(current["default_source"], "alsa_input.usb-mic");
+
+    let (status, planned) = switch(&app, json!({"profile": "call", "dry_run": true})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(planned["commands"].as_array().unwrap().len(), 4);
+    assert!(take_log(path).is_empty());
+
+    let (status, applied) = switch(&app, json!({"profile": "asr"})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        take_log(path),
+        [
+            "wpctl set-default 53",
+            "wpctl set-volume 53 1.00",
+            "wpctl set-mute 53 0"
+        ]
+    );
+    assert_eq!(applied["commands"].as_array().unwrap().len(), 3);
+
+    // The headset refuses its volume: the defaults set before are undone.
+    let (status, error) = switch(&app, json!({"profile": "call"})).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .contains("previous routing restored"));
+    assert_eq!(
+        take_log(path),
+        [
+            "wpctl set-default 61",
+            "wpctl set-default 62",
+            "wpctl set-volume 61 0.60",
+            "wpctl set-default 51",
+            "wpctl set-default 53",
+            "wpctl set-volume 51 0.50",
+            "wpctl set-volume 53 1.00",
+            "wpctl set-mute 53 1",
+            "pw-metadata -n settings 0 clock.force-quantum 0",
+            "pw-metadata -n settings 0 clock.force-rate 0",
+        ]
+    );
+
+    let (status, _) = switch(&app, json!({"profile": "broken"})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = switch(&app, json!({"profile": "studio"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(take_log(path).is_empty());
+
+    let (_, current) = call(
+        &app,
+        Request::get("/audio/profile").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(current["last_switched"], "asr");
+
+    let res = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let metrics = res.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8_lossy(&metrics);
+    assert!(
+        metrics.contains(r#"audio_profile_switches_total{profile="call",outcome="rolled_back"} 1"#),
+        "{metrics}"
+    );
+}
@@ -0,0 +1,206 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{collections::BTreeMap, fs, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, StatusCode},
+    Router,
+};
+use hauski_backup::{BackupDir, Bundle, INDEX_ENTRY};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::post_json;
+
+const TOKEN: &str = "backup-admin";
+
+async fn call(app: &Router, mut request: Request<Body>) -> (StatusCode, Value) {
+    request.headers_mut().insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {TOKEN}")).unwrap(),
+    );
+    common::call(app, request).await
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    call(app, Request::get(uri).body(Body::empty()).unwrap()).await
+}
+
+async fn post(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    call(app, post_json(uri, &body)).await
+}
+
+async fn upsert(app: &Router, doc_id: &str) {
+    let (status, _) = post(
+        app,
+        "/index/upsert",
+        json!({
+            "doc_id": doc_id,
+            "chunks": [{ "chunk_id": format!("{doc_id}#0"), "text": "Hallo Welt", "embedding": [] }],
+            "source_ref": { "origin": "test", "id": doc_id, "trust_level": "high" },
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+async fn documents(app: &Router) -> u64 {
+    let (_, stats) = get(app, "/index/stats").await;
+    stats["total_documents"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn backups_round_trip_and_reject_damaged_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+    let flags = path.join("flags.yaml");
+    fs::write(&flags, "safe_mode: false\n").unwrap();
+    std::env::set_var("XDG_STATE_HOME", path.join("state"));
+    std::env::set_var("HAUSKI_BACKUP_DIR", path.join("backups"));
+    std::env::set_var("HAUSKI_BACKUP_PASSPHRASE", "correct horse");
+    std::env::set_var("HAUSKI_FLAGS", &flags);
+    let app = |api_token: Option<&str>| {
+        let flags = FeatureFlags {
+            api_token: api_token.map(str::to_string),
+            ..FeatureFlags::default()
+        };
+        let (app, _state) = build_app_with_state(
+            Limits::default(),
+            ModelsFile::default(),
+            RoutingPolicy::default(),
+            flags,
+            false,
+            HeaderValue::from_static("*"),
+        );
+        app
+    };
+    let open = app(None);
+    let app = app(Some(TOKEN));
+
+    upsert(&app, "doc-1").await;
+    let (status, created) = post(&app, "/backup", Value::Null).await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    let name = created["backup"]["name"].as_str().unwrap().to_string();
+    assert!(name.ends_with(".tar.zst.enc"), "{name}");
+    assert_eq!(created["backup"]["encrypted"], true);
+    assert_eq!(created["manifest"]["counts"]["documents"], 1);
+    assert_eq!(created["manifest"]["counts"]["configs"], 1);
+
+    let (status, listed) = get(&app, "/backup").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed["encrypt"], true);
+    assert_eq!(listed["backups"].as_array().unwrap().len(), 1);
+
+    let (status, verified) = get(&app, &format!("/backup/{name}")).await;
+    assert_eq!(status, StatusCode::OK, "{verified}");
+    assert_eq!(verified["verified"], true);
+
+    // Forget the document and change the flags; the backup brings both back.
+    let (status, _) = post(
+        &app,
+        "/index/forget",
+        json!({ "filter": { "doc_id": "doc-1" }, "reason": "test", "confirm": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(documents(&app).await, 0);
+    fs::write(&flags, "safe_mode: true\n").unwrap();
+
+    let restore = format!("/backup/{name}/restore");
+    let (status, dry) = post(&app, &restore, json!({ "dry_run": true })).await;
+    assert_eq!(status, StatusCode::OK, "{dry}");
+    assert_eq!(dry["restored"]["documents"], 1);
+    assert_eq!(documents(&app).await, 0);
+
+    // Restore may rewrite routing.yaml and thus the egress allowlist.
+    let (status, _) = post(&open, &restore, json!({ "parts": ["configs"] })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = post(&open, &restore, json!({ "dry_run": true })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, restored) = post(&app, &restore, json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{restored}");
+    assert_eq!(restored["restored"]["documents"], 1);
+    assert_eq!(documents(&app).await, 1);
+    assert_eq!(fs::read_to_string(&flags).unwrap(), "safe_mode: true\n");
+
+    let (status, restored) = post(&app, &restore, json!({ "parts": ["configs"] })).await;
+    assert_eq!(status, StatusCode::OK, "{restored}");
+    assert_eq!(restored["restored"]["configs"], 1);
+    assert_eq!(fs::read_to_string(&flags).unwrap(), "safe_mode: false\n");
+    assert_eq!(
+        fs::read_dir(path).unwrap().count(),
+        3,
+        "no staged files left"
+    );
+
+    // A backup whose contents disagree with its manifest changes nothing,
+    // not even the parts checked before the mismatch.
+    let mut bundle = Bundle::default();
+    let (_, snapshot) = get(&app, "/index/snapshot").await;
+    bundle.insert(INDEX_ENTRY, serde_json::to_vec(&snapshot).unwrap());
+    bundle.insert("configs/flags.yaml", b"safe_mode: true\n".to_vec());
+    let counts = BTreeMap::from([("documents".to_string(), 5)]);
+    let (lying, _) = BackupDir::new(path.join("backups"))
+        .write(&bundle, "0.1.0", counts, Some("correct horse"))
+        .unwrap();
+    let (status, _) = post(
+        &app,
+        &format!("/backup/{}/restore", lying.name),
+        json!({ "parts": ["configs", "index"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fs::read_to_string(&flags).unwrap(), "safe_mode: false\n");
+    fs::remove_file(path.join("backups").join(&lying.name)).unwrap();
+
+    let (status, _) = post(&app, &restore, json!({ "parts": ["everything"] })).await;
+    assert!(status.is_client_error());
+    let (status, _) = get(&app, "/backup/..%2Fetc%2Fpasswd").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get(&app, "/backup/hauski-20240101T000000000Z.tar.zst").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Flip a byte in the ciphertext: verification and restore must refuse it.
+    let archive = path.join("backups").join(&name);
+    let mut bytes = fs::read(&archive).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&archive, bytes).unwrap();
+    let (status, _) = get(&app, &format!("/backup/{name}")).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = post(&app, &restore, json!({})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(documents(&app).await, 1);
+
+    let expected = [
+        r#"backup_runs_total{operation="create",outcome="ok"} 1"#,
+        r#"backup_runs_total{operation="restore",outcome="ok"} 3"#,
+        r#"backup_runs_total{operation="restore",outcome="failed"} 2"#,
+    ];
+    for _ in 0..50 {
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/metrics")
+                    .header(header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        if expected.iter().all(|line| metrics.contains(line)) {
+            assert!(metrics.contains("backup_last_success_timestamp_seconds"));
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("backup metrics missing");
+}
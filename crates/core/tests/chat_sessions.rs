@@ -0,0 +1,181 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{
+    body::Body,
+    http::{self, HeaderValue, Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// Ollama stand-in: answers with the number of messages it received.
+async fn spawn_upstream() -> String {
+    let router = Router::new().route(
+        "/api/chat",
+        post(|Json(body): Json<Value>| async move {
+            let count = body["messages"].as_array().map_or(0, Vec::len);
+            if body["stream"] == true {
+                let lines = [
+                    json!({"message": {"role": "assistant", "content": "Hal"}, "done": false}),
+                    json!({"message": {"role": "assistant", "content": format!("lo {count}\n")}, "done": false}),
+                    json!({"done": true, "prompt_eval_count": 7, "eval_count": 3}),
+                ];
+                let body: String = lines.iter().map(|line| format!("{line}\n")).collect();
+                return body;
+            }
+            json!({"message": {"role": "assistant", "content": format!("{count} Nachrichten")}})
+                .to_string()
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn chat_app() -> Router {
+    chat_app_with(RoutingPolicy::default()).await
+}
+
+async fn chat_app_with(routing: RoutingPolicy) -> Router {
+    for key in [
+        "HAUSKI_CHAT_UPSTREAM_URL",
+        "CHAT_UPSTREAM_URL",
+        "HAUSKI_CHAT_MODEL",
+    ] {
+        std::env::remove_var(key);
+    }
+    let flags = FeatureFlags {
+        chat_upstream_url: Some(spawn_upstream().await),
+        chat_model: Some("mock-model".to_string()),
+        ..FeatureFlags::default()
+    };
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        routing,
+        flags,
+        false,
+        HeaderValue::from_static("*"),
+    );
+    app
+}
+
+fn session_id() -> String {
+    format!(
+        "chat-test-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time went backwards")
+            .as_nanos()
+    )
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = app.clone().oneshot(request).await.expect("request failed");
+    let status = response.status();
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body bytes")
+        .to_bytes();
+    (status, String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn post_json(path: &str, payload: &Value) -> Request<Body> {
+    Request::post(path)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn chat_session_prepends_history() {
+    let app = chat_app().await;
+    let id = session_id();
+    let turn = |content: &str| json!({"session_id": id, "messages": [{"role": "user", "content": content}]});
+
+    let (status, body) = send(&app, post_json("/v1/chat", &turn("Hallo"))).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let first: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(first["content"], "1 Nachrichten");
+    assert_eq!(first["session_id"], id.as_str());
+
+    let (_, body) = send(&app, post_json("/v1/chat", &turn("Und?"))).await;
+    let second: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(second["content"], "3 Nachrichten");
+
+    let path = format!("/v1/chat/sessions/{id}");
+    let (status, body) = send(&app, Request::get(&path).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let session: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(session["messages"].as_array().unwrap().len(), 4);
+    assert_eq!(session["messages"][3]["role"], "assistant");
+
+    let (status, _) = send(&app, Request::delete(&path).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, Request::get(&path).body(Body::empty()).unwrap()).await;
+    let session: Value = serde_json::from_str(&body).unwrap();
+    assert!(session["messages"].as_array().unwrap().is_empty());
+
+    let (status, _) = send(
+        &app,
+        Request::get("/v1/chat/sessions/a%2Fb")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn chat_stream_emits_deltas_and_stores_turn() {
+    let app = chat_app().await;
+    let id = session_id();
+    let payload = json!({"session_id": id, "messages": [{"role": "user", "content": "Hallo"}]});
+
+    let (status, body) = send(&app, post_json("/v1/chat/stream", &payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("event: delta"), "{body}");
+    assert!(body.contains(r#"data: {"content":"Hal"}"#), "{body}");
+    assert!(body.contains("event: done"), "{body}");
+    assert!(body.contains(r#""completion_tokens":3"#), "{body}");
+
+    let path = format!("/v1/chat/sessions/{id}");
+    let (_, body) = send(&app, Request::get(&path).body(Body::empty()).unwrap()).await;
+    let session: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(session["messages"][1]["content"], "Hallo 1\n");
+}
+
+#[tokio::test]
+async fn chat_rejects_unknown_model() {
+    let app = chat_app().await;
+    let payload = json!({"model": "nope", "messages": [{"role": "user", "content": "Hallo"}]});
+    let (status, body) = send(&app, post_json("/v1/chat", &payload)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("unknown_model"), "{body}");
+}
+
+#[tokio::test]
+async fn chat_upstream_goes_through_the_egress_allowlist() {
+    let routing: RoutingPolicy =
+        serde_yaml_ng::from_str("egress:\n  default: deny\n  allow: []\n").unwrap();
+    let app = chat_app_with(routing).await;
+    let payload = json!({"messages": [{"role": "user", "content": "Hallo"}]});
+
+    let (status, body) = send(&app, post_json("/v1/chat", &payload)).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY, "{body}");
+    let (_, metrics) = send(&app, Request::get("/metrics").body(Body::empty()).unwrap()).await;
+    assert!(
+        metrics
+            .lines()
+            .any(|line| line.starts_with("egress_attempts_total")
+                && line.contains(r#"caller="chat""#)
+                && line.contains(r#"decision="denied""#)),
+        "{metrics}"
+    );
+}
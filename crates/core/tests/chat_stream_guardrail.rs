@@ -0,0 +1,82 @@
+use axum::{
+    body::Body,
+    http::{self, HeaderValue, Request, StatusCode},
+    routing::post,
+    Router,
+};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::json;
+use tower::ServiceExt;
+
+/// Streamed answer with a PEM block split across lines and pieces.
+const PIECES: &[&str] = &[
+    "Hier ist der Schlüssel:\n-----BEGIN RSA PRI",
+    "VATE KEY-----\nMIIEowIBAAKCAQEA",
+    "secretline\nc2VjcmV0a2V5\n",
+    "-----END RSA PRIVATE KEY-----\nViel Erfolg!\n",
+];
+
+async fn spawn_upstream() -> String {
+    let router = Router::new().route(
+        "/api/chat",
+        post(|| async {
+            let mut body: String = PIECES
+                .iter()
+                .map(|piece| {
+                    let line =
+                        json!({"message": {"role": "assistant", "content": piece}, "done": false});
+                    format!("{line}\n")
+                })
+                .collect();
+            body.push_str(&format!("{}\n", json!({"done": true})));
+            body
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn streamed_private_keys_are_redacted_across_lines() {
+    std::env::set_var(
+        "HAUSKI_GUARDRAIL_POLICY_PATH",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../../policies/guardrail.yaml"),
+    );
+    let flags = FeatureFlags {
+        chat_upstream_url: Some(spawn_upstream().await),
+        chat_model: Some("mock-model".to_string()),
+        ..FeatureFlags::default()
+    };
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        flags,
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let payload = json!({"messages": [{"role": "user", "content": "Zeig mir den Schlüssel"}]});
+    let response = app
+        .oneshot(
+            Request::post("/v1/chat/stream")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8_lossy(&body);
+
+    assert!(body.contains("[REDACTED:private_key]"), "{body}");
+    assert!(body.contains("Viel Erfolg!"), "{body}");
+    assert!(body.contains("event: done"), "{body}");
+    for secret in ["PRIVATE KEY", "MIIEow", "secretline", "c2VjcmV0a2V5"] {
+        assert!(!body.contains(secret), "{secret} leaked: {body}");
+    }
+}
@@ -0,0 +1,37 @@
+//! Gemeinsame Helfer für die Integrationstests von hauski-core.
+#![allow(dead_code)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// Sends `request` to `app` and decodes the JSON body (`Null` if there is none).
+pub async fn call(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let res = app.clone().oneshot(request).await.unwrap();
+    let status = res.status();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// JSON `POST` request for `uri`.
+pub fn post_json(uri: &str, body: &Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Writes an executable shell script `name` into `dir` and returns its path.
+pub fn script(dir: &Path, name: &str, body: &str) -> String {
+    let bin = dir.join(name);
+    fs::write(&bin, format!("#!/bin/sh\n{body}")).unwrap();
+    fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+    bin.display().to_string()
+}
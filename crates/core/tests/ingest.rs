@@ -0,0 +1,181 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, StatusCode},
+    Router,
+};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{call, post_json};
+
+const BOUNDARY: &str = "hauski-ingest-test";
+
+const MANUAL: &str = r#"<!DOCTYPE html>
+<html><head><title>Heizungshandbuch</title></head>
+<body>
+  <h1>Heizung</h1>
+  <p>Im Herbst alle Heizk&ouml;rper entl&uuml;ften.</p>
+  <h2>Ventile</h2>
+  <p>Thermostatventile klemmen nach dem Sommer gerne.</p>
+</body></html>"#;
+
+async fn post(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    call(app, post_json(uri, &body)).await
+}
+
+/// Uploads `content` as `file` named `name`, plus text `fields`.
+async fn upload(
+    app: &Router,
+    name: &str,
+    content: &str,
+    fields: &[(&str, &str)],
+) -> (StatusCode, Value) {
+    let mut body = String::new();
+    for (field, value) in fields {
+        body.push_str(&format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{field}\"\r\n\r\n{value}\r\n"
+        ));
+    }
+    body.push_str(&format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n{content}\r\n--{BOUNDARY}--\r\n"
+    ));
+    call(
+        app,
+        Request::post("/ingest/file")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap(),
+    )
+    .await
+}
+
+async fn finished_job(app: &Router, id: &str) -> Value {
+    for _ in 0..200 {
+        let (status, record) = call(
+            app,
+            Request::get(format!("/jobs/{id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        if ["succeeded", "failed", "cancelled"].contains(&record["status"].as_str().unwrap()) {
+            return record;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("job {id} did not finish");
+}
+
+#[tokio::test]
+async fn documents_are_extracted_chunked_and_indexed() {
+    let dir = tempfile::tempdir().unwrap();
+    let inbox = dir.path().join("inbox");
+    fs::create_dir(&inbox).unwrap();
+    fs::write(inbox.join("handbuch.html"), MANUAL).unwrap();
+    fs::write(dir.path().join("geheim.html"), MANUAL).unwrap();
+    std::env::set_var("XDG_STATE_HOME", dir.path().join("state"));
+    std::env::set_var("HAUSKI_INGEST_INPUT_DIRS", &inbox);
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let (status, preview) = upload(
+        &app,
+        "handbuch.html",
+        MANUAL,
+        &[("dry_run", "true"), ("chunk_chars", "100")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{preview}");
+    assert_eq!(preview["format"], "html");
+    assert_eq!(preview["title"], "Heizungshandbuch");
+    assert_eq!(preview["namespace"], "docs");
+    assert_eq!(preview["sections"], 2);
+    assert!(preview.get("job").is_none());
+    let chunks = preview["preview"].as_array().unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[1]["headings"], json!(["Heizung", "Ventile"]));
+    assert_eq!(chunks[1]["start"], 0);
+    let doc_id = preview["doc_id"].as_str().unwrap().to_string();
+    assert!(doc_id.starts_with("ingest-"), "{doc_id}");
+
+    let (status, queued) = upload(&app, "handbuch.html", MANUAL, &[]).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{queued}");
+    assert_eq!(queued["doc_id"], doc_id.as_str());
+    assert_eq!(queued["job"]["kind"], "ingest");
+    let job = finished_job(&app, queued["job"]["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["result"]["ingested"], 1, "{job}");
+
+    let (status, found) = post(
+        &app,
+        "/index/search",
+        json!({ "query": "Thermostatventile", "namespace": "docs", "k": 5 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{found}");
+    let hit = &found["matches"][0];
+    assert_eq!(hit["doc_id"], doc_id.as_str());
+    assert_eq!(hit["meta"]["headings"], json!(["Heizung", "Ventile"]));
+    assert_eq!(hit["source_ref"]["origin"], "ingest");
+    assert_eq!(hit["source_ref"]["id"], "handbuch.html");
+    assert_eq!(hit["source_ref"]["trust_level"], "medium");
+
+    let path = inbox.join("handbuch.html");
+    let (status, by_path) = post(
+        &app,
+        "/ingest/file",
+        json!({ "path": path, "doc_id": "handbuch", "dry_run": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{by_path}");
+    assert_eq!(by_path["doc_id"], "handbuch");
+    let (status, _) = post(
+        &app,
+        "/ingest/file",
+        json!({ "path": dir.path().join("geheim.html") }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, unsupported) = upload(&app, "notizen.md", "# Notizen", &[]).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE, "{unsupported}");
+    let (status, _) = upload(&app, "leer.html", "<html><body></body></html>", &[]).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = upload(&app, "handbuch.html", MANUAL, &[("chunk_chars", "5")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let res = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+    for line in [
+        r#"ingest_files_total{format="html",outcome="queued"} 1"#,
+        r#"ingest_files_total{format="html",outcome="dry_run"} 2"#,
+        r#"ingest_files_total{format="unknown",outcome="rejected"} 1"#,
+        r#"ingest_files_total{format="html",outcome="rejected"} 1"#,
+    ] {
+        assert!(metrics.contains(line), "missing {line}");
+    }
+}
@@ -9,14 +9,29 @@ use http_body_util::BodyExt;
 use serde_json::{json, Value};
 use tower::ServiceExt;
 
+const TOKEN: &str = "memory-secret";
+
+fn memory_app(token: Option<&str>) -> axum::Router {
+    let flags = FeatureFlags {
+        memory_api: true,
+        memory_token: token.map(str::to_string),
+        ..FeatureFlags::default()
+    };
+    let allowed_origin = HeaderValue::from_static("*");
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        flags,
+        false,
+        allowed_origin,
+    );
+    app
+}
+
 #[tokio::test]
 async fn memory_routes_available_without_expose_config() {
-    let limits = Limits::default();
-    let models = ModelsFile::default();
-    let routing = RoutingPolicy::default();
-    let flags = FeatureFlags::default();
-    let allowed_origin = HeaderValue::from_static("*");
-    let (app, _state) = build_app_with_state(limits, models, routing, flags, false, allowed_origin);
+    let app = memory_app(Some(TOKEN));
 
     let key = format!(
         "memory-test-{}",
@@ -32,6 +47,7 @@ async fn memory_routes_available_without_expose_config() {
         .oneshot(
             Request::post("/memory/set")
                 .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
                 .body(Body::from(set_payload.to_string()))
                 .expect("failed to build request"),
         )
@@ -46,6 +62,7 @@ async fn memory_routes_available_without_expose_config() {
         .oneshot(
             Request::post("/memory/get")
                 .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
                 .body(Body::from(get_payload.to_string()))
                 .expect("failed to build request"),
         )
@@ -66,12 +83,7 @@ async fn memory_routes_available_without_expose_config() {
 
 #[tokio::test]
 async fn memory_set_rejects_conflicting_ttl_requests() {
-    let limits = Limits::default();
-    let models = ModelsFile::default();
-    let routing = RoutingPolicy::default();
-    let flags = FeatureFlags::default();
-    let allowed_origin = HeaderValue::from_static("*");
-    let (app, _state) = build_app_with_state(limits, models, routing, flags, false, allowed_origin);
+    let app = memory_app(Some(TOKEN));
 
     let payload = json!({
         "key": "conflict-ttl",
@@ -84,6 +96,7 @@ async fn memory_set_rejects_conflicting_ttl_requests() {
         .oneshot(
             Request::post("/memory/set")
                 .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
                 .body(Body::from(payload.to_string()))
                 .expect("failed to build request"),
         )
@@ -104,3 +117,194 @@ async fn memory_set_rejects_conflicting_ttl_requests() {
         "clear_ttl cannot be used together with ttl_sec"
     );
 }
+
+#[tokio::test]
+async fn memory_list_paginates_within_namespace() {
+    let app = memory_app(Some(TOKEN));
+
+    let namespace = format!(
+        "list-test-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time went backwards")
+            .as_nanos()
+    );
+    for name in ["a", "b", "c"] {
+        let set_payload = json!({ "key": format!("{namespace}:{name}"), "value": name });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/memory/set")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                    .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                    .body(Body::from(set_payload.to_string()))
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("set request failed");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let list = |payload: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::post("/memory/list")
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                        .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                        .body(Body::from(payload.to_string()))
+                        .expect("failed to build request"),
+                )
+                .await
+                .expect("list request failed");
+            let status = response.status();
+            let body_bytes = response
+                .into_body()
+                .collect()
+                .await
+                .expect("body bytes")
+                .to_bytes();
+            let payload: Value = serde_json::from_slice(&body_bytes).expect("response json");
+            (status, payload)
+        }
+    };
+
+    let (status, first) = list(json!({ "namespace": namespace, "limit": 2 })).await;
+    assert_eq!(status, StatusCode::OK);
+    let items = first["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["key"], format!("{namespace}:a"));
+    assert_eq!(items[0]["value"], "a");
+    assert_eq!(first["next_cursor"], format!("{namespace}:b"));
+
+    let (status, second) = list(json!({
+        "namespace": namespace,
+        "limit": 2,
+        "cursor": first["next_cursor"],
+        "include_values": false
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let items = second["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["key"], format!("{namespace}:c"));
+    assert!(items[0].get("value").is_none());
+    assert!(second["next_cursor"].is_null());
+
+    let (status, _) = list(json!({ "namespace": namespace, "limit": 0 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(
+            Request::post("/memory/stats")
+                .header(http::header::AUTHORIZATION, format!("Bearer {TOKEN}"))
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("stats request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body bytes")
+        .to_bytes();
+    let stats: Value = serde_json::from_slice(&body_bytes).expect("response json");
+    assert!(stats["total"].as_u64().expect("total") >= 3);
+    assert_eq!(
+        stats["total"].as_u64(),
+        Some(stats["pinned"].as_u64().unwrap() + stats["unpinned"].as_u64().unwrap())
+    );
+}
+
+async fn post_status(app: axum::Router, path: &str, auth: Option<&str>) -> StatusCode {
+    let mut request = Request::post(path).header(http::header::CONTENT_TYPE, "application/json");
+    if let Some(auth) = auth {
+        request = request.header(http::header::AUTHORIZATION, auth);
+    }
+    app.oneshot(
+        request
+            .body(Body::from(json!({ "key": "auth-probe" }).to_string()))
+            .expect("failed to build request"),
+    )
+    .await
+    .expect("request failed")
+    .status()
+}
+
+#[tokio::test]
+async fn memory_routes_require_flag_and_token() {
+    let (disabled, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        true,
+        HeaderValue::from_static("*"),
+    );
+    assert_eq!(
+        post_status(disabled, "/memory/get", Some("Bearer x")).await,
+        StatusCode::NOT_FOUND,
+        "expose_config alone must not mount the memory API"
+    );
+
+    assert_eq!(
+        post_status(memory_app(None), "/memory/get", Some("Bearer x")).await,
+        StatusCode::FORBIDDEN
+    );
+
+    let app = memory_app(Some(TOKEN));
+    assert_eq!(
+        post_status(app.clone(), "/memory/get", None).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_status(app.clone(), "/memory/evict", Some("Bearer wrong")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_status(app, "/memory/get", Some(&format!("Bearer {TOKEN}"))).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn memory_routes_accept_api_token() {
+    let flags = FeatureFlags {
+        memory_api: true,
+        memory_token: Some(TOKEN.to_string()),
+        api_token: Some("api-secret".to_string()),
+        ..FeatureFlags::default()
+    };
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        flags,
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    for (token, expected) in [
+        ("api-secret", StatusCode::OK),
+        (TOKEN, StatusCode::OK),
+        ("wrong", StatusCode::UNAUTHORIZED),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/memory/get")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .header(http::header::AUTHORIZATION, format!("Bearer {token}"))
+                    .body(Body::from(json!({ "key": "api-token" }).to_string()))
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("get request failed");
+        assert_eq!(response.status(), expected, "token {token}");
+    }
+}
@@ -0,0 +1,185 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request, StatusCode},
+    Router,
+};
+use chrono::{Local, TimeDelta};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{call, post_json, script};
+
+/// `notify-send` stand-in logging its arguments, one call per line.
+fn fake_notify_send(dir: &Path) -> String {
+    let log = dir.join("notify.log");
+    script(
+        dir,
+        "notify-send",
+        &format!("echo \"$*\" >> {}\n", log.display()),
+    )
+}
+
+/// Waits for the background deliveries to write `count` lines.
+async fn wait_for_log(dir: &Path, count: usize) -> Vec<String> {
+    for _ in 0..100 {
+        let lines: Vec<String> = fs::read_to_string(dir.join("notify.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if lines.len() >= count {
+            return lines;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("expected {count} notify-send calls");
+}
+
+async fn notify(app: &Router, body: Value) -> (StatusCode, Value) {
+    call(app, post_json("/notify", &body)).await
+}
+
+fn decisions(body: &Value) -> Vec<(String, String)> {
+    body["channels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|routed| {
+            (
+                routed["channel"].as_str().unwrap().to_string(),
+                routed["decision"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn notifications_respect_quiet_hours_and_rate_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+    // Quiet window around the current local time.
+    let now = Local::now().time();
+    let quiet_start = (now - TimeDelta::hours(1)).format("%H:%M");
+    let quiet_end = (now + TimeDelta::hours(1)).format("%H:%M");
+    fs::write(
+        path.join("notifications.yaml"),
+        format!(
+            r#"
+channels:
+  - id: desk
+    kind: desktop
+    events: [manual, "job.*"]
+    rate_limit: {{ max: 1, per_sec: 3600 }}
+    template:
+      title: "[{{{{priority}}}}] {{{{title}}}}"
+  - id: night
+    kind: desktop
+    events: [manual]
+    quiet_hours: {{ start: "{quiet_start}", end: "{quiet_end}" }}
+  - id: alerts
+    kind: desktop
+    events: ["system.*"]
+"#
+        ),
+    )
+    .unwrap();
+    std::env::set_var("HAUSKI_NOTIFICATIONS", path.join("notifications.yaml"));
+    std::env::set_var("HAUSKI_NOTIFY_SEND_BIN", fake_notify_send(path));
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let (status, channels) = call(
+        &app,
+        Request::get("/notify/channels")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let quiet: Vec<(&str, bool)> = channels["channels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["id"].as_str().unwrap(), c["quiet_now"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(
+        quiet,
+        vec![("desk", false), ("night", true), ("alerts", false)]
+    );
+
+    let (status, body) = notify(&app, json!({ "title": "Backup fertig", "body": "3 GiB" })).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(
+        decisions(&body),
+        vec![
+            ("desk".to_string(), "send".to_string()),
+            ("night".to_string(), "quiet".to_string())
+        ]
+    );
+    let log = wait_for_log(path, 1).await;
+    assert_eq!(
+        log,
+        vec!["--app-name HausKI --urgency normal [normal] Backup fertig 3 GiB"]
+    );
+
+    // The hourly slot of `desk` is used up; urgent messages pass quiet hours.
+    let (_, body) = notify(
+        &app,
+        json!({ "title": "Platte voll", "priority": "urgent" }),
+    )
+    .await;
+    assert_eq!(
+        decisions(&body),
+        vec![
+            ("desk".to_string(), "rate_limited".to_string()),
+            ("night".to_string(), "send".to_string())
+        ]
+    );
+    let log = wait_for_log(path, 2).await;
+    assert_eq!(log[1], "--app-name HausKI --urgency critical Platte voll ");
+
+    let (_, body) = notify(&app, json!({ "event": "other", "title": "niemand" })).await;
+    assert!(decisions(&body).is_empty());
+
+    let (status, _) = notify(&app, json!({ "title": "  " })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = notify(&app, json!({ "title": "x", "color": "red" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Deliveries count once `notify-send` returned.
+    let expected = [
+        r#"notifications_total{channel="desk",outcome="sent"} 1"#,
+        r#"notifications_total{channel="desk",outcome="rate_limited"} 1"#,
+        r#"notifications_total{channel="night",outcome="quiet"} 1"#,
+        r#"notifications_total{channel="night",outcome="sent"} 1"#,
+    ];
+    let mut metrics = String::new();
+    for _ in 0..100 {
+        let res = app
+            .clone()
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        metrics = String::from_utf8_lossy(&body).into_owned();
+        if expected.iter().all(|line| metrics.contains(line)) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("notification metrics missing in\n{metrics}");
+}
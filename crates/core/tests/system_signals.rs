@@ -14,14 +14,22 @@ async fn system_signals_returns_expected_keys() {
         latency: hauski_core::Latency {
             llm_p95_ms: 400,
             index_topk20_ms: 60,
+            asr_p95_ms: 900,
         },
         thermal: hauski_core::Thermal {
             gpu_max_c: 80,
             dgpu_power_w: 220,
+            ..Default::default()
         },
         asr: hauski_core::Asr { wer_max_pct: 10 },
+        disk: Default::default(),
+        pressure: Default::default(),
+        power: Default::default(),
+        monitor: Default::default(),
+        shedding: Default::default(),
+        namespaces: Default::default(),
     };
-    let models = ModelsFile { models: vec![] };
+    let models = ModelsFile::default();
     let routing = RoutingPolicy::default();
     let flags = FeatureFlags::default();
     let origin = HeaderValue::from_static("http://localhost");
@@ -55,6 +63,12 @@ async fn system_signals_returns_expected_keys() {
         "Memory pressure out of range"
     );
 
+    assert!(
+        (0.0..=100.0).contains(&signals.raw.cpu_load)
+            && (0.0..=100.0).contains(&signals.raw.memory_pressure),
+        "raw sample out of range"
+    );
+
     // Validate that values are finite (not NaN or Inf)
     assert!(signals.cpu_load.is_finite(), "CPU load is not finite");
     assert!(
@@ -91,3 +105,35 @@ async fn system_signals_returns_expected_keys() {
     // We cannot assert true/false for GPU as it depends on the runner environment,
     // but the field must exist (which is guaranteed by type safety here).
 }
+
+#[tokio::test]
+async fn system_signals_history_starts_with_the_initial_sample() {
+    let origin = HeaderValue::from_static("http://localhost");
+    let (app, _state) = hauski_core::build_app_with_state(
+        Limits::default(),
+        ModelsFile::default(),
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        origin,
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/system/signals/history?step_sec=3600")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let history: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(history["resolution_sec"], 30);
+    assert_eq!(history["step_sec"], 3600);
+    let points = history["points"].as_array().unwrap();
+    assert_eq!(points.len(), 1);
+    assert!(points[0]["cpu_load"].is_number());
+}
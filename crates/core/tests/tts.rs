@@ -0,0 +1,126 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, StatusCode},
+    Router,
+};
+use hauski_core::{build_app_with_state, FeatureFlags, Limits, ModelsFile, RoutingPolicy};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::script;
+
+/// `piper` stand-in: swallows the text and answers with four raw bytes;
+/// voices whose model path contains `broken` fail.
+fn fake_piper(dir: &Path) -> String {
+    let body = "case \"$2\" in *broken*) echo 'voice could not be loaded' >&2; exit 1;; esac\ncat > /dev/null\nprintf 'abcd'\n";
+    script(dir, "piper", body)
+}
+
+fn voice(dir: &Path, name: &str) -> String {
+    let model = dir.join(format!("{name}.onnx"));
+    fs::write(&model, b"onnx").unwrap();
+    fs::write(
+        dir.join(format!("{name}.onnx.json")),
+        r#"{"audio":{"sample_rate":22050}}"#,
+    )
+    .unwrap();
+    model.display().to_string()
+}
+
+async fn post(app: &Router, body: Value) -> (StatusCode, Option<String>, Vec<u8>) {
+    let res = app
+        .clone()
+        .oneshot(
+            Request::post("/tts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = res.status();
+    let content_type = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = res.into_body().collect().await.unwrap().to_bytes().to_vec();
+    (status, content_type, body)
+}
+
+#[tokio::test]
+async fn tts_streams_wav_from_the_configured_voice() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("HAUSKI_PIPER_BIN", fake_piper(dir.path()));
+    let models: ModelsFile = serde_yaml_ng::from_str(&format!(
+        "models: []\nvoices:\n  - id: eva\n    path: {}\n    language: de\n    default: true\n  - id: broken\n    path: {}\n  - id: gone\n    path: /nonexistent/gone.onnx\n",
+        voice(dir.path(), "eva"),
+        voice(dir.path(), "broken"),
+    ))
+    .unwrap();
+    let (app, _state) = build_app_with_state(
+        Limits::default(),
+        models,
+        RoutingPolicy::default(),
+        FeatureFlags::default(),
+        false,
+        HeaderValue::from_static("*"),
+    );
+
+    let res = app
+        .clone()
+        .oneshot(Request::get("/tts/voices").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let voices: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        voices["voices"][0],
+        json!({"id": "eva", "language": "de", "available": true, "default": true})
+    );
+    assert_eq!(voices["voices"][2]["available"], false);
+
+    let (status, content_type, body) = post(&app, json!({"text": "Hallo Welt."})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("audio/wav"));
+    assert_eq!(&body[..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(body[24..28].try_into().unwrap()), 22_050);
+    assert_eq!(&body[44..], b"abcd");
+
+    let (status, _, body) = post(&app, json!({"text": "Hallo", "voice": "broken"})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .contains("voice could not be loaded"));
+
+    for (request, expected) in [
+        (json!({"text": "  "}), StatusCode::BAD_REQUEST),
+        (
+            json!({"text": "Hallo", "voice": "karl"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"text": "Hallo", "speed": 9.0}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"text": "Hallo", "voice": "gone"}),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ),
+        (
+            json!({"text": "x".repeat(5_001)}),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+    ] {
+        let (status, _, _) = post(&app, request.clone()).await;
+        assert_eq!(status, expected, "{request}");
+    }
+}
@@ -0,0 +1,100 @@
+//! Begrenzter In-Memory-Cache für Embeddings, Schlüssel ist der Eingabetext.
+//!
+//! Wiederholte Texte (Suchanfragen, unveränderte Chunks beim Reindex) werden
+//! nicht erneut berechnet. Bei voller Kapazität fliegt der älteste Eintrag
+//! raus; `cache_entries: 0` in `configs/models.yml` schaltet den Cache ab.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// Default number of cached vectors per embedder.
+pub const DEFAULT_CACHE_ENTRIES: usize = 1_024;
+
+#[derive(Debug, Default)]
+struct CacheState {
+    vectors: HashMap<String, Vec<f32>>,
+    order: VecDeque<String>,
+}
+
+/// Shared between clones of an embedder.
+#[derive(Debug, Clone)]
+pub struct EmbeddingCache {
+    capacity: usize,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl EmbeddingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self, text: &str) -> Option<Vec<f32>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.lock().vectors.get(text).cloned()
+    }
+
+    pub fn insert(&self, text: &str, vector: &[f32]) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut state = self.lock();
+        if state.vectors.contains_key(text) {
+            return;
+        }
+        while state.order.len() >= self.capacity {
+            if let Some(oldest) = state.order.pop_front() {
+                state.vectors.remove(&oldest);
+            }
+        }
+        state.order.push_back(text.to_string());
+        state.vectors.insert(text.to_string(), vector.to_vec());
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_oldest_entry_at_capacity() {
+        let cache = EmbeddingCache::new(2);
+        cache.insert("a", &[1.0]);
+        cache.insert("b", &[2.0]);
+        cache.insert("a", &[9.0]);
+        cache.insert("c", &[3.0]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(vec![2.0]));
+        assert_eq!(cache.get("c"), Some(vec![3.0]));
+
+        let disabled = EmbeddingCache::new(0);
+        disabled.insert("a", &[1.0]);
+        assert!(disabled.is_empty());
+        assert_eq!(disabled.get("a"), None);
+    }
+}
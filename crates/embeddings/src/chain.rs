@@ -0,0 +1,191 @@
+//! Fallback-Kette aus mehreren Embeddern.
+//!
+//! Konfiguriert über `embedder_fallback` in `configs/models.yml`, z. B.
+//! lokales Modell → Ollama → entfernter Dienst:
+//!
+//! ```yaml
+//! embedder_fallback: [minilm-local, nomic-ollama]
+//! embedders:
+//!   - id: minilm-local
+//!     latency_budget_ms: 2000
+//!     # …
+//! ```
+//!
+//! Jeder Batch geht zuerst an das erste Mitglied. Schlägt es fehl (nach seinen
+//! eigenen Retries bzw. bei offenem Circuit-Breaker) oder überschreitet es sein
+//! `latency_budget_ms`, übernimmt das nächste. Für das letzte Mitglied gilt kein
+//! Budget. Die Vektoren tragen die ID des Embedders, der sie tatsächlich
+//! erzeugt hat ([`Embedder::embed_batch_tagged`]); Mitglieder können
+//! unterschiedliche Dimensionen haben.
+
+use anyhow::Result;
+
+use crate::{ConfiguredEmbedder, EmbedError, Embedder};
+
+/// Ordered list of embedders; the first one is the primary.
+#[derive(Debug, Clone)]
+pub struct EmbedderChain {
+    members: Vec<ConfiguredEmbedder>,
+}
+
+impl EmbedderChain {
+    /// `None` if `members` is empty.
+    pub fn new(members: Vec<ConfiguredEmbedder>) -> Option<Self> {
+        (!members.is_empty()).then_some(Self { members })
+    }
+
+    pub fn primary(&self) -> &ConfiguredEmbedder {
+        &self.members[0]
+    }
+
+    pub fn members(&self) -> &[ConfiguredEmbedder] {
+        &self.members
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.members.iter().any(|member| member.id() == id)
+    }
+
+    /// Batches in flight during bulk ingestion, taken from the primary.
+    pub fn concurrency(&self) -> usize {
+        self.primary().concurrency()
+    }
+}
+
+impl Embedder for EmbedderChain {
+    /// The smallest batch size of all members, so every fallback accepts a batch.
+    fn max_batch_size(&self) -> usize {
+        self.members
+            .iter()
+            .map(Embedder::max_batch_size)
+            .min()
+            .unwrap_or(crate::DEFAULT_BATCH_SIZE)
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch_tagged(batch)
+            .await
+            .map(|(vectors, _)| vectors)
+    }
+
+    async fn embed_batch_tagged(
+        &self,
+        batch: &[String],
+    ) -> Result<(Vec<Vec<f32>>, Option<String>)> {
+        let last = self.members.len() - 1;
+        let mut error = None;
+        for (position, member) in self.members.iter().enumerate() {
+            let outcome = match member.latency_budget() {
+                Some(budget) if position < last => {
+                    match tokio::time::timeout(budget, member.embed_batch(batch)).await {
+                        Ok(outcome) => outcome,
+                        Err(_) => Err(EmbedError::Timeout(budget).into()),
+                    }
+                }
+                _ => member.embed_batch(batch).await,
+            };
+            match outcome {
+                Ok(vectors) => return Ok((vectors, Some(member.id().to_string()))),
+                Err(err) => error = Some(err),
+            }
+        }
+        Err(error.unwrap_or_else(|| anyhow::anyhow!("empty embedder chain")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmbedderSpec;
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    /// Ollama stand-in answering with `[value; dimension]` after `delay`, or
+    /// with HTTP 500 when `value` is negative.
+    async fn upstream(value: f32, dimension: usize, delay: Duration) -> String {
+        let router = Router::new().route(
+            "/api/embed",
+            post(move |Json(body): Json<Value>| async move {
+                tokio::time::sleep(delay).await;
+                let count = body["input"].as_array().map_or(0, Vec::len);
+                if value < 0.0 {
+                    return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                Ok(Json(
+                    json!({ "embeddings": vec![vec![value; dimension]; count] }),
+                ))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn member(id: &str, url: &str, dimension: usize, budget_ms: Option<u64>) -> ConfiguredEmbedder {
+        serde_json::from_value::<EmbedderSpec>(json!({
+            "id": id,
+            "provider": "ollama",
+            "model": id,
+            "url": url,
+            "dimension": dimension,
+            "max_tokens": 512,
+            "latency_budget_ms": budget_ms,
+            "retry": { "max_attempts": 1 },
+            "cache_entries": 0,
+        }))
+        .unwrap()
+        .build()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_failure_and_tags_the_producing_embedder() {
+        let broken = upstream(-1.0, 2, Duration::ZERO).await;
+        let healthy = upstream(1.0, 3, Duration::ZERO).await;
+        let chain = EmbedderChain::new(vec![
+            member("primary", &broken, 2, None),
+            member("secondary", &healthy, 3, None),
+        ])
+        .unwrap();
+
+        let texts = vec!["a".to_string(), "b".to_string()];
+        let (vectors, tag) = chain.embed_batch_tagged(&texts).await.unwrap();
+        assert_eq!(vectors, [vec![1.0; 3], vec![1.0; 3]]);
+        assert_eq!(tag.as_deref(), Some("secondary"));
+
+        let partial = chain.embed_partial(&texts).await;
+        assert!(partial.is_complete());
+        assert_eq!(
+            partial.produced_by,
+            [Some("secondary".into()), Some("secondary".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_the_latency_budget_is_exceeded() {
+        let slow = upstream(0.5, 2, Duration::from_millis(500)).await;
+        let fast = upstream(1.0, 2, Duration::ZERO).await;
+        let chain = EmbedderChain::new(vec![
+            member("slow", &slow, 2, Some(50)),
+            member("fast", &fast, 2, Some(1)),
+        ])
+        .unwrap();
+
+        let (vectors, tag) = chain.embed_batch_tagged(&["a".to_string()]).await.unwrap();
+        assert_eq!(vectors, [vec![1.0, 1.0]]);
+        // The budget of the last member is not enforced.
+        assert_eq!(tag.as_deref(), Some("fast"));
+
+        let only_broken = EmbedderChain::new(vec![member(
+            "broken",
+            &upstream(-1.0, 2, Duration::ZERO).await,
+            2,
+            None,
+        )])
+        .unwrap();
+        assert!(only_broken.embed(&["a".to_string()]).await.is_err());
+        assert!(EmbedderChain::new(Vec::new()).is_none());
+    }
+}
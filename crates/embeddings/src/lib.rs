@@ -1,18 +1,249 @@
+use std::{fmt, future::Future, ops::Range, pin::Pin, sync::Arc, time::Duration};
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+pub mod cache;
+pub mod chain;
+pub mod local;
+pub mod metrics;
+pub mod parallel;
+pub mod registry;
+pub mod resilience;
+
+pub use cache::EmbeddingCache;
+pub use chain::EmbedderChain;
+pub use local::LocalEmbedder;
+pub use metrics::EmbeddingMetrics;
+pub use parallel::{embed_parallel, EmbedThroughput, DEFAULT_CONCURRENCY};
+pub use registry::{ConfiguredEmbedder, EmbedderRegistry, EmbedderSpec, Provider, RegistryError};
+pub use resilience::{CircuitBreakerConfig, RetryConfig};
+
+/// Default number of texts sent per `/api/embed` request.
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+/// Default timeout for a single `/api/embed` request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Post-processing of output vectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    /// Vectors are used as returned by the provider.
+    #[default]
+    None,
+    /// Vectors are scaled to unit length, so cosine similarity equals the dot product.
+    L2,
+}
+
+impl Normalization {
+    pub fn apply(self, vector: &mut [f32]) {
+        if self == Self::L2 {
+            l2_normalize(vector);
+        }
+    }
+}
+
+/// Scales `vector` to unit length; zero vectors stay unchanged.
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// How token states are combined into one vector (local models only).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pooling {
+    /// Average over all tokens (sentence-transformers default).
+    #[default]
+    Mean,
+    /// State of the `[CLS]` token.
+    Cls,
+    /// Element-wise maximum over all tokens.
+    Max,
+}
+
+/// Where local models run (local models only).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Device {
+    /// CUDA or Metal when compiled in and present, else the CPU.
+    #[default]
+    Auto,
+    Cpu,
+    /// First CUDA GPU; needs the `cuda` feature.
+    Cuda,
+    /// Apple GPU; needs the `metal` feature.
+    Metal,
+}
+
 /// Trait for embedding providers.
-pub trait Embedder {
-    /// Creates embeddings for multiple texts.
-    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+///
+/// Providers implement [`Embedder::embed_batch`] for a single upstream request;
+/// the provided methods split larger inputs into chunks of at most
+/// [`Embedder::max_batch_size`] texts and reassemble the results in input order.
+pub trait Embedder: Send + Sync {
+    /// Upper bound of texts per upstream request.
+    fn max_batch_size(&self) -> usize {
+        DEFAULT_BATCH_SIZE
+    }
+
+    /// Embeds one batch with a single upstream request.
+    fn embed_batch(&self, batch: &[String]) -> impl Future<Output = Result<Vec<Vec<f32>>>> + Send;
+
+    /// Like [`Embedder::embed_batch`], plus the id of the embedder that
+    /// produced the vectors, where it is known (registry embedders, fallback
+    /// chains).
+    fn embed_batch_tagged(
+        &self,
+        batch: &[String],
+    ) -> impl Future<Output = Result<(Vec<Vec<f32>>, Option<String>)>> + Send {
+        async move { self.embed_batch(batch).await.map(|vectors| (vectors, None)) }
+    }
+
+    /// Creates embeddings for multiple texts, one vector per text in input order.
+    /// Fails on the first failing batch.
+    fn embed(&self, texts: &[String]) -> impl Future<Output = Result<Vec<Vec<f32>>>> + Send {
+        async move {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(self.max_batch_size().max(1)) {
+                let vectors = self.embed_batch(batch).await?;
+                check_count(batch, &vectors)?;
+                embeddings.extend(vectors);
+            }
+            Ok(embeddings)
+        }
+    }
+
+    /// Like [`Embedder::embed`], but keeps going after a failed batch and reports
+    /// the failures next to the vectors of the successful batches.
+    fn embed_partial(&self, texts: &[String]) -> impl Future<Output = PartialEmbeddings> + Send {
+        async move {
+            let batch_size = self.max_batch_size().max(1);
+            let mut result = PartialEmbeddings {
+                vectors: Vec::with_capacity(texts.len()),
+                produced_by: Vec::with_capacity(texts.len()),
+                failures: Vec::new(),
+            };
+            for (index, batch) in texts.chunks(batch_size).enumerate() {
+                let start = index * batch_size;
+                let outcome = match self.embed_batch_tagged(batch).await {
+                    Ok((vectors, tag)) => check_count(batch, &vectors).map(|()| (vectors, tag)),
+                    Err(err) => Err(err),
+                };
+                match outcome {
+                    Ok((vectors, tag)) => {
+                        result.vectors.extend(vectors.into_iter().map(Some));
+                        result.produced_by.extend(batch.iter().map(|_| tag.clone()));
+                    }
+                    Err(error) => {
+                        result.vectors.extend(batch.iter().map(|_| None));
+                        result.produced_by.extend(batch.iter().map(|_| None));
+                        result.failures.push(BatchFailure {
+                            batch: index,
+                            inputs: start..start + batch.len(),
+                            error,
+                        });
+                    }
+                }
+            }
+            result
+        }
+    }
 }
 
-/// Stub implementation for Ollama.
+fn check_count(batch: &[String], vectors: &[Vec<f32>]) -> Result<()> {
+    if vectors.len() != batch.len() {
+        return Err(EmbedError::CountMismatch {
+            expected: batch.len(),
+            got: vectors.len(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Result of [`Embedder::embed_partial`].
+#[derive(Debug, Default)]
+pub struct PartialEmbeddings {
+    /// One slot per input text, in input order; `None` where the batch failed.
+    pub vectors: Vec<Option<Vec<f32>>>,
+    /// Id of the embedder behind each slot, if known (see
+    /// [`Embedder::embed_batch_tagged`]).
+    pub produced_by: Vec<Option<String>>,
+    pub failures: Vec<BatchFailure>,
+}
+
+/// A batch that could not be embedded.
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// Zero-based batch number.
+    pub batch: usize,
+    /// Indices of the affected input texts.
+    pub inputs: Range<usize>,
+    pub error: anyhow::Error,
+}
+
+impl PartialEmbeddings {
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns all vectors, or the error of the first failed batch.
+    pub fn into_complete(self) -> Result<Vec<Vec<f32>>> {
+        if let Some(failure) = self.failures.into_iter().next() {
+            return Err(failure.error);
+        }
+        Ok(self.vectors.into_iter().flatten().collect())
+    }
+}
+
+/// Errors raised by the embedders; returned inside `anyhow::Error` and
+/// available via `downcast_ref`.
+#[derive(Debug, thiserror::Error)]
+pub enum EmbedError {
+    #[error("POST {url} failed: {source}")]
+    Request {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("ollama returned status {status}: {message}")]
+    Status { status: u16, message: String },
+    #[error("invalid ollama embed response: {0}")]
+    Decode(#[source] reqwest::Error),
+    #[error("ollama returned {got} embeddings for {expected} inputs")]
+    CountMismatch { expected: usize, got: usize },
+    #[error("embedding call exceeded the timeout of {0:?}")]
+    Timeout(Duration),
+    #[error("circuit breaker open, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+    #[error("POST {url} denied: {reason}")]
+    Denied { url: String, reason: String },
+}
+
+/// Future returned by [`HttpTransport::send`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<reqwest::Response, EmbedError>> + Send + 'a>>;
+
+/// Sends the requests of [`OllamaEmbedder`] instead of its own client, e.g.
+/// through the host's egress allowlist. Denied requests fail with
+/// [`EmbedError::Denied`], transport errors with [`EmbedError::Request`].
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_>;
+}
+
+/// Embedder backed by Ollama's `POST /api/embed`.
 #[derive(Debug, Clone)]
 pub struct OllamaEmbedder {
     base_url: Url,
     model: String,
+    client: reqwest::Client,
+    transport: Option<Arc<dyn HttpTransport>>,
+    batch_size: usize,
+    timeout: Duration,
 }
 
 #[derive(Debug, Serialize)]
@@ -24,6 +255,14 @@ pub struct OllamaEmbedRequest<'a> {
 #[derive(Debug, Deserialize)]
 pub struct OllamaEmbedResponse {
     pub embeddings: Vec<Vec<f32>>,
+    /// Input tokens processed by the model.
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaErrorResponse {
+    error: String,
 }
 
 impl OllamaEmbedder {
@@ -31,9 +270,37 @@ impl OllamaEmbedder {
         Self {
             base_url,
             model: model.into(),
+            client: reqwest::Client::new(),
+            transport: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            timeout: DEFAULT_TIMEOUT,
         }
     }
 
+    /// Reuses an existing HTTP client (connection pool, proxy settings).
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sends requests through `transport`; the client then only builds them.
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Maximum number of texts per `/api/embed` request (default
+    /// [`DEFAULT_BATCH_SIZE`]); values below 1 are treated as 1.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     pub fn base_url(&self) -> &Url {
         &self.base_url
     }
@@ -41,14 +308,420 @@ impl OllamaEmbedder {
     pub fn model(&self) -> &str {
         &self.model
     }
+
+    fn endpoint(&self) -> String {
+        format!("{}/api/embed", self.base_url.as_str().trim_end_matches('/'))
+    }
+
+    /// Like [`Embedder::embed_batch`], plus the token count Ollama reports.
+    pub async fn embed_batch_with_usage(
+        &self,
+        batch: &[String],
+    ) -> Result<(Vec<Vec<f32>>, Option<u64>)> {
+        let url = self.endpoint();
+        let url = url.as_str();
+        let request = OllamaEmbedRequest {
+            model: &self.model,
+            input: batch,
+        };
+        let failed = |source| EmbedError::Request {
+            url: url.to_string(),
+            source,
+        };
+        let request = self
+            .client
+            .post(url)
+            .timeout(self.timeout)
+            .json(&request)
+            .build()
+            .map_err(failed)?;
+        let response = match &self.transport {
+            Some(transport) => transport.send(request).await?,
+            None => self.client.execute(request).await.map_err(failed)?,
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<OllamaErrorResponse>(&body)
+                .map(|parsed| parsed.error)
+                .unwrap_or(body);
+            return Err(EmbedError::Status {
+                status: status.as_u16(),
+                message,
+            }
+            .into());
+        }
+
+        let parsed: OllamaEmbedResponse = response.json().await.map_err(EmbedError::Decode)?;
+        Ok((parsed.embeddings, parsed.prompt_eval_count))
+    }
 }
 
 impl Embedder for OllamaEmbedder {
-    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
-        if texts.is_empty() {
-            return Ok(Vec::new());
+    fn max_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch_with_usage(batch)
+            .await
+            .map(|(vectors, _)| vectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    async fn serve(router: Router) -> Url {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        Url::parse(&format!("http://{addr}/")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn embed_sends_batches_and_keeps_input_order() {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let seen = batches.clone();
+        let router = Router::new().route(
+            "/api/embed",
+            post(move |Json(body): Json<Value>| {
+                let seen = seen.clone();
+                async move {
+                    assert_eq!(body["model"], "nomic-embed-text");
+                    let input: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap();
+                    seen.lock().unwrap().push(input.len());
+                    let embeddings: Vec<Vec<f32>> = input
+                        .iter()
+                        .map(|text| vec![text.len() as f32, 1.0])
+                        .collect();
+                    Json(json!({ "model": "nomic-embed-text", "embeddings": embeddings }))
+                }
+            }),
+        );
+        let embedder =
+            OllamaEmbedder::new(serve(router).await, "nomic-embed-text").with_batch_size(2);
+
+        let texts: Vec<String> = ["a", "bb", "ccc", "dddd", "eeeee"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let embeddings = embedder.embed(&texts).await.unwrap();
+
+        let lengths: Vec<f32> = embeddings.iter().map(|vector| vector[0]).collect();
+        assert_eq!(lengths, [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(*batches.lock().unwrap(), [2, 2, 1]);
+        assert!(embedder.embed(&[]).await.unwrap().is_empty());
+        assert_eq!(batches.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn embed_partial_reports_failed_batches_and_keeps_the_rest() {
+        let router = Router::new().route(
+            "/api/embed",
+            post(|Json(body): Json<Value>| async move {
+                let input: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap();
+                if input.iter().any(|text| text == "kaputt") {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({ "error": "batch too large" })),
+                    );
+                }
+                let embeddings: Vec<Vec<f32>> =
+                    input.iter().map(|text| vec![text.len() as f32]).collect();
+                (StatusCode::OK, Json(json!({ "embeddings": embeddings })))
+            }),
+        );
+        let embedder =
+            OllamaEmbedder::new(serve(router).await, "nomic-embed-text").with_batch_size(2);
+        let texts: Vec<String> = ["a", "bb", "kaputt", "dddd", "eeeee"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let partial = embedder.embed_partial(&texts).await;
+        assert!(!partial.is_complete());
+        let lengths: Vec<Option<f32>> = partial
+            .vectors
+            .iter()
+            .map(|vector| vector.as_ref().map(|vector| vector[0]))
+            .collect();
+        assert_eq!(lengths, [Some(1.0), Some(2.0), None, None, Some(5.0)]);
+        assert_eq!(partial.failures.len(), 1);
+        assert_eq!(partial.failures[0].batch, 1);
+        assert_eq!(partial.failures[0].inputs, 2..4);
+        assert!(partial.into_complete().is_err());
+
+        assert!(embedder.embed(&texts).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn configured_embedder_applies_l2_normalization() {
+        let router = Router::new().route(
+            "/api/embed",
+            post(|| async { Json(json!({ "embeddings": [[3.0, 4.0], [0.0, 0.0]] })) }),
+        );
+        let url = serve(router).await;
+        let spec: EmbedderSpec = serde_json::from_value(json!({
+            "id": "nomic",
+            "provider": "ollama",
+            "model": "nomic-embed-text",
+            "url": url.as_str(),
+            "dimension": 2,
+            "max_tokens": 8192,
+            "normalization": "l2",
+        }))
+        .unwrap();
+        let embedder = spec.build().unwrap();
+        assert_eq!(embedder.id(), "nomic");
+        let texts = vec!["a".to_string(), "b".to_string()];
+        let vectors = embedder.embed(&texts).await.unwrap();
+        assert_eq!(vectors, [vec![0.6, 0.8], vec![0.0, 0.0]]);
+    }
+
+    #[tokio::test]
+    async fn configured_embedder_retries_transient_failures_and_trips_breaker() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let seen = calls.clone();
+        let router = Router::new().route(
+            "/api/embed",
+            post(move || {
+                let call = seen.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match call {
+                        // First batch: two transient failures, then success.
+                        0 | 1 => (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            Json(json!({ "error": "busy" })),
+                        ),
+                        2 => (StatusCode::OK, Json(json!({ "embeddings": [[1.0]] }))),
+                        // Afterwards: permanent failures, no retries.
+                        _ => (
+                            StatusCode::NOT_FOUND,
+                            Json(json!({ "error": "model not found" })),
+                        ),
+                    }
+                }
+            }),
+        );
+        let url = serve(router).await;
+        let spec: EmbedderSpec = serde_json::from_value(json!({
+            "id": "nomic",
+            "provider": "ollama",
+            "model": "nomic-embed-text",
+            "url": url.as_str(),
+            "dimension": 1,
+            "max_tokens": 8192,
+            "timeout_ms": 1000,
+            "retry": { "max_attempts": 3, "base_delay_ms": 1, "max_delay_ms": 5 },
+            "circuit_breaker": { "failure_threshold": 2, "open_secs": 60 },
+        }))
+        .unwrap();
+        let embedder = spec.build().unwrap();
+        let texts = vec!["a".to_string()];
+
+        assert_eq!(embedder.embed(&texts).await.unwrap(), [vec![1.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // A new text, so the cache does not answer.
+        let texts = vec!["b".to_string()];
+        for _ in 0..2 {
+            assert!(embedder.embed(&texts).await.is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        assert!(embedder.is_circuit_open());
+
+        let err = embedder.embed(&texts).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<EmbedError>(),
+            Some(EmbedError::CircuitOpen { .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn configured_embedder_caches_vectors_and_records_metrics() {
+        use prometheus_client::{encoding::text::encode, registry::Registry};
+
+        let inputs = Arc::new(Mutex::new(Vec::new()));
+        let seen = inputs.clone();
+        let router = Router::new().route(
+            "/api/embed",
+            post(move |Json(body): Json<Value>| {
+                let seen = seen.clone();
+                async move {
+                    let input: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap();
+                    seen.lock().unwrap().push(input.clone());
+                    let embeddings: Vec<Vec<f32>> =
+                        input.iter().map(|text| vec![text.len() as f32]).collect();
+                    Json(json!({ "embeddings": embeddings, "prompt_eval_count": 7 }))
+                }
+            }),
+        );
+        let url = serve(router).await;
+        let spec: EmbedderSpec = serde_json::from_value(json!({
+            "id": "nomic",
+            "provider": "ollama",
+            "model": "nomic-embed-text",
+            "url": url.as_str(),
+            "dimension": 1,
+            "max_tokens": 8192,
+        }))
+        .unwrap();
+        let metrics = EmbeddingMetrics::default();
+        let embedder = spec.build().unwrap().with_metrics(metrics.clone());
+
+        let first = vec!["aa".to_string(), "b".to_string()];
+        let second = vec!["b".to_string(), "ccc".to_string(), "aa".to_string()];
+        assert_eq!(
+            embedder.embed(&first).await.unwrap(),
+            [vec![2.0], vec![1.0]]
+        );
+        assert_eq!(
+            embedder.embed(&second).await.unwrap(),
+            [vec![1.0], vec![3.0], vec![2.0]]
+        );
+        assert_eq!(
+            *inputs.lock().unwrap(),
+            [
+                vec!["aa".to_string(), "b".to_string()],
+                vec!["ccc".to_string()]
+            ]
+        );
+
+        let mut registry = Registry::default();
+        metrics.register(&mut registry);
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        let labels = r#"provider="ollama",model="nomic-embed-text""#;
+        for line in [
+            format!(r#"embedding_cache_requests_total{{{labels},result="hit"}} 2"#),
+            format!(r#"embedding_cache_requests_total{{{labels},result="miss"}} 3"#),
+            format!("embedding_texts_total{{{labels}}} 3"),
+            format!("embedding_tokens_total{{{labels}}} 14"),
+            format!("embedding_request_duration_seconds_count{{{labels}}} 2"),
+        ] {
+            assert!(text.contains(&line), "missing {line} in:\n{text}");
+        }
+    }
+
+    #[tokio::test]
+    async fn embed_maps_ollama_errors() {
+        let router = Router::new().route(
+            "/api/embed",
+            post(|| async {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "error": "model \"missing\" not found" })),
+                )
+            }),
+        );
+        let embedder = OllamaEmbedder::new(serve(router).await, "missing");
+        let err = embedder.embed(&["hallo".to_string()]).await.unwrap_err();
+        match err.downcast_ref::<EmbedError>() {
+            Some(EmbedError::Status { status, message }) => {
+                assert_eq!(*status, 404);
+                assert_eq!(message, "model \"missing\" not found");
+            }
+            other => panic!("unexpected error: {other:?}"),
         }
-        // Stub: returns empty vectors until HTTP integration is implemented.
-        Ok(texts.iter().map(|_| Vec::new()).collect())
+    }
+
+    #[tokio::test]
+    async fn embed_rejects_incomplete_responses() {
+        let router = Router::new().route(
+            "/api/embed",
+            post(|| async { Json(json!({ "embeddings": [[0.5, 0.5]] })) }),
+        );
+        let embedder = OllamaEmbedder::new(serve(router).await, "nomic-embed-text");
+        let texts = vec!["eins".to_string(), "zwei".to_string()];
+        let err = embedder.embed(&texts).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<EmbedError>(),
+            Some(EmbedError::CountMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+
+    /// Forwards requests and counts them, or denies every request.
+    #[derive(Debug, Default)]
+    struct CountingTransport {
+        deny: bool,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl HttpTransport for CountingTransport {
+        fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let url = request.url().to_string();
+                self.sent.lock().unwrap().push(url.clone());
+                if self.deny {
+                    return Err(EmbedError::Denied {
+                        url,
+                        reason: "host not allowed".into(),
+                    });
+                }
+                reqwest::Client::new()
+                    .execute(request)
+                    .await
+                    .map_err(|source| EmbedError::Request { url, source })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn embed_sends_through_the_transport() {
+        let router = Router::new().route(
+            "/api/embed",
+            post(|| async { Json(json!({ "embeddings": [[0.5, 0.5]] })) }),
+        );
+        let base = serve(router).await;
+        let transport = Arc::new(CountingTransport::default());
+        let embedder =
+            OllamaEmbedder::new(base.clone(), "nomic-embed-text").with_transport(transport.clone());
+        let embeddings = embedder.embed(&["hallo".to_string()]).await.unwrap();
+        assert_eq!(embeddings, [[0.5, 0.5]]);
+        assert_eq!(
+            *transport.sent.lock().unwrap(),
+            [format!("{base}api/embed")]
+        );
+
+        let denied = Arc::new(CountingTransport {
+            deny: true,
+            ..CountingTransport::default()
+        });
+        let embedder = OllamaEmbedder::new(base, "nomic-embed-text").with_transport(denied);
+        let err = embedder.embed(&["hallo".to_string()]).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<EmbedError>(),
+            Some(EmbedError::Denied { reason, .. }) if reason == "host not allowed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn embed_reports_unreachable_endpoint() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let embedder = OllamaEmbedder::new(
+            Url::parse(&format!("http://{addr}")).unwrap(),
+            "nomic-embed-text",
+        );
+        let err = embedder.embed(&["hallo".to_string()]).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<EmbedError>(),
+            Some(EmbedError::Request { .. })
+        ));
     }
 }
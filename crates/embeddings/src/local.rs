@@ -0,0 +1,580 @@
+//! Lokaler Embedder ohne Ollama-Prozess: ein BERT-Encoder
+//! (z. B. `all-MiniLM-L6-v2`, `gte-small`) wird mit candle direkt aus den
+//! Gewichten geladen und auf CPU, CUDA oder Metal gerechnet.
+//!
+//! Erwartetes Modellverzeichnis (Layout der sentence-transformers-Exporte):
+//!   config.json            – BERT-Konfiguration (wie von `transformers` geschrieben)
+//!   model.safetensors      – Gewichte als F32, F16 oder BF16
+//!   tokenizer.json         – Tokenizer der `tokenizers`-Bibliothek
+//!   vocab.txt              – Ersatz ohne `tokenizer.json`: BERT-WordPiece
+//!   tokenizer_config.json  – optional, nur `do_lower_case` (für `vocab.txt`)
+//!
+//! Pooling: standardmäßig Mittelwert über alle Tokens, danach L2-normalisiert –
+//! wie bei den genannten Modellen; `with_pooling` (`cls`, `max`) und
+//! `with_normalization` ändern das.
+//!
+//! Das Gerät wählt `load_on` (bzw. `device` in der Registry): `cpu`, `cuda`,
+//! `metal` oder `auto` (Default: GPU, wenn einkompiliert und vorhanden, sonst
+//! CPU). CUDA und Metal brauchen die Cargo-Features `cuda` bzw. `metal`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use candle_core::{DType, Tensor, D};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig};
+use serde::Deserialize;
+use tokenizers::{
+    models::wordpiece::WordPiece, normalizers::BertNormalizer,
+    pre_tokenizers::bert::BertPreTokenizer, processors::bert::BertProcessing, Tokenizer,
+    TruncationParams,
+};
+
+use crate::{Device, Embedder, Normalization, Pooling, DEFAULT_BATCH_SIZE};
+
+/// Token limit used when the model allows more (BERT position embeddings).
+pub const DEFAULT_MAX_TOKENS: usize = 256;
+
+const UNK: &str = "[UNK]";
+const CLS: &str = "[CLS]";
+const SEP: &str = "[SEP]";
+
+/// Embedder running a BERT sentence-transformer in-process.
+#[derive(Clone)]
+pub struct LocalEmbedder {
+    model: Arc<BertModel>,
+    config: Arc<BertConfig>,
+    tokenizer: Arc<Tokenizer>,
+    device: candle_core::Device,
+    model_dir: PathBuf,
+    max_tokens: usize,
+    batch_size: usize,
+    pooling: Pooling,
+    normalization: Normalization,
+}
+
+impl std::fmt::Debug for LocalEmbedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalEmbedder")
+            .field("model_dir", &self.model_dir)
+            .field("device", &self.device_name())
+            .field("dimension", &self.dimension())
+            .field("max_tokens", &self.max_tokens)
+            .field("batch_size", &self.batch_size)
+            .field("pooling", &self.pooling)
+            .field("normalization", &self.normalization)
+            .finish()
+    }
+}
+
+impl LocalEmbedder {
+    /// Loads config, tokenizer and weights from `model_dir` on [`Device::Auto`].
+    pub fn load(model_dir: impl AsRef<Path>) -> Result<Self> {
+        Self::load_on(model_dir, Device::default())
+    }
+
+    /// Loads config, tokenizer and weights from `model_dir` onto `device`.
+    pub fn load_on(model_dir: impl AsRef<Path>, device: Device) -> Result<Self> {
+        let model_dir = model_dir.as_ref().to_path_buf();
+        let device = candle_device(device)?;
+
+        let config_path = model_dir.join("config.json");
+        let config: BertConfig = serde_json::from_slice(
+            &fs::read(&config_path).with_context(|| format!("read {}", config_path.display()))?,
+        )
+        .with_context(|| format!("parse {}", config_path.display()))?;
+        if config.num_attention_heads == 0
+            || !config
+                .hidden_size
+                .is_multiple_of(config.num_attention_heads)
+        {
+            bail!(
+                "hidden_size {} is not divisible by num_attention_heads {}",
+                config.hidden_size,
+                config.num_attention_heads
+            );
+        }
+
+        let tokenizer = load_tokenizer(&model_dir)?;
+        if tokenizer.get_vocab_size(true) > config.vocab_size {
+            bail!(
+                "tokenizer has {} entries, model vocab_size is {}",
+                tokenizer.get_vocab_size(true),
+                config.vocab_size
+            );
+        }
+
+        let weights_path = model_dir.join("model.safetensors");
+        let bytes =
+            fs::read(&weights_path).with_context(|| format!("read {}", weights_path.display()))?;
+        let weights = VarBuilder::from_buffered_safetensors(bytes, DType::F32, &device)
+            .map_err(|err| anyhow!("{}: {err}", weights_path.display()))?;
+        let model = BertModel::load(weights, &config)
+            .map_err(|err| anyhow!("{}: {err}", weights_path.display()))?;
+
+        let max_tokens = config.max_position_embeddings.min(DEFAULT_MAX_TOKENS);
+        Ok(Self {
+            tokenizer: Arc::new(truncating(tokenizer, max_tokens)?),
+            model: Arc::new(model),
+            config: Arc::new(config),
+            device,
+            model_dir,
+            max_tokens,
+            batch_size: DEFAULT_BATCH_SIZE,
+            pooling: Pooling::Mean,
+            normalization: Normalization::L2,
+        })
+    }
+
+    /// Truncates inputs to `max_tokens` tokens including `[CLS]`/`[SEP]`;
+    /// capped by the model's position embeddings.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens.clamp(2, self.config.max_position_embeddings);
+        if let Ok(tokenizer) = truncating((*self.tokenizer).clone(), self.max_tokens) {
+            self.tokenizer = Arc::new(tokenizer);
+        }
+        self
+    }
+
+    /// Texts per [`Embedder::embed_batch`] call; values below 1 are treated as 1.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Token pooling (default [`Pooling::Mean`]).
+    pub fn with_pooling(mut self, pooling: Pooling) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    /// Output normalization (default [`Normalization::L2`]).
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn model_dir(&self) -> &Path {
+        &self.model_dir
+    }
+
+    /// Device the model runs on: `cpu`, `cuda:<n>` or `metal`.
+    pub fn device_name(&self) -> String {
+        match self.device.location() {
+            candle_core::DeviceLocation::Cpu => "cpu".to_string(),
+            candle_core::DeviceLocation::Cuda { gpu_id } => format!("cuda:{gpu_id}"),
+            candle_core::DeviceLocation::Metal { .. } => "metal".to_string(),
+        }
+    }
+
+    /// Length of the produced vectors.
+    pub fn dimension(&self) -> usize {
+        self.config.hidden_size
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    /// Tokens fed to the model for `texts`, including `[CLS]`/`[SEP]` and
+    /// after truncation to `max_tokens`.
+    pub fn count_tokens(&self, texts: &[String]) -> usize {
+        texts
+            .iter()
+            .filter_map(|text| self.tokenizer.encode(text.as_str(), true).ok())
+            .map(|encoding| encoding.len())
+            .sum()
+    }
+
+    /// Embeds `texts` synchronously as one padded batch.
+    pub fn embed_blocking(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.iter().map(String::as_str).collect::<Vec<_>>(), true)
+            .map_err(|err| anyhow!("tokenize: {err}"))?;
+        let seq = encodings.iter().map(|e| e.len()).max().unwrap_or(0).max(1);
+        let mut ids = Vec::with_capacity(texts.len() * seq);
+        let mut mask = Vec::with_capacity(texts.len() * seq);
+        for encoding in &encodings {
+            let padding = seq - encoding.len();
+            ids.extend(encoding.get_ids().iter().copied());
+            ids.extend(std::iter::repeat_n(0u32, padding));
+            mask.extend(std::iter::repeat_n(1u32, encoding.len()));
+            mask.extend(std::iter::repeat_n(0u32, padding));
+        }
+
+        let shape = (texts.len(), seq);
+        let ids = Tensor::from_vec(ids, shape, &self.device)?;
+        let mask = Tensor::from_vec(mask, shape, &self.device)?;
+        let token_types = ids.zeros_like()?;
+        let states = self.model.forward(&ids, &token_types, Some(&mask))?;
+        let pooled = pool(&states, &mask, self.pooling)?;
+
+        let mut vectors = pooled.to_dtype(DType::F32)?.to_vec2::<f32>()?;
+        for vector in &mut vectors {
+            self.normalization.apply(vector);
+        }
+        Ok(vectors)
+    }
+}
+
+impl Embedder for LocalEmbedder {
+    fn max_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        let embedder = self.clone();
+        let batch = batch.to_vec();
+        tokio::task::spawn_blocking(move || embedder.embed_blocking(&batch))
+            .await
+            .map_err(|err| anyhow!("local embedder task failed: {err}"))?
+    }
+}
+
+/// Combines `[batch, seq, hidden]` token states into `[batch, hidden]`,
+/// ignoring padded positions.
+fn pool(states: &Tensor, mask: &Tensor, pooling: Pooling) -> candle_core::Result<Tensor> {
+    let mask = mask.to_dtype(states.dtype())?.unsqueeze(D::Minus1)?;
+    match pooling {
+        Pooling::Cls => states.narrow(1, 0, 1)?.squeeze(1),
+        Pooling::Mean => {
+            let sum = states.broadcast_mul(&mask)?.sum(1)?;
+            sum.broadcast_div(&mask.sum(1)?)
+        }
+        Pooling::Max => {
+            // Padded positions get a large negative offset so they never win.
+            let offset = ((mask.ones_like()? - &mask)? * -1e9)?;
+            states.broadcast_add(&offset)?.max(1)
+        }
+    }
+}
+
+fn candle_device(device: Device) -> Result<candle_core::Device> {
+    use candle_core::utils::{cuda_is_available, metal_is_available};
+
+    match device {
+        Device::Cpu => Ok(candle_core::Device::Cpu),
+        Device::Cuda if cuda_is_available() => Ok(candle_core::Device::new_cuda(0)?),
+        Device::Cuda => bail!(
+            "device cuda requested, but hauski-embeddings is built without the `cuda` feature"
+        ),
+        Device::Metal if metal_is_available() => Ok(candle_core::Device::new_metal(0)?),
+        Device::Metal => bail!(
+            "device metal requested, but hauski-embeddings is built without the `metal` feature"
+        ),
+        Device::Auto if cuda_is_available() => Ok(candle_core::Device::cuda_if_available(0)?),
+        Device::Auto if metal_is_available() => Ok(candle_core::Device::metal_if_available(0)?),
+        Device::Auto => Ok(candle_core::Device::Cpu),
+    }
+}
+
+/// `tokenizer.json` when present, else a BERT WordPiece tokenizer built from
+/// `vocab.txt` (and `do_lower_case` from `tokenizer_config.json`).
+fn load_tokenizer(model_dir: &Path) -> Result<Tokenizer> {
+    let json_path = model_dir.join("tokenizer.json");
+    if json_path.exists() {
+        return Tokenizer::from_file(&json_path)
+            .map_err(|err| anyhow!("parse {}: {err}", json_path.display()));
+    }
+
+    let vocab_path = model_dir.join("vocab.txt");
+    let wordpiece = WordPiece::from_file(&vocab_path.display().to_string())
+        .unk_token(UNK.to_string())
+        .build()
+        .map_err(|err| anyhow!("read {}: {err}", vocab_path.display()))?;
+    let lowercase = read_lowercase_flag(&model_dir.join("tokenizer_config.json"))?;
+    let mut tokenizer = Tokenizer::new(wordpiece);
+    let special = |token: &str| {
+        tokenizer
+            .token_to_id(token)
+            .map(|id| (token.to_string(), id))
+            .with_context(|| format!("{} lacks {token}", vocab_path.display()))
+    };
+    let (cls, sep) = (special(CLS)?, special(SEP)?);
+    special(UNK)?;
+    tokenizer
+        .with_normalizer(Some(BertNormalizer::new(true, true, None, lowercase)))
+        .with_pre_tokenizer(Some(BertPreTokenizer))
+        .with_post_processor(Some(BertProcessing::new(sep, cls)));
+    Ok(tokenizer)
+}
+
+/// Disables padding (batches are padded by hand) and truncates to `max_tokens`.
+fn truncating(mut tokenizer: Tokenizer, max_tokens: usize) -> Result<Tokenizer> {
+    tokenizer.with_padding(None);
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length: max_tokens,
+            ..TruncationParams::default()
+        }))
+        .map_err(|err| anyhow!("tokenizer truncation: {err}"))?;
+    Ok(tokenizer)
+}
+
+fn read_lowercase_flag(path: &Path) -> Result<bool> {
+    #[derive(Deserialize)]
+    struct TokenizerConfig {
+        #[serde(default = "default_true")]
+        do_lower_case: bool,
+    }
+
+    if !path.exists() {
+        return Ok(true);
+    }
+    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    let config: TokenizerConfig =
+        serde_json::from_slice(&bytes).with_context(|| format!("parse {}", path.display()))?;
+    Ok(config.do_lower_case)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const VOCAB: &[&str] = &[
+        "[PAD]", "[UNK]", "[CLS]", "[SEP]", "hallo", ",", "welt", "!", "un", "##aff", "##able",
+        "uber", "haus",
+    ];
+
+    fn ids(tokenizer: &Tokenizer, text: &str) -> Vec<u32> {
+        tokenizer.encode(text, true).unwrap().get_ids().to_vec()
+    }
+
+    #[test]
+    fn vocab_txt_tokenizer_splits_punctuation_subwords_and_accents() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("vocab.txt"), VOCAB.join("\n")).unwrap();
+        let tokenizer = truncating(load_tokenizer(dir.path()).unwrap(), 64).unwrap();
+        assert_eq!(
+            ids(&tokenizer, "Hallo, Wélt! unaffable Über xyz"),
+            [2, 4, 5, 6, 7, 8, 9, 10, 11, 1, 3]
+        );
+        let tokenizer = truncating(tokenizer, 4).unwrap();
+        assert_eq!(ids(&tokenizer, "hallo welt haus hallo"), [2, 4, 6, 3]);
+
+        fs::write(
+            dir.path().join("tokenizer_config.json"),
+            r#"{"do_lower_case": false}"#,
+        )
+        .unwrap();
+        let cased = load_tokenizer(dir.path()).unwrap();
+        assert_eq!(ids(&cased, "Hallo hallo"), [2, 1, 4, 3]);
+    }
+
+    /// Writes a tiny random BERT model; `f16_tensor` is stored as F16.
+    fn write_model(dir: &Path, f16_tensor: &str) {
+        let (hidden, intermediate, vocab, positions) = (8usize, 16usize, VOCAB.len(), 16usize);
+        let mut shapes: Vec<(String, Vec<usize>)> = vec![
+            (
+                "embeddings.word_embeddings.weight".into(),
+                vec![vocab, hidden],
+            ),
+            (
+                "embeddings.position_embeddings.weight".into(),
+                vec![positions, hidden],
+            ),
+            (
+                "embeddings.token_type_embeddings.weight".into(),
+                vec![2, hidden],
+            ),
+            ("embeddings.LayerNorm.weight".into(), vec![hidden]),
+            ("embeddings.LayerNorm.bias".into(), vec![hidden]),
+        ];
+        let prefix = "encoder.layer.0";
+        for name in [
+            "attention.self.query",
+            "attention.self.key",
+            "attention.self.value",
+            "attention.output.dense",
+        ] {
+            shapes.push((format!("{prefix}.{name}.weight"), vec![hidden, hidden]));
+            shapes.push((format!("{prefix}.{name}.bias"), vec![hidden]));
+        }
+        shapes.push((
+            format!("{prefix}.intermediate.dense.weight"),
+            vec![intermediate, hidden],
+        ));
+        shapes.push((
+            format!("{prefix}.intermediate.dense.bias"),
+            vec![intermediate],
+        ));
+        shapes.push((
+            format!("{prefix}.output.dense.weight"),
+            vec![hidden, intermediate],
+        ));
+        shapes.push((format!("{prefix}.output.dense.bias"), vec![hidden]));
+        for name in ["attention.output.LayerNorm", "output.LayerNorm"] {
+            shapes.push((format!("{prefix}.{name}.weight"), vec![hidden]));
+            shapes.push((format!("{prefix}.{name}.bias"), vec![hidden]));
+        }
+
+        let mut seed = 0x2545_f491_u32;
+        let mut header = serde_json::Map::new();
+        let mut data = Vec::new();
+        for (name, shape) in shapes {
+            let count: usize = shape.iter().product();
+            let start = data.len();
+            for _ in 0..count {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let value = (seed as f32 / u32::MAX as f32) - 0.5;
+                let value = if name.ends_with("LayerNorm.weight") {
+                    1.0 + value * 0.1
+                } else {
+                    value
+                };
+                if name == f16_tensor {
+                    // Round to f16 by truncating the mantissa (value is normal).
+                    let bits = value.to_bits();
+                    let sign = ((bits >> 16) & 0x8000) as u16;
+                    let exponent = (((bits >> 23) & 0xff) as i32 - 127 + 15) as u16;
+                    let mantissa = ((bits >> 13) & 0x3ff) as u16;
+                    data.extend_from_slice(&(sign | (exponent << 10) | mantissa).to_le_bytes());
+                } else {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            let dtype = if name == f16_tensor { "F16" } else { "F32" };
+            header.insert(
+                name,
+                json!({ "dtype": dtype, "shape": shape, "data_offsets": [start, data.len()] }),
+            );
+        }
+        header.insert("__metadata__".into(), json!({ "format": "pt" }));
+        let header = serde_json::to_vec(&header).unwrap();
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend(header);
+        bytes.extend(data);
+        fs::write(dir.join("model.safetensors"), bytes).unwrap();
+
+        let config = json!({
+            "vocab_size": vocab,
+            "hidden_size": hidden,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": intermediate,
+            "max_position_embeddings": positions,
+            "type_vocab_size": 2,
+            "layer_norm_eps": 1e-12,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.1,
+            "initializer_range": 0.02,
+            "pad_token_id": 0,
+        });
+        fs::write(dir.join("config.json"), config.to_string()).unwrap();
+        fs::write(dir.join("vocab.txt"), VOCAB.join("\n")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_embedder_produces_normalized_vectors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_model(dir.path(), "encoder.layer.0.attention.self.key.weight");
+        let embedder = LocalEmbedder::load_on(dir.path(), Device::Cpu)
+            .unwrap()
+            .with_batch_size(2);
+        assert_eq!(embedder.dimension(), 8);
+        assert_eq!(embedder.max_tokens(), 16);
+        assert_eq!(embedder.device_name(), "cpu");
+
+        let texts: Vec<String> = ["Hallo Welt", "unaffable Haus", "Hallo Welt"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(embedder.count_tokens(&texts), 4 + 6 + 4);
+        let vectors = embedder.embed(&texts).await.unwrap();
+        assert_eq!(vectors.len(), 3);
+        for vector in &vectors {
+            assert_eq!(vector.len(), 8);
+            let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+        }
+        assert_eq!(vectors[0], vectors[2]);
+        assert_ne!(vectors[0], vectors[1]);
+    }
+
+    #[test]
+    fn padding_does_not_change_vectors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_model(dir.path(), "");
+        let short = "Hallo".to_string();
+        let long = "unaffable Haus Hallo Welt, Hallo!".to_string();
+        for pooling in [Pooling::Mean, Pooling::Cls, Pooling::Max] {
+            let embedder = LocalEmbedder::load_on(dir.path(), Device::Cpu)
+                .unwrap()
+                .with_pooling(pooling);
+            let alone = embedder
+                .embed_blocking(std::slice::from_ref(&short))
+                .unwrap();
+            let batched = embedder
+                .embed_blocking(&[long.clone(), short.clone()])
+                .unwrap();
+            for (a, b) in alone[0].iter().zip(&batched[1]) {
+                assert!((a - b).abs() < 1e-5, "{pooling:?}: {a} vs {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn pooling_and_normalization_are_configurable() {
+        let dir = tempfile::tempdir().unwrap();
+        write_model(dir.path(), "");
+        let texts = vec!["Hallo Welt unaffable".to_string()];
+        let embed = |pooling, normalization| {
+            LocalEmbedder::load_on(dir.path(), Device::Cpu)
+                .unwrap()
+                .with_pooling(pooling)
+                .with_normalization(normalization)
+                .embed_blocking(&texts)
+                .unwrap()
+                .remove(0)
+        };
+        let norm = |vector: &[f32]| vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+
+        let mean = embed(Pooling::Mean, Normalization::None);
+        let cls = embed(Pooling::Cls, Normalization::None);
+        let max = embed(Pooling::Max, Normalization::None);
+        assert_ne!(mean, cls);
+        assert!(max.iter().zip(&mean).all(|(max, mean)| max >= mean));
+        // Layer-normed token states average to a vector shorter than one token.
+        assert!((norm(&mean) - 1.0).abs() > 1e-3);
+
+        let mut normalized = mean.clone();
+        crate::l2_normalize(&mut normalized);
+        assert_eq!(embed(Pooling::Mean, Normalization::L2), normalized);
+        assert!((norm(&embed(Pooling::Cls, Normalization::L2)) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn load_reports_missing_tensors_and_devices() {
+        let dir = tempfile::tempdir().unwrap();
+        write_model(dir.path(), "");
+        assert!(LocalEmbedder::load_on(dir.path(), Device::Cpu).is_ok());
+
+        let config_path = dir.path().join("config.json");
+        let mut config: serde_json::Value =
+            serde_json::from_slice(&fs::read(&config_path).unwrap()).unwrap();
+        config["num_hidden_layers"] = json!(2);
+        fs::write(&config_path, config.to_string()).unwrap();
+        let err = LocalEmbedder::load_on(dir.path(), Device::Cpu).unwrap_err();
+        assert!(err.to_string().contains("encoder.layer.1"), "{err}");
+
+        if !candle_core::utils::cuda_is_available() {
+            let err = LocalEmbedder::load_on(dir.path(), Device::Cuda).unwrap_err();
+            assert!(err.to_string().contains("`cuda` feature"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Prometheus-Metriken der Embedder.
+//!
+//! Der Core erzeugt eine [`EmbeddingMetrics`]-Instanz, registriert sie in
+//! seiner Registry und gibt sie jedem gebauten Embedder mit
+//! ([`crate::ConfiguredEmbedder::with_metrics`]). Alle Reihen tragen die Labels
+//! `provider` und `model`:
+//!
+//! - `embedding_request_duration_seconds` – Dauer je Upstream-Aufruf
+//! - `embedding_failures_total` – fehlgeschlagene Aufrufe nach `error`
+//!   (`request`, `status`, `decode`, `count_mismatch`, `timeout`,
+//!   `circuit_open`, `other`)
+//! - `embedding_texts_total` / `embedding_tokens_total` – verarbeitete Texte und Tokens
+//! - `embedding_cache_requests_total` – Cache-Lookups nach `result` (`hit`/`miss`)
+
+use std::fmt;
+
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet, LabelSetEncoder},
+    metrics::{counter::Counter, family::Family, histogram::Histogram},
+    registry::Registry,
+};
+
+use crate::EmbedError;
+
+/// Upper bounds in seconds; local CPU batches can take several seconds.
+const DURATION_BUCKETS: [f64; 11] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EmbedderLabels {
+    pub provider: &'static str,
+    pub model: String,
+}
+
+impl EncodeLabelSet for EmbedderLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        ("provider", self.provider).encode(encoder.encode_label())?;
+        ("model", self.model.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct OutcomeLabels {
+    embedder: EmbedderLabels,
+    key: &'static str,
+    value: &'static str,
+}
+
+impl EncodeLabelSet for OutcomeLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> Result<(), fmt::Error> {
+        self.embedder.encode(encoder)?;
+        (self.key, self.value).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+fn create_duration_histogram() -> Histogram {
+    Histogram::new(DURATION_BUCKETS)
+}
+
+/// Metric families shared by all embedders; cheap to clone.
+#[derive(Debug, Clone)]
+pub struct EmbeddingMetrics {
+    durations: Family<EmbedderLabels, Histogram>,
+    failures: Family<OutcomeLabels, Counter>,
+    texts: Family<EmbedderLabels, Counter>,
+    tokens: Family<EmbedderLabels, Counter>,
+    cache: Family<OutcomeLabels, Counter>,
+}
+
+impl Default for EmbeddingMetrics {
+    fn default() -> Self {
+        Self {
+            durations: Family::new_with_constructor(create_duration_histogram),
+            failures: Family::default(),
+            texts: Family::default(),
+            tokens: Family::default(),
+            cache: Family::default(),
+        }
+    }
+}
+
+impl EmbeddingMetrics {
+    pub fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "embedding_request_duration_seconds",
+            "Duration of upstream embedding calls by provider and model",
+            self.durations.clone(),
+        );
+        registry.register(
+            "embedding_failures",
+            "Total number of failed embedding calls by provider, model and error",
+            self.failures.clone(),
+        );
+        registry.register(
+            "embedding_texts",
+            "Total number of texts embedded by provider and model",
+            self.texts.clone(),
+        );
+        registry.register(
+            "embedding_tokens",
+            "Total number of input tokens processed by provider and model",
+            self.tokens.clone(),
+        );
+        registry.register(
+            "embedding_cache_requests",
+            "Total number of embedding cache lookups by provider, model and result (hit/miss)",
+            self.cache.clone(),
+        );
+    }
+
+    pub(crate) fn observe_call(&self, labels: &EmbedderLabels, seconds: f64) {
+        self.durations.get_or_create(labels).observe(seconds);
+    }
+
+    pub(crate) fn record_success(&self, labels: &EmbedderLabels, texts: usize, tokens: u64) {
+        self.texts.get_or_create(labels).inc_by(texts as u64);
+        self.tokens.get_or_create(labels).inc_by(tokens);
+    }
+
+    pub(crate) fn record_failure(&self, labels: &EmbedderLabels, error: &anyhow::Error) {
+        self.failures
+            .get_or_create(&OutcomeLabels {
+                embedder: labels.clone(),
+                key: "error",
+                value: error_label(error),
+            })
+            .inc();
+    }
+
+    pub(crate) fn record_cache(&self, labels: &EmbedderLabels, hits: usize, misses: usize) {
+        for (value, count) in [("hit", hits), ("miss", misses)] {
+            if count > 0 {
+                self.cache
+                    .get_or_create(&OutcomeLabels {
+                        embedder: labels.clone(),
+                        key: "result",
+                        value,
+                    })
+                    .inc_by(count as u64);
+            }
+        }
+    }
+}
+
+fn error_label(error: &anyhow::Error) -> &'static str {
+    match error.downcast_ref::<EmbedError>() {
+        Some(EmbedError::Request { .. }) => "request",
+        Some(EmbedError::Status { .. }) => "status",
+        Some(EmbedError::Decode(_)) => "decode",
+        Some(EmbedError::CountMismatch { .. }) => "count_mismatch",
+        Some(EmbedError::Timeout(_)) => "timeout",
+        Some(EmbedError::CircuitOpen { .. }) => "circuit_open",
+        Some(EmbedError::Denied { .. }) => "denied",
+        None => "other",
+    }
+}
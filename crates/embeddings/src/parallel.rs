@@ -0,0 +1,227 @@
+//! Parallele Embedding-Berechnung für Bulk-Ingest.
+//!
+//! [`embed_parallel`] teilt die Eingaben in Batches (höchstens
+//! [`Embedder::max_batch_size`] Texte) und legt sie in eine gemeinsame Queue.
+//! `concurrency` Worker holen sich jeweils den nächsten offenen Batch, sobald
+//! sie frei sind – langsame Batches blockieren so keine anderen Worker. Die
+//! Obergrenze hält die GPU ausgelastet, ohne Ollama mit Anfragen zu fluten;
+//! konfiguriert wird sie pro Embedder über `concurrency` in
+//! `configs/models.yml`.
+//!
+//! Das Ergebnis enthält neben den Vektoren (in Eingabereihenfolge) einen
+//! [`EmbedThroughput`] mit Durchsatzkennzahlen des Laufs.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::task::JoinSet;
+
+use crate::{check_count, BatchFailure, Embedder, PartialEmbeddings};
+
+/// Default number of batches embedded at the same time.
+pub const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Throughput figures of one [`embed_parallel`] run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EmbedThroughput {
+    pub texts: usize,
+    pub batches: usize,
+    pub failed_batches: usize,
+    /// Workers actually started (at most one per batch).
+    pub concurrency: usize,
+    /// Batches processed per worker, indexed by worker.
+    pub batches_per_worker: Vec<usize>,
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+impl EmbedThroughput {
+    /// Successfully embedded texts per second of wall-clock time.
+    pub fn texts_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.texts as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    value: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(u64::try_from(value.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Embeds `texts` with up to `concurrency` batches in flight (values below 1
+/// are treated as 1). Like [`Embedder::embed_partial`], failed batches are
+/// reported instead of aborting the run.
+pub async fn embed_parallel<E>(
+    embedder: &E,
+    texts: Vec<String>,
+    concurrency: usize,
+) -> (PartialEmbeddings, EmbedThroughput)
+where
+    E: Embedder + Clone + 'static,
+{
+    let started = Instant::now();
+    let batch_size = embedder.max_batch_size().max(1);
+    let batches = texts.len().div_ceil(batch_size);
+    let workers = concurrency.max(1).min(batches);
+    let texts = Arc::new(texts);
+    let next = Arc::new(AtomicUsize::new(0));
+
+    let mut set = JoinSet::new();
+    for worker in 0..workers {
+        let embedder = embedder.clone();
+        let texts = texts.clone();
+        let next = next.clone();
+        set.spawn(async move {
+            let mut done = Vec::new();
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= batches {
+                    break;
+                }
+                let inputs = index * batch_size..((index + 1) * batch_size).min(texts.len());
+                let batch = &texts[inputs.clone()];
+                let outcome = match embedder.embed_batch_tagged(batch).await {
+                    Ok((vectors, tag)) => check_count(batch, &vectors).map(|()| (vectors, tag)),
+                    Err(err) => Err(err),
+                };
+                done.push((index, inputs, outcome));
+            }
+            (worker, done)
+        });
+    }
+
+    let mut result = PartialEmbeddings {
+        vectors: vec![None; texts.len()],
+        produced_by: vec![None; texts.len()],
+        failures: Vec::new(),
+    };
+    let mut throughput = EmbedThroughput {
+        batches,
+        concurrency: workers,
+        batches_per_worker: vec![0; workers],
+        ..EmbedThroughput::default()
+    };
+    while let Some(joined) = set.join_next().await {
+        let (worker, done) = match joined {
+            Ok(done) => done,
+            // A panicking worker loses its batches; they show up as gaps below.
+            Err(_) => continue,
+        };
+        throughput.batches_per_worker[worker] = done.len();
+        for (index, inputs, outcome) in done {
+            match outcome {
+                Ok((vectors, tag)) => {
+                    throughput.texts += vectors.len();
+                    for (slot, vector) in result.vectors[inputs.clone()].iter_mut().zip(vectors) {
+                        *slot = Some(vector);
+                    }
+                    result.produced_by[inputs].fill(tag);
+                }
+                Err(error) => result.failures.push(BatchFailure {
+                    batch: index,
+                    inputs,
+                    error,
+                }),
+            }
+        }
+    }
+    for index in 0..batches {
+        let inputs = index * batch_size..((index + 1) * batch_size).min(texts.len());
+        let missing = result.vectors[inputs.clone()].iter().any(Option::is_none);
+        if missing && !result.failures.iter().any(|failure| failure.batch == index) {
+            result.failures.push(BatchFailure {
+                batch: index,
+                inputs,
+                error: anyhow::anyhow!("embedding worker panicked"),
+            });
+        }
+    }
+    result.failures.sort_by_key(|failure| failure.batch);
+    throughput.failed_batches = result.failures.len();
+    throughput.elapsed = started.elapsed();
+    (result, throughput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::sync::Mutex;
+
+    /// Echoes text lengths, fails on texts starting with `fail` and tracks
+    /// how many batches run at the same time.
+    #[derive(Clone, Default)]
+    struct Probe {
+        in_flight: Arc<AtomicUsize>,
+        peak: Arc<Mutex<usize>>,
+    }
+
+    impl Embedder for Probe {
+        fn max_batch_size(&self) -> usize {
+            2
+        }
+
+        async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            {
+                let mut peak = self.peak.lock().unwrap();
+                *peak = (*peak).max(now);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            if batch.iter().any(|text| text.starts_with("fail")) {
+                anyhow::bail!("upstream rejected batch");
+            }
+            Ok(batch.iter().map(|text| vec![text.len() as f32]).collect())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn runs_batches_concurrently_up_to_the_limit_and_keeps_order() {
+        let probe = Probe::default();
+        let texts: Vec<String> = (1..=10).map(|n| "x".repeat(n)).collect();
+        let (result, throughput) = embed_parallel(&probe, texts, 3).await;
+
+        assert!(result.is_complete());
+        let lengths: Vec<f32> = result
+            .into_complete()
+            .unwrap()
+            .into_iter()
+            .map(|vector| vector[0])
+            .collect();
+        assert_eq!(lengths, (1..=10).map(|n| n as f32).collect::<Vec<_>>());
+        let peak = *probe.peak.lock().unwrap();
+        assert!(peak > 1 && peak <= 3, "peak concurrency {peak}");
+        assert_eq!(throughput.batches, 5);
+        assert_eq!(throughput.concurrency, 3);
+        assert_eq!(throughput.texts, 10);
+        assert_eq!(throughput.batches_per_worker.iter().sum::<usize>(), 5);
+        assert!(throughput.texts_per_second() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn failed_batches_leave_gaps_and_are_counted() {
+        let texts = ["a", "b", "fail", "c", "d"].map(String::from).to_vec();
+        let (result, throughput) = embed_parallel(&Probe::default(), texts, 8).await;
+
+        assert_eq!(throughput.concurrency, 3);
+        assert_eq!(throughput.failed_batches, 1);
+        assert_eq!(throughput.texts, 3);
+        assert_eq!(result.failures[0].batch, 1);
+        assert_eq!(result.failures[0].inputs, 2..4);
+        assert!(result.vectors[2].is_none() && result.vectors[3].is_none());
+        assert!(result.vectors[4].is_some());
+    }
+}
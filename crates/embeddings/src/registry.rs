@@ -0,0 +1,605 @@
+//! Registry der Embedder: ID → Provider, Modell, Dimension, Token-Limit und
+//! Normalisierung. Quelle ist der Abschnitt `embedders` in `configs/models.yml`:
+//!
+//! ```yaml
+//! embedders:
+//!   - id: nomic-ollama
+//!     provider: ollama
+//!     model: nomic-embed-text
+//!     url: http://127.0.0.1:11434
+//!     dimension: 768
+//!     max_tokens: 8192
+//!     normalization: l2
+//!     default: true
+//! ```
+//!
+//! Ohne `default: true` gilt der erste Eintrag als Standard. Timeouts, Retries
+//! und Circuit-Breaker: siehe [`crate::resilience`].
+
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    cache::{EmbeddingCache, DEFAULT_CACHE_ENTRIES},
+    check_count,
+    local::LocalEmbedder,
+    metrics::{EmbedderLabels, EmbeddingMetrics},
+    resilience::{is_transient, CircuitBreaker, CircuitBreakerConfig, RetryConfig},
+    Device, EmbedError, Embedder, HttpTransport, Normalization, OllamaEmbedder, Pooling,
+    DEFAULT_CONCURRENCY, DEFAULT_TIMEOUT,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// `POST /api/embed` of an Ollama server (`url`).
+    Ollama,
+    /// In-process BERT encoder from `model_dir`, see [`LocalEmbedder`].
+    Local,
+}
+
+impl Provider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ollama => "ollama",
+            Self::Local => "local",
+        }
+    }
+}
+
+/// One entry of the `embedders` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmbedderSpec {
+    pub id: String,
+    pub provider: Provider,
+    /// Upstream model name (`ollama`) or a descriptive name (`local`).
+    pub model: String,
+    /// Length of the produced vectors.
+    pub dimension: usize,
+    /// Input limit in tokens.
+    pub max_tokens: usize,
+    #[serde(default)]
+    pub normalization: Normalization,
+    /// Token pooling; only for provider `local` (default `mean`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pooling: Option<Pooling>,
+    /// Compute device; only for provider `local` (default `auto`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<Device>,
+    /// Base URL of the Ollama server (provider `ollama`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Directory with `config.json`, `model.safetensors` and `tokenizer.json`
+    /// or `vocab.txt` (provider `local`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+    /// Timeout per upstream call (default 60 s).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// In a fallback chain: time after which the next embedder takes over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_budget_ms: Option<u64>,
+    /// Texts per upstream call (default 32).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+    /// Vectors kept in the in-memory cache (default 1024, 0 = off).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_entries: Option<usize>,
+    /// Batches embedded at the same time during bulk ingestion (default 2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("embedder id {0:?} is configured more than once")]
+    DuplicateId(String),
+    #[error("embedder {0:?}: dimension must be greater than 0")]
+    ZeroDimension(String),
+    #[error("embedder {0:?}: provider ollama requires a valid url")]
+    InvalidUrl(String),
+    #[error("embedder {0:?}: provider local requires model_dir")]
+    MissingModelDir(String),
+    #[error("embedder {0:?}: pooling is only configurable for provider local")]
+    PoolingUnsupported(String),
+    #[error("embedder {0:?}: device is only configurable for provider local")]
+    DeviceUnsupported(String),
+    #[error("more than one embedder is marked as default")]
+    MultipleDefaults,
+    #[error("unknown embedder {0:?}")]
+    UnknownEmbedder(String),
+    #[error("embedder {id:?} produces {expected}-dimensional vectors, got {got}")]
+    DimensionMismatch {
+        id: String,
+        expected: usize,
+        got: usize,
+    },
+}
+
+impl EmbedderSpec {
+    fn check(&self) -> Result<(), RegistryError> {
+        if self.dimension == 0 {
+            return Err(RegistryError::ZeroDimension(self.id.clone()));
+        }
+        match self.provider {
+            Provider::Ollama => {
+                let valid = self
+                    .url
+                    .as_deref()
+                    .is_some_and(|url| Url::parse(url).is_ok());
+                if !valid {
+                    return Err(RegistryError::InvalidUrl(self.id.clone()));
+                }
+                if self.pooling.is_some() {
+                    return Err(RegistryError::PoolingUnsupported(self.id.clone()));
+                }
+                if self.device.is_some() {
+                    return Err(RegistryError::DeviceUnsupported(self.id.clone()));
+                }
+            }
+            Provider::Local => {
+                if self.model_dir.as_deref().is_none_or(str::is_empty) {
+                    return Err(RegistryError::MissingModelDir(self.id.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// Checks that `vector` fits this embedder.
+    pub fn validate(&self, vector: &[f32]) -> Result<(), RegistryError> {
+        if vector.len() != self.dimension {
+            return Err(RegistryError::DimensionMismatch {
+                id: self.id.clone(),
+                expected: self.dimension,
+                got: vector.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Instantiates the provider; local models are loaded from disk and their
+    /// dimension is checked against the spec.
+    pub fn build(&self) -> anyhow::Result<ConfiguredEmbedder> {
+        self.check()?;
+        let backend = match self.provider {
+            Provider::Ollama => {
+                let url = Url::parse(self.url.as_deref().unwrap_or_default())?;
+                let mut embedder =
+                    OllamaEmbedder::new(url, self.model.clone()).with_timeout(self.timeout());
+                if let Some(batch_size) = self.batch_size {
+                    embedder = embedder.with_batch_size(batch_size);
+                }
+                Backend::Ollama(embedder)
+            }
+            Provider::Local => {
+                let mut embedder = LocalEmbedder::load_on(
+                    self.model_dir.as_deref().unwrap_or_default(),
+                    self.device.unwrap_or_default(),
+                )?
+                .with_max_tokens(self.max_tokens)
+                .with_pooling(self.pooling.unwrap_or_default())
+                .with_normalization(Normalization::None);
+                if let Some(batch_size) = self.batch_size {
+                    embedder = embedder.with_batch_size(batch_size);
+                }
+                if embedder.dimension() != self.dimension {
+                    return Err(RegistryError::DimensionMismatch {
+                        id: self.id.clone(),
+                        expected: self.dimension,
+                        got: embedder.dimension(),
+                    }
+                    .into());
+                }
+                Backend::Local(embedder)
+            }
+        };
+        Ok(ConfiguredEmbedder {
+            id: self.id.clone(),
+            labels: EmbedderLabels {
+                provider: self.provider.as_str(),
+                model: self.model.clone(),
+            },
+            normalization: self.normalization,
+            concurrency: self.concurrency(),
+            latency_budget: self.latency_budget_ms.map(Duration::from_millis),
+            timeout: self.timeout(),
+            retry: self.retry,
+            breaker: CircuitBreaker::new(self.circuit_breaker),
+            cache: EmbeddingCache::new(self.cache_entries.unwrap_or(DEFAULT_CACHE_ENTRIES)),
+            metrics: EmbeddingMetrics::default(),
+            backend,
+        })
+    }
+}
+
+/// Validated set of embedder specs.
+#[derive(Debug, Clone, Default)]
+pub struct EmbedderRegistry {
+    specs: Vec<EmbedderSpec>,
+    default: usize,
+}
+
+impl EmbedderRegistry {
+    pub fn new(specs: Vec<EmbedderSpec>) -> Result<Self, RegistryError> {
+        for (index, spec) in specs.iter().enumerate() {
+            spec.check()?;
+            if specs[..index].iter().any(|other| other.id == spec.id) {
+                return Err(RegistryError::DuplicateId(spec.id.clone()));
+            }
+        }
+        let mut defaults = specs.iter().enumerate().filter(|(_, spec)| spec.default);
+        let default = defaults.next().map(|(index, _)| index).unwrap_or(0);
+        if defaults.next().is_some() {
+            return Err(RegistryError::MultipleDefaults);
+        }
+        Ok(Self { specs, default })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EmbedderSpec> {
+        self.specs.iter()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&EmbedderSpec> {
+        self.specs.iter().find(|spec| spec.id == id)
+    }
+
+    /// The entry marked `default: true`, otherwise the first one.
+    pub fn default_spec(&self) -> Option<&EmbedderSpec> {
+        self.specs.get(self.default)
+    }
+
+    /// Members of a fallback chain in order; an empty `ids` list means just
+    /// the default embedder.
+    pub fn chain(&self, ids: &[String]) -> Result<Vec<&EmbedderSpec>, RegistryError> {
+        if ids.is_empty() {
+            return Ok(self.default_spec().into_iter().collect());
+        }
+        ids.iter()
+            .map(|id| {
+                self.get(id)
+                    .ok_or_else(|| RegistryError::UnknownEmbedder(id.clone()))
+            })
+            .collect()
+    }
+
+    /// Checks that `vector` was plausibly produced by embedder `id`.
+    pub fn validate(&self, id: &str, vector: &[f32]) -> Result<(), RegistryError> {
+        self.get(id)
+            .ok_or_else(|| RegistryError::UnknownEmbedder(id.to_string()))?
+            .validate(vector)
+    }
+}
+
+/// An embedder built from an [`EmbedderSpec`]: applies the spec's timeout,
+/// retry and circuit-breaker settings to every batch and its normalization to
+/// every vector, whatever the provider returns. Texts seen before are served
+/// from the cache.
+#[derive(Debug, Clone)]
+pub struct ConfiguredEmbedder {
+    id: String,
+    labels: EmbedderLabels,
+    normalization: Normalization,
+    concurrency: usize,
+    latency_budget: Option<Duration>,
+    timeout: Duration,
+    retry: RetryConfig,
+    breaker: CircuitBreaker,
+    cache: EmbeddingCache,
+    metrics: EmbeddingMetrics,
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Ollama(OllamaEmbedder),
+    Local(LocalEmbedder),
+}
+
+impl ConfiguredEmbedder {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Batches in flight for [`crate::embed_parallel`].
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Budget per batch before a fallback chain moves on (`latency_budget_ms`).
+    pub fn latency_budget(&self) -> Option<Duration> {
+        self.latency_budget
+    }
+
+    /// True while the circuit breaker rejects calls.
+    pub fn is_circuit_open(&self) -> bool {
+        self.breaker.is_open()
+    }
+
+    /// True if `text` would be served from the cache without an upstream call.
+    pub fn is_cached(&self, text: &str) -> bool {
+        self.cache.get(text).is_some()
+    }
+
+    /// Number of vectors currently cached.
+    pub fn cached_entries(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Records calls in the shared metric families instead of private ones.
+    pub fn with_metrics(mut self, metrics: EmbeddingMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Sends the requests of remote providers through `transport`; local
+    /// models are unaffected.
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        if let Backend::Ollama(embedder) = self.backend {
+            self.backend = Backend::Ollama(embedder.with_transport(transport));
+        }
+        self
+    }
+
+    /// One upstream call, timed and counted.
+    async fn call_backend(&self, batch: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let started = std::time::Instant::now();
+        let result = match &self.backend {
+            // The HTTP client enforces the timeout itself.
+            Backend::Ollama(embedder) => embedder.embed_batch_with_usage(batch).await,
+            Backend::Local(embedder) => {
+                match tokio::time::timeout(self.timeout, embedder.embed_batch(batch)).await {
+                    Ok(result) => result.map(|vectors| {
+                        let tokens = embedder.count_tokens(batch) as u64;
+                        (vectors, Some(tokens))
+                    }),
+                    Err(_) => Err(EmbedError::Timeout(self.timeout).into()),
+                }
+            }
+        };
+        self.metrics
+            .observe_call(&self.labels, started.elapsed().as_secs_f64());
+        match result {
+            Ok((vectors, tokens)) => {
+                self.metrics
+                    .record_success(&self.labels, vectors.len(), tokens.unwrap_or(0));
+                Ok(vectors)
+            }
+            Err(err) => {
+                self.metrics.record_failure(&self.labels, &err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Embeds `batch` upstream with retries and circuit breaker.
+    async fn embed_uncached(&self, batch: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if let Err(err) = self.breaker.check() {
+            let err = anyhow::Error::from(err);
+            self.metrics.record_failure(&self.labels, &err);
+            return Err(err);
+        }
+        let max_attempts = self.retry.max_attempts.max(1);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.call_backend(batch).await {
+                Ok(mut vectors) => {
+                    self.breaker.record_success();
+                    for vector in &mut vectors {
+                        self.normalization.apply(vector);
+                    }
+                    return Ok(vectors);
+                }
+                Err(err) if attempt < max_attempts && is_transient(&err) => {
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                }
+                Err(err) => {
+                    self.breaker.record_failure();
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+impl Embedder for ConfiguredEmbedder {
+    fn max_batch_size(&self) -> usize {
+        match &self.backend {
+            Backend::Ollama(embedder) => embedder.max_batch_size(),
+            Backend::Local(embedder) => embedder.max_batch_size(),
+        }
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if !self.cache.is_enabled() {
+            return self.embed_uncached(batch).await;
+        }
+        let mut vectors: Vec<Option<Vec<f32>>> =
+            batch.iter().map(|text| self.cache.get(text)).collect();
+        let missing: Vec<String> = batch
+            .iter()
+            .zip(&vectors)
+            .filter(|(_, vector)| vector.is_none())
+            .map(|(text, _)| text.clone())
+            .collect();
+        self.metrics
+            .record_cache(&self.labels, batch.len() - missing.len(), missing.len());
+        if !missing.is_empty() {
+            let computed = self.embed_uncached(&missing).await?;
+            check_count(&missing, &computed)?;
+            let mut computed = missing.iter().zip(computed);
+            for slot in vectors.iter_mut().filter(|slot| slot.is_none()) {
+                if let Some((text, vector)) = computed.next() {
+                    self.cache.insert(text, &vector);
+                    *slot = Some(vector);
+                }
+            }
+        }
+        Ok(vectors.into_iter().flatten().collect())
+    }
+
+    async fn embed_batch_tagged(
+        &self,
+        batch: &[String],
+    ) -> anyhow::Result<(Vec<Vec<f32>>, Option<String>)> {
+        let vectors = self.embed_batch(batch).await?;
+        Ok((vectors, Some(self.id.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(id: &str) -> EmbedderSpec {
+        EmbedderSpec {
+            id: id.into(),
+            provider: Provider::Ollama,
+            model: "nomic-embed-text".into(),
+            dimension: 3,
+            max_tokens: 8192,
+            normalization: Normalization::L2,
+            url: Some("http://127.0.0.1:11434".into()),
+            model_dir: None,
+            pooling: None,
+            device: None,
+            default: false,
+            timeout_ms: None,
+            retry: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            latency_budget_ms: None,
+            batch_size: None,
+            cache_entries: None,
+            concurrency: None,
+        }
+    }
+
+    #[test]
+    fn registry_parses_yaml_and_picks_default() {
+        let specs: Vec<EmbedderSpec> = serde_yaml_ng::from_str(
+            r#"
+- id: minilm
+  provider: local
+  model: all-MiniLM-L6-v2
+  model_dir: ./models/all-MiniLM-L6-v2
+  dimension: 384
+  max_tokens: 256
+  normalization: l2
+  pooling: cls
+  device: cpu
+- id: nomic
+  provider: ollama
+  model: nomic-embed-text
+  url: http://127.0.0.1:11434
+  dimension: 768
+  max_tokens: 8192
+  default: true
+"#,
+        )
+        .unwrap();
+        let registry = EmbedderRegistry::new(specs).unwrap();
+        assert_eq!(registry.default_spec().unwrap().id, "nomic");
+        assert_eq!(registry.get("minilm").unwrap().provider, Provider::Local);
+        assert_eq!(registry.get("minilm").unwrap().pooling, Some(Pooling::Cls));
+        assert_eq!(registry.get("minilm").unwrap().device, Some(Device::Cpu));
+        assert_eq!(
+            registry.get("nomic").unwrap().normalization,
+            Normalization::None
+        );
+        assert!(registry.validate("nomic", &[0.0; 768]).is_ok());
+        assert_eq!(
+            registry.validate("nomic", &[0.0; 384]),
+            Err(RegistryError::DimensionMismatch {
+                id: "nomic".into(),
+                expected: 768,
+                got: 384
+            })
+        );
+        assert_eq!(
+            registry.validate("bge", &[0.0; 384]),
+            Err(RegistryError::UnknownEmbedder("bge".into()))
+        );
+    }
+
+    #[test]
+    fn registry_rejects_inconsistent_specs() {
+        assert_eq!(
+            EmbedderRegistry::new(vec![spec("a"), spec("a")]).err(),
+            Some(RegistryError::DuplicateId("a".into()))
+        );
+        let mut zero = spec("zero");
+        zero.dimension = 0;
+        assert_eq!(
+            EmbedderRegistry::new(vec![zero]).err(),
+            Some(RegistryError::ZeroDimension("zero".into()))
+        );
+        let mut pooled = spec("pooled");
+        pooled.pooling = Some(Pooling::Cls);
+        assert_eq!(
+            EmbedderRegistry::new(vec![pooled]).err(),
+            Some(RegistryError::PoolingUnsupported("pooled".into()))
+        );
+        let mut gpu = spec("gpu");
+        gpu.device = Some(Device::Cuda);
+        assert_eq!(
+            EmbedderRegistry::new(vec![gpu]).err(),
+            Some(RegistryError::DeviceUnsupported("gpu".into()))
+        );
+        let mut local = spec("local");
+        local.provider = Provider::Local;
+        assert_eq!(
+            EmbedderRegistry::new(vec![local]).err(),
+            Some(RegistryError::MissingModelDir("local".into()))
+        );
+        let (mut first, mut second) = (spec("a"), spec("b"));
+        first.default = true;
+        second.default = true;
+        assert_eq!(
+            EmbedderRegistry::new(vec![first, second]).err(),
+            Some(RegistryError::MultipleDefaults)
+        );
+        let registry = EmbedderRegistry::new(vec![spec("a"), spec("b")]).unwrap();
+        assert_eq!(registry.default_spec().unwrap().id, "a");
+        let ids = |chain: Vec<&EmbedderSpec>| -> Vec<String> {
+            chain.into_iter().map(|spec| spec.id.clone()).collect()
+        };
+        assert_eq!(ids(registry.chain(&[]).unwrap()), ["a"]);
+        assert_eq!(
+            ids(registry.chain(&["b".into(), "a".into()]).unwrap()),
+            ["b", "a"]
+        );
+        assert_eq!(
+            registry.chain(&["c".into()]).err(),
+            Some(RegistryError::UnknownEmbedder("c".into()))
+        );
+        assert!(EmbedderRegistry::default().default_spec().is_none());
+    }
+}
@@ -0,0 +1,228 @@
+//! Timeouts, Retries mit Jitter und Circuit-Breaker für Embedding-Aufrufe.
+//!
+//! Konfiguration pro Embedder in `configs/models.yml`:
+//!
+//! ```yaml
+//! embedders:
+//!   - id: nomic-ollama
+//!     # …
+//!     timeout_ms: 30000
+//!     retry: { max_attempts: 3, base_delay_ms: 200, max_delay_ms: 5000 }
+//!     circuit_breaker: { failure_threshold: 5, open_secs: 30 }
+//! ```
+//!
+//! Wiederholt werden nur vorübergehende Fehler (Netzwerk, Timeout, 408, 429,
+//! 5xx). Nach `failure_threshold` fehlgeschlagenen Aufrufen in Folge lehnt der
+//! Breaker `open_secs` lang sofort ab; danach darf ein Probeaufruf durch.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::EmbedError;
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub const DEFAULT_BASE_DELAY_MS: u64 = 200;
+pub const DEFAULT_MAX_DELAY_MS: u64 = 5_000;
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+pub const DEFAULT_OPEN_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct RetryConfig {
+    /// Attempts per batch including the first one (at least 1).
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay_ms: DEFAULT_BASE_DELAY_MS,
+            max_delay_ms: DEFAULT_MAX_DELAY_MS,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Full-jitter backoff after the `attempt`-th failure: uniform in
+    /// `0..=min(max_delay, base · 2^(attempt-1))`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let ceiling = self
+            .base_delay_ms
+            .saturating_mul(factor)
+            .min(self.max_delay_ms);
+        Duration::from_millis(fastrand::u64(0..=ceiling))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed calls that open the breaker; 0 disables it.
+    pub failure_threshold: u32,
+    /// How long an open breaker rejects calls.
+    pub open_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            open_secs: DEFAULT_OPEN_SECS,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Shared between clones of an embedder, so all callers see the same state.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Rejects the call while the breaker is open. Once the open period is
+    /// over, one trial call passes; further calls wait for its outcome.
+    pub fn check(&self) -> Result<(), EmbedError> {
+        let mut state = self.lock();
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < open_until {
+            return Err(EmbedError::CircuitOpen {
+                retry_in: open_until - now,
+            });
+        }
+        // Half-open: let this call through, keep others out for another period.
+        state.open_until = Some(now + Duration::from_secs(self.config.open_secs));
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        *self.lock() = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.config.failure_threshold {
+            state.open_until = Some(Instant::now() + Duration::from_secs(self.config.open_secs));
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.lock()
+            .open_until
+            .is_some_and(|open_until| Instant::now() < open_until)
+    }
+}
+
+/// Whether a failed call may succeed when repeated.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<EmbedError>() {
+        Some(EmbedError::Request { .. } | EmbedError::Timeout(_)) => true,
+        Some(EmbedError::Status { status, .. }) => {
+            *status == 408 || *status == 429 || (500..600).contains(status)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_is_jittered_below_exponential_ceiling() {
+        let retry = RetryConfig {
+            max_attempts: 5,
+            base_delay_ms: 100,
+            max_delay_ms: 250,
+        };
+        for _ in 0..50 {
+            assert!(retry.delay(1) <= Duration::from_millis(100));
+            assert!(retry.delay(2) <= Duration::from_millis(200));
+            assert!(retry.delay(4) <= Duration::from_millis(250));
+        }
+    }
+
+    #[test]
+    fn breaker_opens_after_threshold_and_lets_one_trial_through() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_secs: 0,
+        });
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        // open_secs = 0: the open period is already over, the next call is the trial.
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert!(!breaker.is_open());
+
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 1,
+            open_secs: 60,
+        });
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(matches!(
+            breaker.check(),
+            Err(EmbedError::CircuitOpen { .. })
+        ));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let status = |status| {
+            anyhow::Error::from(EmbedError::Status {
+                status,
+                message: String::new(),
+            })
+        };
+        assert!(is_transient(&status(503)));
+        assert!(is_transient(&status(429)));
+        assert!(!is_transient(&status(404)));
+        assert!(is_transient(
+            &EmbedError::Timeout(Duration::from_secs(1)).into()
+        ));
+        assert!(!is_transient(
+            &EmbedError::CountMismatch {
+                expected: 1,
+                got: 0
+            }
+            .into()
+        ));
+        assert!(!is_transient(&anyhow::anyhow!("local failure")));
+    }
+}
@@ -0,0 +1,57 @@
+//! Golden-Test des lokalen Embedders gegen Referenzvektoren des echten Modells.
+//!
+//! Der Test ist als `#[ignore]` markiert, weil er ein heruntergeladenes Modell
+//! und die mit sentence-transformers erzeugte `golden.json` braucht:
+//!
+//! ```bash
+//! git clone https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2 models/all-MiniLM-L6-v2
+//! python3 scripts/embeddings-golden.py models/all-MiniLM-L6-v2
+//! HAUSKI_TEST_EMBED_MODEL_DIR=models/all-MiniLM-L6-v2 \
+//!   cargo test -p hauski-embeddings --test golden -- --ignored
+//! ```
+//!
+//! `HAUSKI_TEST_EMBED_DEVICE` (`cpu`, `cuda`, `metal`, `auto`; Default `cpu`)
+//! prüft dieselben Vektoren auf der GPU.
+
+use std::{fs, path::PathBuf};
+
+use hauski_embeddings::{local::LocalEmbedder, Device};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Golden {
+    texts: Vec<String>,
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[test]
+#[ignore] // braucht ein echtes Modell, siehe Moduldoku
+fn local_embedder_matches_sentence_transformers() {
+    let model_dir = PathBuf::from(
+        std::env::var("HAUSKI_TEST_EMBED_MODEL_DIR")
+            .expect("HAUSKI_TEST_EMBED_MODEL_DIR must point to the model directory"),
+    );
+    let device: Device = std::env::var("HAUSKI_TEST_EMBED_DEVICE")
+        .map(|device| serde_json::from_value(serde_json::Value::String(device)).unwrap())
+        .unwrap_or(Device::Cpu);
+    let golden: Golden =
+        serde_json::from_slice(&fs::read(model_dir.join("golden.json")).unwrap()).unwrap();
+
+    let embedder = LocalEmbedder::load_on(&model_dir, device).unwrap();
+    let vectors = embedder.embed_blocking(&golden.texts).unwrap();
+
+    assert_eq!(vectors.len(), golden.embeddings.len());
+    for ((text, actual), expected) in golden.texts.iter().zip(&vectors).zip(&golden.embeddings) {
+        assert_eq!(actual.len(), expected.len(), "{text}");
+        let cosine: f32 = actual.iter().zip(expected).map(|(a, b)| a * b).sum();
+        let max_diff = actual
+            .iter()
+            .zip(expected)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(
+            cosine > 0.9999 && max_diff < 1e-3,
+            "{text:?}: cosine {cosine}, max diff {max_diff}"
+        );
+    }
+}
@@ -9,6 +9,7 @@ use chrono::{DateTime, Utc};
 use prometheus_client::encoding::EncodeLabelSet;
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
 use serde::{Deserialize, Serialize};
@@ -20,8 +21,8 @@ use std::{
     collections::{BTreeMap, HashMap},
     io,
     path::{Path, PathBuf},
-    sync::Arc,
-    time::Instant,
+    sync::{Arc, Weak},
+    time::{Duration, Instant, SystemTime},
 };
 use thiserror::Error;
 use tokio::sync::RwLock;
@@ -39,6 +40,12 @@ const SNAPSHOT_CANDIDATES_MAX: usize = 50;
 
 pub type MetricsRecorder = dyn Fn(Method, &'static str, StatusCode, Instant) + Send + Sync;
 
+/// Callback invoked after a document was auto-quarantined.
+pub type QuarantineHook = dyn Fn(QuarantineNotice) + Send + Sync;
+
+/// Callback invoked for every [`IndexEvent`].
+pub type IndexObserver = dyn Fn(&IndexEvent) + Send + Sync;
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 struct WeightFactorLabels {
     factor: String, // "trust", "recency", "context"
@@ -64,6 +71,14 @@ pub struct IndexError {
 }
 
 impl IndexError {
+    pub fn not_quarantined(doc_id: &str) -> Self {
+        Self {
+            error: format!("document '{doc_id}' is not in quarantine"),
+            code: "not_quarantined".into(),
+            details: None,
+        }
+    }
+
     pub fn missing_source_ref() -> Self {
         Self {
             error: "source_ref is required for all index entries".into(),
@@ -75,6 +90,86 @@ impl IndexError {
     }
 }
 
+/// Vector length produced by an embedder, used to validate upserted chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorSpec {
+    pub embedder: String,
+    pub dimension: usize,
+    /// Scale stored vectors to unit length (embedder normalization `l2`).
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+#[derive(Debug, Default)]
+struct VectorSpecs {
+    specs: HashMap<String, VectorSpec>,
+    default: Option<String>,
+}
+
+/// Details about an auto-quarantined document, passed to [`QuarantineHook`]s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineNotice {
+    pub doc_id: String,
+    /// Namespace the document was meant for before quarantine.
+    pub original_namespace: String,
+    pub flags: Vec<ContentFlag>,
+    pub trust_level: TrustLevel,
+    pub origin: String,
+}
+
+/// Index mutations and decisions, reported to [`IndexObserver`]s.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum IndexEvent {
+    Upserted {
+        namespace: String,
+        doc_id: String,
+        chunks: usize,
+        quarantined: bool,
+    },
+    Forgotten {
+        namespace: String,
+        doc_ids: Vec<String>,
+        /// Reason given to `/index/forget`; `None` for internal callers.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    RetentionChanged {
+        namespace: String,
+        config: RetentionConfig,
+    },
+    DecisionRecorded {
+        decision_id: String,
+        namespace: String,
+        selected_id: Option<String>,
+        candidates: usize,
+        policy_hash: String,
+    },
+    OutcomeRecorded(DecisionOutcome),
+    PolicyReloaded(PolicyReload),
+    QuarantineReleased {
+        doc_id: String,
+        /// Namespace the document was released into.
+        namespace: String,
+        flags: Vec<ContentFlag>,
+    },
+}
+
+impl IndexEvent {
+    /// Dotted event type, e.g. `index.upserted`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Upserted { .. } => "index.upserted",
+            Self::Forgotten { .. } => "index.forgotten",
+            Self::RetentionChanged { .. } => "index.retention_changed",
+            Self::DecisionRecorded { .. } => "decision.recorded",
+            Self::OutcomeRecorded(_) => "decision.outcome",
+            Self::PolicyReloaded(_) => "index.policy_reloaded",
+            Self::QuarantineReleased { .. } => "index.quarantine_released",
+        }
+    }
+}
+
 /// Trust level for document sources - indicates how much to trust this content
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -100,11 +195,16 @@ impl std::fmt::Display for TrustLevel {
 impl TrustLevel {
     /// Returns the default trust level for a given origin
     pub fn default_for_origin(origin: &str) -> Self {
+        Self::for_known_origin(origin).unwrap_or(TrustLevel::Medium)
+    }
+
+    /// Trust level of the origins indexd knows, `None` for any other origin.
+    pub fn for_known_origin(origin: &str) -> Option<Self> {
         match origin {
-            "chronik" => TrustLevel::High,
-            "osctx" => TrustLevel::Medium,
-            "user" | "external" | "tool" => TrustLevel::Low,
-            _ => TrustLevel::Medium,
+            "chronik" => Some(TrustLevel::High),
+            "osctx" | "asr" | "ingest" => Some(TrustLevel::Medium),
+            "user" | "external" | "tool" => Some(TrustLevel::Low),
+            _ => None,
         }
     }
 }
@@ -235,7 +335,7 @@ pub struct SourceRef {
 }
 
 /// Retention configuration for a namespace
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetentionConfig {
     /// Time-decay half-life in seconds (None = no decay)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -254,6 +354,52 @@ pub struct RetentionConfig {
     pub purge_strategy: Option<PurgeStrategy>,
 }
 
+/// Budget for one namespace from the `namespaces` section of `limits.yaml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NamespaceBudget {
+    /// Maximum number of documents; upserts of new documents beyond it fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_docs: Option<usize>,
+    /// Maximum bytes of chunk text across all documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Retention in place until one is set via `PUT /index/retention/{namespace}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionConfig>,
+    /// Highest trust level documents from unknown origins keep (see
+    /// [`TrustLevel::for_known_origin`]); higher claims are lowered to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_trust: Option<TrustLevel>,
+}
+
+impl NamespaceBudget {
+    /// Rejects zero limits, which would make the namespace unusable.
+    pub fn validate(&self) -> Result<(), String> {
+        let retention = self.retention.as_ref();
+        let zero = [
+            ("max_docs", self.max_docs == Some(0)),
+            ("max_bytes", self.max_bytes == Some(0)),
+            (
+                "retention.half_life_seconds",
+                retention.is_some_and(|r| r.half_life_seconds == Some(0)),
+            ),
+            (
+                "retention.max_items",
+                retention.is_some_and(|r| r.max_items == Some(0)),
+            ),
+            (
+                "retention.max_age_seconds",
+                retention.is_some_and(|r| r.max_age_seconds == Some(0)),
+            ),
+        ];
+        match zero.iter().find(|(_, is_zero)| *is_zero) {
+            Some((field, _)) => Err(format!("{field} must be positive")),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Strategy for purging old items when retention limits are exceeded
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -308,6 +454,49 @@ fn calculate_decay_factor(age_seconds: i64, half_life_seconds: Option<u64>) -> f
     }
 }
 
+fn text_bytes(chunks: &[ChunkPayload]) -> u64 {
+    chunks
+        .iter()
+        .filter_map(|chunk| chunk.text.as_ref())
+        .map(|text| text.len() as u64)
+        .sum()
+}
+
+/// Fails if storing `chunks` as `doc_id` would exceed the namespace budget.
+/// A replaced document no longer counts against it.
+fn check_budget(
+    budget: &NamespaceBudget,
+    namespace: &str,
+    store: &NamespaceStore,
+    doc_id: &str,
+    chunks: &[ChunkPayload],
+) -> Result<(), IndexError> {
+    let others = store.values().filter(|doc| doc.doc_id != doc_id);
+    let exceeded = |limit: &str, max: u64, after: u64| IndexError {
+        error: format!("namespace {namespace} is over its {limit} budget ({after} > {max})"),
+        code: "namespace_budget_exceeded".into(),
+        details: Some(serde_json::json!({
+            "namespace": namespace,
+            "limit": limit,
+            "max": max,
+            "after_upsert": after,
+        })),
+    };
+    if let Some(max_docs) = budget.max_docs {
+        let after = others.clone().count() + 1;
+        if after > max_docs {
+            return Err(exceeded("max_docs", max_docs as u64, after as u64));
+        }
+    }
+    if let Some(max_bytes) = budget.max_bytes {
+        let after = others.map(|doc| text_bytes(&doc.chunks)).sum::<u64>() + text_bytes(chunks);
+        if after > max_bytes {
+            return Err(exceeded("max_bytes", max_bytes, after));
+        }
+    }
+    Ok(())
+}
+
 fn normalize_namespace(input: &str) -> String {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -479,6 +668,137 @@ pub struct PolicyConfig {
     pub hash: String,
     /// Source of the policy configuration (e.g. "loaded_from_disk", "fallback_defaults").
     pub source: String,
+    /// Starts at 1 and increases with every reload that changed the policies.
+    pub version: u64,
+}
+
+/// Result of [`IndexState::reload_policies`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyReload {
+    pub version: u64,
+    pub previous_hash: String,
+    pub policy_hash: String,
+    /// False if the files yielded the active policies; nothing was swapped.
+    pub changed: bool,
+}
+
+/// Stable hash of the policies.
+///
+/// The hash is used solely for drift detection and diagnostics (see PolicyConfig::hash).
+/// It is NOT a cache key or decision identifier, so hash instability on a serialization
+/// failure is acceptable: the fallback bytes keep the hasher going while the warning
+/// signals the anomaly.
+/// Note: serde_json follows the JSON spec, which does not allow NaN or ±infinity.
+/// It will return an error for f32 values that are non-finite, making these
+/// branches reachable in principle (e.g. if policies were loaded from a source
+/// that produced non-finite weights).
+fn compute_policy_hash(trust: &TrustPolicy, context: &ContextPolicy) -> String {
+    let mut hasher = Sha256::new();
+    match serde_json::to_vec(trust) {
+        Ok(bytes) => hasher.update(bytes),
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to serialize trust policy for hashing, using fallback");
+            hasher.update(b"trust-fallback");
+        }
+    }
+    match serde_json::to_vec(context) {
+        Ok(bytes) => hasher.update(bytes),
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to serialize context policy for hashing, using fallback");
+            hasher.update(b"context-fallback");
+        }
+    }
+    let digest = hasher.finalize();
+    digest.iter().fold(
+        String::with_capacity(digest.len() * 2),
+        |mut output, byte| {
+            use std::fmt::Write as _;
+            write!(&mut output, "{byte:02x}")
+                .expect("writing hexadecimal bytes to String cannot fail");
+            output
+        },
+    )
+}
+
+impl PolicyConfig {
+    /// Helper to get weight for a trust level from policy
+    fn trust_weight(&self, trust_level: TrustLevel) -> f32 {
+        let key = trust_level.to_string();
+        let min_weight = self.trust.min_weight;
+
+        // Policy validation ensures all keys exist.
+        // If not found (shouldn't happen with valid policy), fallback to hardcoded default for safety.
+        let weight = self
+            .trust
+            .trust_weights
+            .get(&key)
+            .cloned()
+            .unwrap_or(match trust_level {
+                TrustLevel::High => 1.0,
+                TrustLevel::Medium => 0.7,
+                TrustLevel::Low => 0.3,
+            });
+
+        // Apply minimum floor defined in policy
+        weight.max(min_weight)
+    }
+
+    /// Helper to get context weight from policy
+    ///
+    /// Strategy:
+    /// 1. Look up weight by `namespace`. If present and != 1.0, it wins (Topology).
+    /// 2. If namespace is "default" or its weight is 1.0 (neutral), look up `origin`. If present, it wins (Semantics).
+    /// 3. Fallback to profile `_default`.
+    fn context_weight(
+        &self,
+        namespace: &str,
+        source_ref: Option<&SourceRef>,
+        profile_name: Option<&str>,
+    ) -> f32 {
+        let profile_name = profile_name.unwrap_or("default");
+        let profile = match self.context.profiles.get(profile_name) {
+            Some(p) => p,
+            None => {
+                if profile_name != "default" {
+                    tracing::warn!(profile = %profile_name, "Requested context profile not found, falling back to default");
+                }
+                match self.context.profiles.get("default") {
+                    Some(p) => p,
+                    None => return 1.0,
+                }
+            }
+        };
+
+        // 1. Check namespace
+        let ns_weight = profile
+            .get(namespace)
+            .filter(|&&w| (w - 1.0).abs() > f32::EPSILON);
+
+        // 2. Check origin
+        let origin_weight = if let Some(sr) = source_ref {
+            profile
+                .get(&sr.origin)
+                .filter(|&&w| (w - 1.0).abs() > f32::EPSILON)
+        } else {
+            None
+        };
+
+        // Decision logic:
+        // - Namespace explicit (non-neutral) wins.
+        // - Origin (non-neutral) wins.
+        // - Profile default wins.
+        // - 1.0.
+
+        if let Some(&w) = ns_weight {
+            return w;
+        }
+
+        if let Some(&w) = origin_weight {
+            return w;
+        }
+
+        *profile.get("_default").unwrap_or(&1.0)
+    }
 }
 
 struct IndexInner {
@@ -486,7 +806,13 @@ struct IndexInner {
     metrics: Arc<MetricsRecorder>,
     budget_ms: u64,
     retention_configs: RwLock<HashMap<String, RetentionConfig>>,
-    policies: PolicyConfig,
+    /// Per-namespace limits from the configuration; see `with_namespace_budgets`.
+    namespace_budgets: BTreeMap<String, NamespaceBudget>,
+    /// Swapped as a whole on reload, so a search never mixes two versions.
+    policies: std::sync::RwLock<Arc<PolicyConfig>>,
+    /// (trust_path, context_path) the policies are (re)loaded from.
+    policy_paths: Option<(PathBuf, PathBuf)>,
+    prom_policy_version: Gauge,
     // Prometheus metrics
     prom_weight_applied: Family<WeightFactorLabels, Counter>,
     prom_score_bucket: Histogram,
@@ -496,6 +822,15 @@ struct IndexInner {
     // Decision metrics
     prom_decision_snapshots_total: Counter,
     prom_decision_outcomes_total: Family<OutcomeLabels, Counter>,
+    /// Monotonic per-namespace mutation counter (upsert, forget, retention changes).
+    /// Lets callers (e.g. response caches) detect stale data without subscribing to events.
+    namespace_generations: std::sync::RwLock<HashMap<String, u64>>,
+    /// Notified for every auto-quarantined document (e.g. to emit webhooks).
+    quarantine_hook: std::sync::RwLock<Option<Arc<QuarantineHook>>>,
+    /// Notified for every mutation and decision (e.g. the chronik event bus).
+    observers: std::sync::RwLock<Vec<Arc<IndexObserver>>>,
+    /// Known embedders; empty = embeddings are stored unchecked.
+    vector_specs: std::sync::RwLock<VectorSpecs>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
@@ -517,6 +852,23 @@ struct DocumentRecord {
     ingested_at: DateTime<Utc>,
     /// Content flags indicating potential security or quality issues
     flags: Vec<ContentFlag>,
+    /// Embedder that produced the chunk vectors, if known.
+    embedder: Option<String>,
+    /// Namespace the document was meant for, while it sits in quarantine.
+    quarantined_from: Option<String>,
+}
+
+impl From<&DocumentRecord> for QuarantineEntry {
+    fn from(doc: &DocumentRecord) -> Self {
+        Self {
+            doc_id: doc.doc_id.clone(),
+            original_namespace: doc.quarantined_from.clone(),
+            flags: doc.flags.clone(),
+            source_ref: doc.source_ref.clone(),
+            ingested_at: doc.ingested_at,
+            chunks: doc.chunks.len(),
+        }
+    }
 }
 
 impl IndexState {
@@ -530,10 +882,10 @@ impl IndexState {
         let (trust_policy, context_policy, policy_hash, policy_source) = if let Some((
             trust_path,
             context_path,
-        )) = policy_paths
+        )) = &policy_paths
         {
             // Attempt to load trust policy
-            let (trust, trust_source) = match Self::load_policy::<TrustPolicy>(&trust_path) {
+            let (trust, trust_source) = match Self::load_policy::<TrustPolicy>(trust_path) {
                 Ok(p) => (p, "file"),
                 Err(e) => {
                     tracing::error!(path = %trust_path.display(), error = %e, "Failed to load trust policy, falling back to default");
@@ -542,8 +894,7 @@ impl IndexState {
             };
 
             // Attempt to load context policy
-            let (context, context_source) = match Self::load_policy::<ContextPolicy>(&context_path)
-            {
+            let (context, context_source) = match Self::load_policy::<ContextPolicy>(context_path) {
                 Ok(p) => (p, "file"),
                 Err(e) => {
                     tracing::error!(path = %context_path.display(), error = %e, "Failed to load context policy, falling back to default");
@@ -551,40 +902,7 @@ impl IndexState {
                 }
             };
 
-            // Compute stable hash of policies.
-            // The hash is used solely for drift detection and diagnostics (see PolicyConfig::hash).
-            // It is NOT a cache key or decision identifier, so hash instability on a serialization
-            // failure is acceptable: the fallback bytes keep the hasher going while the warning
-            // signals the anomaly.
-            // Note: serde_json follows the JSON spec, which does not allow NaN or ±infinity.
-            // It will return an error for f32 values that are non-finite, making these
-            // branches reachable in principle (e.g. if policies were loaded from a source
-            // that produced non-finite weights).
-            let mut hasher = Sha256::new();
-            match serde_json::to_vec(&trust) {
-                Ok(bytes) => hasher.update(bytes),
-                Err(e) => {
-                    tracing::warn!(error = ?e, "Failed to serialize trust policy for hashing, using fallback");
-                    hasher.update(b"trust-fallback");
-                }
-            }
-            match serde_json::to_vec(&context) {
-                Ok(bytes) => hasher.update(bytes),
-                Err(e) => {
-                    tracing::warn!(error = ?e, "Failed to serialize context policy for hashing, using fallback");
-                    hasher.update(b"context-fallback");
-                }
-            }
-            let digest = hasher.finalize();
-            let hash = digest.iter().fold(
-                String::with_capacity(digest.len() * 2),
-                |mut output, byte| {
-                    use std::fmt::Write as _;
-                    write!(&mut output, "{byte:02x}")
-                        .expect("writing hexadecimal bytes to String cannot fail");
-                    output
-                },
-            );
+            let hash = compute_policy_hash(&trust, &context);
 
             let source = if trust_source == "file" && context_source == "file" {
                 "loaded_from_disk".to_string()
@@ -620,6 +938,8 @@ impl IndexState {
         // Decision feedback metrics
         let prom_decision_snapshots_total = Counter::default();
         let prom_decision_outcomes_total = Family::<OutcomeLabels, Counter>::default();
+        let prom_policy_version = Gauge::default();
+        prom_policy_version.set(1);
 
         if let Some(registry) = registry {
             registry.register(
@@ -642,6 +962,11 @@ impl IndexState {
                 "Total number of decision outcomes reported",
                 prom_decision_outcomes_total.clone(),
             );
+            registry.register(
+                "decision_policy_version",
+                "Version of the active trust and context policies, increased on every reload",
+                prom_policy_version.clone(),
+            );
         }
 
         Self {
@@ -650,22 +975,72 @@ impl IndexState {
                 metrics,
                 budget_ms,
                 retention_configs: RwLock::new(HashMap::new()),
-                policies: PolicyConfig {
+                namespace_budgets: BTreeMap::new(),
+                policies: std::sync::RwLock::new(Arc::new(PolicyConfig {
                     trust: trust_policy,
                     context: context_policy,
                     hash: policy_hash,
                     source: policy_source,
-                },
+                    version: 1,
+                })),
+                policy_paths,
+                prom_policy_version,
                 prom_weight_applied,
                 prom_score_bucket,
                 decision_snapshots: RwLock::new(HashMap::new()),
                 decision_outcomes: RwLock::new(HashMap::new()),
                 prom_decision_snapshots_total,
                 prom_decision_outcomes_total,
+                namespace_generations: std::sync::RwLock::new(HashMap::new()),
+                quarantine_hook: std::sync::RwLock::new(None),
+                observers: std::sync::RwLock::new(Vec::new()),
+                vector_specs: std::sync::RwLock::new(VectorSpecs::default()),
             }),
         }
     }
 
+    /// Applies the per-namespace budgets of the configuration: their retention
+    /// becomes the namespace's initial retention config, and upserts are held
+    /// to `max_docs`, `max_bytes` and `default_trust`. Invalid budgets are
+    /// logged and skipped. Call right after [`Self::new`], before the state
+    /// is cloned or watched.
+    pub fn with_namespace_budgets(mut self, budgets: BTreeMap<String, NamespaceBudget>) -> Self {
+        let inner = Arc::get_mut(&mut self.inner)
+            .expect("namespace budgets are applied before the index is shared");
+        for (namespace, budget) in budgets {
+            let namespace = normalize_namespace(&namespace);
+            if let Err(error) = budget.validate() {
+                tracing::warn!(%namespace, %error, "invalid namespace budget, skipped");
+                continue;
+            }
+            if let Some(retention) = &budget.retention {
+                inner
+                    .retention_configs
+                    .get_mut()
+                    .insert(namespace.clone(), retention.clone());
+            }
+            inner.namespace_budgets.insert(namespace, budget);
+        }
+        self
+    }
+
+    /// Configured budgets by namespace.
+    pub fn namespace_budgets(&self) -> &BTreeMap<String, NamespaceBudget> {
+        &self.inner.namespace_budgets
+    }
+
+    /// Load errors of the trust and context policy files, which [`Self::new`]
+    /// would replace with defaults.
+    pub fn check_policy_files(trust_path: &Path, context_path: &Path) -> Vec<String> {
+        let trust = Self::load_policy::<TrustPolicy>(trust_path)
+            .err()
+            .map(|err| format!("trust policy {}: {err}", trust_path.display()));
+        let context = Self::load_policy::<ContextPolicy>(context_path)
+            .err()
+            .map(|err| format!("context policy {}: {err}", context_path.display()));
+        trust.into_iter().chain(context).collect()
+    }
+
     fn load_policy<T: for<'de> Deserialize<'de> + Default + ValidatePolicy>(
         path: &Path,
     ) -> Result<T, PolicyLoadError> {
@@ -675,97 +1050,264 @@ impl IndexState {
         Ok(policy)
     }
 
-    /// Helper to get weight for a trust level from policy
-    fn get_trust_weight(&self, trust_level: TrustLevel) -> f32 {
-        let key = trust_level.to_string();
-        let min_weight = self.inner.policies.trust.min_weight;
+    /// Currently active policies.
+    pub fn policies(&self) -> Arc<PolicyConfig> {
+        self.inner
+            .policies
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 
-        // Policy validation ensures all keys exist.
-        // If not found (shouldn't happen with valid policy), fallback to hardcoded default for safety.
-        let weight = self
+    pub fn policy_hash(&self) -> String {
+        self.policies().hash.clone()
+    }
+
+    /// Re-reads the trust and context policies from their files. Both must
+    /// load and validate; otherwise the active policies stay untouched.
+    pub fn reload_policies(&self) -> Result<PolicyReload, IndexError> {
+        let Some((trust_path, context_path)) = &self.inner.policy_paths else {
+            return Err(IndexError {
+                error: "no policy files configured".into(),
+                code: "policy_files_unconfigured".into(),
+                details: None,
+            });
+        };
+        let invalid = |path: &Path, e: PolicyLoadError| IndexError {
+            error: format!("policy {} rejected: {e}", path.display()),
+            code: "invalid_policy".into(),
+            details: Some(serde_json::json!({ "path": path.display().to_string() })),
+        };
+        let trust =
+            Self::load_policy::<TrustPolicy>(trust_path).map_err(|e| invalid(trust_path, e))?;
+        let context = Self::load_policy::<ContextPolicy>(context_path)
+            .map_err(|e| invalid(context_path, e))?;
+        let hash = compute_policy_hash(&trust, &context);
+
+        let mut policies = self
             .inner
             .policies
-            .trust
-            .trust_weights
-            .get(&key)
-            .cloned()
-            .unwrap_or(match trust_level {
-                TrustLevel::High => 1.0,
-                TrustLevel::Medium => 0.7,
-                TrustLevel::Low => 0.3,
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let previous_hash = policies.hash.clone();
+        if hash == previous_hash {
+            return Ok(PolicyReload {
+                version: policies.version,
+                previous_hash,
+                policy_hash: hash,
+                changed: false,
             });
-
-        // Apply minimum floor defined in policy
-        weight.max(min_weight)
+        }
+        let version = policies.version + 1;
+        *policies = Arc::new(PolicyConfig {
+            trust,
+            context,
+            hash: hash.clone(),
+            source: "reloaded_from_disk".to_string(),
+            version,
+        });
+        drop(policies);
+        self.inner.prom_policy_version.set(version as i64);
+
+        let reload = PolicyReload {
+            version,
+            previous_hash,
+            policy_hash: hash,
+            changed: true,
+        };
+        tracing::info!(
+            version,
+            previous_hash = %reload.previous_hash,
+            policy_hash = %reload.policy_hash,
+            "Decision weighting policies reloaded"
+        );
+        self.notify(IndexEvent::PolicyReloaded(reload.clone()));
+        Ok(reload)
     }
 
-    /// Helper to get context weight from policy
-    ///
-    /// Strategy:
-    /// 1. Look up weight by `namespace`. If present and != 1.0, it wins (Topology).
-    /// 2. If namespace is "default" or its weight is 1.0 (neutral), look up `origin`. If present, it wins (Semantics).
-    /// 3. Fallback to profile `_default`.
-    fn get_context_weight(
-        &self,
-        namespace: &str,
-        source_ref: Option<&SourceRef>,
-        profile_name: Option<&str>,
-    ) -> f32 {
-        let profile_name = profile_name.unwrap_or("default");
-        let profile = match self.inner.policies.context.profiles.get(profile_name) {
-            Some(p) => p,
-            None => {
-                if profile_name != "default" {
-                    tracing::warn!(profile = %profile_name, "Requested context profile not found, falling back to default");
+    /// Reloads the policies whenever one of their files changes, checked
+    /// every `interval` until the index is dropped. Invalid files are logged
+    /// and skipped; the active policies stay in place.
+    pub fn watch_policies(&self, interval: Duration) {
+        let Some((trust_path, context_path)) = self.inner.policy_paths.clone() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let modified = move || {
+            [&trust_path, &context_path]
+                .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        };
+        let inner: Weak<IndexInner> = Arc::downgrade(&self.inner);
+        runtime.spawn(async move {
+            let mut last: [Option<SystemTime>; 2] = modified();
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(inner) = inner.upgrade() else {
+                    return;
+                };
+                let current = modified();
+                if current == last {
+                    continue;
                 }
-                match self.inner.policies.context.profiles.get("default") {
-                    Some(p) => p,
-                    None => return 1.0,
+                last = current;
+                if let Err(error) = (IndexState { inner }).reload_policies() {
+                    tracing::warn!(error = %error.error, "policy file changed but was not reloaded");
                 }
             }
-        };
+        });
+    }
 
-        // 1. Check namespace
-        let ns_weight = profile
-            .get(namespace)
-            .filter(|&&w| (w - 1.0).abs() > f32::EPSILON);
+    pub fn budget_ms(&self) -> u64 {
+        self.inner.budget_ms
+    }
 
-        // 2. Check origin
-        let origin_weight = if let Some(sr) = source_ref {
-            profile
-                .get(&sr.origin)
-                .filter(|&&w| (w - 1.0).abs() > f32::EPSILON)
-        } else {
-            None
-        };
+    /// Current mutation generation of a namespace (0 = never mutated).
+    pub fn namespace_generation(&self, namespace: &str) -> u64 {
+        let namespace = resolve_namespace(Some(namespace));
+        self.inner
+            .namespace_generations
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(namespace.as_ref())
+            .copied()
+            .unwrap_or(0)
+    }
 
-        // Decision logic:
-        // - Namespace explicit (non-neutral) wins.
-        // - Origin (non-neutral) wins.
-        // - Profile default wins.
-        // - 1.0.
+    fn bump_namespace_generation(&self, namespace: &str) {
+        let mut generations = self
+            .inner
+            .namespace_generations
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *generations.entry(namespace.to_string()).or_insert(0) += 1;
+    }
 
-        if let Some(&w) = ns_weight {
-            return w;
-        }
+    fn record(&self, method: Method, path: &'static str, status: StatusCode, started: Instant) {
+        (self.inner.metrics)(method, path, status, started);
+    }
 
-        if let Some(&w) = origin_weight {
-            return w;
+    /// Registers the callback for auto-quarantined documents (replaces any previous one).
+    pub fn on_quarantine(&self, hook: Arc<QuarantineHook>) {
+        *self
+            .inner
+            .quarantine_hook
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(hook);
+    }
+
+    fn notify_quarantine(&self, notice: QuarantineNotice) {
+        let hook = self
+            .inner
+            .quarantine_hook
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(hook) = hook {
+            hook(notice);
         }
+    }
 
-        *profile.get("_default").unwrap_or(&1.0)
+    /// Replaces the known embedders. Chunks with embeddings are then checked
+    /// against the embedder named in `meta.embedder` (or `default`).
+    pub fn register_embedders(
+        &self,
+        specs: impl IntoIterator<Item = VectorSpec>,
+        default: Option<String>,
+    ) {
+        let specs = specs
+            .into_iter()
+            .map(|spec| (spec.embedder.clone(), spec))
+            .collect();
+        *self
+            .inner
+            .vector_specs
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = VectorSpecs { specs, default };
     }
 
-    pub fn policy_hash(&self) -> &str {
-        &self.inner.policies.hash
+    /// Resolves the embedder of a document, validates its chunk vectors and
+    /// normalizes them if the embedder requires unit length.
+    fn prepare_vectors(
+        &self,
+        meta: &Value,
+        chunks: &mut [ChunkPayload],
+    ) -> Result<Option<String>, IndexError> {
+        let explicit = meta.get("embedder").and_then(Value::as_str);
+        let specs = self
+            .inner
+            .vector_specs
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let has_vectors = chunks.iter().any(|chunk| !chunk.embedding.is_empty());
+        if specs.specs.is_empty() || !has_vectors {
+            return Ok(explicit.map(str::to_string));
+        }
+        let Some(embedder) = explicit.or(specs.default.as_deref()) else {
+            return Err(IndexError {
+                error: "meta.embedder is required for chunks with embeddings".into(),
+                code: "missing_embedder".into(),
+                details: None,
+            });
+        };
+        let Some(spec) = specs.specs.get(embedder) else {
+            let mut known: Vec<&String> = specs.specs.keys().collect();
+            known.sort();
+            return Err(IndexError {
+                error: format!("unknown embedder {embedder}"),
+                code: "unknown_embedder".into(),
+                details: Some(serde_json::json!({ "embedder": embedder, "known": known })),
+            });
+        };
+        let expected = spec.dimension;
+        for chunk in chunks.iter().filter(|chunk| !chunk.embedding.is_empty()) {
+            if chunk.embedding.len() != expected {
+                return Err(IndexError {
+                    error: format!(
+                        "embedding has {} dimensions, embedder {embedder} produces {expected}",
+                        chunk.embedding.len()
+                    ),
+                    code: "embedding_dimension_mismatch".into(),
+                    details: Some(serde_json::json!({
+                        "embedder": embedder,
+                        "expected": expected,
+                        "got": chunk.embedding.len(),
+                        "chunk_id": chunk.chunk_id,
+                    })),
+                });
+            }
+        }
+        if spec.normalize {
+            for chunk in chunks.iter_mut() {
+                let norm = chunk.embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+                if norm > 0.0 {
+                    chunk.embedding.iter_mut().for_each(|v| *v /= norm);
+                }
+            }
+        }
+        Ok(Some(embedder.to_string()))
     }
 
-    pub fn budget_ms(&self) -> u64 {
-        self.inner.budget_ms
+    /// Adds an observer for index mutations and decisions.
+    pub fn on_event(&self, observer: Arc<IndexObserver>) {
+        self.inner
+            .observers
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(observer);
     }
 
-    fn record(&self, method: Method, path: &'static str, status: StatusCode, started: Instant) {
-        (self.inner.metrics)(method, path, status, started);
+    fn notify(&self, event: IndexEvent) {
+        let observers = self
+            .inner
+            .observers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        for observer in observers {
+            observer(&event);
+        }
     }
 
     pub async fn upsert(&self, payload: UpsertRequest) -> Result<usize, IndexError> {
@@ -778,7 +1320,30 @@ impl IndexState {
         } = payload;
 
         // Enforce source_ref requirement for semantic security
-        let source_ref = source_ref.ok_or_else(IndexError::missing_source_ref)?;
+        let mut source_ref = source_ref.ok_or_else(IndexError::missing_source_ref)?;
+        let embedder = self.prepare_vectors(&meta, &mut chunks)?;
+
+        // Unknown origins keep at most the namespace's default trust
+        let requested_namespace = normalize_namespace(&namespace);
+        if let Some(default_trust) = self
+            .inner
+            .namespace_budgets
+            .get(&requested_namespace)
+            .and_then(|budget| budget.default_trust)
+        {
+            if TrustLevel::for_known_origin(&source_ref.origin).is_none()
+                && source_ref.trust_level > default_trust
+            {
+                tracing::debug!(
+                    doc_id = %doc_id,
+                    origin = %source_ref.origin,
+                    claimed = %source_ref.trust_level,
+                    trust_level = %default_trust,
+                    "Lowering trust of unknown origin to the namespace default"
+                );
+                source_ref.trust_level = default_trust;
+            }
+        }
 
         // Detect injection patterns in all chunk text
         let mut flags = Vec::new();
@@ -796,7 +1361,8 @@ impl IndexState {
         }
 
         // Trust-gated auto-quarantine
-        let mut target_namespace = normalize_namespace(&namespace);
+        let mut target_namespace = requested_namespace;
+        let mut quarantined = None;
         if should_quarantine(&flags, source_ref.trust_level) {
             tracing::warn!(
                 doc_id = %doc_id,
@@ -806,13 +1372,25 @@ impl IndexState {
                 original_namespace = %target_namespace,
                 "Auto-quarantining document based on trust level and injection flags"
             );
-            target_namespace = QUARANTINE_NAMESPACE.to_string();
+            quarantined = Some(QuarantineNotice {
+                doc_id: doc_id.clone(),
+                original_namespace: std::mem::replace(
+                    &mut target_namespace,
+                    QUARANTINE_NAMESPACE.to_string(),
+                ),
+                flags: flags.clone(),
+                trust_level: source_ref.trust_level,
+                origin: source_ref.origin.clone(),
+            });
         }
 
         let mut store = self.inner.store.write().await;
         let namespace_store = store
             .entry(target_namespace.clone())
             .or_insert_with(HashMap::new);
+        if let Some(budget) = self.inner.namespace_budgets.get(&target_namespace) {
+            check_budget(budget, &target_namespace, namespace_store, &doc_id, &chunks)?;
+        }
         let ingested = chunks.len();
 
         // Log flag detection (even if not quarantined)
@@ -829,15 +1407,30 @@ impl IndexState {
         namespace_store.insert(
             doc_id.clone(),
             DocumentRecord {
-                doc_id,
+                doc_id: doc_id.clone(),
                 namespace: target_namespace.clone(),
                 chunks,
                 meta,
                 source_ref: Some(source_ref),
                 ingested_at: Utc::now(),
                 flags,
+                embedder,
+                quarantined_from: quarantined
+                    .as_ref()
+                    .map(|notice| notice.original_namespace.clone()),
             },
         );
+        drop(store);
+        self.bump_namespace_generation(&target_namespace);
+        self.notify(IndexEvent::Upserted {
+            namespace: target_namespace,
+            doc_id,
+            chunks: ingested,
+            quarantined: quarantined.is_some(),
+        });
+        if let Some(notice) = quarantined {
+            self.notify_quarantine(notice);
+        }
         Ok(ingested)
     }
 
@@ -862,8 +1455,10 @@ impl IndexState {
         // Get retention config for namespace (if any)
         let retention_config = retention_configs.get(namespace.as_ref());
 
+        // One policy version for the whole search, even if a reload happens meanwhile
+        let policies = self.policies();
         // Use recency policy default if no specific retention config
-        let recency_policy = &self.inner.policies.context.recency;
+        let recency_policy = &policies.context.recency;
 
         // Prepare filter criteria (use typed enums, not strings)
         let exclude_flags_set = request.effective_exclude_flags();
@@ -929,6 +1524,15 @@ impl IndexState {
                     continue;
                 };
 
+                let effective_meta = if chunk.meta.is_null() {
+                    &doc.meta
+                } else {
+                    &chunk.meta
+                };
+                if !request.meta_matches(effective_meta) {
+                    continue;
+                }
+
                 // Calculate trust weight from source_ref
                 // Default to Medium trust if source_ref is missing for safety
                 let trust_level = doc
@@ -937,7 +1541,7 @@ impl IndexState {
                     .map(|sr| sr.trust_level)
                     .unwrap_or(TrustLevel::Medium);
 
-                let trust_weight = self.get_trust_weight(trust_level);
+                let trust_weight = policies.trust_weight(trust_level);
 
                 // Calculate recency weight (time-decay) if configured
                 // Clamp age to 0 to handle future timestamps gracefully (clock skew)
@@ -951,7 +1555,7 @@ impl IndexState {
                     .max(recency_policy.min_weight);
 
                 // Calculate context weight based on namespace and profile
-                let context_weight = self.get_context_weight(
+                let context_weight = policies.context_weight(
                     &doc.namespace,
                     doc.source_ref.as_ref(),
                     request.context_profile.as_deref(),
@@ -993,11 +1597,7 @@ impl IndexState {
                         .unwrap_or_else(|| format!("{}#{idx}", doc.doc_id)),
                     score: final_score,
                     text: text.clone(),
-                    meta: if chunk.meta.is_null() {
-                        doc.meta.clone()
-                    } else {
-                        chunk.meta.clone()
-                    },
+                    meta: effective_meta.clone(),
                     source_ref: doc.source_ref.clone(),
                     ingested_at: doc.ingested_at.to_rfc3339(),
                     flags: doc.flags.clone(),
@@ -1137,7 +1737,7 @@ impl IndexState {
                 context_profile: request.context_profile.clone(),
                 candidates,
                 selected_id: Some(matches[0].doc_id.clone()),
-                policy_hash: self.inner.policies.hash.clone(),
+                policy_hash: policies.hash.clone(),
             };
 
             // Store snapshot with capacity management
@@ -1154,7 +1754,16 @@ impl IndexState {
                 }
             }
 
+            let event = IndexEvent::DecisionRecorded {
+                decision_id: decision_id.clone(),
+                namespace: snapshot.namespace.clone(),
+                selected_id: snapshot.selected_id.clone(),
+                candidates: candidates_count,
+                policy_hash: snapshot.policy_hash.clone(),
+            };
             snapshots.insert(decision_id.clone(), snapshot);
+            drop(snapshots);
+            self.notify(event);
 
             // Update metrics
             self.inner.prom_decision_snapshots_total.inc();
@@ -1171,28 +1780,248 @@ impl IndexState {
     }
 
     pub async fn stats(&self) -> StatsResponse {
+        let policies = self.policies();
         let store = self.inner.store.read().await;
         let mut total_docs = 0;
         let mut total_chunks = 0;
         let mut namespace_counts = HashMap::new();
+        let mut embedder_counts = BTreeMap::new();
 
         for (namespace, namespace_store) in store.iter() {
             let doc_count = namespace_store.len();
             let chunk_count: usize = namespace_store.values().map(|doc| doc.chunks.len()).sum();
+            for embedder in namespace_store
+                .values()
+                .filter_map(|doc| doc.embedder.as_ref())
+            {
+                *embedder_counts.entry(embedder.clone()).or_insert(0) += 1;
+            }
 
             total_docs += doc_count;
             total_chunks += chunk_count;
             namespace_counts.insert(namespace.clone(), doc_count);
         }
 
-        StatsResponse {
-            total_documents: total_docs,
-            total_chunks,
-            namespaces: namespace_counts,
-            budget_ms: self.inner.budget_ms,
-            policy_hash: Some(self.inner.policies.hash.clone()),
-            policy_source: Some(self.inner.policies.source.clone()),
+        StatsResponse {
+            total_documents: total_docs,
+            total_chunks,
+            namespaces: namespace_counts,
+            embedders: embedder_counts,
+            budget_ms: self.inner.budget_ms,
+            policy_hash: Some(policies.hash.clone()),
+            policy_source: Some(policies.source.clone()),
+            policy_version: Some(policies.version),
+        }
+    }
+
+    /// Copies every stored document, ordered by namespace and doc id.
+    pub async fn snapshot(&self) -> IndexSnapshot {
+        let store = self.inner.store.read().await;
+        let mut documents: Vec<SnapshotDocument> = store
+            .values()
+            .flat_map(|namespace_store| namespace_store.values())
+            .map(|doc| SnapshotDocument {
+                doc_id: doc.doc_id.clone(),
+                namespace: doc.namespace.clone(),
+                chunks: doc.chunks.clone(),
+                meta: doc.meta.clone(),
+                source_ref: doc.source_ref.clone(),
+                ingested_at: doc.ingested_at,
+                flags: doc.flags.clone(),
+                embedder: doc.embedder.clone(),
+                quarantined_from: doc.quarantined_from.clone(),
+            })
+            .collect();
+        documents.sort_by(|a, b| (&a.namespace, &a.doc_id).cmp(&(&b.namespace, &b.doc_id)));
+        IndexSnapshot { documents }
+    }
+
+    /// Loads documents from [`IndexState::snapshot`] as they were stored:
+    /// namespace, `ingested_at` and flags are kept, nothing is re-quarantined.
+    /// Existing documents with the same id are replaced. Vectors are checked
+    /// against the known embedders first; on error nothing is restored.
+    pub async fn restore(
+        &self,
+        documents: Vec<SnapshotDocument>,
+    ) -> Result<RestoreResponse, IndexError> {
+        let prepared = self.prepare_restore(documents)?;
+        Ok(self.apply_restore(prepared).await)
+    }
+
+    /// Checks documents for [`IndexState::apply_restore`] without touching
+    /// the index, so callers can validate several parts before changing any.
+    pub fn prepare_restore(
+        &self,
+        documents: Vec<SnapshotDocument>,
+    ) -> Result<PreparedRestore, IndexError> {
+        let mut records = Vec::with_capacity(documents.len());
+        for doc in documents {
+            let source_ref = doc.source_ref.ok_or_else(IndexError::missing_source_ref)?;
+            let mut chunks = doc.chunks;
+            let hint = match &doc.embedder {
+                Some(embedder) => serde_json::json!({ "embedder": embedder }),
+                None => doc.meta.clone(),
+            };
+            let embedder = self.prepare_vectors(&hint, &mut chunks)?;
+            for chunk in &mut chunks {
+                chunk.text_lower = chunk.text.as_ref().map(|text| text.to_lowercase());
+            }
+            records.push(DocumentRecord {
+                doc_id: doc.doc_id,
+                namespace: normalize_namespace(&doc.namespace),
+                chunks,
+                meta: doc.meta,
+                source_ref: Some(source_ref),
+                ingested_at: doc.ingested_at,
+                flags: doc.flags,
+                embedder,
+                quarantined_from: doc.quarantined_from,
+            });
+        }
+        Ok(PreparedRestore { records })
+    }
+
+    /// Stores documents checked by [`IndexState::prepare_restore`].
+    pub async fn apply_restore(&self, prepared: PreparedRestore) -> RestoreResponse {
+        let records = prepared.records;
+        let mut namespaces: BTreeMap<String, usize> = BTreeMap::new();
+        let mut events = Vec::with_capacity(records.len());
+        let mut store = self.inner.store.write().await;
+        for record in records {
+            *namespaces.entry(record.namespace.clone()).or_insert(0) += 1;
+            events.push(IndexEvent::Upserted {
+                namespace: record.namespace.clone(),
+                doc_id: record.doc_id.clone(),
+                chunks: record.chunks.len(),
+                quarantined: record.namespace == QUARANTINE_NAMESPACE,
+            });
+            store
+                .entry(record.namespace.clone())
+                .or_insert_with(HashMap::new)
+                .insert(record.doc_id.clone(), record);
+        }
+        drop(store);
+        for namespace in namespaces.keys() {
+            self.bump_namespace_generation(namespace);
+        }
+        for event in events {
+            self.notify(event);
+        }
+        RestoreResponse {
+            restored: namespaces.values().sum(),
+            namespaces,
+        }
+    }
+
+    /// Documents in the quarantine namespace, ordered by doc id.
+    pub async fn quarantined(&self) -> Vec<QuarantineEntry> {
+        let store = self.inner.store.read().await;
+        let mut entries: Vec<QuarantineEntry> = store
+            .get(QUARANTINE_NAMESPACE)
+            .map(|docs| docs.values().map(QuarantineEntry::from).collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| a.doc_id.cmp(&b.doc_id));
+        entries
+    }
+
+    /// A quarantined document with the contamination rules each chunk triggers.
+    pub async fn quarantined_document(&self, doc_id: &str) -> Option<QuarantineDetail> {
+        let store = self.inner.store.read().await;
+        let doc = store.get(QUARANTINE_NAMESPACE)?.get(doc_id)?;
+        Some(QuarantineDetail {
+            entry: QuarantineEntry::from(doc),
+            meta: doc.meta.clone(),
+            chunks: doc
+                .chunks
+                .iter()
+                .map(|chunk| QuarantinedChunk {
+                    chunk_id: chunk.chunk_id.clone(),
+                    text: chunk.text.clone(),
+                    flags: chunk
+                        .text_lower
+                        .as_deref()
+                        .map(detect_injection_patterns)
+                        .unwrap_or_default(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Moves a quarantined document into `namespace`, by default the one it
+    /// was meant for. Flags and provenance are kept and nothing is re-checked;
+    /// an existing document with the same id in the target is not replaced.
+    pub async fn release_quarantined(
+        &self,
+        doc_id: &str,
+        namespace: Option<String>,
+    ) -> Result<ReleaseResponse, IndexError> {
+        let mut store = self.inner.store.write().await;
+        let doc = store
+            .get(QUARANTINE_NAMESPACE)
+            .and_then(|docs| docs.get(doc_id))
+            .ok_or_else(|| IndexError::not_quarantined(doc_id))?;
+        let target = namespace
+            .or_else(|| doc.quarantined_from.clone())
+            .map(|namespace| normalize_namespace(&namespace))
+            .ok_or_else(|| IndexError {
+                error: format!("original namespace of '{doc_id}' is unknown"),
+                code: "release_target_missing".into(),
+                details: Some(serde_json::json!({ "hint": "Pass the target namespace" })),
+            })?;
+        if target == QUARANTINE_NAMESPACE
+            || store
+                .get(&target)
+                .is_some_and(|docs| docs.contains_key(doc_id))
+        {
+            return Err(IndexError {
+                error: format!("'{doc_id}' cannot be released into '{target}'"),
+                code: "release_conflict".into(),
+                details: Some(serde_json::json!({ "namespace": target })),
+            });
         }
+
+        let Some(mut doc) = store
+            .get_mut(QUARANTINE_NAMESPACE)
+            .and_then(|docs| docs.remove(doc_id))
+        else {
+            return Err(IndexError::not_quarantined(doc_id));
+        };
+        doc.namespace = target.clone();
+        doc.quarantined_from = None;
+        let flags = doc.flags.clone();
+        store
+            .entry(target.clone())
+            .or_insert_with(HashMap::new)
+            .insert(doc_id.to_string(), doc);
+        drop(store);
+
+        tracing::warn!(doc_id = %doc_id, namespace = %target, flags = ?flags, "Released document from quarantine");
+        self.bump_namespace_generation(QUARANTINE_NAMESPACE);
+        self.bump_namespace_generation(&target);
+        self.notify(IndexEvent::QuarantineReleased {
+            doc_id: doc_id.to_string(),
+            namespace: target.clone(),
+            flags,
+        });
+        Ok(ReleaseResponse {
+            doc_id: doc_id.to_string(),
+            namespace: target,
+        })
+    }
+
+    /// Deletes a quarantined document for good; `false` if there is none.
+    pub async fn purge_quarantined(&self, doc_id: &str) -> bool {
+        let filter = ForgetFilter {
+            namespace: Some(QUARANTINE_NAMESPACE.to_string()),
+            older_than: None,
+            source_ref_origin: None,
+            doc_id: Some(doc_id.to_string()),
+            allow_namespace_wipe: false,
+        };
+        self.forget_with_reason(filter, false, Some("quarantine purge".into()))
+            .await
+            .forgotten_count
+            > 0
     }
 
     pub async fn related(
@@ -1293,7 +2122,11 @@ impl IndexState {
     pub async fn set_retention_config(&self, namespace: String, config: RetentionConfig) {
         let namespace = normalize_namespace(&namespace);
         let mut configs = self.inner.retention_configs.write().await;
-        configs.insert(namespace, config);
+        // Retention changes affect recency weighting, so treat them as a mutation.
+        self.bump_namespace_generation(&namespace);
+        configs.insert(namespace.clone(), config.clone());
+        drop(configs);
+        self.notify(IndexEvent::RetentionChanged { namespace, config });
     }
 
     /// Get all retention configurations
@@ -1314,6 +2147,17 @@ impl IndexState {
     /// - allow_namespace_wipe requires namespace to be specified (prevents cross-namespace deletion)
     /// - This prevents accidental global or namespace-wide deletion
     pub async fn forget(&self, filter: ForgetFilter, dry_run: bool) -> ForgetResult {
+        self.forget_with_reason(filter, dry_run, None).await
+    }
+
+    /// Like [`IndexState::forget`], but passes `reason` on to the
+    /// [`IndexEvent::Forgotten`] events (and thereby to the audit log).
+    pub async fn forget_with_reason(
+        &self,
+        filter: ForgetFilter,
+        dry_run: bool,
+        reason: Option<String>,
+    ) -> ForgetResult {
         let mut store = self.inner.store.write().await;
         let mut forgotten_count = 0;
         let mut forgotten_docs = Vec::new();
@@ -1349,6 +2193,7 @@ impl IndexState {
             || filter.source_ref_origin.is_some()
             || filter.doc_id.is_some();
 
+        let mut events = Vec::new();
         for namespace_name in namespaces_to_check {
             let namespace_store = match store.get_mut(&namespace_name) {
                 Some(ns) => ns,
@@ -1396,14 +2241,24 @@ impl IndexState {
                 }
             }
 
-            if !dry_run {
+            if !dry_run && !to_remove.is_empty() {
                 for doc_id in &to_remove {
                     namespace_store.remove(doc_id);
                 }
+                self.bump_namespace_generation(&namespace_name);
+                events.push(IndexEvent::Forgotten {
+                    namespace: namespace_name.clone(),
+                    doc_ids: to_remove.clone(),
+                    reason: reason.clone(),
+                });
             }
 
             forgotten_count += to_remove.len();
         }
+        drop(store);
+        for event in events {
+            self.notify(event);
+        }
 
         ForgetResult {
             forgotten_count,
@@ -1414,24 +2269,33 @@ impl IndexState {
 
     /// Preview decay effect without modifying scores
     pub async fn preview_decay(&self, namespace: Option<String>) -> DecayPreview {
+        self.preview_decay_with(namespace, None).await
+    }
+
+    /// Like [`IndexState::preview_decay`], but with `half_life_seconds` in
+    /// place of the configured half-life, to try a value before setting it.
+    pub async fn preview_decay_with(
+        &self,
+        namespace: Option<String>,
+        half_life_seconds: Option<u64>,
+    ) -> DecayPreview {
         let store = self.inner.store.read().await;
         let retention_configs = self.inner.retention_configs.read().await;
         let namespace = resolve_namespace(namespace.as_deref());
+        let half_life_seconds = half_life_seconds.or_else(|| {
+            retention_configs
+                .get(namespace.as_ref())
+                .and_then(|config| config.half_life_seconds)
+        });
 
         let mut previews = Vec::new();
         let now = Utc::now();
 
         if let Some(namespace_store) = store.get(namespace.as_ref()) {
-            let retention_config = retention_configs.get(namespace.as_ref());
-
             for doc in namespace_store.values() {
                 // Clamp age to 0 to handle future timestamps gracefully (clock skew)
                 let age_seconds = (now - doc.ingested_at).num_seconds().max(0);
-                let decay_factor = if let Some(config) = retention_config {
-                    calculate_decay_factor(age_seconds, config.half_life_seconds)
-                } else {
-                    1.0
-                };
+                let decay_factor = calculate_decay_factor(age_seconds, half_life_seconds);
 
                 previews.push(DecayPreviewItem {
                     doc_id: doc.doc_id.clone(),
@@ -1451,6 +2315,7 @@ impl IndexState {
 
         DecayPreview {
             namespace: namespace.to_string(),
+            half_life_seconds,
             total_documents: previews.len(),
             previews,
         }
@@ -1509,6 +2374,8 @@ impl IndexState {
         }
 
         outcomes.insert(outcome.decision_id.clone(), outcome.clone());
+        drop(outcomes);
+        self.notify(IndexEvent::OutcomeRecorded(outcome.clone()));
 
         // Update metrics
         self.inner
@@ -1591,6 +2458,10 @@ where
         .route("/related", post(related_handler))
         .route("/forget", post(forget_handler))
         .route("/retention", axum::routing::get(retention_handler))
+        .route(
+            "/retention/{namespace}",
+            axum::routing::put(set_retention_handler),
+        )
         .route("/decay/preview", post(decay_preview_handler))
         .route(
             "/decisions/snapshot",
@@ -1609,6 +2480,20 @@ where
             "/decisions/outcomes",
             axum::routing::get(list_decision_outcomes_handler),
         )
+        .route("/policy/reload", post(reload_policies_handler))
+        .route(
+            "/snapshot",
+            axum::routing::get(snapshot_handler).post(restore_handler),
+        )
+        .route("/quarantine", axum::routing::get(quarantine_list_handler))
+        .route(
+            "/quarantine/{doc_id}",
+            axum::routing::get(quarantine_show_handler).delete(quarantine_purge_handler),
+        )
+        .route(
+            "/quarantine/{doc_id}/release",
+            post(quarantine_release_handler),
+        )
 }
 
 async fn upsert_handler(
@@ -1688,6 +2573,104 @@ async fn related_handler(
         .into_response()
 }
 
+async fn snapshot_handler(State(state): State<IndexState>) -> Response {
+    let started = Instant::now();
+    let snapshot = state.snapshot().await;
+    state.record(Method::GET, "/index/snapshot", StatusCode::OK, started);
+    (StatusCode::OK, Json(snapshot)).into_response()
+}
+
+async fn restore_handler(
+    State(state): State<IndexState>,
+    Json(payload): Json<IndexSnapshot>,
+) -> Response {
+    let started = Instant::now();
+    match state.restore(payload.documents).await {
+        Ok(response) => {
+            state.record(Method::POST, "/index/snapshot", StatusCode::OK, started);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(error) => {
+            state.record(
+                Method::POST,
+                "/index/snapshot",
+                StatusCode::UNPROCESSABLE_ENTITY,
+                started,
+            );
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response()
+        }
+    }
+}
+
+async fn quarantine_list_handler(State(state): State<IndexState>) -> Response {
+    let started = Instant::now();
+    let documents = state.quarantined().await;
+    state.record(Method::GET, "/index/quarantine", StatusCode::OK, started);
+    (StatusCode::OK, Json(QuarantineList { documents })).into_response()
+}
+
+async fn quarantine_show_handler(
+    State(state): State<IndexState>,
+    axum::extract::Path(doc_id): axum::extract::Path<String>,
+) -> Response {
+    let started = Instant::now();
+    let (status, body) = match state.quarantined_document(&doc_id).await {
+        Some(detail) => (StatusCode::OK, Json(serde_json::json!(detail))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!(IndexError::not_quarantined(&doc_id))),
+        ),
+    };
+    state.record(Method::GET, "/index/quarantine/:doc_id", status, started);
+    (status, body).into_response()
+}
+
+async fn quarantine_release_handler(
+    State(state): State<IndexState>,
+    axum::extract::Path(doc_id): axum::extract::Path<String>,
+    Json(payload): Json<ReleaseRequest>,
+) -> Response {
+    let started = Instant::now();
+    let response = match state.release_quarantined(&doc_id, payload.namespace).await {
+        Ok(released) => (StatusCode::OK, Json(serde_json::json!(released))),
+        Err(error) => {
+            let status = match error.code.as_str() {
+                "not_quarantined" => StatusCode::NOT_FOUND,
+                "release_conflict" => StatusCode::CONFLICT,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            (status, Json(serde_json::json!(error)))
+        }
+    };
+    state.record(
+        Method::POST,
+        "/index/quarantine/:doc_id/release",
+        response.0,
+        started,
+    );
+    response.into_response()
+}
+
+async fn quarantine_purge_handler(
+    State(state): State<IndexState>,
+    axum::extract::Path(doc_id): axum::extract::Path<String>,
+) -> Response {
+    let started = Instant::now();
+    let (status, body) = if state.purge_quarantined(&doc_id).await {
+        (
+            StatusCode::OK,
+            serde_json::json!({ "doc_id": doc_id, "purged": true }),
+        )
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            serde_json::json!(IndexError::not_quarantined(&doc_id)),
+        )
+    };
+    state.record(Method::DELETE, "/index/quarantine/:doc_id", status, started);
+    (status, Json(body)).into_response()
+}
+
 async fn forget_handler(
     State(state): State<IndexState>,
     Json(payload): Json<ForgetRequest>,
@@ -1754,7 +2737,13 @@ async fn forget_handler(
             .into_response();
     }
 
-    let result = state.forget(payload.filter, payload.dry_run).await;
+    let result = state
+        .forget_with_reason(
+            payload.filter,
+            payload.dry_run,
+            Some(payload.reason.clone()),
+        )
+        .await;
 
     // Log the forget operation
     tracing::info!(
@@ -1775,12 +2764,71 @@ async fn retention_handler(State(state): State<IndexState>) -> Response {
     (StatusCode::OK, Json(RetentionResponse { configs })).into_response()
 }
 
+async fn set_retention_handler(
+    State(state): State<IndexState>,
+    axum::extract::Path(namespace): axum::extract::Path<String>,
+    Json(config): Json<RetentionConfig>,
+) -> Response {
+    let started = Instant::now();
+    let zero = [
+        ("half_life_seconds", config.half_life_seconds == Some(0)),
+        ("max_items", config.max_items == Some(0)),
+        ("max_age_seconds", config.max_age_seconds == Some(0)),
+    ];
+    if let Some((field, _)) = zero.iter().find(|(_, is_zero)| *is_zero) {
+        state.record(
+            Method::PUT,
+            "/index/retention/:namespace",
+            StatusCode::BAD_REQUEST,
+            started,
+        );
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("{field} must be positive"),
+                "hint": "Omit the field for no limit"
+            })),
+        )
+            .into_response();
+    }
+    let namespace = normalize_namespace(&namespace);
+    state
+        .set_retention_config(namespace.clone(), config.clone())
+        .await;
+    state.record(
+        Method::PUT,
+        "/index/retention/:namespace",
+        StatusCode::OK,
+        started,
+    );
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "namespace": namespace, "config": config })),
+    )
+        .into_response()
+}
+
 async fn decay_preview_handler(
     State(state): State<IndexState>,
     Json(payload): Json<DecayPreviewRequest>,
 ) -> Response {
     let started = Instant::now();
-    let preview = state.preview_decay(payload.namespace).await;
+    if payload.half_life_seconds == Some(0) {
+        state.record(
+            Method::POST,
+            "/index/decay/preview",
+            StatusCode::BAD_REQUEST,
+            started,
+        );
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "half_life_seconds must be positive" })),
+        )
+            .into_response();
+    }
+    let preview = state
+        .preview_decay_with(payload.namespace, payload.half_life_seconds)
+        .await;
     state.record(
         Method::POST,
         "/index/decay/preview",
@@ -1879,6 +2927,23 @@ async fn record_outcome_handler(
     }
 }
 
+async fn reload_policies_handler(State(state): State<IndexState>) -> Response {
+    let started = Instant::now();
+    let (status, response) = match state.reload_policies() {
+        Ok(reload) => (StatusCode::OK, Json(reload).into_response()),
+        Err(error) => {
+            let status = if error.code == "policy_files_unconfigured" {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::UNPROCESSABLE_ENTITY
+            };
+            (status, (status, Json(error)).into_response())
+        }
+    };
+    state.record(Method::POST, "/index/policy/reload", status, started);
+    response
+}
+
 async fn get_decision_outcome_handler(
     State(state): State<IndexState>,
     axum::extract::Path(id): axum::extract::Path<String>,
@@ -1925,7 +2990,7 @@ async fn list_decision_outcomes_handler(State(state): State<IndexState>) -> Resp
     (StatusCode::OK, Json(DecisionOutcomesResponse { outcomes })).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UpsertRequest {
     pub doc_id: String,
     #[serde(default = "default_namespace")]
@@ -1937,7 +3002,7 @@ pub struct UpsertRequest {
     pub source_ref: Option<SourceRef>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChunkPayload {
     #[serde(default)]
     pub chunk_id: Option<String>,
@@ -1952,6 +3017,108 @@ pub struct ChunkPayload {
     pub meta: Value,
 }
 
+/// A stored document as exported by `GET /index/snapshot`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDocument {
+    pub doc_id: String,
+    pub namespace: String,
+    #[serde(default)]
+    pub chunks: Vec<ChunkPayload>,
+    #[serde(default)]
+    pub meta: Value,
+    pub source_ref: Option<SourceRef>,
+    pub ingested_at: DateTime<Utc>,
+    #[serde(default)]
+    pub flags: Vec<ContentFlag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedder: Option<String>,
+    /// Namespace a quarantined document was meant for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined_from: Option<String>,
+}
+
+/// A document in quarantine, as listed by `GET /index/quarantine`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub doc_id: String,
+    /// Namespace the document was meant for; unknown for documents
+    /// quarantined before this was recorded.
+    pub original_namespace: Option<String>,
+    /// Contamination rules that fired for the document.
+    pub flags: Vec<ContentFlag>,
+    pub source_ref: Option<SourceRef>,
+    pub ingested_at: DateTime<Utc>,
+    pub chunks: usize,
+}
+
+/// Body of `GET /index/quarantine`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QuarantineList {
+    pub documents: Vec<QuarantineEntry>,
+}
+
+/// Body of `GET /index/quarantine/{doc_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineDetail {
+    #[serde(flatten)]
+    pub entry: QuarantineEntry,
+    #[serde(default)]
+    pub meta: Value,
+    pub chunks: Vec<QuarantinedChunk>,
+}
+
+/// A chunk of a quarantined document and the rules its text triggers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedChunk {
+    pub chunk_id: Option<String>,
+    pub text: Option<String>,
+    pub flags: Vec<ContentFlag>,
+}
+
+/// Body of `POST /index/quarantine/{doc_id}/release`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReleaseRequest {
+    /// Target namespace; defaults to the one the document was meant for.
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseResponse {
+    pub doc_id: String,
+    pub namespace: String,
+}
+
+/// Body of `GET /index/snapshot` and `POST /index/snapshot`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IndexSnapshot {
+    pub documents: Vec<SnapshotDocument>,
+}
+
+/// Snapshot documents checked by [`IndexState::prepare_restore`].
+#[derive(Debug)]
+pub struct PreparedRestore {
+    records: Vec<DocumentRecord>,
+}
+
+impl PreparedRestore {
+    /// Documents that [`IndexState::apply_restore`] will store.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreResponse {
+    pub restored: usize,
+    /// Restored documents per namespace.
+    pub namespaces: BTreeMap<String, usize>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SearchRequest {
     pub query: String,
@@ -1981,6 +3148,10 @@ pub struct SearchRequest {
     /// Independent of include_weights - this explicitly controls snapshot emission
     #[serde(default)]
     pub emit_decision_snapshot: bool,
+    /// Metadata filter: every key must be present in the effective chunk meta
+    /// (chunk meta, falling back to document meta) with an equal JSON value.
+    #[serde(default)]
+    pub meta_filter: Option<serde_json::Map<String, Value>>,
 }
 
 impl SearchRequest {
@@ -1997,6 +3168,7 @@ impl SearchRequest {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         }
     }
 
@@ -2007,6 +3179,16 @@ impl SearchRequest {
             Some(flags) => flags.clone(),
         }
     }
+
+    /// Check whether the given meta object satisfies `meta_filter` (AND semantics).
+    fn meta_matches(&self, meta: &Value) -> bool {
+        match &self.meta_filter {
+            None => true,
+            Some(filter) => filter
+                .iter()
+                .all(|(key, expected)| meta.get(key) == Some(expected)),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -2043,11 +3225,16 @@ pub struct StatsResponse {
     pub total_documents: usize,
     pub total_chunks: usize,
     pub namespaces: HashMap<String, usize>,
+    /// Documents per embedder (`meta.embedder` or the default embedder).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub embedders: BTreeMap<String, usize>,
     pub budget_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub policy_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub policy_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_version: Option<u64>,
 }
 
 /// Weight breakdown for decision-making transparency
@@ -2257,12 +3444,17 @@ pub struct RetentionResponse {
 pub struct DecayPreviewRequest {
     #[serde(default)]
     pub namespace: Option<String>,
+    /// Half-life to preview instead of the configured one.
+    #[serde(default)]
+    pub half_life_seconds: Option<u64>,
 }
 
 /// Response for decay preview
 #[derive(Debug, Serialize)]
 pub struct DecayPreview {
     pub namespace: String,
+    /// Half-life the preview was computed with (`None` = no decay).
+    pub half_life_seconds: Option<u64>,
     pub total_documents: usize,
     pub previews: Vec<DecayPreviewItem>,
 }
@@ -2307,6 +3499,40 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn vectors_of_l2_embedders_are_stored_normalized() {
+        let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        state.register_embedders(
+            [VectorSpec {
+                embedder: "minilm".into(),
+                dimension: 2,
+                normalize: true,
+            }],
+            Some("minilm".into()),
+        );
+        state
+            .upsert(UpsertRequest {
+                doc_id: "vec".into(),
+                namespace: "default".into(),
+                chunks: vec![ChunkPayload {
+                    chunk_id: Some("vec#0".into()),
+                    text: Some("Vektor".into()),
+                    text_lower: None,
+                    embedding: vec![3.0, 4.0],
+                    meta: json!({}),
+                }],
+                meta: json!({}),
+                source_ref: Some(test_source_ref("docs", "vec.md")),
+            })
+            .await
+            .unwrap();
+
+        let store = state.inner.store.read().await;
+        let doc = &store["default"]["vec"];
+        assert_eq!(doc.embedder.as_deref(), Some("minilm"));
+        assert_eq!(doc.chunks[0].embedding, [0.6, 0.8]);
+    }
+
     #[tokio::test]
     async fn upsert_and_search_return_ok() {
         let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
@@ -2405,6 +3631,7 @@ mod tests {
                 context_profile: None,
                 include_weights: false,
                 emit_decision_snapshot: false,
+                meta_filter: None,
             })
             .await;
 
@@ -2413,6 +3640,114 @@ mod tests {
         assert!(results[0].text.to_lowercase().contains("rust"));
     }
 
+    #[tokio::test]
+    async fn snapshot_restores_documents_as_stored() {
+        let source = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        source
+            .upsert(UpsertRequest {
+                doc_id: "doc-snap".into(),
+                namespace: "notes".into(),
+                chunks: vec![ChunkPayload {
+                    chunk_id: Some("doc-snap#0".into()),
+                    text: Some("Snapshot Roundtrip".into()),
+                    text_lower: None,
+                    embedding: vec![0.5, 0.5],
+                    meta: json!({"chunk": 0}),
+                }],
+                meta: json!({"doc": "snap"}),
+                source_ref: Some(test_source_ref("user", "snap")),
+            })
+            .await
+            .unwrap();
+        let snapshot = source.snapshot().await;
+        assert_eq!(snapshot.documents.len(), 1);
+        let ingested_at = snapshot.documents[0].ingested_at;
+
+        // Travels as JSON between machines.
+        let snapshot: IndexSnapshot =
+            serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+        let target = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        let restored = target.restore(snapshot.documents).await.unwrap();
+        assert_eq!(restored.restored, 1);
+        assert_eq!(restored.namespaces.get("notes"), Some(&1));
+        assert_eq!(target.namespace_generation("notes"), 1);
+
+        let copy = target.snapshot().await;
+        assert_eq!(copy.documents[0].ingested_at, ingested_at);
+        assert_eq!(copy.documents[0].chunks[0].embedding, vec![0.5, 0.5]);
+        let hits = target
+            .search(&SearchRequest {
+                namespace: Some("notes".into()),
+                ..SearchRequest::test_basic("roundtrip")
+            })
+            .await;
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn namespace_generation_tracks_mutations() {
+        let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+        assert_eq!(state.namespace_generation("default"), 0);
+
+        state
+            .upsert(UpsertRequest {
+                doc_id: "doc-gen".into(),
+                namespace: " default ".into(),
+                chunks: Vec::new(),
+                meta: Value::Null,
+                source_ref: Some(test_source_ref("chronik", "gen")),
+            })
+            .await
+            .expect("upsert should succeed");
+        assert_eq!(state.namespace_generation("default"), 1);
+        assert_eq!(state.namespace_generation("other"), 0);
+
+        let filter = || ForgetFilter {
+            namespace: Some("default".into()),
+            older_than: None,
+            source_ref_origin: None,
+            doc_id: Some("doc-gen".into()),
+            allow_namespace_wipe: false,
+        };
+        state.forget(filter(), true).await;
+        assert_eq!(state.namespace_generation("default"), 1);
+        state.forget(filter(), false).await;
+        assert_eq!(state.namespace_generation("default"), 2);
+    }
+
+    #[tokio::test]
+    async fn search_applies_meta_filter() {
+        let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+
+        for (doc_id, lang) in [("doc-de", "de"), ("doc-en", "en")] {
+            state
+                .upsert(UpsertRequest {
+                    doc_id: doc_id.into(),
+                    namespace: "default".into(),
+                    chunks: vec![ChunkPayload {
+                        chunk_id: None,
+                        text: Some("Rust notes".into()),
+                        text_lower: None,
+                        embedding: Vec::new(),
+                        meta: Value::Null,
+                    }],
+                    meta: json!({"lang": lang, "kind": "note"}),
+                    source_ref: Some(test_source_ref("chronik", doc_id)),
+                })
+                .await
+                .expect("upsert should succeed");
+        }
+
+        let mut request = SearchRequest::test_basic("rust");
+        request.meta_filter = json!({"lang": "de", "kind": "note"}).as_object().cloned();
+        let results = state.search(&request).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].doc_id, "doc-de");
+
+        request.meta_filter = json!({"lang": "fr"}).as_object().cloned();
+        assert!(state.search(&request).await.is_empty());
+    }
+
     #[tokio::test]
     async fn trims_namespace_whitespace_on_upsert_and_search() {
         let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
@@ -2445,6 +3780,7 @@ mod tests {
                 context_profile: None,
                 include_weights: false,
                 emit_decision_snapshot: false,
+                meta_filter: None,
             })
             .await;
 
@@ -2462,6 +3798,7 @@ mod tests {
                 context_profile: None,
                 include_weights: false,
                 emit_decision_snapshot: false,
+                meta_filter: None,
             })
             .await;
 
@@ -2501,6 +3838,7 @@ mod tests {
                 context_profile: None,
                 include_weights: false,
                 emit_decision_snapshot: false,
+                meta_filter: None,
             })
             .await;
 
@@ -2518,6 +3856,7 @@ mod tests {
                 context_profile: None,
                 include_weights: false,
                 emit_decision_snapshot: false,
+                meta_filter: None,
             })
             .await;
 
@@ -2644,4 +3983,68 @@ mod tests {
         assert!(!related.is_empty());
         assert!(related.iter().any(|m| m.doc_id == "doc-rust-guide"));
     }
+
+    #[tokio::test]
+    async fn namespace_budgets_limit_upserts_and_trust() {
+        let budgets = BTreeMap::from([(
+            "notes".to_string(),
+            NamespaceBudget {
+                max_docs: Some(2),
+                max_bytes: Some(10),
+                retention: Some(RetentionConfig {
+                    half_life_seconds: Some(3600),
+                    max_items: None,
+                    max_age_seconds: None,
+                    purge_strategy: None,
+                }),
+                default_trust: Some(TrustLevel::Low),
+            },
+        )]);
+        let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None)
+            .with_namespace_budgets(budgets);
+        let upsert = |doc_id: &str, text: &str, origin: &str| UpsertRequest {
+            doc_id: doc_id.into(),
+            namespace: "notes".into(),
+            chunks: vec![ChunkPayload {
+                chunk_id: None,
+                text: Some(text.into()),
+                text_lower: None,
+                embedding: Vec::new(),
+                meta: json!({}),
+            }],
+            meta: json!({}),
+            source_ref: Some(SourceRef {
+                trust_level: TrustLevel::High,
+                ..test_source_ref(origin, doc_id)
+            }),
+        };
+
+        assert_eq!(
+            state.get_retention_configs().await["notes"].half_life_seconds,
+            Some(3600)
+        );
+        state.upsert(upsert("a", "12345", "scanner")).await.unwrap();
+        state.upsert(upsert("b", "1234", "chronik")).await.unwrap();
+        let err = state.upsert(upsert("c", "1", "chronik")).await.unwrap_err();
+        assert_eq!(err.code, "namespace_budget_exceeded");
+        assert_eq!(err.details.unwrap()["limit"], "max_docs");
+        // Replacing a document does not count it twice.
+        let err = state
+            .upsert(upsert("b", "123456", "chronik"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.details.unwrap()["limit"], "max_bytes");
+        state.upsert(upsert("b", "12345", "chronik")).await.unwrap();
+
+        let store = state.inner.store.read().await;
+        let trust = |doc_id: &str| {
+            store["notes"][doc_id]
+                .source_ref
+                .as_ref()
+                .unwrap()
+                .trust_level
+        };
+        assert_eq!(trust("a"), TrustLevel::Low);
+        assert_eq!(trust("b"), TrustLevel::High);
+    }
 }
@@ -2,11 +2,11 @@ mod common;
 use chrono::{Duration, Utc};
 use common::test_source_ref;
 use hauski_indexd::{
-    ChunkPayload, ForgetFilter, IndexState, PurgeStrategy, RetentionConfig, SearchRequest,
-    UpsertRequest,
+    ChunkPayload, ForgetFilter, IndexEvent, IndexState, PurgeStrategy, RetentionConfig,
+    SearchRequest, UpsertRequest,
 };
 use serde_json::json;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 /// Test that time-decay reduces scores over time
 #[tokio::test]
 async fn test_time_decay_reduces_scores() {
@@ -54,6 +54,7 @@ async fn test_time_decay_reduces_scores() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(results.len(), 1);
@@ -172,6 +173,7 @@ async fn test_forget_by_namespace() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(search_after_dry.len(), 1);
@@ -202,6 +204,7 @@ async fn test_forget_by_namespace() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(search_after.len(), 0);
@@ -217,6 +220,7 @@ async fn test_forget_by_namespace() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(keep_search.len(), 1);
@@ -285,6 +289,7 @@ async fn test_forget_by_source_ref_origin() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(search_code.len(), 1);
@@ -397,6 +402,7 @@ async fn test_forget_by_doc_id() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(search.len(), 2);
@@ -535,6 +541,7 @@ async fn test_decay_affects_search_ranking() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(results1.len(), 1);
@@ -553,6 +560,7 @@ async fn test_decay_affects_search_ranking() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(results2.len(), 1);
@@ -666,6 +674,7 @@ async fn test_forget_uses_and_semantics() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(search.len(), 2);
@@ -792,6 +801,7 @@ async fn test_future_timestamp_handling() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
     assert_eq!(results.len(), 1);
@@ -849,3 +859,67 @@ async fn test_forget_method_blocks_global_wipe() {
         "All 6 documents should still exist"
     );
 }
+
+/// Observers see upserts, retention changes and (non-dry-run) forgets
+#[tokio::test]
+async fn test_observers_receive_mutation_events() {
+    let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+    let events: Arc<Mutex<Vec<IndexEvent>>> = Arc::default();
+    let sink = events.clone();
+    state.on_event(Arc::new(move |event| {
+        sink.lock().unwrap().push(event.clone())
+    }));
+
+    state
+        .upsert(UpsertRequest {
+            doc_id: "doc-1".into(),
+            namespace: "notes".into(),
+            chunks: vec![ChunkPayload {
+                chunk_id: Some("doc-1#0".into()),
+                text: Some("Observed content".into()),
+                text_lower: None,
+                embedding: Vec::new(),
+                meta: json!({}),
+            }],
+            meta: json!({}),
+            source_ref: Some(test_source_ref("chronik", "doc-1")),
+        })
+        .await
+        .expect("upsert should succeed");
+    state
+        .set_retention_config(
+            "notes".into(),
+            RetentionConfig {
+                half_life_seconds: Some(3600),
+                max_items: None,
+                max_age_seconds: None,
+                purge_strategy: None,
+            },
+        )
+        .await;
+    let filter = || ForgetFilter {
+        namespace: Some("notes".into()),
+        older_than: None,
+        source_ref_origin: None,
+        doc_id: Some("doc-1".into()),
+        allow_namespace_wipe: false,
+    };
+    state.forget(filter(), true).await;
+    state.forget(filter(), false).await;
+
+    let events = events.lock().unwrap();
+    let kinds: Vec<_> = events.iter().map(IndexEvent::kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            "index.upserted",
+            "index.retention_changed",
+            "index.forgotten"
+        ]
+    );
+    assert!(matches!(
+        &events[0],
+        IndexEvent::Upserted { namespace, chunks: 1, quarantined: false, .. } if namespace == "notes"
+    ));
+    assert!(matches!(&events[2], IndexEvent::Forgotten { doc_ids, .. } if doc_ids == &["doc-1"]));
+}
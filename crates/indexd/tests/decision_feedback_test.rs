@@ -2,16 +2,23 @@ mod common;
 use common::test_source_ref;
 
 use hauski_indexd::{
-    ChunkPayload, DecisionOutcome, IndexState, OutcomeSignal, OutcomeSource, SearchRequest,
-    UpsertRequest,
+    ChunkPayload, DecisionOutcome, IndexEvent, IndexState, OutcomeSignal, OutcomeSource,
+    SearchRequest, UpsertRequest,
 };
 use serde_json::json;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// Test that decision snapshots are emitted when emit_decision_snapshot is true
 #[tokio::test]
 async fn test_decision_snapshot_emission() {
     let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+    let decisions: Arc<Mutex<Vec<IndexEvent>>> = Arc::default();
+    let sink = decisions.clone();
+    state.on_event(Arc::new(move |event| {
+        if event.kind() == "decision.recorded" {
+            sink.lock().unwrap().push(event.clone());
+        }
+    }));
 
     // Insert test documents
     state
@@ -60,6 +67,7 @@ async fn test_decision_snapshot_emission() {
             context_profile: None,
             include_weights: true,        // For weight data in response
             emit_decision_snapshot: true, // Explicitly emit snapshot
+            meta_filter: None,
         })
         .await;
 
@@ -82,6 +90,13 @@ async fn test_decision_snapshot_emission() {
     assert!(candidate.similarity > 0.0);
     assert_eq!(candidate.weights.trust, 1.0); // High trust from chronik
     assert_eq!(candidate.weights.context, 1.0); // Default context
+
+    // Observers are told about the decision
+    let decisions = decisions.lock().unwrap();
+    assert!(matches!(
+        decisions.as_slice(),
+        [IndexEvent::DecisionRecorded { decision_id, candidates: 2, .. }] if *decision_id == snapshot.decision_id
+    ));
 }
 
 /// Test that decision snapshots are NOT emitted when emit_decision_snapshot is false
@@ -118,6 +133,7 @@ async fn test_decision_snapshot_not_emitted_without_flag() {
             context_profile: None,
             include_weights: false,        // Can be true or false
             emit_decision_snapshot: false, // No snapshot should be emitted
+            meta_filter: None,
         })
         .await;
 
@@ -166,6 +182,7 @@ async fn test_decision_outcome_recording() {
             context_profile: None,
             include_weights: true,
             emit_decision_snapshot: true,
+            meta_filter: None,
         })
         .await;
 
@@ -266,6 +283,7 @@ async fn test_decision_snapshot_includes_policy_hash() {
             context_profile: None,
             include_weights: true,
             emit_decision_snapshot: true,
+            meta_filter: None,
         })
         .await;
 
@@ -127,6 +127,7 @@ async fn test_trust_weighting_affects_ranking() {
             context_profile: None,
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -257,6 +258,7 @@ async fn test_context_profile_weighting() {
             context_profile: Some("incident_response".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -279,6 +281,7 @@ async fn test_context_profile_weighting() {
             context_profile: Some("incident_response".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -301,6 +304,7 @@ async fn test_context_profile_weighting() {
             context_profile: Some("code_analysis".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -376,6 +380,7 @@ async fn test_combined_weighting() {
             context_profile: Some("code_analysis".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -444,6 +449,7 @@ async fn test_weights_omitted_when_not_requested() {
             context_profile: None,
             include_weights: false, // Explicitly don't include weights
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -514,6 +520,7 @@ async fn test_invalid_policies_fallback_to_default() {
             context_profile: None,
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -568,6 +575,7 @@ async fn test_invalid_policies_fallback_to_default() {
             namespace: Some("default".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
             exclude_flags: None,
             min_trust_level: None,
             exclude_origins: None,
@@ -624,6 +632,7 @@ async fn test_context_weighting_falls_back_to_origin() {
             context_profile: Some("incident_response".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
             exclude_flags: None,
             min_trust_level: None,
             exclude_origins: None,
@@ -702,6 +711,7 @@ recency:
             context_profile: Some("custom_profile".into()),
             include_weights: true,
             emit_decision_snapshot: false,
+            meta_filter: None,
             exclude_flags: None,
             min_trust_level: None,
             exclude_origins: None,
@@ -728,3 +738,61 @@ recency:
         "Explicit 1.0 should be treated as neutral and fallback to _default"
     );
 }
+
+/// Reloading swaps valid policies atomically and rejects invalid ones
+#[tokio::test]
+async fn test_policy_reload_swaps_valid_and_keeps_active_on_error() {
+    let (trust_file, context_file) = create_test_policy_files();
+    let mut registry = prometheus_client::registry::Registry::default();
+    let state = IndexState::new(
+        60,
+        Arc::new(|_, _, _, _| {}),
+        Some(&mut registry),
+        Some((
+            trust_file.path().to_path_buf(),
+            context_file.path().to_path_buf(),
+        )),
+    );
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = events.clone();
+    state.on_event(Arc::new(move |event: &hauski_indexd::IndexEvent| {
+        seen.lock().unwrap().push(event.kind());
+    }));
+    let initial = state.policy_hash();
+
+    let unchanged = state.reload_policies().expect("same files reload");
+    assert!(!unchanged.changed);
+    assert_eq!(unchanged.version, 1);
+
+    std::fs::write(
+        trust_file.path(),
+        "trust_weights:\n  high: 1.0\n  medium: 0.5\n  low: 0.2\nmin_weight: 0.1\n",
+    )
+    .unwrap();
+    let reload = state.reload_policies().expect("valid policy reloads");
+    assert!(reload.changed);
+    assert_eq!(reload.version, 2);
+    assert_eq!(reload.previous_hash, initial);
+    assert_eq!(state.policies().trust.trust_weights["low"], 0.2);
+    assert_eq!(state.stats().await.policy_version, Some(2));
+
+    std::fs::write(
+        trust_file.path(),
+        "trust_weights:\n  high: 1.0\n  medium: 0.5\nmin_weight: 0.1\n",
+    )
+    .unwrap();
+    let error = state.reload_policies().unwrap_err();
+    assert_eq!(error.code, "invalid_policy");
+    assert_eq!(state.policy_hash(), reload.policy_hash);
+
+    assert_eq!(*events.lock().unwrap(), ["index.policy_reloaded"]);
+    let mut metrics = String::new();
+    prometheus_client::encoding::text::encode(&mut metrics, &registry).unwrap();
+    assert!(metrics.contains("decision_policy_version 2"));
+
+    let unconfigured = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+    assert_eq!(
+        unconfigured.reload_policies().unwrap_err().code,
+        "policy_files_unconfigured"
+    );
+}
@@ -111,6 +111,7 @@ async fn test_fixture_corpus_indexing_and_search() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -135,6 +136,7 @@ async fn test_fixture_corpus_indexing_and_search() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -220,6 +222,7 @@ async fn test_namespace_isolation() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -238,6 +241,7 @@ async fn test_namespace_isolation() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -277,6 +281,7 @@ async fn test_source_ref_and_ingested_at_populated() {
             context_profile: None,
             include_weights: false,
             emit_decision_snapshot: false,
+            meta_filter: None,
         })
         .await;
 
@@ -289,3 +294,65 @@ async fn test_source_ref_and_ingested_at_populated() {
     // Verify it's a valid RFC3339 timestamp
     assert!(chrono::DateTime::parse_from_rfc3339(&results[0].ingested_at).is_ok());
 }
+
+#[tokio::test]
+async fn test_embeddings_are_validated_against_registered_embedders() {
+    use hauski_indexd::VectorSpec;
+
+    let state = IndexState::new(60, Arc::new(|_, _, _, _| {}), None, None);
+    let upsert = |doc_id: &str, embedder: Option<&str>, dimension: usize| UpsertRequest {
+        doc_id: doc_id.into(),
+        namespace: "default".into(),
+        chunks: vec![ChunkPayload {
+            chunk_id: Some(format!("{doc_id}#0")),
+            text: Some("Vektor".into()),
+            text_lower: None,
+            embedding: vec![0.5; dimension],
+            meta: json!({}),
+        }],
+        meta: embedder.map_or_else(|| json!({}), |id| json!({ "embedder": id })),
+        source_ref: Some(test_source_ref("docs", format!("{doc_id}.md"))),
+    };
+
+    // Without registered embedders vectors are stored unchecked.
+    state.upsert(upsert("free", None, 5)).await.unwrap();
+
+    state.register_embedders(
+        [
+            VectorSpec {
+                embedder: "minilm".into(),
+                dimension: 4,
+                normalize: true,
+            },
+            VectorSpec {
+                embedder: "nomic".into(),
+                dimension: 8,
+                normalize: false,
+            },
+        ],
+        Some("minilm".into()),
+    );
+    state.upsert(upsert("default", None, 4)).await.unwrap();
+    state
+        .upsert(upsert("nomic", Some("nomic"), 8))
+        .await
+        .unwrap();
+
+    let mismatch = state
+        .upsert(upsert("short", Some("nomic"), 4))
+        .await
+        .unwrap_err();
+    assert_eq!(mismatch.code, "embedding_dimension_mismatch");
+    assert_eq!(mismatch.details.unwrap()["expected"], 8);
+
+    let unknown = state
+        .upsert(upsert("bge", Some("bge"), 4))
+        .await
+        .unwrap_err();
+    assert_eq!(unknown.code, "unknown_embedder");
+
+    let stats = state.stats().await;
+    assert_eq!(stats.embedders.get("minilm"), Some(&1));
+    assert_eq!(stats.embedders.get("nomic"), Some(&1));
+    assert_eq!(stats.total_documents, 3);
+}
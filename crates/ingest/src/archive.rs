@@ -0,0 +1,106 @@
+//! ZIP containers (DOCX, EPUB) with a cap on unpacked sizes.
+
+use std::io::{Cursor, Read};
+
+use zip::{result::ZipError, ZipArchive};
+
+use crate::{Format, IngestError};
+
+/// Largest entry read from a container, against zip bombs.
+pub(crate) const MAX_ENTRY_BYTES: u64 = 64 << 20;
+
+pub(crate) struct Archive<'a> {
+    format: Format,
+    zip: ZipArchive<Cursor<&'a [u8]>>,
+}
+
+impl<'a> Archive<'a> {
+    pub(crate) fn open(bytes: &'a [u8], format: Format) -> Result<Self, IngestError> {
+        let zip = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|err| IngestError::malformed(format, err))?;
+        Ok(Self { format, zip })
+    }
+
+    /// Contents of `name`; `None` if the container has no such entry.
+    pub(crate) fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>, IngestError> {
+        let entry = match self.zip.by_name(name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)) => {
+                return Err(IngestError::Encrypted(self.format))
+            }
+            Err(err) => return Err(IngestError::malformed(self.format, err)),
+        };
+        let mut bytes = Vec::new();
+        entry
+            .take(MAX_ENTRY_BYTES + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|err| IngestError::malformed(self.format, format!("{name}: {err}")))?;
+        if bytes.len() as u64 > MAX_ENTRY_BYTES {
+            return Err(IngestError::TooLarge {
+                format: self.format,
+                entry: name.to_string(),
+                limit: MAX_ENTRY_BYTES,
+            });
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Like [`Archive::read`], but the entry must exist; decoded lossily as
+    /// UTF-8.
+    pub(crate) fn read_text(&mut self, name: &str) -> Result<String, IngestError> {
+        let bytes = self
+            .read(name)?
+            .ok_or_else(|| IngestError::malformed(self.format, format!("{name} is missing")))?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// DOCX or EPUB by the entries of a ZIP.
+pub(crate) fn sniff(bytes: &[u8]) -> Option<Format> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes)).ok()?;
+    if zip.index_for_name("word/document.xml").is_some() {
+        return Some(Format::Docx);
+    }
+    let mut mimetype = String::new();
+    zip.by_name("mimetype")
+        .ok()?
+        .take(64)
+        .read_to_string(&mut mimetype)
+        .ok()?;
+    (mimetype.trim() == "application/epub+zip").then_some(Format::Epub)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Write;
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+
+    /// A ZIP with `entries`, as Office and EPUB tools write them.
+    pub(crate) fn zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn oversized_entries_are_refused() {
+        let big = "x".repeat(MAX_ENTRY_BYTES as usize + 1);
+        let bytes = zip(&[("word/document.xml", &big)]);
+        let mut archive = Archive::open(&bytes, Format::Docx).unwrap();
+        assert!(matches!(
+            archive.read("word/document.xml"),
+            Err(IngestError::TooLarge {
+                limit: MAX_ENTRY_BYTES,
+                ..
+            })
+        ));
+        assert!(archive.read("word/styles.xml").unwrap().is_none());
+    }
+}
@@ -0,0 +1,192 @@
+//! Paragraph-aligned chunks with their offsets in the section text.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::Document;
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Chunk {
+    pub text: String,
+    /// Index into [`Document::sections`].
+    pub section: usize,
+    pub headings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    /// Character offset of `text` in the section text.
+    pub start: usize,
+    /// Character offset just past `text`.
+    pub end: usize,
+}
+
+impl Chunk {
+    /// Chunk metadata for indexd.
+    pub(crate) fn meta(&self) -> Value {
+        let mut meta = json!({
+            "section": self.section,
+            "headings": self.headings,
+            "start": self.start,
+            "end": self.end,
+        });
+        if let Some(page) = self.page {
+            meta["page"] = json!(page);
+        }
+        meta
+    }
+}
+
+pub(crate) fn split(document: &Document, max_chars: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    for (index, section) in document.sections.iter().enumerate() {
+        let text = section.text.as_str();
+        // Ranges come in order, so char offsets are counted incrementally.
+        let (mut byte, mut chars) = (0, 0);
+        let mut char_offset = |to: usize| {
+            chars += text[byte..to].chars().count();
+            byte = to;
+            chars
+        };
+        for (start, end) in ranges(text, max_chars) {
+            chunks.push(Chunk {
+                text: text[start..end].to_string(),
+                section: index,
+                headings: section.headings.clone(),
+                page: section.page,
+                start: char_offset(start),
+                end: char_offset(end),
+            });
+        }
+    }
+    chunks
+}
+
+/// Byte ranges of `text` covering whole paragraphs up to `max_chars`.
+fn ranges(text: &str, max_chars: usize) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    let mut position = 0;
+    for paragraph in text.split("\n\n") {
+        let end = position + paragraph.len();
+        cut(text, position, end, max_chars, &mut pieces);
+        position = end + 2;
+    }
+
+    let mut ranges = Vec::new();
+    let mut current: Option<(usize, usize, usize)> = None;
+    for (start, end, chars) in pieces {
+        current = Some(match current {
+            Some((open, close, open_chars)) => {
+                let merged = open_chars + text[close..start].chars().count() + chars;
+                if merged <= max_chars {
+                    (open, end, merged)
+                } else {
+                    ranges.push((open, close));
+                    (start, end, chars)
+                }
+            }
+            None => (start, end, chars),
+        });
+    }
+    ranges.extend(current.map(|(start, end, _)| (start, end)));
+    ranges
+}
+
+/// Splits the paragraph `text[start..end]` into pieces of at most
+/// `max_chars`, at the last space where possible; pushes
+/// `(start, end, chars)`.
+fn cut(
+    text: &str,
+    mut start: usize,
+    end: usize,
+    max_chars: usize,
+    pieces: &mut Vec<(usize, usize, usize)>,
+) {
+    while start < end {
+        let rest = &text[start..end];
+        let Some((limit, _)) = rest.char_indices().nth(max_chars) else {
+            pieces.push((start, end, rest.chars().count()));
+            return;
+        };
+        let split = if rest[limit..].starts_with(' ') {
+            limit
+        } else {
+            rest[..limit]
+                .rfind(' ')
+                .filter(|&space| space > 0)
+                .unwrap_or(limit)
+        };
+        pieces.push((start, start + split, rest[..split].chars().count()));
+        start += split;
+        start += text[start..end].len() - text[start..end].trim_start().len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Format, Section};
+
+    fn document(texts: &[&str]) -> Document {
+        Document {
+            format: Format::Pdf,
+            title: None,
+            pages: Some(texts.len() as u32),
+            sections: texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| Section {
+                    headings: Vec::new(),
+                    page: Some(i as u32 + 1),
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn paragraphs_merge_up_to_the_limit() {
+        let document = document(&["eins\n\nzwei\n\ndrei vier fünf", "Seite zwei"]);
+        let chunks = document.chunks(12);
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["eins\n\nzwei", "drei vier", "fünf", "Seite zwei"]);
+        let offsets: Vec<(usize, Option<u32>, usize, usize)> = chunks
+            .iter()
+            .map(|c| (c.section, c.page, c.start, c.end))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                (0, Some(1), 0, 10),
+                (0, Some(1), 12, 21),
+                (0, Some(1), 22, 26),
+                (1, Some(2), 0, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn offsets_index_characters_of_the_section() {
+        let text = "Überschrift äöü\n\n".to_string() + &"Wärme ".repeat(400);
+        let document = document(&[text.trim_end()]);
+        let chars: Vec<char> = document.sections[0].text.chars().collect();
+        let chunks = document.chunks(100);
+        assert!(chunks.len() > 20);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= 100);
+            let slice: String = chars[chunk.start..chunk.end].iter().collect();
+            assert_eq!(slice, chunk.text);
+            assert!(!chunk.text.starts_with(' ') && !chunk.text.ends_with(' '));
+        }
+        let unbroken = "x".repeat(250);
+        let chunks = document_chunks(&unbroken, 100);
+        assert_eq!(chunks, [100, 100, 50]);
+    }
+
+    fn document_chunks(text: &str, max_chars: usize) -> Vec<usize> {
+        document(&[text])
+            .chunks(max_chars)
+            .iter()
+            .map(|c| c.text.chars().count())
+            .collect()
+    }
+}
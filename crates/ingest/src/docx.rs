@@ -0,0 +1,229 @@
+//! Word documents (Office Open XML).
+
+use std::collections::HashMap;
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{archive::Archive, text::SectionBuilder, xml, Document, Format, IngestError};
+
+pub(crate) fn extract(bytes: &[u8]) -> Result<Document, IngestError> {
+    let mut archive = Archive::open(bytes, Format::Docx)?;
+    let styles = match archive.read("word/styles.xml")? {
+        Some(styles) => heading_styles(&String::from_utf8_lossy(&styles))?,
+        None => HashMap::new(),
+    };
+    let body = archive.read_text("word/document.xml")?;
+    let title = match archive.read("docProps/core.xml")? {
+        Some(core) => {
+            xml::element_text(&String::from_utf8_lossy(&core), b"dc:title", Format::Docx)?
+        }
+        None => None,
+    };
+    Ok(Document {
+        format: Format::Docx,
+        title,
+        pages: None,
+        sections: paragraphs(&body, &styles)?,
+    })
+}
+
+fn malformed(err: impl std::fmt::Display) -> IngestError {
+    IngestError::malformed(Format::Docx, err)
+}
+
+fn val(element: &BytesStart<'_>) -> Result<Option<String>, IngestError> {
+    xml::attribute(element, "w:val", Format::Docx)
+}
+
+/// Heading level per paragraph style id: styles named `heading N` or
+/// `Title`, or with an outline level. The ids themselves are localized
+/// (`berschrift1` in German Word), the names are not.
+fn heading_styles(source: &str) -> Result<HashMap<String, u8>, IngestError> {
+    let mut reader = Reader::from_str(source);
+    let mut levels = HashMap::new();
+    let mut style: Option<String> = None;
+    loop {
+        match reader.read_event().map_err(malformed)? {
+            Event::Start(element) if element.name().as_ref() == b"w:style" => {
+                style = xml::attribute(&element, "w:styleId", Format::Docx)?;
+            }
+            Event::End(element) if element.name().as_ref() == b"w:style" => style = None,
+            Event::Empty(element) => {
+                let Some(id) = &style else { continue };
+                let level = match element.name().as_ref() {
+                    b"w:name" => val(&element)?.and_then(|name| {
+                        let name = name.to_ascii_lowercase();
+                        match name.strip_prefix("heading ") {
+                            Some(level) => level.parse().ok(),
+                            None => (name == "title").then_some(1),
+                        }
+                    }),
+                    b"w:outlineLvl" => val(&element)?
+                        .and_then(|level| level.parse::<u8>().ok())
+                        .filter(|level| *level < 9)
+                        .map(|level| level + 1),
+                    _ => None,
+                };
+                if let Some(level) = level {
+                    levels.insert(id.clone(), level);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(levels)
+}
+
+/// Paragraph being read; text boxes nest paragraphs inside paragraphs.
+#[derive(Default)]
+struct Paragraph {
+    text: String,
+    level: Option<u8>,
+}
+
+fn paragraphs(
+    source: &str,
+    styles: &HashMap<String, u8>,
+) -> Result<Vec<crate::Section>, IngestError> {
+    let mut reader = Reader::from_str(source);
+    let mut builder = SectionBuilder::default();
+    let mut open: Vec<Paragraph> = Vec::new();
+    let mut in_text = false;
+    loop {
+        match reader.read_event().map_err(malformed)? {
+            Event::Start(element) => match element.name().as_ref() {
+                b"w:p" => open.push(Paragraph::default()),
+                b"w:t" => in_text = true,
+                _ => {}
+            },
+            Event::End(element) => match element.name().as_ref() {
+                b"w:t" => in_text = false,
+                b"w:p" => {
+                    let Some(paragraph) = open.pop() else {
+                        continue;
+                    };
+                    match paragraph.level {
+                        Some(level) => builder.heading(level, &paragraph.text),
+                        None => {
+                            builder.text(&paragraph.text);
+                            builder.paragraph_break();
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(element) => {
+                let Some(paragraph) = open.last_mut() else {
+                    continue;
+                };
+                match element.name().as_ref() {
+                    b"w:pStyle" => {
+                        if let Some(level) = val(&element)?.and_then(|id| styles.get(&id)) {
+                            paragraph.level.get_or_insert(*level);
+                        }
+                    }
+                    b"w:outlineLvl" => {
+                        paragraph.level = val(&element)?
+                            .and_then(|level| level.parse::<u8>().ok())
+                            .filter(|level| *level < 9)
+                            .map(|level| level + 1);
+                    }
+                    b"w:tab" => paragraph.text.push(' '),
+                    b"w:br" | b"w:cr" => paragraph.text.push('\n'),
+                    _ => {}
+                }
+            }
+            Event::Text(text) if in_text => {
+                if let Some(paragraph) = open.last_mut() {
+                    paragraph.text.push_str(&text.decode().map_err(malformed)?);
+                }
+            }
+            Event::GeneralRef(reference) if in_text => {
+                if let (Some(paragraph), Some(text)) = (open.last_mut(), xml::reference(&reference))
+                {
+                    paragraph.text.push_str(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::zip;
+
+    const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Standard"><w:name w:val="Normal"/></w:style>
+  <w:style w:type="paragraph" w:styleId="berschrift1"><w:name w:val="heading 1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Zwischentitel">
+    <w:name w:val="Zwischentitel"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr>
+  </w:style>
+</w:styles>"#;
+
+    const BODY: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:pPr><w:pStyle w:val="berschrift1"/></w:pPr><w:r><w:t>Angebot</w:t></w:r></w:p>
+    <w:p><w:r><w:t xml:space="preserve">Preis: 12 </w:t></w:r><w:r><w:t>&lt;netto&gt;</w:t></w:r>
+      <w:r><w:tab/><w:t>zzgl.</w:t><w:br/><w:t>MwSt.</w:t></w:r>
+      <w:r><w:delText>gestrichen</w:delText></w:r></w:p>
+    <w:p><w:pPr><w:pStyle w:val="Zwischentitel"/></w:pPr><w:r><w:t>Fristen</w:t></w:r></w:p>
+    <w:p><w:r><w:t>Gültig bis Mai.</w:t></w:r></w:p>
+    <w:sectPr/>
+  </w:body>
+</w:document>"#;
+
+    const CORE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <dc:title>Angebot Müller &amp; Sohn</dc:title>
+</cp:coreProperties>"#;
+
+    #[test]
+    fn docx_paragraphs_and_headings_are_read() {
+        let bytes = zip(&[
+            ("[Content_Types].xml", "<Types/>"),
+            ("word/document.xml", BODY),
+            ("word/styles.xml", STYLES),
+            ("docProps/core.xml", CORE),
+        ]);
+        assert_eq!(Format::sniff(&bytes), Some(Format::Docx));
+        let document = crate::extract(&bytes, Format::Docx).unwrap();
+        assert_eq!(document.title.as_deref(), Some("Angebot Müller & Sohn"));
+        let sections: Vec<(Vec<&str>, &str)> = document
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.headings.iter().map(String::as_str).collect(),
+                    s.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (vec!["Angebot"], "Angebot\n\nPreis: 12 <netto> zzgl. MwSt."),
+                (vec!["Angebot", "Fristen"], "Fristen\n\nGültig bis Mai."),
+            ]
+        );
+    }
+
+    #[test]
+    fn docx_without_body_is_malformed() {
+        let bytes = zip(&[("word/styles.xml", STYLES)]);
+        let err = crate::extract(&bytes, Format::Docx).unwrap_err();
+        assert!(
+            matches!(&err, IngestError::Malformed { format: Format::Docx, message } if message.contains("word/document.xml")),
+            "{err}"
+        );
+    }
+}
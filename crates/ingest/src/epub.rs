@@ -0,0 +1,193 @@
+//! EPUB 2 and 3: the chapters in spine order, each read as HTML.
+
+use std::collections::HashMap;
+
+use percent_encoding::percent_decode_str;
+use quick_xml::{events::Event, Reader};
+
+use crate::{archive::Archive, html, text::SectionBuilder, xml, Document, Format, IngestError};
+
+pub(crate) fn extract(bytes: &[u8]) -> Result<Document, IngestError> {
+    let mut archive = Archive::open(bytes, Format::Epub)?;
+    let container = archive.read_text("META-INF/container.xml")?;
+    let package_path = rootfile(&container)?
+        .ok_or_else(|| IngestError::malformed(Format::Epub, "container.xml names no rootfile"))?;
+    let package = archive.read_text(&package_path)?;
+    let base = package_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let mut builder = SectionBuilder::default();
+    let mut title = xml::element_text(&package, b"dc:title", Format::Epub)?;
+    for href in chapters(&package)? {
+        let Some(chapter) = archive.read(&resolve(base, &href))? else {
+            continue;
+        };
+        let chapter_title = html::read_into(&mut builder, &String::from_utf8_lossy(&chapter));
+        title = title.or(chapter_title);
+        // Sections end with their chapter.
+        builder.flush();
+    }
+    Ok(Document {
+        format: Format::Epub,
+        title,
+        pages: None,
+        sections: builder.finish(),
+    })
+}
+
+fn malformed(err: impl std::fmt::Display) -> IngestError {
+    IngestError::malformed(Format::Epub, err)
+}
+
+/// `full-path` of the first `rootfile` in `META-INF/container.xml`.
+fn rootfile(container: &str) -> Result<Option<String>, IngestError> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event().map_err(malformed)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"rootfile" =>
+            {
+                return xml::attribute(&element, "full-path", Format::Epub);
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Hrefs of the (X)HTML documents in the spine, in reading order; items
+/// marked `linear="no"` (notes, covers) are left out.
+fn chapters(package: &str) -> Result<Vec<String>, IngestError> {
+    let mut reader = Reader::from_str(package);
+    let mut manifest = HashMap::new();
+    let mut spine = Vec::new();
+    loop {
+        match reader.read_event().map_err(malformed)? {
+            Event::Start(element) | Event::Empty(element) => {
+                let attribute = |name| xml::attribute(&element, name, Format::Epub);
+                match element.local_name().as_ref() {
+                    b"item" => {
+                        let markup = attribute("media-type")?.is_some_and(|media_type| {
+                            media_type == "application/xhtml+xml" || media_type == "text/html"
+                        });
+                        if let (true, Some(id), Some(href)) =
+                            (markup, attribute("id")?, attribute("href")?)
+                        {
+                            manifest.insert(id, href);
+                        }
+                    }
+                    b"itemref" if attribute("linear")?.as_deref() != Some("no") => {
+                        spine.extend(attribute("idref")?);
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(spine
+        .into_iter()
+        .filter_map(|id| manifest.get(&id).cloned())
+        .collect())
+}
+
+/// Container path of `href` relative to the package directory `base`.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode_str(href).decode_utf8_lossy();
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::zip;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    const PACKAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Der Garten</dc:title>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="c2" href="chap2.xhtml#start" media-type="application/xhtml+xml"/>
+    <item id="c1" href="Text/Kapitel%201.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="style.css" media-type="text/css"/>
+  </manifest>
+  <spine>
+    <itemref idref="nav" linear="no"/>
+    <itemref idref="c1"/>
+    <itemref idref="css"/>
+    <itemref idref="c2"/>
+  </spine>
+</package>"#;
+
+    #[test]
+    fn epub_chapters_are_read_in_spine_order() {
+        let bytes = zip(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", PACKAGE),
+            ("OEBPS/nav.xhtml", "<html><body><p>Inhalt</p></body></html>"),
+            (
+                "OEBPS/Text/Kapitel 1.xhtml",
+                r#"<?xml version="1.0"?><html xmlns="http://www.w3.org/1999/xhtml"><head><title>Eins</title></head>
+<body><h1>Frühling</h1><p>Beete vorbereiten.</p></body></html>"#,
+            ),
+            (
+                "OEBPS/chap2.xhtml",
+                "<html><body><p>Gießen nicht vergessen.</p></body></html>",
+            ),
+        ]);
+        assert_eq!(Format::sniff(&bytes), Some(Format::Epub));
+        let document = crate::extract(&bytes, Format::Epub).unwrap();
+        assert_eq!(document.title.as_deref(), Some("Der Garten"));
+        let sections: Vec<(Vec<&str>, &str)> = document
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.headings.iter().map(String::as_str).collect(),
+                    s.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (vec!["Frühling"], "Frühling\n\nBeete vorbereiten."),
+                (vec!["Frühling"], "Gießen nicht vergessen."),
+            ]
+        );
+    }
+
+    #[test]
+    fn hrefs_resolve_against_the_package_directory() {
+        assert_eq!(
+            resolve("OEBPS", "Text/a%20b.xhtml#p1"),
+            "OEBPS/Text/a b.xhtml"
+        );
+        assert_eq!(
+            resolve("OEBPS/pkg", "../Text/c.xhtml"),
+            "OEBPS/Text/c.xhtml"
+        );
+        assert_eq!(resolve("", "./c.xhtml"), "c.xhtml");
+    }
+}
@@ -0,0 +1,166 @@
+//! HTML and XHTML, also the chapters of an EPUB.
+
+use ego_tree::iter::Edge;
+use scraper::{Html, Node};
+
+use crate::{text::SectionBuilder, Document, Format};
+
+/// Elements whose content is no document text.
+const SKIPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "object",
+];
+
+/// Elements that start and end a paragraph.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "caption",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+pub(crate) fn extract(source: &str) -> Document {
+    let mut builder = SectionBuilder::default();
+    let title = read_into(&mut builder, source);
+    Document {
+        format: Format::Html,
+        title,
+        pages: None,
+        sections: builder.finish(),
+    }
+}
+
+/// Adds the body of `source` to `builder`; returns the `<title>`.
+pub(crate) fn read_into(builder: &mut SectionBuilder, source: &str) -> Option<String> {
+    let html = Html::parse_document(source);
+    let mut title: Option<String> = None;
+    let mut in_title = false;
+    // Depth inside a skipped element.
+    let mut skipped = 0usize;
+    for edge in html.tree.root().traverse() {
+        match edge {
+            Edge::Open(node) => match node.value() {
+                Node::Text(text) if in_title => {
+                    title.get_or_insert_with(String::new).push_str(text);
+                }
+                // Source line breaks are plain whitespace in HTML.
+                Node::Text(text) if skipped == 0 => builder.text(&text.replace(['\r', '\n'], " ")),
+                Node::Element(element) => {
+                    let name = element.name();
+                    in_title = name == "title"
+                        && node
+                            .parent()
+                            .and_then(|parent| parent.value().as_element())
+                            .is_some_and(|parent| parent.name() == "head");
+                    if skipped > 0 || SKIPPED.contains(&name) {
+                        skipped += 1;
+                    } else if let Some(level) = heading_level(name) {
+                        builder.begin_heading(level);
+                    } else if name == "br" {
+                        builder.line_break();
+                    } else if BLOCKS.contains(&name) {
+                        builder.paragraph_break();
+                    }
+                }
+                _ => {}
+            },
+            Edge::Close(node) => {
+                let Some(element) = node.value().as_element() else {
+                    continue;
+                };
+                let name = element.name();
+                in_title = false;
+                if skipped > 0 {
+                    skipped -= 1;
+                } else if heading_level(name).is_some() {
+                    builder.end_heading();
+                } else if BLOCKS.contains(&name) {
+                    builder.paragraph_break();
+                }
+            }
+        }
+    }
+    builder.paragraph_break();
+    title
+}
+
+fn heading_level(name: &str) -> Option<u8> {
+    match name.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_is_read_by_headings_and_blocks() {
+        let document = extract(
+            r#"<!DOCTYPE html>
+<html><head><title>Wartung &amp; Pflege</title><style>p { color: red }</style></head>
+<body>
+  <nav><a href="/">Start</a></nav>
+  <h1>Heizung</h1>
+  <p>Im Herbst
+     entlüften.<br>Danach Druck prüfen.</p>
+  <script>track();</script>
+  <h2>Ventile</h2>
+  <ul><li>Thermostat</li><li>Rücklauf</li></ul>
+  <h1>Wasser</h1><div>Filter <b>monatlich</b> spülen.</div>
+</body></html>"#,
+        );
+        assert_eq!(document.title.as_deref(), Some("Wartung & Pflege"));
+        let sections: Vec<(Vec<&str>, &str)> = document
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.headings.iter().map(String::as_str).collect(),
+                    s.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (vec![], "Start"),
+                (
+                    vec!["Heizung"],
+                    "Heizung\n\nIm Herbst entlüften. Danach Druck prüfen."
+                ),
+                (
+                    vec!["Heizung", "Ventile"],
+                    "Ventile\n\nThermostat\n\nRücklauf"
+                ),
+                (vec!["Wasser"], "Wasser\n\nFilter monatlich spülen."),
+            ]
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! Text aus PDF, HTML, DOCX und EPUB für den Index.
+//!
+//! [`extract`] liest eine Datei in ein [`Document`]: Titel, Seitenzahl und
+//! eine Folge von [`Section`]s mit Überschriftenpfad (`headings`), Seite
+//! (nur PDF) und normalisiertem Text.
+//!
+//! - **PDF:** eine Section je Seite; die Überschriften kommen aus den
+//!   Lesezeichen (Outline), soweit vorhanden. Gescannte PDFs ohne Textebene
+//!   liefern [`IngestError::Empty`].
+//! - **HTML:** `h1`–`h6` gliedern, Block-Elemente trennen Absätze; Skripte,
+//!   Styles und `<head>` (bis auf `<title>`) fallen weg.
+//! - **DOCX:** Absätze aus `word/document.xml`, Überschriften über die
+//!   Formatvorlagen (`heading N`/Gliederungsebene) aus `word/styles.xml`,
+//!   Titel aus `docProps/core.xml`.
+//! - **EPUB:** Kapitel in der Reihenfolge des Spine, jedes wie HTML gelesen.
+//!
+//! Normalisiert wird einheitlich: Unicode NFC, Ligaturen aufgelöst,
+//! unsichtbare Zeichen entfernt, Silbentrennung am Zeilenende zurückgenommen,
+//! Leerraum zusammengefasst; Absätze sind durch eine Leerzeile getrennt.
+//!
+//! [`Document::chunks`] teilt jede Section in Chunks aus ganzen Absätzen bis
+//! `max_chars` Zeichen (längere Absätze an Wortgrenzen). Jeder Chunk kennt
+//! Section, Seite, Überschriften und seine Zeichen-Offsets (`start`..`end`)
+//! im Text der Section; [`Document::upsert_request`] legt sie als
+//! Chunk-Metadaten ab.
+
+mod archive;
+mod chunk;
+mod docx;
+mod epub;
+mod html;
+mod pdf;
+mod text;
+mod xml;
+
+use std::{fmt, str::FromStr};
+
+use hauski_indexd::{ChunkPayload, SourceRef, TrustLevel, UpsertRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+pub use chunk::Chunk;
+pub use text::normalize;
+
+/// Default chunk size in characters (as `hauski index upsert`).
+pub const DEFAULT_CHUNK_CHARS: usize = 1_200;
+/// Namespace for ingested documents unless configured otherwise.
+pub const DEFAULT_NAMESPACE: &str = "docs";
+/// `source_ref.origin` of ingested documents.
+pub const ORIGIN: &str = "ingest";
+
+/// Document id derived from the file content, so ingesting the same file
+/// again replaces the document instead of adding a copy.
+pub fn doc_id(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hex: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("ingest-{hex}")
+}
+
+/// Provenance of a document read from `source` (file name or path).
+pub fn source_ref(source: &str) -> SourceRef {
+    SourceRef {
+        origin: ORIGIN.to_string(),
+        id: source.to_string(),
+        offset: None,
+        trust_level: TrustLevel::default_for_origin(ORIGIN),
+        injected_by: None,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("unsupported format: {0}")]
+    Unsupported(String),
+    #[error("{format} is malformed: {message}")]
+    Malformed { format: Format, message: String },
+    #[error("{0} is encrypted")]
+    Encrypted(Format),
+    #[error("{format} entry {entry} exceeds {limit} bytes when unpacked")]
+    TooLarge {
+        format: Format,
+        entry: String,
+        limit: u64,
+    },
+    #[error("{0} contains no extractable text")]
+    Empty(Format),
+}
+
+impl IngestError {
+    /// Format of the rejected file, once it was recognized.
+    pub fn format(&self) -> Option<Format> {
+        match self {
+            Self::Unsupported(_) => None,
+            Self::Malformed { format, .. } | Self::TooLarge { format, .. } => Some(*format),
+            Self::Encrypted(format) | Self::Empty(format) => Some(*format),
+        }
+    }
+
+    pub(crate) fn malformed(format: Format, err: impl fmt::Display) -> Self {
+        Self::Malformed {
+            format,
+            message: err.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Pdf,
+    Html,
+    Docx,
+    Epub,
+}
+
+impl Format {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Html => "html",
+            Self::Docx => "docx",
+            Self::Epub => "epub",
+        }
+    }
+
+    /// Format for a file name by its extension.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        extension.parse().ok()
+    }
+
+    /// Format by content: `%PDF-`, a ZIP with `word/document.xml` or an EPUB
+    /// `mimetype`, or markup starting like HTML.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"%PDF-") {
+            return Some(Self::Pdf);
+        }
+        if bytes.starts_with(b"PK\x03\x04") {
+            return archive::sniff(bytes);
+        }
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_lowercase();
+        let head = head.trim_start_matches('\u{feff}').trim_start();
+        let markup = ["<!doctype html", "<html", "<?xml", "<head", "<body"];
+        markup
+            .iter()
+            .any(|start| head.starts_with(start))
+            .then_some(Self::Html)
+    }
+
+    /// Content first, then the extension of `name`.
+    pub fn detect(name: Option<&str>, bytes: &[u8]) -> Result<Self, IngestError> {
+        Self::sniff(bytes)
+            .or_else(|| name.and_then(Self::from_file_name))
+            .ok_or_else(|| IngestError::Unsupported(name.unwrap_or("upload").to_string()))
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "pdf" => Ok(Self::Pdf),
+            "html" | "htm" | "xhtml" => Ok(Self::Html),
+            "docx" => Ok(Self::Docx),
+            "epub" => Ok(Self::Epub),
+            other => Err(format!("unknown format '{other}' (pdf, html, docx, epub)")),
+        }
+    }
+}
+
+/// A part of a document below one heading (or one PDF page).
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Section {
+    /// Enclosing headings, outermost first.
+    pub headings: Vec<String>,
+    /// 1-based page (PDF only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    /// Normalized paragraphs, separated by a blank line.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Document {
+    pub format: Format,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Page count (PDF only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<u32>,
+    pub sections: Vec<Section>,
+}
+
+impl Document {
+    /// Characters of text over all sections.
+    pub fn chars(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|section| section.text.chars().count())
+            .sum()
+    }
+
+    /// Chunks of whole paragraphs up to `max_chars`, section by section.
+    pub fn chunks(&self, max_chars: usize) -> Vec<Chunk> {
+        chunk::split(self, max_chars.max(1))
+    }
+
+    /// The `/index/upsert` request for `chunks` of this document; chunk ids
+    /// are `<doc_id>#<n>`.
+    pub fn upsert_request(
+        &self,
+        chunks: &[Chunk],
+        doc_id: &str,
+        namespace: &str,
+        source_ref: SourceRef,
+    ) -> UpsertRequest {
+        let chunks = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| ChunkPayload {
+                chunk_id: Some(format!("{doc_id}#{i}")),
+                text: Some(chunk.text.clone()),
+                text_lower: None,
+                embedding: Vec::new(),
+                meta: chunk.meta(),
+            })
+            .collect();
+        UpsertRequest {
+            doc_id: doc_id.to_string(),
+            namespace: namespace.to_string(),
+            chunks,
+            meta: json!({
+                "kind": self.format,
+                "title": self.title,
+                "pages": self.pages,
+                "sections": self.sections.len(),
+                "source": source_ref.id,
+            }),
+            source_ref: Some(source_ref),
+        }
+    }
+}
+
+/// Extracts and normalizes the text of `bytes` in `format`.
+pub fn extract(bytes: &[u8], format: Format) -> Result<Document, IngestError> {
+    let mut document = match format {
+        Format::Pdf => pdf::extract(bytes)?,
+        Format::Html => html::extract(&String::from_utf8_lossy(bytes)),
+        Format::Docx => docx::extract(bytes)?,
+        Format::Epub => epub::extract(bytes)?,
+    };
+    document.sections.retain(|section| !section.text.is_empty());
+    if document.sections.is_empty() {
+        return Err(IngestError::Empty(format));
+    }
+    document.title = document
+        .title
+        .map(|title| normalize(&title))
+        .filter(|title| !title.is_empty())
+        .or_else(|| document.sections[0].headings.first().cloned());
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_detected_by_content_before_extension() {
+        assert_eq!(Format::sniff(b"%PDF-1.7\n"), Some(Format::Pdf));
+        assert_eq!(
+            Format::sniff(b"\xef\xbb\xbf <!DOCTYPE html><p>x"),
+            Some(Format::Html)
+        );
+        assert_eq!(Format::sniff(b"plain text"), None);
+        assert_eq!(
+            Format::detect(Some("bericht.pdf"), b"<html><body>x</body></html>").unwrap(),
+            Format::Html
+        );
+        assert_eq!(
+            Format::detect(Some("Kapitel.XHTML"), b"Text").unwrap(),
+            Format::Html
+        );
+        assert!(matches!(
+            Format::detect(Some("notes.md"), b"# Notes"),
+            Err(IngestError::Unsupported(name)) if name == "notes.md"
+        ));
+    }
+
+    #[test]
+    fn documents_without_text_are_rejected() {
+        let err = extract(b"<html><script>var x;</script></html>", Format::Html).unwrap_err();
+        assert!(matches!(err, IngestError::Empty(Format::Html)));
+    }
+
+    #[test]
+    fn upsert_requests_carry_offsets_and_provenance() {
+        let document = extract(
+            b"<title>Handbuch</title><h1>Heizung</h1><p>Entl\xc3\xbcften im Herbst.</p>",
+            Format::Html,
+        )
+        .unwrap();
+        assert_eq!(document.title.as_deref(), Some("Handbuch"));
+        let chunks = document.chunks(DEFAULT_CHUNK_CHARS);
+        let request = document.upsert_request(
+            &chunks,
+            "handbuch.html",
+            DEFAULT_NAMESPACE,
+            source_ref("handbuch.html"),
+        );
+        assert_eq!(request.chunks.len(), 1);
+        let chunk = &request.chunks[0];
+        assert_eq!(chunk.chunk_id.as_deref(), Some("handbuch.html#0"));
+        assert_eq!(
+            chunk.text.as_deref(),
+            Some("Heizung\n\nEntlüften im Herbst.")
+        );
+        assert_eq!(
+            chunk.meta,
+            json!({ "section": 0, "headings": ["Heizung"], "start": 0, "end": 29 })
+        );
+        assert_eq!(request.meta["kind"], "html");
+        assert_eq!(request.meta["title"], "Handbuch");
+        let source_ref = request.source_ref.unwrap();
+        assert_eq!(source_ref.origin, "ingest");
+        assert_eq!(source_ref.trust_level, TrustLevel::Medium);
+        assert_eq!(doc_id(b"x"), doc_id(b"x"));
+        assert_ne!(doc_id(b"x"), doc_id(b"y"));
+        assert_eq!(doc_id(b"x").len(), "ingest-".len() + 16);
+    }
+}
@@ -0,0 +1,198 @@
+//! PDF: one section per page, headings from the document outline.
+
+use lopdf::{decode_text_string, Document as Pdf};
+
+use crate::{normalize, Document, Format, IngestError, Section};
+
+pub(crate) fn extract(bytes: &[u8]) -> Result<Document, IngestError> {
+    let pdf = Pdf::load_mem(bytes).map_err(|err| IngestError::malformed(Format::Pdf, err))?;
+    // Loading already decrypts documents with an empty user password.
+    if pdf.is_encrypted() && pdf.encryption_state.is_none() {
+        return Err(IngestError::Encrypted(Format::Pdf));
+    }
+
+    let outline = pdf.get_toc().map(|toc| toc.toc).unwrap_or_default();
+    let mut outline = outline.into_iter().peekable();
+    let mut headings: Vec<String> = Vec::new();
+    let mut sections = Vec::new();
+    let mut failure = None;
+    let pages = pdf.get_pages();
+    for &page in pages.keys() {
+        while let Some(entry) = outline.next_if(|entry| entry.page <= page as usize) {
+            let title = normalize(&entry.title);
+            if !title.is_empty() {
+                headings.truncate(entry.level.saturating_sub(1));
+                headings.push(title);
+            }
+        }
+        // Pages with unreadable fonts keep whatever text could be read.
+        let mut text = String::new();
+        for fragment in pdf.extract_text_chunks(&[page]) {
+            match fragment {
+                Ok(fragment) => text.push_str(&fragment),
+                Err(err) => failure = Some(err),
+            }
+        }
+        sections.push(Section {
+            headings: headings.clone(),
+            page: Some(page),
+            text: normalize(&text),
+        });
+    }
+    if let Some(err) = failure {
+        if sections.iter().all(|section| section.text.is_empty()) {
+            return Err(IngestError::malformed(Format::Pdf, err));
+        }
+    }
+
+    let title = pdf
+        .trailer
+        .get_deref(b"Info", &pdf)
+        .and_then(|info| info.as_dict())
+        .and_then(|info| info.get_deref(b"Title", &pdf))
+        .and_then(decode_text_string)
+        .ok();
+    Ok(Document {
+        format: Format::Pdf,
+        title,
+        pages: Some(pages.len() as u32),
+        sections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lopdf::{
+        content::{Content, Operation},
+        dictionary, Bookmark, Object, ObjectId, Stream,
+    };
+
+    use super::*;
+
+    /// A PDF with one page per text and an outline entry
+    /// `(level, title, page index)` per bookmark.
+    fn pdf(pages: &[&str], bookmarks: &[(u8, &str, usize)]) -> Vec<u8> {
+        let mut pdf = Pdf::with_version("1.5");
+        let pages_id = pdf.new_object_id();
+        let font_id = pdf.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_ids: Vec<ObjectId> = pages
+            .iter()
+            .map(|text| {
+                // One text object per line, as most producers write them.
+                let operations: Vec<Operation> = text
+                    .lines()
+                    .enumerate()
+                    .flat_map(|(i, line)| {
+                        [
+                            Operation::new("BT", vec![]),
+                            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                            Operation::new("Td", vec![72.into(), (720 - 14 * i as i64).into()]),
+                            Operation::new("Tj", vec![Object::string_literal(line)]),
+                            Operation::new("ET", vec![]),
+                        ]
+                    })
+                    .collect();
+                let content = Content { operations }.encode().unwrap();
+                let content_id = pdf.add_object(Stream::new(dictionary! {}, content));
+                pdf.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                })
+            })
+            .collect();
+        pdf.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => page_ids.iter().map(|&id| id.into()).collect::<Vec<Object>>(),
+                "Count" => page_ids.len() as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+
+        let mut parents: Vec<u32> = Vec::new();
+        for &(level, title, page) in bookmarks {
+            parents.truncate(usize::from(level) - 1);
+            let bookmark = Bookmark::new(title.to_string(), [0.0; 3], 0, page_ids[page]);
+            parents.push(pdf.add_bookmark(bookmark, parents.last().copied()));
+        }
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if let Some(outline_id) = pdf.build_outline() {
+            catalog.set("Outlines", outline_id);
+        }
+        let catalog_id = pdf.add_object(catalog);
+        let info_id = pdf.add_object(dictionary! {
+            "Title" => Object::string_literal("Bedienungsanleitung"),
+        });
+        pdf.trailer.set("Root", catalog_id);
+        pdf.trailer.set("Info", info_id);
+
+        let mut bytes = Vec::new();
+        pdf.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn pdf_pages_become_sections_under_outline_headings() {
+        let bytes = pdf(
+            &[
+                "Sicherheitshinweise lesen.",
+                "Filter alle drei Mo-\nnate reinigen.",
+                "Fehlercodes siehe Tabelle.",
+            ],
+            &[(1, "Einleitung", 0), (1, "Wartung", 1), (2, "Fehler", 2)],
+        );
+        assert_eq!(Format::sniff(&bytes), Some(Format::Pdf));
+        let document = crate::extract(&bytes, Format::Pdf).unwrap();
+        assert_eq!(document.title.as_deref(), Some("Bedienungsanleitung"));
+        assert_eq!(document.pages, Some(3));
+        let sections: Vec<(Option<u32>, Vec<&str>, &str)> = document
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.page,
+                    s.headings.iter().map(String::as_str).collect(),
+                    s.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (Some(1), vec!["Einleitung"], "Sicherheitshinweise lesen."),
+                (
+                    Some(2),
+                    vec!["Wartung"],
+                    "Filter alle drei Monate reinigen."
+                ),
+                (
+                    Some(3),
+                    vec!["Wartung", "Fehler"],
+                    "Fehlercodes siehe Tabelle."
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn garbage_is_malformed() {
+        let err = crate::extract(b"%PDF-1.7\nnot really", Format::Pdf).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                IngestError::Malformed {
+                    format: Format::Pdf,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Normalization and the section builder shared by the markup formats.
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::Section;
+
+/// Normalizes extracted text: NFC, ligatures and special spaces replaced,
+/// invisible characters dropped, hyphenation at line ends undone, whitespace
+/// collapsed. Blank lines separate paragraphs; the result joins them with
+/// exactly one blank line.
+pub fn normalize(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for c in text.replace("\r\n", "\n").nfc() {
+        match c {
+            '\u{fb00}' => cleaned.push_str("ff"),
+            '\u{fb01}' => cleaned.push_str("fi"),
+            '\u{fb02}' => cleaned.push_str("fl"),
+            '\u{fb03}' => cleaned.push_str("ffi"),
+            '\u{fb04}' => cleaned.push_str("ffl"),
+            '\u{fb05}' | '\u{fb06}' => cleaned.push_str("st"),
+            '\r' | '\u{2028}' | '\u{2029}' | '\u{0c}' => cleaned.push('\n'),
+            '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => {
+                cleaned.push(' ')
+            }
+            '\u{ad}' | '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => {}
+            '\n' | '\t' => cleaned.push(c),
+            c if c.is_control() => {}
+            c => cleaned.push(c),
+        }
+    }
+
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for line in cleaned.split('\n') {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if current.is_empty() {
+            current = line;
+        } else if let Some(cut) = hyphen_break(&current, &line) {
+            if cut {
+                current.pop();
+            }
+            current.push_str(&line);
+        } else {
+            current.push(' ');
+            current.push_str(&line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.join("\n\n")
+}
+
+/// `current` ends in a word with `-` that `next` continues: `Some(true)` for
+/// a cut word (`Heiz-` `körper`), `Some(false)` for a compound that keeps its
+/// hyphen (`Nord-` `Süd`).
+fn hyphen_break(current: &str, next: &str) -> Option<bool> {
+    let mut end = current.chars().rev();
+    if end.next() != Some('-') || !end.next().is_some_and(char::is_alphabetic) {
+        return None;
+    }
+    let first = next.chars().next()?;
+    first.is_alphabetic().then(|| first.is_lowercase())
+}
+
+/// Collects text and headings into [`Section`]s.
+#[derive(Debug, Default)]
+pub(crate) struct SectionBuilder {
+    sections: Vec<Section>,
+    /// Open headings with their level, outermost first.
+    headings: Vec<(u8, String)>,
+    paragraphs: Vec<String>,
+    /// The section holds more than its own headings.
+    has_body: bool,
+    current: String,
+    /// Level and text of the heading being read.
+    heading: Option<(u8, String)>,
+}
+
+impl SectionBuilder {
+    pub(crate) fn text(&mut self, text: &str) {
+        match &mut self.heading {
+            Some((_, heading)) => heading.push_str(text),
+            None => self.current.push_str(text),
+        }
+    }
+
+    pub(crate) fn line_break(&mut self) {
+        self.text("\n");
+    }
+
+    pub(crate) fn paragraph_break(&mut self) {
+        if self.heading.is_some() {
+            return;
+        }
+        let paragraph = normalize(&self.current);
+        self.current.clear();
+        if !paragraph.is_empty() {
+            self.paragraphs.push(paragraph);
+            self.has_body = true;
+        }
+    }
+
+    pub(crate) fn begin_heading(&mut self, level: u8) {
+        self.paragraph_break();
+        self.heading = Some((level, String::new()));
+    }
+
+    /// Closes the heading; it opens a new section and becomes its first
+    /// paragraph. A heading directly below its parent keeps the parent's
+    /// heading text in the same section.
+    pub(crate) fn end_heading(&mut self) {
+        let Some((level, text)) = self.heading.take() else {
+            return;
+        };
+        let text = normalize(&text).replace("\n\n", " ");
+        if text.is_empty() {
+            return;
+        }
+        let nested = self.headings.last().is_some_and(|(open, _)| *open < level);
+        if self.has_body || !nested {
+            self.flush();
+        }
+        self.headings.retain(|(open, _)| *open < level);
+        self.headings.push((level, text.clone()));
+        self.paragraphs.push(text);
+    }
+
+    pub(crate) fn heading(&mut self, level: u8, text: &str) {
+        self.begin_heading(level);
+        self.text(text);
+        self.end_heading();
+    }
+
+    /// Ends the current section, e.g. at a chapter boundary.
+    pub(crate) fn flush(&mut self) {
+        self.paragraph_break();
+        if !self.paragraphs.is_empty() {
+            self.sections.push(Section {
+                headings: self.headings.iter().map(|(_, text)| text.clone()).collect(),
+                page: None,
+                text: self.paragraphs.join("\n\n"),
+            });
+        }
+        self.paragraphs.clear();
+        self.has_body = false;
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<Section> {
+        self.end_heading();
+        self.flush();
+        self.sections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization_repairs_extraction_artifacts() {
+        assert_eq!(
+            normalize(
+                "Die Of\u{fb01}ce-\nSoftware  ist\u{a0}neu.\r\n\r\n\n Zwei\u{ad}ter\tAbsatz "
+            ),
+            "Die Office-Software ist neu.\n\nZweiter Absatz"
+        );
+        assert_eq!(normalize("Heiz-\nkörper"), "Heizkörper");
+        assert_eq!(normalize("Nord-\nSüd"), "Nord-Süd");
+        assert_eq!(normalize("Äpfel -\nBirnen"), "Äpfel - Birnen");
+        assert_eq!(normalize("u\u{308}ber"), "über");
+        assert_eq!(normalize(" \n\u{200b}\n "), "");
+    }
+
+    #[test]
+    fn sections_follow_the_heading_hierarchy() {
+        let mut builder = SectionBuilder::default();
+        builder.text("Vorwort.");
+        builder.paragraph_break();
+        builder.heading(1, "Teil 1");
+        builder.heading(2, "Kapitel A");
+        builder.text("Text A.");
+        builder.heading(2, "Kapitel B");
+        builder.text("Text B.");
+        builder.heading(1, "Teil 2");
+        let sections = builder.finish();
+        let summary: Vec<(Vec<&str>, &str)> = sections
+            .iter()
+            .map(|s| {
+                (
+                    s.headings.iter().map(String::as_str).collect(),
+                    s.text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (vec![], "Vorwort."),
+                (
+                    vec!["Teil 1", "Kapitel A"],
+                    "Teil 1\n\nKapitel A\n\nText A."
+                ),
+                (vec!["Teil 1", "Kapitel B"], "Kapitel B\n\nText B."),
+                (vec!["Teil 2"], "Teil 2"),
+            ]
+        );
+    }
+}
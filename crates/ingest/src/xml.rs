@@ -0,0 +1,75 @@
+//! Helpers for the XML inside DOCX and EPUB containers.
+
+use quick_xml::{
+    escape::resolve_predefined_entity,
+    events::{BytesRef, BytesStart, Event},
+    Reader,
+};
+
+use crate::{Format, IngestError};
+
+/// Unescaped value of the attribute `name` (qualified, e.g. `w:val`).
+pub(crate) fn attribute(
+    element: &BytesStart<'_>,
+    name: &str,
+    format: Format,
+) -> Result<Option<String>, IngestError> {
+    let malformed = |err: String| IngestError::malformed(format, err);
+    match element
+        .try_get_attribute(name)
+        .map_err(|err| malformed(err.to_string()))?
+    {
+        Some(attribute) => Ok(Some(
+            attribute
+                .unescape_value()
+                .map_err(|err| malformed(err.to_string()))?
+                .into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Text of an entity or character reference such as `&amp;` or `&#228;`.
+pub(crate) fn reference(reference: &BytesRef<'_>) -> Option<String> {
+    if let Ok(Some(c)) = reference.resolve_char_ref() {
+        return Some(c.to_string());
+    }
+    let name = reference.decode().ok()?;
+    resolve_predefined_entity(&name).map(str::to_string)
+}
+
+/// Text of the first element named `name` (qualified, e.g. `dc:title`).
+pub(crate) fn element_text(
+    xml: &str,
+    name: &[u8],
+    format: Format,
+) -> Result<Option<String>, IngestError> {
+    let mut reader = Reader::from_str(xml);
+    let mut text: Option<String> = None;
+    loop {
+        match reader
+            .read_event()
+            .map_err(|err| IngestError::malformed(format, err))?
+        {
+            Event::Start(element) if element.name().as_ref() == name => {
+                text = Some(String::new());
+            }
+            Event::End(element) if element.name().as_ref() == name => return Ok(text),
+            Event::Text(content) => {
+                if let Some(text) = &mut text {
+                    let content = content
+                        .decode()
+                        .map_err(|err| IngestError::malformed(format, err))?;
+                    text.push_str(&content);
+                }
+            }
+            Event::GeneralRef(entity) => {
+                if let (Some(text), Some(resolved)) = (&mut text, reference(&entity)) {
+                    text.push_str(&resolved);
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
@@ -14,10 +14,13 @@ use prometheus_client::{
     encoding::{EncodeLabelSet, LabelSetEncoder},
     metrics::{counter::Counter, family::Family},
 };
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tokio::task::{self, JoinHandle};
 
+mod queue;
+pub use queue::{QueueStats, QueuedTask, RetryDecision, TaskStatus};
+
 // ---------- Connection Manager ----------
 
 #[derive(Debug)]
@@ -112,6 +115,37 @@ pub enum TtlUpdate {
     /// TTL bleibt unverändert (nur sinnvoll, wenn ein Eintrag existiert).
     Preserve,
 }
+
+/// Parameter für [`MemoryStore::list`] (Keyset-Pagination, sortiert nach Key).
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Nur Keys mit diesem Präfix (wörtlich, keine LIKE-Wildcards).
+    pub prefix: String,
+    /// Cursor: nur Keys strikt nach diesem Key.
+    pub after: Option<String>,
+    /// Maximale Anzahl an Einträgen pro Seite.
+    pub limit: usize,
+    /// Werte mitladen; ohne werden nur Metadaten gelesen.
+    pub include_values: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedItem {
+    pub key: String,
+    /// `None`, wenn ohne `include_values` gelistet wurde.
+    pub value: Option<Vec<u8>>,
+    pub ttl_sec: Option<i64>,
+    pub pinned: bool,
+    pub created_ts: DateTime<Utc>,
+    pub updated_ts: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListPage {
+    pub items: Vec<ListedItem>,
+    /// Key des letzten Eintrags, falls weitere Seiten existieren.
+    pub next_cursor: Option<String>,
+}
 #[derive(Clone, Debug)]
 pub struct MemoryConfig {
     /// Optionaler Pfad zur DB-Datei. Default: $`XDG_STATE_HOME/hauski/memory.db`
@@ -150,15 +184,22 @@ pub fn init_default() -> Result<&'static MemoryStore> {
     init_with(MemoryConfig::default())
 }
 
+impl MemoryConfig {
+    /// `db_path` oder der Default unter `$XDG_STATE_HOME` (bzw. `~/.local/state`).
+    pub fn resolved_db_path(&self) -> PathBuf {
+        self.db_path.clone().unwrap_or_else(|| {
+            let base = dirs::state_dir().unwrap_or_else(|| {
+                // Fallback in $HOME/.local/state
+                let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+                home.join(".local/state")
+            });
+            base.join("hauski").join("memory.db")
+        })
+    }
+}
+
 pub fn init_with(cfg: MemoryConfig) -> Result<&'static MemoryStore> {
-    let base = dirs::state_dir().unwrap_or_else(|| {
-        // Fallback in $HOME/.local/state
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        home.join(".local/state")
-    });
-    let db_path = cfg
-        .db_path
-        .unwrap_or_else(|| base.join("hauski").join("memory.db"));
+    let db_path = cfg.resolved_db_path();
 
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)
@@ -194,6 +235,7 @@ pub fn init_with(cfg: MemoryConfig) -> Result<&'static MemoryStore> {
             );
             ",
         )?;
+        queue::ensure_schema(&conn)?;
     }
 
     // spawn janitor
@@ -377,6 +419,86 @@ impl MemoryStore {
         .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
     }
 
+    /// Schreibt einen konsistenten Snapshot der Datenbank nach `target`
+    /// (`VACUUM INTO`; die Zieldatei darf noch nicht existieren) und liefert
+    /// die Zahl der Einträge darin.
+    pub async fn backup_to(&self, target: PathBuf) -> Result<usize> {
+        let pool = self.pool.clone();
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .context("MemoryStore::backup_to: r2d2 pool get")?;
+            let target = target
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("backup path is not valid UTF-8"))?
+                .to_string();
+            conn.execute("VACUUM INTO ?1", params![target])?;
+            let snapshot = Connection::open(&target)?;
+            let items: i64 =
+                snapshot.query_row("SELECT COUNT(*) FROM memory_items", [], |r| r.get(0))?;
+            Ok::<usize, anyhow::Error>(items as usize)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+
+    /// Prüft einen Snapshot von [`MemoryStore::backup_to`] mit
+    /// `integrity_check`, ohne etwas zu ändern, und liefert die Zahl der
+    /// Einträge darin.
+    pub async fn inspect_snapshot(source: PathBuf) -> Result<usize> {
+        task::spawn_blocking(move || {
+            let snapshot = Connection::open_with_flags(&source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .context("MemoryStore::inspect_snapshot: open")?;
+            let check: String = snapshot.query_row("PRAGMA integrity_check", [], |r| r.get(0))?;
+            if check != "ok" {
+                anyhow::bail!("snapshot failed integrity_check: {check}");
+            }
+            let items: i64 =
+                snapshot.query_row("SELECT COUNT(*) FROM memory_items", [], |r| r.get(0))?;
+            Ok(items as usize)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+
+    /// Ersetzt alle Einträge durch die aus einem Snapshot von
+    /// [`MemoryStore::backup_to`]; prüft die Datei vorher mit
+    /// `integrity_check`. Die Task-Queue bleibt unberührt. Liefert die Zahl
+    /// der übernommenen Einträge.
+    pub async fn restore_from(&self, source: PathBuf) -> Result<usize> {
+        let pool = self.pool.clone();
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .context("MemoryStore::restore_from: r2d2 pool get")?;
+            let source = source
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("snapshot path is not valid UTF-8"))?
+                .to_string();
+            conn.execute("ATTACH DATABASE ?1 AS snapshot", params![source])?;
+            let restored = (|| {
+                let check: String =
+                    conn.query_row("PRAGMA snapshot.integrity_check", [], |r| r.get(0))?;
+                if check != "ok" {
+                    anyhow::bail!("snapshot failed integrity_check: {check}");
+                }
+                let tx = conn.transaction()?;
+                tx.execute("DELETE FROM main.memory_items", [])?;
+                let restored = tx.execute(
+                    "INSERT INTO main.memory_items(key, value, ttl_sec, pinned, created_ts, updated_ts)
+                     SELECT key, value, ttl_sec, pinned, created_ts, updated_ts FROM snapshot.memory_items",
+                    [],
+                )?;
+                tx.commit()?;
+                Ok(restored)
+            })();
+            conn.execute("DETACH DATABASE snapshot", [])?;
+            restored
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+
     pub async fn scan_prefix(&self, prefix: String) -> Result<Vec<String>> {
         let pool = self.pool.clone();
 
@@ -389,13 +511,7 @@ impl MemoryStore {
             // so '\' represents the single backslash character used as the ESCAPE argument.
             let mut stmt =
                 conn.prepare("SELECT key FROM memory_items WHERE key LIKE ?1 ESCAPE '\\'")?;
-            // Escape LIKE wildcards in the prefix to prevent unintended pattern matching.
-            // The backslash is the ESCAPE char (see above), so it must be doubled first.
-            let escaped = prefix
-                .replace('\\', "\\\\")
-                .replace('%', "\\%")
-                .replace('_', "\\_");
-            let keys_iter = stmt.query_map(params![format!("{}%", escaped)], |row| row.get(0))?;
+            let keys_iter = stmt.query_map(params![like_prefix(&prefix)], |row| row.get(0))?;
 
             let mut keys = Vec::new();
             for key in keys_iter {
@@ -406,6 +522,83 @@ impl MemoryStore {
         .await
         .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
     }
+
+    /// Listet Einträge seitenweise, sortiert nach Key.
+    pub async fn list(&self, opts: ListOptions) -> Result<ListPage> {
+        let pool = self.pool.clone();
+        let ops_total = self.ops_total.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool.get().context("MemoryStore::list: r2d2 pool get")?;
+            // Werte nur lesen, wenn sie gebraucht werden (können groß sein).
+            let value_column = if opts.include_values { "value" } else { "NULL" };
+            let mut stmt = conn.prepare(&format!(
+                r"SELECT key, {value_column}, ttl_sec, pinned, created_ts, updated_ts
+                    FROM memory_items
+                    WHERE key LIKE ?1 ESCAPE '\' AND (?2 IS NULL OR key > ?2)
+                    ORDER BY key
+                    LIMIT ?3"
+            ))?;
+            // Einen Eintrag mehr laden, um das Vorhandensein einer nächsten Seite zu erkennen.
+            let fetch = i64::try_from(opts.limit.saturating_add(1)).unwrap_or(i64::MAX);
+            let rows =
+                stmt.query_map(params![like_prefix(&opts.prefix), opts.after, fetch], |r| {
+                    let pinned_i: i64 = r.get(3)?;
+                    let created: String = r.get(4)?;
+                    let updated: String = r.get(5)?;
+                    Ok(ListedItem {
+                        key: r.get(0)?,
+                        value: r.get(1)?,
+                        ttl_sec: r.get(2)?,
+                        pinned: pinned_i != 0,
+                        created_ts: parse_ts(&created, "created_ts"),
+                        updated_ts: parse_ts(&updated, "updated_ts"),
+                    })
+                })?;
+
+            let mut items = Vec::new();
+            for row in rows {
+                items.push(row?);
+            }
+            let next_cursor = if items.len() > opts.limit {
+                items.truncate(opts.limit);
+                items.last().map(|item| item.key.clone())
+            } else {
+                None
+            };
+
+            ops_total
+                .get_or_create(&MemoryLabels {
+                    namespace: Cow::Borrowed("default"),
+                    layer: Cow::Borrowed("short_term"),
+                })
+                .inc();
+            Ok::<ListPage, anyhow::Error>(ListPage { items, next_cursor })
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+}
+
+/// Baut ein LIKE-Muster für ein wörtliches Präfix.
+///
+/// LIKE-Wildcards im Präfix werden escaped, damit sie nicht als Muster wirken.
+/// Der Backslash ist das ESCAPE-Zeichen und muss daher zuerst verdoppelt werden.
+/// (SQLite behandelt den Backslash in String-Literalen nicht besonders, `'\'` ist
+/// also genau ein Zeichen.)
+fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{escaped}%")
+}
+
+fn parse_ts(raw: &str, field: &str) -> DateTime<Utc> {
+    raw.parse().unwrap_or_else(|e| {
+        tracing::warn!(error = ?e, field, "failed to parse timestamp");
+        Utc::now()
+    })
 }
 
 async fn janitor_task(pool: r2d2::Pool<SqliteConnectionManager>, every_secs: u64) {
@@ -439,13 +632,13 @@ async fn janitor_task(pool: r2d2::Pool<SqliteConnectionManager>, every_secs: u64
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use std::time::Duration;
 
     /// Test-interne Hilfsfunktion, die einen isolierten Store für jeden Test erstellt.
     /// Gibt den Store und das `TempDir` zurück, um dessen Lebensdauer an den Test zu binden.
-    fn test_store(janitor_interval_secs: u64) -> (MemoryStore, tempfile::TempDir) {
+    pub(crate) fn test_store(janitor_interval_secs: u64) -> (MemoryStore, tempfile::TempDir) {
         let tmp = tempfile::tempdir().unwrap();
         let db_path = tmp.path().join("m.db");
 
@@ -463,6 +656,7 @@ mod tests {
                 );",
             )
             .unwrap();
+            queue::ensure_schema(&conn).unwrap();
         }
 
         let jp = tokio::spawn(janitor_task(pool.clone(), janitor_interval_secs));
@@ -476,6 +670,62 @@ mod tests {
         (store, tmp)
     }
 
+    #[tokio::test]
+    async fn backup_writes_a_readable_snapshot() {
+        let (store, tmp) = test_store(60);
+        store
+            .set("k".into(), b"v".to_vec(), TtlUpdate::Clear, None)
+            .await
+            .unwrap();
+        let target = tmp.path().join("backup.db");
+        assert_eq!(store.backup_to(target.clone()).await.unwrap(), 1);
+
+        let conn = Connection::open(&target).unwrap();
+        let value: Vec<u8> = conn
+            .query_row("SELECT value FROM memory_items WHERE key='k'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(value, b"v");
+        assert!(store.backup_to(target).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn restore_replaces_all_items_from_a_snapshot() {
+        let (store, tmp) = test_store(60);
+        store
+            .set(
+                "kept".into(),
+                b"old".to_vec(),
+                TtlUpdate::Set(60),
+                Some(true),
+            )
+            .await
+            .unwrap();
+        let target = tmp.path().join("backup.db");
+        store.backup_to(target.clone()).await.unwrap();
+        store
+            .set("kept".into(), b"new".to_vec(), TtlUpdate::Clear, None)
+            .await
+            .unwrap();
+        store
+            .set("later".into(), b"x".to_vec(), TtlUpdate::Clear, None)
+            .await
+            .unwrap();
+
+        assert_eq!(store.restore_from(target).await.unwrap(), 1);
+        let item = store.get("kept".into()).await.unwrap().unwrap();
+        assert_eq!(item.value, b"old");
+        assert_eq!(item.ttl_sec, Some(60));
+        assert!(item.pinned);
+        assert!(store.get("later".into()).await.unwrap().is_none());
+
+        let broken = tmp.path().join("broken.db");
+        std::fs::write(&broken, b"not a database").unwrap();
+        assert!(store.restore_from(broken).await.is_err());
+        assert!(store.get("kept".into()).await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn verify_pragmas() {
         let (store, _tmp) = test_store(60);
@@ -732,4 +982,49 @@ mod tests {
             "scan_prefix('foo') must match all five keys"
         );
     }
+
+    #[tokio::test]
+    async fn list_pages_by_key_and_can_omit_values() {
+        let (store, _tmp) = test_store(60);
+        for key in ["ns:c", "ns:a", "ns:b", "other:x", "ns_y"] {
+            store
+                .set(
+                    key.into(),
+                    key.as_bytes().to_vec(),
+                    TtlUpdate::Set(60),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let first = store
+            .list(ListOptions {
+                prefix: "ns:".into(),
+                after: None,
+                limit: 2,
+                include_values: true,
+            })
+            .await
+            .unwrap();
+        let keys: Vec<_> = first.items.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, vec!["ns:a", "ns:b"]);
+        assert_eq!(first.items[0].value.as_deref(), Some(&b"ns:a"[..]));
+        assert_eq!(first.items[0].ttl_sec, Some(60));
+        assert_eq!(first.next_cursor.as_deref(), Some("ns:b"));
+
+        let second = store
+            .list(ListOptions {
+                prefix: "ns:".into(),
+                after: first.next_cursor,
+                limit: 2,
+                include_values: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].key, "ns:c");
+        assert!(second.items[0].value.is_none());
+        assert!(second.next_cursor.is_none());
+    }
 }
@@ -0,0 +1,490 @@
+//! Dauerhafte Task-Queue in derselben SQLite-Datei wie der Memory-Store.
+//!
+//! Ein Task bleibt in `task_queue`, bis er erfolgreich abgeschlossen wurde
+//! (at-least-once): Nach einem Absturz stehen unterbrochene Tasks weiter als
+//! `running` in der Tabelle und werden per [`MemoryStore::recover_tasks`] wieder
+//! eingereiht. Fehlgeschlagene Versuche werden bis `max_attempts` erneut
+//! geplant, danach landet der Task als `dead` in der Dead-Letter-Ablage.
+//!
+//! Mehrere Verbraucher teilen sich die Tabelle über die Spalte `queue`
+//! (z. B. `jobs`, `outbox`); Wiederanlauf, Statistik und Dead Letters sind je
+//! Queue getrennt.
+
+use std::{borrow::Cow, time::Duration};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tokio::task;
+
+use crate::{parse_ts, MemoryLabels, MemoryStore};
+
+const TASK_QUEUE_SCHEMA: &str = r"
+    CREATE TABLE IF NOT EXISTS task_queue(
+        id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        payload BLOB NOT NULL,
+        status TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        max_attempts INTEGER NOT NULL,
+        run_after TEXT NOT NULL,
+        last_error TEXT NULL,
+        created_ts TEXT NOT NULL,
+        updated_ts TEXT NOT NULL,
+        queue TEXT NOT NULL DEFAULT 'jobs'
+    );
+    CREATE INDEX IF NOT EXISTS task_queue_status ON task_queue(status, created_ts);
+";
+
+/// Legt `task_queue` an und ergänzt die `queue`-Spalte in älteren Datenbanken.
+pub(crate) fn ensure_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(TASK_QUEUE_SCHEMA)?;
+    let has_queue: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('task_queue') WHERE name='queue'",
+        [],
+        |r| r.get(0),
+    )?;
+    if has_queue == 0 {
+        conn.execute_batch("ALTER TABLE task_queue ADD COLUMN queue TEXT NOT NULL DEFAULT 'jobs'")?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS task_queue_lane ON task_queue(queue, status, created_ts)",
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    /// Alle Versuche aufgebraucht (Dead Letter).
+    Dead,
+}
+
+impl TaskStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Dead => "dead",
+        }
+    }
+
+    fn parse(raw: &str) -> Self {
+        match raw {
+            "running" => Self::Running,
+            "dead" => Self::Dead,
+            _ => Self::Queued,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub id: String,
+    pub kind: String,
+    pub payload: Vec<u8>,
+    pub status: TaskStatus,
+    /// Bereits begonnene Versuche.
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Frühester Zeitpunkt für den nächsten Versuch.
+    pub run_after: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_ts: DateTime<Utc>,
+    pub updated_ts: DateTime<Utc>,
+}
+
+/// Ergebnis von [`MemoryStore::fail_task`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Erneut eingereiht; nächster Versuch ab `run_after`.
+    Retry {
+        attempts: u32,
+        run_after: DateTime<Utc>,
+    },
+    /// `max_attempts` erreicht, Task liegt in der Dead-Letter-Ablage.
+    Dead { attempts: u32 },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub queued: u64,
+    pub running: u64,
+    pub dead: u64,
+}
+
+const TASK_COLUMNS: &str =
+    "id, kind, payload, status, attempts, max_attempts, run_after, last_error, created_ts, updated_ts";
+
+fn task_from_row(row: &Row<'_>) -> rusqlite::Result<QueuedTask> {
+    let status: String = row.get(3)?;
+    let run_after: String = row.get(6)?;
+    let created_ts: String = row.get(8)?;
+    let updated_ts: String = row.get(9)?;
+    Ok(QueuedTask {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        payload: row.get(2)?,
+        status: TaskStatus::parse(&status),
+        attempts: row.get(4)?,
+        max_attempts: row.get(5)?,
+        run_after: parse_ts(&run_after, "run_after"),
+        last_error: row.get(7)?,
+        created_ts: parse_ts(&created_ts, "created_ts"),
+        updated_ts: parse_ts(&updated_ts, "updated_ts"),
+    })
+}
+
+impl MemoryStore {
+    fn count_queue_op(&self) {
+        self.ops_total
+            .get_or_create(&MemoryLabels {
+                namespace: Cow::Borrowed("task_queue"),
+                layer: Cow::Borrowed("durable"),
+            })
+            .inc();
+    }
+
+    /// Reiht einen neuen Task in `queue` ein (Status `queued`, sofort fällig).
+    pub async fn enqueue_task(
+        &self,
+        queue: &str,
+        id: String,
+        kind: String,
+        payload: Vec<u8>,
+        max_attempts: u32,
+    ) -> Result<()> {
+        let pool = self.pool.clone();
+        let queue = queue.to_string();
+        task::spawn_blocking(move || {
+            let now = Utc::now().to_rfc3339();
+            let conn = pool
+                .get()
+                .context("MemoryStore::enqueue_task: r2d2 pool get")?;
+            conn.execute(
+                r"INSERT INTO task_queue(id,kind,payload,status,attempts,max_attempts,run_after,created_ts,updated_ts,queue)
+                    VALUES (?1,?2,?3,?4,0,?5,?6,?6,?6,?7)",
+                params![
+                    id,
+                    kind,
+                    payload,
+                    TaskStatus::Queued.as_str(),
+                    max_attempts.max(1),
+                    now,
+                    queue
+                ],
+            )?;
+            Ok::<(), anyhow::Error>(())
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(())
+    }
+
+    /// Markiert den Beginn eines Versuchs; liefert die neue Versuchsnummer
+    /// oder `None`, wenn der Task nicht (mehr) ausführbar ist.
+    pub async fn start_task_attempt(&self, id: String) -> Result<Option<u32>> {
+        let pool = self.pool.clone();
+        let attempts = task::spawn_blocking(move || {
+            let now = Utc::now().to_rfc3339();
+            let conn = pool
+                .get()
+                .context("MemoryStore::start_task_attempt: r2d2 pool get")?;
+            let attempts = conn
+                .query_row(
+                    r"UPDATE task_queue
+                        SET status=?2, attempts=attempts+1, updated_ts=?3
+                        WHERE id=?1 AND status<>?4
+                        RETURNING attempts",
+                    params![
+                        id,
+                        TaskStatus::Running.as_str(),
+                        now,
+                        TaskStatus::Dead.as_str()
+                    ],
+                    |r| r.get::<_, u32>(0),
+                )
+                .optional()?;
+            Ok::<_, anyhow::Error>(attempts)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(attempts)
+    }
+
+    /// Entfernt einen erledigten (oder abgebrochenen) Task aus der Queue.
+    pub async fn complete_task(&self, id: String) -> Result<bool> {
+        let pool = self.pool.clone();
+        let removed = task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .context("MemoryStore::complete_task: r2d2 pool get")?;
+            let n = conn.execute("DELETE FROM task_queue WHERE id=?1", params![id])?;
+            Ok::<_, anyhow::Error>(n > 0)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(removed)
+    }
+
+    /// Verbucht einen fehlgeschlagenen Versuch: erneut einreihen (nach
+    /// `retry_delay`) oder, wenn `max_attempts` erreicht ist, als Dead Letter ablegen.
+    pub async fn fail_task(
+        &self,
+        id: String,
+        error: String,
+        retry_delay: Duration,
+    ) -> Result<Option<RetryDecision>> {
+        let pool = self.pool.clone();
+        let decision = task::spawn_blocking(move || {
+            let now = Utc::now();
+            let conn = pool
+                .get()
+                .context("MemoryStore::fail_task: r2d2 pool get")?;
+            let Some((attempts, max_attempts)) = conn
+                .query_row(
+                    "SELECT attempts, max_attempts FROM task_queue WHERE id=?1",
+                    params![id],
+                    |r| Ok((r.get::<_, u32>(0)?, r.get::<_, u32>(1)?)),
+                )
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            let (status, run_after, decision) = if attempts >= max_attempts {
+                (TaskStatus::Dead, now, RetryDecision::Dead { attempts })
+            } else {
+                let delay = chrono::Duration::from_std(retry_delay)
+                    .unwrap_or_else(|_| chrono::Duration::seconds(60));
+                let run_after = now + delay;
+                (
+                    TaskStatus::Queued,
+                    run_after,
+                    RetryDecision::Retry {
+                        attempts,
+                        run_after,
+                    },
+                )
+            };
+            conn.execute(
+                r"UPDATE task_queue
+                    SET status=?2, run_after=?3, last_error=?4, updated_ts=?5
+                    WHERE id=?1",
+                params![
+                    id,
+                    status.as_str(),
+                    run_after.to_rfc3339(),
+                    error,
+                    now.to_rfc3339()
+                ],
+            )?;
+            Ok::<_, anyhow::Error>(Some(decision))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(decision)
+    }
+
+    /// Legt einen Task ohne weitere Versuche direkt als Dead Letter ab
+    /// (z. B. bei dauerhaft abgelehnten Zustellungen).
+    pub async fn bury_task(&self, id: String, error: String) -> Result<bool> {
+        let pool = self.pool.clone();
+        let buried = task::spawn_blocking(move || {
+            let now = Utc::now().to_rfc3339();
+            let conn = pool
+                .get()
+                .context("MemoryStore::bury_task: r2d2 pool get")?;
+            let n = conn.execute(
+                r"UPDATE task_queue
+                    SET status=?2, last_error=?3, updated_ts=?4
+                    WHERE id=?1",
+                params![id, TaskStatus::Dead.as_str(), error, now],
+            )?;
+            Ok::<_, anyhow::Error>(n > 0)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(buried)
+    }
+
+    /// Nach einem Neustart: unterbrochene (`running`) Tasks zurück auf `queued`
+    /// setzen und alle ausstehenden Tasks in Einreihungsreihenfolge liefern.
+    pub async fn recover_tasks(&self, queue: &str) -> Result<Vec<QueuedTask>> {
+        let pool = self.pool.clone();
+        let queue = queue.to_string();
+        let tasks = task::spawn_blocking(move || {
+            let now = Utc::now().to_rfc3339();
+            let conn = pool
+                .get()
+                .context("MemoryStore::recover_tasks: r2d2 pool get")?;
+            conn.execute(
+                "UPDATE task_queue SET status=?1, updated_ts=?2 WHERE status=?3 AND queue=?4",
+                params![
+                    TaskStatus::Queued.as_str(),
+                    now,
+                    TaskStatus::Running.as_str(),
+                    queue
+                ],
+            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {TASK_COLUMNS} FROM task_queue WHERE status=?1 AND queue=?2 ORDER BY created_ts, id"
+            ))?;
+            let tasks = stmt
+                .query_map(params![TaskStatus::Queued.as_str(), queue], task_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok::<_, anyhow::Error>(tasks)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))??;
+        self.count_queue_op();
+        Ok(tasks)
+    }
+
+    pub async fn queue_stats(&self, queue: &str) -> Result<QueueStats> {
+        let pool = self.pool.clone();
+        let queue = queue.to_string();
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .context("MemoryStore::queue_stats: r2d2 pool get")?;
+            let mut stmt = conn.prepare(
+                "SELECT status, COUNT(*) FROM task_queue WHERE queue=?1 GROUP BY status",
+            )?;
+            let mut stats = QueueStats::default();
+            let rows = stmt.query_map(params![queue], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (status, count) = row?;
+                let count = u64::try_from(count).unwrap_or(0);
+                match TaskStatus::parse(&status) {
+                    TaskStatus::Queued => stats.queued += count,
+                    TaskStatus::Running => stats.running += count,
+                    TaskStatus::Dead => stats.dead += count,
+                }
+            }
+            Ok::<_, anyhow::Error>(stats)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+
+    /// Dead Letters, neueste zuerst.
+    pub async fn dead_tasks(&self, queue: &str, limit: usize) -> Result<Vec<QueuedTask>> {
+        let pool = self.pool.clone();
+        let queue = queue.to_string();
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .context("MemoryStore::dead_tasks: r2d2 pool get")?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {TASK_COLUMNS} FROM task_queue WHERE status=?1 AND queue=?3 ORDER BY updated_ts DESC, id DESC LIMIT ?2"
+            ))?;
+            let tasks = stmt
+                .query_map(
+                    params![
+                        TaskStatus::Dead.as_str(),
+                        i64::try_from(limit).unwrap_or(i64::MAX),
+                        queue
+                    ],
+                    task_from_row,
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok::<_, anyhow::Error>(tasks)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("spawn_blocking failed: {}", e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_store;
+
+    #[tokio::test]
+    async fn failed_attempts_retry_until_dead_letter() {
+        let (store, _tmp) = test_store(60);
+        store
+            .enqueue_task("jobs", "t1".into(), "ingest".into(), b"{}".to_vec(), 2)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.start_task_attempt("t1".into()).await.unwrap(),
+            Some(1)
+        );
+        let decision = store
+            .fail_task("t1".into(), "boom".into(), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert!(matches!(
+            decision,
+            Some(RetryDecision::Retry { attempts: 1, run_after }) if run_after > Utc::now()
+        ));
+
+        assert_eq!(
+            store.start_task_attempt("t1".into()).await.unwrap(),
+            Some(2)
+        );
+        let decision = store
+            .fail_task("t1".into(), "boom again".into(), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(decision, Some(RetryDecision::Dead { attempts: 2 }));
+        assert_eq!(store.start_task_attempt("t1".into()).await.unwrap(), None);
+
+        let stats = store.queue_stats("jobs").await.unwrap();
+        assert_eq!((stats.queued, stats.running, stats.dead), (0, 0, 1));
+        let dead = store.dead_tasks("jobs", 10).await.unwrap();
+        assert_eq!(dead[0].last_error.as_deref(), Some("boom again"));
+    }
+
+    #[tokio::test]
+    async fn interrupted_tasks_are_recovered_until_completed() {
+        let (store, _tmp) = test_store(60);
+        for id in ["a", "b"] {
+            store
+                .enqueue_task(
+                    "jobs",
+                    id.into(),
+                    "ingest".into(),
+                    id.as_bytes().to_vec(),
+                    3,
+                )
+                .await
+                .unwrap();
+        }
+        store
+            .enqueue_task("outbox", "o".into(), "quarantine".into(), vec![], 3)
+            .await
+            .unwrap();
+        store.start_task_attempt("a".into()).await.unwrap();
+
+        let recovered = store.recover_tasks("jobs").await.unwrap();
+        let ids: Vec<_> = recovered.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(recovered[0].status, TaskStatus::Queued);
+        assert_eq!(recovered[0].attempts, 1);
+        assert_eq!(recovered[1].payload, b"b");
+
+        assert!(store.complete_task("a".into()).await.unwrap());
+        assert!(!store.complete_task("a".into()).await.unwrap());
+        assert_eq!(store.recover_tasks("jobs").await.unwrap().len(), 1);
+
+        assert!(store
+            .bury_task("o".into(), "410 Gone".into())
+            .await
+            .unwrap());
+        assert_eq!(store.queue_stats("outbox").await.unwrap().dead, 1);
+        assert_eq!(store.queue_stats("jobs").await.unwrap().dead, 0);
+    }
+}
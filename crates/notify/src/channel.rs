@@ -0,0 +1,401 @@
+//! Channels from the `channels` section of `notifications.yaml`.
+
+use std::{env, process::Command};
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::{Message, NotifyError, Priority};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    /// Desktop notification over D-Bus (`notify-send`).
+    Desktop,
+    /// Push over an ntfy server.
+    Ntfy,
+    /// Message into a Matrix room.
+    Matrix,
+}
+
+impl ChannelKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Ntfy => "ntfy",
+            Self::Matrix => "matrix",
+        }
+    }
+}
+
+/// Window in local time in which only urgent messages go out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHours {
+    /// `HH:MM`; may be later than `end` for a window over midnight.
+    pub start: String,
+    pub end: String,
+    /// Messages at or above this priority still go out (default `urgent`).
+    #[serde(default = "default_quiet_priority")]
+    pub min_priority: Priority,
+}
+
+fn default_quiet_priority() -> Priority {
+    Priority::Urgent
+}
+
+impl QuietHours {
+    fn bounds(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        let parse = |value: &str| {
+            NaiveTime::parse_from_str(value.trim(), "%H:%M")
+                .map_err(|_| format!("quiet_hours: '{value}' is not HH:MM"))
+        };
+        Ok((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    /// Whether `time` falls into the window; `start` is inclusive, `end` not.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let Ok((start, end)) = self.bounds() else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+
+    /// Whether `priority` is held back at `time`.
+    pub fn holds(&self, priority: Priority, time: NaiveTime) -> bool {
+        priority < self.min_priority && self.contains(time)
+    }
+}
+
+/// At most `max` messages per `per_sec` seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub max: u32,
+    pub per_sec: u64,
+}
+
+/// Overrides for title and body; `{{name}}` is replaced by the
+/// notification's `event`, `title`, `body`, `priority` or one of its fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// One entry of the `channels` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelSpec {
+    pub id: String,
+    pub kind: ChannelKind,
+    /// ntfy server or Matrix homeserver, e.g. `https://ntfy.sh`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// ntfy topic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Matrix room id, e.g. `!abc:example.org`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    /// Environment variable holding the access token (ntfy optional, Matrix required).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// Subscribed events; `system.*` matches a prefix, empty means all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub min_priority: Priority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<Template>,
+}
+
+/// What it takes to deliver a message on a channel.
+#[derive(Debug)]
+pub enum Delivery {
+    Command(Command),
+    Http(HttpRequest),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    /// `POST` or `PUT`.
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl ChannelSpec {
+    pub(crate) fn validate(&self) -> Result<(), NotifyError> {
+        let invalid = |reason: &str| NotifyError::InvalidChannel {
+            id: self.id.clone(),
+            reason: reason.to_string(),
+        };
+        if self.id.trim().is_empty() {
+            return Err(invalid("id is empty"));
+        }
+        let has = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        match self.kind {
+            ChannelKind::Desktop => {}
+            ChannelKind::Ntfy if !has(&self.url) || !has(&self.topic) => {
+                return Err(invalid("ntfy needs url and topic"));
+            }
+            ChannelKind::Matrix if !has(&self.url) || !has(&self.room) || !has(&self.token_env) => {
+                return Err(invalid("matrix needs url, room and token_env"));
+            }
+            ChannelKind::Ntfy | ChannelKind::Matrix => {}
+        }
+        if let Some(url) = &self.url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(invalid("url must start with http:// or https://"));
+            }
+        }
+        if let Some(quiet) = &self.quiet_hours {
+            quiet.bounds().map_err(|reason| invalid(&reason))?;
+        }
+        if let Some(limit) = self.rate_limit {
+            if limit.max == 0 || limit.per_sec == 0 {
+                return Err(invalid("rate_limit needs max and per_sec above 0"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the channel subscribed `event`.
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => event.starts_with(prefix),
+                    None => pattern == event,
+                })
+    }
+
+    /// How to deliver `message`; `txn_id` makes Matrix sends idempotent.
+    pub fn delivery(
+        &self,
+        message: &Message,
+        notify_send_bin: &str,
+        txn_id: &str,
+    ) -> Result<Delivery, NotifyError> {
+        let url = self
+            .url
+            .as_deref()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+        match self.kind {
+            ChannelKind::Desktop => {
+                let mut command = Command::new(notify_send_bin);
+                command
+                    .args([
+                        "--app-name",
+                        "HausKI",
+                        "--urgency",
+                        message.priority.urgency(),
+                    ])
+                    .arg(&message.title)
+                    .arg(&message.body);
+                Ok(Delivery::Command(command))
+            }
+            ChannelKind::Ntfy => {
+                // JSON publishing keeps non-ASCII titles out of HTTP headers.
+                let mut headers =
+                    vec![("Content-Type".to_string(), "application/json".to_string())];
+                if let Some(token) = self.token()? {
+                    headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+                }
+                let body = json!({
+                    "topic": self.topic,
+                    "title": message.title,
+                    "message": message.body,
+                    "priority": message.priority.ntfy_level(),
+                    "tags": [message.event],
+                });
+                Ok(Delivery::Http(HttpRequest {
+                    method: "POST",
+                    url: url.to_string(),
+                    headers,
+                    body: body.to_string(),
+                }))
+            }
+            ChannelKind::Matrix => {
+                let token = self.token()?.unwrap_or_default();
+                let room = percent_encode(self.room.as_deref().unwrap_or_default());
+                let text = if message.body.is_empty() {
+                    message.title.clone()
+                } else {
+                    format!("{}\n{}", message.title, message.body)
+                };
+                Ok(Delivery::Http(HttpRequest {
+                    method: "PUT",
+                    url: format!(
+                        "{url}/_matrix/client/v3/rooms/{room}/send/m.room.message/{}",
+                        percent_encode(txn_id)
+                    ),
+                    headers: vec![
+                        ("Authorization".to_string(), format!("Bearer {token}")),
+                        ("Content-Type".to_string(), "application/json".to_string()),
+                    ],
+                    body: json!({"msgtype": "m.text", "body": text}).to_string(),
+                }))
+            }
+        }
+    }
+
+    fn token(&self) -> Result<Option<String>, NotifyError> {
+        let Some(name) = &self.token_env else {
+            return Ok(None);
+        };
+        match env::var(name) {
+            Ok(token) if !token.trim().is_empty() => Ok(Some(token.trim().to_string())),
+            _ => Err(NotifyError::MissingToken {
+                id: self.id.clone(),
+                env: name.clone(),
+            }),
+        }
+    }
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(yaml: &str) -> ChannelSpec {
+        serde_yaml_ng::from_str(yaml).unwrap()
+    }
+
+    fn message() -> Message {
+        Message {
+            event: "quarantine".into(),
+            title: "Dokument in Quarantäne".into(),
+            body: "notes/a.md".into(),
+            priority: Priority::High,
+        }
+    }
+
+    #[test]
+    fn channels_build_their_requests() {
+        let ntfy = channel("id: phone\nkind: ntfy\nurl: https://ntfy.example.org/\ntopic: hauski");
+        let Delivery::Http(request) = ntfy.delivery(&message(), "notify-send", "1").unwrap() else {
+            panic!("ntfy delivers over HTTP");
+        };
+        assert_eq!(request.url, "https://ntfy.example.org");
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(
+            body,
+            json!({"topic": "hauski", "title": "Dokument in Quarantäne", "message": "notes/a.md",
+                   "priority": 4, "tags": ["quarantine"]})
+        );
+
+        let matrix = channel(
+            "id: room\nkind: matrix\nurl: https://matrix.example.org\nroom: '!abc:example.org'\ntoken_env: HAUSKI_TEST_MATRIX_TOKEN",
+        );
+        assert!(matches!(
+            matrix.delivery(&message(), "notify-send", "1"),
+            Err(NotifyError::MissingToken { .. })
+        ));
+        env::set_var("HAUSKI_TEST_MATRIX_TOKEN", "secret");
+        let Delivery::Http(request) = matrix.delivery(&message(), "notify-send", "01J").unwrap()
+        else {
+            panic!("matrix delivers over HTTP");
+        };
+        assert_eq!(request.method, "PUT");
+        assert_eq!(
+            request.url,
+            "https://matrix.example.org/_matrix/client/v3/rooms/%21abc%3Aexample.org/send/m.room.message/01J"
+        );
+        assert!(request
+            .body
+            .contains(r#""body":"Dokument in Quarantäne\nnotes/a.md""#));
+
+        let desktop = channel("id: desktop\nkind: desktop");
+        let Delivery::Command(command) = desktop.delivery(&message(), "notify-send", "1").unwrap()
+        else {
+            panic!("desktop runs notify-send");
+        };
+        let args: Vec<_> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "--app-name",
+                "HausKI",
+                "--urgency",
+                "critical",
+                "Dokument in Quarantäne",
+                "notes/a.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn channels_are_validated() {
+        let invalid = |yaml: &str| channel(yaml).validate().is_err();
+        assert!(invalid("id: phone\nkind: ntfy\nurl: https://ntfy.sh"));
+        assert!(invalid(
+            "id: room\nkind: matrix\nurl: https://m.org\nroom: '!a:m.org'"
+        ));
+        assert!(invalid("id: phone\nkind: ntfy\nurl: ntfy.sh\ntopic: t"));
+        assert!(invalid(
+            "id: d\nkind: desktop\nquiet_hours: { start: '22:00', end: '7' }"
+        ));
+        assert!(invalid(
+            "id: d\nkind: desktop\nrate_limit: { max: 0, per_sec: 60 }"
+        ));
+        assert!(!invalid(
+            "id: d\nkind: desktop\nevents: [system.*]\nquiet_hours: { start: '22:00', end: '07:00' }"
+        ));
+    }
+
+    #[test]
+    fn quiet_hours_span_midnight_and_events_match_prefixes() {
+        let quiet = QuietHours {
+            start: "22:00".into(),
+            end: "07:00".into(),
+            min_priority: Priority::Urgent,
+        };
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(quiet.contains(at(23, 30)));
+        assert!(quiet.contains(at(6, 59)));
+        assert!(!quiet.contains(at(7, 0)));
+        assert!(!quiet.contains(at(12, 0)));
+        assert!(quiet.holds(Priority::High, at(2, 0)));
+        assert!(!quiet.holds(Priority::Urgent, at(2, 0)));
+
+        let channel = channel("id: d\nkind: desktop\nevents: [system.*, quarantine]");
+        assert!(channel.wants("system.gpu_throttle"));
+        assert!(channel.wants("quarantine"));
+        assert!(!channel.wants("job.completed"));
+    }
+}
@@ -0,0 +1,406 @@
+//! Benachrichtigungen über Desktop (D-Bus), ntfy und Matrix.
+//!
+//! Subsysteme melden eine [`Notification`] (Ereignis, Titel, Text, Priorität,
+//! Felder); [`Notifier::route`] entscheidet je Kanal, ob und wie sie rausgeht:
+//!
+//! - **Abo:** `events` des Kanals (`system.*` als Präfix, leer = alle) und
+//!   `min_priority`.
+//! - **Ruhezeiten:** Innerhalb von `quiet_hours` (lokale Zeit) gehen nur
+//!   Nachrichten ab `quiet_hours.min_priority` (Default `urgent`) raus.
+//! - **Rate-Limit:** höchstens `rate_limit.max` Nachrichten je
+//!   `rate_limit.per_sec` Sekunden und Kanal.
+//! - **Vorlagen:** `template.title`/`template.body` mit `{{event}}`,
+//!   `{{title}}`, `{{body}}`, `{{priority}}` und den Feldern der Meldung.
+//!
+//! Die Zustellung beschreibt [`ChannelSpec::delivery`]: `notify-send` für den
+//! Desktop, HTTP-Anfragen für ntfy und Matrix. Ausführen muss sie der
+//! Aufrufer – in HausKI über den Egress-geprüften Client des Core.
+//!
+//! Konfiguration ([`Notifier::new`]):
+//!   HAUSKI_NOTIFY_SEND_BIN  (Default `notify-send`)
+
+mod channel;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    env, fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+pub use channel::{
+    ChannelKind, ChannelSpec, Delivery, HttpRequest, QuietHours, RateLimit, Template,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum NotifyError {
+    #[error("channel id {0:?} is configured more than once")]
+    DuplicateChannel(String),
+    #[error("channel {id:?}: {reason}")]
+    InvalidChannel { id: String, reason: String },
+    #[error("channel {id:?}: token variable {env} is not set")]
+    MissingToken { id: String, env: String },
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+
+    /// `notify-send --urgency`.
+    fn urgency(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High | Self::Urgent => "critical",
+        }
+    }
+
+    /// ntfy `Priority` header (1–5).
+    fn ntfy_level(self) -> u8 {
+        match self {
+            Self::Low => 2,
+            Self::Normal => 3,
+            Self::High => 4,
+            Self::Urgent => 5,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Contents of `notifications.yaml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationsFile {
+    #[serde(default)]
+    pub channels: Vec<ChannelSpec>,
+}
+
+/// What a subsystem has to say.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Notification {
+    /// Event name channels subscribe to, e.g. `job.completed`.
+    pub event: String,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Extra values for `{{name}}` in channel templates.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Notification {
+    pub fn new(event: impl Into<String>, title: impl Into<String>, priority: Priority) -> Self {
+        Self {
+            event: event.into(),
+            title: title.into(),
+            body: String::new(),
+            priority,
+            fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        match name {
+            "event" => Some(&self.event),
+            "title" => Some(&self.title),
+            "body" => Some(&self.body),
+            "priority" => Some(self.priority.as_str()),
+            _ => self.fields.get(name).map(String::as_str),
+        }
+    }
+
+    /// `template` with `{{name}}` replaced; unknown names stay as they are.
+    pub fn render(&self, template: &str) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find("}}") {
+                Some(end) => {
+                    let name = after[..end].trim();
+                    match self.value(name) {
+                        Some(value) => out.push_str(value),
+                        None => out.push_str(&rest[start..start + 2 + end + 2]),
+                    }
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// A notification rendered for one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub event: String,
+    pub title: String,
+    pub body: String,
+    pub priority: Priority,
+}
+
+/// Outcome of [`Notifier::route`] for one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    Send(Message),
+    /// Held back by the channel's quiet hours.
+    Quiet,
+    /// Over the channel's rate limit.
+    RateLimited,
+}
+
+impl Decision {
+    /// Label for metrics and API responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Send(_) => "send",
+            Self::Quiet => "quiet",
+            Self::RateLimited => "rate_limited",
+        }
+    }
+}
+
+/// Checked channels with their rate-limit windows.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    channels: Vec<ChannelSpec>,
+    notify_send_bin: String,
+    sent: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl Notifier {
+    pub fn new(channels: Vec<ChannelSpec>) -> Result<Self, NotifyError> {
+        let mut ids = HashSet::new();
+        for channel in &channels {
+            if !ids.insert(channel.id.as_str()) {
+                return Err(NotifyError::DuplicateChannel(channel.id.clone()));
+            }
+            channel.validate()?;
+        }
+        let notify_send_bin = env::var("HAUSKI_NOTIFY_SEND_BIN")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "notify-send".into());
+        Ok(Self {
+            channels,
+            notify_send_bin,
+            sent: Mutex::default(),
+        })
+    }
+
+    pub fn channels(&self) -> &[ChannelSpec] {
+        &self.channels
+    }
+
+    pub fn notify_send_bin(&self) -> &str {
+        &self.notify_send_bin
+    }
+
+    /// Channels interested in `notification` and what to do on each; a
+    /// [`Decision::Send`] uses up one slot of the channel's rate limit.
+    pub fn route(
+        &self,
+        notification: &Notification,
+        now: Instant,
+        local_time: NaiveTime,
+    ) -> Vec<(&ChannelSpec, Decision)> {
+        let mut sent = self
+            .sent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.channels
+            .iter()
+            .filter(|channel| {
+                channel.wants(&notification.event) && notification.priority >= channel.min_priority
+            })
+            .map(|channel| {
+                let quiet = channel
+                    .quiet_hours
+                    .as_ref()
+                    .is_some_and(|quiet| quiet.holds(notification.priority, local_time));
+                if quiet {
+                    return (channel, Decision::Quiet);
+                }
+                if let Some(limit) = channel.rate_limit {
+                    let window = sent.entry(channel.id.clone()).or_default();
+                    let period = Duration::from_secs(limit.per_sec);
+                    while window
+                        .front()
+                        .is_some_and(|at| now.saturating_duration_since(*at) >= period)
+                    {
+                        window.pop_front();
+                    }
+                    if window.len() >= limit.max as usize {
+                        return (channel, Decision::RateLimited);
+                    }
+                    window.push_back(now);
+                }
+                (channel, Decision::Send(render(channel, notification)))
+            })
+            .collect()
+    }
+}
+
+fn render(channel: &ChannelSpec, notification: &Notification) -> Message {
+    let template = channel.template.clone().unwrap_or_default();
+    let pick = |template: Option<String>, plain: &str| match template {
+        Some(template) => notification.render(&template),
+        None => plain.to_string(),
+    };
+    Message {
+        event: notification.event.clone(),
+        title: pick(template.title, &notification.title),
+        body: pick(template.body, &notification.body),
+        priority: notification.priority,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifier(yaml: &str) -> Notifier {
+        Notifier::new(serde_yaml_ng::from_str(yaml).unwrap()).unwrap()
+    }
+
+    fn noon() -> NaiveTime {
+        NaiveTime::from_hms_opt(12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn templates_fill_in_notification_values() {
+        let notification = Notification::new("job.completed", "Job fertig", Priority::Normal)
+            .body("ingest")
+            .field("job_id", "01J");
+        assert_eq!(
+            notification.render("[{{priority}}] {{title}} ({{ job_id }}) {{unknown}} {{"),
+            "[normal] Job fertig (01J) {{unknown}} {{"
+        );
+
+        let notifier = notifier(
+            "- id: phone\n  kind: ntfy\n  url: https://ntfy.sh\n  topic: t\n  template: { title: 'HausKI: {{title}}' }",
+        );
+        let routed = notifier.route(&notification, Instant::now(), noon());
+        assert_eq!(
+            routed[0].1,
+            Decision::Send(Message {
+                event: "job.completed".into(),
+                title: "HausKI: Job fertig".into(),
+                body: "ingest".into(),
+                priority: Priority::Normal,
+            })
+        );
+    }
+
+    #[test]
+    fn routing_honours_subscriptions_quiet_hours_and_rate_limits() {
+        let notifier = notifier(
+            r#"
+- id: desktop
+  kind: desktop
+  events: [system.*]
+  min_priority: high
+- id: phone
+  kind: ntfy
+  url: https://ntfy.sh
+  topic: hauski
+  quiet_hours: { start: "22:00", end: "07:00" }
+  rate_limit: { max: 2, per_sec: 60 }
+"#,
+        );
+        let decisions = |notification: &Notification, now: Instant, time: NaiveTime| {
+            notifier
+                .route(notification, now, time)
+                .into_iter()
+                .map(|(channel, decision)| (channel.id.as_str(), decision.as_str()))
+                .collect::<Vec<_>>()
+        };
+
+        let throttle = Notification::new("system.gpu_throttle", "GPU", Priority::High);
+        let start = Instant::now();
+        assert_eq!(
+            decisions(&throttle, start, noon()),
+            [("desktop", "send"), ("phone", "send")]
+        );
+        let job = Notification::new("job.completed", "Job", Priority::Normal);
+        assert_eq!(decisions(&job, start, noon()), [("phone", "send")]);
+        assert_eq!(decisions(&job, start, noon()), [("phone", "rate_limited")]);
+        assert_eq!(
+            decisions(&job, start + Duration::from_secs(60), noon()),
+            [("phone", "send")]
+        );
+
+        let night = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
+        let later = start + Duration::from_secs(600);
+        assert_eq!(decisions(&job, later, night), [("phone", "quiet")]);
+        let urgent = Notification::new("job.completed", "Job", Priority::Urgent);
+        assert_eq!(decisions(&urgent, later, night), [("phone", "send")]);
+
+        assert_eq!(
+            Notifier::new(
+                serde_yaml_ng::from_str("[{id: a, kind: desktop}, {id: a, kind: desktop}]")
+                    .unwrap()
+            )
+            .unwrap_err(),
+            NotifyError::DuplicateChannel("a".into())
+        );
+    }
+}
@@ -0,0 +1,587 @@
+//! Dauerhaftes Entscheidungsprotokoll (SQLite).
+//!
+//! Jede Entscheidung wird mit Kontext (`features`), Modus, Scores, Begründung
+//! und dem Hash der Modellparameter zum Entscheidungszeitpunkt abgelegt. So
+//! bleibt nachvollziehbar, welcher Modellstand welche Aktion vorgeschlagen
+//! hat – auch nach weiteren Updates des Bandits.
+//!
+//! Rückmeldungen (`reward`) hängen an der Entscheidungs-ID; pro Entscheidung
+//! zählt nur die erste, Wiederholungen werden verworfen.
+//!
+//! Eingriffe in den Lernzustand (z. B. Zurücksetzen) landen als
+//! [`AuditEvent`] in `policy_events`.
+
+use std::{
+    path::Path,
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::Mode;
+
+/// Upper bound for [`DecisionQuery::limit`] in [`DecisionLog::query`].
+pub const MAX_QUERY_LIMIT: usize = 500;
+/// Upper bound for [`DecisionQuery::limit`] in [`DecisionLog::history`].
+pub const MAX_HISTORY_LIMIT: usize = 10_000;
+
+const SCHEMA: &str = r"
+    CREATE TABLE IF NOT EXISTS policy_decisions(
+        id TEXT PRIMARY KEY,
+        ts TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        mode TEXT NOT NULL,
+        action TEXT NOT NULL,
+        proposed TEXT NOT NULL,
+        baseline TEXT NOT NULL,
+        score REAL NOT NULL,
+        strategy TEXT NOT NULL,
+        why TEXT NOT NULL,
+        parameters_hash TEXT NOT NULL,
+        context TEXT NOT NULL,
+        scores TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS policy_decisions_kind_ts ON policy_decisions(kind, ts);
+    CREATE INDEX IF NOT EXISTS policy_decisions_ts ON policy_decisions(ts);
+    CREATE TABLE IF NOT EXISTS policy_feedback(
+        decision_id TEXT PRIMARY KEY REFERENCES policy_decisions(id),
+        ts TEXT NOT NULL,
+        action TEXT NOT NULL,
+        reward REAL NOT NULL,
+        context TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS policy_events(
+        id TEXT PRIMARY KEY,
+        ts TEXT NOT NULL,
+        event TEXT NOT NULL,
+        kind TEXT,
+        detail TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS policy_events_ts ON policy_events(ts);
+    CREATE INDEX IF NOT EXISTS policy_events_event_ts ON policy_events(event, ts);
+";
+
+const COLUMNS: &str = "id, ts, kind, mode, action, proposed, baseline, score, strategy, why, parameters_hash, context, scores";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub id: String,
+    pub ts: DateTime<Utc>,
+    pub kind: String,
+    pub mode: Mode,
+    pub action: String,
+    pub proposed: String,
+    pub baseline: String,
+    pub score: f64,
+    pub strategy: String,
+    pub why: String,
+    /// Hash of the bandit state the decision was made with.
+    pub parameters_hash: String,
+    /// `features` of the decision context.
+    pub context: Value,
+    pub scores: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackRecord {
+    pub decision_id: String,
+    pub ts: DateTime<Utc>,
+    /// Action the reward refers to (the applied action of the decision).
+    pub action: String,
+    pub reward: f64,
+    /// Context the bandit was updated with.
+    pub context: Value,
+}
+
+/// Administrative change of the policy state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: String,
+    pub ts: DateTime<Utc>,
+    /// E.g. `reset`.
+    pub event: String,
+    /// Affected kind; `None` for all kinds.
+    pub kind: Option<String>,
+    pub detail: Value,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DecisionQuery {
+    pub kind: Option<String>,
+    pub mode: Option<Mode>,
+    /// Only decisions at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Clamped to `1..=MAX_QUERY_LIMIT` (`MAX_HISTORY_LIMIT` for history).
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub event: Option<String>,
+    /// Matches `detail.host`.
+    pub host: Option<String>,
+    /// Only events at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only events before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Clamped to `1..=MAX_QUERY_LIMIT`.
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct DecisionLog {
+    conn: Mutex<Connection>,
+}
+
+impl DecisionLog {
+    /// Opens (and creates) the log at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create parent dir for {}", path.display()))?;
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("open sqlite at {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "busy_timeout", 5000)?;
+        Self::with_connection(conn)
+    }
+
+    /// Non-durable log, e.g. if the database file cannot be opened.
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn record(&self, record: &DecisionRecord) -> Result<()> {
+        self.lock().execute(
+            &format!("INSERT INTO policy_decisions({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"),
+            params![
+                record.id,
+                timestamp(&record.ts),
+                record.kind,
+                record.mode.as_str(),
+                record.action,
+                record.proposed,
+                record.baseline,
+                record.score,
+                record.strategy,
+                record.why,
+                record.parameters_hash,
+                record.context.to_string(),
+                record.scores.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<DecisionRecord>> {
+        let record = self
+            .lock()
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM policy_decisions WHERE id = ?1"),
+                params![id],
+                record_from_row,
+            )
+            .optional()?;
+        Ok(record)
+    }
+
+    /// Stores `feedback` unless its decision already has one; returns whether
+    /// it was stored.
+    pub fn record_feedback(&self, feedback: &FeedbackRecord) -> Result<bool> {
+        let inserted = self.lock().execute(
+            "INSERT OR IGNORE INTO policy_feedback(decision_id, ts, action, reward, context)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                feedback.decision_id,
+                timestamp(&feedback.ts),
+                feedback.action,
+                feedback.reward,
+                feedback.context.to_string(),
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn feedback(&self, decision_id: &str) -> Result<Option<FeedbackRecord>> {
+        let feedback = self
+            .lock()
+            .query_row(
+                "SELECT decision_id, ts, action, reward, context FROM policy_feedback WHERE decision_id = ?1",
+                params![decision_id],
+                |row| feedback_from_row(row, 0),
+            )
+            .optional()?;
+        Ok(feedback)
+    }
+
+    pub fn record_event(&self, event: &AuditEvent) -> Result<()> {
+        self.lock().execute(
+            "INSERT INTO policy_events(id, ts, event, kind, detail) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                event.id,
+                timestamp(&event.ts),
+                event.event,
+                event.kind,
+                event.detail.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Latest audit events, newest first; `limit` is clamped to
+    /// `1..=MAX_QUERY_LIMIT`.
+    pub fn events(&self, limit: usize) -> Result<Vec<AuditEvent>> {
+        self.query_events(&EventQuery {
+            limit,
+            ..Default::default()
+        })
+    }
+
+    /// Matching audit events, newest first.
+    pub fn query_events(&self, query: &EventQuery) -> Result<Vec<AuditEvent>> {
+        let conn = self.lock();
+        let mut statement = conn.prepare(
+            "SELECT id, ts, event, kind, detail FROM policy_events
+             WHERE (?1 IS NULL OR event = ?1) AND (?2 IS NULL OR json_extract(detail, '$.host') = ?2)
+               AND (?3 IS NULL OR ts >= ?3) AND (?4 IS NULL OR ts < ?4)
+             ORDER BY ts DESC, id DESC LIMIT ?5",
+        )?;
+        let events = statement
+            .query_map(
+                params![
+                    query.event,
+                    query.host,
+                    query.since.as_ref().map(timestamp),
+                    query.until.as_ref().map(timestamp),
+                    query.limit.clamp(1, MAX_QUERY_LIMIT) as i64,
+                ],
+                |row| {
+                    let ts: String = row.get(1)?;
+                    let detail: String = row.get(4)?;
+                    Ok(AuditEvent {
+                        id: row.get(0)?,
+                        ts: parse_timestamp(1, &ts)?,
+                        event: row.get(2)?,
+                        kind: row.get(3)?,
+                        detail: serde_json::from_str(&detail)
+                            .map_err(|err| text_error(4, err.into()))?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    /// Matching decisions, newest first.
+    pub fn query(&self, query: &DecisionQuery) -> Result<Vec<DecisionRecord>> {
+        Ok(self
+            .select(query, MAX_QUERY_LIMIT)?
+            .into_iter()
+            .map(|(record, _)| record)
+            .collect())
+    }
+
+    /// Matching decisions with their feedback, newest first.
+    pub fn history(
+        &self,
+        query: &DecisionQuery,
+    ) -> Result<Vec<(DecisionRecord, Option<FeedbackRecord>)>> {
+        self.select(query, MAX_HISTORY_LIMIT)
+    }
+
+    fn select(
+        &self,
+        query: &DecisionQuery,
+        max_limit: usize,
+    ) -> Result<Vec<(DecisionRecord, Option<FeedbackRecord>)>> {
+        let conn = self.lock();
+        let columns = COLUMNS
+            .split(", ")
+            .map(|column| format!("d.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut statement = conn.prepare(&format!(
+            "SELECT {columns}, f.decision_id, f.ts, f.action, f.reward, f.context
+             FROM policy_decisions d LEFT JOIN policy_feedback f ON f.decision_id = d.id
+             WHERE (?1 IS NULL OR d.kind = ?1) AND (?2 IS NULL OR d.mode = ?2) AND (?3 IS NULL OR d.ts >= ?3)
+             ORDER BY d.ts DESC, d.id DESC LIMIT ?4"
+        ))?;
+        let records = statement
+            .query_map(
+                params![
+                    query.kind,
+                    query.mode.map(|mode| mode.as_str()),
+                    query.since.as_ref().map(timestamp),
+                    query.limit.clamp(1, max_limit) as i64,
+                ],
+                |row| {
+                    let record = record_from_row(row)?;
+                    let feedback = match row.get::<_, Option<String>>(13)? {
+                        Some(_) => Some(feedback_from_row(row, 13)?),
+                        None => None,
+                    };
+                    Ok((record, feedback))
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+}
+
+/// Fixed-width UTC timestamps, so text order is time order.
+fn timestamp(ts: &DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+fn text_error(index: usize, err: Box<dyn std::error::Error + Send + Sync>) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, err)
+}
+
+fn parse_timestamp(index: usize, ts: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|err| text_error(index, err.into()))
+}
+
+/// Reads the five feedback columns starting at `offset`.
+fn feedback_from_row(row: &Row<'_>, offset: usize) -> rusqlite::Result<FeedbackRecord> {
+    let ts: String = row.get(offset + 1)?;
+    let context: String = row.get(offset + 4)?;
+    Ok(FeedbackRecord {
+        decision_id: row.get(offset)?,
+        ts: parse_timestamp(offset + 1, &ts)?,
+        action: row.get(offset + 2)?,
+        reward: row.get(offset + 3)?,
+        context: serde_json::from_str(&context)
+            .map_err(|err| text_error(offset + 4, err.into()))?,
+    })
+}
+
+fn record_from_row(row: &Row<'_>) -> rusqlite::Result<DecisionRecord> {
+    let ts: String = row.get(1)?;
+    let mode: String = row.get(3)?;
+    let context: String = row.get(11)?;
+    let scores: String = row.get(12)?;
+    Ok(DecisionRecord {
+        id: row.get(0)?,
+        ts: parse_timestamp(1, &ts)?,
+        kind: row.get(2)?,
+        mode: serde_json::from_value(Value::String(mode))
+            .map_err(|err| text_error(3, err.into()))?,
+        action: row.get(4)?,
+        proposed: row.get(5)?,
+        baseline: row.get(6)?,
+        score: row.get(7)?,
+        strategy: row.get(8)?,
+        why: row.get(9)?,
+        parameters_hash: row.get(10)?,
+        context: serde_json::from_str(&context).map_err(|err| text_error(11, err.into()))?,
+        scores: serde_json::from_str(&scores).map_err(|err| text_error(12, err.into()))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn record(id: &str, kind: &str, mode: Mode, ts: DateTime<Utc>) -> DecisionRecord {
+        DecisionRecord {
+            id: id.into(),
+            ts,
+            kind: kind.into(),
+            mode,
+            action: "notify".into(),
+            proposed: "snooze".into(),
+            baseline: "notify".into(),
+            score: 0.5,
+            strategy: "lin_ucb".into(),
+            why: "test".into(),
+            parameters_hash: "00ff".into(),
+            context: json!({"load": 0.3}),
+            scores: json!({"notify": {"score": 0.4}}),
+        }
+    }
+
+    #[test]
+    fn records_survive_reopen_and_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/decisions.db");
+        let now = Utc::now();
+        {
+            let log = DecisionLog::open(&path).unwrap();
+            log.record(&record(
+                "a",
+                "reminder",
+                Mode::Shadow,
+                now - Duration::hours(2),
+            ))
+            .unwrap();
+            log.record(&record(
+                "b",
+                "reminder",
+                Mode::Live,
+                now - Duration::minutes(5),
+            ))
+            .unwrap();
+            log.record(&record("c", "routing", Mode::Shadow, now))
+                .unwrap();
+            assert!(log
+                .record(&record("c", "routing", Mode::Shadow, now))
+                .is_err());
+        }
+
+        let log = DecisionLog::open(&path).unwrap();
+        let stored = log.get("a").unwrap().unwrap();
+        assert_eq!(stored.context, json!({"load": 0.3}));
+        assert_eq!(stored.mode, Mode::Shadow);
+        assert!(log.get("missing").unwrap().is_none());
+
+        let all = log
+            .query(&DecisionQuery {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        let ids: Vec<_> = all.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["c", "b", "a"]);
+
+        let reminders = log
+            .query(&DecisionQuery {
+                kind: Some("reminder".into()),
+                since: Some(now - Duration::hours(1)),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].id, "b");
+
+        let shadow = log
+            .query(&DecisionQuery {
+                mode: Some(Mode::Shadow),
+                limit: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(shadow.len(), 1);
+        assert_eq!(shadow[0].id, "c");
+    }
+
+    #[test]
+    fn only_the_first_feedback_per_decision_is_kept() {
+        let log = DecisionLog::in_memory().unwrap();
+        log.record(&record("a", "reminder", Mode::Live, Utc::now()))
+            .unwrap();
+        let feedback = |reward: f64| FeedbackRecord {
+            decision_id: "a".into(),
+            ts: Utc::now(),
+            action: "notify".into(),
+            reward,
+            context: json!({"load": 0.3}),
+        };
+        assert!(log.record_feedback(&feedback(1.0)).unwrap());
+        assert!(!log.record_feedback(&feedback(0.0)).unwrap());
+        assert_eq!(log.feedback("a").unwrap().unwrap().reward, 1.0);
+        assert!(log.feedback("b").unwrap().is_none());
+
+        log.record_event(&AuditEvent {
+            id: "e1".into(),
+            ts: Utc::now(),
+            event: "reset".into(),
+            kind: None,
+            detail: json!({"kinds": ["reminder"]}),
+        })
+        .unwrap();
+        let events = log.events(10).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].detail["kinds"][0], "reminder");
+
+        log.record(&record("b", "reminder", Mode::Live, Utc::now()))
+            .unwrap();
+        let history = log
+            .history(&DecisionQuery {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].0.id, "b");
+        assert!(history[0].1.is_none());
+        assert_eq!(history[1].1.as_ref().unwrap().reward, 1.0);
+    }
+
+    #[test]
+    fn events_filter_by_event_host_and_time_range() {
+        let log = DecisionLog::in_memory().unwrap();
+        let now = Utc::now();
+        let event = |id: &str, event: &str, host: &str, ts| AuditEvent {
+            id: id.into(),
+            ts,
+            event: event.into(),
+            kind: Some("webhooks".into()),
+            detail: json!({"host": host}),
+        };
+        for record in [
+            event("a", "egress", "example.org", now - Duration::hours(3)),
+            event("b", "egress", "example.org", now - Duration::hours(1)),
+            event("c", "egress", "other.org", now - Duration::minutes(30)),
+            event("d", "reset", "example.org", now),
+        ] {
+            log.record_event(&record).unwrap();
+        }
+
+        let ids = |query: EventQuery| -> Vec<String> {
+            log.query_events(&query)
+                .unwrap()
+                .into_iter()
+                .map(|event| event.id)
+                .collect()
+        };
+        assert_eq!(
+            ids(EventQuery {
+                event: Some("egress".into()),
+                host: Some("example.org".into()),
+                limit: 10,
+                ..Default::default()
+            }),
+            ["b", "a"]
+        );
+        assert_eq!(
+            ids(EventQuery {
+                event: Some("egress".into()),
+                since: Some(now - Duration::hours(2)),
+                until: Some(now - Duration::minutes(45)),
+                limit: 10,
+                ..Default::default()
+            }),
+            ["b"]
+        );
+        assert_eq!(
+            ids(EventQuery {
+                limit: 2,
+                ..Default::default()
+            }),
+            ["d", "c"]
+        );
+    }
+}
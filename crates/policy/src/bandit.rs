@@ -0,0 +1,562 @@
+//! Kontextueller Bandit mit linearem Modell pro Aktion.
+//!
+//! Jede Aktion hält eine Ridge-Regression über den Merkmalsvektor des
+//! Kontexts (siehe [`crate::features`]): `A = λI + Σ x xᵀ`, `b = Σ r x`,
+//! Schätzer `θ = A⁻¹ b`. `A⁻¹` wird per Sherman-Morrison aktualisiert, jede
+//! Rückmeldung kostet damit `O(d²)`.
+//!
+//! Strategien:
+//! - `lin_ucb` – Score `θ·x + α·√(xᵀA⁻¹x)`; deterministisch, `alpha` steuert
+//!   die Exploration.
+//! - `thompson` – Score `θ̃·x` mit `θ̃ ~ N(θ, v²A⁻¹)`; `scale` (= v) steuert die
+//!   Exploration. Mit `seed` sind die Ziehungen reproduzierbar.
+//!
+//! Zusätzlich wählt `epsilon` (ε-greedy) mit dieser Wahrscheinlichkeit eine
+//! zufällige Aktion statt der bestbewerteten. Alle Explorationsparameter sind
+//! nach oben begrenzt (siehe [`BanditConfig::validate`]).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{features, remind_bandit::DecisionContext};
+
+pub const DEFAULT_FEATURE_DIMENSION: usize = 16;
+pub const MAX_FEATURE_DIMENSION: usize = 1_024;
+/// Upper bound of `lin_ucb` `alpha` and `thompson` `scale`.
+pub const MAX_EXPLORATION: f64 = 5.0;
+/// Upper bound of `epsilon`; beyond it decisions are mostly random.
+pub const MAX_EPSILON: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Strategy {
+    LinUcb { alpha: f64 },
+    Thompson { scale: f64 },
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self::LinUcb { alpha: 1.0 }
+    }
+}
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LinUcb { .. } => "lin_ucb",
+            Self::Thompson { .. } => "thompson",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanditConfig {
+    /// Candidate actions; ties go to the earlier one.
+    pub actions: Vec<String>,
+    pub strategy: Strategy,
+    /// Hash buckets for context features (plus one bias term).
+    pub dimension: usize,
+    /// Ridge regularisation λ (prior precision), > 0.
+    pub ridge: f64,
+    /// Probability of picking a random action instead of the best one.
+    pub epsilon: f64,
+    /// Seed for Thompson sampling and epsilon; `None` draws from the OS.
+    pub seed: Option<u64>,
+}
+
+impl Default for BanditConfig {
+    fn default() -> Self {
+        Self {
+            actions: Vec::new(),
+            strategy: Strategy::default(),
+            dimension: DEFAULT_FEATURE_DIMENSION,
+            ridge: 1.0,
+            epsilon: 0.0,
+            seed: None,
+        }
+    }
+}
+
+impl BanditConfig {
+    /// Checks that exploration parameters lie in `0..=MAX_EXPLORATION`
+    /// (`alpha`, `scale`) and `0..=MAX_EPSILON`, that `ridge` is positive and
+    /// `dimension` in `1..=MAX_FEATURE_DIMENSION`.
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = |value: f64, max: f64| value.is_finite() && (0.0..=max).contains(&value);
+        let (name, value) = match self.strategy {
+            Strategy::LinUcb { alpha } => ("alpha", alpha),
+            Strategy::Thompson { scale } => ("scale", scale),
+        };
+        if !in_range(value, MAX_EXPLORATION) {
+            return Err(format!("{name} must be within 0..={MAX_EXPLORATION}"));
+        }
+        if !in_range(self.epsilon, MAX_EPSILON) {
+            return Err(format!("epsilon must be within 0..={MAX_EPSILON}"));
+        }
+        if !(self.ridge.is_finite() && self.ridge > 0.0) {
+            return Err("ridge must be finite and > 0".to_string());
+        }
+        if !(1..=MAX_FEATURE_DIMENSION).contains(&self.dimension) {
+            return Err(format!(
+                "dimension must be within 1..={MAX_FEATURE_DIMENSION}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Linear model of one action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Arm {
+    /// `A⁻¹`, row-major `d × d`.
+    a_inv: Vec<f64>,
+    b: Vec<f64>,
+    pub plays: u64,
+    pub reward_sum: f64,
+}
+
+impl Arm {
+    fn new(size: usize, ridge: f64) -> Self {
+        let mut a_inv = vec![0.0; size * size];
+        for i in 0..size {
+            a_inv[i * size + i] = 1.0 / ridge;
+        }
+        Self {
+            a_inv,
+            b: vec![0.0; size],
+            plays: 0,
+            reward_sum: 0.0,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.b.len()
+    }
+
+    /// Current estimate `θ = A⁻¹ b`.
+    pub fn theta(&self) -> Vec<f64> {
+        mat_vec(&self.a_inv, &self.b)
+    }
+
+    pub fn mean_reward(&self) -> f64 {
+        if self.plays == 0 {
+            0.0
+        } else {
+            self.reward_sum / self.plays as f64
+        }
+    }
+
+    fn update(&mut self, x: &[f64], reward: f64) {
+        // Sherman-Morrison: (A + xxᵀ)⁻¹ = A⁻¹ - (A⁻¹x)(A⁻¹x)ᵀ / (1 + xᵀA⁻¹x)
+        let size = self.size();
+        let ax = mat_vec(&self.a_inv, x);
+        let denominator = 1.0 + dot(x, &ax);
+        for i in 0..size {
+            for j in 0..size {
+                self.a_inv[i * size + j] -= ax[i] * ax[j] / denominator;
+            }
+        }
+        for (b, xi) in self.b.iter_mut().zip(x) {
+            *b += reward * xi;
+        }
+        self.plays += 1;
+        self.reward_sum += reward;
+    }
+}
+
+/// Score of one action for one context.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArmScore {
+    pub score: f64,
+    /// Predicted reward `θ·x`.
+    pub estimate: f64,
+    /// Exploration part of the score (UCB bonus or sampling deviation).
+    pub exploration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BanditDecision {
+    pub action: String,
+    pub score: f64,
+    pub scores: BTreeMap<String, ArmScore>,
+    pub strategy: &'static str,
+    pub why: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextualBandit {
+    config: BanditConfig,
+    arms: BTreeMap<String, Arm>,
+    #[serde(skip)]
+    rng: fastrand::Rng,
+}
+
+impl ContextualBandit {
+    pub fn new(config: BanditConfig) -> Self {
+        let mut bandit = Self {
+            config,
+            arms: BTreeMap::new(),
+            rng: fastrand::Rng::new(),
+        };
+        bandit.config.dimension = bandit.config.dimension.max(1);
+        if bandit.config.ridge.is_nan() || bandit.config.ridge <= 0.0 {
+            bandit.config.ridge = 1.0;
+        }
+        for action in bandit.config.actions.clone() {
+            bandit.arm_mut(&action);
+        }
+        bandit.reseed();
+        bandit
+    }
+
+    /// Restarts the random stream from the configured seed (after `new` and
+    /// after deserialising a snapshot).
+    pub fn reseed(&mut self) {
+        if let Some(seed) = self.config.seed {
+            self.rng = fastrand::Rng::with_seed(seed);
+        }
+    }
+
+    pub fn config(&self) -> &BanditConfig {
+        &self.config
+    }
+
+    pub fn arm(&self, action: &str) -> Option<&Arm> {
+        self.arms.get(action)
+    }
+
+    /// Takes over the learned arms of `saved` (e.g. from disk) if its feature
+    /// dimension matches; the own configuration stays in effect. Returns
+    /// whether the state was taken over.
+    pub fn restore_from(&mut self, saved: ContextualBandit) -> bool {
+        if saved.config.dimension != self.config.dimension {
+            return false;
+        }
+        for action in saved.config.actions {
+            if !self.config.actions.contains(&action) {
+                self.config.actions.push(action);
+            }
+        }
+        self.arms.extend(saved.arms);
+        true
+    }
+
+    /// Stable hash of configuration and learned parameters, e.g. to tell
+    /// which model state produced a decision.
+    pub fn parameters_hash(&self) -> String {
+        let state = serde_json::to_string(self).unwrap_or_default();
+        format!("{:016x}", features::fnv1a(&state))
+    }
+
+    pub fn features(&self, ctx: &DecisionContext) -> Vec<f64> {
+        features::encode(&ctx.kind, &ctx.features, self.config.dimension)
+    }
+
+    fn arm_mut(&mut self, action: &str) -> &mut Arm {
+        let size = self.config.dimension + 1;
+        let ridge = self.config.ridge;
+        self.arms
+            .entry(action.to_string())
+            .or_insert_with(|| Arm::new(size, ridge))
+    }
+
+    /// Scores every action for `ctx` and picks the best. `None` without actions.
+    pub fn decide(&mut self, ctx: &DecisionContext) -> Option<BanditDecision> {
+        let x = self.features(ctx);
+        let mut scores = BTreeMap::new();
+        let mut best: Option<(&str, f64)> = None;
+        for action in &self.config.actions {
+            let Some(arm) = self.arms.get(action) else {
+                continue;
+            };
+            let theta = arm.theta();
+            let estimate = dot(&theta, &x);
+            let exploration = match self.config.strategy {
+                Strategy::LinUcb { alpha } => {
+                    alpha * dot(&x, &mat_vec(&arm.a_inv, &x)).max(0.0).sqrt()
+                }
+                Strategy::Thompson { scale } => {
+                    let sampled = sample_gaussian(&mut self.rng, &theta, &arm.a_inv, scale);
+                    dot(&sampled, &x) - estimate
+                }
+            };
+            let score = estimate + exploration;
+            if best.is_none_or(|(_, top)| score > top) {
+                best = Some((action, score));
+            }
+            scores.insert(
+                action.clone(),
+                ArmScore {
+                    score,
+                    estimate,
+                    exploration,
+                },
+            );
+        }
+        let (best, _) = best?;
+        let mut action = best.to_string();
+        let chosen = &scores[&action];
+        let runner_up = scores
+            .iter()
+            .filter(|(name, _)| name.as_str() != action)
+            .max_by(|a, b| a.1.score.total_cmp(&b.1.score));
+        let mut why = format!(
+            "{} chose '{action}' for kind '{}': score {:.3} (estimate {:.3} + exploration {:.3})",
+            self.config.strategy.name(),
+            ctx.kind,
+            chosen.score,
+            chosen.estimate,
+            chosen.exploration,
+        );
+        if let Some((name, other)) = runner_up {
+            why.push_str(&format!(", next best '{name}' {:.3}", other.score));
+        }
+        let epsilon = self.config.epsilon;
+        if epsilon > 0.0 && self.rng.f64() < epsilon {
+            let index = self.rng.usize(..scores.len());
+            if let Some(random) = scores.keys().nth(index) {
+                action = random.clone();
+                why.push_str(&format!(
+                    "; exploring with epsilon {epsilon:.2}, picked '{action}' at random"
+                ));
+            }
+        }
+        Some(BanditDecision {
+            score: scores[&action].score,
+            action,
+            scores,
+            strategy: self.config.strategy.name(),
+            why,
+        })
+    }
+
+    /// Updates the model of `action` with the observed `reward` for `ctx`.
+    /// Unknown actions become candidates. Non-finite rewards are ignored.
+    pub fn update(&mut self, ctx: &DecisionContext, action: &str, reward: f64) {
+        if !reward.is_finite() {
+            return;
+        }
+        if !self.config.actions.iter().any(|known| known == action) {
+            self.config.actions.push(action.to_string());
+        }
+        let x = self.features(ctx);
+        self.arm_mut(action).update(&x, reward);
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(matrix: &[f64], x: &[f64]) -> Vec<f64> {
+    let size = x.len();
+    (0..size)
+        .map(|row| dot(&matrix[row * size..(row + 1) * size], x))
+        .collect()
+}
+
+/// Lower-triangular `L` with `L Lᵀ = matrix`; `None` if not positive definite.
+fn cholesky(matrix: &[f64], size: usize) -> Option<Vec<f64>> {
+    let mut lower = vec![0.0; size * size];
+    for i in 0..size {
+        for j in 0..=i {
+            let sum: f64 = (0..j)
+                .map(|k| lower[i * size + k] * lower[j * size + k])
+                .sum();
+            if i == j {
+                let diagonal = matrix[i * size + i] - sum;
+                if diagonal <= 0.0 {
+                    return None;
+                }
+                lower[i * size + j] = diagonal.sqrt();
+            } else {
+                lower[i * size + j] = (matrix[i * size + j] - sum) / lower[j * size + j];
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// Draws from `N(mean, scale² · covariance)`; falls back to `mean` if the
+/// covariance has lost positive definiteness numerically.
+fn sample_gaussian(
+    rng: &mut fastrand::Rng,
+    mean: &[f64],
+    covariance: &[f64],
+    scale: f64,
+) -> Vec<f64> {
+    let size = mean.len();
+    let Some(lower) = cholesky(covariance, size) else {
+        return mean.to_vec();
+    };
+    let z: Vec<f64> = (0..size).map(|_| standard_normal(rng)).collect();
+    (0..size)
+        .map(|i| mean[i] + scale * dot(&lower[i * size..i * size + i + 1], &z[..=i]))
+        .collect()
+}
+
+/// Box-Muller transform.
+fn standard_normal(rng: &mut fastrand::Rng) -> f64 {
+    let u1 = rng.f64().max(f64::MIN_POSITIVE);
+    let u2 = rng.f64();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx(features: serde_json::Value) -> DecisionContext {
+        DecisionContext {
+            kind: "reminder".into(),
+            features,
+        }
+    }
+
+    fn bandit(strategy: Strategy, seed: u64) -> ContextualBandit {
+        ContextualBandit::new(BanditConfig {
+            actions: vec!["notify".into(), "snooze".into()],
+            strategy,
+            seed: Some(seed),
+            ..BanditConfig::default()
+        })
+    }
+
+    /// `notify` pays off while focused is false, `snooze` while it is true.
+    fn reward(features: &serde_json::Value, action: &str) -> f64 {
+        let focused = features["focused"].as_bool().unwrap_or(false);
+        match (focused, action) {
+            (false, "notify") | (true, "snooze") => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn train(bandit: &mut ContextualBandit, rounds: usize) {
+        for round in 0..rounds {
+            let features = json!({ "focused": round % 2 == 0 });
+            let context = ctx(features.clone());
+            let action = bandit.decide(&context).unwrap().action;
+            bandit.update(&context, &action, reward(&features, &action));
+        }
+    }
+
+    #[test]
+    fn lin_ucb_learns_context_dependent_actions() {
+        let mut bandit = bandit(Strategy::LinUcb { alpha: 0.5 }, 1);
+        train(&mut bandit, 200);
+        let away = bandit.decide(&ctx(json!({ "focused": false }))).unwrap();
+        let focused = bandit.decide(&ctx(json!({ "focused": true }))).unwrap();
+        assert_eq!(away.action, "notify");
+        assert_eq!(focused.action, "snooze");
+        assert!(away.scores["notify"].estimate > 0.8);
+        assert!(away
+            .why
+            .starts_with("lin_ucb chose 'notify' for kind 'reminder'"));
+    }
+
+    #[test]
+    fn thompson_sampling_is_reproducible_with_a_seed_and_learns() {
+        let run = || {
+            let mut bandit = bandit(Strategy::Thompson { scale: 0.3 }, 42);
+            train(&mut bandit, 200);
+            let decisions: Vec<_> = (0..20)
+                .map(|i| {
+                    bandit
+                        .decide(&ctx(json!({ "focused": i % 2 == 0 })))
+                        .unwrap()
+                })
+                .collect();
+            (bandit.arm("notify").unwrap().plays, decisions)
+        };
+        let (plays, decisions) = run();
+        assert_eq!(run(), (plays, decisions.clone()));
+        let correct = decisions
+            .iter()
+            .enumerate()
+            .filter(|(i, d)| d.action == if i % 2 == 0 { "snooze" } else { "notify" })
+            .count();
+        assert!(correct >= 18, "only {correct} of 20 decisions were right");
+    }
+
+    #[test]
+    fn epsilon_explores_random_actions_reproducibly() {
+        let run = |epsilon: f64| {
+            let mut bandit = ContextualBandit::new(BanditConfig {
+                actions: vec!["notify".into(), "snooze".into()],
+                strategy: Strategy::LinUcb { alpha: 0.0 },
+                epsilon,
+                seed: Some(3),
+                ..BanditConfig::default()
+            });
+            let context = ctx(json!({ "load": 0.5 }));
+            bandit.update(&context, "notify", 1.0);
+            (0..100)
+                .map(|_| bandit.decide(&context).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert!(run(0.0).iter().all(|d| d.action == "notify"));
+        let explored = run(0.5);
+        assert_eq!(explored, run(0.5));
+        let random: Vec<_> = explored.iter().filter(|d| d.action == "snooze").collect();
+        assert!((10..=40).contains(&random.len()), "{}", random.len());
+        assert!(random[0].why.contains("exploring with epsilon 0.50"));
+        assert_eq!(random[0].score, random[0].scores["snooze"].score);
+    }
+
+    #[test]
+    fn updates_are_per_action_and_snapshots_round_trip() {
+        let mut bandit = bandit(Strategy::default(), 7);
+        let context = ctx(json!({ "load": 0.5 }));
+        bandit.update(&context, "notify", 1.0);
+        bandit.update(&context, "dismiss", 0.5);
+        assert_eq!(bandit.arm("notify").unwrap().plays, 1);
+        assert_eq!(bandit.arm("snooze").unwrap().plays, 0);
+        assert_eq!(bandit.config().actions, ["notify", "snooze", "dismiss"]);
+        bandit.update(&context, "notify", f64::NAN);
+        assert_eq!(bandit.arm("notify").unwrap().plays, 1);
+
+        let snapshot = serde_json::to_value(&bandit).unwrap();
+        let mut restored: ContextualBandit = serde_json::from_value(snapshot).unwrap();
+        restored.reseed();
+        assert_eq!(restored.arm("notify"), bandit.arm("notify"));
+        assert_eq!(restored.parameters_hash(), bandit.parameters_hash());
+        restored.update(&context, "snooze", 0.1);
+        assert_ne!(restored.parameters_hash(), bandit.parameters_hash());
+        assert_eq!(restored.decide(&context).unwrap().action, "notify");
+
+        let mut fresh = ContextualBandit::new(BanditConfig {
+            actions: vec!["snooze".into(), "call".into()],
+            ..BanditConfig::default()
+        });
+        assert!(fresh.restore_from(bandit.clone()));
+        assert_eq!(
+            fresh.config().actions,
+            ["snooze", "call", "notify", "dismiss"]
+        );
+        assert_eq!(fresh.arm("notify"), bandit.arm("notify"));
+        assert_eq!(fresh.arm("call").unwrap().plays, 0);
+        let mut narrow = ContextualBandit::new(BanditConfig {
+            dimension: 4,
+            ..BanditConfig::default()
+        });
+        assert!(!narrow.restore_from(bandit));
+        assert!(narrow.arm("notify").is_none());
+    }
+
+    #[test]
+    fn sherman_morrison_matches_the_explicit_inverse() {
+        let mut arm = Arm::new(2, 1.0);
+        arm.update(&[1.0, 2.0], 1.0);
+        // A = I + [[1, 2], [2, 4]] = [[2, 2], [2, 5]], A⁻¹ = [[5, -2], [-2, 2]] / 6
+        let expected = [5.0 / 6.0, -2.0 / 6.0, -2.0 / 6.0, 2.0 / 6.0];
+        for (got, want) in arm.a_inv.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-12);
+        }
+        let lower = cholesky(&[4.0, 2.0, 2.0, 3.0], 2).unwrap();
+        assert_eq!(lower, [2.0, 0.0, 1.0, 2f64.sqrt()]);
+        assert!(cholesky(&[1.0, 2.0, 2.0, 1.0], 2).is_none());
+    }
+}
@@ -0,0 +1,107 @@
+//! Ressourcen-Budgets pro Intent.
+//!
+//! `policies/decisions.yaml` kann pro Intent ein Budget (`latency_ms`,
+//! `tokens`, `energy_wh`) und pro Aktion einer Art deren Kosten festlegen.
+//! Überschreitet die anzuwendende Aktion das Budget, weicht die Engine auf
+//! die bestbewertete Aktion aus, die hineinpasst (Downgrade); passt keine,
+//! wird die Entscheidung abgelehnt. Aktionen ohne Kostenangabe gelten als
+//! kostenlos, Ressourcen ohne Budget als unbegrenzt.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Resources {
+    pub latency_ms: Option<f64>,
+    pub tokens: Option<f64>,
+    pub energy_wh: Option<f64>,
+}
+
+impl Resources {
+    fn entries(&self) -> [(&'static str, Option<f64>); 3] {
+        [
+            ("latency_ms", self.latency_ms),
+            ("tokens", self.tokens),
+            ("energy_wh", self.energy_wh),
+        ]
+    }
+
+    /// Share of `budget` used per limited resource (`1.0` = fully used).
+    pub fn utilization(&self, budget: &Resources) -> BTreeMap<&'static str, f64> {
+        self.entries()
+            .into_iter()
+            .zip(budget.entries())
+            .filter_map(|((resource, cost), (_, limit))| {
+                let limit = limit?;
+                Some((resource, cost.unwrap_or(0.0) / limit))
+            })
+            .collect()
+    }
+
+    /// Resources whose cost exceeds `budget`.
+    pub fn exceeded(&self, budget: &Resources) -> Vec<&'static str> {
+        self.utilization(budget)
+            .into_iter()
+            .filter(|(_, share)| *share > 1.0)
+            .map(|(resource, _)| resource)
+            .collect()
+    }
+
+    /// Checks that all given values are finite and, for budgets, positive.
+    pub fn validate(&self, budget: bool) -> Result<(), String> {
+        for (resource, value) in self.entries() {
+            let Some(value) = value else { continue };
+            let valid = value.is_finite() && if budget { value > 0.0 } else { value >= 0.0 };
+            if !valid {
+                let bound = if budget { "> 0" } else { ">= 0" };
+                return Err(format!("{resource} must be finite and {bound}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Budget outcome of a decision.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetCheck {
+    pub intent: String,
+    /// Action that was replaced because it exceeded the budget.
+    pub downgraded_from: Option<String>,
+    /// Utilization of the applied action.
+    pub utilization: BTreeMap<&'static str, f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utilization_covers_only_limited_resources() {
+        let budget = Resources {
+            latency_ms: Some(200.0),
+            tokens: Some(1_000.0),
+            energy_wh: None,
+        };
+        let cost = Resources {
+            latency_ms: Some(300.0),
+            tokens: None,
+            energy_wh: Some(5.0),
+        };
+        let utilization = cost.utilization(&budget);
+        assert_eq!(utilization.len(), 2);
+        assert_eq!(utilization["latency_ms"], 1.5);
+        assert_eq!(utilization["tokens"], 0.0);
+        assert_eq!(cost.exceeded(&budget), ["latency_ms"]);
+        assert!(Resources::default().exceeded(&budget).is_empty());
+
+        assert!(budget.validate(true).is_ok());
+        let zero = Resources {
+            tokens: Some(0.0),
+            ..Resources::default()
+        };
+        assert!(zero.validate(true).is_err());
+        assert!(zero.validate(false).is_ok());
+    }
+}
@@ -0,0 +1,551 @@
+//! Entscheidungs-Engine: ein kontextueller Bandit pro Entscheidungsart (`kind`).
+//!
+//! Konfiguration (`policies/decisions.yaml`):
+//!
+//! ```yaml
+//! mode: shadow            # Default für alle Arten
+//! kinds:
+//!   reminder:
+//!     actions: [notify, snooze]
+//!     baseline: notify     # im Shadow-Modus angewandte Aktion (Default: erste)
+//!     mode: live           # optional, überschreibt `mode`
+//!     strategy: { kind: lin_ucb, alpha: 1.0 }   # alpha/scale 0..=5
+//!     epsilon: 0.1         # optional, zufällige Aktion mit p = ε (0..=0.5)
+//!     intent: remind       # optional, Default: `features.intent` bzw. Art
+//!     costs:               # optional, Kosten je Aktion
+//!       notify: { latency_ms: 50, energy_wh: 0.01 }
+//! budgets:                # optional, Budget je Intent (siehe `budget`)
+//!   remind: { latency_ms: 200, energy_wh: 0.05 }
+//! ```
+//!
+//! Im Shadow-Modus rechnet der Bandit mit, angewandt wird aber die Baseline;
+//! im Live-Modus gilt der Vorschlag des Bandits. Arten ohne Eintrag werden
+//! abgelehnt.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    bandit::{ArmScore, BanditConfig, ContextualBandit},
+    budget::{BudgetCheck, Resources},
+    remind_bandit::DecisionContext,
+    state::{EngineState, STATE_SCHEMA_VERSION},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// The bandit only proposes; the baseline action is applied.
+    #[default]
+    Shadow,
+    /// The bandit's proposal is applied.
+    Live,
+}
+
+impl Mode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Shadow => "shadow",
+            Self::Live => "live",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KindConfig {
+    #[serde(flatten)]
+    pub bandit: BanditConfig,
+    /// Action applied in shadow mode; defaults to the first action.
+    pub baseline: Option<String>,
+    /// Overrides [`PolicyConfig::mode`] for this kind.
+    pub mode: Option<Mode>,
+    /// Intent whose budget applies if the context names none.
+    pub intent: Option<String>,
+    /// Resource cost per action.
+    pub costs: BTreeMap<String, Resources>,
+}
+
+impl KindConfig {
+    pub fn baseline(&self) -> Option<&str> {
+        self.baseline
+            .as_deref()
+            .or_else(|| self.bandit.actions.first().map(String::as_str))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    pub mode: Mode,
+    pub kinds: BTreeMap<String, KindConfig>,
+    /// Resource budget per intent.
+    pub budgets: BTreeMap<String, Resources>,
+}
+
+impl Default for PolicyConfig {
+    /// Shadow mode with the reminder decision (`notify`/`snooze`).
+    fn default() -> Self {
+        let reminder = KindConfig {
+            bandit: BanditConfig {
+                actions: vec!["notify".to_string(), "snooze".to_string()],
+                ..BanditConfig::default()
+            },
+            baseline: Some("notify".to_string()),
+            ..KindConfig::default()
+        };
+        Self {
+            mode: Mode::Shadow,
+            kinds: BTreeMap::from([("reminder".to_string(), reminder)]),
+            budgets: BTreeMap::new(),
+        }
+    }
+}
+
+impl PolicyConfig {
+    /// Checks that every kind has actions and a baseline among them, that its
+    /// exploration parameters are within bounds, that costs refer to known
+    /// actions and that budgets are positive.
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        for (intent, budget) in &self.budgets {
+            budget.validate(true).map_err(|err| {
+                PolicyError::InvalidConfig(format!("budget of intent '{intent}': {err}"))
+            })?;
+        }
+        for (kind, config) in &self.kinds {
+            if config.bandit.actions.is_empty() {
+                return Err(PolicyError::InvalidConfig(format!(
+                    "kind '{kind}' has no actions"
+                )));
+            }
+            config
+                .bandit
+                .validate()
+                .map_err(|err| PolicyError::InvalidConfig(format!("kind '{kind}': {err}")))?;
+            if let Some(baseline) = &config.baseline {
+                if !config.bandit.actions.contains(baseline) {
+                    return Err(PolicyError::InvalidConfig(format!(
+                        "baseline '{baseline}' of kind '{kind}' is not one of its actions"
+                    )));
+                }
+            }
+            for (action, cost) in &config.costs {
+                if !config.bandit.actions.contains(action) {
+                    return Err(PolicyError::InvalidConfig(format!(
+                        "cost of '{action}' in kind '{kind}' refers to an unknown action"
+                    )));
+                }
+                cost.validate(false).map_err(|err| {
+                    PolicyError::InvalidConfig(format!("cost of '{kind}/{action}': {err}"))
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("unknown decision kind '{0}'")]
+    UnknownKind(String),
+    #[error("invalid policy config: {0}")]
+    InvalidConfig(String),
+    #[error("policy state has schema version {found}, expected {expected}")]
+    IncompatibleState { found: u32, expected: u32 },
+    #[error("no action of kind '{kind}' fits the {} budget of intent '{intent}'", resources.join("/"))]
+    BudgetExceeded {
+        kind: String,
+        intent: String,
+        resources: Vec<&'static str>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyDecision {
+    pub kind: String,
+    pub mode: Mode,
+    /// Action to apply: the proposal in live mode, the baseline in shadow mode.
+    pub action: String,
+    /// Action chosen by the bandit.
+    pub proposed: String,
+    pub baseline: String,
+    pub score: f64,
+    pub scores: BTreeMap<String, ArmScore>,
+    pub strategy: &'static str,
+    pub why: String,
+    /// [`ContextualBandit::parameters_hash`] before the decision.
+    pub parameters_hash: String,
+    /// Set if a budget applies to the intent of the decision.
+    pub budget: Option<BudgetCheck>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyEngine {
+    config: PolicyConfig,
+    bandits: BTreeMap<String, ContextualBandit>,
+}
+
+impl Default for PolicyEngine {
+    fn default() -> Self {
+        Self::new(PolicyConfig::default())
+    }
+}
+
+impl PolicyEngine {
+    pub fn new(config: PolicyConfig) -> Self {
+        let bandits = config
+            .kinds
+            .iter()
+            .map(|(kind, kind_config)| {
+                (
+                    kind.clone(),
+                    ContextualBandit::new(kind_config.bandit.clone()),
+                )
+            })
+            .collect();
+        Self { config, bandits }
+    }
+
+    pub fn config(&self) -> &PolicyConfig {
+        &self.config
+    }
+
+    pub fn bandit(&self, kind: &str) -> Option<&ContextualBandit> {
+        self.bandits.get(kind)
+    }
+
+    /// Configured mode of `kind`.
+    pub fn mode(&self, kind: &str) -> Mode {
+        self.config
+            .kinds
+            .get(kind)
+            .and_then(|config| config.mode)
+            .unwrap_or(self.config.mode)
+    }
+
+    /// Intent whose budget applies to `ctx`: `features.intent`, else the
+    /// configured intent of the kind, else the kind itself.
+    pub fn intent(&self, ctx: &DecisionContext) -> String {
+        ctx.features
+            .get("intent")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                self.config
+                    .kinds
+                    .get(&ctx.kind)
+                    .and_then(|config| config.intent.clone())
+            })
+            .unwrap_or_else(|| ctx.kind.clone())
+    }
+
+    /// Decides for `ctx`; `mode` overrides the configured mode. An action
+    /// over the intent's budget is downgraded to the best-scoring action
+    /// within it; if none fits, the decision fails with
+    /// [`PolicyError::BudgetExceeded`].
+    pub fn decide(
+        &mut self,
+        ctx: &DecisionContext,
+        mode: Option<Mode>,
+    ) -> Result<PolicyDecision, PolicyError> {
+        let mode = mode.unwrap_or_else(|| self.mode(&ctx.kind));
+        let unknown = || PolicyError::UnknownKind(ctx.kind.clone());
+        let baseline = self
+            .config
+            .kinds
+            .get(&ctx.kind)
+            .and_then(KindConfig::baseline)
+            .ok_or_else(unknown)?
+            .to_string();
+        let bandit = self.bandits.get_mut(&ctx.kind).ok_or_else(unknown)?;
+        let parameters_hash = bandit.parameters_hash();
+        let decision = bandit.decide(ctx).ok_or_else(unknown)?;
+        let mut action = match mode {
+            Mode::Live => decision.action.clone(),
+            Mode::Shadow => baseline.clone(),
+        };
+        let mut why = match mode {
+            Mode::Live => decision.why,
+            Mode::Shadow => format!(
+                "shadow mode, applying baseline '{baseline}'; {}",
+                decision.why
+            ),
+        };
+
+        let intent = self.intent(ctx);
+        let mut budget_check = None;
+        if let Some(budget) = self.config.budgets.get(&intent) {
+            let costs = &self.config.kinds[&ctx.kind].costs;
+            let cost = |action: &str| costs.get(action).copied().unwrap_or_default();
+            let exceeded = cost(&action).exceeded(budget);
+            let mut downgraded_from = None;
+            if !exceeded.is_empty() {
+                let mut ranked: Vec<(&String, &ArmScore)> = decision.scores.iter().collect();
+                ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+                let Some((fallback, _)) = ranked
+                    .into_iter()
+                    .find(|(candidate, _)| cost(candidate).exceeded(budget).is_empty())
+                else {
+                    return Err(PolicyError::BudgetExceeded {
+                        kind: ctx.kind.clone(),
+                        intent,
+                        resources: exceeded,
+                    });
+                };
+                why = format!(
+                    "{why}; downgraded from '{action}' to '{fallback}', which fits the {} budget of intent '{intent}'",
+                    exceeded.join("/")
+                );
+                downgraded_from = Some(std::mem::replace(&mut action, fallback.clone()));
+            }
+            budget_check = Some(BudgetCheck {
+                utilization: cost(&action).utilization(budget),
+                intent,
+                downgraded_from,
+            });
+        }
+
+        Ok(PolicyDecision {
+            kind: ctx.kind.clone(),
+            mode,
+            action,
+            proposed: decision.action,
+            baseline,
+            score: decision.score,
+            scores: decision.scores,
+            strategy: decision.strategy,
+            why,
+            parameters_hash,
+            budget: budget_check,
+        })
+    }
+
+    /// Learned state of all kinds, e.g. for [`EngineState::save`].
+    pub fn state(&self) -> EngineState {
+        EngineState {
+            schema_version: STATE_SCHEMA_VERSION,
+            saved_at: chrono::Utc::now(),
+            bandits: self.bandits.clone(),
+        }
+    }
+
+    /// Restores learned state for the configured kinds; kinds that are no
+    /// longer configured or whose feature dimension changed start fresh.
+    /// Returns the restored kinds.
+    pub fn restore(&mut self, state: EngineState) -> Result<Vec<String>, PolicyError> {
+        if state.schema_version != STATE_SCHEMA_VERSION {
+            return Err(PolicyError::IncompatibleState {
+                found: state.schema_version,
+                expected: STATE_SCHEMA_VERSION,
+            });
+        }
+        let mut restored = Vec::new();
+        for (kind, saved) in state.bandits {
+            if let Some(bandit) = self.bandits.get_mut(&kind) {
+                if bandit.restore_from(saved) {
+                    restored.push(kind);
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Forgets everything learned for `kind` (all kinds if `None`). Returns
+    /// the reset kinds.
+    pub fn reset(&mut self, kind: Option<&str>) -> Result<Vec<String>, PolicyError> {
+        let kinds: Vec<String> = match kind {
+            Some(kind) if self.config.kinds.contains_key(kind) => vec![kind.to_string()],
+            Some(kind) => return Err(PolicyError::UnknownKind(kind.to_string())),
+            None => self.config.kinds.keys().cloned().collect(),
+        };
+        for kind in &kinds {
+            let config = self.config.kinds[kind].bandit.clone();
+            self.bandits
+                .insert(kind.clone(), ContextualBandit::new(config));
+        }
+        Ok(kinds)
+    }
+
+    /// Feeds the observed `reward` for `action` in `ctx` into the bandit of
+    /// `ctx.kind`.
+    pub fn update(
+        &mut self,
+        ctx: &DecisionContext,
+        action: &str,
+        reward: f64,
+    ) -> Result<(), PolicyError> {
+        let bandit = self
+            .bandits
+            .get_mut(&ctx.kind)
+            .ok_or_else(|| PolicyError::UnknownKind(ctx.kind.clone()))?;
+        bandit.update(ctx, action, reward);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx(kind: &str) -> DecisionContext {
+        DecisionContext {
+            kind: kind.into(),
+            features: json!({"load": 0.5}),
+        }
+    }
+
+    #[test]
+    fn shadow_applies_baseline_and_live_applies_proposal() {
+        let config: PolicyConfig = serde_yaml_ng::from_str(
+            "mode: shadow\nkinds:\n  routing:\n    actions: [local, cloud]\n    baseline: cloud\n    strategy: { kind: lin_ucb, alpha: 0.0 }\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let mut engine = PolicyEngine::new(config);
+
+        // Ties go to the first action, so the bandit proposes `local`.
+        let shadow = engine.decide(&ctx("routing"), None).unwrap();
+        assert_eq!(shadow.mode, Mode::Shadow);
+        assert_eq!(shadow.proposed, "local");
+        assert_eq!(shadow.action, "cloud");
+        assert!(shadow.why.starts_with("shadow mode"));
+
+        let live = engine.decide(&ctx("routing"), Some(Mode::Live)).unwrap();
+        assert_eq!(live.action, "local");
+        assert_eq!(live.scores.len(), 2);
+
+        assert_eq!(
+            engine.decide(&ctx("unknown"), None),
+            Err(PolicyError::UnknownKind("unknown".into()))
+        );
+
+        engine.update(&ctx("routing"), "cloud", 1.0).unwrap();
+        let live = engine.decide(&ctx("routing"), Some(Mode::Live)).unwrap();
+        assert_eq!(live.action, "cloud");
+        assert_ne!(live.parameters_hash, shadow.parameters_hash);
+        assert!(engine.update(&ctx("unknown"), "cloud", 1.0).is_err());
+    }
+
+    #[test]
+    fn state_restores_matching_kinds_and_reset_forgets() {
+        let mut engine = PolicyEngine::default();
+        engine.update(&ctx("reminder"), "snooze", 1.0).unwrap();
+        let mut state = engine.state();
+        let learned = engine.bandit("reminder").unwrap().parameters_hash();
+
+        let mut restarted = PolicyEngine::default();
+        assert_eq!(restarted.restore(state.clone()).unwrap(), ["reminder"]);
+        assert_eq!(
+            restarted.bandit("reminder").unwrap().parameters_hash(),
+            learned
+        );
+
+        assert_eq!(restarted.reset(None).unwrap(), ["reminder"]);
+        assert_eq!(
+            restarted
+                .bandit("reminder")
+                .unwrap()
+                .arm("snooze")
+                .unwrap()
+                .plays,
+            0
+        );
+        assert!(restarted.reset(Some("routing")).is_err());
+
+        state.schema_version = STATE_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            restarted.restore(state),
+            Err(PolicyError::IncompatibleState { .. })
+        ));
+    }
+
+    #[test]
+    fn budgets_downgrade_or_reject_expensive_actions() {
+        let yaml = "mode: live\nbudgets:\n  answer: { latency_ms: 500 }\nkinds:\n  routing:\n    actions: [cloud, local]\n    intent: answer\n    strategy: { kind: lin_ucb, alpha: 0.0 }\n    costs:\n      cloud: { latency_ms: 900 }\n      local: { latency_ms: 300 }\n";
+        let config: PolicyConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        config.validate().unwrap();
+        let mut engine = PolicyEngine::new(config.clone());
+
+        // Ties go to `cloud`, which is over budget.
+        let decision = engine.decide(&ctx("routing"), None).unwrap();
+        assert_eq!(decision.proposed, "cloud");
+        assert_eq!(decision.action, "local");
+        let budget = decision.budget.unwrap();
+        assert_eq!(budget.intent, "answer");
+        assert_eq!(budget.downgraded_from.as_deref(), Some("cloud"));
+        assert_eq!(budget.utilization["latency_ms"], 0.6);
+        assert!(decision.why.contains("downgraded from 'cloud'"));
+
+        // Contexts naming another intent are not limited.
+        let free = DecisionContext {
+            kind: "routing".into(),
+            features: json!({"intent": "chat"}),
+        };
+        let decision = engine.decide(&free, None).unwrap();
+        assert_eq!(decision.action, "cloud");
+        assert!(decision.budget.is_none());
+
+        let mut tight = config;
+        tight.budgets.get_mut("answer").unwrap().latency_ms = Some(100.0);
+        let mut engine = PolicyEngine::new(tight);
+        assert_eq!(
+            engine.decide(&ctx("routing"), None),
+            Err(PolicyError::BudgetExceeded {
+                kind: "routing".into(),
+                intent: "answer".into(),
+                resources: vec!["latency_ms"],
+            })
+        );
+    }
+
+    #[test]
+    fn validation_rejects_foreign_baselines() {
+        let mut config = PolicyConfig::default();
+        config.kinds.get_mut("reminder").unwrap().baseline = Some("call".into());
+        assert!(matches!(
+            config.validate(),
+            Err(PolicyError::InvalidConfig(_))
+        ));
+        config
+            .kinds
+            .get_mut("reminder")
+            .unwrap()
+            .bandit
+            .actions
+            .clear();
+        assert!(config.validate().is_err());
+        assert!(PolicyConfig::default().validate().is_ok());
+
+        let mut config = PolicyConfig::default();
+        let reminder = config.kinds.get_mut("reminder").unwrap();
+        reminder.costs.insert("call".into(), Resources::default());
+        assert!(config.validate().is_err());
+        let mut config = PolicyConfig::default();
+        config.budgets.insert(
+            "remind".into(),
+            Resources {
+                tokens: Some(0.0),
+                ..Resources::default()
+            },
+        );
+        assert!(config.validate().is_err());
+
+        for (yaml, valid) in [
+            ("{ kind: lin_ucb, alpha: 0.0 }", true),
+            ("{ kind: lin_ucb, alpha: 5.0 }\n    epsilon: 0.5", true),
+            ("{ kind: lin_ucb, alpha: -0.1 }", false),
+            ("{ kind: thompson, scale: 50.0 }", false),
+            ("{ kind: lin_ucb, alpha: 1.0 }\n    epsilon: 0.9", false),
+            ("{ kind: lin_ucb, alpha: 1.0 }\n    ridge: 0.0", false),
+        ] {
+            let config: PolicyConfig = serde_yaml_ng::from_str(&format!(
+                "kinds:\n  reminder:\n    actions: [notify, snooze]\n    strategy: {yaml}\n"
+            ))
+            .unwrap();
+            assert_eq!(config.validate().is_ok(), valid, "{yaml}");
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Eskalation zu Cloud-Anbietern.
+//!
+//! Lokal ist der Default. Eine Anfrage darf nur in die Cloud, wenn alle
+//! Prüfungen bestehen:
+//!
+//! 1. `safe_mode` ist aus – im Safe-Mode wird jede Eskalation verweigert
+//! 2. `cloud_fallback.enabled` in `policies/routing.yaml`
+//! 3. der Intent steht in `intents`
+//! 4. keines der Sensitivitäts-Flags steht in `blocked_flags`
+//! 5. das Latenzbudget reicht für einen Cloud-Roundtrip (`min_budget_ms`)
+//! 6. der Nutzer hat zugestimmt (falls `require_consent`)
+//!
+//! Alle Prüfungen werden ausgewertet und mit Begründung zurückgegeben, damit
+//! der Aufrufer sieht, woran eine Eskalation scheitert.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationConfig {
+    pub enabled: bool,
+    /// Intents that may leave the machine.
+    pub intents: Vec<String>,
+    /// Sensitivity flags that keep a request local.
+    pub blocked_flags: Vec<String>,
+    /// Smallest latency budget that still fits a cloud round trip.
+    pub min_budget_ms: u64,
+    pub require_consent: bool,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intents: vec!["chat".to_string(), "search".to_string()],
+            blocked_flags: ["pii", "secret", "credentials", "health", "private"]
+                .map(String::from)
+                .to_vec(),
+            min_budget_ms: 1_500,
+            require_consent: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EscalationRequest {
+    pub intent: Option<String>,
+    /// Content sensitivity flags, e.g. `pii`.
+    pub sensitivity: Vec<String>,
+    pub latency_budget_ms: Option<u64>,
+    pub consent: bool,
+    pub safe_mode: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Local,
+    Cloud,
+}
+
+impl Target {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Cloud => "cloud",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationCheck {
+    pub check: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EscalationDecision {
+    pub target: Target,
+    /// Every check in evaluation order.
+    pub checks: Vec<EscalationCheck>,
+    pub why: String,
+}
+
+/// Decides whether `request` may escalate to a cloud provider.
+pub fn decide(config: &EscalationConfig, request: &EscalationRequest) -> EscalationDecision {
+    let mut checks = Vec::with_capacity(6);
+    let mut check = |check: &'static str, passed: bool, detail: String| {
+        checks.push(EscalationCheck {
+            check,
+            passed,
+            detail,
+        });
+    };
+
+    check(
+        "safe_mode",
+        !request.safe_mode,
+        if request.safe_mode {
+            "safe mode is active, cloud escalation is refused".to_string()
+        } else {
+            "safe mode is off".to_string()
+        },
+    );
+    check(
+        "enabled",
+        config.enabled,
+        format!(
+            "cloud fallback is {} in the routing policy",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+    );
+    let intent = request.intent.as_deref().map(str::trim).unwrap_or_default();
+    let intent_allowed = config.intents.iter().any(|allowed| allowed == intent);
+    check(
+        "intent",
+        intent_allowed,
+        match (intent.is_empty(), intent_allowed) {
+            (true, _) => "no intent given".to_string(),
+            (false, true) => format!("intent '{intent}' may escalate"),
+            (false, false) => format!("intent '{intent}' stays local"),
+        },
+    );
+    let blocked: Vec<&str> = request
+        .sensitivity
+        .iter()
+        .map(|flag| flag.trim())
+        .filter(|flag| {
+            config
+                .blocked_flags
+                .iter()
+                .any(|blocked| blocked.eq_ignore_ascii_case(flag))
+        })
+        .collect();
+    check(
+        "sensitivity",
+        blocked.is_empty(),
+        if blocked.is_empty() {
+            "no blocking sensitivity flags".to_string()
+        } else {
+            format!("sensitive content ({}) stays local", blocked.join(", "))
+        },
+    );
+    let fits_budget = request
+        .latency_budget_ms
+        .is_none_or(|budget| budget >= config.min_budget_ms);
+    check(
+        "latency_budget",
+        fits_budget,
+        match request.latency_budget_ms {
+            None => "no latency budget given".to_string(),
+            Some(budget) if fits_budget => format!(
+                "budget {budget} ms fits a cloud round trip ({} ms)",
+                config.min_budget_ms
+            ),
+            Some(budget) => format!(
+                "budget {budget} ms is below a cloud round trip ({} ms)",
+                config.min_budget_ms
+            ),
+        },
+    );
+    let consented = request.consent || !config.require_consent;
+    check(
+        "consent",
+        consented,
+        match (config.require_consent, request.consent) {
+            (false, _) => "consent not required".to_string(),
+            (true, true) => "user consented".to_string(),
+            (true, false) => "user consent missing".to_string(),
+        },
+    );
+
+    let failed: Vec<&str> = checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| check.detail.as_str())
+        .collect();
+    let (target, why) = if failed.is_empty() {
+        (Target::Cloud, "all escalation checks passed".to_string())
+    } else {
+        (
+            Target::Local,
+            format!("staying local: {}", failed.join("; ")),
+        )
+    };
+    EscalationDecision {
+        target,
+        checks,
+        why,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed() -> EscalationRequest {
+        EscalationRequest {
+            intent: Some("chat".into()),
+            sensitivity: vec!["public".into()],
+            latency_budget_ms: Some(5_000),
+            consent: true,
+            safe_mode: false,
+        }
+    }
+
+    #[test]
+    fn escalates_only_if_every_check_passes() {
+        let config = EscalationConfig {
+            enabled: true,
+            ..EscalationConfig::default()
+        };
+        let decision = decide(&config, &allowed());
+        assert_eq!(decision.target, Target::Cloud);
+        assert_eq!(decision.checks.len(), 6);
+
+        let cases = [
+            EscalationRequest {
+                safe_mode: true,
+                ..allowed()
+            },
+            EscalationRequest {
+                intent: Some("execute".into()),
+                ..allowed()
+            },
+            EscalationRequest {
+                sensitivity: vec!["PII".into()],
+                ..allowed()
+            },
+            EscalationRequest {
+                latency_budget_ms: Some(200),
+                ..allowed()
+            },
+            EscalationRequest {
+                consent: false,
+                ..allowed()
+            },
+        ];
+        for request in cases {
+            let decision = decide(&config, &request);
+            assert_eq!(decision.target, Target::Local, "{request:?}");
+            assert_eq!(
+                decision.checks.iter().filter(|check| !check.passed).count(),
+                1
+            );
+        }
+
+        let refused = decide(
+            &EscalationConfig::default(),
+            &EscalationRequest {
+                safe_mode: true,
+                ..allowed()
+            },
+        );
+        assert!(refused
+            .why
+            .starts_with("staying local: safe mode is active"));
+        assert!(refused.why.contains("disabled"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Merkmalsvektoren aus JSON-Kontexten (Hashing-Trick).
+//!
+//! `Context.features` ist beliebiges JSON. Jedes Blatt landet in einem von
+//! `dimension` Buckets, bestimmt durch einen stabilen FNV-1a-Hash seines
+//! Pfads; der Vektor endet mit einem konstanten Bias-Eintrag.
+//!
+//! - Zahlen: Wert unter `pfad` (Werte sollten grob auf `[-1, 1]` skaliert sein)
+//! - Booleans: `1.0` unter `pfad`, falls `true`
+//! - Strings: `1.0` unter `pfad=wert` (One-Hot)
+//! - Arrays: jedes Element unter demselben Pfad; Objekte: `pfad.schlüssel`
+//! - `kind` des Kontexts: `1.0` unter `kind=<kind>`
+
+use serde_json::Value;
+
+/// Stable 64-bit FNV-1a; `DefaultHasher` may change between Rust releases.
+pub(crate) fn fnv1a(input: &str) -> u64 {
+    input.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Encodes `kind` and `features` into `dimension + 1` values (last = bias).
+pub fn encode(kind: &str, features: &Value, dimension: usize) -> Vec<f64> {
+    let dimension = dimension.max(1);
+    let mut vector = vec![0.0; dimension + 1];
+    let mut add = |key: &str, value: f64| {
+        let bucket = (fnv1a(key) % dimension as u64) as usize;
+        vector[bucket] += value;
+    };
+    add(&format!("kind={kind}"), 1.0);
+    walk("", features, &mut add);
+    vector[dimension] = 1.0;
+    vector
+}
+
+fn walk(path: &str, value: &Value, add: &mut impl FnMut(&str, f64)) {
+    match value {
+        Value::Null => {}
+        Value::Bool(flag) => {
+            if *flag {
+                add(path, 1.0);
+            }
+        }
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64().filter(|n| n.is_finite()) {
+                add(path, number);
+            }
+        }
+        Value::String(text) => add(&format!("{path}={text}"), 1.0),
+        Value::Array(items) => items.iter().for_each(|item| walk(path, item, add)),
+        Value::Object(map) => {
+            for (key, item) in map {
+                let nested = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                walk(&nested, item, add);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn encoding_is_stable_and_ends_with_bias() {
+        let features = json!({"load": 0.5, "quiet": true, "mode": "focus", "tags": ["a", "b"]});
+        let first = encode("reminder", &features, 8);
+        let second = encode("reminder", &features, 8);
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 9);
+        assert_eq!(first[8], 1.0);
+        // kind + load + quiet + mode + two tags
+        let total: f64 = first[..8].iter().sum();
+        assert!((total - 5.5).abs() < 1e-9);
+
+        let away = encode("reminder", &json!({"mode": "away"}), 16);
+        assert_ne!(encode("reminder", &json!({"mode": "focus"}), 16), away);
+        assert_ne!(encode("routing", &json!({"mode": "away"}), 16), away);
+    }
+}
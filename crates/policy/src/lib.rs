@@ -1,3 +1,12 @@
+pub mod audit;
+pub mod backtest;
+pub mod bandit;
+pub mod budget;
+pub mod engine;
+pub mod escalation;
+pub mod features;
 pub mod policy_client;
 pub mod remind_bandit;
+pub mod report;
+pub mod state;
 pub mod utils;
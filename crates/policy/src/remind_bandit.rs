@@ -1,49 +1,19 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct ArmStats {
-    plays: u64,
-    reward: f64,
-}
-
-impl ArmStats {
-    fn average(&self) -> f64 {
-        if self.plays == 0 {
-            0.0
-        } else {
-            self.reward / self.plays as f64
-        }
-    }
-}
+use crate::bandit::{BanditConfig, ContextualBandit};
 
+/// Contextual bandit for reminders, choosing between `notify` and `snooze`
+/// (LinUCB by default, see [`crate::bandit`]).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct RemindBandit {
-    actions: Vec<String>,
-    stats: HashMap<String, ArmStats>,
-    #[serde(default = "default_epsilon")]
-    epsilon: f64,
-}
-
-fn default_epsilon() -> f64 {
-    0.1
+    bandit: ContextualBandit,
 }
 
 impl Default for RemindBandit {
     fn default() -> Self {
-        let actions = vec!["notify".to_string(), "snooze".to_string()];
-        let stats = actions
-            .iter()
-            .map(|action| (action.clone(), ArmStats::default()))
-            .collect();
-        Self {
-            actions,
-            stats,
-            epsilon: default_epsilon(),
-        }
+        Self::new(BanditConfig::default())
     }
 }
 
@@ -61,8 +31,24 @@ pub struct DecisionOutcome {
 }
 
 impl RemindBandit {
+    /// Uses `config`; without actions the reminder actions are filled in.
+    pub fn new(mut config: BanditConfig) -> Self {
+        if config.actions.is_empty() {
+            config.actions = vec!["notify".to_string(), "snooze".to_string()];
+        }
+        Self {
+            bandit: ContextualBandit::new(config),
+        }
+    }
+
+    pub fn bandit(&self) -> &ContextualBandit {
+        &self.bandit
+    }
+
+    /// Replaces the state with `snapshot`; invalid snapshots are ignored.
     pub fn load(&mut self, snapshot: Value) {
-        if let Ok(loaded) = serde_json::from_value::<Self>(snapshot) {
+        if let Ok(mut loaded) = serde_json::from_value::<Self>(snapshot) {
+            loaded.bandit.reseed();
             *self = loaded;
         }
     }
@@ -72,33 +58,25 @@ impl RemindBandit {
     }
 
     pub fn decide(&mut self, ctx: &DecisionContext) -> DecisionOutcome {
-        let _ = ctx;
-        let action = self.best_action().unwrap_or_else(|| {
-            self.actions
-                .first()
-                .cloned()
-                .unwrap_or_else(|| "notify".into())
-        });
-
-        DecisionOutcome {
-            action,
-            parameters: json!({}),
+        match self.bandit.decide(ctx) {
+            Some(decision) => DecisionOutcome {
+                action: decision.action,
+                parameters: json!({
+                    "score": decision.score,
+                    "strategy": decision.strategy,
+                    "scores": decision.scores,
+                    "why": decision.why,
+                }),
+            },
+            None => DecisionOutcome {
+                action: "notify".into(),
+                parameters: json!({}),
+            },
         }
     }
 
     pub fn feedback(&mut self, ctx: &DecisionContext, action: &str, reward: f32) {
-        let _ = ctx;
-        let entry = self.stats.entry(action.to_string()).or_default();
-        entry.plays = entry.plays.saturating_add(1);
-        entry.reward += reward as f64;
-    }
-
-    fn best_action(&self) -> Option<String> {
-        self.actions.iter().cloned().max_by(|a, b| {
-            let left = self.stats.get(a).map(ArmStats::average).unwrap_or_default();
-            let right = self.stats.get(b).map(ArmStats::average).unwrap_or_default();
-            left.partial_cmp(&right).unwrap_or(Ordering::Equal)
-        })
+        self.bandit.update(ctx, action, f64::from(reward));
     }
 }
 
@@ -0,0 +1,224 @@
+//! Vergleich Shadow vs. Baseline aus dem Entscheidungsprotokoll.
+//!
+//! Im Shadow-Modus wird die Baseline angewandt, der Vorschlag des Bandits nur
+//! protokolliert. Pro Entscheidungsart fasst der Bericht zusammen:
+//!
+//! - Divergenzrate: Anteil der Entscheidungen mit `proposed != baseline`
+//! - hypothetische Reward-Differenz: beobachteter Reward der Baseline plus die
+//!   zum Entscheidungszeitpunkt geschätzte Differenz `θ·x(proposed) − θ·x(baseline)`;
+//!   bei Übereinstimmung ist die Differenz 0
+//! - Konfidenz: 95-%-Intervall (Normalapproximation) der mittleren Differenz
+//!
+//! Belastbar ist der Bericht erst ab [`MIN_SAMPLES`] Rückmeldungen; die
+//! Empfehlung lautet dann `switch_to_live`, wenn das Intervall vollständig über
+//! 0 liegt, sonst `keep_shadow`.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    audit::{DecisionRecord, FeedbackRecord},
+    engine::Mode,
+};
+
+/// Decisions with feedback needed before a recommendation is made.
+pub const MIN_SAMPLES: usize = 30;
+const Z_95: f64 = 1.96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recommendation {
+    InsufficientData,
+    KeepShadow,
+    SwitchToLive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowReport {
+    pub kind: String,
+    /// Shadow decisions considered.
+    pub decisions: usize,
+    pub divergent: usize,
+    pub divergence_rate: f64,
+    /// Shadow decisions with feedback.
+    pub with_feedback: usize,
+    /// Mean observed reward of the applied baseline.
+    pub baseline_mean_reward: Option<f64>,
+    /// Mean hypothetical reward had the proposals been applied.
+    pub policy_mean_reward: Option<f64>,
+    /// `policy_mean_reward - baseline_mean_reward`.
+    pub reward_difference: Option<f64>,
+    /// 95 % interval of `reward_difference`.
+    pub confidence_interval: Option<[f64; 2]>,
+    pub recommendation: Recommendation,
+}
+
+/// One report per kind (sorted by kind); non-shadow decisions are ignored.
+pub fn shadow_reports(history: &[(DecisionRecord, Option<FeedbackRecord>)]) -> Vec<ShadowReport> {
+    let mut by_kind: BTreeMap<&str, Vec<&(DecisionRecord, Option<FeedbackRecord>)>> =
+        BTreeMap::new();
+    for entry in history
+        .iter()
+        .filter(|(record, _)| record.mode == Mode::Shadow)
+    {
+        by_kind
+            .entry(entry.0.kind.as_str())
+            .or_default()
+            .push(entry);
+    }
+    by_kind
+        .into_iter()
+        .map(|(kind, entries)| report(kind, &entries))
+        .collect()
+}
+
+fn report(kind: &str, entries: &[&(DecisionRecord, Option<FeedbackRecord>)]) -> ShadowReport {
+    let decisions = entries.len();
+    let divergent = entries
+        .iter()
+        .filter(|(record, _)| record.proposed != record.baseline)
+        .count();
+
+    let (rewards, differences): (Vec<f64>, Vec<f64>) = entries
+        .iter()
+        .filter_map(|(record, feedback)| {
+            let feedback = feedback.as_ref()?;
+            Some((feedback.reward, estimated_difference(record)))
+        })
+        .unzip();
+    let with_feedback = rewards.len();
+    let baseline_mean_reward = mean(&rewards);
+    let reward_difference = mean(&differences);
+    let policy_mean_reward = baseline_mean_reward
+        .zip(reward_difference)
+        .map(|(baseline, difference)| baseline + difference);
+    let confidence_interval = confidence_interval(&differences);
+    let recommendation = match confidence_interval {
+        _ if with_feedback < MIN_SAMPLES => Recommendation::InsufficientData,
+        Some([low, _]) if low > 0.0 => Recommendation::SwitchToLive,
+        _ => Recommendation::KeepShadow,
+    };
+
+    ShadowReport {
+        kind: kind.to_string(),
+        decisions,
+        divergent,
+        divergence_rate: if decisions == 0 {
+            0.0
+        } else {
+            divergent as f64 / decisions as f64
+        },
+        with_feedback,
+        baseline_mean_reward,
+        policy_mean_reward,
+        reward_difference,
+        confidence_interval,
+        recommendation,
+    }
+}
+
+/// Predicted reward of the proposal minus that of the baseline at decision
+/// time; 0 if they agree or the scores lack estimates.
+fn estimated_difference(record: &DecisionRecord) -> f64 {
+    if record.proposed == record.baseline {
+        return 0.0;
+    }
+    let estimate = |action: &str| record.scores[action]["estimate"].as_f64();
+    estimate(&record.proposed)
+        .zip(estimate(&record.baseline))
+        .map_or(0.0, |(proposed, baseline)| proposed - baseline)
+}
+
+pub(crate) fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// 95 % interval of the mean of `values` (normal approximation); needs at
+/// least two values.
+pub(crate) fn confidence_interval(values: &[f64]) -> Option<[f64; 2]> {
+    let n = values.len();
+    let mean = mean(values).filter(|_| n > 1)?;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let margin = Z_95 * (variance / n as f64).sqrt();
+    Some([mean - margin, mean + margin])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn entry(
+        proposed: &str,
+        mode: Mode,
+        reward: Option<f64>,
+    ) -> (DecisionRecord, Option<FeedbackRecord>) {
+        let record = DecisionRecord {
+            id: format!("{proposed}-{reward:?}"),
+            ts: Utc::now(),
+            kind: "reminder".into(),
+            mode,
+            action: "notify".into(),
+            proposed: proposed.into(),
+            baseline: "notify".into(),
+            score: 0.0,
+            strategy: "lin_ucb".into(),
+            why: String::new(),
+            parameters_hash: String::new(),
+            context: json!({}),
+            scores: json!({
+                "notify": {"estimate": 0.2},
+                "snooze": {"estimate": 0.7},
+            }),
+        };
+        let feedback = reward.map(|reward| FeedbackRecord {
+            decision_id: record.id.clone(),
+            ts: Utc::now(),
+            action: "notify".into(),
+            reward,
+            context: json!({}),
+        });
+        (record, feedback)
+    }
+
+    #[test]
+    fn summarizes_divergence_and_reward_difference() {
+        let mut history = vec![
+            entry("snooze", Mode::Shadow, Some(0.0)),
+            entry("notify", Mode::Shadow, Some(0.5)),
+            entry("snooze", Mode::Shadow, None),
+            entry("notify", Mode::Shadow, None),
+            entry("snooze", Mode::Live, Some(1.0)),
+        ];
+        let reports = shadow_reports(&history);
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.decisions, 4);
+        assert_eq!(report.divergent, 2);
+        assert_eq!(report.divergence_rate, 0.5);
+        assert_eq!(report.with_feedback, 2);
+        assert_eq!(report.baseline_mean_reward, Some(0.25));
+        // Only the divergent decision with feedback contributes 0.5.
+        assert!((report.reward_difference.unwrap() - 0.25).abs() < 1e-12);
+        assert!((report.policy_mean_reward.unwrap() - 0.5).abs() < 1e-12);
+        assert_eq!(report.recommendation, Recommendation::InsufficientData);
+
+        history = (0..MIN_SAMPLES)
+            .map(|i| entry("snooze", Mode::Shadow, Some(i as f64 / 100.0)))
+            .collect();
+        let report = &shadow_reports(&history)[0];
+        let [low, high] = report.confidence_interval.unwrap();
+        assert!((low - 0.5).abs() < 1e-9 && (high - 0.5).abs() < 1e-9);
+        assert_eq!(report.recommendation, Recommendation::SwitchToLive);
+
+        history = (0..MIN_SAMPLES)
+            .map(|i| entry("notify", Mode::Shadow, Some(i as f64)))
+            .collect();
+        assert_eq!(
+            shadow_reports(&history)[0].recommendation,
+            Recommendation::KeepShadow
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Persistenz des gelernten Zustands.
+//!
+//! Der Zustand aller Banditen wird als JSON mit `schema_version` gespeichert.
+//! Geschrieben wird atomar (temporäre Datei + Umbenennen), damit ein Absturz
+//! beim Speichern den letzten Stand nicht zerstört. Beim Laden wird die
+//! Version geprüft; inkompatible Stände werden abgelehnt, nicht umgedeutet.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{bandit::ContextualBandit, engine::PolicyError};
+
+/// Bump when the serialized bandit layout changes incompatibly.
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineState {
+    pub schema_version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub bandits: BTreeMap<String, ContextualBandit>,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    schema_version: u32,
+}
+
+impl EngineState {
+    /// `None` if there is no file at `path`.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("read {}", path.display()));
+            }
+        };
+        let probe: VersionProbe = serde_json::from_str(&text)
+            .with_context(|| format!("{} has no schema_version", path.display()))?;
+        if probe.schema_version != STATE_SCHEMA_VERSION {
+            return Err(PolicyError::IncompatibleState {
+                found: probe.schema_version,
+                expected: STATE_SCHEMA_VERSION,
+            }
+            .into());
+        }
+        let state = serde_json::from_str(&text)
+            .with_context(|| format!("parse policy state {}", path.display()))?;
+        Ok(Some(state))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("create parent dir for {}", path.display()))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PolicyEngine;
+
+    #[test]
+    fn save_load_round_trip_and_version_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/policy_state.json");
+        assert!(EngineState::load(&path).unwrap().is_none());
+
+        let engine = PolicyEngine::default();
+        engine.state().save(&path).unwrap();
+        let loaded = EngineState::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.schema_version, STATE_SCHEMA_VERSION);
+        assert!(loaded.bandits.contains_key("reminder"));
+
+        fs::write(&path, r#"{"schema_version": 99, "bandits": {}}"#).unwrap();
+        let err = EngineState::load(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PolicyError>(),
+            Some(PolicyError::IncompatibleState { found: 99, .. })
+        ));
+    }
+}
@@ -2,43 +2,90 @@ pub mod utils {
     pub mod events;
 }
 
-#[cfg(feature = "heimlern")]
+/// Reminder policy served by the policy API: the contextual bandit from
+/// [`policy::remind_bandit`]. The `heimlern` feature only swaps in the frozen
+/// `heimlern-core` wire types (see `docs/contracts/heimlern-compatibility-freeze.md`);
+/// the vendored `heimlern-bandits` shim is not used for decisions.
 pub mod heimlern {
-    pub use heimlern_bandits::RemindBandit;
-    pub use heimlern_core::{Context, Decision};
-}
+    use policy::remind_bandit::{self, DecisionContext};
+    use serde_json::json;
 
-#[cfg(not(feature = "heimlern"))]
-pub mod heimlern {
-    use serde_json::{json, Value};
+    #[cfg(feature = "heimlern")]
+    pub use heimlern_core::{Context, Decision};
 
+    #[cfg(not(feature = "heimlern"))]
     #[derive(Clone, Debug)]
     pub struct Context {
         pub kind: String,
-        pub features: Value,
+        pub features: serde_json::Value,
     }
 
+    #[cfg(not(feature = "heimlern"))]
     #[derive(Clone, Debug)]
     pub struct Decision {
         pub action: String,
         pub score: f32,
         pub why: String,
-        pub context: Option<Value>,
+        pub context: Option<serde_json::Value>,
     }
 
     #[derive(Default, Clone)]
-    pub struct RemindBandit;
+    pub struct RemindBandit {
+        inner: remind_bandit::RemindBandit,
+    }
+
+    fn decision_context(ctx: &Context) -> DecisionContext {
+        DecisionContext {
+            kind: ctx.kind.clone(),
+            features: ctx.features.clone(),
+        }
+    }
 
     impl RemindBandit {
         pub fn decide(&mut self, ctx: &Context) -> Decision {
+            let outcome = self.inner.decide(&decision_context(ctx));
+            let params = &outcome.parameters;
             Decision {
-                action: "shadow".to_string(),
-                score: 0.0,
-                why: format!("shadow-mode decision for kind '{}'.", ctx.kind),
-                context: Some(json!({})),
+                score: params["score"].as_f64().unwrap_or(0.0) as f32,
+                why: params["why"].as_str().map_or_else(
+                    || format!("default action for kind '{}'", ctx.kind),
+                    str::to_string,
+                ),
+                context: Some(json!({
+                    "strategy": params["strategy"],
+                    "scores": params["scores"],
+                })),
+                action: outcome.action,
             }
         }
 
-        pub fn feedback(&mut self, _ctx: &Context, _action: &str, _reward: f32) {}
+        pub fn feedback(&mut self, ctx: &Context, action: &str, reward: f32) {
+            self.inner.feedback(&decision_context(ctx), action, reward);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn decisions_follow_feedback() {
+            let mut bandit = RemindBandit::default();
+            let ctx = Context {
+                kind: "reminder".into(),
+                features: json!({"hour": 9}),
+            };
+            for _ in 0..5 {
+                bandit.feedback(&ctx, "snooze", 1.0);
+                bandit.feedback(&ctx, "notify", 0.0);
+            }
+
+            let decision = bandit.decide(&ctx);
+            assert_eq!(decision.action, "snooze");
+            assert!(decision.score > 0.0);
+            let context = decision.context.unwrap();
+            assert_eq!(context["strategy"], "lin_ucb");
+            assert!(context["scores"]["notify"].is_object());
+        }
     }
 }
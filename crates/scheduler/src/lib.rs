@@ -0,0 +1,614 @@
+//! Zeitgesteuerte Aufgaben (cron-artig) für HausKI.
+//!
+//! Zeitpläne stehen in einer YAML-Datei:
+//!
+//! ```yaml
+//! schedules:
+//!   - id: retention-nightly
+//!     cron: "0 30 3 * * *"     # sec min hour day-of-month month day-of-week [year], Ortszeit
+//!     task: retention_sweep
+//!     jitter_sec: 600           # zufällige Verzögerung 0..=600 s pro Lauf
+//!     params: { dry_run: false }
+//! ```
+//!
+//! Was ein `task` bedeutet, entscheidet der Aufrufer über [`TaskRunner`]; dieses
+//! Crate kümmert sich um Takt, Jitter, Überlappungsschutz (ein Lauf pro Zeitplan
+//! gleichzeitig, weitere Auslösungen werden übersprungen), den Status des letzten
+//! Laufs und Metriken pro Zeitplan. Meldet der Runner [`TaskRunner::paused`], fallen
+//! planmäßige Läufe aus (Outcome `paused`), manuelle Auslösungen laufen weiter.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    future::Future,
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, Utc};
+use prometheus_client::{
+    encoding::{EncodeLabel, EncodeLabelSet, LabelSetEncoder},
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// Longest single sleep while waiting for the next run, so that wall-clock
+/// jumps (suspend, NTP) are noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Result of a task run: a summary, or an error message.
+pub type TaskFuture = Pin<Box<dyn Future<Output = Result<Value, String>> + Send>>;
+
+/// Executes the `task` of a schedule.
+pub trait TaskRunner: Send + Sync + 'static {
+    fn run(&self, schedule: &ScheduleDef) -> TaskFuture;
+
+    /// While `true`, timed runs are skipped (outcome `paused`); manual
+    /// triggers still run.
+    fn paused(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchedulesFile {
+    #[serde(default)]
+    pub schedules: Vec<ScheduleDef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleDef {
+    pub id: String,
+    /// Cron expression with seconds, evaluated in local time.
+    pub cron: String,
+    pub task: String,
+    #[serde(default)]
+    pub params: Value,
+    /// Upper bound of the random delay added to every run.
+    #[serde(default)]
+    pub jitter_sec: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn load_schedules(path: impl AsRef<Path>) -> Result<SchedulesFile> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("failed to read schedules {}: {e}", path.display()))?;
+    serde_yaml_ng::from_str(&content)
+        .map_err(|e| anyhow!("failed to parse schedules {}: {e}", path.display()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Schedule,
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RunRecord {
+    pub trigger: RunTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScheduleStatus {
+    pub id: String,
+    pub task: String,
+    pub cron: String,
+    pub enabled: bool,
+    pub jitter_sec: u64,
+    pub running: bool,
+    /// Planned start of the next run (jitter included).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<RunRecord>,
+    pub runs_total: u64,
+    pub failures_total: u64,
+    /// Triggers dropped because the previous run was still active.
+    pub skipped_total: u64,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ScheduleLabels {
+    schedule: String,
+}
+
+impl EncodeLabelSet for ScheduleLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> fmt::Result {
+        ("schedule", self.schedule.as_str()).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ScheduleRunLabels {
+    schedule: String,
+    outcome: &'static str,
+}
+
+impl EncodeLabelSet for ScheduleRunLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder<'_>) -> fmt::Result {
+        ("schedule", self.schedule.as_str()).encode(encoder.encode_label())?;
+        ("outcome", self.outcome).encode(encoder.encode_label())?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Metrics {
+    runs: Family<ScheduleRunLabels, Counter>,
+    last_duration: Family<ScheduleLabels, Gauge<f64, AtomicU64>>,
+    last_success: Family<ScheduleLabels, Gauge<f64, AtomicU64>>,
+}
+
+#[derive(Default)]
+struct EntryState {
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<RunRecord>,
+    runs_total: u64,
+    failures_total: u64,
+    skipped_total: u64,
+}
+
+struct Entry {
+    def: ScheduleDef,
+    cron: cron::Schedule,
+    running: AtomicBool,
+    state: Mutex<EntryState>,
+}
+
+impl Entry {
+    fn state(&self) -> std::sync::MutexGuard<'_, EntryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn labels(&self) -> ScheduleLabels {
+        ScheduleLabels {
+            schedule: self.def.id.clone(),
+        }
+    }
+
+    fn run_labels(&self, outcome: &'static str) -> ScheduleRunLabels {
+        ScheduleRunLabels {
+            schedule: self.def.id.clone(),
+            outcome,
+        }
+    }
+}
+
+pub struct Scheduler {
+    entries: Vec<Arc<Entry>>,
+    runner: OnceLock<Arc<dyn TaskRunner>>,
+    metrics: Metrics,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("schedules", &self.entries.len())
+            .field("started", &self.runner.get().is_some())
+            .finish()
+    }
+}
+
+impl Scheduler {
+    /// Validates all schedules (unique ids, parseable cron expressions).
+    pub fn new(file: SchedulesFile) -> Result<Self> {
+        let mut seen = HashSet::new();
+        let mut entries = Vec::with_capacity(file.schedules.len());
+        for def in file.schedules {
+            if def.id.trim().is_empty() {
+                return Err(anyhow!("schedule without id"));
+            }
+            if !seen.insert(def.id.clone()) {
+                return Err(anyhow!("duplicate schedule id '{}'", def.id));
+            }
+            let cron = cron::Schedule::from_str(&def.cron)
+                .map_err(|e| anyhow!("schedule '{}': invalid cron '{}': {e}", def.id, def.cron))?;
+            entries.push(Arc::new(Entry {
+                def,
+                cron,
+                running: AtomicBool::new(false),
+                state: Mutex::new(EntryState::default()),
+            }));
+        }
+        Ok(Self {
+            entries,
+            runner: OnceLock::new(),
+            metrics: Metrics::default(),
+        })
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+            runner: OnceLock::new(),
+            metrics: Metrics::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn register_metrics(&self, registry: &mut Registry) {
+        registry.register(
+            "scheduler_runs",
+            "Total number of scheduled task runs by schedule and outcome (ok, error, skipped, paused)",
+            self.metrics.runs.clone(),
+        );
+        registry.register(
+            "scheduler_last_run_duration_seconds",
+            "Duration of the last run per schedule",
+            self.metrics.last_duration.clone(),
+        );
+        registry.register(
+            "scheduler_last_success_timestamp_seconds",
+            "Unix time of the last successful run per schedule",
+            self.metrics.last_success.clone(),
+        );
+    }
+
+    /// Starts one timer per enabled schedule; later calls are ignored.
+    /// Must be called from within a Tokio runtime.
+    pub fn start(self: &Arc<Self>, runner: Arc<dyn TaskRunner>) {
+        if self.runner.set(runner).is_err() {
+            tracing::warn!("scheduler already started");
+            return;
+        }
+        for entry in self.entries.iter().filter(|entry| entry.def.enabled) {
+            tokio::spawn(self.clone().drive(entry.clone()));
+        }
+        tracing::info!(schedules = self.entries.len(), "scheduler started");
+    }
+
+    /// Runs a schedule right away (subject to overlap protection).
+    /// `None` for unknown ids or before [`Scheduler::start`], `Some(false)`
+    /// if the previous run is still active.
+    pub fn trigger(self: &Arc<Self>, id: &str) -> Option<bool> {
+        let entry = self.entries.iter().find(|entry| entry.def.id == id)?;
+        self.runner.get()?;
+        Some(self.fire(entry, RunTrigger::Manual))
+    }
+
+    pub fn statuses(&self) -> Vec<ScheduleStatus> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let state = entry.state();
+                ScheduleStatus {
+                    id: entry.def.id.clone(),
+                    task: entry.def.task.clone(),
+                    cron: entry.def.cron.clone(),
+                    enabled: entry.def.enabled,
+                    jitter_sec: entry.def.jitter_sec,
+                    running: entry.running.load(Ordering::Acquire),
+                    next_run: state.next_run,
+                    last_run: state.last_run.clone(),
+                    runs_total: state.runs_total,
+                    failures_total: state.failures_total,
+                    skipped_total: state.skipped_total,
+                }
+            })
+            .collect()
+    }
+
+    async fn drive(self: Arc<Self>, entry: Arc<Entry>) {
+        loop {
+            let Some(fire_at) = next_fire(&entry.cron, entry.def.jitter_sec, Local::now()) else {
+                tracing::info!(schedule = %entry.def.id, "schedule has no further runs");
+                entry.state().next_run = None;
+                return;
+            };
+            entry.state().next_run = Some(fire_at);
+            while let Ok(remaining) = (fire_at - Utc::now()).to_std() {
+                if remaining.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(remaining.min(MAX_SLEEP)).await;
+            }
+            if self.runner.get().is_some_and(|runner| runner.paused()) {
+                entry.state().skipped_total += 1;
+                self.metrics
+                    .runs
+                    .get_or_create(&entry.run_labels("paused"))
+                    .inc();
+                tracing::info!(schedule = %entry.def.id, "scheduler paused, skipping run");
+                continue;
+            }
+            self.fire(&entry, RunTrigger::Schedule);
+        }
+    }
+
+    fn fire(&self, entry: &Arc<Entry>, trigger: RunTrigger) -> bool {
+        let Some(runner) = self.runner.get().cloned() else {
+            return false;
+        };
+        if entry.running.swap(true, Ordering::AcqRel) {
+            entry.state().skipped_total += 1;
+            self.metrics
+                .runs
+                .get_or_create(&entry.run_labels("skipped"))
+                .inc();
+            tracing::warn!(schedule = %entry.def.id, "previous run still active, skipping");
+            return false;
+        }
+
+        let entry = entry.clone();
+        let runs = self.metrics.runs.clone();
+        let last_duration = self.metrics.last_duration.clone();
+        let last_success = self.metrics.last_success.clone();
+        tokio::spawn(async move {
+            let started_at = Utc::now();
+            let started = Instant::now();
+            tracing::info!(schedule = %entry.def.id, task = %entry.def.task, ?trigger, "scheduled task started");
+            let outcome = tokio::spawn(runner.run(&entry.def))
+                .await
+                .unwrap_or_else(|err| Err(format!("task panicked: {err}")));
+            let duration = started.elapsed();
+            let finished_at = Utc::now();
+
+            let ok = outcome.is_ok();
+            last_duration
+                .get_or_create(&entry.labels())
+                .set(duration.as_secs_f64());
+            if ok {
+                last_success
+                    .get_or_create(&entry.labels())
+                    .set(finished_at.timestamp() as f64);
+            }
+            runs.get_or_create(&entry.run_labels(if ok { "ok" } else { "error" }))
+                .inc();
+            match &outcome {
+                Ok(_) => {
+                    tracing::info!(schedule = %entry.def.id, duration_ms = duration.as_millis() as u64, "scheduled task finished")
+                }
+                Err(err) => {
+                    tracing::warn!(schedule = %entry.def.id, error = %err, "scheduled task failed")
+                }
+            }
+
+            let (result, error) = match outcome {
+                Ok(result) => (Some(result), None),
+                Err(err) => (None, Some(err)),
+            };
+            {
+                let mut state = entry.state();
+                state.runs_total += 1;
+                if !ok {
+                    state.failures_total += 1;
+                }
+                state.last_run = Some(RunRecord {
+                    trigger,
+                    started_at,
+                    finished_at,
+                    duration_ms: duration.as_millis() as u64,
+                    ok,
+                    result,
+                    error,
+                });
+            }
+            entry.running.store(false, Ordering::Release);
+        });
+        true
+    }
+}
+
+/// Next cron tick after `now`, delayed by a random jitter of up to `jitter_sec`.
+fn next_fire(
+    cron: &cron::Schedule,
+    jitter_sec: u64,
+    now: DateTime<Local>,
+) -> Option<DateTime<Utc>> {
+    let tick = cron.after(&now).next()?.with_timezone(&Utc);
+    let jitter_ms = fastrand::u64(0..=jitter_sec.saturating_mul(1_000));
+    Some(tick + chrono::Duration::milliseconds(i64::try_from(jitter_ms).unwrap_or(i64::MAX)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Semaphore;
+
+    fn def(id: &str, cron: &str) -> ScheduleDef {
+        ScheduleDef {
+            id: id.into(),
+            cron: cron.into(),
+            task: "noop".into(),
+            params: Value::Null,
+            jitter_sec: 0,
+            enabled: true,
+        }
+    }
+
+    /// Runs block until a permit is released, so tests control run length.
+    struct GatedRunner(Arc<Semaphore>);
+
+    impl TaskRunner for GatedRunner {
+        fn run(&self, schedule: &ScheduleDef) -> TaskFuture {
+            let gate = self.0.clone();
+            let id = schedule.id.clone();
+            Box::pin(async move {
+                let _permit = gate.acquire().await.map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "schedule": id }))
+            })
+        }
+    }
+
+    async fn wait_for_runs(scheduler: &Scheduler, runs: u64) -> ScheduleStatus {
+        for _ in 0..300 {
+            let status = scheduler.statuses().remove(0);
+            if status.runs_total >= runs && !status.running {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("schedule did not reach {runs} runs");
+    }
+
+    #[test]
+    fn loads_yaml_and_rejects_invalid_schedules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules.yaml");
+        std::fs::write(
+            &path,
+            r#"
+schedules:
+  - id: retention-nightly
+    cron: "0 30 3 * * *"
+    task: retention_sweep
+    jitter_sec: 600
+  - id: backup-weekly
+    cron: "0 0 4 * * Sun"
+    task: backup
+    enabled: false
+    params: { keep: 4 }
+"#,
+        )
+        .unwrap();
+        let file = load_schedules(&path).unwrap();
+        assert_eq!(file.schedules.len(), 2);
+        assert_eq!(file.schedules[1].params["keep"], 4);
+        let scheduler = Scheduler::new(file).unwrap();
+        let statuses = scheduler.statuses();
+        assert!(statuses[0].enabled && !statuses[1].enabled);
+        assert_eq!(statuses[0].jitter_sec, 600);
+
+        let err = Scheduler::new(SchedulesFile {
+            schedules: vec![def("a", "not a cron")],
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("schedule 'a'"));
+        let err = Scheduler::new(SchedulesFile {
+            schedules: vec![def("a", "0 * * * * *"), def("a", "0 * * * * *")],
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("duplicate"));
+    }
+
+    #[test]
+    fn jitter_delays_within_bounds() {
+        let cron = cron::Schedule::from_str("0 0 * * * *").unwrap();
+        let now = Local::now();
+        let tick = cron.after(&now).next().unwrap().with_timezone(&Utc);
+        assert_eq!(next_fire(&cron, 0, now), Some(tick));
+        for _ in 0..50 {
+            let fire = next_fire(&cron, 90, now).unwrap();
+            assert!(fire >= tick && fire <= tick + chrono::Duration::seconds(90));
+        }
+    }
+
+    #[tokio::test]
+    async fn overlapping_triggers_are_skipped() {
+        let scheduler = Arc::new(
+            Scheduler::new(SchedulesFile {
+                // Yearly: only manual triggers run during the test.
+                schedules: vec![def("sweep", "0 0 0 1 1 *")],
+            })
+            .unwrap(),
+        );
+        let gate = Arc::new(Semaphore::new(0));
+        assert_eq!(scheduler.trigger("sweep"), None);
+        scheduler.start(Arc::new(GatedRunner(gate.clone())));
+
+        assert_eq!(scheduler.trigger("sweep"), Some(true));
+        assert_eq!(scheduler.trigger("sweep"), Some(false));
+        assert_eq!(scheduler.trigger("unknown"), None);
+        gate.add_permits(1);
+
+        let status = wait_for_runs(&scheduler, 1).await;
+        assert_eq!(status.skipped_total, 1);
+        assert!(status.next_run.is_some());
+        let last = status.last_run.unwrap();
+        assert!(last.ok);
+        assert_eq!(last.trigger, RunTrigger::Manual);
+        assert_eq!(last.result.unwrap()["schedule"], "sweep");
+
+        let mut registry = Registry::default();
+        scheduler.register_metrics(&mut registry);
+        let mut text = String::new();
+        prometheus_client::encoding::text::encode(&mut text, &registry).unwrap();
+        assert!(text.contains(r#"scheduler_runs_total{schedule="sweep",outcome="skipped"} 1"#));
+        assert!(text.contains(r#"scheduler_runs_total{schedule="sweep",outcome="ok"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn cron_ticks_run_the_task() {
+        let scheduler = Arc::new(
+            Scheduler::new(SchedulesFile {
+                schedules: vec![def("every-second", "* * * * * *")],
+            })
+            .unwrap(),
+        );
+        scheduler.start(Arc::new(GatedRunner(Arc::new(Semaphore::new(
+            Semaphore::MAX_PERMITS,
+        )))));
+        let status = wait_for_runs(&scheduler, 1).await;
+        assert_eq!(status.last_run.unwrap().trigger, RunTrigger::Schedule);
+        assert_eq!(status.failures_total, 0);
+    }
+
+    struct PausedRunner;
+
+    impl TaskRunner for PausedRunner {
+        fn run(&self, _schedule: &ScheduleDef) -> TaskFuture {
+            Box::pin(async { Ok(Value::Null) })
+        }
+
+        fn paused(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn paused_runners_skip_ticks_but_not_manual_triggers() {
+        let scheduler = Arc::new(
+            Scheduler::new(SchedulesFile {
+                schedules: vec![def("every-second", "* * * * * *")],
+            })
+            .unwrap(),
+        );
+        scheduler.start(Arc::new(PausedRunner));
+        for _ in 0..300 {
+            if scheduler.statuses()[0].skipped_total > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let status = scheduler.statuses().remove(0);
+        assert!(status.skipped_total > 0);
+        assert_eq!(status.runs_total, 0);
+
+        assert_eq!(scheduler.trigger("every-second"), Some(true));
+        let status = wait_for_runs(&scheduler, 1).await;
+        assert_eq!(status.last_run.unwrap().trigger, RunTrigger::Manual);
+    }
+}
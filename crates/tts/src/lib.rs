@@ -0,0 +1,298 @@
+//! Lokale Sprachausgabe mit Piper.
+//!
+//! Das Crate beschreibt, wie das Piper-Binary (`piper`) mit einer ONNX-Stimme
+//! aufgerufen wird: Text kommt zeilenweise über stdin, rohe 16-Bit-Mono-Samples
+//! (`--output_raw`) gehen nach stdout – Satz für Satz, sobald Piper sie erzeugt
+//! hat. Daraus wird ein WAV-Strom ([`wav_header`]) oder, über `ffmpeg`, Opus in
+//! Ogg ([`AudioFormat::Opus`]).
+//!
+//! Stimmen reicht der Aufrufer als [`VoiceSpec`] herein (in HausKI der
+//! Abschnitt `voices` von `configs/models.yml`); [`VoiceRegistry`] prüft die
+//! Liste und wählt per ID oder nimmt die Default-Stimme.
+//!
+//! Die Prozesse werden nur als [`std::process::Command`] zusammengebaut;
+//! starten und verbinden kann sie der Aufrufer synchron oder mit Tokio.
+//!
+//! Konfiguration ([`TtsConfig::from_env`]):
+//!   HAUSKI_PIPER_BIN   (Default `piper`)
+//!   HAUSKI_FFMPEG_BIN  (Default `ffmpeg`)
+
+mod voice;
+
+use std::{env, path::PathBuf, process::Command};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+pub use voice::{VoiceRegistry, VoiceSpec};
+
+/// Range accepted for [`SynthOptions::speed`].
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TtsError {
+    #[error("voice id {0:?} is configured more than once")]
+    DuplicateVoice(String),
+    #[error("more than one voice is marked as default")]
+    MultipleDefaults,
+    #[error("voice {id:?}: {reason}")]
+    InvalidVoice { id: String, reason: String },
+    #[error("voice '{0}' is not configured")]
+    UnknownVoice(String),
+    #[error("no voice configured")]
+    NoVoice,
+    #[error("voice file {} is missing", .0.display())]
+    VoiceMissing(PathBuf),
+    #[error("text is empty")]
+    EmptyText,
+    #[error("speed must be between {} and {}", SPEED_RANGE.start(), SPEED_RANGE.end())]
+    InvalidSpeed,
+}
+
+/// Container of the synthesized audio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// 16-bit PCM WAV at the voice's sample rate.
+    #[default]
+    Wav,
+    /// Opus in Ogg, encoded by ffmpeg.
+    Opus,
+}
+
+impl AudioFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Opus => "opus",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Wav => "audio/wav",
+            Self::Opus => "audio/ogg",
+        }
+    }
+}
+
+/// Per-call settings on top of the voice.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SynthOptions {
+    /// Overrides the voice's speaker.
+    pub speaker: Option<u32>,
+    /// Speaking rate relative to the voice; 2 is twice as fast.
+    pub speed: Option<f32>,
+}
+
+impl SynthOptions {
+    pub fn validate(&self) -> Result<(), TtsError> {
+        match self.speed {
+            Some(speed) if !SPEED_RANGE.contains(&speed) => Err(TtsError::InvalidSpeed),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtsConfig {
+    pub piper_bin: String,
+    pub ffmpeg_bin: String,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            piper_bin: "piper".into(),
+            ffmpeg_bin: "ffmpeg".into(),
+        }
+    }
+}
+
+impl TtsConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            piper_bin: env_string("HAUSKI_PIPER_BIN").unwrap_or(defaults.piper_bin),
+            ffmpeg_bin: env_string("HAUSKI_FFMPEG_BIN").unwrap_or(defaults.ffmpeg_bin),
+        }
+    }
+
+    /// Piper reading text from stdin and writing raw samples to stdout.
+    pub fn piper_command(&self, voice: &VoiceSpec, options: &SynthOptions) -> Command {
+        let mut command = Command::new(&self.piper_bin);
+        command
+            .arg("--model")
+            .arg(voice.model_path())
+            .arg("--config")
+            .arg(voice.config_path())
+            .arg("--output_raw");
+        if let Some(speaker) = options.speaker.or(voice.speaker) {
+            command.args(["--speaker", &speaker.to_string()]);
+        }
+        let length_scale = voice.length_scale.unwrap_or(1.0) / options.speed.unwrap_or(1.0);
+        if length_scale != 1.0 {
+            command.args(["--length_scale", &length_scale.to_string()]);
+        }
+        command
+    }
+
+    /// ffmpeg turning raw samples on stdin into `format` on stdout; `None`
+    /// when the raw samples only need a [`wav_header`].
+    pub fn encoder_command(&self, format: AudioFormat, sample_rate: u32) -> Option<Command> {
+        match format {
+            AudioFormat::Wav => None,
+            AudioFormat::Opus => {
+                let mut command = Command::new(&self.ffmpeg_bin);
+                command
+                    .args(["-nostdin", "-loglevel", "error", "-f", "s16le"])
+                    .args(["-ar", &sample_rate.to_string(), "-ac", "1", "-i", "pipe:0"])
+                    .args(["-c:a", "libopus", "-b:a", "32k", "-f", "ogg", "pipe:1"]);
+                Some(command)
+            }
+        }
+    }
+}
+
+/// Text as Piper reads it: one utterance per line, blank lines and
+/// surrounding whitespace dropped.
+pub fn prepare_text(text: &str) -> Result<String, TtsError> {
+    let mut prepared = String::with_capacity(text.len() + 1);
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        prepared.push_str(line);
+        prepared.push('\n');
+    }
+    if prepared.is_empty() {
+        return Err(TtsError::EmptyText);
+    }
+    Ok(prepared)
+}
+
+/// Header of a 16-bit mono WAV. Without `data_len` the sizes are set to
+/// their maximum, the usual marker for a stream of unknown length.
+pub fn wav_header(sample_rate: u32, data_len: Option<u32>) -> [u8; 44] {
+    let data_len = data_len.unwrap_or(u32::MAX - 36);
+    let mut header = [0_u8; 44];
+    let mut put = |offset: usize, bytes: &[u8]| {
+        header[offset..offset + bytes.len()].copy_from_slice(bytes);
+    };
+    put(0, b"RIFF");
+    put(4, &(36 + data_len).to_le_bytes());
+    put(8, b"WAVEfmt ");
+    put(16, &16_u32.to_le_bytes());
+    put(20, &1_u16.to_le_bytes()); // PCM
+    put(22, &1_u16.to_le_bytes()); // mono
+    put(24, &sample_rate.to_le_bytes());
+    put(28, &(sample_rate * 2).to_le_bytes());
+    put(32, &2_u16.to_le_bytes());
+    put(34, &16_u16.to_le_bytes());
+    put(36, b"data");
+    put(40, &data_len.to_le_bytes());
+    header
+}
+
+fn env_string(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn piper_gets_speaker_and_length_scale() {
+        let config = TtsConfig::default();
+        let voice = VoiceSpec {
+            id: "eva".into(),
+            path: "/voices/eva.onnx".into(),
+            config: None,
+            language: Some("de".into()),
+            speaker: Some(3),
+            length_scale: Some(1.2),
+            default: false,
+        };
+        let command = config.piper_command(&voice, &SynthOptions::default());
+        assert_eq!(command.get_program(), "piper");
+        assert_eq!(
+            args(&command),
+            [
+                "--model",
+                "/voices/eva.onnx",
+                "--config",
+                "/voices/eva.onnx.json",
+                "--output_raw",
+                "--speaker",
+                "3",
+                "--length_scale",
+                "1.2"
+            ]
+        );
+
+        let options = SynthOptions {
+            speaker: Some(0),
+            speed: Some(1.2),
+        };
+        let args = args(&config.piper_command(&voice, &options));
+        assert!(
+            args.ends_with(&["--speaker".into(), "0".into()]),
+            "{args:?}"
+        );
+        assert!(SynthOptions {
+            speed: Some(8.0),
+            ..options
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn opus_goes_through_ffmpeg() {
+        let config = TtsConfig::default();
+        assert!(config.encoder_command(AudioFormat::Wav, 22_050).is_none());
+        let command = config.encoder_command(AudioFormat::Opus, 22_050).unwrap();
+        let args = args(&command).join(" ");
+        assert!(
+            args.contains("-f s16le -ar 22050 -ac 1 -i pipe:0"),
+            "{args}"
+        );
+        assert!(
+            args.ends_with("-c:a libopus -b:a 32k -f ogg pipe:1"),
+            "{args}"
+        );
+        assert_eq!(AudioFormat::Opus.content_type(), "audio/ogg");
+    }
+
+    #[test]
+    fn text_is_one_utterance_per_line() {
+        assert_eq!(
+            prepare_text("  Hallo Welt.\n\n  Wie geht's?  ").unwrap(),
+            "Hallo Welt.\nWie geht's?\n"
+        );
+        assert_eq!(prepare_text(" \n\t").unwrap_err(), TtsError::EmptyText);
+    }
+
+    #[test]
+    fn wav_header_describes_16_bit_mono() {
+        let header = wav_header(22_050, Some(4));
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(header[4..8].try_into().unwrap()), 40);
+        assert_eq!(
+            u32::from_le_bytes(header[24..28].try_into().unwrap()),
+            22_050
+        );
+        assert_eq!(u32::from_le_bytes(header[40..44].try_into().unwrap()), 4);
+        let streaming = wav_header(16_000, None);
+        assert_eq!(&streaming[4..8], &u32::MAX.to_le_bytes());
+    }
+}
@@ -0,0 +1,212 @@
+//! Piper voices from the `voices` section of `models.yml`.
+
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::TtsError;
+
+/// Sample rate of Piper's medium-quality voices, used when the voice config
+/// does not name one.
+const DEFAULT_SAMPLE_RATE: u32 = 22_050;
+
+/// One entry of the `voices` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VoiceSpec {
+    pub id: String,
+    /// Piper voice model (`.onnx`); `~` is expanded.
+    pub path: String,
+    /// Voice config (default `<path>.json`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// Language of the voice, e.g. `de`; informational.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Speaker of a multi-speaker voice (default 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    /// Phoneme duration factor; above 1 speaks slower (default 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length_scale: Option<f32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+}
+
+impl VoiceSpec {
+    pub fn model_path(&self) -> PathBuf {
+        expand(&self.path)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        match &self.config {
+            Some(config) => expand(config),
+            None => {
+                let mut path = self.model_path().into_os_string();
+                path.push(".json");
+                PathBuf::from(path)
+            }
+        }
+    }
+
+    /// Whether model and config exist.
+    pub fn is_available(&self) -> bool {
+        self.check_files().is_ok()
+    }
+
+    /// Like [`is_available`](Self::is_available), naming the missing file.
+    pub fn check_files(&self) -> Result<(), TtsError> {
+        for path in [self.model_path(), self.config_path()] {
+            if !path.is_file() {
+                return Err(TtsError::VoiceMissing(path));
+            }
+        }
+        Ok(())
+    }
+
+    /// `audio.sample_rate` from the voice config.
+    pub fn sample_rate(&self) -> Result<u32, TtsError> {
+        let path = self.config_path();
+        let raw = fs::read_to_string(&path).map_err(|_| TtsError::VoiceMissing(path.clone()))?;
+        let config: serde_json::Value =
+            serde_json::from_str(&raw).map_err(|err| TtsError::InvalidVoice {
+                id: self.id.clone(),
+                reason: format!("{}: {err}", path.display()),
+            })?;
+        match config["audio"]["sample_rate"].as_u64() {
+            Some(rate) => u32::try_from(rate).map_err(|_| TtsError::InvalidVoice {
+                id: self.id.clone(),
+                reason: format!("sample_rate {rate} out of range"),
+            }),
+            None => Ok(DEFAULT_SAMPLE_RATE),
+        }
+    }
+}
+
+fn expand(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).as_ref())
+}
+
+/// Checked list of voices.
+#[derive(Debug, Clone, Default)]
+pub struct VoiceRegistry {
+    voices: Vec<VoiceSpec>,
+}
+
+impl VoiceRegistry {
+    pub fn new(voices: Vec<VoiceSpec>) -> Result<Self, TtsError> {
+        let mut ids = HashSet::new();
+        for voice in &voices {
+            if !ids.insert(voice.id.as_str()) {
+                return Err(TtsError::DuplicateVoice(voice.id.clone()));
+            }
+            if voice.path.trim().is_empty() {
+                return Err(TtsError::InvalidVoice {
+                    id: voice.id.clone(),
+                    reason: "path is empty".into(),
+                });
+            }
+            if voice.length_scale.is_some_and(|scale| scale <= 0.0) {
+                return Err(TtsError::InvalidVoice {
+                    id: voice.id.clone(),
+                    reason: "length_scale must be greater than 0".into(),
+                });
+            }
+        }
+        if voices.iter().filter(|voice| voice.default).count() > 1 {
+            return Err(TtsError::MultipleDefaults);
+        }
+        Ok(Self { voices })
+    }
+
+    pub fn voices(&self) -> &[VoiceSpec] {
+        &self.voices
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voices.is_empty()
+    }
+
+    /// The voice marked `default`, else the first one.
+    pub fn default_voice(&self) -> Option<&VoiceSpec> {
+        self.voices
+            .iter()
+            .find(|voice| voice.default)
+            .or_else(|| self.voices.first())
+    }
+
+    /// The voice `id`, or the default voice without one.
+    pub fn select(&self, id: Option<&str>) -> Result<&VoiceSpec, TtsError> {
+        match id {
+            Some(id) => self
+                .voices
+                .iter()
+                .find(|voice| voice.id == id)
+                .ok_or_else(|| TtsError::UnknownVoice(id.to_string())),
+            None => self.default_voice().ok_or(TtsError::NoVoice),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(id: &str, default: bool) -> VoiceSpec {
+        VoiceSpec {
+            id: id.into(),
+            path: format!("/voices/{id}.onnx"),
+            config: None,
+            language: None,
+            speaker: None,
+            length_scale: None,
+            default,
+        }
+    }
+
+    #[test]
+    fn registry_selects_by_id_or_default() {
+        let registry =
+            VoiceRegistry::new(vec![voice("thorsten", false), voice("eva", true)]).unwrap();
+        assert_eq!(registry.select(None).unwrap().id, "eva");
+        assert_eq!(registry.select(Some("thorsten")).unwrap().id, "thorsten");
+        assert!(matches!(
+            registry.select(Some("karl")),
+            Err(TtsError::UnknownVoice(id)) if id == "karl"
+        ));
+        assert!(matches!(
+            VoiceRegistry::default().select(None),
+            Err(TtsError::NoVoice)
+        ));
+        assert_eq!(
+            registry.voices()[0].config_path(),
+            PathBuf::from("/voices/thorsten.onnx.json")
+        );
+
+        assert_eq!(
+            VoiceRegistry::new(vec![voice("eva", false), voice("eva", false)]).unwrap_err(),
+            TtsError::DuplicateVoice("eva".into())
+        );
+        assert_eq!(
+            VoiceRegistry::new(vec![voice("a", true), voice("b", true)]).unwrap_err(),
+            TtsError::MultipleDefaults
+        );
+    }
+
+    #[test]
+    fn sample_rate_comes_from_the_voice_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = dir.path().join("eva.onnx");
+        fs::write(&model, b"onnx").unwrap();
+        let mut spec = voice("eva", false);
+        spec.path = model.display().to_string();
+        assert!(!spec.is_available());
+        assert!(matches!(spec.sample_rate(), Err(TtsError::VoiceMissing(_))));
+
+        fs::write(spec.config_path(), r#"{"audio":{"sample_rate":16000}}"#).unwrap();
+        assert!(spec.is_available());
+        assert_eq!(spec.sample_rate().unwrap(), 16_000);
+        fs::write(spec.config_path(), "{}").unwrap();
+        assert_eq!(spec.sample_rate().unwrap(), DEFAULT_SAMPLE_RATE);
+    }
+}
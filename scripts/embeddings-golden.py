@@ -0,0 +1,44 @@
+#!/usr/bin/env python3
+"""Write golden.json with reference embeddings for crates/embeddings/tests/golden.rs.
+
+Usage: python3 scripts/embeddings-golden.py <model_dir>
+
+Needs `sentence-transformers`; the vectors are mean-pooled and L2-normalized,
+matching the defaults of `LocalEmbedder`.
+"""
+
+from __future__ import annotations
+
+import json
+import sys
+from pathlib import Path
+
+TEXTS = [
+    "Hallo Welt!",
+    "The quick brown fox jumps over the lazy dog.",
+    "Übermorgen räumt der Hausmeister den Keller auf – café, naïve, Ångström.",
+    "unaffable antidisestablishmentarianism",
+    "日本語のテキストも埋め込めます。",
+    "",
+]
+
+
+def main() -> int:
+    if len(sys.argv) != 2:
+        print(__doc__.strip(), file=sys.stderr)
+        return 2
+    from sentence_transformers import SentenceTransformer, models
+
+    model_dir = Path(sys.argv[1])
+    transformer = models.Transformer(str(model_dir), max_seq_length=256)
+    pooling = models.Pooling(transformer.get_word_embedding_dimension(), pooling_mode="mean")
+    model = SentenceTransformer(modules=[transformer, pooling], device="cpu")
+    vectors = model.encode(TEXTS, normalize_embeddings=True, convert_to_numpy=True)
+    golden = {"texts": TEXTS, "embeddings": vectors.tolist()}
+    (model_dir / "golden.json").write_text(json.dumps(golden), encoding="utf-8")
+    print(f"{model_dir / 'golden.json'}: {len(TEXTS)} vectors")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())